@@ -0,0 +1,19 @@
+/* Generate the C header of the functions exported with the ffi feature, so
+ * that it never falls out of step with them. */
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap());
+        let config = cbindgen::Config::from_file(dir.join("cbindgen.toml"))
+            .expect("cbindgen.toml is valid");
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(dir.join("src/ffi.rs"))
+            .generate()
+            .expect("unable to generate the C header")
+            .write_to_file(dir.join("include/vampir.h"));
+    }
+}
@@ -0,0 +1,151 @@
+/* Feeds structured programs through compilation over the field of Halo2.
+ * Programs are built from arbitrary bytes as items that only refer to inputs
+ * and definitions made before them, so that nearly all of them are accepted
+ * and the passes behind the parser are exercised rather than name
+ * resolution. Compilation may still refuse a program, such as one dividing
+ * by zero, but must do so with an error.
+ *
+ * Run from the root of the repository with a nightly toolchain:
+ *   cargo +nightly fuzz run compile
+ * A crash is saved under fuzz/artifacts/compile/. Running the target on it
+ * alone with VAMPIR_FUZZ_PRINT set prints the program it stands for, which
+ * reproduces the crash with vamp-ir once saved as a .pir file:
+ *   VAMPIR_FUZZ_PRINT=1 cargo +nightly fuzz run compile fuzz/artifacts/compile/crash-<digest> */
+#![no_main]
+
+use arbitrary::Arbitrary;
+use halo2_proofs::pasta::Fp;
+use libfuzzer_sys::fuzz_target;
+use std::fmt::Write;
+use vamp_ir::ast::Module;
+use vamp_ir::halo2::field::{Halo2CostModel, PrimeFieldOps};
+use vamp_ir::transform::{CompileOptions, MAX_OPT_LEVEL};
+
+/* The number of inputs, named x0 onwards, that expressions draw on. */
+const INPUTS: u8 = 8;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    DivideZ,
+    IntDivide,
+    Modulo,
+}
+
+#[derive(Arbitrary, Debug)]
+enum Expr {
+    Input(u8),
+    // A definition made earlier, or an input if there are none
+    Defined(u8),
+    Constant(u64),
+    Negate(Box<Expr>),
+    Infix(Op, Box<Expr>, Box<Expr>),
+    Power(Box<Expr>, u8),
+    Fresh(Box<Expr>),
+}
+
+#[derive(Arbitrary, Debug)]
+enum Item {
+    Constrain(Expr, Expr),
+    Define(Expr),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Program {
+    // Which of the inputs are public, one bit each
+    public: u8,
+    opt_level: u8,
+    items: Vec<Item>,
+}
+
+impl Expr {
+    /* Write this expression as source after the given number of definitions,
+     * bracketing every operation so that no precedence is relied upon. */
+    fn write(&self, source: &mut String, defined: usize) {
+        match self {
+            Expr::Input(index) => write!(source, "x{}", index % INPUTS).unwrap(),
+            Expr::Defined(index) if defined > 0 => write!(source, "d{}", *index as usize % defined).unwrap(),
+            Expr::Defined(index) => write!(source, "x{}", index % INPUTS).unwrap(),
+            Expr::Constant(value) => write!(source, "{}", value).unwrap(),
+            Expr::Negate(expr) => {
+                source.push_str("(-");
+                expr.write(source, defined);
+                source.push(')');
+            },
+            Expr::Infix(op, lhs, rhs) => {
+                source.push('(');
+                lhs.write(source, defined);
+                source.push_str(match op {
+                    Op::Add => " + ",
+                    Op::Subtract => " - ",
+                    Op::Multiply => " * ",
+                    Op::Divide => " / ",
+                    Op::DivideZ => " | ",
+                    Op::IntDivide => " \\ ",
+                    Op::Modulo => " % ",
+                });
+                rhs.write(source, defined);
+                source.push(')');
+            },
+            Expr::Power(expr, exponent) => {
+                source.push('(');
+                expr.write(source, defined);
+                write!(source, ")^{}", exponent % 8).unwrap();
+            },
+            Expr::Fresh(expr) => {
+                source.push_str("fresh (");
+                expr.write(source, defined);
+                source.push(')');
+            },
+        }
+    }
+}
+
+impl Program {
+    fn source(&self) -> String {
+        let mut source = String::new();
+        let public: Vec<_> = (0..INPUTS)
+            .filter(|index| self.public & (1 << index) != 0)
+            .map(|index| format!("x{}", index))
+            .collect();
+        if !public.is_empty() {
+            writeln!(source, "pub {};", public.join(", ")).unwrap();
+        }
+        let mut defined = 0;
+        for item in &self.items {
+            match item {
+                Item::Constrain(lhs, rhs) => {
+                    lhs.write(&mut source, defined);
+                    source.push_str(" = ");
+                    rhs.write(&mut source, defined);
+                },
+                Item::Define(expr) => {
+                    write!(source, "def d{} = ", defined).unwrap();
+                    expr.write(&mut source, defined);
+                    defined += 1;
+                },
+            }
+            source.push_str(";\n");
+        }
+        source
+    }
+}
+
+fuzz_target!(|program: Program| {
+    let source = program.source();
+    if std::env::var_os("VAMPIR_FUZZ_PRINT").is_some() {
+        println!("{}", source);
+    }
+    // Programs without items are not programs
+    let module = match Module::parse(&source) {
+        Ok(module) => module,
+        Err(_) => return,
+    };
+    let options = CompileOptions::default()
+        .opt_level(program.opt_level % (MAX_OPT_LEVEL + 1))
+        .cost_model(Box::new(Halo2CostModel));
+    let _ = vamp_ir::compile(module, &PrimeFieldOps::<Fp>::default(), &options);
+});
@@ -0,0 +1,27 @@
+/* Feeds mutated bytes to the readers of circuit files, proof files, and
+ * proof containers, which must refuse corrupted files with errors rather
+ * than panics or allocations as large as the lengths written in them.
+ * Circuits are only decoded, as generating their keys would make each run
+ * take seconds.
+ *
+ * Seed the corpus with files written by vamp-ir, whose mutations get past
+ * the header far more often than random bytes do:
+ *   mkdir -p fuzz/corpus/deserialize
+ *   vamp-ir halo2 compile -s tests/r1cs.pir -o fuzz/corpus/deserialize/circuit
+ *   vamp-ir halo2 prove -c fuzz/corpus/deserialize/circuit -i tests/r1cs.inputs \
+ *     -o fuzz/corpus/deserialize/proof
+ *   cargo +nightly fuzz run deserialize
+ * Crashes are saved under fuzz/artifacts/deserialize/ and are reproduced and
+ * shrunk as those of the parse target are. */
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vamp_ir::artifact::{parse_artifact, ArtifactKind};
+use vamp_ir::container::ProofContainer;
+use vamp_ir::halo2::circuit::{HaloCircuitData, Halo2Proof};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_artifact(data, ArtifactKind::Halo2Circuit, |reader| HaloCircuitData::read(reader));
+    let _ = Halo2Proof::from_bytes(data);
+    let _ = ProofContainer::from_cbor(data);
+});
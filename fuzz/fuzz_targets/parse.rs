@@ -0,0 +1,22 @@
+/* Feeds arbitrary bytes to the parser, which must refuse whatever is not a
+ * program with an error rather than a panic or an overflowing stack.
+ *
+ * Run from the root of the repository with a nightly toolchain and
+ * cargo-fuzz, seeding the corpus with the programs under tests/:
+ *   mkdir -p fuzz/corpus/parse && cp tests/*.pir fuzz/corpus/parse/
+ *   cargo +nightly fuzz run parse
+ * Each crash is saved under fuzz/artifacts/parse/, and is reproduced by
+ * running the target on it alone, then shrunk with tmin:
+ *   cargo +nightly fuzz run parse fuzz/artifacts/parse/crash-<digest>
+ *   cargo +nightly fuzz tmin parse fuzz/artifacts/parse/crash-<digest>
+ * Fixed crashes become tests in tests/fuzz_regressions.rs. */
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vamp_ir::ast::Module;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(source) = std::str::from_utf8(data) {
+        let _ = Module::parse(source);
+    }
+});
@@ -0,0 +1,123 @@
+#ifndef VAMPIR_H
+#define VAMPIR_H
+
+/* Generated by cbindgen from src/ffi.rs with the ffi feature; do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The call succeeded.
+ */
+#define VAMPIR_OK 0
+
+/**
+ * The proof is invalid, or was made for other public values.
+ */
+#define VAMPIR_INVALID_PROOF 1
+
+/**
+ * The source, inputs, or buffers given cannot be used.
+ */
+#define VAMPIR_INPUT_ERROR 2
+
+/**
+ * The proving system failed, or vamp-ir hit a bug.
+ */
+#define VAMPIR_INTERNAL_ERROR 3
+
+/**
+ * A buffer of bytes allocated by vamp-ir.
+ *
+ * Every buffer that a function fills in belongs to the caller, who must
+ * release it with `vampir_buffer_free` and nothing else. Functions set the
+ * buffer to `{NULL, 0}` before doing anything, so it can be freed whether or
+ * not the call succeeded.
+ */
+typedef struct {
+  uint8_t *data;
+  size_t len;
+} VampirBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Compile the given source to a circuit of the given backend, filling in
+ * `circuit` with the contents of the circuit file that the command line
+ * would write. Only `"halo2"` is supported.
+ *
+ * # Safety
+ *
+ * `source` and `backend` must be nul-terminated strings, and `circuit` must
+ * point to a `VampirBuffer` that the caller does not hold allocated bytes in.
+ */
+int vampir_compile(const char *source, const char *backend, VampirBuffer *circuit);
+
+/**
+ * Prove the given circuit on the inputs of the given JSON object, which maps
+ * the names of the inputs to integers or to strings holding them, filling in
+ * `proof` with the contents of the proof file that the command line would
+ * write. The randomness that hides the witnesses is drawn from the given
+ * entropy, at least 32 bytes of it, or from the operating system if
+ * `entropy` is null.
+ *
+ * # Safety
+ *
+ * `circuit` must point to `circuit_len` readable bytes, `entropy` must be
+ * null or point to `entropy_len` readable bytes, `inputs_json` must be a
+ * nul-terminated string, and `proof` must point to a `VampirBuffer` that the
+ * caller does not hold allocated bytes in.
+ */
+int vampir_prove(const uint8_t *circuit,
+                 size_t circuit_len,
+                 const char *inputs_json,
+                 const uint8_t *entropy,
+                 size_t entropy_len,
+                 VampirBuffer *proof);
+
+/**
+ * Check that the given proof of the given circuit is valid and was made for
+ * the public values of the given JSON object, which must name every public
+ * variable of the circuit and nothing else. Returns `VAMPIR_OK` if so, and
+ * `VAMPIR_INVALID_PROOF` if not.
+ *
+ * # Safety
+ *
+ * `circuit` and `proof` must point to `circuit_len` and `proof_len` readable
+ * bytes, and `public_json` must be a nul-terminated string.
+ */
+int vampir_verify(const uint8_t *circuit,
+                  size_t circuit_len,
+                  const uint8_t *proof,
+                  size_t proof_len,
+                  const char *public_json);
+
+/**
+ * The message of the error of the last call made on this thread, or null if
+ * it succeeded. The string belongs to vamp-ir, and stays valid until the
+ * next call made on this thread; it must not be freed.
+ */
+const char *vampir_last_error(void);
+
+/**
+ * Release the bytes of the given buffer, which must have been filled in by
+ * vamp-ir, and set it to `{NULL, 0}`. Buffers that are already empty are
+ * left alone, so freeing one twice is harmless.
+ *
+ * # Safety
+ *
+ * `buffer` must be null or point to a `VampirBuffer` filled in by vamp-ir,
+ * or set to `{NULL, 0}`, and left unchanged since.
+ */
+void vampir_buffer_free(VampirBuffer *buffer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* VAMPIR_H */
@@ -0,0 +1,64 @@
+use crate::ast::{Module, VariableId};
+use crate::error::Error;
+use crate::plonk::field::{make_constant, PrimeFieldOps};
+use crate::r1cs::R1cs;
+use crate::transform::derive_available_witnesses;
+use ark_ff::{PrimeField, SquareRootField};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/* A compiled module as a circuit of arkworks, so that proving systems such as
+ * Groth16 and Marlin can prove it. Its constraints are lowered as for the
+ * .r1cs export, with the public variables of the module as the public inputs
+ * of the circuit, in order. */
+pub struct ModuleCircuit<F> where F: PrimeField {
+    r1cs: R1cs,
+    // Values of the variables, asked for only when proving
+    assignments: HashMap<VariableId, BigInt>,
+    phantom: PhantomData<F>,
+}
+
+impl<F> ModuleCircuit<F> where F: PrimeField + SquareRootField {
+    /* Wrap the given module, compiled over F, with the given values of its
+     * variables, from which those of any other variables defined by it are
+     * derived. Generating keys needs no values, so they may be left empty
+     * then. Fails if a constraint is not of rank 1. */
+    pub fn new(module: &Module, mut assignments: HashMap<VariableId, BigInt>) -> Result<Self, Error> {
+        let field_ops = PrimeFieldOps::<F>::default();
+        let r1cs = R1cs::new(module, &field_ops)?;
+        if !assignments.is_empty() {
+            derive_available_witnesses(module, &mut assignments, &field_ops);
+        }
+        Ok(Self { r1cs, assignments, phantom: PhantomData })
+    }
+}
+
+impl<F> ConstraintSynthesizer<F> for ModuleCircuit<F> where F: PrimeField {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let mut wires = vec![Variable::One];
+        for (index, var) in self.r1cs.wires.iter().enumerate() {
+            let value = || {
+                self.assignments.get(&var.id)
+                    .map(make_constant::<F>)
+                    .ok_or(SynthesisError::AssignmentMissing)
+            };
+            let wire = if index < self.r1cs.public_inputs as usize {
+                cs.new_input_variable(value)?
+            } else {
+                cs.new_witness_variable(value)?
+            };
+            wires.push(wire);
+        }
+        let lc = |terms: &crate::r1cs::LinearCombination| {
+            terms.iter().fold(LinearCombination::zero(), |lc, (wire, coeff)| {
+                lc + (make_constant::<F>(coeff), wires[*wire as usize])
+            })
+        };
+        for constraint in &self.r1cs.constraints {
+            cs.enforce_constraint(lc(&constraint.a), lc(&constraint.b), lc(&constraint.c))?;
+        }
+        Ok(())
+    }
+}
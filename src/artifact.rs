@@ -0,0 +1,476 @@
+#[cfg(feature = "compiler")]
+use crate::container::unwrap_container;
+#[cfg(feature = "compiler")]
+use crate::encoding::{decode, Encoding};
+#[cfg(feature = "compiler")]
+use crate::error::Error;
+#[cfg(feature = "compiler")]
+use crate::stdio::is_stdin;
+#[cfg(feature = "compiler")]
+use crate::output::write_output;
+#[cfg(feature = "compiler")]
+use crate::status::Outcome;
+#[cfg(feature = "compiler")]
+use log::warn;
+use std::fmt;
+use std::io::{Chain, Cursor, Read, Write};
+#[cfg(feature = "compiler")]
+use std::path::Path;
+
+/* The bytes that every file written by vamp-ir starts with. */
+const MAGIC: &[u8; 8] = b"VAMPIR\x00\x01";
+
+/* The version of the layout that follows the header. Files whose layout is
+ * older than this must be recompiled. */
+pub const FORMAT_VERSION: u16 = 2;
+
+/* The most bytes that decoding a file may set aside for the lists and
+ * strings in it at once. Lengths read from a corrupted file can be anything,
+ * so those beyond this are refused rather than allocated. */
+pub const DECODE_LIMIT: usize = 1 << 30;
+
+/* Standard FNV-1a offset basis and prime. */
+pub const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/* Fold the given bytes into the given FNV-1a hash. Unlike the hashers of the
+ * standard library, this is stable across platforms and toolchains. */
+pub fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/* The kinds of files that vamp-ir writes, each tied to a backend and curve. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArtifactKind {
+    Halo2Circuit,
+    Halo2Proof,
+    Halo2Verifier,
+    PlonkParams,
+    PlonkCircuit,
+    PlonkProof,
+    Groth16Bn254Circuit,
+    Groth16Bn254Proof,
+    Groth16Bls12_381Circuit,
+    Groth16Bls12_381Proof,
+    BulletproofsCircuit,
+    BulletproofsProof,
+    Plonky2Circuit,
+    Plonky2Proof,
+}
+
+impl ArtifactKind {
+    const ALL: [ArtifactKind; 14] = [
+        ArtifactKind::Halo2Circuit,
+        ArtifactKind::Halo2Proof,
+        ArtifactKind::Halo2Verifier,
+        ArtifactKind::PlonkParams,
+        ArtifactKind::PlonkCircuit,
+        ArtifactKind::PlonkProof,
+        ArtifactKind::Groth16Bn254Circuit,
+        ArtifactKind::Groth16Bn254Proof,
+        ArtifactKind::Groth16Bls12_381Circuit,
+        ArtifactKind::Groth16Bls12_381Proof,
+        ArtifactKind::BulletproofsCircuit,
+        ArtifactKind::BulletproofsProof,
+        ArtifactKind::Plonky2Circuit,
+        ArtifactKind::Plonky2Proof,
+    ];
+
+    /* The byte identifying this kind in headers. */
+    pub fn tag(&self) -> u8 {
+        match self {
+            ArtifactKind::Halo2Circuit => 0x01,
+            ArtifactKind::Halo2Proof => 0x02,
+            ArtifactKind::Halo2Verifier => 0x03,
+            ArtifactKind::PlonkParams => 0x10,
+            ArtifactKind::PlonkCircuit => 0x11,
+            ArtifactKind::PlonkProof => 0x12,
+            ArtifactKind::Groth16Bn254Circuit => 0x20,
+            ArtifactKind::Groth16Bn254Proof => 0x21,
+            ArtifactKind::Groth16Bls12_381Circuit => 0x22,
+            ArtifactKind::Groth16Bls12_381Proof => 0x23,
+            ArtifactKind::BulletproofsCircuit => 0x30,
+            ArtifactKind::BulletproofsProof => 0x31,
+            ArtifactKind::Plonky2Circuit => 0x40,
+            ArtifactKind::Plonky2Proof => 0x41,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.tag() == tag)
+    }
+
+    pub fn backend(&self) -> &'static str {
+        match self {
+            ArtifactKind::Halo2Circuit | ArtifactKind::Halo2Proof | ArtifactKind::Halo2Verifier => "halo2",
+            ArtifactKind::PlonkParams | ArtifactKind::PlonkCircuit | ArtifactKind::PlonkProof => "plonk",
+            ArtifactKind::BulletproofsCircuit | ArtifactKind::BulletproofsProof => "bp",
+            ArtifactKind::Plonky2Circuit | ArtifactKind::Plonky2Proof => "plonky2",
+            _ => "groth16",
+        }
+    }
+
+    pub fn curve(&self) -> &'static str {
+        match self {
+            ArtifactKind::Halo2Circuit | ArtifactKind::Halo2Proof | ArtifactKind::Halo2Verifier => "pallas",
+            ArtifactKind::Groth16Bn254Circuit | ArtifactKind::Groth16Bn254Proof => "bn254",
+            ArtifactKind::BulletproofsCircuit | ArtifactKind::BulletproofsProof => "ristretto255",
+            ArtifactKind::Plonky2Circuit | ArtifactKind::Plonky2Proof => "goldilocks",
+            _ => "bls12-381",
+        }
+    }
+
+    pub fn is_proof(&self) -> bool {
+        self.noun() == "proof"
+    }
+
+    fn noun(&self) -> &'static str {
+        match self {
+            ArtifactKind::Halo2Circuit
+            | ArtifactKind::PlonkCircuit
+            | ArtifactKind::Groth16Bn254Circuit
+            | ArtifactKind::Groth16Bls12_381Circuit
+            | ArtifactKind::BulletproofsCircuit
+            | ArtifactKind::Plonky2Circuit => "circuit",
+            ArtifactKind::Halo2Proof
+            | ArtifactKind::PlonkProof
+            | ArtifactKind::Groth16Bn254Proof
+            | ArtifactKind::Groth16Bls12_381Proof
+            | ArtifactKind::BulletproofsProof
+            | ArtifactKind::Plonky2Proof => "proof",
+            ArtifactKind::PlonkParams => "public parameters file",
+            ArtifactKind::Halo2Verifier => "verifier",
+        }
+    }
+}
+
+impl fmt::Display for ArtifactKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.backend(), self.noun())
+    }
+}
+
+/* Prints the metadata of a file of some kind from its contents after the
+ * header to the given outcome, failing if they cannot be decoded. */
+#[cfg(feature = "compiler")]
+pub type Inspector = fn(&mut dyn Read, &mut Outcome) -> Result<(), String>;
+
+/* What the header of a file says about it. */
+#[derive(Clone, Debug)]
+pub struct Header {
+    pub kind: ArtifactKind,
+    // Version of the layout that follows the header
+    pub format_version: u16,
+    // Version of vamp-ir that wrote the file
+    pub tool_version: String,
+}
+
+/* Write the header of a file of the given kind. */
+pub fn write_header<W: Write>(mut writer: W, kind: ArtifactKind) -> std::io::Result<()> {
+    let tool_version = env!("CARGO_PKG_VERSION").as_bytes();
+    writer.write_all(MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&[kind.tag(), tool_version.len() as u8])?;
+    writer.write_all(tool_version)
+}
+
+/* Read as many bytes as fit in the given buffer, stopping early only at the
+ * end of the input, and return how many were read. */
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/* Read the header of a file, if it has one. Files without one predate
+ * headers, so the bytes read in looking for it are put back in front of the
+ * returned reader for the legacy layout to be decoded from. */
+pub fn read_header<R: Read>(
+    mut reader: R,
+) -> Result<(Option<Header>, Chain<Cursor<Vec<u8>>, R>), String> {
+    let mut magic = [0u8; MAGIC.len()];
+    let read = read_up_to(&mut reader, &mut magic).map_err(|err| err.to_string())?;
+    if &magic != MAGIC {
+        return Ok((None, Cursor::new(magic[..read].to_vec()).chain(reader)));
+    }
+    let mut fixed = [0u8; 4];
+    reader.read_exact(&mut fixed).map_err(|_| "truncated header".to_string())?;
+    let format_version = u16::from_le_bytes([fixed[0], fixed[1]]);
+    let kind = ArtifactKind::from_tag(fixed[2])
+        .ok_or_else(|| format!("unknown kind of file with tag {:#04x}", fixed[2]))?;
+    let mut tool_version = vec![0u8; fixed[3] as usize];
+    reader.read_exact(&mut tool_version).map_err(|_| "truncated header".to_string())?;
+    let tool_version = String::from_utf8_lossy(&tool_version).to_string();
+    let header = Header { kind, format_version, tool_version };
+    Ok((Some(header), Cursor::new(vec![]).chain(reader)))
+}
+
+/* Check that the given header, if any, is that of a file of the given kind in
+ * the current layout. */
+pub fn check_header(header: &Option<Header>, expected: ArtifactKind) -> Result<(), String> {
+    let header = match header {
+        Some(header) => header,
+        None => return Ok(()),
+    };
+    if header.kind != expected {
+        return Err(format!("this is a {}, not a {}", header.kind, expected));
+    }
+    if header.format_version < FORMAT_VERSION {
+        return Err(format!(
+            "file produced by an older vamp-ir ({}); recompile",
+            header.tool_version,
+        ));
+    }
+    if header.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "file produced by a newer vamp-ir ({}); upgrade vamp-ir to read it",
+            header.tool_version,
+        ));
+    }
+    Ok(())
+}
+
+/* The digest by which the contents of a file after its header are told
+ * apart. */
+pub fn content_digest(contents: &[u8]) -> String {
+    format!("{:016x}", fnv1a(FNV_OFFSET, contents))
+}
+
+/* Print what the header of the given file, named as given, says about it to
+ * the given outcome, and then its metadata as printed by whichever of the
+ * given inspectors is for its kind. Contents in a layout other than the
+ * current one are not decoded. Returns the kind of the file if its contents
+ * were decoded. */
+#[cfg(feature = "compiler")]
+pub fn inspect(
+    name: &str,
+    bytes: &[u8],
+    inspectors: &[(ArtifactKind, Inspector)],
+    outcome: &mut Outcome,
+) -> Result<Option<ArtifactKind>, Error> {
+    let (header, mut reader) = read_header(bytes)
+        .map_err(|err| Error::Artifact(format!("{}: {}", name, err)))?;
+    let header = header.ok_or_else(|| Error::Artifact(format!(
+        "{}: no header, so it was produced by an older vamp-ir or not by vamp-ir at all",
+        name,
+    )))?;
+    let mut contents = vec![];
+    reader.read_to_end(&mut contents).map_err(|err| Error::Artifact(format!("{}: {}", name, err)))?;
+    let digest = content_digest(&contents);
+    outcome.println(format!("* {}: {}", name, header.kind));
+    outcome.println(format!("** Backend: {}", header.kind.backend()));
+    outcome.println(format!("** Curve: {}", header.kind.curve()));
+    outcome.println(format!("** Written by: vamp-ir {}", header.tool_version));
+    outcome.println(format!("** Format version: {}", header.format_version));
+    outcome.println(format!("** Content digest: {}", digest));
+    outcome.detail("kind", header.kind.to_string());
+    outcome.detail("backend", header.kind.backend());
+    outcome.detail("curve", header.kind.curve());
+    outcome.detail("tool_version", header.tool_version.clone());
+    outcome.detail("format_version", header.format_version);
+    outcome.detail("content_digest", digest);
+    if header.format_version != FORMAT_VERSION {
+        outcome.println("** Contents are not in the current format, so they are not decoded");
+        outcome.detail("decoded", false);
+        return Ok(None);
+    }
+    let (_, inspector) = inspectors.iter()
+        .find(|(kind, _)| *kind == header.kind)
+        .ok_or_else(|| Error::Artifact(format!(
+            "{}: this vamp-ir was built without support for {}s, so it is not decoded",
+            name, header.kind,
+        )))?;
+    inspector(&mut contents.as_slice(), outcome)
+        .map_err(|err| Error::Artifact(format!("{}: corrupted {}: {}", name, header.kind, err)))?;
+    outcome.detail("decoded", true);
+    Ok(Some(header.kind))
+}
+
+/* A BLAKE2b digest of the given parts in hex, each part prefixed with its
+ * length so that parts cannot run together. Unlike content_digest, this
+ * resists deliberately made collisions, so it can stand in for what it
+ * digests where telling two of those apart matters. */
+pub fn secure_digest(parts: &[&[u8]]) -> String {
+    let mut state = blake2b_simd::Params::new().hash_length(32).to_state();
+    for part in parts {
+        state.update(&(part.len() as u64).to_le_bytes());
+        state.update(part);
+    }
+    state.finalize().to_hex().to_string()
+}
+
+/* The digests of the contents of a file after its header. */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Digests {
+    // Short digest by which metadata and reports name the file
+    pub content: String,
+    // Digest that resists collisions, under which whatever is made for the
+    // file is cached
+    pub secure: String,
+}
+
+impl Digests {
+    pub fn of(contents: &[u8]) -> Self {
+        Self { content: content_digest(contents), secure: secure_digest(&[contents]) }
+    }
+}
+
+/* Write a file of the given kind to the given path, or to standard output if
+ * the path is -, serializing its contents after the header with the given
+ * function and storing the whole in the given encoding. An existing file is
+ * only replaced if forced. Returns the digests of the contents. */
+#[cfg(feature = "compiler")]
+pub fn write_artifact<E: fmt::Debug>(
+    path: &Path,
+    kind: ArtifactKind,
+    force: bool,
+    encoding: Encoding,
+    serialize: impl FnOnce(&mut Vec<u8>) -> Result<(), E>,
+) -> Result<Digests, String> {
+    let mut bytes = vec![];
+    write_header(&mut bytes, kind).expect("writing to memory cannot fail");
+    let header_len = bytes.len();
+    serialize(&mut bytes)
+        .map_err(|err| format!("cannot write {}: {:?}", path.to_string_lossy(), err))?;
+    let digest = Digests::of(&bytes[header_len..]);
+    write_output(path, encoding.encode(bytes), force)?;
+    Ok(digest)
+}
+
+/* Serialize a file of the given kind into memory as write_artifact would
+ * write it in binary, for callers that keep files somewhere other than a
+ * filesystem. */
+pub fn artifact_bytes<E: fmt::Debug>(
+    kind: ArtifactKind,
+    serialize: impl FnOnce(&mut Vec<u8>) -> Result<(), E>,
+) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![];
+    write_header(&mut bytes, kind).expect("writing to memory cannot fail");
+    serialize(&mut bytes).map_err(|err| format!("cannot write {}: {:?}", kind, err))?;
+    Ok(bytes)
+}
+
+/* Undo whichever encoding the given contents are stored in, taking the proof
+ * out of them if they are a proof container. */
+#[cfg(feature = "compiler")]
+pub fn unwrap_contents(contents: Vec<u8>) -> Result<Vec<u8>, String> {
+    let contents = decode(contents, MAGIC);
+    if contents.starts_with(MAGIC) {
+        return Ok(contents);
+    }
+    unwrap_container(contents)
+}
+
+/* Check that the given contents are stored in binary, as the text encodings
+ * and proof containers are only undone by builds with the compiler. */
+#[cfg(not(feature = "compiler"))]
+pub fn unwrap_contents(contents: Vec<u8>) -> Result<Vec<u8>, String> {
+    if contents.starts_with(MAGIC) {
+        return Ok(contents);
+    }
+    Err("not a binary file; text encodings and proof containers are only read by builds with the compiler feature".to_string())
+}
+
+/* Deserialize a file of the given kind from its bytes in memory, stored in
+ * any encoding or in a container. Unlike files read from paths, these must
+ * have a header, as nothing that predates headers handed files around in
+ * memory. */
+pub fn parse_artifact<T, E: fmt::Debug>(
+    contents: &[u8],
+    expected: ArtifactKind,
+    deserialize: impl FnOnce(&mut dyn Read) -> Result<T, E>,
+) -> Result<T, String> {
+    let contents = unwrap_contents(contents.to_vec())?;
+    let (header, mut reader) = read_header(contents.as_slice())?;
+    if header.is_none() {
+        return Err(format!("not a {}", expected));
+    }
+    check_header(&header, expected)?;
+    deserialize(&mut reader).map_err(|err| format!("corrupted {}: {:?}", expected, err))
+}
+
+/* Deserialize a file of the given kind from the given reader, as
+ * parse_artifact does from memory. Binary files are decoded as their bytes
+ * arrive, so they are never held in memory whole; text encodings and
+ * containers can only be undone on the whole of the contents, so those are
+ * read to the end first. */
+pub fn parse_artifact_from_reader<R: Read, T, E: fmt::Debug>(
+    reader: R,
+    expected: ArtifactKind,
+    deserialize: impl FnOnce(&mut dyn Read) -> Result<T, E>,
+) -> Result<T, String> {
+    let (header, mut reader) = read_header(reader)?;
+    if header.is_none() {
+        let mut contents = vec![];
+        reader.read_to_end(&mut contents).map_err(|err| err.to_string())?;
+        return parse_artifact(&contents, expected, deserialize);
+    }
+    check_header(&header, expected)?;
+    deserialize(&mut reader).map_err(|err| format!("corrupted {}: {:?}", expected, err))
+}
+
+/* Read the contents of the file at the given path, or of standard input if
+ * the path is -, undoing any text encoding that they are stored in. A proof
+ * container gives the proof file inside it, so that containers are read
+ * wherever bare proofs are. */
+#[cfg(feature = "compiler")]
+pub fn read_contents(path: &Path) -> Result<Vec<u8>, String> {
+    let contents = if is_stdin(path) {
+        let mut contents = vec![];
+        std::io::stdin().read_to_end(&mut contents).map(|_| contents)
+    } else {
+        std::fs::read(path)
+    }.map_err(|err| err.to_string())?;
+    unwrap_contents(contents)
+}
+
+/* Read a file of the given kind from the given path, as read_contents does,
+ * deserializing its contents after the header with the given function. Files
+ * without headers are still decoded in the layout that predates them, with a
+ * warning, so that they keep working until they can be recompiled. */
+#[cfg(feature = "compiler")]
+pub fn read_artifact<T, E: fmt::Debug>(
+    path: &Path,
+    expected: ArtifactKind,
+    deserialize: impl FnOnce(&mut dyn Read) -> Result<T, E>,
+) -> Result<T, String> {
+    read_digested_artifact(path, expected, deserialize).map(|(value, _)| value)
+}
+
+/* Read a file as read_artifact does, along with the digests of its
+ * contents. */
+#[cfg(feature = "compiler")]
+pub fn read_digested_artifact<T, E: fmt::Debug>(
+    path: &Path,
+    expected: ArtifactKind,
+    deserialize: impl FnOnce(&mut dyn Read) -> Result<T, E>,
+) -> Result<(T, Digests), String> {
+    let contents = read_contents(path)?;
+    let (header, mut reader) = read_header(contents.as_slice())?;
+    check_header(&header, expected)?;
+    let digest = match header {
+        Some(_) => Digests::of(reader.get_ref().1),
+        None => Digests::of(&contents),
+    };
+    if header.is_none() {
+        warn!(
+            "* Warning: {} has no header, so it was produced by an older vamp-ir; \
+             recompile it, as such files will stop being accepted",
+            path.to_string_lossy(),
+        );
+    }
+    let value = deserialize(&mut reader).map_err(|err| match header {
+        Some(_) => format!("corrupted {}: {:?}", expected, err),
+        None => format!("not a {}, or produced by an older vamp-ir; recompile", expected),
+    })?;
+    Ok((value, digest))
+}
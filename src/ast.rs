@@ -1,11 +1,13 @@
 use pest::iterators::Pair;
+use pest::Span;
 use std::fmt;
 use std::fmt::Write;
 use crate::typecheck::Type;
 use crate::pest::Parser;
 use bincode::{Encode, Decode};
-use std::collections::{HashMap, HashSet};
-use crate::transform::VarGen;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use crate::transform::{CompileError, VarGen};
+use crate::diagnostics::{Diagnostic, SourceSpan};
 use num_bigint::BigInt;
 use num_traits::Num;
 use std::ops::Neg;
@@ -14,27 +16,115 @@ use std::ops::Neg;
 pub struct VampirParser;
 
 #[derive(Debug, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Module {
     pub pubs: Vec<Variable>,
     pub defs: Vec<Definition>,
     pub exprs: Vec<TExpr>,
+    // Human-readable names of the variables occuring in this module
+    pub names: BTreeMap<VariableId, String>,
+    // Where the items of this module came from in its source, if parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub spans: ItemSpans,
+}
+
+/* Where the definitions and expressions of a module came from in its source,
+ * so that errors found after parsing can point back at it. Spans only matter
+ * while compiling, so they are left out of encoded circuits. */
+#[derive(Debug, Clone, Default)]
+pub struct ItemSpans {
+    // Span of the definition of each fully qualified name
+    pub defs: HashMap<String, SourceSpan>,
+    // Span of each expression of the module, in order
+    pub exprs: Vec<Option<SourceSpan>>,
+}
+
+impl ItemSpans {
+    /* Record the given file as the one that every span is in. */
+    pub fn set_file(&mut self, file: &str) {
+        for span in self.defs.values_mut().chain(self.exprs.iter_mut().flatten()) {
+            span.file = Some(file.to_string());
+        }
+    }
+}
+
+impl bincode::Encode for ItemSpans {
+    fn encode<E: bincode::enc::Encoder>(
+        &self,
+        _encoder: &mut E,
+    ) -> core::result::Result<(), bincode::error::EncodeError> {
+        Ok(())
+    }
+}
+
+impl bincode::Decode for ItemSpans {
+    fn decode<D: bincode::de::Decoder>(
+        _decoder: &mut D,
+    ) -> core::result::Result<Self, bincode::error::DecodeError> {
+        Ok(Self::default())
+    }
 }
 
 impl Module {
     pub fn parse(unparsed_file: &str) -> Result<Self, pest::error::Error<Rule>> {
+        Self::parse_with_warnings(unparsed_file, &mut vec![])
+    }
+
+    /* Parse the given module, appending a warning for each local binding that
+     * shadows an earlier binding of the same name to the given list. */
+    pub fn parse_with_warnings(
+        unparsed_file: &str,
+        warnings: &mut Vec<Diagnostic>,
+    ) -> Result<Self, pest::error::Error<Rule>> {
+        Self::parse_with_context(unparsed_file, &Module::default(), warnings)
+    }
+
+    /* Parse the given module so that it can refer to the definitions of the
+     * given module, which it is intended to be merged into. Redefining any of
+     * those definitions is an error. */
+    pub fn parse_with_context(
+        unparsed_file: &str,
+        context: &Module,
+        warnings: &mut Vec<Diagnostic>,
+    ) -> Result<Self, pest::error::Error<Rule>> {
+        check_nesting(unparsed_file)?;
         let mut pairs = VampirParser::parse(Rule::moduleItems, &unparsed_file)?;
         let mut defs = vec![];
         let mut exprs = vec![];
+        let mut expr_spans = vec![];
         let mut pubs = vec![];
+        let mut resolver = NameResolver::default();
+        for name in context.defined_names() {
+            resolver.defined.insert(name, None);
+        }
+        // Consecutive clauses of the same function definition
+        let mut clauses: Vec<(Definition, Span)> = vec![];
         while let Some(pair) = pairs.next() {
+            // Any item other than a further clause completes the current
+            // function definition
+            if !clauses.is_empty() && pair.as_rule() != Rule::definition {
+                defs.push(resolver.define(std::mem::take(&mut clauses))?);
+            }
             match pair.as_rule() {
                 Rule::expr => {
-                    let expr = TExpr::parse(pair).expect("expected expression");
+                    let span = pair.as_span();
+                    let mut expr = TExpr::parse(pair).expect("expected expression");
+                    resolver.resolve_expr(&mut expr, &HashSet::new())
+                        .map_err(|msg| custom_error(span.clone(), msg))?;
+                    resolver.report_shadowing(&span);
                     exprs.push(expr);
+                    expr_spans.push(Some(SourceSpan::from(&span)));
                 },
                 Rule::definition => {
+                    let span = pair.as_span();
                     let definition = Definition::parse(pair).expect("expected definition");
-                    defs.push(definition);
+                    if !clauses.last().map_or(true, |(last, _)| last.is_clause_of(&definition)) {
+                        defs.push(resolver.define(std::mem::take(&mut clauses))?);
+                    }
+                    clauses.push((definition, span));
+                },
+                Rule::namespace => {
+                    resolver.parse_namespace(pair, &mut defs)?;
                 },
                 Rule::declaration => {
                     let mut pairs = pair.into_inner();
@@ -43,21 +133,373 @@ impl Module {
                         pubs.push(var);
                     }
                 },
-                Rule::EOI => return Ok(Self {
-                    pubs,
-                    defs,
-                    exprs,
-                }),
-                _ => unreachable!("module item should either be expression, definition, or EOI")
+                Rule::EOI => {
+                    warnings.append(&mut resolver.warnings);
+                    let def_spans = resolver.defined.iter()
+                        .filter_map(|(name, span)| Some((name.clone(), SourceSpan::from(span.as_ref()?))))
+                        .collect();
+                    return Ok(Self {
+                        pubs,
+                        defs,
+                        exprs,
+                        names: BTreeMap::new(),
+                        spans: ItemSpans { defs: def_spans, exprs: expr_spans },
+                    })
+                },
+                _ => unreachable!("module item should either be expression, definition, namespace, or EOI")
             }
         }
         unreachable!("EOI should have been encountered")
     }
 }
 
+/* Apply the given function to the variables of the given environment,
+ * including the variables that it binds. */
+fn visit_env_variables(
+    env: &mut HashMap<VariableId, TExpr>,
+    f: &mut impl FnMut(&mut Variable),
+) {
+    *env = std::mem::take(env).into_iter().map(|(id, mut expr)| {
+        let mut var = Variable::new(id);
+        f(&mut var);
+        expr.visit_variables(f);
+        (var.id, expr)
+    }).collect();
+}
+
+/* Make a parse error with the given message at the given location. */
+fn custom_error(span: Span, message: String) -> pest::error::Error<Rule> {
+    pest::error::Error::new_from_span(pest::error::ErrorVariant::CustomError { message }, span)
+}
+
+/* The deepest that brackets may nest in a source. Parsing recurses through
+ * every level, so sources nested without bound would overflow the stack. */
+pub const MAX_NESTING: usize = 256;
+
+/* Refuse the given source if its brackets outside comments nest deeper than
+ * MAX_NESTING, pointing at the first bracket that goes too deep. */
+fn check_nesting(source: &str) -> Result<(), pest::error::Error<Rule>> {
+    let bytes = source.as_bytes();
+    let mut depth = 0usize;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match &bytes[pos..] {
+            [b'/', b'/', ..] => pos += bytes[pos..].iter()
+                .position(|byte| *byte == b'\n')
+                .unwrap_or(bytes.len() - pos),
+            [b'/', b'*', ..] => pos += bytes[pos + 2..].windows(2)
+                .position(|window| window == b"*/")
+                .map_or(bytes.len() - pos, |end| end + 4),
+            [b'(' | b'[' | b'{', ..] => {
+                depth += 1;
+                if depth > MAX_NESTING {
+                    let span = Span::new(source, pos, pos + 1).expect("brackets are single characters");
+                    return Err(custom_error(span, format!("brackets nest deeper than {} levels", MAX_NESTING)));
+                }
+                pos += 1;
+            },
+            [b')' | b']' | b'}', ..] => {
+                depth = depth.saturating_sub(1);
+                pos += 1;
+            },
+            _ => pos += 1,
+        }
+    }
+    Ok(())
+}
+
+/* Render the given location as a line and column pair. */
+fn describe_span(span: &Span) -> String {
+    let (line, col) = span.start_pos().line_col();
+    format!("{}:{}", line, col)
+}
+
+/* Describe where a definition was made. Definitions without a location were
+ * made by an earlier module. */
+fn describe_site(site: &Option<Span>) -> String {
+    match site {
+        Some(span) => format!("at {}", describe_span(span)),
+        None => "in an earlier module".to_string(),
+    }
+}
+
+/* Resolves the names occuring in a module to the fully qualified names of the
+ * definitions that they refer to. Definitions made within a namespace are
+ * given names qualified by the path to that namespace. A name may only be
+ * defined once within a namespace, whereas local bindings may shadow earlier
+ * bindings. */
+#[derive(Default)]
+struct NameResolver<'a> {
+    // The enclosing namespaces, outermost first
+    path: Vec<String>,
+    // The fully qualified names defined so far and their locations
+    defined: HashMap<String, Option<Span<'a>>>,
+    // Local bindings in the current item that shadow earlier bindings
+    shadowed: Vec<String>,
+    // Warnings about all the shadowing local bindings found so far
+    warnings: Vec<Diagnostic>,
+}
+
+impl<'a> NameResolver<'a> {
+    /* Parse the given namespace, appending its flattened definitions to the
+     * given list. */
+    fn parse_namespace(
+        &mut self,
+        pair: Pair<'a, Rule>,
+        defs: &mut Vec<Definition>,
+    ) -> Result<(), pest::error::Error<Rule>> {
+        let mut pairs = pair.into_inner();
+        let name = pairs.next().expect("namespace should have a name");
+        self.path.push(name.as_str().to_string());
+        let mut clauses: Vec<(Definition, Span)> = vec![];
+        for pair in pairs {
+            if !clauses.is_empty() && pair.as_rule() != Rule::definition {
+                defs.push(self.define(std::mem::take(&mut clauses))?);
+            }
+            match pair.as_rule() {
+                Rule::definition => {
+                    let span = pair.as_span();
+                    let definition = Definition::parse(pair).expect("expected definition");
+                    if !clauses.last().map_or(true, |(last, _)| last.is_clause_of(&definition)) {
+                        defs.push(self.define(std::mem::take(&mut clauses))?);
+                    }
+                    clauses.push((definition, span));
+                },
+                Rule::namespace => self.parse_namespace(pair, defs)?,
+                _ => unreachable!("namespace item should either be definition or namespace")
+            }
+        }
+        if !clauses.is_empty() {
+            defs.push(self.define(clauses)?);
+        }
+        self.path.pop();
+        Ok(())
+    }
+
+    /* Merge the given clauses into a single definition, resolve the names in
+     * its body, and qualify the names that it defines. */
+    fn define(
+        &mut self,
+        clauses: Vec<(Definition, Span<'a>)>,
+    ) -> Result<Definition, pest::error::Error<Rule>> {
+        let span = clauses[0].1.clone();
+        let mut def = Definition::merge_clauses(clauses.into_iter().map(|x| x.0).collect());
+        let mut names = vec![];
+        def.0.0.collect_names(&mut names);
+        let qualified: Vec<_> = names.iter().map(|name| self.qualify(name)).collect();
+        // Definitions cannot silently replace each other
+        for name in &qualified {
+            if let Some(prev) = self.defined.get(name) {
+                return Err(custom_error(span.clone(), format!(
+                    "{} is defined {} and again at {}",
+                    name, describe_site(prev), describe_span(&span),
+                )));
+            }
+        }
+        // Functions are in scope within their own bodies
+        if let (Pat::Variable(_), Expr::Function(_)) = (&def.0.0.v, &def.0.1.v) {
+            for name in &qualified {
+                self.defined.insert(name.clone(), Some(span.clone()));
+            }
+        }
+        self.resolve_expr(&mut def.0.1, &HashSet::new())
+            .map_err(|msg| custom_error(span.clone(), msg))?;
+        self.report_shadowing(&span);
+        let prefix = self.qualify("");
+        def.0.0.qualify_names(&prefix);
+        for name in qualified {
+            self.defined.insert(name, Some(span.clone()));
+        }
+        Ok(def)
+    }
+
+    /* Warn about the shadowing local bindings found in the item at the given
+     * location. */
+    fn report_shadowing(&mut self, span: &Span) {
+        for name in std::mem::take(&mut self.shadowed) {
+            let warning = Diagnostic::warning("shadowed-binding", format!(
+                "local binding of {} in the item at {} shadows an earlier binding",
+                name, describe_span(span),
+            ));
+            self.warnings.push(warning
+                .with_span(SourceSpan::from(span))
+                .with_suggestion(format!("rename this binding of {}", name)));
+        }
+    }
+
+    /* Add the names bound by the given pattern to the given set, noting those
+     * that shadow an earlier local binding or definition. */
+    fn bind_pattern(&mut self, pat: &TPat, bound: &mut HashSet<String>) {
+        let mut names = vec![];
+        pat.collect_names(&mut names);
+        for name in names {
+            if bound.contains(&name) || matches!(self.resolve(&name), Ok(Some(_))) {
+                self.shadowed.push(name.clone());
+            }
+            bound.insert(name);
+        }
+    }
+
+    /* Qualify the given name with the path to the current namespace. */
+    fn qualify(&self, name: &str) -> String {
+        let mut qualified = String::new();
+        for ns in &self.path {
+            write!(qualified, "{}::", ns).unwrap();
+        }
+        qualified + name
+    }
+
+    /* Determine the fully qualified name that the given name refers to from
+     * within the current namespace. Unqualified names refer to the definition
+     * in the innermost enclosing namespace, whereas a qualified name must
+     * refer to exactly one definition. */
+    fn resolve(&self, name: &str) -> Result<Option<String>, String> {
+        let mut candidates = vec![];
+        for depth in (0..=self.path.len()).rev() {
+            let mut candidate = String::new();
+            for ns in &self.path[..depth] {
+                write!(candidate, "{}::", ns).unwrap();
+            }
+            candidate += name;
+            if let Some(span) = self.defined.get(&candidate) {
+                candidates.push((candidate, span));
+            }
+        }
+        if !name.contains("::") {
+            return Ok(candidates.into_iter().next().map(|x| x.0));
+        }
+        match candidates.as_slice() {
+            [] => Err(format!("unresolved qualified name {}", name)),
+            [(candidate, _)] => Ok(Some(candidate.clone())),
+            [(candidate1, span1), (candidate2, span2), ..] => Err(format!(
+                "ambiguous name {} could refer to {} defined {} or to {} defined {}",
+                name, candidate1, describe_site(span1), candidate2, describe_site(span2),
+            )),
+        }
+    }
+
+    /* Resolve the free variables in the given expression to the definitions
+     * they refer to. The given set holds the names bound locally. */
+    fn resolve_expr(&mut self, expr: &mut TExpr, bound: &HashSet<String>) -> Result<(), String> {
+        match &mut expr.v {
+            Expr::Variable(var) => match &var.name {
+                Some(name) if !bound.contains(name) => {
+                    if let Some(qualified) = self.resolve(name)? {
+                        var.name = Some(qualified);
+                    }
+                },
+                _ => {},
+            },
+            Expr::Sequence(exprs) => {
+                for expr in exprs {
+                    self.resolve_expr(expr, bound)?;
+                }
+            },
+            Expr::Product(expr1, expr2) | Expr::Cons(expr1, expr2) |
+            Expr::Infix(_, expr1, expr2) | Expr::Application(expr1, expr2) => {
+                self.resolve_expr(expr1, bound)?;
+                self.resolve_expr(expr2, bound)?;
+            },
+            Expr::Negate(expr1) => self.resolve_expr(expr1, bound)?,
+            Expr::Function(fun) => {
+                let mut bound = bound.clone();
+                for param in &fun.params {
+                    self.bind_pattern(param, &mut bound);
+                }
+                self.resolve_expr(&mut fun.body, &bound)?;
+            },
+            Expr::LetBinding(binding, expr2) => {
+                let mut inner = bound.clone();
+                self.bind_pattern(&binding.0, &mut inner);
+                // Functions are in scope within their own bodies
+                if let (Pat::Variable(_), Expr::Function(_)) = (&binding.0.v, &binding.1.v) {
+                    self.resolve_expr(&mut binding.1, &inner)?;
+                } else {
+                    self.resolve_expr(&mut binding.1, bound)?;
+                }
+                self.resolve_expr(expr2, &inner)?;
+            },
+            Expr::Match(matche) => {
+                self.resolve_expr(&mut matche.0, bound)?;
+                for (pat, expr2) in matche.1.iter().zip(matche.2.iter_mut()) {
+                    let mut inner = bound.clone();
+                    self.bind_pattern(pat, &mut inner);
+                    self.resolve_expr(expr2, &inner)?;
+                }
+            },
+            Expr::Unit | Expr::Nil | Expr::Constant(_) | Expr::Intrinsic(_) => {},
+        }
+        Ok(())
+    }
+}
+
+impl Module {
+    /* The names defined by this module's definitions. */
+    pub fn defined_names(&self) -> Vec<String> {
+        let mut names = vec![];
+        for def in &self.defs {
+            def.0.0.collect_names(&mut names);
+        }
+        names
+    }
+
+    /* Append the given module to this one. Fails if both modules define the
+     * same name. Public variables declared by both modules are identified.
+     * Any variables of the given module that have already been numbered are
+     * renumbered past this module's variables so that their ids cannot
+     * collide. */
+    pub fn merge(&mut self, mut other: Module) -> Result<(), String> {
+        let names: HashSet<_> = self.defined_names().into_iter().collect();
+        for name in other.defined_names() {
+            if names.contains(&name) {
+                return Err(format!("{} is defined by more than one module", name));
+            }
+        }
+        let mut offset = 0;
+        self.visit_variables(&mut |var: &mut Variable| offset = offset.max(var.id));
+        if offset > 0 {
+            other.offset_variables(offset);
+        }
+        for var in other.pubs {
+            if !self.pubs.iter().any(|x| x.name.is_some() && x.name == var.name) {
+                self.pubs.push(var);
+            }
+        }
+        self.spans.exprs.resize(self.exprs.len(), None);
+        other.spans.exprs.resize(other.exprs.len(), None);
+        self.defs.extend(other.defs);
+        self.exprs.extend(other.exprs);
+        self.names.extend(other.names);
+        self.spans.defs.extend(other.spans.defs);
+        self.spans.exprs.extend(other.spans.exprs);
+        Ok(())
+    }
+
+    /* Shift the ids of all numbered variables in this module by the given
+     * amount. Unnumbered variables keep the id 0. */
+    fn offset_variables(&mut self, offset: VariableId) {
+        self.visit_variables(&mut |var: &mut Variable| if var.id != 0 { var.id += offset });
+        self.names = std::mem::take(&mut self.names).into_iter().map(|(id, name)| (id + offset, name)).collect();
+    }
+
+    /* Apply the given function to every variable occuring in this module. */
+    fn visit_variables(&mut self, f: &mut impl FnMut(&mut Variable)) {
+        for var in &mut self.pubs {
+            f(var);
+        }
+        for def in &mut self.defs {
+            def.0.0.visit_variables(f);
+            def.0.1.visit_variables(f);
+        }
+        for expr in &mut self.exprs {
+            expr.visit_variables(f);
+        }
+    }
+}
+
 impl Default for Module {
     fn default() -> Self {
-        Self { defs: vec![], exprs: vec![], pubs: vec![] }
+        Self { defs: vec![], exprs: vec![], pubs: vec![], names: BTreeMap::new(), spans: ItemSpans::default() }
     }
 }
 
@@ -80,6 +522,7 @@ impl fmt::Display for Module {
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Definition(pub LetBinding);
 
 impl Definition {
@@ -90,6 +533,59 @@ impl Definition {
         let binding = LetBinding::parse(pair).expect("definition should contain single binding");
         Some(Self(binding))
     }
+
+    /* Check whether this definition and the given one are clauses of the same
+     * function. That is, whether they both define a function of the same name
+     * taking the same number of parameters. */
+    pub fn is_clause_of(&self, other: &Definition) -> bool {
+        match (&self.0.0.v, &self.0.1.v, &other.0.0.v, &other.0.1.v) {
+            (Pat::Variable(var1), Expr::Function(fun1),
+             Pat::Variable(var2), Expr::Function(fun2)) =>
+                var1.name.is_some() && var1.name == var2.name &&
+                fun1.params.len() == fun2.params.len(),
+            _ => false,
+        }
+    }
+
+    /* Combine the clauses of a function definition into a single definition.
+     * The resulting function matches its arguments against the parameters of
+     * each clause in turn and evaluates the body of the first clause that
+     * matches. Since the function's name is in scope within its own body, this
+     * enables recursion on compile-time constants. */
+    pub fn merge_clauses(mut clauses: Vec<Definition>) -> Definition {
+        if clauses.len() == 1 {
+            return clauses.pop().unwrap();
+        }
+        let first = clauses.first().expect("definition should have at least one clause");
+        let (name, arity) = match (&first.0.0.v, &first.0.1.v) {
+            (Pat::Variable(var), Expr::Function(fun)) => (var.clone(), fun.params.len()),
+            _ => unreachable!("only function definitions can have multiple clauses"),
+        };
+        // Make parameters through which the arguments can be matched
+        let mut params = vec![];
+        for idx in 0..arity {
+            let param_name = format!("{}.{}", name.name.as_ref().unwrap(), idx);
+            params.push(Pat::Variable(Variable { name: Some(param_name), id: 0 }).type_pat(None));
+        }
+        let scrutinee = params.iter().rev().map(TPat::to_expr)
+            .reduce(|acc, param| Expr::Product(Box::new(param), Box::new(acc)).type_expr(None))
+            .expect("function should have at least one parameter");
+        // Now make an arm for each clause of the definition
+        let mut pats = vec![];
+        let mut bodies = vec![];
+        for clause in clauses {
+            if let Expr::Function(fun) = clause.0.1.v {
+                let pat = fun.params.into_iter().rev()
+                    .reduce(|acc, param| Pat::Product(Box::new(param), Box::new(acc)).type_pat(None))
+                    .expect("function should have at least one parameter");
+                pats.push(pat);
+                bodies.push(*fun.body);
+            }
+        }
+        let body = Expr::Match(Match(Box::new(scrutinee), pats, bodies)).type_expr(None);
+        let fun = Expr::Function(Function { params, body: Box::new(body), env: HashMap::new() });
+        Self(LetBinding(Pat::Variable(name).type_pat(None), Box::new(fun.type_expr(None))))
+    }
 }
 
 impl fmt::Display for Definition {
@@ -99,6 +595,7 @@ impl fmt::Display for Definition {
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LetBinding(pub TPat, pub Box<TExpr>);
 
 impl LetBinding {
@@ -186,7 +683,73 @@ impl bincode::Decode for BigIntBincode {
     }
 }
 
+// This module is required to serialize BigInts as strings with serde, since
+// their own serde implementation writes lists of digits
+#[cfg(feature = "serde")]
+pub mod bigint_serde {
+    use super::parse_prefixed_num;
+    use num_bigint::BigInt;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &BigInt, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigInt, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse_prefixed_num::<BigInt>(&text).map_err(|_| D::Error::custom(format!("{} is not an integer", text)))
+    }
+}
+
+// This module serializes maps to BigInts as maps to strings, as bigint_serde
+// does each value
+#[cfg(feature = "serde")]
+pub mod bigint_map_serde {
+    use super::parse_prefixed_num;
+    use num_bigint::BigInt;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    pub fn serialize<K, S>(map: &HashMap<K, BigInt>, serializer: S) -> Result<S::Ok, S::Error>
+    where K: Serialize, S: Serializer {
+        serializer.collect_map(map.iter().map(|(key, value)| (key, value.to_string())))
+    }
+
+    pub fn deserialize<'de, K, D>(deserializer: D) -> Result<HashMap<K, BigInt>, D::Error>
+    where K: Deserialize<'de> + Eq + Hash, D: Deserializer<'de> {
+        HashMap::<K, String>::deserialize(deserializer)?.into_iter()
+            .map(|(key, text)| match parse_prefixed_num::<BigInt>(&text) {
+                Ok(value) => Ok((key, value)),
+                Err(_) => Err(D::Error::custom(format!("{} is not an integer", text))),
+            })
+            .collect()
+    }
+}
+
+/* Values assigned to variables by their identifiers, such as those that
+ * assign_inputs gives back. Serialized, the values are decimal strings. */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct Assignments(
+    #[cfg_attr(feature = "serde", serde(with = "bigint_map_serde"))]
+    pub HashMap<VariableId, BigInt>,
+);
+
+impl From<HashMap<VariableId, BigInt>> for Assignments {
+    fn from(values: HashMap<VariableId, BigInt>) -> Self {
+        Self(values)
+    }
+}
+
+impl From<Assignments> for HashMap<VariableId, BigInt> {
+    fn from(assignments: Assignments) -> Self {
+        assignments.0
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Pat {
     Unit,
     Nil,
@@ -194,10 +757,12 @@ pub enum Pat {
     Product(Box<TPat>, Box<TPat>),
     Cons(Box<TPat>, Box<TPat>),
     Variable(Variable),
+    #[cfg_attr(feature = "serde", serde(with = "bigint_serde"))]
     Constant(BigInt),
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TPat {
     pub v: Pat,
     pub t: Option<Type>,
@@ -223,6 +788,56 @@ impl Pat {
 }
 
 impl TPat {
+    /* Collect the names of the variables bound by this pattern. */
+    pub fn collect_names(&self, names: &mut Vec<String>) {
+        match &self.v {
+            Pat::Variable(var) => names.extend(var.name.clone()),
+            Pat::As(pat, var) => {
+                pat.collect_names(names);
+                names.extend(var.name.clone());
+            },
+            Pat::Product(pat1, pat2) | Pat::Cons(pat1, pat2) => {
+                pat1.collect_names(names);
+                pat2.collect_names(names);
+            },
+            Pat::Unit | Pat::Nil | Pat::Constant(_) => {},
+        }
+    }
+
+    /* Apply the given function to every variable occuring in this pattern. */
+    fn visit_variables(&mut self, f: &mut impl FnMut(&mut Variable)) {
+        match &mut self.v {
+            Pat::Variable(var) => f(var),
+            Pat::As(pat, var) => {
+                pat.visit_variables(f);
+                f(var);
+            },
+            Pat::Product(pat1, pat2) | Pat::Cons(pat1, pat2) => {
+                pat1.visit_variables(f);
+                pat2.visit_variables(f);
+            },
+            Pat::Unit | Pat::Nil | Pat::Constant(_) => {},
+        }
+    }
+
+    /* Prefix the names of the variables bound by this pattern. */
+    fn qualify_names(&mut self, prefix: &str) {
+        match &mut self.v {
+            Pat::Variable(var) => {
+                var.name = var.name.as_ref().map(|name| format!("{}{}", prefix, name));
+            },
+            Pat::As(pat, var) => {
+                pat.qualify_names(prefix);
+                var.name = var.name.as_ref().map(|name| format!("{}{}", prefix, name));
+            },
+            Pat::Product(pat1, pat2) | Pat::Cons(pat1, pat2) => {
+                pat1.qualify_names(prefix);
+                pat2.qualify_names(prefix);
+            },
+            Pat::Unit | Pat::Nil | Pat::Constant(_) => {},
+        }
+    }
+
     pub fn parse(pair: Pair<Rule>) -> Option<Self> {
         if pair.as_rule() != Rule::pattern { return None }
         let mut pairs = pair.into_inner();
@@ -276,7 +891,8 @@ impl TPat {
                 Some(Pat::Nil.type_pat(None))
             },
             Rule::constant => {
-                let value = pair.as_str().parse().ok().expect("constant should be an integer");
+                let value = parse_literal(pair.as_str())
+                    .expect("constant should be an integer");
                 Some(Pat::Constant(value).type_pat(None))
             },
             Rule::valueName => {
@@ -417,12 +1033,14 @@ impl :: bincode :: Decode for Pat
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TExpr {
     pub v: Expr,
     pub t: Option<Type>,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     Unit,
     Nil,
@@ -432,6 +1050,7 @@ pub enum Expr {
     Infix(InfixOp, Box<TExpr>, Box<TExpr>),
     Negate(Box<TExpr>),
     Application(Box<TExpr>, Box<TExpr>),
+    #[cfg_attr(feature = "serde", serde(with = "bigint_serde"))]
     Constant(BigInt),
     Variable(Variable),
     Function(Function),
@@ -603,6 +1222,11 @@ impl :: bincode :: Decode for Expr
     }
 }
 
+/* Parse the given integer literal, discarding any digit separators. */
+fn parse_literal(string: &str) -> Result<BigInt, num_bigint::ParseBigIntError> {
+    parse_prefixed_num(&string.replace('_', ""))
+}
+
 /* Parse signed integer literals beginning with at most one occurrence of 0x
  * (indicating a radix of 16), 0o (radix 8), or 0b (radix 2). */
 pub fn parse_prefixed_num<T>(string: &str) -> Result<T, T::FromStrRadixErr>
@@ -631,27 +1255,86 @@ where T: Num + Neg<Output = T> {
 }
 
 impl TExpr {
+    /* Apply the given function to every variable occuring in this
+     * expression, including those in captured environments. */
+    fn visit_variables(&mut self, f: &mut impl FnMut(&mut Variable)) {
+        match &mut self.v {
+            Expr::Variable(var) => f(var),
+            Expr::Sequence(exprs) => {
+                for expr in exprs {
+                    expr.visit_variables(f);
+                }
+            },
+            Expr::Product(expr1, expr2) | Expr::Cons(expr1, expr2) |
+            Expr::Infix(_, expr1, expr2) | Expr::Application(expr1, expr2) => {
+                expr1.visit_variables(f);
+                expr2.visit_variables(f);
+            },
+            Expr::Negate(expr1) => expr1.visit_variables(f),
+            Expr::Function(Function { params, body, env }) => {
+                for param in params {
+                    param.visit_variables(f);
+                }
+                body.visit_variables(f);
+                visit_env_variables(env, f);
+            },
+            Expr::Intrinsic(Intrinsic { params, env, .. }) => {
+                for param in params {
+                    param.visit_variables(f);
+                }
+                visit_env_variables(env, f);
+            },
+            Expr::LetBinding(binding, expr2) => {
+                binding.0.visit_variables(f);
+                binding.1.visit_variables(f);
+                expr2.visit_variables(f);
+            },
+            Expr::Match(matche) => {
+                matche.0.visit_variables(f);
+                for pat in &mut matche.1 {
+                    pat.visit_variables(f);
+                }
+                for expr2 in &mut matche.2 {
+                    expr2.visit_variables(f);
+                }
+            },
+            Expr::Unit | Expr::Nil | Expr::Constant(_) => {},
+        }
+    }
+
     pub fn parse(pair: Pair<Rule>) -> Option<Self> {
         if pair.as_rule() != Rule::expr { return None }
-        let string = pair.as_str();
         let mut pairs = pair.into_inner();
-        if string.starts_with("fun") {
-            let pair = pairs.next().expect("expression should not be empty");
-            Function::parse(pair).map(|x| Expr::Function(x).type_expr(None))
-        } else if string.starts_with("def") {
-            let pair = pairs.next().expect("body expression should be prefixed by binding");
-            let binding = LetBinding::parse(pair).expect("expression should start with binding");
-            let mut body = vec![];
-            while let Some(pair) = pairs.next() {
-                body.push(Self::parse(pair).expect("expression should end with expression"));
-            }
-            if body.is_empty() { panic!("expression should not be empty") }
-            Some(Expr::LetBinding(binding, Box::new(Expr::Sequence(body).type_expr(None))).type_expr(None))
-        } else {
-            let pair = pairs.next().expect("expression should not be empty");
-            Self::parse_expr1(pair)
+        let pair = pairs.next().expect("expression should not be empty");
+        match pair.as_rule() {
+            Rule::function =>
+                Function::parse(pair).map(|x| Expr::Function(x).type_expr(None)),
+            Rule::letBinding => {
+                let binding = LetBinding::parse(pair).expect("expression should start with binding");
+                let mut body = vec![];
+                while let Some(pair) = pairs.next() {
+                    body.push(Self::parse(pair).expect("expression should end with expression"));
+                }
+                if body.is_empty() { panic!("expression should not be empty") }
+                Some(Expr::LetBinding(binding, Box::new(Expr::Sequence(body).type_expr(None))).type_expr(None))
+            },
+            Rule::letIn => Self::parse_let_in(pair),
+            Rule::expr1 => Self::parse_expr1(pair),
+            _ => unreachable!("expression is of unknown form")
         }
     }
+
+    /* Parse an expression of the form let x = a in b. The bound variable is
+     * only visible within b, so it may shadow names from enclosing scopes. */
+    pub fn parse_let_in(pair: Pair<Rule>) -> Option<Self> {
+        if pair.as_rule() != Rule::letIn { return None }
+        let mut pairs = pair.into_inner();
+        let pair = pairs.next().expect("let expression should start with binding");
+        let binding = LetBinding::parse(pair).expect("let expression should start with binding");
+        let pair = pairs.next().expect("let expression should end with body");
+        let body = Self::parse(pair).expect("let expression should end with body");
+        Some(Expr::LetBinding(binding, Box::new(body)).type_expr(None))
+    }
     
     pub fn parse_expr1(pair: Pair<Rule>) -> Option<Self> {
         if pair.as_rule() != Rule::expr1 { return None }
@@ -704,10 +1387,18 @@ impl TExpr {
         let pair = pairs.next_back().expect("expression should not be empty");
         let mut exprs =
             Self::parse_expr5(pair).expect("expression should start with product");
-        while let Some(pair) = pairs.next_back() {
-            let rhs = Self::parse_expr5(pair)
-                .expect("expected RHS to be a product");
-            exprs = Expr::Cons(Box::new(rhs), Box::new(exprs)).type_expr(None);
+        while let Some(op) = pairs.next_back() {
+            let lhs_pair = pairs.next_back().expect("expected LHS product");
+            let lhs = Self::parse_expr5(lhs_pair)
+                .expect("expected LHS to be a product");
+            exprs = if op.as_str() == "++" {
+                // List concatenation is implemented by an intrinsic
+                let append = Expr::Variable(Variable::intrinsic("++")).type_expr(None);
+                let app = Expr::Application(Box::new(append), Box::new(lhs)).type_expr(None);
+                Expr::Application(Box::new(app), Box::new(exprs)).type_expr(None)
+            } else {
+                Expr::Cons(Box::new(lhs), Box::new(exprs)).type_expr(None)
+            };
         }
         Some(exprs)
     }
@@ -779,17 +1470,37 @@ impl TExpr {
     pub fn parse_expr9(pair: Pair<Rule>) -> Option<Self> {
         if pair.as_rule() != Rule::expr9 { return None }
         let mut pairs = pair.into_inner();
-        let pair = pairs.next().expect("expression should not be empty");
-        let mut expr =
-            Self::parse_expr10(pair).expect("expression should start with product");
+        let mut exprs: Vec<Self> = vec![];
         while let Some(pair) = pairs.next() {
-            let rhs = Self::parse_expr10(pair)
-                .expect("expected RHS to be a product");
+            if pair.as_rule() == Rule::slice {
+                // Slices apply to the immediately preceding expression
+                let expr = exprs.pop().expect("slice should follow an expression");
+                exprs.push(Self::parse_slice(pair, expr).expect("expected slice"));
+            } else {
+                exprs.push(Self::parse_expr10(pair).expect("expected application operand"));
+            }
+        }
+        let mut exprs = exprs.into_iter();
+        let mut expr = exprs.next().expect("expression should not be empty");
+        for rhs in exprs {
             expr = Expr::Application(Box::new(expr), Box::new(rhs)).type_expr(None);
         }
         Some(expr)
     }
 
+    /* Parse a slice of the given list expression. Slices are implemented by an
+     * intrinsic taking the start, the end, and the list. */
+    pub fn parse_slice(pair: Pair<Rule>, list: Self) -> Option<Self> {
+        if pair.as_rule() != Rule::slice { return None }
+        let mut pairs = pair.into_inner();
+        let mut expr = Expr::Variable(Variable::intrinsic("[..]")).type_expr(None);
+        while let Some(pair) = pairs.next() {
+            let bound = Self::parse(pair).expect("slice bound should be an expression");
+            expr = Expr::Application(Box::new(expr), Box::new(bound)).type_expr(None);
+        }
+        Some(Expr::Application(Box::new(expr), Box::new(list)).type_expr(None))
+    }
+
     pub fn parse_expr10(pair: Pair<Rule>) -> Option<Self> {
         if pair.as_rule() != Rule::expr10 { return None }
         let string = pair.as_str();
@@ -800,14 +1511,15 @@ impl TExpr {
         } else if pair.as_rule() == Rule::constant && string.starts_with("[") {
             Some(Expr::Nil.type_expr(None))
         } else if pair.as_rule() == Rule::constant {
-            let value = parse_prefixed_num(pair.as_str())
+            let value = parse_literal(pair.as_str())
                 .expect("constant should be an integer");
             Some(Expr::Constant(value).type_expr(None))
-        } else if pair.as_rule() == Rule::valueName {
+        } else if matches!(pair.as_rule(), Rule::valueName | Rule::qualifiedName) {
             let name = Variable::parse(pair).expect("expression should be value name");
             Some(Expr::Variable(name).type_expr(None))
         } else if string.starts_with("(") || string.starts_with("fun") ||
-        string.starts_with("def") || string.starts_with("match") {
+        string.starts_with("def") || string.starts_with("let") ||
+        string.starts_with("match") {
             Self::parse(pair)
         } else {
             unreachable!("expression is of unknown form")
@@ -864,6 +1576,7 @@ impl fmt::Display for TExpr {
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Match(pub Box<TExpr>, pub Vec<TPat>, pub Vec<TExpr>);
 
 impl fmt::Display for Match {
@@ -885,6 +1598,7 @@ impl fmt::Display for Match {
 }
 
 #[derive(Debug, Clone, Copy, Encode, Decode, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InfixOp {
     Divide,
     DivideZ,
@@ -934,6 +1648,7 @@ impl fmt::Display for InfixOp {
 pub type VariableId = u32;
 
 #[derive(Clone, Debug, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variable {
     pub name: Option<String>,
     pub id: VariableId,
@@ -943,9 +1658,15 @@ impl Variable {
     pub fn new(id: VariableId) -> Self {
         Self { id, name: None }
     }
+
+    /* Make a reference to the intrinsic with the given name. Such names are
+     * not valid identifiers and hence cannot be shadowed by programs. */
+    pub fn intrinsic(name: &str) -> Self {
+        Self { id: 0, name: Some(name.to_string()) }
+    }
     
     pub fn parse(pair: Pair<Rule>) -> Option<Self> {
-        if pair.as_rule() != Rule::valueName { return None }
+        if !matches!(pair.as_rule(), Rule::valueName | Rule::qualifiedName) { return None }
         Some(Self{name: Some(pair.as_str().to_string()), id: 0 })
     }
 }
@@ -961,6 +1682,7 @@ impl fmt::Display for Variable {
 }
 
 #[derive(Debug, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Function {
     pub params: Vec<TPat>,
     pub body: Box<TExpr>,
@@ -1008,7 +1730,7 @@ type IntrinsicImp = fn(
     &HashMap<VariableId, TExpr>,
     &mut HashSet<VariableId>,
     &mut VarGen
-) -> TExpr;
+) -> Result<TExpr, CompileError>;
 
 #[derive(Clone)]
 pub struct Intrinsic {
@@ -1023,7 +1745,7 @@ impl bincode::Encode for Intrinsic {
         &self,
         _encoder: &mut E,
     ) -> core::result::Result<(), bincode::error::EncodeError> {
-        panic!("intrinsic functions cannot be encoded")
+        Err(bincode::error::EncodeError::Other("intrinsic functions cannot be encoded"))
     }
 }
 
@@ -1031,7 +1753,21 @@ impl bincode::Decode for Intrinsic {
     fn decode<D: bincode::de::Decoder>(
         _decoder: &mut D,
     ) -> core::result::Result<Self, bincode::error::DecodeError> {
-        panic!("intrinsic functions cannot be decoded")
+        Err(bincode::error::DecodeError::Other("intrinsic functions cannot be decoded"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Intrinsic {
+    fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("intrinsic functions cannot be serialized"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Intrinsic {
+    fn deserialize<D: serde::Deserializer<'de>>(_deserializer: D) -> Result<Self, D::Error> {
+        Err(serde::de::Error::custom("intrinsic functions cannot be deserialized"))
     }
 }
 
@@ -1071,7 +1807,7 @@ impl Intrinsic {
         bindings: &HashMap<VariableId, TExpr>,
         prover_defs: &mut HashSet<VariableId>,
         gen: &mut VarGen
-    ) -> TExpr {
+    ) -> Result<TExpr, CompileError> {
         (self.imp)(&self.params, bindings, prover_defs, gen)
     }
 }
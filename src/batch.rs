@@ -0,0 +1,111 @@
+use crate::metadata::is_metadata_path;
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::stdio::PathOrStdio;
+use serde_json::json;
+use vamp_ir::error::panic_message;
+use std::fs;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/* Whether one proof of a batch was accepted, and how long checking it took. */
+pub struct ProofVerdict {
+    pub proof: PathBuf,
+    pub result: Result<(), CommandError>,
+    pub time: Duration,
+}
+
+/* Gather the proofs given one by one followed by those in the given directory,
+ * in order of name. Hidden files and the metadata of proofs are left out of
+ * the directory's. */
+pub fn collect_proofs(proofs: &[PathOrStdio], proof_dir: Option<&Path>) -> Result<Vec<PathBuf>, CommandError> {
+    let mut collected: Vec<_> = proofs.iter().map(|proof| proof.to_path_buf()).collect();
+    if let Some(dir) = proof_dir {
+        let name = dir.to_string_lossy();
+        let mut found = vec![];
+        for entry in fs::read_dir(dir).map_err(|err| CommandError::input(&name, err))? {
+            let path = entry.map_err(|err| CommandError::input(&name, err))?.path();
+            let hidden = path.file_name().is_some_and(|file| file.to_string_lossy().starts_with('.'));
+            if path.is_file() && !hidden && !is_metadata_path(&path) {
+                found.push(path);
+            }
+        }
+        if found.is_empty() {
+            return Err(CommandError::Input(format!("{}: no proofs found", name)));
+        }
+        found.sort();
+        collected.extend(found);
+    }
+    Ok(collected)
+}
+
+/* Check each of the given proofs with the given function on a pool of as many
+ * threads as there are processors, and give the verdicts in the order of the
+ * proofs. A proof whose check panics is taken to be unusable. */
+pub fn verify_all<F>(proofs: &[PathBuf], verify: F) -> Vec<ProofVerdict>
+where
+    F: Fn(&Path) -> Result<(), CommandError> + Sync,
+{
+    let threads = std::thread::available_parallelism()
+        .map_or(1, |threads| threads.get())
+        .clamp(1, proofs.len().max(1));
+    let next = AtomicUsize::new(0);
+    let verdicts = Mutex::new(Vec::with_capacity(proofs.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(proof) = proofs.get(index) else { break };
+                let start = Instant::now();
+                let result = catch_unwind(AssertUnwindSafe(|| verify(proof)))
+                    .unwrap_or_else(|payload| Err(CommandError::Input(panic_message(payload.as_ref()))));
+                let verdict = ProofVerdict { proof: proof.clone(), result, time: start.elapsed() };
+                verdicts.lock().expect("no verifier panics while holding the verdicts").push((index, verdict));
+            });
+        }
+    });
+    let mut verdicts = verdicts.into_inner().expect("no verifier panics while holding the verdicts");
+    verdicts.sort_by_key(|(index, _)| *index);
+    verdicts.into_iter().map(|(_, verdict)| verdict).collect()
+}
+
+/* Print a line for each of the given verdicts and a summary to the given
+ * outcome. If any proof was not accepted, the outcome fails as the most severe
+ * of the errors would have made a single proof fail. */
+pub fn report_verdicts(mut outcome: Outcome, verdicts: Vec<ProofVerdict>) -> CommandResult {
+    outcome.println("* Proofs:");
+    let mut listed = vec![];
+    for verdict in &verdicts {
+        let name = verdict.proof.to_string_lossy();
+        let millis = verdict.time.as_secs_f64() * 1000.0;
+        match &verdict.result {
+            Ok(()) => outcome.println(format!("** {}: valid ({:.1} ms)", name, millis)),
+            Err(err) => outcome.println(format!("** {}: {} ({:.1} ms)", name, err, millis)),
+        }
+        listed.push(json!({
+            "proof": name,
+            "valid": verdict.result.is_ok(),
+            "error": verdict.result.as_ref().err().map(|err| err.to_string()),
+            "ms": millis,
+        }));
+    }
+    let valid = verdicts.iter().filter(|verdict| verdict.result.is_ok()).count();
+    outcome.println(format!("* {} of {} proofs are valid", valid, verdicts.len()));
+    outcome.detail("proofs", listed);
+    outcome.detail("valid", valid == verdicts.len());
+    let worst = verdicts.iter()
+        .filter_map(|verdict| verdict.result.as_ref().err())
+        .max_by_key(|err| err.exit_status());
+    if let Some(worst) = worst {
+        let summary = format!("{} of {} proofs were not accepted", verdicts.len() - valid, verdicts.len());
+        outcome.failure = Some(match worst {
+            CommandError::Failure(_) => CommandError::Failure(summary),
+            CommandError::Input(_) => CommandError::Input(summary),
+            CommandError::Internal(_) => CommandError::Internal(summary),
+            CommandError::Diagnosed(_, _) => CommandError::Input(summary),
+        });
+    }
+    Ok(outcome)
+}
@@ -0,0 +1,94 @@
+use crate::ast::{Module, VariableId};
+use crate::error::Error;
+use crate::r1cs::R1cs;
+use crate::transform::{derive_available_witnesses, FieldOps};
+use bellman::{Circuit, ConstraintSystem, LinearCombination, SynthesisError, Variable};
+use ff::PrimeField;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+/* A compiled module as a circuit of bellman, so that the provers built on it
+ * can prove it. Its constraints are lowered as for the .r1cs export, with the
+ * public variables of the module as the public inputs of the circuit, in
+ * order, just as ark_adapter::ModuleCircuit lowers them for arkworks. */
+pub struct BellmanCircuit<S> where S: PrimeField {
+    r1cs: R1cs,
+    // Values of the variables in canonical form, asked for only when proving
+    assignments: HashMap<VariableId, BigInt>,
+    phantom: PhantomData<S>,
+}
+
+/* The given canonical integer as an element of the field of S. */
+fn scalar<S: PrimeField>(value: &BigInt) -> S {
+    S::from_str_vartime(&value.to_string()).expect("canonical integers are elements of the field")
+}
+
+/* The given linear combination of wires as one of bellman, given the
+ * variables allocated for the wires. */
+fn linear_combination<S: PrimeField>(
+    terms: &crate::r1cs::LinearCombination,
+    wires: &[Variable],
+) -> LinearCombination<S> {
+    terms.iter().fold(LinearCombination::zero(), |lc, (wire, coeff)| {
+        lc + (scalar::<S>(coeff), wires[*wire as usize])
+    })
+}
+
+impl<S> BellmanCircuit<S> where S: PrimeField {
+    /* Wrap the given module, compiled over the field of S with the given
+     * operations, with the given values of its variables, from which those
+     * of any other variables defined by it are derived. Generating
+     * parameters needs no values, so they may be left empty then. Fails if
+     * the operations are of another field than that of S, or if a constraint
+     * is not of rank 1. */
+    pub fn new(
+        module: &Module,
+        mut assignments: HashMap<VariableId, BigInt>,
+        field_ops: &dyn FieldOps,
+    ) -> Result<Self, Error> {
+        let r1cs = R1cs::new(module, field_ops)?;
+        if S::from_str_vartime(&(&r1cs.prime - 1).to_string()) != Some(-S::one()) {
+            return Err(Error::Compile(format!(
+                "the module is compiled over the field of order {}, which is not the field of the circuit",
+                r1cs.prime,
+            )));
+        }
+        if !assignments.is_empty() {
+            derive_available_witnesses(module, &mut assignments, field_ops);
+        }
+        for value in assignments.values_mut() {
+            *value = field_ops.canonical(value.clone());
+        }
+        Ok(Self { r1cs, assignments, phantom: PhantomData })
+    }
+}
+
+impl<S> Circuit<S> for BellmanCircuit<S> where S: PrimeField {
+    fn synthesize<CS: ConstraintSystem<S>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+        let mut wires = vec![CS::one()];
+        for (index, var) in self.r1cs.wires.iter().enumerate() {
+            let value = || {
+                self.assignments.get(&var.id)
+                    .map(scalar::<S>)
+                    .ok_or(SynthesisError::AssignmentMissing)
+            };
+            let name = || format!("wire {}", index + 1);
+            let wire = if index < self.r1cs.public_inputs as usize {
+                cs.alloc_input(name, value)?
+            } else {
+                cs.alloc(name, value)?
+            };
+            wires.push(wire);
+        }
+        for (index, constraint) in self.r1cs.constraints.iter().enumerate() {
+            cs.enforce(
+                || format!("constraint {}", index),
+                |_| linear_combination(&constraint.a, &wires),
+                |_| linear_combination(&constraint.b, &wires),
+                |_| linear_combination(&constraint.c, &wires),
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use serde_json::{json, Value};
+use std::fmt;
+use std::time::Duration;
+
+/* The measurements of generating keys for, proving, and verifying a circuit
+ * with one backend, each repeated the same number of times. */
+pub struct BackendBench {
+    pub backend: &'static str,
+    // Base 2 logarithm of the number of rows, padded, of the circuit
+    pub k: u32,
+    pub keygen: Vec<Duration>,
+    pub prove: Vec<Duration>,
+    pub verify: Vec<Duration>,
+    // Sizes in bytes, where the backend can serialize what they measure
+    pub proof_size: usize,
+    pub proving_key_size: Option<usize>,
+    pub verifying_key_size: Option<usize>,
+}
+
+/* The middle of the given times, or the mean of the two middle ones. */
+pub fn median(times: &[Duration]) -> Duration {
+    let mut times = times.to_vec();
+    times.sort();
+    match times.len() {
+        0 => Duration::ZERO,
+        len if len % 2 == 1 => times[len / 2],
+        len => (times[len / 2 - 1] + times[len / 2]) / 2,
+    }
+}
+
+/* Write the given time in milliseconds. */
+fn millis(time: Duration) -> String {
+    format!("{:.1} ms", time.as_secs_f64() * 1000.0)
+}
+
+/* Write the given size, if known, in bytes. */
+fn bytes(size: Option<usize>) -> String {
+    size.map_or("-".to_string(), |size| format!("{} B", size))
+}
+
+impl BackendBench {
+    pub fn to_json(&self) -> Value {
+        let millis = |times: &[Duration]| median(times).as_secs_f64() * 1000.0;
+        json!({
+            "backend": self.backend,
+            "k": self.k,
+            "rows": 1u64 << self.k,
+            "keygen_ms": millis(&self.keygen),
+            "prove_ms": millis(&self.prove),
+            "verify_ms": millis(&self.verify),
+            "proof_bytes": self.proof_size,
+            "proving_key_bytes": self.proving_key_size,
+            "verifying_key_bytes": self.verifying_key_size,
+        })
+    }
+}
+
+/* The measurements of every backend compared. */
+pub struct BenchReport {
+    pub iterations: usize,
+    pub backends: Vec<BackendBench>,
+}
+
+impl BenchReport {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "iterations": self.iterations,
+            "backends": self.backends.iter().map(BackendBench::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "* Median times over {} iterations:", self.iterations)?;
+        write!(
+            f,
+            "** {:<8} {:>4} {:>12} {:>12} {:>12} {:>12} {:>14} {:>14}",
+            "Backend", "k", "Keygen", "Prove", "Verify", "Proof", "Proving key", "Verifying key",
+        )?;
+        for bench in &self.backends {
+            write!(
+                f,
+                "\n** {:<8} {:>4} {:>12} {:>12} {:>12} {:>12} {:>14} {:>14}",
+                bench.backend,
+                bench.k,
+                millis(median(&bench.keygen)),
+                millis(median(&bench.prove)),
+                millis(median(&bench.verify)),
+                bytes(Some(bench.proof_size)),
+                bytes(bench.proving_key_size),
+                bytes(bench.verifying_key_size),
+            )?;
+        }
+        Ok(())
+    }
+}
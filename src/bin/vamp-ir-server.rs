@@ -0,0 +1,43 @@
+/* Serves the proving service of vamp_ir::server over HTTP until killed. */
+
+use clap::Parser;
+use std::path::PathBuf;
+use vamp_ir::server::{Server, ServerConfig};
+
+#[derive(Parser)]
+#[command(version, about = "Compiles, proves, and verifies Vamp-IR programs over HTTP")]
+struct Args {
+    /// Address on which requests are answered
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: String,
+    /// Directory in which compiled circuits are cached, by default that of
+    /// vamp-ir under the user's cache directory
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+    /// Number of jobs, proofs or key generation, run at once
+    #[arg(long, default_value_t = 1)]
+    workers: usize,
+    /// Number of circuits kept in memory with their keys
+    #[arg(long, default_value_t = 16)]
+    loaded_circuits: usize,
+}
+
+fn main() {
+    let args = Args::parse();
+    let config = ServerConfig::default()
+        .proof_workers(args.workers)
+        .max_loaded_circuits(args.loaded_circuits);
+    let config = match args.cache_dir {
+        Some(dir) => config.cache_dir(dir),
+        None => config,
+    };
+    let server = match Server::bind(&args.listen, config) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(2);
+        },
+    };
+    println!("* Listening on http://{}", server.addr());
+    server.wait();
+}
@@ -0,0 +1,108 @@
+/* Verifies a Halo2 proof with nothing but the verifier of the library, so
+ * that it can be shipped inside other products: it never prompts, proves, or
+ * compiles, and builds with --no-default-features --features verifier-cli.
+ * Exits with the statuses of vamp-ir: 1 if the proof is rejected, 2 if what
+ * was given cannot be used, and 3 if something else goes wrong. */
+
+use clap::Parser;
+use num_bigint::BigInt;
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use vamp_ir::artifact::{read_header, unwrap_contents, ArtifactKind};
+use vamp_ir::halo2::verify::VerifierArtifact;
+use vamp_ir::status::{FAILURE_STATUS, INPUT_ERROR_STATUS, INTERNAL_ERROR_STATUS};
+use vamp_ir::{load_verifier_artifact, verify, verify_public, Error};
+
+#[derive(Parser)]
+#[command(version, about = "Verifies Halo2 proofs of Vamp-IR circuits, and nothing else")]
+struct Args {
+    /// Path to the verifier written by halo2 compile --emit-verifier, or to
+    /// the circuit itself when the halo2 backend is built in
+    #[arg(short, long)]
+    circuit: PathBuf,
+    /// Path to the proof, or - for standard input, in any encoding or
+    /// container when the compiler is built in and in binary otherwise
+    #[arg(short, long)]
+    proof: PathBuf,
+    /// Path to a JSON array of the values that the public variables must
+    /// take, in the order they are declared, as numbers or decimal strings
+    #[arg(long)]
+    public_inputs: Option<PathBuf>,
+}
+
+/* Read the verifier of the given verifier file, or of the given circuit file
+ * if circuits can be read by this build, and generate its verifying key. */
+fn load_verifier(bytes: &[u8]) -> Result<VerifierArtifact, Error> {
+    let kind = read_header(bytes).ok().and_then(|(header, _)| header).map(|header| header.kind);
+    match kind {
+        #[cfg(feature = "halo2")]
+        Some(ArtifactKind::Halo2Circuit) => {
+            let circuit = vamp_ir::halo2::circuit::Halo2Circuit::from_bytes(bytes)?;
+            VerifierArtifact::from_data(circuit.verifier_data())
+        },
+        #[cfg(not(feature = "halo2"))]
+        Some(ArtifactKind::Halo2Circuit) => Err(Error::Artifact(
+            "this binary was built without the halo2 backend, so it reads verifiers written by \
+             halo2 compile --emit-verifier but not circuits".to_string(),
+        )),
+        _ => load_verifier_artifact(bytes),
+    }
+}
+
+/* Read the values of the public variables from the JSON array in the file at
+ * the given path. */
+fn read_public_values(path: &Path) -> Result<Vec<BigInt>, Error> {
+    let context = |err: String| Error::Inputs(format!("{}: {}", path.to_string_lossy(), err));
+    let text = fs::read_to_string(path).map_err(|err| context(err.to_string()))?;
+    let values: Vec<Value> = serde_json::from_str(&text)
+        .map_err(|err| context(format!("not a JSON array of values: {}", err)))?;
+    values.iter().map(|value| {
+        let digits = match value {
+            Value::Number(number) => number.to_string(),
+            Value::String(digits) => digits.clone(),
+            _ => return Err(context(format!("{} is not an integer", value))),
+        };
+        digits.parse::<BigInt>().map_err(|_| context(format!("{} is not an integer", value)))
+    }).collect()
+}
+
+/* Read the file at the given path, or standard input if the path is -,
+ * undoing whatever encoding this build can. */
+fn read_file(path: &Path) -> Result<Vec<u8>, Error> {
+    let context = |err: String| Error::Artifact(format!("{}: {}", path.to_string_lossy(), err));
+    let contents = if path == Path::new("-") {
+        let mut contents = vec![];
+        std::io::stdin().read_to_end(&mut contents).map(|_| contents)
+    } else {
+        fs::read(path)
+    }.map_err(|err| context(err.to_string()))?;
+    unwrap_contents(contents).map_err(context)
+}
+
+fn run(args: &Args) -> Result<(), Error> {
+    let circuit = read_file(&args.circuit)?;
+    let proof = read_file(&args.proof)?;
+    let expected = args.public_inputs.as_deref().map(read_public_values).transpose()?;
+    let verifier = load_verifier(&circuit)?;
+    match expected {
+        Some(expected) => verify_public(&verifier, &proof, &expected),
+        None => verify(&verifier, &proof),
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => println!("* Zero-knowledge proof is valid"),
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            std::process::exit(match err {
+                Error::InvalidProof(_) => FAILURE_STATUS,
+                Error::Backend(_) => INTERNAL_ERROR_STATUS,
+                _ => INPUT_ERROR_STATUS,
+            });
+        },
+    }
+}
@@ -0,0 +1,107 @@
+/* What the bindings to other languages share: compiling, proving, and
+ * verifying circuits held in byte buffers, in the format of the files of the
+ * command line, with inputs and public values given as JSON. */
+
+use crate::ast::parse_prefixed_num;
+use crate::error::Error;
+use crate::halo2::circuit::{Halo2Circuit, Halo2Proof};
+use crate::halo2::synth::{make_constant, Halo2CostModel, PrimeFieldOps};
+use crate::transform::{input_name, CompileOptions};
+use halo2_proofs::pasta::Fp;
+use num_bigint::BigInt;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{RngCore, SeedableRng};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/* The fewest bytes of entropy that proving accepts, enough to seed the
+ * generator that hides the witnesses. */
+pub const MIN_ENTROPY: usize = 32;
+
+/* Seed a generator with the given entropy, which callers that cannot reach
+ * the randomness of the operating system take from one of their own. All of
+ * it is folded into the seed, so that none of it is wasted. */
+pub fn seeded_rng(entropy: &[u8]) -> Result<ChaCha20Rng, Error> {
+    if entropy.len() < MIN_ENTROPY {
+        return Err(Error::Inputs(format!(
+            "{} bytes of entropy were given, but proving needs at least {}",
+            entropy.len(), MIN_ENTROPY,
+        )));
+    }
+    let mut seed = [0u8; MIN_ENTROPY];
+    for (index, byte) in entropy.iter().enumerate() {
+        seed[index % MIN_ENTROPY] ^= byte;
+    }
+    Ok(ChaCha20Rng::from_seed(seed))
+}
+
+/* Parse a JSON object mapping names to values, each either an integer or a
+ * string holding one in any of the notations of the source language. */
+pub fn parse_values(json: &str) -> Result<HashMap<String, BigInt>, Error> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|err| Error::Inputs(format!("inputs are not JSON: {}", err)))?;
+    let Value::Object(entries) = value else {
+        return Err(Error::Inputs("inputs must be a JSON object mapping names to values".to_string()));
+    };
+    entries.into_iter().map(|(name, value)| {
+        let parsed = match &value {
+            Value::String(text) => parse_prefixed_num::<BigInt>(text.trim()).ok(),
+            Value::Number(number) => number.as_i64().map(BigInt::from)
+                .or_else(|| number.as_u64().map(BigInt::from)),
+            _ => None,
+        };
+        parsed.map(|parsed| (name.clone(), parsed))
+            .ok_or_else(|| Error::Inputs(format!("value of {} is not an integer: {}", name, value)))
+    }).collect()
+}
+
+/* Compile the given source to a circuit of the given backend, given back as
+ * the contents of the circuit file that the command line would write. Only
+ * Halo2 circuits can be made this way, as PLONK circuits are proved with
+ * public parameters kept apart from them. */
+pub fn compile(source: &str, backend: &str) -> Result<Vec<u8>, Error> {
+    if backend != "halo2" {
+        return Err(Error::Compile(format!("cannot compile for {} here, only for halo2", backend)));
+    }
+    let module = crate::parse(source)?;
+    let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+    let compiled = crate::compile(module, &PrimeFieldOps::<Fp>::default(), &options)?;
+    Halo2Circuit::new(compiled.module)?.to_bytes()
+}
+
+/* Prove the circuit of the given circuit file on the inputs of the given JSON
+ * object, drawing the randomness that hides the witnesses from the given
+ * generator. Gives back the contents of the proof file that the command line
+ * would write. */
+pub fn prove(circuit: &[u8], inputs_json: &str, rng: impl RngCore) -> Result<Vec<u8>, Error> {
+    let inputs = parse_values(inputs_json)?;
+    let circuit = Halo2Circuit::from_bytes(circuit)?;
+    circuit.prove_with_rng(&inputs, rng)?.to_bytes()
+}
+
+/* Check that the given proof file holds a valid proof of the circuit of the
+ * given circuit file, made for the public values of the given JSON object,
+ * which must name every public variable of the circuit and nothing else.
+ * Proofs that are invalid or of other public values fail with
+ * Error::InvalidProof. */
+pub fn verify(circuit: &[u8], proof: &[u8], public_json: &str) -> Result<(), Error> {
+    let expected = parse_values(public_json)?;
+    let circuit = Halo2Circuit::from_bytes(circuit)?;
+    let proof = Halo2Proof::from_bytes(proof)?;
+    let names: Vec<_> = circuit.module().pubs.iter().map(input_name).collect();
+    if let Some(unknown) = expected.keys().find(|name| !names.contains(name)) {
+        return Err(Error::Inputs(format!("{} is not a public variable of the circuit", unknown)));
+    }
+    if let Some(missing) = names.iter().find(|name| !expected.contains_key(*name)) {
+        return Err(Error::Inputs(format!("no value was given for public variable {}", missing)));
+    }
+    circuit.verify(&proof)?;
+    let mismatches: Vec<_> = names.iter().zip(&proof.public_values)
+        .filter(|(name, val)| make_constant::<Fp>(expected[*name].clone()) != **val)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if !mismatches.is_empty() {
+        return Err(Error::InvalidProof(format!("proof is of other values of {}", mismatches.join(", "))));
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use crate::ast::{Module, VariableId};
+use crate::artifact::{artifact_bytes, parse_artifact, ArtifactKind, DECODE_LIMIT};
+use crate::assign_inputs;
+use crate::bp::synth::{make_scalar, prover, verifier, ScalarFieldOps};
+use crate::error::Error;
+use crate::r1cs::R1cs;
+use ark_serialize::{Read, SerializationError};
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use bincode::error::{DecodeError, EncodeError};
+use curve25519_dalek_ng::scalar::Scalar;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::io::Write;
+
+/* Captures all the data required to use a Bulletproofs circuit, which needs
+ * no setup, so only the module itself. */
+pub struct BulletproofsCircuitData {
+    pub module: Module,
+    // Optimization level that the circuit was compiled at
+    pub opt_level: u8,
+    // Public variables fixed to constants at compile time, in order
+    pub defines: Vec<(String, String)>,
+}
+
+impl BulletproofsCircuitData {
+    pub fn read<R>(mut reader: R) -> Result<Self, DecodeError>
+    where R: std::io::Read {
+        let module: Module =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let opt_level: u8 =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let defines: Vec<(String, String)> =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        Ok(Self { module, opt_level, defines })
+    }
+
+    pub fn write<W>(&self, mut writer: W) -> Result<(), EncodeError>
+    where W: std::io::Write {
+        bincode::encode_into_std_write(
+            &self.module,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            self.opt_level,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            &self.defines,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofDataBulletproofs {
+    pub proof: Vec<u8>,
+    // Values of the public variables that the proof was made for, in the
+    // order they are declared, as encoded by encode_public_values
+    pub public_inputs: Vec<Vec<u8>>,
+}
+
+/* Encode the given values of public variables as they are written in proofs,
+ * which is as the canonical little-endian encodings of the scalars. */
+pub fn encode_public_values(values: &[Scalar]) -> Vec<Vec<u8>> {
+    values.iter().map(|val| val.as_bytes().to_vec()).collect()
+}
+
+/* Decode the values of public variables written in a proof. */
+pub fn decode_public_values(encoded: &[Vec<u8>]) -> Result<Vec<Scalar>, String> {
+    encoded.iter().map(|bytes| {
+        let bytes = <[u8; 32]>::try_from(bytes.as_slice())
+            .map_err(|_| format!("public input of {} bytes is not a scalar", bytes.len()))?;
+        Scalar::from_canonical_bytes(bytes)
+            .ok_or_else(|| "public input is not a scalar".to_string())
+    }).collect()
+}
+
+/* A Bulletproofs circuit held in memory along with the rank-1 constraint
+ * system that its module is lowered to. */
+pub struct BulletproofsCircuit {
+    data: BulletproofsCircuitData,
+    r1cs: R1cs,
+}
+
+/* A Bulletproofs proof along with the values of the public variables that it
+ * was made for, in the order that they are declared. */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BulletproofsProof {
+    pub proof: Vec<u8>,
+    pub public_values: Vec<Scalar>,
+}
+
+impl BulletproofsCircuit {
+    /* Lower the given module, compiled over the scalar field of Ristretto255,
+     * to a circuit. Fails if a constraint is not of rank 1. */
+    pub fn new(module: Module) -> Result<Self, Error> {
+        Self::from_data(BulletproofsCircuitData { module, opt_level: 0, defines: vec![] })
+    }
+
+    /* Lower the module of the given data, as compiled or as read from a
+     * file. */
+    pub fn from_data(data: BulletproofsCircuitData) -> Result<Self, Error> {
+        let r1cs = R1cs::new(&data.module, &ScalarFieldOps::default())?;
+        Ok(Self { data, r1cs })
+    }
+
+    /* Read a circuit from the contents of a circuit file written by the command
+     * line, in any of its encodings. */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let data = parse_artifact(bytes, ArtifactKind::BulletproofsCircuit, |reader| BulletproofsCircuitData::read(reader))
+            .map_err(Error::Artifact)?;
+        Self::from_data(data)
+    }
+
+    /* The contents of a circuit file holding this circuit, as the command line
+     * writes it in binary. */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        artifact_bytes(ArtifactKind::BulletproofsCircuit, |writer| self.data.write(writer))
+            .map_err(Error::Artifact)
+    }
+
+    pub fn data(&self) -> &BulletproofsCircuitData {
+        &self.data
+    }
+
+    pub fn module(&self) -> &Module {
+        &self.data.module
+    }
+
+    pub fn r1cs(&self) -> &R1cs {
+        &self.r1cs
+    }
+
+    /* Prove knowledge of the witnesses that the given inputs, named as in the
+     * source, give rise to. */
+    pub fn prove(&self, inputs: &HashMap<String, BigInt>) -> Result<BulletproofsProof, Error> {
+        let assigns = assign_inputs(self.module(), inputs, &ScalarFieldOps::default())?;
+        self.prove_assigned(assigns)
+    }
+
+    /* Prove knowledge of the given values of every variable, as assign_inputs
+     * gives them back. */
+    pub fn prove_assigned(&self, assigns: HashMap<VariableId, BigInt>) -> Result<BulletproofsProof, Error> {
+        let field_ops = ScalarFieldOps::default();
+        let module = self.module();
+        let witness: Vec<_> = self.r1cs.witness(&assigns, &field_ops)?.iter().map(make_scalar).collect();
+        let public_values: Vec<_> = module.pubs.iter().map(|var| make_scalar(&assigns[&var.id])).collect();
+        let proof = prover(&module.pubs, &self.r1cs, &witness, &public_values)
+            .map_err(|err| Error::Backend(format!("unable to generate proof: {:?}", err)))?;
+        Ok(BulletproofsProof { proof, public_values })
+    }
+
+    /* Check the given proof of this circuit against the public values that it
+     * carries. */
+    pub fn verify(&self, proof: &BulletproofsProof) -> Result<(), Error> {
+        let pubs = &self.module().pubs;
+        if proof.public_values.len() != pubs.len() {
+            return Err(Error::InvalidProof(format!(
+                "proof has {} public inputs, but the circuit has {} public variables",
+                proof.public_values.len(),
+                pubs.len(),
+            )));
+        }
+        verifier(pubs, &self.r1cs, &proof.proof, &proof.public_values)
+            .map_err(|err| Error::InvalidProof(format!("{:?}", err)))
+    }
+}
+
+impl BulletproofsProof {
+    pub fn read<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        let ProofDataBulletproofs { proof, public_inputs } = ProofDataBulletproofs::deserialize(reader)?;
+        let public_values = decode_public_values(&public_inputs)
+            .map_err(|_| SerializationError::InvalidData)?;
+        Ok(Self { proof, public_values })
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        let proof_data = ProofDataBulletproofs {
+            proof: self.proof.clone(),
+            public_inputs: encode_public_values(&self.public_values),
+        };
+        proof_data.serialize(writer)
+    }
+
+    /* Read a proof from the contents of a proof file written by the command
+     * line, in any of its encodings. */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        parse_artifact(bytes, ArtifactKind::BulletproofsProof, |reader| Self::read(reader))
+            .map_err(Error::Artifact)
+    }
+
+    /* The contents of a proof file holding this proof, as the command line
+     * writes it in binary. */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        artifact_bytes(ArtifactKind::BulletproofsProof, |writer| self.write(writer))
+            .map_err(Error::Artifact)
+    }
+}
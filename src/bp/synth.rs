@@ -0,0 +1,208 @@
+use crate::ast::Variable;
+use crate::plonk::field::{make_constant, PrimeFieldOps};
+use crate::r1cs::{LinearCombination as Terms, R1cs};
+use crate::transform::CostModel;
+use ark_ff::biginteger::BigInteger256 as BigInteger;
+use ark_ff::fields::{FftParameters, Fp256, Fp256Parameters, FpParameters};
+use bulletproofs::r1cs::{ConstraintSystem, LinearCombination, Prover, R1CSError, R1CSProof, Verifier};
+use bulletproofs::{BulletproofGens, PedersenGens};
+use curve25519_dalek_ng::scalar::Scalar;
+use merlin::Transcript;
+use num_bigint::{BigInt, BigUint, Sign};
+use std::collections::HashMap;
+
+/* The scalar field of Ristretto255, of prime order 2^252 +
+ * 27742317777372353535851937790883648493, as a field of arkworks so that
+ * programs are compiled and constants folded over the very field that
+ * Bulletproofs proves in. */
+pub type Fr = Fp256<FrParameters>;
+
+/* The operations of the scalar field of Ristretto255. */
+pub type ScalarFieldOps = PrimeFieldOps<Fr>;
+
+pub struct FrParameters;
+
+impl Fp256Parameters for FrParameters {}
+
+impl FftParameters for FrParameters {
+    type BigInt = BigInteger;
+
+    // The order of the multiplicative group is 2^2 * 3 * 11 * 198211423230930754013084525763697 *
+    // 276602624281642239937218680557139826668747
+    const TWO_ADICITY: u32 = 2;
+
+    // 2^T, in Montgomery form
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0x7c790e32b42f0e7d, 0x4c8ce706a7ae2cc8, 0xd73823cc921779ad, 0x05599959893f562a,
+    ]);
+}
+
+impl FpParameters for FrParameters {
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0x5812631a5cf5d3ed, 0x14def9dea2f79cd6, 0x0000000000000000, 0x1000000000000000,
+    ]);
+
+    const MODULUS_BITS: u32 = 253;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 3;
+
+    // 2^256 mod MODULUS
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([
+        0xd6ec31748d98951d, 0xc6ef5bf4737dcf70, 0xfffffffffffffffe, 0x0fffffffffffffff,
+    ]);
+
+    // 2^512 mod MODULUS
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([
+        0xa40611e3449c0f01, 0xd00e1ba768859347, 0xceec73d217f5be65, 0x0399411b7c309a3d,
+    ]);
+
+    // -MODULUS^-1 mod 2^64
+    const INV: u64 = 0xd2b51da312547e1b;
+
+    // 2, in Montgomery form
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([
+        0x55c5ffcebe3b564d, 0x78ffbe0a4404020b, 0xfffffffffffffffd, 0x0fffffffffffffff,
+    ]);
+
+    #[rustfmt::skip]
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0x2c09318d2e7ae9f6, 0x0a6f7cef517bce6b, 0x0000000000000000, 0x0800000000000000,
+    ]);
+
+    // (MODULUS - 1) / 2^TWO_ADICITY
+    #[rustfmt::skip]
+    const T: BigInteger = BigInteger([
+        0x960498c6973d74fb, 0x0537be77a8bde735, 0x0000000000000000, 0x0400000000000000,
+    ]);
+
+    #[rustfmt::skip]
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        0xcb024c634b9eba7d, 0x029bdf3bd45ef39a, 0x0000000000000000, 0x0200000000000000,
+    ]);
+}
+
+/* Costs of the constructs of circuits proved with Bulletproofs. Proofs and
+ * proving time grow with the number of multipliers, which only
+ * multiplications and the allocation of variables use; additions and copies
+ * become linear constraints, which are nearly free. */
+pub struct BulletproofsCostModel;
+
+impl CostModel for BulletproofsCostModel {
+    fn add_gate(&self) -> u64 { 1 }
+    fn mul_gate(&self) -> u64 { 4 }
+    fn copy_constraint(&self) -> u64 { 0 }
+    fn public_input(&self) -> u64 { 1 }
+}
+
+/* Make a scalar of Ristretto255 from a signed value. */
+pub fn make_scalar(c: &BigInt) -> Scalar {
+    let value: BigUint = make_constant::<Fr>(c).into();
+    let mut bytes = [0u8; 32];
+    let digits = value.to_bytes_le();
+    bytes[..digits.len()].copy_from_slice(&digits);
+    Scalar::from_bytes_mod_order(bytes)
+}
+
+/* The given scalar as the integer it stands for. */
+pub fn scalar_value(val: &Scalar) -> BigInt {
+    BigInt::from_bytes_le(Sign::Plus, val.as_bytes())
+}
+
+/* The number of generators needed to prove the given system: one multiplier
+ * for each multiplication, and one for every two wires allocated, rounded up
+ * to a power of two as the prover pads them. */
+fn gens_capacity(r1cs: &R1cs) -> usize {
+    let multiplications = r1cs.constraints.iter()
+        .filter(|constraint| !constraint.a.is_empty() || !constraint.b.is_empty())
+        .count();
+    (multiplications + (r1cs.wires.len() + 1) / 2).max(1).next_power_of_two()
+}
+
+/* Start the transcript of a proof of the given system, binding it to the
+ * shape of the system and to the values of its public variables. */
+fn transcript(r1cs: &R1cs, public_values: &[Scalar]) -> Transcript {
+    let mut transcript = Transcript::new(b"vamp-ir bulletproofs");
+    transcript.append_u64(b"wires", r1cs.wires.len() as u64);
+    transcript.append_u64(b"constraints", r1cs.constraints.len() as u64);
+    for value in public_values {
+        transcript.append_message(b"public", value.as_bytes());
+    }
+    transcript
+}
+
+/* Allocate the wires of the given system in the given constraint system,
+ * with the given values when proving, and constrain them as the system does.
+ * Each public variable is constrained to the value given for it, in the order
+ * they are declared, so that verifiers need know nothing else. */
+fn synthesize<CS: ConstraintSystem>(
+    cs: &mut CS,
+    pubs: &[Variable],
+    r1cs: &R1cs,
+    witness: Option<&[Scalar]>,
+    public_values: &[Scalar],
+) -> Result<(), R1CSError> {
+    let mut wires = vec![bulletproofs::r1cs::Variable::One()];
+    for index in 1..=r1cs.wires.len() {
+        wires.push(cs.allocate(witness.map(|witness| witness[index]))?);
+    }
+    let indices: HashMap<_, _> = r1cs.wires.iter().enumerate()
+        .map(|(index, var)| (var.id, index + 1))
+        .collect();
+    for (var, value) in pubs.iter().zip(public_values) {
+        cs.constrain(wires[indices[&var.id]] - *value);
+    }
+    let lc = |terms: &Terms| -> LinearCombination {
+        terms.iter().map(|(wire, coeff)| (wires[*wire as usize], make_scalar(coeff))).collect()
+    };
+    for constraint in &r1cs.constraints {
+        if constraint.a.is_empty() && constraint.b.is_empty() {
+            cs.constrain(lc(&constraint.c));
+        } else {
+            let (_, _, product) = cs.multiply(lc(&constraint.a), lc(&constraint.b));
+            cs.constrain(product - lc(&constraint.c));
+        }
+    }
+    Ok(())
+}
+
+/* Prove that the given values of the wires of the given system, starting
+ * with the one that holds one, satisfy it, with the given values of the
+ * public variables of its module. */
+pub fn prover(
+    pubs: &[Variable],
+    r1cs: &R1cs,
+    witness: &[Scalar],
+    public_values: &[Scalar],
+) -> Result<Vec<u8>, R1CSError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(gens_capacity(r1cs), 1);
+    let mut transcript = transcript(r1cs, public_values);
+    let mut prover = Prover::new(&pc_gens, &mut transcript);
+    synthesize(&mut prover, pubs, r1cs, Some(witness), public_values)?;
+    let proof = prover.prove(&bp_gens)?;
+    Ok(proof.to_bytes())
+}
+
+/* Check the given proof that the given system is satisfied by some values
+ * that give its public variables the given values. */
+pub fn verifier(
+    pubs: &[Variable],
+    r1cs: &R1cs,
+    proof: &[u8],
+    public_values: &[Scalar],
+) -> Result<(), R1CSError> {
+    let proof = R1CSProof::from_bytes(proof)?;
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(gens_capacity(r1cs), 1);
+    let mut transcript = transcript(r1cs, public_values);
+    let mut verifier = Verifier::new(&mut transcript);
+    synthesize(&mut verifier, pubs, r1cs, None, public_values)?;
+    verifier.verify(&proof, &pc_gens, &bp_gens)
+}
@@ -0,0 +1,107 @@
+use crate::ast::Module;
+use crate::artifact::{fnv1a, secure_digest, FNV_OFFSET};
+use crate::diagnostics::Diagnostic;
+use std::fs;
+use log::debug;
+use std::path::PathBuf;
+
+/* The project-local directory in which compiled modules are cached. */
+const CACHE_DIR: &str = ".vampir-cache";
+
+/* Identifies a compilation by everything that can affect its result: the
+ * compiler version, the backend and field, the options, and the source texts
+ * in order. */
+pub struct CacheKey {
+    // Compiler version, backend, field, and options in readable form
+    header: String,
+    // Cryptographic digest of the header and sources, which names the cache
+    // entry and which entries are checked against
+    digest: String,
+}
+
+impl CacheKey {
+    pub fn new(backend: &str, options: &str, texts: &[String]) -> Self {
+        let header = format!("vamp-ir {} {} {}", env!("CARGO_PKG_VERSION"), backend, options);
+        let parts: Vec<_> = std::iter::once(&header).chain(texts).map(String::as_bytes).collect();
+        let digest = secure_digest(&parts);
+        Self { header, digest }
+    }
+
+    /* The path of the cache entry for this key. */
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(CACHE_DIR).join(format!("{}.bin", self.digest))
+    }
+}
+
+/* The contents of a cache entry. The module is kept encoded so that its
+ * checksum can be verified before it is decoded. */
+#[derive(bincode::Encode, bincode::Decode)]
+struct CacheEntry {
+    header: String,
+    digest: String,
+    source_constraints: u64,
+    module: Vec<u8>,
+    checksum: u64,
+}
+
+/* Look up the module compiled under the given key along with the number of
+ * constraints in its source. Missing entries, entries made under another key,
+ * and corrupted entries are all treated as misses, the last with a warning
+ * appended to the given list. */
+pub fn lookup(key: &CacheKey, warnings: &mut Vec<Diagnostic>) -> Option<(Module, usize)> {
+    let path = key.path();
+    let bytes = fs::read(&path).ok()?;
+    let config = bincode::config::standard();
+    let decoded = bincode::decode_from_slice::<CacheEntry, _>(&bytes, config)
+        .ok()
+        .filter(|(entry, _)| fnv1a(FNV_OFFSET, &entry.module) == entry.checksum)
+        .and_then(|(entry, _)| {
+            let (module, _) = bincode::decode_from_slice::<Module, _>(&entry.module, config).ok()?;
+            Some((entry, module))
+        });
+    match decoded {
+        Some((entry, module)) if entry.header == key.header && entry.digest == key.digest => {
+            debug!("* Reusing compilation cached in {}...", path.to_string_lossy());
+            Some((module, entry.source_constraints as usize))
+        },
+        Some(_) => None,
+        None => {
+            warnings.push(Diagnostic::warning(
+                "corrupted-cache-entry",
+                format!("ignoring corrupted cache entry {}", path.to_string_lossy()),
+            ).with_suggestion(format!("delete {}", path.to_string_lossy())));
+            None
+        },
+    }
+}
+
+/* Store the given compiled module under the given key. Failing to do so only
+ * costs a later recompilation, so it is merely warned about in the given list.
+ */
+pub fn store(key: &CacheKey, module: &Module, source_constraints: usize, warnings: &mut Vec<Diagnostic>) {
+    let path = key.path();
+    let config = bincode::config::standard();
+    let result = bincode::encode_to_vec(module, config).and_then(|module| {
+        let checksum = fnv1a(FNV_OFFSET, &module);
+        bincode::encode_to_vec(CacheEntry {
+            header: key.header.clone(),
+            digest: key.digest.clone(),
+            source_constraints: source_constraints as u64,
+            module,
+            checksum,
+        }, config)
+    });
+    let written = result
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| {
+            fs::create_dir_all(CACHE_DIR)
+                .and_then(|_| fs::write(&path, bytes))
+                .map_err(|err| err.to_string())
+        });
+    if let Err(err) = written {
+        warnings.push(Diagnostic::warning(
+            "cache-write-failed",
+            format!("unable to write cache entry {}: {}", path.to_string_lossy(), err),
+        ));
+    }
+}
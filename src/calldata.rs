@@ -0,0 +1,65 @@
+use crate::error::Error;
+use crate::interface::Interface;
+use num_bigint::{BigInt, Sign};
+use std::collections::BTreeMap;
+
+/* The number of bytes in a word of the Ethereum ABI. */
+const WORD_BYTES: usize = 32;
+
+/* The number of bytes of the function selector that calldata of a call, as
+ * opposed to bare encoded arguments, begins with. */
+const SELECTOR_BYTES: usize = 4;
+
+/* Decode the given ABI-encoded calldata, written in hexadecimal with or
+ * without 0x, into the values of the public variables of the circuit with the
+ * given interface, in the order that they are declared. The values are either
+ * encoded as uint256 arguments, one word each as abi.encode writes them, or as
+ * a single uint256[] argument, whose words are preceded by its offset and
+ * length; either may follow a function selector. Every word must be below the
+ * given order of the field of the circuit. The values are named as they are
+ * in files of public inputs, so they can be checked as those are. */
+pub fn decode_public_calldata(
+    calldata: &str,
+    interface: &Interface,
+    modulus: &BigInt,
+) -> Result<BTreeMap<String, BigInt>, Error> {
+    let digits = calldata.trim();
+    let digits = digits.strip_prefix("0x").unwrap_or(digits);
+    let bytes = hex::decode(digits)
+        .map_err(|err| Error::Inputs(format!("calldata is not hexadecimal: {}", err)))?;
+    let bytes = match bytes.len() % WORD_BYTES {
+        0 => &bytes[..],
+        SELECTOR_BYTES => &bytes[SELECTOR_BYTES..],
+        _ => return Err(Error::Inputs(format!(
+            "calldata of {} bytes is neither whole words nor whole words after a function selector",
+            bytes.len(),
+        ))),
+    };
+    let words: Vec<_> = bytes.chunks(WORD_BYTES)
+        .map(|word| BigInt::from_bytes_be(Sign::Plus, word))
+        .collect();
+    let count = interface.public.len();
+    let values = if words.len() == count {
+        &words[..]
+    } else if words.len() == count + 2
+        && words[0] == BigInt::from(WORD_BYTES)
+        && words[1] == BigInt::from(count) {
+        &words[2..]
+    } else {
+        return Err(Error::Inputs(format!(
+            "calldata holds {} word(s), which encode no {} public value(s) as uint256 arguments or as a uint256[]",
+            words.len(), count,
+        )));
+    };
+    let mut named = BTreeMap::new();
+    for (name, value) in interface.public.iter().zip(values) {
+        if value >= modulus {
+            return Err(Error::Inputs(format!(
+                "the value {} of public variable {} is not below the order of the field, {}",
+                value, name, modulus,
+            )));
+        }
+        named.insert(name.clone(), value.clone());
+    }
+    Ok(named)
+}
@@ -0,0 +1,123 @@
+use crate::cli::shared::{compile_uncached, parse_define, ProofSystems};
+use crate::sources::Prelude;
+use crate::bench::BenchReport;
+use crate::inputs::{resolve_inputs, InputsFormat, Interactivity};
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::stdio::{ClaimStdio, PathOrStdio, StdioClaims};
+use crate::transform::{input_name, required_inputs, CompileOptions, DEFAULT_UNROLL_LIMIT, MAX_OPT_LEVEL};
+use log::info;
+use num_bigint::BigInt;
+
+use clap::Args;
+
+use std::collections::BTreeMap;
+
+#[derive(Args)]
+pub struct Bench {
+    /// Paths to source files to be compiled, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be compiled after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Optimization level at which the circuits are compiled
+    #[arg(short = 'O', long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=MAX_OPT_LEVEL as i64))]
+    opt_level: u8,
+    /// Path to the inputs file, or - for standard input
+    #[arg(short, long)]
+    inputs: Option<PathOrStdio>,
+    /// Format of the inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Read inputs from VAMPIR_INPUT_<name> environment variables, with any
+    /// character of a name that cannot appear in one written as _, overriding
+    /// the inputs file
+    #[arg(long)]
+    env_inputs: bool,
+    /// Set an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    overrides: Vec<(String, BigInt)>,
+    /// Fail instead of prompting for missing inputs, as when CI or
+    /// VAMPIR_NONINTERACTIVE is set
+    #[arg(long)]
+    non_interactive: bool,
+    /// Backends to compare, separated by commas
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "halo2,plonk")]
+    backends: Vec<ProofSystems>,
+    /// Number of times that keys are generated and proofs made and verified
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u64).range(1..))]
+    iterations: u64,
+}
+
+/* Implements the subcommand that compiles source files for each of the given
+ * backends and times generating keys, proving, and verifying with each. Inputs
+ * are resolved once, against the first backend's circuit, and given to the
+ * others by name. */
+pub fn bench_cmd(Bench { sources, source, prelude, unroll_limit, opt_level, inputs, format, env_inputs, overrides, non_interactive, backends, iterations }: &Bench) -> CommandResult {
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let expected_path_to_inputs = sources[0].with_extension("inputs");
+    let iterations = *iterations as usize;
+    let mut named_inputs: Option<BTreeMap<String, BigInt>> = None;
+    let mut report = BenchReport { iterations, backends: vec![] };
+    for backend in backends {
+        info!("* Compiling constraints for {}...", backend.name());
+        let options = CompileOptions::default()
+            .unroll_limit(*unroll_limit)
+            .opt_level(*opt_level)
+            .cost_model(backend.cost_model());
+        let module = compile_uncached(prelude, &sources, backend.field_ops().as_ref(), &options)?;
+        let assigns = match &named_inputs {
+            None => {
+                let assigns = resolve_inputs(
+                    &module,
+                    inputs.as_deref(),
+                    &expected_path_to_inputs,
+                    *format,
+                    *env_inputs,
+                    overrides,
+                    Interactivity::choose(*non_interactive),
+                )?;
+                named_inputs = Some(required_inputs(&module).iter()
+                    .map(|var| (input_name(var), assigns[&var.id].clone()))
+                    .collect());
+                assigns
+            },
+            Some(named_inputs) => required_inputs(&module).iter()
+                .map(|var| match named_inputs.get(&input_name(var)) {
+                    Some(value) => Ok((var.id, value.clone())),
+                    None => Err(CommandError::Internal(format!(
+                        "input {} only arises in the {} circuit",
+                        input_name(var), backend.name(),
+                    ))),
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        info!("* Benchmarking {} over {} iterations...", backend.name(), iterations);
+        report.backends.push(match backend {
+            #[cfg(feature = "halo2")]
+            ProofSystems::Halo2 => crate::cli::halo2::bench_halo2(module, assigns, iterations)?,
+            #[cfg(feature = "plonk")]
+            ProofSystems::Plonk => crate::cli::plonk::bench_plonk(module, assigns, iterations)?,
+            #[allow(unreachable_patterns)]
+            backend => return Err(backend.missing()),
+        });
+    }
+    let mut outcome = Outcome::default();
+    outcome.println(&report);
+    outcome.detail("bench", report.to_json());
+    Ok(outcome)
+}
+
+impl ClaimStdio for Bench {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--source", self.sources.iter().chain(&self.source))
+            .reads("--inputs", &self.inputs);
+    }
+}
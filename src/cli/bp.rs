@@ -0,0 +1,190 @@
+use crate::cli::shared::print_circuit_summary;
+use crate::artifact::{read_digested_artifact, ArtifactKind};
+use crate::cli::driver::{check_prove_outputs, compile_with, prove_with, verify_with, Backend, CompileArgs, ProveArgs, VerifyArgs};
+use crate::stdio::{ClaimStdio, StdioClaims};
+use log::info;
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::batch::collect_proofs;
+use num_bigint::BigInt;
+use crate::ast::{Module, VariableId};
+use crate::transform::{CostModel, FieldOps};
+use crate::bp::synth::{make_scalar, scalar_value, BulletproofsCostModel, ScalarFieldOps};
+use crate::bp::circuit::{BulletproofsCircuit, BulletproofsCircuitData, BulletproofsProof};
+use vamp_ir::error::Error;
+
+use ark_serialize::SerializationError;
+use bincode::error::EncodeError;
+use curve25519_dalek_ng::scalar::Scalar;
+
+use clap::Subcommand;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Subcommand)]
+pub enum BpCommands {
+    /// Compiles a given source file to a circuit
+    Compile(CompileArgs),
+    /// Proves knowledge of witnesses satisfying a circuit
+    Prove(ProveArgs),
+    /// Verifies that a proof is a correct one
+    Verify(VerifyArgs),
+}
+
+/* The calls of Bulletproofs over Ristretto255 that the shared compile, prove,
+ * and verify subcommands make. */
+struct Bulletproofs;
+
+impl Backend for Bulletproofs {
+    type Circuit = BulletproofsCircuit;
+    type Proof = BulletproofsProof;
+    type Value = Scalar;
+
+    fn name(&self) -> String {
+        "bp".to_string()
+    }
+
+    fn circuit_kind(&self) -> ArtifactKind {
+        ArtifactKind::BulletproofsCircuit
+    }
+
+    fn proof_kind(&self) -> ArtifactKind {
+        ArtifactKind::BulletproofsProof
+    }
+
+    fn field_ops(&self) -> Box<dyn FieldOps> {
+        Box::new(ScalarFieldOps::default())
+    }
+
+    fn cost_model(&self) -> Box<dyn CostModel> {
+        Box::new(BulletproofsCostModel)
+    }
+
+    fn build(&self, module: Module, opt_level: u8, defines: Vec<(String, String)>, outcome: &mut Outcome) -> Result<BulletproofsCircuit, CommandError> {
+        // Lowering checks that every constraint is of rank 1 before anything
+        // is written
+        info!("* Lowering constraints to a rank-1 constraint system...");
+        let data = BulletproofsCircuitData { module, opt_level, defines };
+        let circuit = BulletproofsCircuit::from_data(data)
+            .map_err(|err| CommandError::input("unable to compile circuit", err))?;
+        outcome.println(format!("** Multiplications: {}", multiplications(&circuit)));
+        outcome.detail("multiplications", multiplications(&circuit));
+        Ok(circuit)
+    }
+
+    fn write_circuit(&self, circuit: &BulletproofsCircuit, writer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        circuit.data().write(writer)
+    }
+
+    fn module<'a>(&self, circuit: &'a BulletproofsCircuit) -> &'a Module {
+        circuit.module()
+    }
+
+    fn opt_level(&self, circuit: &BulletproofsCircuit) -> u8 {
+        circuit.data().opt_level
+    }
+
+    fn defines<'a>(&self, circuit: &'a BulletproofsCircuit) -> &'a [(String, String)] {
+        &circuit.data().defines
+    }
+
+    fn prove(&self, circuit: &BulletproofsCircuit, assigns: HashMap<VariableId, BigInt>) -> Result<BulletproofsProof, Error> {
+        circuit.prove_assigned(assigns)
+    }
+
+    fn verify(&self, circuit: &BulletproofsCircuit, proof: &BulletproofsProof) -> Result<(), Error> {
+        circuit.verify(proof)
+    }
+
+    fn write_proof(&self, proof: &BulletproofsProof, writer: &mut Vec<u8>) -> Result<(), SerializationError> {
+        proof.write(writer)
+    }
+
+    fn read_proof(&self, reader: &mut dyn Read) -> Result<BulletproofsProof, SerializationError> {
+        BulletproofsProof::read(reader)
+    }
+
+    fn public_values<'a>(&self, proof: &'a BulletproofsProof) -> &'a [Scalar] {
+        &proof.public_values
+    }
+
+    fn make_value(&self, value: &BigInt) -> Scalar {
+        make_scalar(value)
+    }
+
+    fn value_of(&self, value: &Scalar) -> BigInt {
+        scalar_value(value)
+    }
+}
+
+/* Read the Bulletproofs circuit at the given path and lower its module,
+ * giving it along with the digest of its contents. */
+fn read_circuit(path: &Path) -> Result<(BulletproofsCircuit, String), CommandError> {
+    let name = path.to_string_lossy();
+    let (data, digest) = read_digested_artifact(path, ArtifactKind::BulletproofsCircuit, |reader| BulletproofsCircuitData::read(reader))
+        .map_err(|err| CommandError::input(&name, err))?;
+    let circuit = BulletproofsCircuit::from_data(data).map_err(|err| CommandError::input(&name, err))?;
+    Ok((circuit, digest.content))
+}
+
+/* The number of multiplication gates of the given circuit, which with the
+ * number of its wires sets the size of its proofs. */
+fn multiplications(circuit: &BulletproofsCircuit) -> usize {
+    circuit.r1cs().constraints.iter()
+        .filter(|constraint| !constraint.a.is_empty() || !constraint.b.is_empty())
+        .count()
+}
+
+/* Implements the subcommand that creates a proof from interactively entered
+ * inputs. */
+fn prove_bp_cmd(args: &ProveArgs) -> CommandResult {
+    check_prove_outputs(args)?;
+    info!("* Reading arithmetic circuit...");
+    let (circuit, digest) = read_circuit(&args.circuit)?;
+    prove_with(&Bulletproofs, args, circuit, digest)
+}
+
+/* Implements the subcommand that verifies that proofs are correct. */
+fn verify_bp_cmd(args: &VerifyArgs) -> CommandResult {
+    let proofs = collect_proofs(&args.proofs, args.proof_dir.as_deref())?;
+    info!("* Reading arithmetic circuit...");
+    let (circuit, digest) = read_circuit(&args.circuit)?;
+    verify_with(&Bulletproofs, args, &proofs, circuit, digest)
+}
+
+/* Print the metadata of the Bulletproofs circuit read from the given
+ * reader. */
+pub fn inspect_circuit(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let data = BulletproofsCircuitData::read(reader).map_err(|err| format!("{:?}", err))?;
+    print_circuit_summary(&data.module, &data.defines, outcome);
+    outcome.println(format!("** Optimization level: {}", data.opt_level));
+    outcome.detail("opt_level", data.opt_level);
+    Ok(())
+}
+
+/* Print the metadata of the Bulletproofs proof read from the given reader. */
+pub fn inspect_proof(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let proof = BulletproofsProof::read(reader).map_err(|err| format!("{:?}", err))?;
+    outcome.println(format!("** Proof size: {} bytes", proof.proof.len()));
+    outcome.detail("proof_bytes", proof.proof.len());
+    Ok(())
+}
+
+impl ClaimStdio for BpCommands {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        match self {
+            BpCommands::Compile(args) => args.claim_stdio(claims),
+            BpCommands::Prove(args) => args.claim_stdio(claims),
+            BpCommands::Verify(args) => args.claim_stdio(claims),
+        }
+    }
+}
+
+pub fn bp(bp_commands: &BpCommands) -> CommandResult {
+    match bp_commands {
+        BpCommands::Compile(args) => compile_with(&Bulletproofs, args),
+        BpCommands::Prove(args) => prove_bp_cmd(args),
+        BpCommands::Verify(args) => verify_bp_cmd(args),
+    }
+}
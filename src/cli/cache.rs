@@ -0,0 +1,64 @@
+use crate::cli::shared::ProofSystems;
+use crate::key_cache::{clear_entries, list_entries};
+use crate::status::{CommandError, CommandResult};
+use crate::stdio::{ClaimStdio, StdioClaims};
+
+use clap::{Args, Subcommand};
+
+use std::path::PathBuf;
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// Lists the cached entries with their sizes
+    List(CacheList),
+    /// Removes cached entries
+    Clear(CacheClear),
+}
+
+#[derive(Args)]
+pub struct CacheList {
+    /// Directory of the cache, by default vamp-ir under $XDG_CACHE_HOME or
+    /// ~/.cache
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct CacheClear {
+    /// Directory of the cache, by default vamp-ir under $XDG_CACHE_HOME or
+    /// ~/.cache
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+    /// Only remove the entries of this backend
+    #[arg(long, value_enum)]
+    backend: Option<ProofSystems>,
+    /// Only remove the entries for the circuit with this digest, as printed by
+    /// inspect
+    #[arg(long)]
+    circuit: Option<String>,
+}
+
+/* Implements the subcommand that lists the entries of the cache of public
+ * parameters and keys. */
+fn cache_list_cmd(CacheList { cache_dir }: &CacheList) -> CommandResult {
+    list_entries(cache_dir.as_deref()).map_err(CommandError::from)
+}
+
+/* Implements the subcommand that removes entries from the cache of public
+ * parameters and keys. */
+fn cache_clear_cmd(CacheClear { cache_dir, backend, circuit }: &CacheClear) -> CommandResult {
+    let backend = backend.map(|backend| backend.name());
+    clear_entries(cache_dir.as_deref(), backend.as_deref(), circuit.as_deref()).map_err(CommandError::from)
+}
+
+impl ClaimStdio for CacheCommands {
+    fn claim_stdio(&self, _: &mut StdioClaims) {}
+}
+
+/* Run the given subcommand of the cache. */
+pub fn cache(cache_commands: &CacheCommands) -> CommandResult {
+    match cache_commands {
+        CacheCommands::List(args) => cache_list_cmd(args),
+        CacheCommands::Clear(args) => cache_clear_cmd(args),
+    }
+}
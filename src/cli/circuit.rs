@@ -0,0 +1,331 @@
+use crate::cli::shared::{parse_define, precheck_inputs, show_proof_metadata, ProofSystems};
+#[cfg(all(feature = "plonk", feature = "halo2"))]
+use crate::cli::shared::print_circuit_summary;
+use crate::artifact::{self, check_header, read_contents, read_header, ArtifactKind, Inspector};
+use crate::ast::Module;
+use crate::inputs::{inputs_template, resolve_file_inputs, resolve_inputs, split_input_files, InputsFormat, Interactivity};
+use crate::output::{check_output, write_output};
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::stdio::{is_stdout, ClaimStdio, PathOrStdio, StdioClaims};
+#[cfg(all(feature = "plonk", feature = "halo2"))]
+use crate::transform::convert_constants;
+use log::info;
+use num_bigint::BigInt;
+
+use clap::Args;
+
+use std::path::Path;
+
+#[derive(Args)]
+pub struct CircuitCheck {
+    /// Path to the compiled circuit whose constraints are checked, of either
+    /// backend
+    #[arg(short, long)]
+    circuit: PathOrStdio,
+    /// Backend that the circuit must have been compiled for, which is
+    /// otherwise read from its header
+    #[arg(long, value_enum)]
+    backend: Option<ProofSystems>,
+    /// Path to prover's input file, or - for standard input
+    #[arg(short, long)]
+    inputs: Option<PathOrStdio>,
+    /// Path to a file of the values of public variables, or - for standard
+    /// input, in place of --inputs
+    #[arg(long, conflicts_with = "inputs")]
+    public_inputs: Option<PathOrStdio>,
+    /// Path to a file of the values of inputs that are not public, or - for
+    /// standard input, in place of --inputs
+    #[arg(long, conflicts_with = "inputs")]
+    private_inputs: Option<PathOrStdio>,
+    /// Format of the input files, detected from their extensions by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Read inputs from VAMPIR_INPUT_<name> environment variables, with any
+    /// character of a name that cannot appear in one written as _, overriding
+    /// the inputs file
+    #[arg(long)]
+    env_inputs: bool,
+    /// Set an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    overrides: Vec<(String, BigInt)>,
+    /// Fail instead of prompting for missing inputs, as when CI or
+    /// VAMPIR_NONINTERACTIVE is set
+    #[arg(long)]
+    non_interactive: bool,
+}
+
+#[derive(Args)]
+pub struct Inspect {
+    /// Path to the file written by vamp-ir to be inspected
+    artifact: PathOrStdio,
+}
+
+#[derive(Args)]
+pub struct InputsTemplate {
+    /// Path to the compiled circuit whose inputs are listed, of either backend
+    #[arg(short, long)]
+    circuit: PathOrStdio,
+    /// Format of the template, detected from the extension of the output by
+    /// default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Path to which the template is written instead of standard output
+    #[arg(short, long)]
+    output: Option<PathOrStdio>,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+pub struct Convert {
+    /// Path to the compiled circuit to be converted, of either backend, or -
+    /// for standard input
+    #[arg(short, long)]
+    input: PathOrStdio,
+    /// Backend for which the circuit is rebuilt
+    #[arg(long, value_enum)]
+    to: ProofSystems,
+    /// Path to which the converted circuit is written, or - for standard output
+    #[arg(short, long)]
+    output: PathOrStdio,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+    /// Path to public parameters, or - for standard input, from which the keys
+    /// of PLONK circuits are generated
+    #[arg(short, long, required_if_eq("to", "plonk"))]
+    universal_params: Option<PathOrStdio>,
+    /// Do not perform validity checks on public parameters
+    #[arg(long)]
+    unchecked: bool,
+}
+
+/* The printers of the metadata of each kind of file that vamp-ir writes and
+ * that this build of it can read. */
+const INSPECTORS: &[(ArtifactKind, Inspector)] = &[
+    #[cfg(feature = "halo2")]
+    (ArtifactKind::Halo2Circuit, crate::cli::halo2::inspect_circuit),
+    #[cfg(feature = "halo2")]
+    (ArtifactKind::Halo2Proof, crate::cli::halo2::inspect_proof),
+    #[cfg(feature = "halo2")]
+    (ArtifactKind::Halo2Verifier, crate::cli::halo2::inspect_verifier),
+    #[cfg(feature = "plonk")]
+    (ArtifactKind::PlonkParams, crate::cli::plonk::inspect_params),
+    #[cfg(feature = "plonk")]
+    (ArtifactKind::PlonkCircuit, crate::cli::plonk::inspect_circuit),
+    #[cfg(feature = "plonk")]
+    (ArtifactKind::PlonkProof, crate::cli::plonk::inspect_proof),
+    #[cfg(feature = "groth16")]
+    (ArtifactKind::Groth16Bn254Circuit, crate::cli::groth16::inspect_circuit::<ark_bn254::Bn254>),
+    #[cfg(feature = "groth16")]
+    (ArtifactKind::Groth16Bn254Proof, crate::cli::groth16::inspect_proof::<ark_bn254::Bn254>),
+    #[cfg(feature = "groth16")]
+    (ArtifactKind::Groth16Bls12_381Circuit, crate::cli::groth16::inspect_circuit::<ark_bls12_381::Bls12_381>),
+    #[cfg(feature = "groth16")]
+    (ArtifactKind::Groth16Bls12_381Proof, crate::cli::groth16::inspect_proof::<ark_bls12_381::Bls12_381>),
+    #[cfg(feature = "bulletproofs")]
+    (ArtifactKind::BulletproofsCircuit, crate::cli::bp::inspect_circuit),
+    #[cfg(feature = "bulletproofs")]
+    (ArtifactKind::BulletproofsProof, crate::cli::bp::inspect_proof),
+    #[cfg(feature = "plonky2")]
+    (ArtifactKind::Plonky2Circuit, crate::cli::plonky2::inspect_circuit),
+    #[cfg(feature = "plonky2")]
+    (ArtifactKind::Plonky2Proof, crate::cli::plonky2::inspect_proof),
+];
+
+/* Implements the subcommand that prints the metadata of a file written by
+ * vamp-ir, as told apart by its header, along with that of proofs kept beside
+ * them. Nothing is proved or verified. */
+pub fn inspect_cmd(Inspect { artifact }: &Inspect) -> CommandResult {
+    let path = artifact.to_string_lossy();
+    let bytes = read_contents(artifact).map_err(|err| CommandError::input(&path, err))?;
+    let mut outcome = Outcome::default();
+    let decoded = artifact::inspect(&path, &bytes, INSPECTORS, &mut outcome)?;
+    if decoded.is_some_and(|kind| kind.is_proof()) {
+        show_proof_metadata(&mut outcome, artifact);
+    }
+    Ok(outcome)
+}
+
+/* Read the compiled circuit at the given path, of either backend as its header
+ * says, giving that backend along with the module of the circuit and the
+ * optimization level and definitions that it was compiled with. */
+fn read_any_circuit(circuit: &Path) -> Result<(ProofSystems, (Module, u8, Vec<(String, String)>)), CommandError> {
+    let path = circuit.to_string_lossy();
+    let bytes = read_contents(circuit).map_err(|err| CommandError::input(&path, err))?;
+    let (header, mut reader) = read_header(bytes.as_slice())
+        .map_err(|err| CommandError::input(&path, err))?;
+    let kind = match &header {
+        Some(header) => header.kind,
+        None => return Err(CommandError::Input(format!(
+            "{}: no header, so it was produced by an older vamp-ir; recompile",
+            path,
+        ))),
+    };
+    check_header(&header, kind).map_err(|err| CommandError::input(&path, err))?;
+    let (system, parts) = match kind {
+        #[cfg(feature = "halo2")]
+        ArtifactKind::Halo2Circuit => (ProofSystems::Halo2, crate::cli::halo2::read_circuit_parts(&mut reader)),
+        #[cfg(feature = "plonk")]
+        ArtifactKind::PlonkCircuit => (ProofSystems::Plonk, crate::cli::plonk::read_circuit_parts(&mut reader)),
+        #[cfg(not(feature = "halo2"))]
+        ArtifactKind::Halo2Circuit => return Err(CommandError::input(&path, ProofSystems::Halo2.missing())),
+        #[cfg(not(feature = "plonk"))]
+        ArtifactKind::PlonkCircuit => return Err(CommandError::input(&path, ProofSystems::Plonk.missing())),
+        ArtifactKind::Groth16Bn254Circuit
+        | ArtifactKind::Groth16Bls12_381Circuit
+        | ArtifactKind::BulletproofsCircuit
+        | ArtifactKind::Plonky2Circuit => return Err(CommandError::Input(format!(
+            "{}: {}s are only supported by the {} commands so far",
+            path, kind, kind.backend(),
+        ))),
+        kind => return Err(CommandError::Input(format!("{}: this is a {}, not a circuit", path, kind))),
+    };
+    let parts = parts.map_err(|err| CommandError::Input(format!("{}: corrupted {}: {}", path, kind, err)))?;
+    Ok((system, parts))
+}
+
+/* Implements the subcommand that checks inputs against a compiled circuit of
+ * either backend, as a prover given --dry-run does, without needing to know
+ * which backend the circuit is for. */
+pub fn check_cmd(CircuitCheck { circuit, backend, inputs, public_inputs, private_inputs, format, env_inputs, overrides, non_interactive }: &CircuitCheck) -> CommandResult {
+    info!("* Reading arithmetic circuit...");
+    let (system, (module, _, _)) = read_any_circuit(circuit)?;
+    if let Some(backend) = backend.filter(|backend| *backend != system) {
+        return Err(CommandError::Input(format!(
+            "{}: this is a {} circuit, not a {} one",
+            circuit, system.name(), backend.name(),
+        )));
+    }
+    let mut expected_path_to_inputs = circuit.to_path_buf();
+    expected_path_to_inputs.set_extension("inputs");
+    let split_files = split_input_files(public_inputs.as_deref(), private_inputs.as_deref());
+    let assigns = if split_files.is_empty() {
+        resolve_inputs(
+            &module,
+            inputs.as_deref(),
+            &expected_path_to_inputs,
+            *format,
+            *env_inputs,
+            overrides,
+            Interactivity::choose(*non_interactive),
+        )?
+    } else {
+        resolve_file_inputs(&module, &split_files, *format, *env_inputs, overrides)?
+    };
+    precheck_inputs(&module, &assigns, system.field_ops().as_ref())?;
+    let mut outcome = Outcome::default();
+    outcome.println(format!(
+        "* Inputs satisfy all {} constraints of the {} circuit",
+        module.exprs.len(), system.name(),
+    ));
+    outcome.detail("backend", system.name());
+    Ok(outcome)
+}
+
+/* Implements the subcommand that writes a file of inputs to a compiled circuit
+ * for the user to fill in. The inputs listed are exactly those that proving
+ * demands. */
+pub fn inputs_template_cmd(InputsTemplate { circuit, format, output, force }: &InputsTemplate) -> CommandResult {
+    if let Some(path) = output {
+        check_output(path, *force).map_err(CommandError::Input)?;
+    }
+    let (_, (module, _, _)) = read_any_circuit(circuit)?;
+    let format = format.unwrap_or_else(|| match output {
+        Some(path) if !is_stdout(path) => InputsFormat::detect(path),
+        _ => InputsFormat::Json,
+    });
+    let template = inputs_template(&module, format);
+    let mut outcome = Outcome::default();
+    match output {
+        Some(path) => {
+            write_output(path, template, *force).map_err(CommandError::Input)?;
+            outcome.artifact("inputs", path);
+        },
+        None => outcome.text = template,
+    }
+    Ok(outcome)
+}
+
+/* Implements the subcommand that rebuilds a compiled circuit for another
+ * backend from the module that it embeds, whose constants are carried over to
+ * the field of that backend. Nothing is recompiled, so the circuit keeps the
+ * optimizations and definitions it was compiled with. */
+#[cfg(all(feature = "plonk", feature = "halo2"))]
+pub fn convert_cmd(Convert { input, to, output, force, universal_params, unchecked }: &Convert) -> CommandResult {
+    check_output(output, *force).map_err(CommandError::Input)?;
+    info!("* Reading arithmetic circuit...");
+    let path = input.to_string_lossy();
+    let (from, (mut module, opt_level, defines)) = read_any_circuit(input)?;
+    if from == *to {
+        return Err(CommandError::Input(format!("{}: this is already a {} circuit", path, to.name())));
+    }
+
+    info!("* Converting constants to the field of {}...", to.name());
+    let (from_ops, to_ops) = (from.field_ops(), to.field_ops());
+    convert_constants(&mut module, from_ops.as_ref(), to_ops.as_ref()).map_err(|lost| {
+        let lost: Vec<_> = lost.iter().map(BigInt::to_string).collect();
+        CommandError::Input(format!(
+            "{}: constant(s) {} of the {} circuit stand for no integer of the field of {}",
+            path, lost.join(", "), from.name(), to.name(),
+        ))
+    })?;
+
+    info!("* Synthesizing arithmetic circuit...");
+    let mut outcome = Outcome::default();
+    outcome.println(format!("* Converted {} circuit to {}:", from.name(), to.name()));
+    print_circuit_summary(&module, &defines, &mut outcome);
+    let digest = match to {
+        ProofSystems::Halo2 => crate::cli::halo2::write_circuit(module, opt_level, defines, output, *force)?,
+        ProofSystems::Plonk => {
+            let universal_params = universal_params.as_deref()
+                .expect("PLONK circuits are only converted to with public parameters");
+            outcome.time("keygen", || crate::cli::plonk::write_circuit(
+                module, opt_level, defines, universal_params, *unchecked, output, *force,
+            ))?
+        },
+    };
+    outcome.artifact("circuit", output);
+    outcome.circuit_hash = Some(digest.content);
+    info!("* Circuit conversion success!");
+    Ok(outcome)
+}
+
+/* Converting reads the circuits of one backend and writes those of the other,
+ * so it needs both. */
+#[cfg(not(all(feature = "plonk", feature = "halo2")))]
+pub fn convert_cmd(_: &Convert) -> CommandResult {
+    let missing = if cfg!(feature = "plonk") { ProofSystems::Halo2 } else { ProofSystems::Plonk };
+    Err(missing.missing())
+}
+
+impl ClaimStdio for CircuitCheck {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--circuit", [&self.circuit])
+            .reads("--inputs", &self.inputs)
+            .reads("--public-inputs", &self.public_inputs)
+            .reads("--private-inputs", &self.private_inputs);
+    }
+}
+
+impl ClaimStdio for Inspect {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("ARTIFACT", [&self.artifact]);
+    }
+}
+
+impl ClaimStdio for InputsTemplate {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--circuit", [&self.circuit])
+            .writes("--output", &self.output);
+    }
+}
+
+impl ClaimStdio for Convert {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--input", [&self.input])
+            .reads("--universal-params", &self.universal_params)
+            .writes("--output", [&self.output]);
+    }
+}
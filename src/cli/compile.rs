@@ -0,0 +1,331 @@
+use crate::cli::shared::{compile_and_report, compile_uncached, parse_define, record_stats, write_interface, write_ir, ProofSystems};
+use crate::sources::{cache_key, read_source, source_label, Prelude};
+use crate::ast::Module;
+use crate::diagnostics::DiagnosticsFormat;
+use crate::inputs::{check_known_inputs, describe_inputs, read_named_inputs, InputsFormat};
+use crate::interface::describe_unexpected;
+use crate::output::{check_output, write_output};
+use crate::pretty::print_module;
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::stdio::{is_stdout, ClaimStdio, PathOrStdio, StdioClaims};
+use crate::transform::{check_satisfiability, equivalent, input_name, required_inputs, CompileOptions, EquivalenceReport, DEFAULT_INLINE_LIMIT, DEFAULT_UNROLL_LIMIT, MAX_OPT_LEVEL};
+use vamp_ir::error::Error;
+use log::{debug, info};
+use num_bigint::BigInt;
+
+use clap::Args;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Args)]
+pub struct IrCompile {
+    /// Paths to source files to be compiled, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be compiled after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Proof system whose field the constraints are compiled over
+    #[arg(long, value_enum, default_value_t = ProofSystems::Plonk)]
+    field: ProofSystems,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Warn when local bindings shadow earlier bindings
+    #[arg(long)]
+    warn_shadowing: bool,
+    /// Treat compilation warnings as errors
+    #[arg(long)]
+    deny_warnings: bool,
+    /// Optimization level: 0 runs the required passes only, 1 adds cheap
+    /// rewrites, and 2 also shares gates
+    #[arg(short = 'O', long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=MAX_OPT_LEVEL as i64))]
+    opt_level: u8,
+    /// Largest single-use definition, in expression nodes, inlined from -O1
+    #[arg(long, default_value_t = DEFAULT_INLINE_LIMIT)]
+    inline_limit: usize,
+    /// Fix a public variable to a constant at compile time, given as name=value
+    #[arg(long = "define", value_name = "NAME=VALUE", value_parser = parse_define)]
+    defines: Vec<(String, BigInt)>,
+    /// Neither reuse nor record compilations in the .vampir-cache directory
+    #[arg(long)]
+    no_cache: bool,
+    /// Path to which the compiled intermediate representation is written, or -
+    /// for standard output
+    #[arg(long)]
+    emit_ir: PathOrStdio,
+    /// Path to which a JSON description of the inputs of the circuit is
+    /// written, or - for standard output
+    #[arg(long)]
+    emit_interface: Option<PathOrStdio>,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+    /// Print a summary of the compiled circuit, which is the default
+    #[arg(long, overrides_with = "no_stats")]
+    stats: bool,
+    /// Do not print a summary of the compiled circuit
+    #[arg(long, overrides_with = "stats")]
+    no_stats: bool,
+    /// Format in which warnings and errors are reported
+    #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+    diagnostics_format: DiagnosticsFormat,
+}
+
+#[derive(Args)]
+pub struct Canonicalize {
+    /// Path to the source file to be canonicalized, or - for standard input
+    source: PathOrStdio,
+    /// Path to which the canonical source is written instead of standard output
+    #[arg(short, long)]
+    output: Option<PathOrStdio>,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+    /// Order definitions by name where their dependencies allow
+    #[arg(long)]
+    sort: bool,
+}
+
+#[derive(Args)]
+pub struct Diff {
+    /// Path to the first source file to be compared
+    first: PathOrStdio,
+    /// Path to the second source file to be compared
+    second: PathOrStdio,
+    /// Prelude merged before each source file: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Proof system whose field the constraints are compiled over
+    #[arg(long, value_enum, default_value_t = ProofSystems::Plonk)]
+    field: ProofSystems,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Number of random assignments tried when the circuits are not identical
+    #[arg(long, default_value_t = 64)]
+    samples: usize,
+    /// Compile the second circuit at the highest optimization level, so as to
+    /// check the optimizations
+    #[arg(long)]
+    optimize_second: bool,
+}
+
+#[derive(Args)]
+pub struct SatisfiabilityTest {
+    /// Paths to source files to be compiled, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be compiled after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Proof system whose field the constraints are compiled over
+    #[arg(long, value_enum, default_value_t = ProofSystems::Plonk)]
+    field: ProofSystems,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Optimization level: 0 runs the required passes only, 1 adds cheap
+    /// rewrites, and 2 also shares gates
+    #[arg(short = 'O', long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=MAX_OPT_LEVEL as i64))]
+    opt_level: u8,
+    /// Largest single-use definition, in expression nodes, inlined from -O1
+    #[arg(long, default_value_t = DEFAULT_INLINE_LIMIT)]
+    inline_limit: usize,
+    /// Number of random assignments tried
+    #[arg(short = 'n', long, default_value_t = 16)]
+    samples: usize,
+    /// Path to a file fixing the values of some inputs, or - for standard input
+    #[arg(short, long)]
+    inputs: Option<PathOrStdio>,
+    /// Format of the inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Fix an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    overrides: Vec<(String, BigInt)>,
+}
+
+/* Implements the subcommand that compiles source files into the intermediate
+ * representation without synthesizing a circuit. */
+pub fn compile_ir_cmd(IrCompile { sources, source, prelude, field, unroll_limit, warn_shadowing, deny_warnings, opt_level, inline_limit, defines, no_cache, emit_ir, emit_interface, force, stats: print_timings, no_stats, diagnostics_format }: &IrCompile) -> CommandResult {
+    check_output(emit_ir, *force).map_err(CommandError::Input)?;
+    if let Some(emit_interface) = emit_interface {
+        check_output(emit_interface, *force).map_err(CommandError::Input)?;
+    }
+    info!("* Compiling constraints...");
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let options = format!(
+        "field={:?} unroll-limit={} warn-shadowing={} deny-warnings={} opt-level={} inline-limit={} defines={:?}",
+        field, unroll_limit, warn_shadowing, deny_warnings, opt_level, inline_limit, defines,
+    );
+    let key = (!*no_cache).then(|| cache_key("ir", &options, prelude, &sources)).transpose()?;
+    let field_ops = field.field_ops();
+    let options = CompileOptions::default()
+        .unroll_limit(*unroll_limit)
+        .deny_warnings(*deny_warnings)
+        .opt_level(*opt_level)
+        .inline_limit(*inline_limit)
+        .cost_model(field.cost_model());
+    let options = defines.iter().cloned()
+        .fold(options, |options, (name, value)| options.define(name, value));
+    let mut outcome = Outcome::default();
+    let (module_3ac, mut stats) = outcome.time("compile", || compile_and_report(key, prelude, &sources, *warn_shadowing, field_ops.as_ref(), &options, *diagnostics_format))?;
+    info!("* Writing intermediate representation...");
+    write_ir(&module_3ac, emit_ir, *force)?;
+    outcome.artifact("ir", emit_ir);
+    if let Some(emit_interface) = emit_interface {
+        write_interface(&module_3ac, emit_interface, *force)?;
+        outcome.artifact("interface", emit_interface);
+    }
+    info!("* Constraint compilation success!");
+    if !*print_timings {
+        stats.pass_timings.clear();
+    }
+    // Standard output only carries the representation when it is written there
+    let stdout_used = is_stdout(emit_ir) || emit_interface.as_deref().map_or(false, is_stdout);
+    record_stats(&mut outcome, &stats, !*no_stats && !stdout_used);
+    Ok(outcome)
+}
+
+/* Implements the subcommand that prints a source file back with stable
+ * spacing and decimal literals. */
+pub fn canonicalize_cmd(Canonicalize { source, output, force, sort }: &Canonicalize) -> CommandResult {
+    if let Some(path) = output {
+        check_output(path, *force).map_err(CommandError::Input)?;
+    }
+    let unparsed_file = read_source(source)?;
+    let module = Module::parse(&unparsed_file)
+        .map_err(|err| CommandError::Input(format!("{}:\n{}", source_label(source), err)))?;
+    let canonical = print_module(&module, *sort);
+    let mut outcome = Outcome::default();
+    match output {
+        Some(path) => {
+            write_output(path, canonical, *force).map_err(CommandError::Input)?;
+            outcome.artifact("source", path);
+        },
+        None => outcome.text = canonical,
+    }
+    Ok(outcome)
+}
+
+/* Implements the subcommand that compiles two source files and searches for
+ * an assignment that satisfies exactly one of the resulting circuits. */
+pub fn diff_cmd(Diff { first, second, prelude, field, unroll_limit, samples, optimize_second }: &Diff) -> CommandResult {
+    let field_ops = field.field_ops();
+    let mut modules = vec![];
+    for (source, opt_level) in [(first, 0), (second, if *optimize_second { MAX_OPT_LEVEL } else { 0 })] {
+        info!("* Compiling {}...", source.to_string_lossy());
+        let options = CompileOptions::default()
+            .unroll_limit(*unroll_limit)
+            .opt_level(opt_level)
+            .cost_model(field.cost_model());
+        modules.push(compile_uncached(prelude, &[source.clone()], field_ops.as_ref(), &options)?);
+    }
+    info!("* Comparing circuits...");
+    let report = equivalent(&modules[0], &modules[1], field_ops.as_ref(), *samples)
+        .map_err(|err| Error::Diagnosed(err.to_diagnostic()))?;
+    let mut outcome = Outcome::default();
+    outcome.println(&report);
+    match report {
+        EquivalenceReport::Distinguished { .. } =>
+            Err(CommandError::Failure(format!("circuits are not equivalent\n{}", report))),
+        _ => Ok(outcome),
+    }
+}
+
+/* Implements the subcommand that compiles source files and checks the
+ * resulting constraints against random inputs. */
+pub fn test_cmd(SatisfiabilityTest { sources, source, prelude, field, unroll_limit, opt_level, inline_limit, samples, inputs, format, overrides }: &SatisfiabilityTest) -> CommandResult {
+    let field_ops = field.field_ops();
+    info!("* Compiling constraints...");
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let options = CompileOptions::default()
+        .unroll_limit(*unroll_limit)
+        .opt_level(*opt_level)
+        .inline_limit(*inline_limit)
+        .cost_model(field.cost_model());
+    let module_3ac = compile_uncached(prelude, &sources, field_ops.as_ref(), &options)?;
+    // Only the circuit's public variables and inputs can be fixed
+    let input_variables: Vec<_> = module_3ac.pubs.iter()
+        .cloned()
+        .chain(required_inputs(&module_3ac))
+        .collect();
+    let known: BTreeSet<_> = input_variables.iter().map(input_name).collect();
+    let check_names = |named_assignments: &BTreeMap<String, BigInt>, source: &str| {
+        let unexpected: Vec<_> = named_assignments.keys()
+            .filter(|name| !known.contains(*name))
+            .cloned()
+            .collect();
+        if unexpected.is_empty() {
+            Ok(())
+        } else {
+            Err(CommandError::Input(format!("{}: unexpected inputs: {}", source, describe_unexpected(&unexpected, &known))))
+        }
+    };
+    let mut fixed = BTreeMap::new();
+    if let Some(path) = inputs {
+        info!("* Reading inputs from {}...", describe_inputs(path));
+        let named_assignments = read_named_inputs(path, &input_variables, *format)
+            .and_then(|mut named| check_known_inputs(&module_3ac, &mut named).map(|_| named))
+            .map_err(|err| CommandError::input(&describe_inputs(path), err))?;
+        check_names(&named_assignments, &describe_inputs(path))?;
+        fixed.extend(named_assignments);
+    }
+    // Values given on the command line take precedence over the file
+    let overrides: BTreeMap<_, _> = overrides.iter().cloned().collect();
+    check_names(&overrides, "command line")?;
+    fixed.extend(overrides);
+    debug!("* Effective inputs:");
+    for (name, value) in &fixed {
+        debug!("** {} = {}", name, value);
+    }
+    info!("* Sampling assignments...");
+    let mut outcome = Outcome::default();
+    let report = outcome.time("sample", || check_satisfiability(&module_3ac, &fixed, field_ops.as_ref(), *samples))
+        .map_err(|err| Error::Diagnosed(err.to_diagnostic()))?;
+    outcome.println(&report);
+    outcome.detail("samples", report.samples);
+    outcome.detail("satisfied", report.satisfied);
+    if report.satisfied == 0 && report.samples > 0 {
+        return Err(CommandError::Failure("no sampled assignment satisfies the constraints".to_string()));
+    }
+    Ok(outcome)
+}
+
+impl ClaimStdio for IrCompile {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--source", self.sources.iter().chain(&self.source))
+            .writes("--emit-ir", [&self.emit_ir])
+            .writes("--emit-interface", &self.emit_interface);
+    }
+}
+
+impl ClaimStdio for Canonicalize {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("SOURCE", [&self.source])
+            .writes("--output", &self.output);
+    }
+}
+
+impl ClaimStdio for Diff {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("FIRST", [&self.first])
+            .reads("SECOND", [&self.second]);
+    }
+}
+
+impl ClaimStdio for SatisfiabilityTest {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--source", self.sources.iter().chain(&self.source))
+            .reads("--inputs", &self.inputs);
+    }
+}
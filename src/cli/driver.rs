@@ -0,0 +1,419 @@
+use crate::cli::shared::{check_metadata_output, compile_and_report, parse_define, precheck_inputs, print_defines, public_values, record_defines, record_stats, show_proof_metadata, write_interface, write_ir, write_proof, write_proof_metadata};
+use crate::sources::{cache_key, Prelude};
+use crate::artifact::{read_artifact, write_artifact, ArtifactKind, Digests};
+use crate::container::ContainerFormat;
+use crate::encoding::Encoding;
+use crate::output::check_output;
+use crate::stdio::{is_stdout, ClaimStdio, PathOrStdio, StdioClaims};
+use crate::progress::{in_phase, terminal_progress, Phase};
+use crate::diagnostics::DiagnosticsFormat;
+use log::info;
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::batch::{report_verdicts, verify_all};
+use crate::inputs::{read_expected_public, resolve_file_inputs, resolve_inputs, split_input_files, InputsFormat, Interactivity};
+use num_bigint::BigInt;
+use crate::ast::{Module, VariableId};
+use vamp_ir::error::Error;
+use crate::transform::{input_name, CompileOptions, CostModel, FieldOps, DEFAULT_INLINE_LIMIT, DEFAULT_UNROLL_LIMIT, MAX_OPT_LEVEL};
+
+use ark_serialize::SerializationError;
+use bincode::error::EncodeError;
+use clap::Args;
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/* The calls particular to a backend whose circuits carry everything needed to
+ * prove and verify with them. The compile, prove, and verify subcommands of
+ * such backends are otherwise the same, and are driven by the functions
+ * below. */
+pub trait Backend {
+    type Circuit;
+    type Proof;
+    // Field elements of the public values that proofs carry
+    type Value: PartialEq;
+
+    // Name under which compilations are cached
+    fn name(&self) -> String;
+    fn circuit_kind(&self) -> ArtifactKind;
+    fn proof_kind(&self) -> ArtifactKind;
+    fn field_ops(&self) -> Box<dyn FieldOps>;
+    fn cost_model(&self) -> Box<dyn CostModel>;
+
+    // Makes the circuit of the given compiled module, reporting its size to
+    // the given outcome
+    fn build(&self, module: Module, opt_level: u8, defines: Vec<(String, String)>, outcome: &mut Outcome) -> Result<Self::Circuit, CommandError>;
+    // Called once the given circuit is written with the given digests
+    fn written(&self, _circuit: &Self::Circuit, _digests: &Digests) {}
+    fn write_circuit(&self, circuit: &Self::Circuit, writer: &mut Vec<u8>) -> Result<(), EncodeError>;
+    fn module<'a>(&self, circuit: &'a Self::Circuit) -> &'a Module;
+    fn opt_level(&self, circuit: &Self::Circuit) -> u8;
+    fn defines<'a>(&self, circuit: &'a Self::Circuit) -> &'a [(String, String)];
+
+    // Proves with the given assignment of every variable derived from the
+    // inputs
+    fn prove(&self, circuit: &Self::Circuit, assigns: HashMap<VariableId, BigInt>) -> Result<Self::Proof, Error>;
+    fn verify(&self, circuit: &Self::Circuit, proof: &Self::Proof) -> Result<(), Error>;
+    fn write_proof(&self, proof: &Self::Proof, writer: &mut Vec<u8>) -> Result<(), SerializationError>;
+    fn read_proof(&self, reader: &mut dyn Read) -> Result<Self::Proof, SerializationError>;
+    fn public_values<'a>(&self, proof: &'a Self::Proof) -> &'a [Self::Value];
+    fn make_value(&self, value: &BigInt) -> Self::Value;
+    fn value_of(&self, value: &Self::Value) -> BigInt;
+}
+
+#[derive(Args)]
+pub struct CompileArgs {
+    /// Paths to source files to be compiled, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    pub sources: Vec<PathOrStdio>,
+    /// Path to a source file to be compiled after any positional ones
+    #[arg(short, long)]
+    pub source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    pub prelude: Prelude,
+    /// Path to which circuit is written, or - for standard output
+    #[arg(short, long)]
+    pub output: PathOrStdio,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    pub force: bool,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    pub unroll_limit: usize,
+    /// Warn when local bindings shadow earlier bindings
+    #[arg(long)]
+    pub warn_shadowing: bool,
+    /// Treat compilation warnings as errors
+    #[arg(long)]
+    pub deny_warnings: bool,
+    /// Optimization level: 0 runs the required passes only, 1 adds cheap
+    /// rewrites, and 2 also shares gates
+    #[arg(short = 'O', long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=MAX_OPT_LEVEL as i64))]
+    pub opt_level: u8,
+    /// Largest single-use definition, in expression nodes, inlined from -O1
+    #[arg(long, default_value_t = DEFAULT_INLINE_LIMIT)]
+    pub inline_limit: usize,
+    /// Fix a public variable to a constant at compile time, given as name=value
+    #[arg(long = "define", value_name = "NAME=VALUE", value_parser = parse_define)]
+    pub defines: Vec<(String, BigInt)>,
+    /// Neither reuse nor record compilations in the .vampir-cache directory,
+    /// nor record any keys made in the key cache
+    #[arg(long)]
+    pub no_cache: bool,
+    /// Path to which the compiled intermediate representation is written, or -
+    /// for standard output
+    #[arg(long)]
+    pub emit_ir: Option<PathOrStdio>,
+    /// Path to which a JSON description of the inputs of the circuit is
+    /// written, or - for standard output
+    #[arg(long)]
+    pub emit_interface: Option<PathOrStdio>,
+    /// Print a summary of the compiled circuit, which is the default
+    #[arg(long, overrides_with = "no_stats")]
+    pub stats: bool,
+    /// Do not print a summary of the compiled circuit
+    #[arg(long, overrides_with = "stats")]
+    pub no_stats: bool,
+    /// Format in which warnings and errors are reported
+    #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+    pub diagnostics_format: DiagnosticsFormat,
+}
+
+#[derive(Args)]
+pub struct ProveArgs {
+    /// Path to circuit on which to construct proof
+    #[arg(short, long)]
+    pub circuit: PathOrStdio,
+    /// Path to which the proof is written, or - for standard output
+    #[arg(short, long)]
+    pub output: PathOrStdio,
+    /// Encoding in which the proof is written
+    #[arg(long, value_enum, default_value_t = Encoding::Binary)]
+    pub encoding: Encoding,
+    /// Wrap the proof, along with its public inputs and metadata, in a
+    /// self-describing container
+    #[arg(long, value_enum)]
+    pub container: Option<ContainerFormat>,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    pub force: bool,
+    /// Also write the public inputs, circuit digest, proving time, and
+    /// vamp-ir version to a PROOF.meta.json file beside the proof
+    #[arg(long)]
+    pub metadata: bool,
+    /// Path to prover's input file, or - for standard input
+    #[arg(short, long)]
+    pub inputs: Option<PathOrStdio>,
+    /// Path to a file of the values of public variables, or - for standard
+    /// input, in place of --inputs
+    #[arg(long, conflicts_with = "inputs")]
+    pub public_inputs: Option<PathOrStdio>,
+    /// Path to a file of the values of inputs that are not public, or - for
+    /// standard input, in place of --inputs
+    #[arg(long, conflicts_with = "inputs")]
+    pub private_inputs: Option<PathOrStdio>,
+    /// Format of the input files, detected from their extensions by default
+    #[arg(long, value_enum)]
+    pub format: Option<InputsFormat>,
+    /// Read inputs from VAMPIR_INPUT_<name> environment variables, with any
+    /// character of a name that cannot appear in one written as _, overriding
+    /// the inputs file
+    #[arg(long)]
+    pub env_inputs: bool,
+    /// Set an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    pub overrides: Vec<(String, BigInt)>,
+    /// Fail instead of prompting for missing inputs, as when CI or
+    /// VAMPIR_NONINTERACTIVE is set
+    #[arg(long)]
+    pub non_interactive: bool,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Path to circuit on which to construct proof
+    #[arg(short, long)]
+    pub circuit: PathOrStdio,
+    /// Path to a proof to be verified, or - for standard input, in any
+    /// encoding; give several times to verify several proofs
+    #[arg(short, long = "proof", required_unless_present = "proof_dir")]
+    pub proofs: Vec<PathOrStdio>,
+    /// Directory whose every file is a proof to be verified
+    #[arg(long)]
+    pub proof_dir: Option<PathBuf>,
+    /// Path to a file of the values that the public variables must take, or -
+    /// for standard input; proofs of other values are rejected
+    #[arg(long)]
+    pub public_inputs: Option<PathOrStdio>,
+    /// ABI-encoded calldata, in hexadecimal, of the values that the public
+    /// variables must take, in place of --public-inputs
+    #[arg(long, conflicts_with = "public_inputs")]
+    pub public_calldata: Option<String>,
+    /// Format of the public inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    pub format: Option<InputsFormat>,
+}
+
+/* Implements the subcommand that compiles a vamp-ir file into a circuit of
+ * the given backend. */
+pub fn compile_with<B: Backend>(
+    backend: &B,
+    CompileArgs { sources, source, prelude, output, force, unroll_limit, warn_shadowing, deny_warnings, opt_level, inline_limit, defines, no_cache, emit_ir, emit_interface, stats: print_timings, no_stats, diagnostics_format }: &CompileArgs,
+) -> CommandResult {
+    check_output(output, *force).map_err(CommandError::Input)?;
+    if let Some(emit_ir) = emit_ir {
+        check_output(emit_ir, *force).map_err(CommandError::Input)?;
+    }
+    if let Some(emit_interface) = emit_interface {
+        check_output(emit_interface, *force).map_err(CommandError::Input)?;
+    }
+    info!("* Compiling constraints...");
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let options = format!(
+        "unroll-limit={} warn-shadowing={} deny-warnings={} opt-level={} inline-limit={} defines={:?}",
+        unroll_limit, warn_shadowing, deny_warnings, opt_level, inline_limit, defines,
+    );
+    let key = (!*no_cache).then(|| cache_key(&backend.name(), &options, prelude, &sources)).transpose()?;
+    let options = CompileOptions::default()
+        .unroll_limit(*unroll_limit)
+        .deny_warnings(*deny_warnings)
+        .opt_level(*opt_level)
+        .inline_limit(*inline_limit)
+        .cost_model(backend.cost_model());
+    let options = defines.iter().cloned()
+        .fold(options, |options, (name, value)| options.define(name, value));
+    let field_ops = backend.field_ops();
+    let mut outcome = Outcome::default();
+    let (module_3ac, mut stats) = outcome.time("compile", || compile_and_report(key, prelude, &sources, *warn_shadowing, &*field_ops, &options, *diagnostics_format))?;
+    if let Some(emit_ir) = emit_ir {
+        write_ir(&module_3ac, emit_ir, *force)?;
+        outcome.artifact("ir", emit_ir);
+    }
+    if let Some(emit_interface) = emit_interface {
+        write_interface(&module_3ac, emit_interface, *force)?;
+        outcome.artifact("interface", emit_interface);
+    }
+
+    let circuit = backend.build(module_3ac, *opt_level, record_defines(defines), &mut outcome)?;
+    if !*print_timings {
+        stats.pass_timings.clear();
+    }
+    info!("* Serializing circuit to storage...");
+    let digests = write_artifact(output, backend.circuit_kind(), *force, Encoding::Binary, |writer| backend.write_circuit(&circuit, writer))
+        .map_err(CommandError::Input)?;
+    outcome.artifact("circuit", output);
+    backend.written(&circuit, &digests);
+    outcome.circuit_hash = Some(digests.content);
+
+    info!("* Constraint compilation success!");
+    // Standard output only carries the circuit when it is written there
+    record_stats(&mut outcome, &stats, !*no_stats && !is_stdout(output));
+    Ok(outcome)
+}
+
+/* Check that the files that proving with the given arguments writes can be,
+ * before the circuit is read. */
+pub fn check_prove_outputs(args: &ProveArgs) -> Result<(), CommandError> {
+    check_output(&args.output, args.force).map_err(CommandError::Input)?;
+    if args.metadata {
+        check_metadata_output(&args.output, args.force)?;
+    }
+    Ok(())
+}
+
+/* Implements the subcommand that creates a proof from interactively entered
+ * inputs, on the given circuit of the given backend whose contents have the
+ * given digest. */
+pub fn prove_with<B: Backend>(
+    backend: &B,
+    ProveArgs { circuit: circuit_path, output, encoding, container, force, metadata, inputs, public_inputs, private_inputs, format, env_inputs, overrides, non_interactive }: &ProveArgs,
+    circuit: B::Circuit,
+    digest: String,
+) -> CommandResult {
+    let mut expected_path_to_inputs = circuit_path.to_path_buf();
+    expected_path_to_inputs.set_extension("inputs");
+    let mut outcome = Outcome::default();
+    outcome.circuit_hash = Some(digest);
+    info!("** Circuit compiled at optimization level {}", backend.opt_level(&circuit));
+    print_defines(backend.defines(&circuit));
+
+    // Gather program inputs from the command line, files, or the user
+    let module = backend.module(&circuit);
+    let split_files = split_input_files(public_inputs.as_deref(), private_inputs.as_deref());
+    let var_assignments = if split_files.is_empty() {
+        resolve_inputs(
+            module,
+            inputs.as_deref(),
+            &expected_path_to_inputs,
+            *format,
+            *env_inputs,
+            overrides,
+            Interactivity::choose(*non_interactive),
+        )?
+    } else {
+        resolve_file_inputs(module, &split_files, *format, *env_inputs, overrides)?
+    };
+    let field_ops = backend.field_ops();
+    let metadata_inputs = (*metadata || container.is_some()).then(|| public_values(module, &var_assignments, &*field_ops)).transpose()?;
+    // Backends prove from the assignment they are given, so bad inputs are
+    // caught first
+    let var_assignments = precheck_inputs(module, &var_assignments, &*field_ops)?;
+
+    info!("* Proving knowledge of witnesses...");
+    let progress = terminal_progress();
+    let proof = outcome.time("prove", || in_phase(progress.as_ref(), Phase::CreateProof, || backend.prove(&circuit, var_assignments)))
+        .map_err(|err| CommandError::internal("unable to generate proof", err))?;
+
+    info!("* Serializing proof to storage...");
+    write_proof(&outcome, output, backend.proof_kind(), *force, *encoding, *container, metadata_inputs.as_deref(), |writer| backend.write_proof(&proof, writer))?;
+    outcome.artifact("proof", output);
+    if let Some(metadata_inputs) = metadata_inputs.filter(|_| *metadata) {
+        write_proof_metadata(&mut outcome, backend.proof_kind(), output, metadata_inputs, *force)?;
+    }
+
+    info!("* Proof generation success!");
+    Ok(outcome)
+}
+
+/* Implements the subcommand that verifies that the given proofs are correct,
+ * on the given circuit of the given backend whose contents have the given
+ * digest. */
+pub fn verify_with<B: Backend>(
+    backend: &B,
+    VerifyArgs { public_inputs, public_calldata, format, .. }: &VerifyArgs,
+    proofs: &[PathBuf],
+    circuit: B::Circuit,
+    digest: String,
+) -> CommandResult {
+    let mut outcome = Outcome::default();
+    outcome.circuit_hash = Some(digest);
+    print_defines(backend.defines(&circuit));
+    let module = backend.module(&circuit);
+    let expected = read_expected_public(
+        module, public_inputs.as_deref(), public_calldata.as_deref(), *format, &*backend.field_ops(),
+    )?;
+
+    let read_proof = |proof: &Path| {
+        read_artifact(proof, backend.proof_kind(), |reader| backend.read_proof(reader))
+            .map_err(|err| CommandError::input(&proof.to_string_lossy(), err))
+    };
+    // The proof is checked against the values that it carries, which must
+    // be those expected if any are
+    let check = |proof: B::Proof| {
+        if let Some(expected) = &expected {
+            check_public_inputs(backend, module, backend.public_values(&proof), expected)?;
+        }
+        backend.verify(&circuit, &proof).map_err(|err| CommandError::Failure(err.to_string()))
+    };
+    if let [proof] = proofs {
+        let proof_data = read_proof(proof)?;
+        show_proof_metadata(&mut outcome, proof);
+        outcome.println("* Public inputs:");
+        let mut public_inputs = serde_json::Map::new();
+        for (var, val) in module.pubs.iter().zip(backend.public_values(&proof_data)) {
+            let val = backend.value_of(val);
+            outcome.println(format!("{} = {}", var, val));
+            public_inputs.insert(var.to_string(), val.to_string().into());
+        }
+        outcome.detail("public_inputs", public_inputs);
+        info!("* Verifying proof validity...");
+        outcome.time("verify", || check(proof_data))?;
+        info!("* Zero-knowledge proof is valid");
+        outcome.detail("valid", true);
+        return Ok(outcome);
+    }
+    info!("* Verifying {} proofs...", proofs.len());
+    let verdicts = outcome.time("verify", || verify_all(proofs, |proof| check(read_proof(proof)?)));
+    report_verdicts(outcome, verdicts)
+}
+
+/* Check that the given public inputs of a proof on the given module, in the
+ * order its public variables are declared, take the given values. Every value
+ * that differs is reported at once. */
+fn check_public_inputs<B: Backend>(
+    backend: &B,
+    module: &Module,
+    values: &[B::Value],
+    expected: &BTreeMap<String, BigInt>,
+) -> Result<(), CommandError> {
+    let mut mismatches: Vec<_> = module.pubs.iter().zip(values)
+        .map(|(var, val)| (input_name(var), val))
+        .filter(|(name, val)| backend.make_value(&expected[name]) != **val)
+        .map(|(name, val)| format!("{} is {} rather than {}", name, backend.value_of(val), expected[&name]))
+        .collect();
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    mismatches.sort();
+    Err(CommandError::Failure(format!("proof is of other public inputs: {}", mismatches.join("; "))))
+}
+
+impl ClaimStdio for CompileArgs {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--source", self.sources.iter().chain(&self.source))
+            .writes("--output", [&self.output])
+            .writes("--emit-ir", &self.emit_ir)
+            .writes("--emit-interface", &self.emit_interface);
+    }
+}
+
+impl ClaimStdio for ProveArgs {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--circuit", [&self.circuit])
+            .reads("--inputs", &self.inputs)
+            .reads("--public-inputs", &self.public_inputs)
+            .reads("--private-inputs", &self.private_inputs)
+            .writes("--output", [&self.output]);
+    }
+}
+
+impl ClaimStdio for VerifyArgs {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--circuit", [&self.circuit])
+            .reads("--proof", &self.proofs)
+            .reads("--public-inputs", &self.public_inputs);
+    }
+}
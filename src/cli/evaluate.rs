@@ -0,0 +1,343 @@
+use crate::cli::shared::{check_constraints, compile_uncached, parse_define};
+use crate::sources::Prelude;
+use crate::artifact::read_contents;
+use crate::ast::Module;
+use crate::export::{self, witness_document, ExportFormat, Field, ImportFormat};
+use crate::inputs::{resolve_inputs, supplied_inputs, InputsFormat, Interactivity};
+use crate::output::{check_output, write_output};
+use crate::pretty::print_module;
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::stdio::{ClaimStdio, PathOrStdio, StdioClaims};
+use crate::transform::{derive_available_witnesses, derive_witnesses, input_name, ordered_module_variables, CompileOptions, FieldOps, DEFAULT_UNROLL_LIMIT};
+use log::info;
+use num_bigint::BigInt;
+
+use clap::Args;
+
+#[derive(Args)]
+pub struct Interpret {
+    /// Paths to source files to be evaluated, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be evaluated after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Field over which the program is evaluated
+    #[arg(long, value_enum, default_value_t = Field::Bls12_381)]
+    field: Field,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Path to the inputs file, or - for standard input
+    #[arg(short, long)]
+    inputs: Option<PathOrStdio>,
+    /// Format of the inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Read inputs from VAMPIR_INPUT_<name> environment variables, with any
+    /// character of a name that cannot appear in one written as _, overriding
+    /// the inputs file
+    #[arg(long)]
+    env_inputs: bool,
+    /// Set an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    overrides: Vec<(String, BigInt)>,
+    /// Fail instead of prompting for missing inputs, as when CI or
+    /// VAMPIR_NONINTERACTIVE is set
+    #[arg(long)]
+    non_interactive: bool,
+    /// Print the values of all variables rather than only the public ones
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Args)]
+pub struct WitnessExport {
+    /// Paths to source files to be evaluated, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be evaluated after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Field over which the witness is computed
+    #[arg(long, value_enum, default_value_t = Field::Bls12_381)]
+    field: Field,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Path to the inputs file, or - for standard input
+    #[arg(short, long)]
+    inputs: Option<PathOrStdio>,
+    /// Format of the inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Read inputs from VAMPIR_INPUT_<name> environment variables, with any
+    /// character of a name that cannot appear in one written as _, overriding
+    /// the inputs file
+    #[arg(long)]
+    env_inputs: bool,
+    /// Set an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    overrides: Vec<(String, BigInt)>,
+    /// Path to which the witness is written, or - for standard output
+    #[arg(short, long)]
+    output: PathOrStdio,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+pub struct Export {
+    /// Paths to source files to be exported, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be exported after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// What to export
+    #[arg(long, value_enum)]
+    format: ExportFormat,
+    /// Field over which the constraints are written
+    #[arg(long, value_enum, default_value_t = Field::Bn254)]
+    field: Field,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Path to the inputs file, or - for standard input, when exporting a
+    /// witness or a zkinterface witness
+    #[arg(short, long)]
+    inputs: Option<PathOrStdio>,
+    /// Format of the inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    inputs_format: Option<InputsFormat>,
+    /// Read inputs from VAMPIR_INPUT_<name> environment variables, with any
+    /// character of a name that cannot appear in one written as _, overriding
+    /// the inputs file
+    #[arg(long)]
+    env_inputs: bool,
+    /// Set an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    overrides: Vec<(String, BigInt)>,
+    /// Fail instead of prompting for missing inputs, as when CI or
+    /// VAMPIR_NONINTERACTIVE is set
+    #[arg(long)]
+    non_interactive: bool,
+    /// Path to which the export is written, or - for standard output
+    #[arg(short, long)]
+    output: PathOrStdio,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+pub struct Import {
+    /// Path to the file to be imported, or - for standard input
+    circuit: PathOrStdio,
+    /// Format of the file to be imported
+    #[arg(long, value_enum)]
+    format: ImportFormat,
+    /// Field that the constraints must be over, that of the backend by which
+    /// the imported program is to be proved
+    #[arg(long, value_enum, default_value_t = Field::Bn254)]
+    field: Field,
+    /// Path to which the source file is written, or - for standard output
+    #[arg(short, long)]
+    output: PathOrStdio,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+}
+
+/* Compile the given source files into three-address form so that they can be
+ * evaluated over the field with the given operations. */
+fn compile_for_evaluation(
+    prelude: &Prelude,
+    sources: &[PathOrStdio],
+    unroll_limit: usize,
+    field_ops: &dyn FieldOps,
+) -> Result<Module, CommandError> {
+    info!("* Compiling constraints...");
+    let options = CompileOptions::default().unroll_limit(unroll_limit);
+    compile_uncached(prelude, sources, field_ops, &options)
+}
+
+/* Implements the subcommand that evaluates source files on given inputs,
+ * deriving every defined variable and checking every constraint. Exits with
+ * a failure status if any constraint is violated. */
+pub fn interpret_cmd(Interpret { sources, source, prelude, field, unroll_limit, inputs, format, env_inputs, overrides, non_interactive, all }: &Interpret) -> CommandResult {
+    let ops = field.ops();
+    let field_ops = ops.as_ref();
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let module_3ac = compile_for_evaluation(prelude, &sources, *unroll_limit, field_ops)?;
+    // Look for inputs alongside the first source by default
+    let expected_path_to_inputs = sources[0].with_extension("inputs");
+    let mut assigns = resolve_inputs(
+        &module_3ac,
+        inputs.as_deref(),
+        &expected_path_to_inputs,
+        *format,
+        *env_inputs,
+        overrides,
+        Interactivity::choose(*non_interactive),
+    )?;
+    info!("* Evaluating definitions...");
+    derive_witnesses(&module_3ac, &mut assigns, field_ops)
+        .map_err(|err| CommandError::Input(err.to_string()))?;
+    let mut outcome = Outcome::default();
+    let shown = if *all {
+        outcome.println("* Variables:");
+        ordered_module_variables(&module_3ac)
+    } else {
+        outcome.println("* Public variables:");
+        module_3ac.pubs.clone()
+    };
+    let mut values = serde_json::Map::new();
+    for var in shown {
+        outcome.println(format!("** {} = {}", input_name(&var), assigns[&var.id]));
+        values.insert(input_name(&var), assigns[&var.id].to_string().into());
+    }
+    outcome.detail("values", values);
+    info!("* Checking constraints...");
+    check_constraints(&module_3ac, &assigns, field_ops)?;
+    outcome.println(format!("* All {} constraint(s) are satisfied", module_3ac.exprs.len()));
+    Ok(outcome)
+}
+
+/* Implements the subcommand that writes the value of every variable of source
+ * files, as derived from given inputs, to a JSON document. Variables whose
+ * values are not supplied and cannot be derived are listed separately. */
+pub fn witness_cmd(WitnessExport { sources, source, prelude, field, unroll_limit, inputs, format, env_inputs, overrides, output, force }: &WitnessExport) -> CommandResult {
+    check_output(output, *force).map_err(CommandError::Input)?;
+    let ops = field.ops();
+    let field_ops = ops.as_ref();
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let module_3ac = compile_for_evaluation(prelude, &sources, *unroll_limit, field_ops)?;
+    let mut assigns = supplied_inputs(&module_3ac, inputs.as_deref(), *format, *env_inputs, overrides)?;
+    info!("* Evaluating definitions...");
+    derive_available_witnesses(&module_3ac, &mut assigns, field_ops);
+    let witness = witness_document(&module_3ac, &assigns, *field);
+    let unknown = witness["unknown"].as_array().map_or(0, Vec::len);
+    if unknown > 0 {
+        info!("* Values of {} variable(s) could not be derived", unknown);
+    }
+    info!("* Writing witness to {}...", output.to_string_lossy());
+    let text = serde_json::to_string_pretty(&witness)
+        .map_err(|err| CommandError::internal("cannot serialize witness", err))?;
+    write_output(output, text + "\n", *force).map_err(CommandError::Input)?;
+    info!("* Witness export success!");
+    let mut outcome = Outcome::default();
+    outcome.artifact("witness", output);
+    Ok(outcome)
+}
+
+/* Implements the subcommand that writes the constraints of source files, or
+ * the values of their wires derived from given inputs, in the given format for
+ * other tools. Inputs are only resolved when the format carries values, and
+ * are checked against the constraints before anything is written. */
+pub fn export_cmd(Export { sources, source, prelude, format, field, unroll_limit, inputs, inputs_format, env_inputs, overrides, non_interactive, output, force }: &Export) -> CommandResult {
+    check_output(output, *force).map_err(CommandError::Input)?;
+    let ops = field.ops();
+    let field_ops = ops.as_ref();
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let module_3ac = compile_for_evaluation(prelude, &sources, *unroll_limit, field_ops)?;
+    // The inputs are looked for alongside the first source by default, and
+    // zkinterface messages only carry values when inputs are given
+    let wants_values = match format {
+        ExportFormat::Wtns => true,
+        ExportFormat::Zkinterface => inputs.is_some() || *env_inputs || !overrides.is_empty(),
+        ExportFormat::R1cs | ExportFormat::ConstraintsJson => false,
+    };
+    let assigns = if wants_values {
+        let expected_path_to_inputs = sources[0].with_extension("inputs");
+        let mut assigns = resolve_inputs(
+            &module_3ac,
+            inputs.as_deref(),
+            &expected_path_to_inputs,
+            *inputs_format,
+            *env_inputs,
+            overrides,
+            Interactivity::choose(*non_interactive),
+        )?;
+        info!("* Evaluating definitions...");
+        derive_witnesses(&module_3ac, &mut assigns, field_ops)
+            .map_err(|err| CommandError::Input(err.to_string()))?;
+        info!("* Checking constraints...");
+        check_constraints(&module_3ac, &assigns, field_ops)?;
+        Some(assigns)
+    } else {
+        None
+    };
+    let exported = export::export(&module_3ac, *format, *field, assigns.as_ref())?;
+    info!("* Writing export to {}...", output.to_string_lossy());
+    write_output(output, exported.contents, *force).map_err(CommandError::Input)?;
+    info!("* Export success!");
+    let mut outcome = Outcome::default();
+    outcome.println(exported.summary);
+    outcome.artifact(format.role(), output);
+    outcome.detail("field", field.name());
+    Ok(outcome)
+}
+
+/* Implements the subcommand that reads a rank-1 constraint system written by
+ * another tool back as a source file, with wire k as the variable w_k. */
+pub fn import_cmd(Import { circuit, format, field, output, force }: &Import) -> CommandResult {
+    check_output(output, *force).map_err(CommandError::Input)?;
+    let path = circuit.to_string_lossy();
+    let bytes = read_contents(circuit).map_err(|err| CommandError::input(&path, err))?;
+    let r1cs = export::import(&bytes, *format, *field).map_err(|err| CommandError::input(&path, err))?;
+    let source = print_module(&r1cs.to_module(), false);
+    write_output(output, source, *force).map_err(CommandError::Input)?;
+    let mut outcome = Outcome::default();
+    outcome.println(format!(
+        "* Imported {} constraint(s) over {} wire(s), {} of them public",
+        r1cs.constraints.len(), r1cs.wire_count(), r1cs.public_inputs,
+    ));
+    info!("* Import success!");
+    outcome.artifact("source", output);
+    outcome.detail("field", field.name());
+    Ok(outcome)
+}
+
+impl ClaimStdio for Interpret {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--source", self.sources.iter().chain(&self.source))
+            .reads("--inputs", &self.inputs);
+    }
+}
+
+impl ClaimStdio for WitnessExport {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--source", self.sources.iter().chain(&self.source))
+            .reads("--inputs", &self.inputs)
+            .writes("--output", [&self.output]);
+    }
+}
+
+impl ClaimStdio for Export {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--source", self.sources.iter().chain(&self.source))
+            .reads("--inputs", &self.inputs)
+            .writes("--output", [&self.output]);
+    }
+}
+
+impl ClaimStdio for Import {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("CIRCUIT", [&self.circuit])
+            .writes("--output", [&self.output]);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::cli::shared::{open_key_cache, print_circuit_summary};
+use crate::artifact::{check_header, content_digest, read_contents, read_header, ArtifactKind, Digests};
+use crate::cli::driver::{check_prove_outputs, compile_with, prove_with, verify_with, Backend, CompileArgs, ProveArgs, VerifyArgs};
+use crate::stdio::{ClaimStdio, StdioClaims};
+use crate::progress::{in_phase, terminal_progress, Phase};
+use log::info;
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::batch::collect_proofs;
+use num_bigint::{BigInt, BigUint, ToBigInt};
+use crate::ast::{Module, VariableId};
+use crate::transform::{CostModel, FieldOps};
+use crate::plonk::field::{make_constant, PlonkCostModel, PrimeFieldOps};
+use crate::groth16::{Groth16CircuitData, Groth16Proof};
+use crate::key_cache::{KeyCache, PK, VK};
+use vamp_ir::error::Error;
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::PairingEngine;
+use ark_ff::{PrimeField, SquareRootField};
+use ark_serialize::SerializationError;
+use bincode::error::EncodeError;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{OsRng, SeedableRng};
+
+use clap::{Args, Subcommand, ValueEnum};
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+#[derive(Subcommand)]
+pub enum Groth16Commands {
+    /// Compiles a given source file to a circuit, running its setup
+    Compile(Groth16Compile),
+    /// Proves knowledge of witnesses satisfying a circuit
+    Prove(Groth16Prove),
+    /// Verifies that a proof is a correct one
+    Verify(VerifyArgs),
+}
+
+/* The curves whose pairings Groth16 circuits can be proved with. */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+pub enum Groth16Curve {
+    /// BN254, whose pairings Ethereum checks cheaply
+    Bn254,
+    /// BLS12-381
+    #[value(name = "bls12-381")]
+    Bls12_381,
+}
+
+impl Groth16Curve {
+    fn circuit_kind(&self) -> ArtifactKind {
+        match self {
+            Groth16Curve::Bn254 => ArtifactKind::Groth16Bn254Circuit,
+            Groth16Curve::Bls12_381 => ArtifactKind::Groth16Bls12_381Circuit,
+        }
+    }
+
+    fn proof_kind(&self) -> ArtifactKind {
+        match self {
+            Groth16Curve::Bn254 => ArtifactKind::Groth16Bn254Proof,
+            Groth16Curve::Bls12_381 => ArtifactKind::Groth16Bls12_381Proof,
+        }
+    }
+
+    /* The curve of circuits of the given kind, if they are Groth16 ones. */
+    fn of_circuit(kind: ArtifactKind) -> Option<Self> {
+        [Groth16Curve::Bn254, Groth16Curve::Bls12_381].into_iter()
+            .find(|curve| curve.circuit_kind() == kind)
+    }
+}
+
+#[derive(Args)]
+pub struct Groth16Compile {
+    #[command(flatten)]
+    common: CompileArgs,
+    /// Curve whose pairings the circuit is proved with
+    #[arg(long, value_enum, default_value_t = Groth16Curve::Bn254)]
+    curve: Groth16Curve,
+    /// Directory in which keys are cached, by default vamp-ir under
+    /// $XDG_CACHE_HOME or ~/.cache
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+    /// Draw the secrets of the setup from a generator seeded with this
+    /// number, so that circuits are reproducible; anyone who knows the seed
+    /// can forge proofs of the circuit
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct Groth16Prove {
+    #[command(flatten)]
+    common: ProveArgs,
+    /// Draw the randomness of the proof from a generator seeded with this
+    /// number, so that it is reproducible; such proofs do not hide the
+    /// witnesses from anyone who knows the seed
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+/* The calls of Groth16 over the curve of E that the shared compile, prove,
+ * and verify subcommands make. */
+struct Groth16<E> {
+    curve: Groth16Curve,
+    // Seed of the randomness of setups and proofs that are to be reproducible
+    seed: Option<u64>,
+    // Cache in which the keys of compiled circuits are recorded, if any
+    cache: Option<KeyCache>,
+    engine: PhantomData<E>,
+}
+
+impl<E> Groth16<E> {
+    fn new(curve: Groth16Curve, seed: Option<u64>, cache: Option<KeyCache>) -> Self {
+        Self { curve, seed, cache, engine: PhantomData }
+    }
+}
+
+impl<E> Backend for Groth16<E>
+where E: PairingEngine, E::Fr: SquareRootField {
+    type Circuit = Groth16CircuitData<E>;
+    type Proof = Groth16Proof<E>;
+    type Value = E::Fr;
+
+    fn name(&self) -> String {
+        format!("groth16-{}", self.curve.circuit_kind().curve())
+    }
+
+    fn circuit_kind(&self) -> ArtifactKind {
+        self.curve.circuit_kind()
+    }
+
+    fn proof_kind(&self) -> ArtifactKind {
+        self.curve.proof_kind()
+    }
+
+    fn field_ops(&self) -> Box<dyn FieldOps> {
+        Box::new(PrimeFieldOps::<E::Fr>::default())
+    }
+
+    // Every constraint costs one rank-1 constraint, much as every gate of
+    // PLONK costs one row
+    fn cost_model(&self) -> Box<dyn CostModel> {
+        Box::new(PlonkCostModel)
+    }
+
+    fn build(&self, module: Module, opt_level: u8, defines: Vec<(String, String)>, outcome: &mut Outcome) -> Result<Groth16CircuitData<E>, CommandError> {
+        info!("* Running circuit-specific setup...");
+        let progress = terminal_progress();
+        outcome.time("keygen", || in_phase(progress.as_ref(), Phase::KeygenPk, || match self.seed {
+            Some(seed) => Groth16CircuitData::<E>::setup(module, opt_level, defines, ChaCha20Rng::seed_from_u64(seed)),
+            None => Groth16CircuitData::<E>::setup(module, opt_level, defines, OsRng),
+        })).map_err(|err| CommandError::input("unable to compile circuit", err))
+    }
+
+    fn written(&self, circuit: &Groth16CircuitData<E>, digests: &Digests) {
+        if let Some(cache) = &self.cache {
+            cache.store_key(&self.name(), &digests.secure, PK, "proving key", &circuit.pk);
+            cache.store_key(&self.name(), &digests.secure, VK, "verifying key", &circuit.pk.vk);
+        }
+    }
+
+    fn write_circuit(&self, circuit: &Groth16CircuitData<E>, writer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        circuit.write(writer)
+    }
+
+    fn module<'a>(&self, circuit: &'a Groth16CircuitData<E>) -> &'a Module {
+        &circuit.module
+    }
+
+    fn opt_level(&self, circuit: &Groth16CircuitData<E>) -> u8 {
+        circuit.opt_level
+    }
+
+    fn defines<'a>(&self, circuit: &'a Groth16CircuitData<E>) -> &'a [(String, String)] {
+        &circuit.defines
+    }
+
+    fn prove(&self, circuit: &Groth16CircuitData<E>, assigns: HashMap<VariableId, BigInt>) -> Result<Groth16Proof<E>, Error> {
+        match self.seed {
+            Some(seed) => circuit.prove(assigns, ChaCha20Rng::seed_from_u64(seed)),
+            None => circuit.prove(assigns, OsRng),
+        }
+    }
+
+    fn verify(&self, circuit: &Groth16CircuitData<E>, proof: &Groth16Proof<E>) -> Result<(), Error> {
+        circuit.verify(proof)
+    }
+
+    fn write_proof(&self, proof: &Groth16Proof<E>, writer: &mut Vec<u8>) -> Result<(), SerializationError> {
+        proof.write(writer)
+    }
+
+    fn read_proof(&self, reader: &mut dyn Read) -> Result<Groth16Proof<E>, SerializationError> {
+        Groth16Proof::<E>::read(reader)
+    }
+
+    fn public_values<'a>(&self, proof: &'a Groth16Proof<E>) -> &'a [E::Fr] {
+        &proof.public_values
+    }
+
+    fn make_value(&self, value: &BigInt) -> E::Fr {
+        make_constant::<E::Fr>(value)
+    }
+
+    fn value_of(&self, value: &E::Fr) -> BigInt {
+        field_value(value)
+    }
+}
+
+/* The given field element as the integer it stands for. */
+fn field_value<F: PrimeField>(val: &F) -> BigInt {
+    Into::<BigUint>::into(*val).to_bigint().unwrap()
+}
+
+/* Read the Groth16 circuit at the given path, of whichever curve its header
+ * names, giving that curve along with the contents after the header and
+ * their digest. Circuits of this backend always have headers. */
+fn read_circuit_contents(path: &Path) -> Result<(Groth16Curve, Vec<u8>, String), CommandError> {
+    let name = path.to_string_lossy();
+    let contents = read_contents(path).map_err(|err| CommandError::input(&name, err))?;
+    let (header, reader) = read_header(contents.as_slice())
+        .map_err(|err| CommandError::input(&name, err))?;
+    // Nothing predating headers was a Groth16 circuit
+    let Some(kind) = header.as_ref().map(|header| header.kind) else {
+        return Err(CommandError::Input(format!("{}: not a {}", name, ArtifactKind::Groth16Bn254Circuit)));
+    };
+    let curve = Groth16Curve::of_circuit(kind).unwrap_or(Groth16Curve::Bn254);
+    check_header(&header, curve.circuit_kind()).map_err(|err| CommandError::input(&name, err))?;
+    let body = reader.get_ref().1.to_vec();
+    let digest = content_digest(&body);
+    Ok((curve, body, digest))
+}
+
+/* Decode the contents of a Groth16 circuit over the given curve. */
+fn decode_circuit<E>(path: &Path, curve: Groth16Curve, body: &[u8]) -> Result<Groth16CircuitData<E>, CommandError>
+where E: PairingEngine, E::Fr: SquareRootField {
+    Groth16CircuitData::<E>::read(body).map_err(|err| CommandError::Input(format!(
+        "{}: corrupted {}: {:?}",
+        path.to_string_lossy(), curve.circuit_kind(), err,
+    )))
+}
+
+/* Implements the subcommand that compiles a vamp-ir file into a Groth16
+ * circuit over the chosen curve, running its setup, whose keys are written
+ * into the circuit. */
+fn compile_groth16_cmd(Groth16Compile { common, curve, cache_dir, seed }: &Groth16Compile) -> CommandResult {
+    let cache = open_key_cache(common.no_cache, cache_dir);
+    match curve {
+        Groth16Curve::Bn254 => compile_with(&Groth16::<Bn254>::new(*curve, *seed, cache), common),
+        Groth16Curve::Bls12_381 => compile_with(&Groth16::<Bls12_381>::new(*curve, *seed, cache), common),
+    }
+}
+
+/* Implements the subcommand that creates a proof from interactively entered
+ * inputs, with the curve that the circuit names. */
+fn prove_groth16_cmd(Groth16Prove { common, seed }: &Groth16Prove) -> CommandResult {
+    check_prove_outputs(common)?;
+    info!("* Reading arithmetic circuit...");
+    let (curve, body, digest) = read_circuit_contents(&common.circuit)?;
+    match curve {
+        Groth16Curve::Bn254 => {
+            let circuit = decode_circuit::<Bn254>(&common.circuit, curve, &body)?;
+            prove_with(&Groth16::<Bn254>::new(curve, *seed, None), common, circuit, digest)
+        },
+        Groth16Curve::Bls12_381 => {
+            let circuit = decode_circuit::<Bls12_381>(&common.circuit, curve, &body)?;
+            prove_with(&Groth16::<Bls12_381>::new(curve, *seed, None), common, circuit, digest)
+        },
+    }
+}
+
+/* Implements the subcommand that verifies that proofs are correct, with the
+ * curve that the circuit names. */
+fn verify_groth16_cmd(args: &VerifyArgs) -> CommandResult {
+    let proofs = collect_proofs(&args.proofs, args.proof_dir.as_deref())?;
+    info!("* Reading arithmetic circuit...");
+    let (curve, body, digest) = read_circuit_contents(&args.circuit)?;
+    match curve {
+        Groth16Curve::Bn254 => {
+            let circuit = decode_circuit::<Bn254>(&args.circuit, curve, &body)?;
+            verify_with(&Groth16::<Bn254>::new(curve, None, None), args, &proofs, circuit, digest)
+        },
+        Groth16Curve::Bls12_381 => {
+            let circuit = decode_circuit::<Bls12_381>(&args.circuit, curve, &body)?;
+            verify_with(&Groth16::<Bls12_381>::new(curve, None, None), args, &proofs, circuit, digest)
+        },
+    }
+}
+
+/* Print the metadata of the Groth16 circuit over the curve of E read from
+ * the given reader. */
+pub fn inspect_circuit<E>(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String>
+where E: PairingEngine, E::Fr: SquareRootField {
+    let Groth16CircuitData::<E> { module, pk, opt_level, defines } =
+        Groth16CircuitData::read(reader).map_err(|err| format!("{:?}", err))?;
+    print_circuit_summary(&module, &defines, outcome);
+    outcome.println(format!("** Optimization level: {}", opt_level));
+    outcome.detail("opt_level", opt_level);
+    outcome.println(format!("** Proving key size: {} bytes", ark_serialize::CanonicalSerialize::serialized_size(&pk)));
+    outcome.detail("proving_key_bytes", ark_serialize::CanonicalSerialize::serialized_size(&pk));
+    Ok(())
+}
+
+/* Print the metadata of the Groth16 proof over the curve of E read from the
+ * given reader. */
+pub fn inspect_proof<E>(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String>
+where E: PairingEngine {
+    let proof = Groth16Proof::<E>::read(reader).map_err(|err| format!("{:?}", err))?;
+    let size = ark_serialize::CanonicalSerialize::serialized_size(&proof.proof);
+    outcome.println(format!("** Proof size: {} bytes", size));
+    outcome.detail("proof_bytes", size);
+    Ok(())
+}
+
+impl ClaimStdio for Groth16Commands {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        match self {
+            Groth16Commands::Compile(args) => args.common.claim_stdio(claims),
+            Groth16Commands::Prove(args) => args.common.claim_stdio(claims),
+            Groth16Commands::Verify(args) => args.claim_stdio(claims),
+        }
+    }
+}
+
+pub fn groth16(groth16_commands: &Groth16Commands) -> CommandResult {
+    match groth16_commands {
+        Groth16Commands::Compile(args) => compile_groth16_cmd(args),
+        Groth16Commands::Prove(args) => prove_groth16_cmd(args),
+        Groth16Commands::Verify(args) => verify_groth16_cmd(args),
+    }
+}
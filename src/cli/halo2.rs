@@ -0,0 +1,659 @@
+use crate::cli::shared::{check_metadata_output, compile_and_report, dry_run_cache_key, parse_define, precheck_inputs, print_circuit_summary, print_defines, public_values, record_defines, record_stats, show_proof_metadata, write_interface, write_ir, write_proof, write_proof_metadata};
+use crate::sources::{cache_key, Prelude};
+use crate::artifact::{read_artifact, read_digested_artifact, write_artifact, ArtifactKind, Digests};
+use crate::dry_run::DryRun;
+use crate::repeat::{proof_targets, report_proving_times};
+use crate::container::ContainerFormat;
+use crate::encoding::Encoding;
+use crate::metadata::metadata_path;
+use crate::output::check_output;
+use crate::stdio::{is_stdout, ClaimStdio, PathOrStdio, StdioClaims};
+use crate::progress::{in_phase, terminal_progress, NoProgress, Phase};
+use crate::diagnostics::DiagnosticsFormat;
+use log::info;
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::batch::{collect_proofs, report_verdicts, verify_all};
+use crate::inputs::{describe_inputs, read_expected_public, resolve_file_inputs, resolve_inputs, split_input_files, InputsFormat, Interactivity};
+use num_bigint::BigInt;
+use crate::ast::{Module, VariableId};
+use crate::bench::BackendBench;
+use crate::transform::{input_name, CompileOptions, DEFAULT_INLINE_LIMIT, DEFAULT_UNROLL_LIMIT, MAX_OPT_LEVEL};
+use crate::halo2::synth::{Halo2Module, Halo2CostModel, PrimeFieldOps, verifier, prover, keygen, make_constant};
+use crate::halo2::circuit::{decode_public_values, encode_public_values, field_value, HaloCircuitData, ProofDataHalo2};
+use crate::halo2::verify::VerifierData;
+
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::keygen_vk;
+
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+
+use clap::{Args, Subcommand};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+#[derive(Subcommand)]
+pub enum Halo2Commands {
+    /// Compiles a given source file to a circuit
+    Compile(Halo2Compile),
+    /// Proves knowledge of witnesses satisfying a circuit
+    Prove(Halo2Prove),
+    /// Verifies that a proof is a correct one
+    Verify(Halo2Verify),
+}
+
+#[derive(Args)]
+pub struct Halo2Compile {
+    /// Paths to source files to be compiled, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be compiled after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Path to which circuit is written, or - for standard output
+    #[arg(short, long)]
+    output: PathOrStdio,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Warn when local bindings shadow earlier bindings
+    #[arg(long)]
+    warn_shadowing: bool,
+    /// Treat compilation warnings as errors
+    #[arg(long)]
+    deny_warnings: bool,
+    /// Optimization level: 0 runs the required passes only, 1 adds cheap
+    /// rewrites, and 2 also shares gates
+    #[arg(short = 'O', long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=MAX_OPT_LEVEL as i64))]
+    opt_level: u8,
+    /// Largest single-use definition, in expression nodes, inlined from -O1
+    #[arg(long, default_value_t = DEFAULT_INLINE_LIMIT)]
+    inline_limit: usize,
+    /// Fix a public variable to a constant at compile time, given as name=value
+    #[arg(long = "define", value_name = "NAME=VALUE", value_parser = parse_define)]
+    defines: Vec<(String, BigInt)>,
+    /// Neither reuse nor record compilations in the .vampir-cache directory
+    #[arg(long)]
+    no_cache: bool,
+    /// Path to which the compiled intermediate representation is written, or -
+    /// for standard output
+    #[arg(long)]
+    emit_ir: Option<PathOrStdio>,
+    /// Path to which a JSON description of the inputs of the circuit is
+    /// written, or - for standard output
+    #[arg(long)]
+    emit_interface: Option<PathOrStdio>,
+    /// Path to which a verifier of the circuit is written, holding only its
+    /// public parameters and layout, or - for standard output
+    #[arg(long)]
+    emit_verifier: Option<PathOrStdio>,
+    /// Print a summary of the compiled circuit, which is the default
+    #[arg(long, overrides_with = "no_stats")]
+    stats: bool,
+    /// Do not print a summary of the compiled circuit
+    #[arg(long, overrides_with = "stats")]
+    no_stats: bool,
+    /// Format in which warnings and errors are reported
+    #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+    diagnostics_format: DiagnosticsFormat,
+    /// Compile and report the padded size of the circuit, the estimated sizes
+    /// of what it would make, and the files it would write, without making
+    /// parameters or keys or writing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct Halo2Prove {
+    /// Path to circuit on which to construct proof
+    #[arg(short, long)]
+    circuit: PathOrStdio,
+    /// Path to which the proof is written, or - for standard output
+    #[arg(short, long, required_unless_present = "output_dir", conflicts_with = "output_dir")]
+    output: Option<PathOrStdio>,
+    /// Encoding in which the proof is written
+    #[arg(long, value_enum, default_value_t = Encoding::Binary)]
+    encoding: Encoding,
+    /// Wrap the proof, along with its public inputs and metadata, in a
+    /// self-describing container
+    #[arg(long, value_enum)]
+    container: Option<ContainerFormat>,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+    /// Also write the public inputs, circuit digest, proving time, and
+    /// vamp-ir version to a PROOF.meta.json file beside the proof
+    #[arg(long)]
+    metadata: bool,
+    /// Check the inputs against the circuit and report the estimated size of
+    /// the proof and the files that would be written, without proving
+    #[arg(long)]
+    dry_run: bool,
+    /// Make this many proofs of the same inputs with the same proving key
+    /// and report the least, median, and greatest times taken; only the last
+    /// is written unless --output-dir is given
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    repeat: u64,
+    /// Directory into which every proof is written, as proof-1, proof-2, and
+    /// so on, in place of --output
+    #[arg(long)]
+    output_dir: Option<PathBuf>,
+    /// Draw the randomness of proofs from a generator seeded with this number,
+    /// so that they are reproducible and repeated proofs must be identical;
+    /// such proofs do not hide the witnesses from anyone who knows the seed
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Path to prover's input file, or - for standard input
+    #[arg(short, long)]
+    inputs: Option<PathOrStdio>,
+    /// Path to a file of the values of public variables, or - for standard
+    /// input, in place of --inputs
+    #[arg(long, conflicts_with = "inputs")]
+    public_inputs: Option<PathOrStdio>,
+    /// Path to a file of the values of inputs that are not public, or - for
+    /// standard input, in place of --inputs
+    #[arg(long, conflicts_with = "inputs")]
+    private_inputs: Option<PathOrStdio>,
+    /// Format of the input files, detected from their extensions by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Read inputs from VAMPIR_INPUT_<name> environment variables, with any
+    /// character of a name that cannot appear in one written as _, overriding
+    /// the inputs file
+    #[arg(long)]
+    env_inputs: bool,
+    /// Set an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    overrides: Vec<(String, BigInt)>,
+    /// Fail instead of prompting for missing inputs, as when CI or
+    /// VAMPIR_NONINTERACTIVE is set
+    #[arg(long)]
+    non_interactive: bool,
+}
+
+
+
+#[derive(Args)]
+pub struct Halo2Verify {
+    /// Path to circuit on which to construct proof
+    #[arg(short, long)]
+    circuit: PathOrStdio,
+    /// Path to a proof to be verified, or - for standard input, in any
+    /// encoding; give several times to verify several proofs
+    #[arg(short, long = "proof", required_unless_present = "proof_dir")]
+    proofs: Vec<PathOrStdio>,
+    /// Directory whose every file is a proof to be verified
+    #[arg(long)]
+    proof_dir: Option<PathBuf>,
+    /// Path to a file of the values that the public variables must take, or -
+    /// for standard input; proofs of other values are rejected
+    #[arg(long)]
+    public_inputs: Option<PathOrStdio>,
+    /// ABI-encoded calldata, in hexadecimal, of the values that the public
+    /// variables must take, in place of --public-inputs
+    #[arg(long, conflicts_with = "public_inputs")]
+    public_calldata: Option<String>,
+    /// Format of the public inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+}
+
+/* Implements the subcommand that compiles a vamp-ir file into a Halo2 circuit.
+ */
+ fn compile_halo2_cmd(Halo2Compile { sources, source, prelude, output, force, unroll_limit, warn_shadowing, deny_warnings, opt_level, inline_limit, defines, no_cache, emit_ir, emit_interface, emit_verifier, stats: print_timings, no_stats, diagnostics_format, dry_run }: &Halo2Compile) -> CommandResult {
+    check_output(output, *force).map_err(CommandError::Input)?;
+    if let Some(emit_ir) = emit_ir {
+        check_output(emit_ir, *force).map_err(CommandError::Input)?;
+    }
+    if let Some(emit_interface) = emit_interface {
+        check_output(emit_interface, *force).map_err(CommandError::Input)?;
+    }
+    if let Some(emit_verifier) = emit_verifier {
+        check_output(emit_verifier, *force).map_err(CommandError::Input)?;
+    }
+    info!("* Compiling constraints...");
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let options = format!(
+        "unroll-limit={} warn-shadowing={} deny-warnings={} opt-level={} inline-limit={} defines={:?}",
+        unroll_limit, warn_shadowing, deny_warnings, opt_level, inline_limit, defines,
+    );
+    let mut key = (!*no_cache).then(|| cache_key("halo2", &options, prelude, &sources)).transpose()?;
+    let mut plan = dry_run.then(DryRun::default);
+    if let Some(plan) = &mut plan {
+        key = dry_run_cache_key(key, plan);
+    }
+    let options = CompileOptions::default()
+        .unroll_limit(*unroll_limit)
+        .deny_warnings(*deny_warnings)
+        .opt_level(*opt_level)
+        .inline_limit(*inline_limit)
+        .cost_model(Box::new(Halo2CostModel));
+    let options = defines.iter().cloned()
+        .fold(options, |options, (name, value)| options.define(name, value));
+    let field_ops = PrimeFieldOps::<Fp>::default();
+    let mut outcome = Outcome::default();
+    let (module_3ac, mut stats) = outcome.time("compile", || compile_and_report(key, prelude, &sources, *warn_shadowing, &field_ops, &options, *diagnostics_format))?;
+    if let Some(emit_ir) = emit_ir {
+        match &mut plan {
+            Some(plan) => plan.output("ir", emit_ir),
+            None => {
+                write_ir(&module_3ac, emit_ir, *force)?;
+                outcome.artifact("ir", emit_ir);
+            },
+        }
+    }
+    if let Some(emit_interface) = emit_interface {
+        match &mut plan {
+            Some(plan) => plan.output("interface", emit_interface),
+            None => {
+                write_interface(&module_3ac, emit_interface, *force)?;
+                outcome.artifact("interface", emit_interface);
+            },
+        }
+    }
+
+    info!("* Synthesizing arithmetic circuit...");
+    let circuit = Halo2Module::<Fp>::new(module_3ac.clone());
+    if !*print_timings {
+        stats.pass_timings.clear();
+    }
+    stats.k = Some(circuit.k);
+    if let Some(mut plan) = plan {
+        let (params_size, proof_size) = estimated_sizes(circuit.k);
+        plan.k = Some(circuit.k);
+        // Circuits are dominated by the parameters they carry
+        plan.size("circuit", Some(params_size));
+        plan.size("proof", Some(proof_size));
+        plan.size("proving key", None);
+        plan.size("verifying key", None);
+        plan.output("circuit", output);
+        if let Some(emit_verifier) = emit_verifier {
+            plan.output("verifier", emit_verifier);
+        }
+        record_stats(&mut outcome, &stats, !*no_stats);
+        plan.report(&mut outcome);
+        return Ok(outcome);
+    }
+    let params: Params<EqAffine> = Params::new(circuit.k);
+    let circuit_data = HaloCircuitData { params, circuit, opt_level: *opt_level, defines: record_defines(defines) };
+    let digest = write_artifact(output, ArtifactKind::Halo2Circuit, *force, Encoding::Binary, |writer| circuit_data.write(writer))
+        .map_err(CommandError::Input)?;
+    outcome.artifact("circuit", output);
+    outcome.circuit_hash = Some(digest.content);
+    if let Some(emit_verifier) = emit_verifier {
+        let verifier_data = VerifierData {
+            params: circuit_data.params.clone(),
+            layout: circuit_data.circuit.layout().map_err(CommandError::Input)?,
+        };
+        write_artifact(emit_verifier, ArtifactKind::Halo2Verifier, *force, Encoding::Binary, |writer| verifier_data.write(writer))
+            .map_err(CommandError::Input)?;
+        outcome.artifact("verifier", emit_verifier);
+    }
+
+    info!("* Constraint compilation success!");
+    // Standard output only carries the circuit or verifier when either is
+    // written there
+    let to_stdout = is_stdout(output) || emit_verifier.iter().any(|path| is_stdout(path));
+    record_stats(&mut outcome, &stats, !*no_stats && !to_stdout);
+    Ok(outcome)
+}
+
+
+
+/* Implements the subcommand that creates a proof from interactively entered
+ * inputs, or several proofs with the same key when asked to repeat. */
+fn prove_halo2_cmd(Halo2Prove { circuit, output, encoding, container, force, metadata, dry_run, repeat, output_dir, seed, inputs, public_inputs, private_inputs, format, env_inputs, overrides, non_interactive }: &Halo2Prove) -> CommandResult {
+    let targets = proof_targets(output.as_deref(), output_dir.as_deref(), *repeat as usize);
+    for target in targets.iter().flatten() {
+        check_output(target, *force).map_err(CommandError::Input)?;
+        if *metadata {
+            check_metadata_output(target, *force)?;
+        }
+    }
+    info!("* Reading arithmetic circuit...");
+    let mut expected_path_to_inputs = circuit.to_path_buf();
+        expected_path_to_inputs.set_extension("inputs");    
+
+    // Circuits carry their public parameters, which dominate their size
+    let progress = terminal_progress();
+    let mut outcome = Outcome::default();
+    let (HaloCircuitData { params, mut circuit, opt_level, defines }, digest) =
+        in_phase(progress.as_ref(), Phase::LoadParams, || read_digested_artifact(circuit, ArtifactKind::Halo2Circuit, |reader| HaloCircuitData::read(reader)))
+            .map_err(|err| CommandError::input(&circuit.to_string_lossy(), err))?;
+    outcome.circuit_hash = Some(digest.content);
+    info!("** Circuit compiled at optimization level {}", opt_level);
+    print_defines(&defines);
+
+    // Gather program inputs from the command line, files, or the user
+    let split_files = split_input_files(public_inputs.as_deref(), private_inputs.as_deref());
+    let var_assignments_ints = if split_files.is_empty() {
+        resolve_inputs(
+            &circuit.module,
+            inputs.as_deref(),
+            &expected_path_to_inputs,
+            *format,
+            *env_inputs,
+            overrides,
+            Interactivity::choose(*non_interactive),
+        )?
+    } else {
+        resolve_file_inputs(&circuit.module, &split_files, *format, *env_inputs, overrides)?
+    };
+    let metadata_inputs = (*metadata || container.is_some()).then(|| public_values(&circuit.module, &var_assignments_ints, &PrimeFieldOps::<Fp>::default())).transpose()?;
+    if *dry_run {
+        precheck_inputs(&circuit.module, &var_assignments_ints, &PrimeFieldOps::<Fp>::default())?;
+    }
+
+    let mut var_assignments = HashMap::new();
+    for (k, v) in var_assignments_ints {
+        var_assignments.insert(k, make_constant(v));
+    }
+
+    // Populate variable definitions
+    circuit.populate_variables(var_assignments, progress.as_ref())
+        .map_err(|err| CommandError::Input(err.to_string()))?;
+    if *dry_run {
+        let mut plan = DryRun::default();
+        plan.k = Some(circuit.k);
+        plan.size("proof", Some(estimated_sizes(circuit.k).1));
+        for target in targets.iter().flatten() {
+            plan.output("proof", target);
+            if *metadata {
+                plan.output("metadata", &metadata_path(target));
+            }
+        }
+        plan.report(&mut outcome);
+        return Ok(outcome);
+    }
+
+    // Generating proving key, once the circuit is known to lay out
+    circuit.layout().map_err(CommandError::Input)?;
+    info!("* Generating proving key...");
+    let (pk, _vk) = outcome.time("keygen", || keygen(&circuit, &params, progress.as_ref()))
+        .map_err(|err| CommandError::internal("unable to generate keys", format!("{:?}", err)))?;
+
+    if let Some(dir) = output_dir {
+        fs::create_dir_all(dir).map_err(|err| CommandError::input(&dir.to_string_lossy(), err))?;
+    }
+
+    // Start proving witnesses, as many times as asked with the same key
+    info!("* Proving knowledge of witnesses...");
+    let public_values = circuit.public_values().map_err(|err| CommandError::Input(err.to_string()))?;
+    let public_inputs = encode_public_values(&public_values);
+    let mut times = vec![];
+    let mut first_proof = None;
+    for (index, target) in targets.iter().enumerate() {
+        let proof = outcome.time("prove", || prover(circuit.clone(), &params, &pk, *seed, progress.as_ref()))
+            .map_err(|err| CommandError::internal("unable to generate proof", format!("{:?}", err)))?;
+        times.extend(outcome.timing("prove"));
+        // Proofs made with the same seed must not differ
+        if let Some(seed) = seed {
+            if *first_proof.get_or_insert_with(|| proof.clone()) != proof {
+                return Err(CommandError::Internal(format!(
+                    "proof {} differs from proof 1 though both were made with seed {}",
+                    index + 1, seed,
+                )));
+            }
+        }
+        let Some(target) = target else { continue };
+        info!("* Serializing proof to storage...");
+        let proof_data = ProofDataHalo2 { proof, public_inputs: public_inputs.clone() };
+        write_proof(&outcome, target, ArtifactKind::Halo2Proof, *force, *encoding, *container, metadata_inputs.as_deref(), |writer| proof_data.serialize(writer))?;
+        outcome.artifact("proof", target);
+        if let Some(metadata_inputs) = metadata_inputs.as_ref().filter(|_| *metadata) {
+            write_proof_metadata(&mut outcome, ArtifactKind::Halo2Proof, target, metadata_inputs.clone(), *force)?;
+        }
+    }
+    report_proving_times(&mut outcome, &times);
+
+    info!("* Proof generation success!");
+    Ok(outcome)
+}
+
+
+
+/* Implements the subcommand that verifies that proofs are correct. The
+ * circuit is read and its verifying key generated once for all of them. */
+fn verify_halo2_cmd(Halo2Verify { circuit, proofs, proof_dir, public_inputs, public_calldata, format }: &Halo2Verify) -> CommandResult {
+    let proofs = collect_proofs(proofs, proof_dir.as_deref())?;
+    info!("* Reading arithmetic circuit...");
+    let mut outcome = Outcome::default();
+    let (HaloCircuitData { params, circuit, opt_level: _, defines }, digest) =
+        read_digested_artifact(circuit, ArtifactKind::Halo2Circuit, |reader| HaloCircuitData::read(reader))
+            .map_err(|err| CommandError::input(&circuit.to_string_lossy(), err))?;
+    outcome.circuit_hash = Some(digest.content);
+    print_defines(&defines);
+    let expected = read_expected_public(
+        &circuit.module, public_inputs.as_deref(), public_calldata.as_deref(), *format, &PrimeFieldOps::<Fp>::default(),
+    )?;
+
+    info!("* Generating verifying key...");
+    let vk = outcome.time("keygen", || in_phase(terminal_progress().as_ref(), Phase::KeygenVk, || keygen_vk(&params, &circuit)))
+        .map_err(|err| CommandError::internal("unable to generate verifying key", format!("{:?}", err)))?;
+
+    let read_proof = |proof: &Path| {
+        read_artifact(proof, ArtifactKind::Halo2Proof, |reader| ProofDataHalo2::deserialize(reader))
+            .and_then(|ProofDataHalo2 { proof, public_inputs }| {
+                let values = decode_public_values(&public_inputs)?;
+                if values.len() != circuit.module.pubs.len() {
+                    return Err(format!(
+                        "proof has {} public inputs, but the circuit has {} public variables",
+                        values.len(),
+                        circuit.module.pubs.len(),
+                    ));
+                }
+                Ok((proof, values))
+            })
+            .map_err(|err| CommandError::input(&proof.to_string_lossy(), err))
+    };
+    // The proof is checked against the values that it carries, which must
+    // be those expected if any are
+    let check = |(proof, values): (Vec<u8>, Vec<Fp>)| {
+        if let Some(expected) = &expected {
+            check_public_inputs(&circuit.module, &values, expected)?;
+        }
+        verifier(&params, &vk, &proof, &values)
+            .map_err(|err| CommandError::Failure(format!("zero-knowledge proof is invalid: {:?}", err)))
+    };
+    if let [proof] = proofs.as_slice() {
+        let proof_data = read_proof(proof)?;
+        show_proof_metadata(&mut outcome, proof);
+        outcome.println("* Public inputs:");
+        let mut public_inputs = serde_json::Map::new();
+        for (var, val) in circuit.module.pubs.iter().zip(&proof_data.1) {
+            let val = field_value(val);
+            outcome.println(format!("{} = {}", var, val));
+            public_inputs.insert(var.to_string(), val.to_string().into());
+        }
+        outcome.detail("public_inputs", public_inputs);
+        // Veryfing proof
+        info!("* Verifying proof validity...");
+        outcome.time("verify", || check(proof_data))?;
+        info!("* Zero-knowledge proof is valid");
+        outcome.detail("valid", true);
+        return Ok(outcome);
+    }
+    info!("* Verifying {} proofs...", proofs.len());
+    let verdicts = outcome.time("verify", || verify_all(&proofs, |proof| check(read_proof(proof)?)));
+    report_verdicts(outcome, verdicts)
+}
+
+/* Check that the given public inputs of a proof on the given module, in the
+ * order its public variables are declared, take the given values. Every value
+ * that differs is reported at once. */
+fn check_public_inputs(
+    module: &Module,
+    values: &[Fp],
+    expected: &BTreeMap<String, BigInt>,
+) -> Result<(), CommandError> {
+    let mut mismatches: Vec<_> = module.pubs.iter().zip(values)
+        .map(|(var, val)| (input_name(var), val))
+        .filter(|(name, val)| make_constant::<Fp>(expected[name].clone()) != **val)
+        .map(|(name, val)| format!("{} is {} rather than {}", name, field_value(val), expected[&name]))
+        .collect();
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    mismatches.sort();
+    Err(CommandError::Failure(format!("proof is of other public inputs: {}", mismatches.join("; "))))
+}
+
+/* Estimate the sizes in bytes of the public parameters and of a proof of a
+ * Halo2 circuit with 2^k rows. Points and scalars of the Pasta curves take 32
+ * bytes each. The parameters hold two points for each row and a few more,
+ * and a proof for the three advice and five fixed columns of Halo2Module
+ * holds about 33 commitments and evaluations besides the two points of each
+ * of the k rounds of its inner product argument. */
+fn estimated_sizes(k: u32) -> (u64, u64) {
+    let params = 4 + 32 * (2 * (1u64 << k) + 2);
+    let proof = 32 * (33 + 2 * k as u64);
+    (params, proof)
+}
+
+/* Print the metadata of the Halo2 circuit read from the given reader. */
+pub fn inspect_circuit(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let HaloCircuitData { params: _, circuit, opt_level, defines } =
+        HaloCircuitData::read(reader).map_err(|err| format!("{:?}", err))?;
+    outcome.println(format!("** k: {}", circuit.k));
+    outcome.detail("k", circuit.k);
+    print_circuit_summary(&circuit.module, &defines, outcome);
+    outcome.println(format!("** Optimization level: {}", opt_level));
+    outcome.detail("opt_level", opt_level);
+    Ok(())
+}
+
+/* Time generating keys for, proving, and verifying the circuit of the given
+ * module on the given inputs, the given number of times. Halo2 keys cannot be
+ * serialized, so their sizes are not measured. */
+pub fn bench_halo2(
+    module: Module,
+    inputs: HashMap<VariableId, BigInt>,
+    iterations: usize,
+) -> Result<BackendBench, CommandError> {
+    let mut circuit = Halo2Module::<Fp>::new(module);
+    let params: Params<EqAffine> = Params::new(circuit.k);
+    let assigns = inputs.into_iter().map(|(k, v)| (k, make_constant(v))).collect();
+    circuit.populate_variables(assigns, &NoProgress)
+        .map_err(|err| CommandError::Input(err.to_string()))?;
+    let public_values = circuit.public_values().map_err(|err| CommandError::Input(err.to_string()))?;
+    let mut bench = BackendBench {
+        backend: "halo2",
+        k: circuit.k,
+        keygen: vec![],
+        prove: vec![],
+        verify: vec![],
+        proof_size: 0,
+        proving_key_size: None,
+        verifying_key_size: None,
+    };
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let (pk, vk) = keygen(&circuit, &params, &NoProgress)
+            .map_err(|err| CommandError::internal("unable to generate keys", format!("{:?}", err)))?;
+        bench.keygen.push(start.elapsed());
+        let start = Instant::now();
+        let proof = prover(circuit.clone(), &params, &pk, None, &NoProgress)
+            .map_err(|err| CommandError::internal("unable to generate proof", format!("{:?}", err)))?;
+        bench.prove.push(start.elapsed());
+        let start = Instant::now();
+        verifier(&params, &vk, &proof, &public_values)
+            .map_err(|err| CommandError::Failure(format!("halo2 proof is invalid: {:?}", err)))?;
+        bench.verify.push(start.elapsed());
+        bench.proof_size = proof.len();
+    }
+    Ok(bench)
+}
+
+/* Read the module of the Halo2 circuit read from the given reader. */
+pub fn read_circuit_module(reader: &mut dyn std::io::Read) -> Result<Module, String> {
+    read_circuit_parts(reader).map(|(module, _, _)| module)
+}
+
+/* Read the module of the Halo2 circuit read from the given reader along with
+ * the optimization level and definitions that it was compiled with. */
+pub fn read_circuit_parts(reader: &mut dyn std::io::Read) -> Result<(Module, u8, Vec<(String, String)>), String> {
+    let HaloCircuitData { params: _, circuit, opt_level, defines } =
+        HaloCircuitData::read(reader).map_err(|err| format!("{:?}", err))?;
+    Ok((circuit.module, opt_level, defines))
+}
+
+/* Synthesize a Halo2 circuit from the given module, whose constants are
+ * already elements of the Pallas field, and write it to the given path with
+ * the given optimization level and definitions, as compile does. Returns the
+ * digests of the circuit. */
+pub fn write_circuit(
+    module: Module,
+    opt_level: u8,
+    defines: Vec<(String, String)>,
+    output: &Path,
+    force: bool,
+) -> Result<Digests, CommandError> {
+    let circuit = Halo2Module::<Fp>::new(module);
+    let params: Params<EqAffine> = Params::new(circuit.k);
+    let circuit_data = HaloCircuitData { params, circuit, opt_level, defines };
+    write_artifact(output, ArtifactKind::Halo2Circuit, force, Encoding::Binary, |writer| circuit_data.write(writer))
+        .map_err(CommandError::Input)
+}
+
+/* Print the metadata of the Halo2 verifier read from the given reader. */
+pub fn inspect_verifier(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let VerifierData { params: _, layout } = VerifierData::read(reader).map_err(|err| format!("{:?}", err))?;
+    outcome.println(format!("** Public variables: {}", layout.public.len()));
+    outcome.detail("public_variables", layout.public.len());
+    outcome.println(format!("** Gates: {}", layout.gates.len()));
+    outcome.detail("gates", layout.gates.len());
+    Ok(())
+}
+
+/* Print the metadata of the Halo2 proof read from the given reader. */
+pub fn inspect_proof(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let ProofDataHalo2 { proof, public_inputs: _ } = ProofDataHalo2::deserialize(reader)
+        .map_err(|err| format!("{:?}", err))?;
+    outcome.println(format!("** Proof size: {} bytes", proof.len()));
+    outcome.detail("proof_bytes", proof.len());
+    Ok(())
+}
+
+impl ClaimStdio for Halo2Commands {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        match self {
+            Halo2Commands::Compile(args) => {
+                claims.reads("--source", args.sources.iter().chain(&args.source))
+                    .writes("--output", [&args.output])
+                    .writes("--emit-ir", &args.emit_ir)
+                    .writes("--emit-interface", &args.emit_interface)
+                    .writes("--emit-verifier", &args.emit_verifier);
+            },
+            Halo2Commands::Prove(args) => {
+                claims.reads("--circuit", [&args.circuit])
+                    .reads("--inputs", &args.inputs)
+                    .reads("--public-inputs", &args.public_inputs)
+                    .reads("--private-inputs", &args.private_inputs)
+                    .writes("--output", &args.output);
+            },
+            Halo2Commands::Verify(args) => {
+                claims.reads("--circuit", [&args.circuit])
+                    .reads("--proof", &args.proofs)
+                    .reads("--public-inputs", &args.public_inputs);
+            },
+        }
+    }
+}
+
+pub fn halo2(halo2_commands: &Halo2Commands) -> CommandResult {
+    match halo2_commands {
+        Halo2Commands::Compile(args) => compile_halo2_cmd(args),
+        Halo2Commands::Prove(args) => prove_halo2_cmd(args),
+        Halo2Commands::Verify(args) => verify_halo2_cmd(args),
+    }
+}
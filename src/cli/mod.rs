@@ -0,0 +1,19 @@
+pub mod bench;
+#[cfg(feature = "bulletproofs")]
+pub mod bp;
+pub mod cache;
+pub mod circuit;
+pub mod compile;
+#[cfg(any(feature = "bulletproofs", feature = "groth16", feature = "plonky2"))]
+pub mod driver;
+pub mod evaluate;
+#[cfg(feature = "groth16")]
+pub mod groth16;
+#[cfg(feature = "halo2")]
+pub mod halo2;
+#[cfg(feature = "plonk")]
+pub mod plonk;
+#[cfg(feature = "plonky2")]
+pub mod plonky2;
+pub mod shared;
+pub mod watch;
@@ -0,0 +1,843 @@
+use crate::cli::shared::{check_metadata_output, compile_and_report, dry_run_cache_key, open_key_cache, parse_define, precheck_inputs, print_circuit_summary, print_defines, public_values, record_defines, record_stats, show_proof_metadata, write_interface, write_ir, write_proof, write_proof_metadata};
+use crate::sources::{cache_key, Prelude};
+use crate::artifact::{read_artifact, read_digested_artifact, write_artifact, ArtifactKind, Digests};
+use crate::dry_run::DryRun;
+use crate::repeat::{proof_targets, report_proving_times};
+use crate::container::ContainerFormat;
+use crate::encoding::Encoding;
+use crate::metadata::metadata_path;
+use crate::output::check_output;
+use crate::stdio::{is_stdin, is_stdout, ClaimStdio, PathOrStdio, StdioClaims};
+use crate::progress::{in_phase, terminal_progress, NoProgress, Phase};
+use crate::diagnostics::DiagnosticsFormat;
+use log::{debug, info, warn};
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::batch::{collect_proofs, report_verdicts, verify_all};
+use crate::inputs::{describe_inputs, read_expected_public, resolve_file_inputs, resolve_inputs, split_input_files, InputsFormat, Interactivity};
+use crate::key_cache::{KeyCache, PARAMS, PK, VK};
+use num_bigint::BigInt;
+use crate::ast::{Module, Variable, VariableId};
+use crate::bench::BackendBench;
+use crate::transform::{input_name, CompileOptions, DEFAULT_INLINE_LIMIT, DEFAULT_UNROLL_LIMIT, MAX_OPT_LEVEL};
+use crate::plonk::synth::{PlonkModule, PlonkCostModel, PrimeFieldOps, make_constant};
+use crate::plonk::circuit::{PlonkCircuitData, ProofDataPlonk, UniversalParams, PC};
+use vamp_ir::Error;
+
+use plonk_core::prelude::VerifierData;
+use plonk_core::proof_system::pi::PublicInputs;
+use plonk::error::to_pc_error;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use ark_bls12_381::Fr as BlsScalar;
+use ark_ed_on_bls12_381::EdwardsParameters as JubJubParameters;
+use ark_poly_commit::{PCUniversalParams, PolynomialCommitment};
+use plonk_core::circuit::{Circuit, verify_proof};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use rand_core::OsRng;
+
+use clap::{Args, Subcommand};
+
+
+#[derive(Subcommand)]
+pub enum PlonkCommands {
+    /// Sets up the public parameters required for proving
+    Setup(Setup),
+    /// Compiles a given source file to a circuit
+    Compile(PlonkCompile),
+    /// Proves knowledge of witnesses satisfying a circuit
+    Prove(PlonkProve),
+    /// Verifies that a proof is a correct one
+    Verify(PlonkVerify),
+}
+
+#[derive(Args)]
+pub struct Setup {
+    /// Maximum degree exponent of the polynomial commitment scheme
+    #[arg(short, long, default_value_t = 10)]
+    max_degree: u128,
+    /// Path to which the public parameters are written, or - for standard
+    /// output
+    #[arg(short, long)]
+    output: PathOrStdio,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+    /// Disable validity checks on the generated public parameters
+    #[arg(long)]
+    unchecked: bool,
+}
+
+#[derive(Args)]
+pub struct PlonkCompile {
+    /// Path to public parameters, or - for standard input
+    #[arg(short, long)]
+    universal_params: PathOrStdio,
+    /// Paths to source files to be compiled, in order, any one of which may be -
+    /// for standard input
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be compiled after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Path to which circuit is written, or - for standard output
+    #[arg(short, long)]
+    output: PathOrStdio,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Warn when local bindings shadow earlier bindings
+    #[arg(long)]
+    warn_shadowing: bool,
+    /// Treat compilation warnings as errors
+    #[arg(long)]
+    deny_warnings: bool,
+    /// Optimization level: 0 runs the required passes only, 1 adds cheap
+    /// rewrites, and 2 also shares gates
+    #[arg(short = 'O', long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=MAX_OPT_LEVEL as i64))]
+    opt_level: u8,
+    /// Largest single-use definition, in expression nodes, inlined from -O1
+    #[arg(long, default_value_t = DEFAULT_INLINE_LIMIT)]
+    inline_limit: usize,
+    /// Fix a public variable to a constant at compile time, given as name=value
+    #[arg(long = "define", value_name = "NAME=VALUE", value_parser = parse_define)]
+    defines: Vec<(String, BigInt)>,
+    /// Neither reuse nor record compilations in the .vampir-cache directory,
+    /// nor record the public parameters used and the keys made in the cache
+    #[arg(long)]
+    no_cache: bool,
+    /// Directory in which public parameters and keys are cached, by default
+    /// vamp-ir under $XDG_CACHE_HOME or ~/.cache
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+    /// Path to which the compiled intermediate representation is written, or -
+    /// for standard output
+    #[arg(long)]
+    emit_ir: Option<PathOrStdio>,
+    /// Path to which a JSON description of the inputs of the circuit is
+    /// written, or - for standard output
+    #[arg(long)]
+    emit_interface: Option<PathOrStdio>,
+    /// Print a summary of the compiled circuit, which is the default
+    #[arg(long, overrides_with = "no_stats")]
+    stats: bool,
+    /// Do not print a summary of the compiled circuit
+    #[arg(long, overrides_with = "stats")]
+    no_stats: bool,
+    /// Do not perform validity checks on public parameters
+    #[arg(long)]
+    unchecked: bool,
+    /// Format in which warnings and errors are reported
+    #[arg(long, value_enum, default_value_t = DiagnosticsFormat::Human)]
+    diagnostics_format: DiagnosticsFormat,
+    /// Compile and report the padded size of the circuit, the estimated sizes
+    /// of what it would make, and the files it would write, without making
+    /// keys or writing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct PlonkProve {
+    /// Path to public parameters, by default those cached for the circuit
+    #[arg(short, long)]
+    universal_params: Option<PathOrStdio>,
+    /// Neither use nor record public parameters in the cache
+    #[arg(long)]
+    no_cache: bool,
+    /// Directory of the cache, by default vamp-ir under $XDG_CACHE_HOME or
+    /// ~/.cache
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+    /// Path to circuit on which to construct proof
+    #[arg(short, long)]
+    circuit: PathOrStdio,
+    /// Path to which the proof is written, or - for standard output
+    #[arg(short, long, required_unless_present = "output_dir", conflicts_with = "output_dir")]
+    output: Option<PathOrStdio>,
+    /// Encoding in which the proof is written
+    #[arg(long, value_enum, default_value_t = Encoding::Binary)]
+    encoding: Encoding,
+    /// Wrap the proof, along with its public inputs and metadata, in a
+    /// self-describing container
+    #[arg(long, value_enum)]
+    container: Option<ContainerFormat>,
+    /// Overwrite output files that already exist
+    #[arg(long)]
+    force: bool,
+    /// Also write the public inputs, circuit digest, proving time, and
+    /// vamp-ir version to a PROOF.meta.json file beside the proof
+    #[arg(long)]
+    metadata: bool,
+    /// Check the inputs against the circuit and report the estimated size of
+    /// the proof and the files that would be written, without proving
+    #[arg(long)]
+    dry_run: bool,
+    /// Make this many proofs of the same inputs with the same proving key
+    /// and report the least, median, and greatest times taken; only the last
+    /// is written unless --output-dir is given
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    repeat: u64,
+    /// Directory into which every proof is written, as proof-1, proof-2, and
+    /// so on, in place of --output
+    #[arg(long)]
+    output_dir: Option<PathBuf>,
+    /// Do not perform validity checks on public parameters
+    #[arg(long)]
+    unchecked: bool,
+    /// Path to prover's input file, or - for standard input
+    #[arg(short, long)]
+    inputs: Option<PathOrStdio>,
+    /// Path to a file of the values of public variables, or - for standard
+    /// input, in place of --inputs
+    #[arg(long, conflicts_with = "inputs")]
+    public_inputs: Option<PathOrStdio>,
+    /// Path to a file of the values of inputs that are not public, or - for
+    /// standard input, in place of --inputs
+    #[arg(long, conflicts_with = "inputs")]
+    private_inputs: Option<PathOrStdio>,
+    /// Format of the input files, detected from their extensions by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Read inputs from VAMPIR_INPUT_<name> environment variables, with any
+    /// character of a name that cannot appear in one written as _, overriding
+    /// the inputs file
+    #[arg(long)]
+    env_inputs: bool,
+    /// Set an input, overriding the inputs file, given as name=value
+    #[arg(short = 'D', long = "set", value_name = "NAME=VALUE", value_parser = parse_define)]
+    overrides: Vec<(String, BigInt)>,
+    /// Fail instead of prompting for missing inputs, as when CI or
+    /// VAMPIR_NONINTERACTIVE is set
+    #[arg(long)]
+    non_interactive: bool,
+}
+
+#[derive(Args)]
+pub struct PlonkVerify {
+    /// Path to public parameters, by default those cached for the circuit
+    #[arg(short, long)]
+    universal_params: Option<PathOrStdio>,
+    /// Neither use nor record public parameters in the cache
+    #[arg(long)]
+    no_cache: bool,
+    /// Directory of the cache, by default vamp-ir under $XDG_CACHE_HOME or
+    /// ~/.cache
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+    /// Path to circuit on which to construct proof
+    #[arg(short, long)]
+    circuit: PathOrStdio,
+    /// Path to a proof to be verified, or - for standard input, in any
+    /// encoding; give several times to verify several proofs
+    #[arg(short, long = "proof", required_unless_present = "proof_dir")]
+    proofs: Vec<PathOrStdio>,
+    /// Directory whose every file is a proof to be verified
+    #[arg(long)]
+    proof_dir: Option<PathBuf>,
+    /// Path to a file of the values that the public variables must take, or -
+    /// for standard input; proofs of other values are rejected
+    #[arg(long)]
+    public_inputs: Option<PathOrStdio>,
+    /// ABI-encoded calldata, in hexadecimal, of the values that the public
+    /// variables must take, in place of --public-inputs
+    #[arg(long, conflicts_with = "public_inputs")]
+    public_calldata: Option<String>,
+    /// Format of the public inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Do not perform validity checks on public parameters
+    #[arg(long)]
+    unchecked: bool,
+    /// Read public inputs that are not laid out as the circuit expects as
+    /// zero rather than rejecting the proof
+    #[arg(long)]
+    lenient_public_inputs: bool,
+}
+
+impl ClaimStdio for PlonkCommands {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        match self {
+            PlonkCommands::Setup(args) => {
+                claims.writes("--output", [&args.output]);
+            },
+            PlonkCommands::Compile(args) => {
+                claims.reads("--universal-params", [&args.universal_params])
+                    .reads("--source", args.sources.iter().chain(&args.source))
+                    .writes("--output", [&args.output])
+                    .writes("--emit-ir", &args.emit_ir)
+                    .writes("--emit-interface", &args.emit_interface);
+            },
+            PlonkCommands::Prove(args) => {
+                claims.reads("--universal-params", &args.universal_params)
+                    .reads("--circuit", [&args.circuit])
+                    .reads("--inputs", &args.inputs)
+                    .reads("--public-inputs", &args.public_inputs)
+                    .reads("--private-inputs", &args.private_inputs)
+                    .writes("--output", &args.output);
+            },
+            PlonkCommands::Verify(args) => {
+                claims.reads("--universal-params", &args.universal_params)
+                    .reads("--circuit", [&args.circuit])
+                    .reads("--proof", &args.proofs)
+                    .reads("--public-inputs", &args.public_inputs);
+            },
+        }
+    }
+}
+
+pub fn plonk(plonk_commands: &PlonkCommands) -> CommandResult {
+    match plonk_commands {
+        PlonkCommands::Setup(args) => setup_plonk_cmd(args),
+        PlonkCommands::Compile(args) => compile_plonk_cmd(args),
+        PlonkCommands::Prove(args) => prove_plonk_cmd(args),
+        PlonkCommands::Verify(args) => verify_plonk_cmd(args),
+    }
+}
+
+/* Read the public parameters from the given file, optionally without checking
+ * their validity. */
+fn read_params(path: &Path, unchecked: bool) -> Result<UniversalParams, CommandError> {
+    read_artifact(path, ArtifactKind::PlonkParams, |reader| if unchecked {
+        UniversalParams::deserialize_unchecked(reader)
+    } else {
+        UniversalParams::deserialize(reader)
+    }).map_err(|err| CommandError::input(&path.to_string_lossy(), err))
+}
+
+/* Check, for a dry run, that the public parameters at the given path are
+ * there to be read, without the cost of reading them. */
+fn check_params_exist(path: &Path) -> Result<(), CommandError> {
+    if is_stdin(path) || path.is_file() {
+        Ok(())
+    } else {
+        Err(CommandError::input(&path.to_string_lossy(), "no such file"))
+    }
+}
+
+/* Estimate the sizes in bytes of the proving key, verifying key, and a proof
+ * of a PLONK circuit padded to the given number of gates. Points of BLS12-381
+ * take 48 bytes compressed and scalars 32. The proving key holds about 15
+ * polynomials of the size of the circuit together with their evaluations on
+ * a coset 8 times as large, while the verifying key and the proof hold about
+ * 15 commitments each, the proof besides about 16 evaluations. */
+fn estimated_sizes(padded_size: u64) -> (u64, u64, u64) {
+    let pk = 32 * 9 * 15 * padded_size;
+    let vk = 48 * 15 + 64;
+    let proof = 48 * 15 + 32 * 16;
+    (pk, vk, proof)
+}
+
+/* Cache the public parameters at the given path for the circuit with the
+ * given digest, unless some already are. Failing to only costs giving them
+ * again later, so it is merely warned of. */
+fn cache_params(cache: &KeyCache, circuit_hash: &str, params: &Path) {
+    if is_stdin(params) || cache.lookup("plonk", circuit_hash, PARAMS).is_some() {
+        return;
+    }
+    let stored = std::fs::read(params)
+        .map_err(|err| format!("cannot read {}: {}", params.to_string_lossy(), err))
+        .and_then(|contents| cache.store("plonk", circuit_hash, PARAMS, &contents));
+    match stored {
+        Ok(path) => debug!("* Cached public parameters in {}", path.to_string_lossy()),
+        Err(err) => warn!("* Warning: unable to cache public parameters: {}", err),
+    }
+}
+
+/* Find the public parameters for the circuit with the given digest: those at
+ * the given path, which are then cached for the circuit, or else those cached
+ * for it already. */
+fn locate_params(given: Option<&Path>, circuit_hash: &str, cache: Option<&KeyCache>) -> Result<PathBuf, CommandError> {
+    if let Some(path) = given {
+        if let Some(cache) = cache {
+            cache_params(cache, circuit_hash, path);
+        }
+        return Ok(path.to_path_buf());
+    }
+    let cache = cache.ok_or_else(|| CommandError::Input(
+        "no public parameters were given with -u, and the cache is not in use".to_string(),
+    ))?;
+    let path = cache.lookup("plonk", circuit_hash, PARAMS).ok_or_else(|| CommandError::Input(format!(
+        "no public parameters were given with -u, and none are cached in {} for circuit {}",
+        cache.root().to_string_lossy(), circuit_hash,
+    )))?;
+    info!("** Using public parameters cached in {}", path.to_string_lossy());
+    Ok(path)
+}
+
+/* Print the metadata of the public parameters read from the given reader.
+ * They are not checked, since that is costly and beside the point. */
+pub fn inspect_params(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let pp = UniversalParams::deserialize_unchecked(reader)
+        .map_err(|err| format!("{:?}", err))?;
+    outcome.println(format!("** Maximum degree: {}", pp.max_degree()));
+    outcome.detail("max_degree", pp.max_degree());
+    Ok(())
+}
+
+/* Print the metadata of the PLONK circuit read from the given reader. */
+pub fn inspect_circuit(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let PlonkCircuitData { pk_p: _, vk: _, circuit, opt_level, defines } =
+        PlonkCircuitData::read(reader).map_err(|err| format!("{:?}", err))?;
+    outcome.println(format!("** Padded size: {}", circuit.padded_circuit_size()));
+    outcome.detail("padded_size", circuit.padded_circuit_size());
+    print_circuit_summary(&circuit.module, &defines, outcome);
+    outcome.println(format!("** Optimization level: {}", opt_level));
+    outcome.detail("opt_level", opt_level);
+    Ok(())
+}
+
+/* Time generating keys for, proving, and verifying the circuit of the given
+ * module on the given inputs, the given number of times. Public parameters
+ * just large enough for the circuit are set up beforehand, untimed. */
+pub fn bench_plonk(
+    module: Module,
+    inputs: HashMap<VariableId, BigInt>,
+    iterations: usize,
+) -> Result<BackendBench, CommandError> {
+    let mut circuit = PlonkModule::<BlsScalar, JubJubParameters>::new(module);
+    let padded_size = circuit.padded_circuit_size();
+    let pp = PC::setup(2 * padded_size, None, &mut OsRng)
+        .map_err(to_pc_error::<BlsScalar, PC>)
+        .map_err(|err| CommandError::internal("unable to setup public parameters", format!("{:?}", err)))?;
+    let assigns = inputs.iter().map(|(k, v)| (*k, make_constant(v))).collect();
+    circuit.populate_variables(assigns, &NoProgress)
+        .map_err(|err| CommandError::Input(err.to_string()))?;
+    let mut bench = BackendBench {
+        backend: "plonk",
+        k: padded_size.trailing_zeros(),
+        keygen: vec![],
+        prove: vec![],
+        verify: vec![],
+        proof_size: 0,
+        proving_key_size: None,
+        verifying_key_size: None,
+    };
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let (pk_p, (vk, _)) = circuit.compile::<PC>(&pp)
+            .map_err(|err| CommandError::input("unable to compile circuit", format!("{:?}", err)))?;
+        bench.keygen.push(start.elapsed());
+        bench.proving_key_size = Some(pk_p.serialized_size());
+        bench.verifying_key_size = Some(vk.serialized_size());
+        let start = Instant::now();
+        let (proof, pi) = circuit.gen_proof::<PC>(&pp, pk_p, b"Test")
+            .map_err(|err| CommandError::internal("unable to generate proof", format!("{:?}", err)))?;
+        bench.prove.push(start.elapsed());
+        let start = Instant::now();
+        verify_proof::<BlsScalar, JubJubParameters, PC>(&pp, vk, &proof, &pi, b"Test")
+            .map_err(|err| CommandError::Failure(format!("plonk proof is invalid: {:?}", err)))?;
+        bench.verify.push(start.elapsed());
+        bench.proof_size = ProofDataPlonk { proof, pi }.serialized_size();
+    }
+    Ok(bench)
+}
+
+/* Read the module of the PLONK circuit read from the given reader. */
+pub fn read_circuit_module(reader: &mut dyn std::io::Read) -> Result<Module, String> {
+    read_circuit_parts(reader).map(|(module, _, _)| module)
+}
+
+/* Read the module of the PLONK circuit read from the given reader along with
+ * the optimization level and definitions that it was compiled with. */
+pub fn read_circuit_parts(reader: &mut dyn std::io::Read) -> Result<(Module, u8, Vec<(String, String)>), String> {
+    let PlonkCircuitData { pk_p: _, vk: _, circuit, opt_level, defines } =
+        PlonkCircuitData::read(reader).map_err(|err| format!("{:?}", err))?;
+    Ok((circuit.module, opt_level, defines))
+}
+
+/* Synthesize a PLONK circuit from the given module, whose constants are
+ * already elements of the BLS12-381 scalar field, generate its keys from the
+ * public parameters at the given path, and write it to the given path with
+ * the given optimization level and definitions, as compile does. Returns the
+ * digests of the circuit. */
+pub fn write_circuit(
+    module: Module,
+    opt_level: u8,
+    defines: Vec<(String, String)>,
+    universal_params: &Path,
+    unchecked: bool,
+    output: &Path,
+    force: bool,
+) -> Result<Digests, CommandError> {
+    info!("* Reading public parameters...");
+    let progress = terminal_progress();
+    let pp = in_phase(progress.as_ref(), Phase::LoadParams, || read_params(universal_params, unchecked))?;
+    let mut circuit = PlonkModule::<BlsScalar, JubJubParameters>::new(module);
+    circuit.check_constraints().map_err(CommandError::Input)?;
+    let (pk_p, vk) = in_phase(progress.as_ref(), Phase::KeygenPk, || circuit.compile::<PC>(&pp))
+        .map_err(|err| CommandError::input("unable to compile circuit", format!("{:?}", err)))?;
+    let circuit_data = PlonkCircuitData { pk_p, vk, circuit, opt_level, defines };
+    write_artifact(output, ArtifactKind::PlonkCircuit, force, Encoding::Binary, |writer| circuit_data.write(writer))
+        .map_err(CommandError::Input)
+}
+
+/* Print the metadata of the PLONK proof read from the given reader. */
+pub fn inspect_proof(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let proof_data = ProofDataPlonk::deserialize(reader)
+        .map_err(|err| format!("{:?}", err))?;
+    outcome.println(format!("** Proof size: {} bytes", proof_data.serialized_size()));
+    outcome.detail("proof_bytes", proof_data.serialized_size());
+    Ok(())
+}
+
+/* Implements the subcommand that generates the public parameters for proofs. */
+fn setup_plonk_cmd(Setup { max_degree, output, force, unchecked }: &Setup) -> CommandResult {
+    check_output(output, *force).map_err(CommandError::Input)?;
+    // Generate CRS
+    info!("* Setting up public parameters...");
+    let mut outcome = Outcome::default();
+    let pp = outcome.time("setup", || PC::setup(1 << max_degree, None, &mut OsRng))
+        .map_err(to_pc_error::<BlsScalar, PC>)
+        .map_err(|err| CommandError::internal(
+            "unable to setup polynomial commitment scheme public parameters",
+            format!("{:?}", err),
+        ))?;
+    write_artifact(output, ArtifactKind::PlonkParams, *force, Encoding::Binary, |writer| if *unchecked {
+        pp.serialize_unchecked(writer)
+    } else {
+        pp.serialize(writer)
+    }).map_err(CommandError::Input)?;
+    outcome.artifact("params", output);
+    info!("* Public parameter setup success!");
+    Ok(outcome)
+}
+
+/* Implements the subcommand that compiles a vamp-ir file into a PLONK circuit.
+ */
+ fn compile_plonk_cmd(PlonkCompile { universal_params, sources, source, prelude, output, force, unroll_limit, warn_shadowing, deny_warnings, opt_level, inline_limit, defines, no_cache, cache_dir, emit_ir, emit_interface, stats: print_timings, no_stats, unchecked, diagnostics_format, dry_run }: &PlonkCompile) -> CommandResult {
+    check_output(output, *force).map_err(CommandError::Input)?;
+    if let Some(emit_ir) = emit_ir {
+        check_output(emit_ir, *force).map_err(CommandError::Input)?;
+    }
+    if let Some(emit_interface) = emit_interface {
+        check_output(emit_interface, *force).map_err(CommandError::Input)?;
+    }
+    info!("* Compiling constraints...");
+    let sources: Vec<_> = sources.iter().chain(source).cloned().collect();
+    let options = format!(
+        "unroll-limit={} warn-shadowing={} deny-warnings={} opt-level={} inline-limit={} defines={:?}",
+        unroll_limit, warn_shadowing, deny_warnings, opt_level, inline_limit, defines,
+    );
+    let mut key = (!*no_cache).then(|| cache_key("plonk", &options, prelude, &sources)).transpose()?;
+    let mut plan = dry_run.then(DryRun::default);
+    if let Some(plan) = &mut plan {
+        key = dry_run_cache_key(key, plan);
+    }
+    let options = CompileOptions::default()
+        .unroll_limit(*unroll_limit)
+        .deny_warnings(*deny_warnings)
+        .opt_level(*opt_level)
+        .inline_limit(*inline_limit)
+        .cost_model(Box::new(PlonkCostModel));
+    let options = defines.iter().cloned()
+        .fold(options, |options, (name, value)| options.define(name, value));
+    let field_ops = PrimeFieldOps::<BlsScalar>::default();
+    let mut outcome = Outcome::default();
+    let (module_3ac, mut stats) = outcome.time("compile", || compile_and_report(key, prelude, &sources, *warn_shadowing, &field_ops, &options, *diagnostics_format))?;
+    if let Some(emit_ir) = emit_ir {
+        match &mut plan {
+            Some(plan) => plan.output("ir", emit_ir),
+            None => {
+                write_ir(&module_3ac, emit_ir, *force)?;
+                outcome.artifact("ir", emit_ir);
+            },
+        }
+    }
+    if let Some(emit_interface) = emit_interface {
+        match &mut plan {
+            Some(plan) => plan.output("interface", emit_interface),
+            None => {
+                write_interface(&module_3ac, emit_interface, *force)?;
+                outcome.artifact("interface", emit_interface);
+            },
+        }
+    }
+    if let Some(mut plan) = plan {
+        check_params_exist(universal_params)?;
+        let circuit = PlonkModule::<BlsScalar, JubJubParameters>::new(module_3ac);
+        let padded_size = circuit.padded_circuit_size();
+        if !*print_timings {
+            stats.pass_timings.clear();
+        }
+        stats.k = Some(padded_size.trailing_zeros());
+        let (pk_size, vk_size, proof_size) = estimated_sizes(padded_size as u64);
+        plan.k = stats.k;
+        plan.size("circuit", Some(pk_size + vk_size));
+        plan.size("proving key", Some(pk_size));
+        plan.size("verifying key", Some(vk_size));
+        plan.size("proof", Some(proof_size));
+        plan.output("circuit", output);
+        if let Some(cache) = open_key_cache(*no_cache, cache_dir) {
+            plan.cache(format!(
+                "would record {} as the public parameters of the circuit, along with its keys, in {}",
+                universal_params.to_string_lossy(), cache.root().to_string_lossy(),
+            ));
+        }
+        record_stats(&mut outcome, &stats, !*no_stats);
+        plan.report(&mut outcome);
+        return Ok(outcome);
+    }
+
+    info!("* Reading public parameters...");
+    let progress = terminal_progress();
+    let pp = in_phase(progress.as_ref(), Phase::LoadParams, || read_params(universal_params, *unchecked))?;
+
+    info!("* Synthesizing arithmetic circuit...");
+    let mut circuit = PlonkModule::<BlsScalar, JubJubParameters>::new(module_3ac.clone());
+    // Compile the circuit, which generates both keys
+    let (pk_p, vk) = outcome.time("keygen", || in_phase(progress.as_ref(), Phase::KeygenPk, || circuit.compile::<PC>(&pp)))
+        .map_err(|err| CommandError::input("unable to compile circuit", format!("{:?}", err)))?;
+    if !*print_timings {
+        stats.pass_timings.clear();
+    }
+    stats.k = Some(circuit.padded_circuit_size().trailing_zeros());
+    info!("* Serializing circuit to storage...");
+    let circuit_data = PlonkCircuitData { pk_p, vk, circuit, opt_level: *opt_level, defines: record_defines(defines) };
+    let digest = write_artifact(output, ArtifactKind::PlonkCircuit, *force, Encoding::Binary, |writer| circuit_data.write(writer))
+        .map_err(CommandError::Input)?;
+    outcome.artifact("circuit", output);
+    if let Some(cache) = open_key_cache(*no_cache, cache_dir) {
+        cache_params(&cache, &digest.secure, universal_params);
+        cache.store_key("plonk", &digest.secure, PK, "proving key", &circuit_data.pk_p);
+        cache.store_key("plonk", &digest.secure, VK, "verifying key", &circuit_data.vk);
+    }
+    outcome.circuit_hash = Some(digest.content);
+
+    info!("* Constraint compilation success!");
+    // Standard output only carries the circuit when it is written there
+    record_stats(&mut outcome, &stats, !*no_stats && !is_stdout(output));
+    Ok(outcome)
+}
+
+
+/* Implements the subcommand that creates a proof from interactively entered
+ * inputs. */
+ fn prove_plonk_cmd(PlonkProve { universal_params, no_cache, cache_dir, circuit, output, encoding, container, force, metadata, dry_run, repeat, output_dir, unchecked, inputs, public_inputs, private_inputs, format, env_inputs, overrides, non_interactive }: &PlonkProve) -> CommandResult {
+    let targets = proof_targets(output.as_deref(), output_dir.as_deref(), *repeat as usize);
+    for target in targets.iter().flatten() {
+        check_output(target, *force).map_err(CommandError::Input)?;
+        if *metadata {
+            check_metadata_output(target, *force)?;
+        }
+    }
+    info!("* Reading arithmetic circuit...");
+    let mut expected_path_to_inputs = circuit.to_path_buf();
+    expected_path_to_inputs.set_extension("inputs");
+
+    let mut outcome = Outcome::default();
+    let (PlonkCircuitData { pk_p, vk: _vk, mut circuit, opt_level, defines }, digest) =
+        read_digested_artifact(circuit, ArtifactKind::PlonkCircuit, |reader| PlonkCircuitData::read(reader))
+            .map_err(|err| CommandError::input(&circuit.to_string_lossy(), err))?;
+    circuit.check_constraints().map_err(CommandError::Input)?;
+    let cache = open_key_cache(*no_cache, cache_dir);
+    let mut plan = dry_run.then(DryRun::default);
+    let universal_params = match (&mut plan, universal_params, &cache) {
+        // Given parameters are only cached once a proof is made with them
+        (Some(plan), Some(path), Some(cache)) => {
+            plan.cache(format!(
+                "would record {} as the public parameters of the circuit in {}",
+                path.to_string_lossy(), cache.root().to_string_lossy(),
+            ));
+            path.to_path_buf()
+        },
+        _ => locate_params(universal_params.as_deref(), &digest.secure, cache.as_ref())?,
+    };
+    outcome.circuit_hash = Some(digest.content);
+    info!("** Circuit compiled at optimization level {}", opt_level);
+    print_defines(&defines);
+
+    // Gather program inputs from the command line, files, or the user
+    let split_files = split_input_files(public_inputs.as_deref(), private_inputs.as_deref());
+    let var_assignments_ints = if split_files.is_empty() {
+        resolve_inputs(
+            &circuit.module,
+            inputs.as_deref(),
+            &expected_path_to_inputs,
+            *format,
+            *env_inputs,
+            overrides,
+            Interactivity::choose(*non_interactive),
+        )?
+    } else {
+        resolve_file_inputs(&circuit.module, &split_files, *format, *env_inputs, overrides)?
+    };
+    let metadata_inputs = (*metadata || container.is_some()).then(|| public_values(&circuit.module, &var_assignments_ints, &PrimeFieldOps::<BlsScalar>::default())).transpose()?;
+    if plan.is_some() {
+        precheck_inputs(&circuit.module, &var_assignments_ints, &PrimeFieldOps::<BlsScalar>::default())?;
+    }
+
+    let mut var_assignments = HashMap::new();
+    for (k, v) in var_assignments_ints {
+        var_assignments.insert(k, make_constant(&v));
+    }
+    
+    // Populate variable definitions
+    let progress = terminal_progress();
+    circuit.populate_variables(var_assignments, progress.as_ref())
+        .map_err(|err| CommandError::Input(err.to_string()))?;
+    if let Some(mut plan) = plan {
+        check_params_exist(&universal_params)?;
+        let padded_size = circuit.padded_circuit_size();
+        plan.k = Some(padded_size.trailing_zeros());
+        plan.size("proof", Some(estimated_sizes(padded_size as u64).2));
+        for target in targets.iter().flatten() {
+            plan.output("proof", target);
+            if *metadata {
+                plan.output("metadata", &metadata_path(target));
+            }
+        }
+        plan.report(&mut outcome);
+        return Ok(outcome);
+    }
+    
+    info!("* Reading public parameters...");
+    let pp = in_phase(progress.as_ref(), Phase::LoadParams, || read_params(&universal_params, *unchecked))?;
+
+    if let Some(dir) = output_dir {
+        fs::create_dir_all(dir).map_err(|err| CommandError::input(&dir.to_string_lossy(), err))?;
+    }
+
+    // Start proving witnesses, as many times as asked with the same key
+    info!("* Proving knowledge of witnesses...");
+    let mut times = vec![];
+    for target in &targets {
+        let (proof, pi) = outcome.time("prove", || in_phase(progress.as_ref(), Phase::CreateProof, || circuit.gen_proof::<PC>(&pp, pk_p.clone(), b"Test")))
+            .map_err(|err| CommandError::internal("unable to generate proof", format!("{:?}", err)))?;
+        times.extend(outcome.timing("prove"));
+        let Some(target) = target else { continue };
+        info!("* Serializing proof to storage...");
+        write_proof(&outcome, target, ArtifactKind::PlonkProof, *force, *encoding, *container, metadata_inputs.as_deref(), |writer| ProofDataPlonk { proof, pi }.serialize(writer))?;
+        outcome.artifact("proof", target);
+        if let Some(metadata_inputs) = metadata_inputs.as_ref().filter(|_| *metadata) {
+            write_proof_metadata(&mut outcome, ArtifactKind::PlonkProof, target, metadata_inputs.clone(), *force)?;
+        }
+    }
+    report_proving_times(&mut outcome, &times);
+
+    info!("* Proof generation success!");
+    Ok(outcome)
+}
+
+/* Implements the subcommand that verifies that proofs are correct. The
+ * circuit and public parameters are read once for all of them, and public
+ * inputs are shown when there is just one. */
+fn verify_plonk_cmd(PlonkVerify { universal_params, no_cache, cache_dir, circuit, proofs, proof_dir, public_inputs, public_calldata, format, unchecked, lenient_public_inputs }: &PlonkVerify) -> CommandResult {
+    let proofs = collect_proofs(proofs, proof_dir.as_deref())?;
+    info!("* Reading arithmetic circuit...");
+    let mut outcome = Outcome::default();
+    let (PlonkCircuitData { pk_p: _pk_p, vk, circuit, opt_level: _, defines }, digest) =
+        read_digested_artifact(circuit, ArtifactKind::PlonkCircuit, |reader| PlonkCircuitData::read(reader))
+            .map_err(|err| CommandError::input(&circuit.to_string_lossy(), err))?;
+    let cache = open_key_cache(*no_cache, cache_dir);
+    let universal_params = locate_params(universal_params.as_deref(), &digest.secure, cache.as_ref())?;
+    outcome.circuit_hash = Some(digest.content);
+    print_defines(&defines);
+    let expected = read_expected_public(
+        &circuit.module, public_inputs.as_deref(), public_calldata.as_deref(), *format, &PrimeFieldOps::<BlsScalar>::default(),
+    )?;
+    // The values expected of the public variables, in the order they are
+    // declared, which tell dropped zeros from missing inputs
+    let expected_values: Option<Vec<_>> = expected.as_ref().map(|expected| circuit.module.pubs.iter()
+        .map(|var| make_constant::<BlsScalar>(&expected[&input_name(var)]))
+        .collect());
+
+    let read_proof = |proof: &Path| {
+        read_artifact(proof, ArtifactKind::PlonkProof, |reader| ProofDataPlonk::deserialize(reader))
+            .map_err(|err| CommandError::input(&proof.to_string_lossy(), err))
+    };
+    let single = match proofs.as_slice() {
+        [proof] => {
+            info!("* Reading zero-knowledge proof...");
+            let proof_data = read_proof(proof)?;
+            show_proof_metadata(&mut outcome, proof);
+            outcome.println("* Public inputs:");
+            let mut public_inputs = serde_json::Map::new();
+            let annotated = annotate_public_inputs(
+                &circuit, &vk.1, &proof_data.pi, expected_values.as_deref(), *lenient_public_inputs,
+            )?;
+            for (var, val) in annotated.values() {
+                outcome.println(format!("{} = {}", var, val));
+                public_inputs.insert(var.to_string(), val.to_string().into());
+            }
+            outcome.detail("public_inputs", public_inputs);
+            Some(proof_data)
+        },
+        _ => None,
+    };
+
+    info!("* Reading public parameters...");
+    let pp = in_phase(terminal_progress().as_ref(), Phase::LoadParams, || read_params(&universal_params, *unchecked))?;
+
+    // Verifier POV
+    let check = |ProofDataPlonk { proof, pi }: ProofDataPlonk| {
+        let annotated = annotate_public_inputs(&circuit, &vk.1, &pi, expected_values.as_deref(), *lenient_public_inputs)?;
+        if let Some(expected) = &expected {
+            check_public_inputs(annotated, expected)?;
+        }
+        let verifier_data = VerifierData::new(vk.0.clone(), pi);
+        verify_proof::<BlsScalar, JubJubParameters, PC>(
+            &pp,
+            verifier_data.key,
+            &proof,
+            &verifier_data.pi,
+            b"Test",
+        ).map_err(|err| CommandError::Failure(format!("zero-knowledge proof is invalid: {:?}", err)))
+    };
+    if let Some(proof_data) = single {
+        info!("* Verifying proof validity...");
+        outcome.time("verify", || check(proof_data))?;
+        info!("* Zero-knowledge proof is valid");
+        outcome.detail("valid", true);
+        return Ok(outcome);
+    }
+    info!("* Verifying {} proofs...", proofs.len());
+    let verdicts = outcome.time("verify", || verify_all(&proofs, |proof| check(read_proof(proof)?)));
+    report_verdicts(outcome, verdicts)
+}
+
+/* Annotate the public inputs of a proof on the given circuit, whose public
+ * variables are at the given positions, with those variables. Inputs that are
+ * laid out otherwise, or missing where the given expected values are not
+ * zero, reject the proof unless leniency is asked for. */
+fn annotate_public_inputs(
+    circuit: &PlonkModule<BlsScalar, JubJubParameters>,
+    positions: &Vec<usize>,
+    pi: &PublicInputs<BlsScalar>,
+    expected: Option<&[BlsScalar]>,
+    lenient: bool,
+) -> Result<HashMap<VariableId, (Variable, BlsScalar)>, CommandError> {
+    if lenient {
+        return Ok(circuit.annotate_public_inputs(positions, pi));
+    }
+    circuit.annotate_public_inputs_strict(positions, pi, expected).map_err(|err| match err {
+        Error::InvalidProof(_) => CommandError::Failure(err.to_string()),
+        _ => CommandError::Input(err.to_string()),
+    })
+}
+
+/* Check that the given annotated public inputs of a proof take the given
+ * values. Every value that differs is reported at once. */
+fn check_public_inputs(
+    annotated: HashMap<VariableId, (Variable, BlsScalar)>,
+    expected: &BTreeMap<String, BigInt>,
+) -> Result<(), CommandError> {
+    let mut mismatches: Vec<_> = annotated.into_values()
+        .map(|(var, val)| (input_name(&var), val))
+        .filter(|(name, val)| make_constant::<BlsScalar>(&expected[name]) != *val)
+        .map(|(name, val)| format!("{} is {} rather than {}", name, val, expected[&name]))
+        .collect();
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    mismatches.sort();
+    Err(CommandError::Failure(format!("proof is of other public inputs: {}", mismatches.join("; "))))
+}
\ No newline at end of file
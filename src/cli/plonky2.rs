@@ -0,0 +1,180 @@
+use crate::cli::shared::print_circuit_summary;
+use crate::artifact::{read_digested_artifact, ArtifactKind};
+use crate::cli::driver::{check_prove_outputs, compile_with, prove_with, verify_with, Backend, CompileArgs, ProveArgs, VerifyArgs};
+use crate::stdio::{ClaimStdio, StdioClaims};
+use log::info;
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::batch::collect_proofs;
+use num_bigint::BigInt;
+use crate::ast::{Module, VariableId};
+use crate::transform::{CostModel, FieldOps};
+use crate::plonky2::synth::{field_value, make_constant, GoldilocksFieldOps, Plonky2CostModel, F};
+use crate::plonky2::circuit::{Plonky2Circuit, Plonky2CircuitData, Plonky2Proof};
+use vamp_ir::error::Error;
+
+use ark_serialize::SerializationError;
+use bincode::error::EncodeError;
+
+use clap::Subcommand;
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Subcommand)]
+pub enum Plonky2Commands {
+    /// Compiles a given source file to a circuit
+    Compile(CompileArgs),
+    /// Proves knowledge of witnesses satisfying a circuit
+    Prove(ProveArgs),
+    /// Verifies that a proof is a correct one
+    Verify(VerifyArgs),
+}
+
+/* The calls of plonky2 over Goldilocks that the shared compile, prove, and
+ * verify subcommands make. */
+struct Plonky2;
+
+impl Backend for Plonky2 {
+    type Circuit = Plonky2Circuit;
+    type Proof = Plonky2Proof;
+    type Value = F;
+
+    fn name(&self) -> String {
+        "plonky2".to_string()
+    }
+
+    fn circuit_kind(&self) -> ArtifactKind {
+        ArtifactKind::Plonky2Circuit
+    }
+
+    fn proof_kind(&self) -> ArtifactKind {
+        ArtifactKind::Plonky2Proof
+    }
+
+    fn field_ops(&self) -> Box<dyn FieldOps> {
+        Box::new(GoldilocksFieldOps)
+    }
+
+    fn cost_model(&self) -> Box<dyn CostModel> {
+        Box::new(Plonky2CostModel)
+    }
+
+    fn build(&self, module: Module, opt_level: u8, defines: Vec<(String, String)>, outcome: &mut Outcome) -> Result<Plonky2Circuit, CommandError> {
+        // Building checks that every constraint maps onto arithmetic gates
+        // before anything is written
+        info!("* Building plonky2 circuit...");
+        let data = Plonky2CircuitData { module, opt_level, defines };
+        let circuit = Plonky2Circuit::from_data(data)
+            .map_err(|err| CommandError::input("unable to compile circuit", err))?;
+        outcome.println(format!("** Gates: {}", circuit.gates()));
+        outcome.detail("gates", circuit.gates());
+        Ok(circuit)
+    }
+
+    fn write_circuit(&self, circuit: &Plonky2Circuit, writer: &mut Vec<u8>) -> Result<(), EncodeError> {
+        circuit.data().write(writer)
+    }
+
+    fn module<'a>(&self, circuit: &'a Plonky2Circuit) -> &'a Module {
+        circuit.module()
+    }
+
+    fn opt_level(&self, circuit: &Plonky2Circuit) -> u8 {
+        circuit.data().opt_level
+    }
+
+    fn defines<'a>(&self, circuit: &'a Plonky2Circuit) -> &'a [(String, String)] {
+        &circuit.data().defines
+    }
+
+    fn prove(&self, circuit: &Plonky2Circuit, assigns: HashMap<VariableId, BigInt>) -> Result<Plonky2Proof, Error> {
+        circuit.prove_assigned(assigns)
+    }
+
+    fn verify(&self, circuit: &Plonky2Circuit, proof: &Plonky2Proof) -> Result<(), Error> {
+        circuit.verify(proof)
+    }
+
+    fn write_proof(&self, proof: &Plonky2Proof, writer: &mut Vec<u8>) -> Result<(), SerializationError> {
+        proof.write(writer)
+    }
+
+    fn read_proof(&self, reader: &mut dyn Read) -> Result<Plonky2Proof, SerializationError> {
+        Plonky2Proof::read(reader)
+    }
+
+    fn public_values<'a>(&self, proof: &'a Plonky2Proof) -> &'a [F] {
+        &proof.public_values
+    }
+
+    fn make_value(&self, value: &BigInt) -> F {
+        make_constant(value)
+    }
+
+    fn value_of(&self, value: &F) -> BigInt {
+        field_value(value)
+    }
+}
+
+/* Read the plonky2 circuit at the given path and build its module, giving it
+ * along with the digest of its contents. */
+fn read_circuit(path: &Path) -> Result<(Plonky2Circuit, String), CommandError> {
+    let name = path.to_string_lossy();
+    let (data, digest) = read_digested_artifact(path, ArtifactKind::Plonky2Circuit, |reader| Plonky2CircuitData::read(reader))
+        .map_err(|err| CommandError::input(&name, err))?;
+    let circuit = Plonky2Circuit::from_data(data).map_err(|err| CommandError::input(&name, err))?;
+    Ok((circuit, digest.content))
+}
+
+/* Implements the subcommand that creates a proof from interactively entered
+ * inputs. */
+fn prove_plonky2_cmd(args: &ProveArgs) -> CommandResult {
+    check_prove_outputs(args)?;
+    info!("* Reading arithmetic circuit...");
+    let (circuit, digest) = read_circuit(&args.circuit)?;
+    prove_with(&Plonky2, args, circuit, digest)
+}
+
+/* Implements the subcommand that verifies that proofs are correct. */
+fn verify_plonky2_cmd(args: &VerifyArgs) -> CommandResult {
+    let proofs = collect_proofs(&args.proofs, args.proof_dir.as_deref())?;
+    info!("* Reading arithmetic circuit...");
+    let (circuit, digest) = read_circuit(&args.circuit)?;
+    verify_with(&Plonky2, args, &proofs, circuit, digest)
+}
+
+/* Print the metadata of the plonky2 circuit read from the given reader. */
+pub fn inspect_circuit(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let data = Plonky2CircuitData::read(reader).map_err(|err| format!("{:?}", err))?;
+    print_circuit_summary(&data.module, &data.defines, outcome);
+    outcome.println(format!("** Optimization level: {}", data.opt_level));
+    outcome.detail("opt_level", data.opt_level);
+    Ok(())
+}
+
+/* Print the metadata of the plonky2 proof read from the given reader. */
+pub fn inspect_proof(reader: &mut dyn std::io::Read, outcome: &mut Outcome) -> Result<(), String> {
+    let proof = Plonky2Proof::read(reader).map_err(|err| format!("{:?}", err))?;
+    outcome.println(format!("** Proof size: {} bytes", proof.proof.len()));
+    outcome.detail("proof_bytes", proof.proof.len());
+    Ok(())
+}
+
+impl ClaimStdio for Plonky2Commands {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        match self {
+            Plonky2Commands::Compile(args) => args.claim_stdio(claims),
+            Plonky2Commands::Prove(args) => args.claim_stdio(claims),
+            Plonky2Commands::Verify(args) => args.claim_stdio(claims),
+        }
+    }
+}
+
+pub fn plonky2(plonky2_commands: &Plonky2Commands) -> CommandResult {
+    match plonky2_commands {
+        Plonky2Commands::Compile(args) => compile_with(&Plonky2, args),
+        Plonky2Commands::Prove(args) => prove_plonky2_cmd(args),
+        Plonky2Commands::Verify(args) => verify_plonky2_cmd(args),
+    }
+}
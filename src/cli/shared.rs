@@ -0,0 +1,352 @@
+use crate::artifact::{artifact_bytes, write_artifact, ArtifactKind};
+use crate::ast::{Module, VariableId};
+use crate::cache::CacheKey;
+use crate::container::{read_container, ContainerFormat, ProofContainer};
+use crate::diagnostics::{report, DiagnosticsFormat};
+use crate::dry_run::DryRun;
+use crate::encoding::Encoding;
+use crate::inputs::parse_input_value;
+#[cfg(any(feature = "plonk", feature = "groth16"))]
+use crate::key_cache::KeyCache;
+use crate::metadata::{metadata_path, read_metadata, write_metadata, ProofMetadata};
+use crate::output::{check_output, write_output};
+use crate::sources::{compile_module, compile_sources, parse_sources, Prelude};
+use crate::status::{CommandError, Outcome};
+use crate::stdio::{is_stdout, PathOrStdio};
+use crate::transform::{derive_witnesses, input_name, violated_constraints, CompileOptions, CostModel, FieldOps, ModuleStats};
+use ark_bls12_381::Fr as BlsScalar;
+use clap::ValueEnum;
+use halo2_proofs::pasta::Fp;
+use log::{info, warn};
+use num_bigint::BigInt;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+#[cfg(any(feature = "plonk", feature = "groth16"))]
+use std::path::PathBuf;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, ValueEnum)]
+pub enum ProofSystems {
+    /// PLONK general-purpose zero-knowledge proof scheme
+    Plonk,
+    /// Halo 2 zero-knowledge proving system
+    Halo2,
+}
+
+impl ProofSystems {
+    /* The name by which this proof system is given on the command line. */
+    pub fn name(&self) -> String {
+        self.to_possible_value().expect("proof systems have names").get_name().to_string()
+    }
+
+    /* The error of commands that need the backend of this proof system when
+     * this build leaves it out. */
+    pub fn missing(&self) -> CommandError {
+        CommandError::Input(format!("this binary was built without the {} backend", self.name()))
+    }
+
+    /* Make the operations of the field of this proof system. */
+    pub fn field_ops(&self) -> Box<dyn FieldOps> {
+        match self {
+            ProofSystems::Plonk => Box::new(crate::plonk::field::PrimeFieldOps::<BlsScalar>::default()),
+            ProofSystems::Halo2 => Box::new(crate::halo2::field::PrimeFieldOps::<Fp>::default()),
+        }
+    }
+
+    /* Get the cost model of the backend of this proof system. */
+    pub fn cost_model(&self) -> Box<dyn CostModel> {
+        match self {
+            ProofSystems::Plonk => Box::new(crate::plonk::field::PlonkCostModel),
+            ProofSystems::Halo2 => Box::new(crate::halo2::field::Halo2CostModel),
+        }
+    }
+}
+
+/* Parse a compile-time definition of a public variable given as name=value. */
+pub fn parse_define(arg: &str) -> Result<(String, BigInt), String> {
+    let (name, value) = arg.split_once('=')
+        .ok_or_else(|| format!("expected name=value, found {}", arg))?;
+    let value = parse_input_value(value)
+        .map_err(|err| format!("invalid value for {}: {}", name.trim(), err))?;
+    Ok((name.trim().to_string(), value))
+}
+
+/* Put the given compile-time definitions into the form recorded in circuit
+ * metadata. */
+pub fn record_defines(defines: &[(String, BigInt)]) -> Vec<(String, String)> {
+    defines.iter().map(|(name, value)| (name.clone(), value.to_string())).collect()
+}
+
+/* Print the number of constraints and the public variables of the given
+ * circuit module, along with those fixed by the given definitions, to the
+ * given outcome. */
+pub fn print_circuit_summary(module: &Module, defines: &[(String, String)], outcome: &mut Outcome) {
+    outcome.println(format!("** Constraints: {}", module.exprs.len()));
+    let pubs: Vec<_> = module.pubs.iter().map(input_name).collect();
+    outcome.println(format!("** Public variables ({}): {}", pubs.len(), pubs.join(", ")));
+    for (name, value) in defines {
+        outcome.println(format!("** Public variable {} fixed to {} at compile time", name, value));
+    }
+    outcome.detail("constraints", module.exprs.len());
+    outcome.detail("public_variables", pubs);
+    let defines: serde_json::Map<_, _> = defines.iter()
+        .map(|(name, value)| (name.clone(), json!(value)))
+        .collect();
+    outcome.detail("defines", defines);
+}
+
+/* Record the given statistics of a compiled circuit in the given outcome, and
+ * print them to it if asked to. */
+pub fn record_stats(outcome: &mut Outcome, stats: &ModuleStats, print: bool) {
+    if print {
+        outcome.println(stats);
+    }
+    let pass_timings: serde_json::Map<_, _> = stats.pass_timings.iter()
+        .map(|(pass, duration)| (pass.to_string(), json!(duration.as_secs_f64() * 1000.0)))
+        .collect();
+    outcome.detail("stats", json!({
+        "source_constraints": stats.source_constraints,
+        "constraints": stats.constraints,
+        "auxiliary_variables": stats.auxiliary_variables,
+        "definition_depth": stats.definition_depth,
+        "public_variables": stats.pubs,
+        "shapes": stats.shapes,
+        "k": stats.k,
+        "estimated_cost": stats.estimated_cost,
+        "pass_ms": pass_timings,
+    }));
+}
+
+/* Derive the values of the public variables of the given module from the
+ * given inputs, by name and in the order they are declared. */
+pub fn public_values(
+    module: &Module,
+    inputs: &HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Result<Vec<(String, String)>, CommandError> {
+    let mut assigns = inputs.clone();
+    derive_witnesses(module, &mut assigns, field_ops)
+        .map_err(|err| CommandError::Input(err.to_string()))?;
+    Ok(module.pubs.iter().map(|var| (input_name(var), assigns[&var.id].to_string())).collect())
+}
+
+/* Check, before any work is done, that the metadata of a proof can be written
+ * beside the proof at the given path. */
+pub fn check_metadata_output(proof: &Path, force: bool) -> Result<(), CommandError> {
+    if is_stdout(proof) {
+        return Err(CommandError::Input(
+            "--metadata needs the proof to be written to a file, not standard output".to_string(),
+        ));
+    }
+    check_output(&metadata_path(proof), force).map_err(CommandError::Input)
+}
+
+/* Describe the proof of the given kind just made, taking the circuit digest
+ * and proving time from the given outcome. */
+fn proof_metadata(outcome: &Outcome, kind: ArtifactKind, public_inputs: Vec<(String, String)>) -> ProofMetadata {
+    ProofMetadata::new(
+        kind,
+        outcome.circuit_hash.clone().unwrap_or_default(),
+        outcome.timing("prove").unwrap_or_default(),
+        public_inputs,
+    )
+}
+
+/* Write the metadata of the proof of the given kind just written to the given
+ * path. */
+pub fn write_proof_metadata(
+    outcome: &mut Outcome,
+    kind: ArtifactKind,
+    proof: &Path,
+    public_inputs: Vec<(String, String)>,
+    force: bool,
+) -> Result<(), CommandError> {
+    let metadata = proof_metadata(outcome, kind, public_inputs);
+    let path = write_metadata(proof, &metadata, force).map_err(CommandError::Input)?;
+    outcome.artifact("metadata", &path);
+    Ok(())
+}
+
+/* Write a proof of the given kind to the given path as write_artifact does,
+ * or wrapped in the given container along with its metadata, whose public
+ * inputs must then be given. */
+#[allow(clippy::too_many_arguments)]
+pub fn write_proof<E: std::fmt::Debug>(
+    outcome: &Outcome,
+    path: &Path,
+    kind: ArtifactKind,
+    force: bool,
+    encoding: Encoding,
+    container: Option<ContainerFormat>,
+    public_inputs: Option<&[(String, String)]>,
+    serialize: impl FnOnce(&mut Vec<u8>) -> Result<(), E>,
+) -> Result<(), CommandError> {
+    match container {
+        None => write_artifact(path, kind, force, encoding, serialize).map(|_| ()),
+        Some(ContainerFormat::Cbor) => artifact_bytes(kind, serialize).and_then(|proof| {
+            let public_inputs = public_inputs.expect("public inputs are given for containers").to_vec();
+            let container = ProofContainer { metadata: proof_metadata(outcome, kind, public_inputs), proof };
+            write_output(path, encoding.encode(container.to_cbor()), force)
+        }),
+    }.map_err(CommandError::Input)
+}
+
+/* Show the metadata of the given proof, if any, to the given outcome: that
+ * inside it if it is a container, or otherwise that in the file beside it. It
+ * plays no part in verification, so problems with it are only warned of. */
+pub fn show_proof_metadata(outcome: &mut Outcome, proof: &Path) {
+    let found = match read_container(proof) {
+        Ok(Some(container)) => Ok(Some((container.metadata, proof.to_path_buf()))),
+        Ok(None) => read_metadata(proof).map(|metadata| metadata.map(|metadata| (metadata, metadata_path(proof)))),
+        Err(err) => Err(err),
+    };
+    match found {
+        Ok(Some((metadata, source))) => {
+            if outcome.circuit_hash.as_ref().is_some_and(|hash| *hash != metadata.circuit_hash) {
+                warn!(
+                    "* Warning: {} describes a proof for circuit {}, not the one given",
+                    source.to_string_lossy(), metadata.circuit_hash,
+                );
+            }
+            metadata.report(outcome);
+        },
+        Ok(None) => {},
+        Err(err) => warn!("* Warning: ignoring unreadable proof metadata: {}", err),
+    }
+}
+
+/* Log the public variables that a circuit was specialized to. */
+pub fn print_defines(defines: &[(String, String)]) {
+    for (name, value) in defines {
+        info!("** Public variable {} fixed to {} at compile time", name, value);
+    }
+}
+
+/* Parse the given prelude and sources and compile them as
+ * sources::compile_sources does, reporting the warnings raised in the given
+ * format whether or not compilation succeeds. Errors that come with a
+ * diagnostic are left for main to report in the same format. */
+pub fn compile_and_report(
+    key: Option<CacheKey>,
+    prelude: &Prelude,
+    sources: &[PathOrStdio],
+    warn_shadowing: bool,
+    field_ops: &dyn FieldOps,
+    options: &CompileOptions,
+    format: DiagnosticsFormat,
+) -> Result<(Module, ModuleStats), CommandError> {
+    let mut warnings = vec![];
+    let compiled = compile_sources(key, prelude, sources, warn_shadowing, field_ops, options, &mut warnings);
+    report(&warnings, format);
+    compiled.map_err(|err| CommandError::diagnosed(err, format))
+}
+
+/* Parse the given prelude and sources and compile them over the given field
+ * with the given options, neither reusing nor recording compilations in the
+ * cache, for commands that only evaluate or compare them. Warnings about
+ * shadowed bindings are left out, and the rest are reported to people. */
+pub fn compile_uncached(
+    prelude: &Prelude,
+    sources: &[PathOrStdio],
+    field_ops: &dyn FieldOps,
+    options: &CompileOptions,
+) -> Result<Module, CommandError> {
+    let module = parse_sources(prelude, sources, &mut vec![])?;
+    let mut warnings = vec![];
+    let compiled = compile_module(module, field_ops, options, &mut warnings);
+    report(&warnings, DiagnosticsFormat::Human);
+    Ok(compiled?.module)
+}
+
+/* Write the given compiled module to the given path in a stable textual form:
+ * the public variables, then the definitions used to derive witnesses, then
+ * the constraints, each on a line of its own. */
+pub fn write_ir(module: &Module, path: &Path, force: bool) -> Result<(), CommandError> {
+    let mut ir = String::new();
+    ir.push_str("// Public variables\n");
+    for var in &module.pubs {
+        ir.push_str(&format!("pub {};\n", var));
+    }
+    ir.push_str("// Definitions\n");
+    for def in &module.defs {
+        ir.push_str(&format!("{};\n", def));
+    }
+    ir.push_str("// Constraints\n");
+    for expr in &module.exprs {
+        ir.push_str(&format!("{};\n", expr));
+    }
+    write_output(path, ir, force).map_err(CommandError::Input)
+}
+
+/* Write the interface of the given compiled module to the given path as a
+ * JSON document, as described in the interface module. */
+pub fn write_interface(module: &Module, path: &Path, force: bool) -> Result<(), CommandError> {
+    let interface = module.interface().to_json();
+    let document = serde_json::to_string_pretty(&interface).expect("JSON values always serialize") + "\n";
+    write_output(path, document, force).map_err(CommandError::Input)
+}
+
+/* Check that the given assignment, with the witnesses already derived from
+ * it, satisfies every constraint of the given module. Otherwise the violated
+ * constraints are the result, so they go with the error. */
+pub fn check_constraints(
+    module: &Module,
+    assigns: &HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Result<(), CommandError> {
+    let violated = violated_constraints(module, assigns, field_ops);
+    if violated.is_empty() {
+        return Ok(());
+    }
+    let lines: Vec<_> = violated.iter()
+        .map(|expr| format!("** Constraint {} is violated", expr))
+        .collect();
+    Err(CommandError::Failure(format!(
+        "{} of {} constraint(s) are violated\n{}",
+        violated.len(), module.exprs.len(), lines.join("\n"),
+    )))
+}
+
+/* Check, for check or a prover given --dry-run, that the given inputs satisfy
+ * the constraints of the given module once its witnesses are derived from
+ * them, so that bad inputs are found without the cost of proving. Returns the
+ * value of every variable, for provers that take them as they are. */
+pub fn precheck_inputs(
+    module: &Module,
+    inputs: &HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Result<HashMap<VariableId, BigInt>, CommandError> {
+    info!("* Checking constraints...");
+    let mut assigns = inputs.clone();
+    derive_witnesses(module, &mut assigns, field_ops)
+        .map_err(|err| CommandError::Input(err.to_string()))?;
+    check_constraints(module, &assigns, field_ops)?;
+    Ok(assigns)
+}
+
+/* Note in the given dry run whether the compilation under the given key, if
+ * any, would be reused from the cache or recorded in it. The key is only
+ * given back when there is a compilation to reuse, so that a dry run writes
+ * nothing to the cache. */
+pub fn dry_run_cache_key(key: Option<CacheKey>, dry_run: &mut DryRun) -> Option<CacheKey> {
+    let key = key?;
+    let path = key.path();
+    if path.exists() {
+        dry_run.cache(format!("would reuse the compilation cached in {}", path.to_string_lossy()));
+        Some(key)
+    } else {
+        dry_run.cache(format!("would record the compilation in {}", path.to_string_lossy()));
+        None
+    }
+}
+
+/* Open the cache of public parameters and keys in the given directory, if
+ * any, unless told not to. */
+#[cfg(any(feature = "plonk", feature = "groth16"))]
+pub fn open_key_cache(no_cache: bool, cache_dir: &Option<PathBuf>) -> Option<KeyCache> {
+    if no_cache {
+        None
+    } else {
+        KeyCache::open(cache_dir.as_deref())
+    }
+}
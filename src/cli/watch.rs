@@ -0,0 +1,127 @@
+use crate::cli::shared::{compile_and_report, ProofSystems};
+use crate::sources::{cache_key, Prelude};
+use crate::diagnostics::{report, DiagnosticsFormat};
+use crate::inputs::{resolve_file_inputs, InputScope, InputsFormat};
+use crate::status::{CommandError, CommandResult};
+use crate::stdio::{is_stdin, ClaimStdio, PathOrStdio, StdioClaims};
+use crate::transform::{derive_witnesses, violated_constraints, CompileOptions, FieldOps, DEFAULT_INLINE_LIMIT, DEFAULT_UNROLL_LIMIT, MAX_OPT_LEVEL};
+use crate::watch::Watcher;
+use log::info;
+
+use clap::Args;
+
+use std::time::{Duration, Instant};
+
+#[derive(Args)]
+pub struct SourceWatch {
+    /// Paths to source files to be compiled, in order
+    #[arg(required_unless_present = "source")]
+    sources: Vec<PathOrStdio>,
+    /// Path to a source file to be compiled after any positional ones
+    #[arg(short, long)]
+    source: Vec<PathOrStdio>,
+    /// Prelude merged before the sources: none, std, or a path to a source file
+    #[arg(long, default_value = "none")]
+    prelude: Prelude,
+    /// Backend whose field and cost model the sources are compiled for
+    #[arg(long, value_enum, default_value_t = ProofSystems::Halo2)]
+    backend: ProofSystems,
+    /// Maximum depth to which recursive definitions are unrolled
+    #[arg(long, default_value_t = DEFAULT_UNROLL_LIMIT)]
+    unroll_limit: usize,
+    /// Optimization level at which the sources are compiled
+    #[arg(short = 'O', long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=MAX_OPT_LEVEL as i64))]
+    opt_level: u8,
+    /// Path to a file of inputs checked against every compilation, which is
+    /// watched as well
+    #[arg(long)]
+    check_inputs: Option<PathOrStdio>,
+    /// Format of the inputs file, detected from its extension by default
+    #[arg(long, value_enum)]
+    format: Option<InputsFormat>,
+    /// Neither reuse nor record compilations in the .vampir-cache directory
+    #[arg(long)]
+    no_cache: bool,
+    /// Milliseconds for which files must stay unchanged before recompiling
+    #[arg(long, default_value_t = 200)]
+    debounce_ms: u64,
+}
+
+/* Compile the given sources as the watch subcommand does, and check the
+ * inputs in the given file against the result if any, printing a line with
+ * the time taken for each step. */
+fn watch_round(args: &SourceWatch, sources: &[PathOrStdio], field_ops: &dyn FieldOps) -> Result<(), CommandError> {
+    let start = Instant::now();
+    // Compilations are cached just as halo2 compile and plonk compile cache
+    // them at the default options, so each can reuse the other's
+    let options = format!(
+        "unroll-limit={} warn-shadowing=false deny-warnings=false opt-level={} inline-limit={} defines=[]",
+        args.unroll_limit, args.opt_level, DEFAULT_INLINE_LIMIT,
+    );
+    let key = (!args.no_cache).then(|| cache_key(&args.backend.name(), &options, &args.prelude, sources)).transpose()?;
+    let options = CompileOptions::default()
+        .unroll_limit(args.unroll_limit)
+        .opt_level(args.opt_level)
+        .cost_model(args.backend.cost_model());
+    let (module, _) = compile_and_report(key, &args.prelude, sources, false, field_ops, &options, DiagnosticsFormat::Human)?;
+    println!(
+        "* Compiled in {:.1} ms: {} constraint(s)",
+        start.elapsed().as_secs_f64() * 1000.0, module.exprs.len(),
+    );
+    let Some(path) = &args.check_inputs else { return Ok(()) };
+    let start = Instant::now();
+    let mut assigns = resolve_file_inputs(&module, &[(path.as_path(), InputScope::All)], args.format, false, &[])?;
+    if let Err(err) = derive_witnesses(&module, &mut assigns, field_ops) {
+        println!("* Inputs cannot be evaluated: {}", err);
+        return Ok(());
+    }
+    let violated = violated_constraints(&module, &assigns, field_ops);
+    let millis = start.elapsed().as_secs_f64() * 1000.0;
+    if violated.is_empty() {
+        println!("* Inputs satisfy all {} constraint(s) in {:.1} ms", module.exprs.len(), millis);
+    } else {
+        println!("* Inputs violate {} of {} constraint(s) in {:.1} ms", violated.len(), module.exprs.len(), millis);
+        for expr in violated {
+            println!("** Constraint {} is violated", expr);
+        }
+    }
+    Ok(())
+}
+
+/* Implements the subcommand that recompiles source files whenever they
+ * change, until interrupted. Errors in the sources or inputs are reported and
+ * then waited out rather than ending the command. */
+pub fn watch_cmd(args: &SourceWatch) -> CommandResult {
+    let sources: Vec<_> = args.sources.iter().chain(&args.source).cloned().collect();
+    let mut watched: Vec<_> = sources.iter().map(|source| source.to_path_buf()).collect();
+    if let Prelude::Path(path) = &args.prelude {
+        watched.push(path.clone());
+    }
+    watched.extend(args.check_inputs.iter().map(|path| path.to_path_buf()));
+    if watched.iter().any(|path| is_stdin(path)) {
+        return Err(CommandError::Input("standard input cannot be watched for changes".to_string()));
+    }
+    let field_ops = args.backend.field_ops();
+    let mut watcher = Watcher::new(watched, Duration::from_millis(args.debounce_ms));
+    loop {
+        let start = Instant::now();
+        if let Err(err) = watch_round(args, &sources, field_ops.as_ref()) {
+            if let CommandError::Diagnosed(diagnostic, format) = &err {
+                report(std::slice::from_ref(diagnostic), *format);
+            }
+            println!("* Failed after {:.1} ms: {}", start.elapsed().as_secs_f64() * 1000.0, err);
+        }
+        info!("* Watching {} file(s) for changes...", watcher.paths().len());
+        let changed: Vec<_> = watcher.wait().iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        info!("* Changed: {}", changed.join(", "));
+    }
+}
+
+impl ClaimStdio for SourceWatch {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        claims.reads("--source", self.sources.iter().chain(&self.source))
+            .reads("--check-inputs", &self.check_inputs);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::ast::{Expr, InfixOp, Module, Pat, TExpr, Variable};
+use crate::transform::required_inputs;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+
+/* The version of the layout of constraint documents. Like that of interface
+ * documents, it is raised whenever a field is removed or changes meaning. */
+pub const CONSTRAINTS_VERSION: u64 = 1;
+
+/* A variable as it is written in constraint documents, by its id and the name
+ * that it was given in the program or derived for it by the compiler. */
+fn variable_json(var: &Variable) -> Value {
+    json!({ "kind": "variable", "id": var.id, "name": var.name })
+}
+
+/* The given operand of an operation as it is written in constraint
+ * documents: a variable, a constant as a decimal string so that no reader
+ * rounds it, or an operation on further operands. */
+fn operand_json(expr: &TExpr) -> Result<Value, String> {
+    match &expr.v {
+        Expr::Variable(var) => Ok(variable_json(var)),
+        Expr::Constant(c) => Ok(json!({ "kind": "constant", "value": c.to_string() })),
+        Expr::Negate(_) | Expr::Infix(_, _, _) => {
+            let (operator, operands) = operation_json(expr)?;
+            Ok(json!({ "kind": "operation", "operator": operator, "operands": operands }))
+        },
+        _ => Err(format!("{} is not arithmetic", expr)),
+    }
+}
+
+/* The operator of the given expression and its operands. A negation is
+ * written as - with a single operand, and a lone variable or constant as =
+ * with itself as its operand. */
+fn operation_json(expr: &TExpr) -> Result<(String, Vec<Value>), String> {
+    match &expr.v {
+        Expr::Infix(op, lhs, rhs) => Ok((op.to_string(), vec![operand_json(lhs)?, operand_json(rhs)?])),
+        Expr::Negate(operand) => Ok(("-".to_string(), vec![operand_json(operand)?])),
+        _ => Ok(("=".to_string(), vec![operand_json(expr)?])),
+    }
+}
+
+/* Collect the ids of the variables that the given expression reads. */
+fn collect_dependencies(expr: &TExpr, ids: &mut BTreeSet<u32>) {
+    match &expr.v {
+        Expr::Variable(var) => { ids.insert(var.id); },
+        Expr::Negate(operand) => collect_dependencies(operand, ids),
+        Expr::Infix(_, lhs, rhs) => {
+            collect_dependencies(lhs, ids);
+            collect_dependencies(rhs, ids);
+        },
+        _ => {},
+    }
+}
+
+impl Module {
+    /* Write the constraint system of this compiled module as a JSON document
+     * of its version, its variables with whether each is an input that the
+     * prover supplies or defined by the module, its public variables, the
+     * definitions by which the prover derives variables with the ids of the
+     * variables that each reads, and its constraints. Each definition and
+     * constraint is written as an operator, its operands, and the result that
+     * they equal. Fails if the module has not been compiled down to arithmetic
+     * on variables and constants. */
+    pub fn constraints_json(&self) -> Result<Value, String> {
+        let inputs: BTreeSet<_> = required_inputs(self).iter().map(|var| var.id).collect();
+        let mut variables = vec![];
+        let mut seen = BTreeSet::new();
+        let mut record = |var: &Variable| if seen.insert(var.id) {
+            let role = if inputs.contains(&var.id) { "input" } else { "defined" };
+            variables.push(json!({ "id": var.id, "name": var.name, "role": role }));
+        };
+        for var in required_inputs(self) {
+            record(&var);
+        }
+        let mut definitions = vec![];
+        for def in &self.defs {
+            let var = match &def.0.0.v {
+                Pat::Variable(var) => var,
+                _ => return Err(format!("definition {} does not bind a single variable", def)),
+            };
+            record(var);
+            let (operator, operands) = operation_json(&def.0.1)
+                .map_err(|err| format!("definition of {}: {}", var, err))?;
+            let mut depends_on = BTreeSet::new();
+            collect_dependencies(&def.0.1, &mut depends_on);
+            definitions.push(json!({
+                "operator": operator,
+                "operands": operands,
+                "result": variable_json(var),
+                "depends_on": depends_on,
+            }));
+        }
+        let mut constraints = vec![];
+        for expr in &self.exprs {
+            let (lhs, rhs) = match &expr.v {
+                Expr::Infix(InfixOp::Equal, lhs, rhs) => (lhs, rhs),
+                _ => return Err(format!("constraint {} is not an equation", expr)),
+            };
+            let (operator, operands) = operation_json(lhs)
+                .map_err(|err| format!("constraint {}: {}", expr, err))?;
+            let result = operand_json(rhs).map_err(|err| format!("constraint {}: {}", expr, err))?;
+            constraints.push(json!({ "operator": operator, "operands": operands, "result": result }));
+        }
+        let public: Vec<_> = self.pubs.iter().map(variable_json).collect();
+        Ok(json!({
+            "version": CONSTRAINTS_VERSION,
+            "variables": variables,
+            "public": public,
+            "definitions": definitions,
+            "constraints": constraints,
+        }))
+    }
+}
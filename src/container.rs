@@ -0,0 +1,187 @@
+use crate::artifact::read_header;
+use crate::encoding::decode;
+use crate::metadata::ProofMetadata;
+use ciborium::value::Value;
+use clap::ValueEnum;
+use std::path::Path;
+use std::time::Duration;
+
+/* The CBOR tag wrapping every proof container, the ASCII of "vamp". */
+pub const CONTAINER_TAG: u64 = 0x7661_6d70;
+
+/* The bytes that every proof container starts with: the head of its tag. */
+pub const CONTAINER_START: &[u8; 5] = b"\xdavamp";
+
+/* The version of the layout of the map inside the tag. Containers of other
+ * versions are refused rather than misread. */
+pub const CONTAINER_VERSION: u64 = 1;
+
+/* The self-describing envelopes that proofs can be written in, besides the
+ * bare proof files of vamp-ir. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum ContainerFormat {
+    /// A tagged CBOR map of the backend, curve, circuit digest, public
+    /// inputs, proof, and how it was made
+    Cbor,
+}
+
+/* A proof in a self-describing envelope. The proof is kept as vamp-ir writes
+ * proof files in binary, header and all, so that it is read just as a bare
+ * proof file is. Everything else describes it: like the metadata files
+ * written beside proofs, none of it is read when verifying, which checks the
+ * public inputs carried by the proof itself. */
+pub struct ProofContainer {
+    pub metadata: ProofMetadata,
+    pub proof: Vec<u8>,
+}
+
+fn text(value: &str) -> Value {
+    Value::Text(value.to_string())
+}
+
+/* The value of the given key of a decoded map, if it has one. */
+fn entry<'a>(entries: &'a [(Value, Value)], key: &str) -> Result<&'a Value, String> {
+    entries.iter()
+        .find(|(k, _)| matches!(k, Value::Text(k) if k == key))
+        .map(|(_, value)| value)
+        .ok_or_else(|| format!("proof container has no {}", key))
+}
+
+fn text_entry(entries: &[(Value, Value)], key: &str) -> Result<String, String> {
+    match entry(entries, key)? {
+        Value::Text(value) => Ok(value.clone()),
+        _ => Err(format!("{} of proof container is not text", key)),
+    }
+}
+
+fn integer_entry(entries: &[(Value, Value)], key: &str) -> Result<u64, String> {
+    match entry(entries, key)? {
+        Value::Integer(value) => u64::try_from(*value)
+            .map_err(|_| format!("{} of proof container is out of range", key)),
+        _ => Err(format!("{} of proof container is not an integer", key)),
+    }
+}
+
+fn map_entries(value: &Value, what: &str) -> Result<Vec<(Value, Value)>, String> {
+    match value {
+        Value::Map(entries) => Ok(entries.clone()),
+        _ => Err(format!("{} is not a map", what)),
+    }
+}
+
+impl ProofContainer {
+    /* Encode this container as the given tag around a map with text keys,
+     * in the order below. Integers and lengths take the fewest bytes that
+     * hold them, so the same container is always encoded the same way:
+     *   version: CONTAINER_VERSION
+     *   backend, curve: as named by inspect, such as halo2 and pallas
+     *   circuit_hash: the content digest of the circuit, as text
+     *   public_inputs: an array of [name, decimal value] arrays, in the
+     *     order that the public variables are declared
+     *   proof: the bytes of the proof file
+     *   metadata: a map of tool_version, prover_us, and timestamp */
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let metadata = &self.metadata;
+        let public_inputs = metadata.public_inputs.iter()
+            .map(|(name, value)| Value::Array(vec![text(name), text(value)]))
+            .collect();
+        let prover_us = u64::try_from(metadata.prover_time.as_micros()).unwrap_or(u64::MAX);
+        let about = Value::Map(vec![
+            (text("tool_version"), text(&metadata.tool_version)),
+            (text("prover_us"), Value::Integer(prover_us.into())),
+            (text("timestamp"), Value::Integer(metadata.timestamp.into())),
+        ]);
+        let map = Value::Map(vec![
+            (text("version"), Value::Integer(CONTAINER_VERSION.into())),
+            (text("backend"), text(&metadata.backend)),
+            (text("curve"), text(&metadata.curve)),
+            (text("circuit_hash"), text(&metadata.circuit_hash)),
+            (text("public_inputs"), Value::Array(public_inputs)),
+            (text("proof"), Value::Bytes(self.proof.clone())),
+            (text("metadata"), about),
+        ]);
+        let mut bytes = vec![];
+        ciborium::ser::into_writer(&Value::Tag(CONTAINER_TAG, Box::new(map)), &mut bytes)
+            .expect("writing to memory cannot fail");
+        bytes
+    }
+
+    /* Decode a container encoded as to_cbor does. Keys may come in any order
+     * and unknown keys are ignored, but the proof must be a proof file of the
+     * backend and curve that the container names. */
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, String> {
+        let value: Value = ciborium::de::from_reader(bytes)
+            .map_err(|err| format!("malformed proof container: {}", err))?;
+        let map = match value {
+            Value::Tag(CONTAINER_TAG, map) => map,
+            _ => return Err("not a vamp-ir proof container".to_string()),
+        };
+        let entries = map_entries(&map, "proof container")?;
+        let version = integer_entry(&entries, "version")?;
+        if version != CONTAINER_VERSION {
+            return Err(format!(
+                "version {} of the proof container is not supported, only version {}",
+                version, CONTAINER_VERSION,
+            ));
+        }
+        let public_inputs = match entry(&entries, "public_inputs")? {
+            Value::Array(inputs) => inputs.iter().map(|input| match input {
+                Value::Array(pair) => match pair.as_slice() {
+                    [Value::Text(name), Value::Text(value)] => Ok((name.clone(), value.clone())),
+                    _ => Err("public inputs of proof container must be a name and a value".to_string()),
+                },
+                _ => Err("public inputs of proof container must be a name and a value".to_string()),
+            }).collect::<Result<Vec<_>, _>>()?,
+            _ => return Err("public_inputs of proof container is not an array".to_string()),
+        };
+        let proof = match entry(&entries, "proof")? {
+            Value::Bytes(proof) => proof.clone(),
+            _ => return Err("proof of proof container is not a byte string".to_string()),
+        };
+        let about = map_entries(entry(&entries, "metadata")?, "metadata of proof container")?;
+        let metadata = ProofMetadata {
+            tool_version: text_entry(&about, "tool_version")?,
+            backend: text_entry(&entries, "backend")?,
+            curve: text_entry(&entries, "curve")?,
+            circuit_hash: text_entry(&entries, "circuit_hash")?,
+            prover_time: Duration::from_micros(integer_entry(&about, "prover_us")?),
+            timestamp: integer_entry(&about, "timestamp")?,
+            public_inputs,
+        };
+        let (header, _) = read_header(proof.as_slice())?;
+        match header {
+            Some(header) if header.kind.backend() == metadata.backend && header.kind.curve() == metadata.curve => {},
+            Some(header) => return Err(format!(
+                "proof container holds a {} over {}, but claims to hold one of {} over {}",
+                header.kind, header.kind.curve(), metadata.backend, metadata.curve,
+            )),
+            None => return Err("proof container does not hold a file written by vamp-ir".to_string()),
+        }
+        Ok(Self { metadata, proof })
+    }
+}
+
+/* The proof file inside the given contents if they are a container, in any
+ * of the encodings, or otherwise the given contents as they are. */
+pub fn unwrap_container(contents: Vec<u8>) -> Result<Vec<u8>, String> {
+    let contents = decode(contents, CONTAINER_START);
+    if !contents.starts_with(CONTAINER_START) {
+        return Ok(contents);
+    }
+    ProofContainer::from_cbor(&contents).map(|container| container.proof)
+}
+
+/* Read the container at the given path, if the file there is one. Standard
+ * input can only be read once, so it is never taken to be one. */
+pub fn read_container(path: &Path) -> Result<Option<ProofContainer>, String> {
+    if crate::stdio::is_stdin(path) {
+        return Ok(None);
+    }
+    let name = path.to_string_lossy();
+    let contents = std::fs::read(path).map_err(|err| format!("{}: {}", name, err))?;
+    let contents = decode(contents, CONTAINER_START);
+    if !contents.starts_with(CONTAINER_START) {
+        return Ok(None);
+    }
+    ProofContainer::from_cbor(&contents).map(Some).map_err(|err| format!("{}: {}", name, err))
+}
@@ -0,0 +1,199 @@
+use crate::ast::Rule;
+use crate::render::Renderer;
+use clap::ValueEnum;
+use log::{log, Level};
+use pest::error::{Error, ErrorVariant, LineColLocation};
+use pest::Span;
+use serde_json::{json, Value};
+use std::fmt;
+
+/* How serious a diagnostic is. Errors stop compilation, warnings do not
+ * unless warnings are denied. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/* A region of a source file. Lines and columns are counted from one. */
+#[derive(Clone, Debug)]
+pub struct SourceSpan {
+    // Path of the file containing the region, if it came from one
+    pub file: Option<String>,
+    // Line and column at which the region starts
+    pub start: (usize, usize),
+    // Line and column at which the region ends
+    pub end: (usize, usize),
+    // Text of the line on which the region starts, if known, for showing it
+    pub line: Option<String>,
+}
+
+impl From<&Span<'_>> for SourceSpan {
+    fn from(span: &Span) -> Self {
+        Self {
+            file: None,
+            start: span.start_pos().line_col(),
+            end: span.end_pos().line_col(),
+            line: Some(span.start_pos().line_of().trim_end_matches(['\n', '\r']).to_string()),
+        }
+    }
+}
+
+impl fmt::Display for SourceSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(f, "{}:{}", self.start.0, self.start.1)
+    }
+}
+
+/* The words that the keyword rule of vampir.pest keeps from naming variables
+ * or functions, with the syntax that took over those that programs written
+ * before may still use as names. */
+const RESERVED_WORDS: [(&str, Option<&str>); 6] = [
+    ("fun", None),
+    ("def", None),
+    ("pub", None),
+    ("let", Some("let-in expressions")),
+    ("in", Some("let-in expressions")),
+    ("module", Some("module declarations")),
+];
+
+/* The reserved word, if any, that starts at the given column, counted from
+ * one, of the given line. A word that newer syntax took over may instead end
+ * just before it, as that syntax consumes the word before the parse fails. */
+fn reserved_word_at(line: &str, column: usize) -> Option<(&'static str, Option<&'static str>)> {
+    let is_word = |c: &char| c.is_ascii_alphanumeric() || *c == '_';
+    let chars: Vec<char> = line.chars().collect();
+    let column = column.saturating_sub(1).min(chars.len());
+    let after: String = chars[column..].iter().take_while(|c| is_word(c)).collect();
+    let before: Vec<char> = chars[..column].iter().rev().skip_while(|c| c.is_whitespace()).take_while(|c| is_word(c)).copied().collect();
+    let before: String = before.into_iter().rev().collect();
+    let reserved = |word: &str| RESERVED_WORDS.iter().find(|(reserved, _)| *reserved == word).copied();
+    reserved(&after).or_else(|| reserved(&before).filter(|(_, syntax)| syntax.is_some()))
+}
+
+/* A warning or error raised while compiling, kept as data so that tools can
+ * consume it as well as people. */
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    // Stable identifier of the kind of problem, such as shadowed-binding
+    pub code: &'static str,
+    pub message: String,
+    // Region of the source that the problem was found in, if known
+    pub span: Option<SourceSpan>,
+    // Further explanations of the problem, in order
+    pub notes: Vec<String>,
+    // Change that would likely resolve the problem
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn warning(code: &'static str, message: String) -> Self {
+        Self { severity: Severity::Warning, code, message, span: None, notes: vec![], suggestion: None }
+    }
+
+    pub fn error(code: &'static str, message: String) -> Self {
+        Self { severity: Severity::Error, code, message, span: None, notes: vec![], suggestion: None }
+    }
+
+    pub fn with_span(mut self, span: SourceSpan) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: String) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+
+    /* Make an error from the given failure to parse the given file, whose
+     * contents are given. */
+    pub fn from_parse_error(err: &Error<Rule>, file: &str, source: &str) -> Self {
+        let message = match &err.variant {
+            ErrorVariant::CustomError { message } => message.clone(),
+            ErrorVariant::ParsingError { positives, .. } if !positives.is_empty() => {
+                let expected: Vec<_> = positives.iter().map(|rule| format!("{:?}", rule)).collect();
+                format!("expected {}", expected.join(" or "))
+            },
+            ErrorVariant::ParsingError { .. } => "unexpected input".to_string(),
+        };
+        let (start, end) = match err.line_col {
+            LineColLocation::Pos(pos) => (pos, pos),
+            LineColLocation::Span(start, end) => (start, end),
+        };
+        let line = source.lines().nth(start.0.saturating_sub(1)).map(str::to_string);
+        let reserved = line.as_deref().and_then(|line| reserved_word_at(line, start.1));
+        let mut diagnostic = Self::error("parse-error", message)
+            .with_span(SourceSpan { file: Some(file.to_string()), start, end, line });
+        // Names that later syntax took over fail to parse where they are used
+        if let Some((word, syntax)) = reserved {
+            diagnostic = diagnostic.with_note(format!("{} is a reserved word and cannot name a variable or function", word));
+            if let Some(syntax) = syntax {
+                diagnostic = diagnostic
+                    .with_note(format!("{} became reserved with {}", word, syntax))
+                    .with_suggestion(format!("rename {} to something else, such as {}_", word, word));
+            }
+        }
+        diagnostic
+    }
+
+    /* Render this diagnostic as a JSON object. */
+    pub fn to_json(&self) -> Value {
+        let span = self.span.as_ref().map(|span| json!({
+            "file": span.file,
+            "start": { "line": span.start.0, "column": span.start.1 },
+            "end": { "line": span.end.0, "column": span.end.1 },
+        }));
+        json!({
+            "severity": self.severity.name(),
+            "code": self.code,
+            "message": self.message,
+            "span": span,
+            "notes": self.notes,
+            "suggestion": self.suggestion,
+        })
+    }
+}
+
+/* The ways in which diagnostics can be reported. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum DiagnosticsFormat {
+    /// Prose on standard error, interleaved with the progress messages, in
+    /// color when standard error is a terminal
+    Human,
+    /// A JSON object per line on standard error
+    Json,
+}
+
+/* Report the given diagnostics in the given format. */
+pub fn report(diagnostics: &[Diagnostic], format: DiagnosticsFormat) {
+    for diagnostic in diagnostics {
+        match format {
+            DiagnosticsFormat::Human => {
+                let level = match diagnostic.severity {
+                    Severity::Warning => Level::Warn,
+                    Severity::Error => Level::Error,
+                };
+                log!(level, "{}", Renderer::current().render(diagnostic));
+            },
+            DiagnosticsFormat::Json => eprintln!("{}", diagnostic.to_json()),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::status::Outcome;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/* What a command given --dry-run found that it would do, in place of the
+ * expensive cryptography that it skipped. Sizes are estimates made from the
+ * padded size of the circuit, since the artifacts were never made. */
+#[derive(Default)]
+pub struct DryRun {
+    // Base 2 logarithm of the padded number of rows of the circuit
+    pub k: Option<u32>,
+    // Estimated sizes in bytes of the artifacts involved, or none for those
+    // that the backend never serializes
+    sizes: Vec<(&'static str, Option<u64>)>,
+    // Files that would have been written, by role
+    outputs: Vec<(&'static str, PathBuf)>,
+    // How each cache would have been used
+    cache: Vec<String>,
+}
+
+/* Write the given number of bytes in the largest binary unit that keeps it at
+ * least one. */
+fn describe_size(bytes: u64) -> String {
+    let units = ["KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = "bytes";
+    for next in units {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    if unit == "bytes" {
+        format!("{} bytes", bytes)
+    } else {
+        format!("{:.1} {}", size, unit)
+    }
+}
+
+impl DryRun {
+    pub fn size(&mut self, item: &'static str, bytes: Option<u64>) {
+        self.sizes.push((item, bytes));
+    }
+
+    pub fn output(&mut self, role: &'static str, path: &Path) {
+        self.outputs.push((role, path.to_path_buf()));
+    }
+
+    pub fn cache(&mut self, note: String) {
+        self.cache.push(note);
+    }
+
+    /* Print what was found to the given outcome and record it there. */
+    pub fn report(self, outcome: &mut Outcome) {
+        outcome.println("* Dry run: no keys or proofs were made and no files were written");
+        if let Some(k) = self.k {
+            outcome.println(format!("** k: {} ({} rows)", k, 1u64 << k));
+        }
+        outcome.println("** Estimated sizes:");
+        for (item, bytes) in &self.sizes {
+            match bytes {
+                Some(bytes) => outcome.println(format!("*** {}: about {}", item, describe_size(*bytes))),
+                None => outcome.println(format!("*** {}: never written", item)),
+            }
+        }
+        outcome.println("** Would write:");
+        for (role, path) in &self.outputs {
+            outcome.println(format!("*** {}: {}", role, path.to_string_lossy()));
+        }
+        if !self.cache.is_empty() {
+            outcome.println("** Caches:");
+            for note in &self.cache {
+                outcome.println(format!("*** {}", note));
+            }
+        }
+        let sizes: serde_json::Map<_, _> = self.sizes.iter()
+            .map(|(item, bytes)| (item.to_string(), json!(bytes)))
+            .collect();
+        let outputs: serde_json::Map<_, _> = self.outputs.iter()
+            .map(|(role, path)| (role.to_string(), json!(path.to_string_lossy())))
+            .collect();
+        outcome.detail("dry_run", json!({
+            "k": self.k,
+            "estimated_bytes": sizes,
+            "outputs": outputs,
+            "cache": self.cache,
+        }));
+    }
+}
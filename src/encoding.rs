@@ -0,0 +1,46 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::ValueEnum;
+
+/* The encodings in which files written by vamp-ir can be stored. Text
+ * encodings suit pasting proofs into JSON-RPC requests and test fixtures. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum Encoding {
+    /// Raw bytes
+    Binary,
+    /// Lowercase hexadecimal digits on a single line
+    Hex,
+    /// Standard base64 on a single line
+    Base64,
+}
+
+impl Encoding {
+    /* Encode the given bytes in this encoding. */
+    pub fn encode(&self, bytes: Vec<u8>) -> Vec<u8> {
+        match self {
+            Encoding::Binary => bytes,
+            Encoding::Hex => (hex::encode(bytes) + "\n").into_bytes(),
+            Encoding::Base64 => (STANDARD.encode(bytes) + "\n").into_bytes(),
+        }
+    }
+}
+
+/* Undo whichever encoding the given contents are in, given the bytes that
+ * they start with when decoded. Text is only taken to be hexadecimal or
+ * base64 if it decodes to the expected start, so anything else, such as a
+ * file without a header, is left as raw bytes. */
+pub fn decode(contents: Vec<u8>, start: &[u8]) -> Vec<u8> {
+    if contents.starts_with(start) {
+        return contents;
+    }
+    let compact: String = match std::str::from_utf8(&contents) {
+        Ok(text) => text.split_whitespace().collect(),
+        Err(_) => return contents,
+    };
+    let digits = compact.strip_prefix("0x").unwrap_or(&compact);
+    [hex::decode(digits).ok(), STANDARD.decode(&compact).ok()]
+        .into_iter()
+        .flatten()
+        .find(|bytes| bytes.starts_with(start))
+        .unwrap_or(contents)
+}
@@ -0,0 +1,83 @@
+#[cfg(feature = "compiler")]
+use crate::diagnostics::Diagnostic;
+#[cfg(feature = "compiler")]
+use crate::transform::{CompileError, EvalError};
+use std::fmt;
+
+/* The ways in which the library can fail, each with a message fit to show to
+ * whoever supplied the program or inputs at fault. */
+#[derive(Debug)]
+pub enum Error {
+    // The source could not be parsed
+    #[cfg(feature = "compiler")]
+    Parse(Diagnostic),
+    // The sources could not be merged or compiled, as the diagnostic
+    // describes along with its notes and suggestions
+    #[cfg(feature = "compiler")]
+    Diagnosed(Diagnostic),
+    // The program is malformed or could not be compiled as asked
+    Compile(String),
+    // The inputs do not fit the circuit, or do not satisfy its constraints
+    Inputs(String),
+    // The proving system failed to set up, generate keys, or prove
+    Backend(String),
+    // The verifier rejected a proof
+    InvalidProof(String),
+    // An artifact could not be read or written
+    Artifact(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "compiler")]
+            Error::Parse(diagnostic) | Error::Diagnosed(diagnostic) => match &diagnostic.span {
+                Some(span) => write!(f, "{}: {}", span, diagnostic.message),
+                None => write!(f, "{}", diagnostic.message),
+            },
+            Error::Compile(message) => write!(f, "{}", message),
+            Error::Inputs(message) => write!(f, "{}", message),
+            Error::Backend(message) => write!(f, "{}", message),
+            Error::InvalidProof(message) => write!(f, "zero-knowledge proof is invalid: {}", message),
+            Error::Artifact(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(feature = "compiler")]
+impl Error {
+    /* The diagnostic that this error carries, if it was raised with one. */
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
+        match self {
+            Error::Parse(diagnostic) | Error::Diagnosed(diagnostic) => Some(diagnostic),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "compiler")]
+impl From<CompileError> for Error {
+    fn from(err: CompileError) -> Self {
+        match err.span() {
+            Some(span) => Error::Compile(format!("{}: {}", span, err)),
+            None => Error::Compile(err.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "compiler")]
+impl From<EvalError> for Error {
+    fn from(err: EvalError) -> Self {
+        Error::Inputs(err.to_string())
+    }
+}
+
+/* Get the message that a panic was raised with. */
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    match payload.downcast_ref::<String>() {
+        Some(message) => message.clone(),
+        None => payload.downcast_ref::<&str>().copied().unwrap_or("unknown error").to_string(),
+    }
+}
@@ -0,0 +1,190 @@
+use crate::ast::{Module, VariableId};
+use crate::error::Error;
+use crate::r1cs::R1cs;
+use crate::transform::{input_name, ordered_module_variables, FieldOps};
+#[cfg(feature = "zkinterface")]
+use crate::zkif;
+use ark_bls12_381::Fr as BlsScalar;
+use clap::ValueEnum;
+use halo2_proofs::pasta::Fp;
+use num_bigint::BigInt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/* The fields that programs can be evaluated, exported, and imported over. */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+pub enum Field {
+    /// Scalar field of the Pallas curve, used by Halo 2
+    Pallas,
+    /// Scalar field of the BLS12-381 curve, used by PLONK
+    #[value(name = "bls12-381")]
+    Bls12_381,
+    /// Scalar field of the BN254 curve
+    Bn254,
+}
+
+impl Field {
+    /* Make the operations of this field. */
+    pub fn ops(&self) -> Box<dyn FieldOps> {
+        match self {
+            Field::Pallas => Box::new(crate::halo2::field::PrimeFieldOps::<Fp>::default()),
+            Field::Bls12_381 => Box::new(crate::plonk::field::PrimeFieldOps::<BlsScalar>::default()),
+            Field::Bn254 => Box::new(crate::plonk::field::PrimeFieldOps::<ark_bn254::Fr>::default()),
+        }
+    }
+
+    /* The name by which this field is given on the command line. */
+    pub fn name(&self) -> String {
+        self.to_possible_value().expect("fields have names").get_name().to_string()
+    }
+
+    /* The prime that is the order of this field. */
+    pub fn prime(&self) -> BigInt {
+        self.ops().canonical(BigInt::from(-1)) + 1
+    }
+}
+
+/* The files that circuits can be exported to for other tools. */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+pub enum ExportFormat {
+    /// Rank-1 constraint system, in the .r1cs format of circom
+    R1cs,
+    /// Values of the wires of the rank-1 constraint system, in the .wtns
+    /// format of snarkjs
+    Wtns,
+    /// Rank-1 constraint system as zkinterface messages, along with the
+    /// values of its variables when inputs are given
+    Zkinterface,
+    /// Constraints, definitions, and public variables of the compiled
+    /// program as a JSON document for auditing
+    ConstraintsJson,
+}
+
+impl ExportFormat {
+    /* The role of the file written in this format among the outputs of a
+     * command. */
+    pub fn role(&self) -> &'static str {
+        match self {
+            ExportFormat::R1cs => "r1cs",
+            ExportFormat::Wtns => "wtns",
+            ExportFormat::Zkinterface => "zkif",
+            ExportFormat::ConstraintsJson => "constraints",
+        }
+    }
+}
+
+/* The files written by other tools that circuits can be imported from. */
+#[derive(Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+pub enum ImportFormat {
+    /// Rank-1 constraint system, in the .r1cs format of circom
+    R1cs,
+}
+
+/* What a circuit was exported to: the contents of the file, and a line
+ * summarizing them. */
+pub struct Exported {
+    pub contents: Vec<u8>,
+    pub summary: String,
+}
+
+/* Describe the value of every variable of the given module over the given
+ * field, as found in the given assignment, in a JSON document. Variables
+ * without values are listed separately. */
+pub fn witness_document(module: &Module, assigns: &HashMap<VariableId, BigInt>, field: Field) -> Value {
+    let mut known = vec![];
+    let mut unknown = vec![];
+    for var in ordered_module_variables(module) {
+        match assigns.get(&var.id) {
+            Some(value) => known.push(json!({
+                "name": input_name(&var), "id": var.id, "value": value.to_string(),
+            })),
+            None => unknown.push(json!({ "name": input_name(&var), "id": var.id })),
+        }
+    }
+    json!({
+        "field": field.name(),
+        "variables": known,
+        "unknown": unknown,
+    })
+}
+
+/* Export the given compiled module over the given field in the given format.
+ * The value of every variable, derived from inputs, is needed for witnesses,
+ * and is put alongside the constraints of zkinterface messages if given. The
+ * rank-1 constraint system and its witness are laid out the same way, so a
+ * witness fits the system exported from the same module. */
+pub fn export(
+    module: &Module,
+    format: ExportFormat,
+    field: Field,
+    assigns: Option<&HashMap<VariableId, BigInt>>,
+) -> Result<Exported, Error> {
+    if format == ExportFormat::ConstraintsJson {
+        let mut document = module.constraints_json().map_err(Error::Compile)?;
+        document["field"] = json!(field.name());
+        let contents = serde_json::to_string_pretty(&document).expect("JSON values always serialize") + "\n";
+        return Ok(Exported {
+            contents: contents.into_bytes(),
+            summary: format!(
+                "* Exported {} constraint(s) and {} definition(s) as JSON",
+                module.exprs.len(), module.defs.len(),
+            ),
+        });
+    }
+    let ops = field.ops();
+    let r1cs = R1cs::new(module, ops.as_ref())?;
+    let witness = assigns.map(|assigns| r1cs.witness(assigns, ops.as_ref())).transpose()?;
+    let mut contents = vec![];
+    let summary = match format {
+        ExportFormat::R1cs => {
+            r1cs.write_r1cs(&mut contents)
+                .map_err(|err| Error::Artifact(format!("cannot write constraint system: {}", err)))?;
+            format!("* Exported {} constraint(s) over {} wire(s)", r1cs.constraints.len(), r1cs.wire_count())
+        },
+        ExportFormat::Wtns => {
+            let witness = witness.ok_or_else(|| Error::Inputs("witnesses are only exported from inputs".to_string()))?;
+            r1cs.write_wtns(&witness, &mut contents)
+                .map_err(|err| Error::Artifact(format!("cannot write witness: {}", err)))?;
+            format!("* Exported the values of {} wire(s)", witness.len())
+        },
+        #[cfg(not(feature = "zkinterface"))]
+        ExportFormat::Zkinterface => return Err(Error::Compile(
+            "vamp-ir was built without zkinterface export".to_string(),
+        )),
+        #[cfg(feature = "zkinterface")]
+        ExportFormat::Zkinterface => {
+            zkif::write_zkif(&r1cs, witness.as_deref(), &mut contents)
+                .map_err(|err| Error::Artifact(format!("cannot write zkinterface messages: {}", err)))?;
+            format!(
+                "* Exported {} constraint(s) over {} wire(s){}",
+                r1cs.constraints.len(), r1cs.wire_count(),
+                if witness.is_some() { " with their values" } else { "" },
+            )
+        },
+        ExportFormat::ConstraintsJson => unreachable!("constraint documents are not rank-1 constraint systems"),
+    };
+    Ok(Exported { contents, summary })
+}
+
+/* Read the given file written by another tool in the given format as a
+ * rank-1 constraint system over the given field. The constraints are only
+ * meaningful over the field of the file, so any other field is refused. */
+pub fn import(bytes: &[u8], format: ImportFormat, field: Field) -> Result<R1cs, Error> {
+    let r1cs = match format {
+        ImportFormat::R1cs => R1cs::read_r1cs(bytes),
+    }?;
+    if r1cs.prime != field.prime() {
+        let actual = Field::value_variants().iter().find(|other| other.prime() == r1cs.prime);
+        return Err(Error::Inputs(match actual {
+            Some(actual) => format!(
+                "the constraints are over the field {}, not {}, so give --field {} and prove them with a backend over it",
+                actual.name(), field.name(), actual.name(),
+            ),
+            None => format!(
+                "the constraints are over the field of order {}, which no backend of vamp-ir proves over",
+                r1cs.prime,
+            ),
+        }));
+    }
+    Ok(r1cs)
+}
@@ -0,0 +1,218 @@
+//! C bindings for compiling, proving, and verifying Halo2 circuits, whose
+//! header is generated into include/vampir.h by cbindgen. The comments here
+//! are written as documentation so that cbindgen carries them into it.
+
+use crate::bindings;
+use crate::error::{panic_message, Error};
+use rand_core::OsRng;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+/// The call succeeded.
+pub const VAMPIR_OK: c_int = 0;
+/// The proof is invalid, or was made for other public values.
+pub const VAMPIR_INVALID_PROOF: c_int = 1;
+/// The source, inputs, or buffers given cannot be used.
+pub const VAMPIR_INPUT_ERROR: c_int = 2;
+/// The proving system failed, or vamp-ir hit a bug.
+pub const VAMPIR_INTERNAL_ERROR: c_int = 3;
+
+/// A buffer of bytes allocated by vamp-ir.
+///
+/// Every buffer that a function fills in belongs to the caller, who must
+/// release it with `vampir_buffer_free` and nothing else. Functions set the
+/// buffer to `{NULL, 0}` before doing anything, so it can be freed whether or
+/// not the call succeeded.
+#[repr(C)]
+pub struct VampirBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+thread_local! {
+    // The message of the error of the last call made on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: Option<String>) {
+    let message = message.map(|message| {
+        CString::new(message.replace('\0', "")).expect("nul bytes were removed")
+    });
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/* Run the body of an exported function, recording how it failed, if it did,
+ * and giving back the status that tells the caller. Failures are returned as
+ * errors, but a panic from a bug must still not unwind into C, so one is
+ * reported as an internal error. */
+fn run(f: impl FnOnce() -> Result<(), Error>) -> c_int {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => {
+            set_last_error(None);
+            VAMPIR_OK
+        },
+        Ok(Err(err)) => {
+            let status = match err {
+                Error::InvalidProof(_) => VAMPIR_INVALID_PROOF,
+                Error::Backend(_) => VAMPIR_INTERNAL_ERROR,
+                _ => VAMPIR_INPUT_ERROR,
+            };
+            set_last_error(Some(err.to_string()));
+            status
+        },
+        Err(payload) => {
+            set_last_error(Some(panic_message(payload.as_ref())));
+            VAMPIR_INTERNAL_ERROR
+        },
+    }
+}
+
+/* The bytes of the given buffer, which may be null only if it is empty. */
+unsafe fn bytes<'a>(data: *const u8, len: usize, name: &str) -> Result<&'a [u8], Error> {
+    if data.is_null() {
+        return match len {
+            0 => Ok(&[]),
+            _ => Err(Error::Inputs(format!("{} is null", name))),
+        };
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+/* The given nul-terminated string, which must be UTF-8. */
+unsafe fn text<'a>(string: *const c_char, name: &str) -> Result<&'a str, Error> {
+    if string.is_null() {
+        return Err(Error::Inputs(format!("{} is null", name)));
+    }
+    CStr::from_ptr(string).to_str().map_err(|_| Error::Inputs(format!("{} is not UTF-8", name)))
+}
+
+/* Empty the given buffer that is to be filled in, failing if there is none. */
+unsafe fn clear(out: *mut VampirBuffer, name: &str) -> Result<(), Error> {
+    if out.is_null() {
+        return Err(Error::Inputs(format!("{} is null", name)));
+    }
+    *out = VampirBuffer { data: ptr::null_mut(), len: 0 };
+    Ok(())
+}
+
+/* Hand the given bytes to the caller in the given buffer. */
+unsafe fn fill(out: *mut VampirBuffer, bytes: Vec<u8>) {
+    let len = bytes.len();
+    let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+    *out = VampirBuffer { data, len };
+}
+
+/// Compile the given source to a circuit of the given backend, filling in
+/// `circuit` with the contents of the circuit file that the command line
+/// would write. Only `"halo2"` is supported.
+///
+/// # Safety
+///
+/// `source` and `backend` must be nul-terminated strings, and `circuit` must
+/// point to a `VampirBuffer` that the caller does not hold allocated bytes in.
+#[no_mangle]
+pub unsafe extern "C" fn vampir_compile(
+    source: *const c_char,
+    backend: *const c_char,
+    circuit: *mut VampirBuffer,
+) -> c_int {
+    run(|| unsafe {
+        clear(circuit, "circuit")?;
+        let contents = bindings::compile(text(source, "source")?, text(backend, "backend")?)?;
+        fill(circuit, contents);
+        Ok(())
+    })
+}
+
+/// Prove the given circuit on the inputs of the given JSON object, which maps
+/// the names of the inputs to integers or to strings holding them, filling in
+/// `proof` with the contents of the proof file that the command line would
+/// write. The randomness that hides the witnesses is drawn from the given
+/// entropy, at least 32 bytes of it, or from the operating system if
+/// `entropy` is null.
+///
+/// # Safety
+///
+/// `circuit` must point to `circuit_len` readable bytes, `entropy` must be
+/// null or point to `entropy_len` readable bytes, `inputs_json` must be a
+/// nul-terminated string, and `proof` must point to a `VampirBuffer` that the
+/// caller does not hold allocated bytes in.
+#[no_mangle]
+pub unsafe extern "C" fn vampir_prove(
+    circuit: *const u8,
+    circuit_len: usize,
+    inputs_json: *const c_char,
+    entropy: *const u8,
+    entropy_len: usize,
+    proof: *mut VampirBuffer,
+) -> c_int {
+    run(|| unsafe {
+        clear(proof, "proof")?;
+        let circuit = bytes(circuit, circuit_len, "circuit")?;
+        let inputs_json = text(inputs_json, "inputs")?;
+        let contents = if entropy.is_null() {
+            bindings::prove(circuit, inputs_json, OsRng)?
+        } else {
+            let rng = bindings::seeded_rng(bytes(entropy, entropy_len, "entropy")?)?;
+            bindings::prove(circuit, inputs_json, rng)?
+        };
+        fill(proof, contents);
+        Ok(())
+    })
+}
+
+/// Check that the given proof of the given circuit is valid and was made for
+/// the public values of the given JSON object, which must name every public
+/// variable of the circuit and nothing else. Returns `VAMPIR_OK` if so, and
+/// `VAMPIR_INVALID_PROOF` if not.
+///
+/// # Safety
+///
+/// `circuit` and `proof` must point to `circuit_len` and `proof_len` readable
+/// bytes, and `public_json` must be a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn vampir_verify(
+    circuit: *const u8,
+    circuit_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+    public_json: *const c_char,
+) -> c_int {
+    run(|| unsafe {
+        bindings::verify(
+            bytes(circuit, circuit_len, "circuit")?,
+            bytes(proof, proof_len, "proof")?,
+            text(public_json, "public values")?,
+        )
+    })
+}
+
+/// The message of the error of the last call made on this thread, or null if
+/// it succeeded. The string belongs to vamp-ir, and stays valid until the
+/// next call made on this thread; it must not be freed.
+#[no_mangle]
+pub extern "C" fn vampir_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Release the bytes of the given buffer, which must have been filled in by
+/// vamp-ir, and set it to `{NULL, 0}`. Buffers that are already empty are
+/// left alone, so freeing one twice is harmless.
+///
+/// # Safety
+///
+/// `buffer` must be null or point to a `VampirBuffer` filled in by vamp-ir,
+/// or set to `{NULL, 0}`, and left unchanged since.
+#[no_mangle]
+pub unsafe extern "C" fn vampir_buffer_free(buffer: *mut VampirBuffer) {
+    let Some(buffer) = buffer.as_mut() else {
+        return;
+    };
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+    }
+    *buffer = VampirBuffer { data: ptr::null_mut(), len: 0 };
+}
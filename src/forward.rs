@@ -0,0 +1,216 @@
+use crate::artifact::read_header;
+use clap::Command;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File};
+use std::path::Path;
+
+/* The environment variable naming the backend used when none is given. */
+pub const BACKEND_VAR: &str = "VAMPIR_BACKEND";
+
+/* The backends to which commands are forwarded, by their names on the
+ * command line. */
+const BACKENDS: &[&str] = &[
+    "halo2",
+    "plonk",
+    #[cfg(feature = "groth16")]
+    "groth16",
+    #[cfg(feature = "bulletproofs")]
+    "bp",
+    #[cfg(feature = "plonky2")]
+    "plonky2",
+];
+
+/* The top-level commands that are forwarded to the command of the same name
+ * of a backend. */
+const FORWARDED: [&str; 3] = ["compile", "prove", "verify"];
+
+/* Settings that a project can fix for all of its commands, so that they need
+ * not be repeated on every command line. Only the environment sets them for
+ * now. */
+#[derive(Clone, Debug, Default)]
+pub struct ProjectConfig {
+    // Backend of commands that are given none and cannot infer one, as
+    // named by the project and checked only when it is used
+    pub backend: Option<String>,
+}
+
+impl ProjectConfig {
+    /* Read the settings from the environment. Variables that are empty are
+     * taken to be unset. */
+    pub fn from_env() -> Self {
+        let backend = std::env::var(BACKEND_VAR).ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        Self { backend }
+    }
+
+    /* The backend named by the project, if it names a known one. */
+    fn backend(&self) -> Result<Option<String>, String> {
+        self.backend.as_deref()
+            .map(|name| parse_backend(name).map_err(|err| format!("{} is set to {}", BACKEND_VAR, err)))
+            .transpose()
+    }
+}
+
+fn parse_backend(name: &str) -> Result<String, String> {
+    let name = name.to_lowercase();
+    if BACKENDS.contains(&name.as_str()) {
+        Ok(name)
+    } else {
+        Err(format!("'{}', which is not one of {}", name, BACKENDS.join(" or ")))
+    }
+}
+
+/* Check whether the given argument is an option of any of the given commands
+ * whose value is the argument that follows it. Values given with = or run
+ * together with a short option are part of the argument itself. */
+fn takes_next_value(commands: &[&Command], arg: &str) -> bool {
+    let takes_value = |found: &dyn Fn(&clap::Arg) -> bool| commands.iter()
+        .flat_map(|command| command.get_arguments())
+        .any(|option| found(option) && option.get_action().takes_values());
+    if let Some(long) = arg.strip_prefix("--") {
+        return !long.contains('=') && takes_value(&|option| option.get_long() == Some(long));
+    }
+    let Some(shorts) = arg.strip_prefix('-') else {
+        return false;
+    };
+    // Short flags can be clustered, the first of them taking a value taking
+    // the rest of the cluster as its value if there is any
+    let shorts: Vec<_> = shorts.chars().collect();
+    shorts.iter()
+        .position(|short| takes_value(&|option| option.get_short() == Some(*short)))
+        .is_some_and(|index| index + 1 == shorts.len())
+}
+
+/* Take the --backend arguments out of the given arguments of a command,
+ * giving the last one if there are any. The values of the options of the
+ * given commands and arguments after -- are left alone. */
+fn take_backend(commands: &[&Command], args: &mut Vec<OsString>) -> Result<Option<String>, String> {
+    let mut backend = None;
+    let mut index = 0;
+    while index < args.len() {
+        let arg = args[index].to_string_lossy().into_owned();
+        if arg == "--" {
+            break;
+        } else if arg == "--backend" {
+            args.remove(index);
+            if index == args.len() {
+                return Err("a value is required for '--backend <BACKEND>' but none was supplied".to_string());
+            }
+            let value = args.remove(index);
+            backend = Some(parse_backend(&value.to_string_lossy())
+                .map_err(|err| format!("invalid value {} for '--backend <BACKEND>'", err))?);
+        } else if let Some(value) = arg.strip_prefix("--backend=") {
+            backend = Some(parse_backend(value)
+                .map_err(|err| format!("invalid value {} for '--backend <BACKEND>'", err))?);
+            args.remove(index);
+        } else if takes_next_value(commands, &arg) {
+            index += 2;
+        } else {
+            index += 1;
+        }
+    }
+    Ok(backend)
+}
+
+/* Find the circuit named by -c or --circuit among the given arguments of a
+ * command, in any of the forms that clap accepts. */
+fn circuit_arg(args: &[OsString]) -> Option<OsString> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let text = arg.to_string_lossy();
+        if text == "--" {
+            break;
+        } else if text == "-c" || text == "--circuit" {
+            return args.next().cloned();
+        } else if let Some(value) = text.strip_prefix("--circuit=") {
+            return Some(value.into());
+        } else if let Some(value) = text.strip_prefix("-c").filter(|value| !value.is_empty()) {
+            return Some(value.trim_start_matches('=').into());
+        }
+    }
+    None
+}
+
+/* The backend of the circuit at the given path, as its header names it, if
+ * it can be read without consuming what the command itself must read: pipes,
+ * standard input among them, are left alone. */
+fn circuit_backend(path: &OsStr) -> Option<&'static str> {
+    if path == "-" || !fs::metadata(path).ok()?.is_file() {
+        return None;
+    }
+    let file = File::open(Path::new(path)).ok()?;
+    let (header, _) = read_header(file).ok()?;
+    Some(header?.kind.backend())
+}
+
+/* Find the top-level command among the given arguments, skipping the global
+ * options of the given command line along with their values. Nothing after
+ * -- is a command. */
+fn command_position(cli: &Command, args: &[OsString]) -> Option<usize> {
+    let mut index = 1;
+    while index < args.len() {
+        let arg = args[index].to_string_lossy();
+        if arg == "--" {
+            return None;
+        } else if !arg.starts_with('-') {
+            return Some(index);
+        }
+        index += if takes_next_value(&[cli], &arg) { 2 } else { 1 };
+    }
+    None
+}
+
+/* Rewrite the given command line so that the top-level compile, prove, and
+ * verify commands run the command of the same name of a backend, as though
+ * it had been given in full. The backend of a circuit that is proved or
+ * verified is named by its header, which any --backend must agree with;
+ * otherwise it is the one given with --backend or by the project. Without
+ * either, compile keeps compiling to the intermediate representation alone.
+ * Command lines that need no forwarding are given back as they are. Which
+ * options take values is read from the given definition of the command line.
+ */
+pub fn forward_args(cli: &Command, mut args: Vec<OsString>, config: &ProjectConfig) -> Result<Vec<OsString>, String> {
+    let Some(position) = command_position(cli, &args) else {
+        return Ok(args);
+    };
+    let command = args[position].to_string_lossy().into_owned();
+    if !FORWARDED.contains(&command.as_str()) {
+        return Ok(args);
+    }
+    // The options of the command are those of the commands of every backend
+    // that it can be forwarded to, along with the global options
+    let mut commands = vec![cli];
+    commands.extend(BACKENDS.iter()
+        .filter_map(|backend| cli.find_subcommand(backend)?.find_subcommand(&command)));
+    let mut rest = args.split_off(position + 1);
+    let given = take_backend(&commands, &mut rest)?;
+    let inferred = if command == "compile" {
+        None
+    } else {
+        circuit_arg(&rest).and_then(|circuit| circuit_backend(&circuit).map(|backend| (circuit, backend)))
+    };
+    let backend = match (given, inferred) {
+        (Some(given), Some((circuit, backend))) if given != backend => return Err(format!(
+            "--backend {} was given, but {} is a {} circuit",
+            given, circuit.to_string_lossy(), backend,
+        )),
+        (_, Some((_, backend))) => Some(backend.to_string()),
+        (Some(given), None) => Some(given),
+        (None, None) => config.backend()?,
+    };
+    let Some(backend) = backend else {
+        let help = rest.iter().any(|arg| arg == "-h" || arg == "--help");
+        if command == "compile" || help {
+            args.extend(rest);
+            return Ok(args);
+        }
+        return Err(format!(
+            "cannot tell which backend to {} with: give --backend, set {}, or give a circuit whose header names one",
+            command, BACKEND_VAR,
+        ));
+    };
+    args.insert(position, backend.into());
+    args.extend(rest);
+    Ok(args)
+}
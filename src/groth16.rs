@@ -0,0 +1,132 @@
+use crate::ark_adapter::ModuleCircuit;
+use crate::artifact::DECODE_LIMIT;
+use crate::ast::{Module, VariableId};
+use crate::error::Error;
+use crate::plonk::field::make_constant;
+use crate::transform::input_name;
+use ark_ec::PairingEngine;
+use ark_ff::SquareRootField;
+use ark_groth16::{create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof, Proof, ProvingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use bincode::error::{DecodeError, EncodeError};
+use num_bigint::BigInt;
+use rand_core::RngCore;
+use std::collections::HashMap;
+
+/* Captures all the data required to use a Groth16 circuit, whose keys come
+ * from a setup of its own. */
+pub struct Groth16CircuitData<E> where E: PairingEngine {
+    pub module: Module,
+    // Proving key, which holds the verifying key
+    pub pk: ProvingKey<E>,
+    // Optimization level that the circuit was compiled at
+    pub opt_level: u8,
+    // Public variables fixed to constants at compile time, in order
+    pub defines: Vec<(String, String)>,
+}
+
+/* A Groth16 proof along with the values of the public variables that it was
+ * made for, in the order that they are declared. */
+pub struct Groth16Proof<E> where E: PairingEngine {
+    pub proof: Proof<E>,
+    pub public_values: Vec<E::Fr>,
+}
+
+impl<E> Groth16CircuitData<E> where E: PairingEngine, E::Fr: SquareRootField {
+    /* Run the setup of the circuit of the given module, compiled over the
+     * scalar field of E, drawing its toxic waste from the given generator. */
+    pub fn setup<R: RngCore>(
+        module: Module,
+        opt_level: u8,
+        defines: Vec<(String, String)>,
+        mut rng: R,
+    ) -> Result<Self, Error> {
+        let circuit = ModuleCircuit::<E::Fr>::new(&module, HashMap::new())?;
+        let pk = generate_random_parameters::<E, _, _>(circuit, &mut rng)
+            .map_err(|err| Error::Backend(format!("unable to generate keys: {:?}", err)))?;
+        Ok(Self { module, pk, opt_level, defines })
+    }
+
+    pub fn read<R>(mut reader: R) -> Result<Self, DecodeError>
+    where R: std::io::Read {
+        let pk = ProvingKey::<E>::deserialize(&mut reader)
+            .map_err(|x| DecodeError::OtherString(x.to_string()))?;
+        let module: Module =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let opt_level: u8 =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let defines: Vec<(String, String)> =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        Ok(Self { module, pk, opt_level, defines })
+    }
+
+    pub fn write<W>(&self, mut writer: W) -> Result<(), EncodeError>
+    where W: std::io::Write {
+        self.pk.serialize(&mut writer)
+            .map_err(|x| EncodeError::OtherString(x.to_string()))?;
+        bincode::encode_into_std_write(
+            &self.module,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            self.opt_level,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            &self.defines,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        Ok(())
+    }
+
+    /* Prove knowledge of the given values of every variable, as assign_inputs
+     * gives them back, drawing the randomness that hides them from the given
+     * generator. */
+    pub fn prove<R: RngCore>(&self, assigns: HashMap<VariableId, BigInt>, mut rng: R) -> Result<Groth16Proof<E>, Error> {
+        let public_values = self.module.pubs.iter()
+            .map(|var| assigns.get(&var.id)
+                .map(make_constant::<E::Fr>)
+                .ok_or_else(|| Error::Inputs(format!("no value was derived for {}", input_name(var)))))
+            .collect::<Result<_, _>>()?;
+        let circuit = ModuleCircuit::<E::Fr>::new(&self.module, assigns)?;
+        let proof = create_random_proof(circuit, &self.pk, &mut rng)
+            .map_err(|err| Error::Backend(format!("unable to generate proof: {:?}", err)))?;
+        Ok(Groth16Proof { proof, public_values })
+    }
+
+    /* Check the given proof of this circuit against the public values that it
+     * carries. */
+    pub fn verify(&self, proof: &Groth16Proof<E>) -> Result<(), Error> {
+        if proof.public_values.len() != self.module.pubs.len() {
+            return Err(Error::InvalidProof(format!(
+                "proof has {} public inputs, but the circuit has {} public variables",
+                proof.public_values.len(),
+                self.module.pubs.len(),
+            )));
+        }
+        let pvk = prepare_verifying_key(&self.pk.vk);
+        match verify_proof(&pvk, &proof.proof, &proof.public_values) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Error::InvalidProof("pairing check failed".to_string())),
+            Err(err) => Err(Error::InvalidProof(format!("{:?}", err))),
+        }
+    }
+}
+
+impl<E> Groth16Proof<E> where E: PairingEngine {
+    pub fn read<R>(mut reader: R) -> Result<Self, ark_serialize::SerializationError>
+    where R: std::io::Read {
+        let proof = Proof::<E>::deserialize(&mut reader)?;
+        let public_values = Vec::<E::Fr>::deserialize(&mut reader)?;
+        Ok(Self { proof, public_values })
+    }
+
+    pub fn write<W>(&self, mut writer: W) -> Result<(), ark_serialize::SerializationError>
+    where W: std::io::Write {
+        self.proof.serialize(&mut writer)?;
+        self.public_values.serialize(&mut writer)
+    }
+}
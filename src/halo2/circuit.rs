@@ -0,0 +1,189 @@
+use crate::ast::Module;
+use crate::artifact::{artifact_bytes, parse_artifact, parse_artifact_from_reader, ArtifactKind, DECODE_LIMIT};
+use crate::assign_inputs;
+use crate::error::Error;
+use crate::halo2::synth::{keygen, make_constant, prover_with_rng, Halo2Module, PrimeFieldOps};
+use crate::halo2::verify::{check_proof, read_params, Layout, VerifierData};
+use crate::progress::NoProgress;
+use bincode::error::{DecodeError, EncodeError};
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::Params;
+use num_bigint::BigInt;
+use rand_core::{OsRng, RngCore};
+use std::collections::HashMap;
+
+pub use crate::halo2::verify::{decode_public_values, encode_public_values, field_value, Halo2Proof, ProofDataHalo2};
+
+/* Captures all the data required to use a Halo2 circuit. */
+pub struct HaloCircuitData {
+    pub params: Params<EqAffine>,
+    pub circuit: Halo2Module::<Fp>,
+    // Optimization level that the circuit was compiled at
+    pub opt_level: u8,
+    // Public variables fixed to constants at compile time, in order
+    pub defines: Vec<(String, String)>,
+}
+
+impl HaloCircuitData {
+    pub fn read<R>(mut reader: R) -> Result<Self, DecodeError>
+    where R: std::io::Read {
+        let params = read_params(&mut reader)?;
+        let circuit: Halo2Module::<Fp> =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let opt_level: u8 =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let defines: Vec<(String, String)> =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        Ok(Self { params, circuit, opt_level, defines })
+    }
+
+    pub fn write<W>(&self, mut writer: W) -> Result<(), EncodeError>
+    where W: std::io::Write {
+        self.params.write(&mut writer)
+            .map_err(|x| EncodeError::OtherString(x.to_string()))?;
+        bincode::encode_into_std_write(
+            &self.circuit,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            self.opt_level,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            &self.defines,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        Ok(())
+    }
+}
+
+/* A Halo2 circuit held in memory along with the public parameters and keys
+ * that it is proved and verified with. */
+pub struct Halo2Circuit {
+    data: HaloCircuitData,
+    layout: Layout<Fp>,
+    pk: ProvingKey<EqAffine>,
+    vk: VerifyingKey<EqAffine>,
+}
+
+impl Halo2Circuit {
+    /* Synthesize a circuit from the given module, compiled over the Pallas
+     * field, with public parameters just large enough for it, and generate
+     * its keys. */
+    pub fn new(module: Module) -> Result<Self, Error> {
+        let circuit = Halo2Module::<Fp>::new(module);
+        let params: Params<EqAffine> = Params::new(circuit.k);
+        Self::from_data(HaloCircuitData { params, circuit, opt_level: 0, defines: vec![] })
+    }
+
+    /* Generate the keys of the circuit of the given data, as compiled or as
+     * read from a file. Fails if its module cannot be laid out as gates. */
+    pub fn from_data(data: HaloCircuitData) -> Result<Self, Error> {
+        let layout = data.circuit.layout().map_err(Error::Backend)?;
+        let (pk, vk) = keygen(&data.circuit, &data.params, &NoProgress)
+            .map_err(|err| Error::Backend(format!("unable to generate keys: {:?}", err)))?;
+        Ok(Self { data, layout, pk, vk })
+    }
+
+    /* Read a circuit from the contents of a circuit file written by the
+     * command line, in any of its encodings, and generate its keys. */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let data = parse_artifact(bytes, ArtifactKind::Halo2Circuit, |reader| HaloCircuitData::read(reader))
+            .map_err(Error::Artifact)?;
+        Self::from_data(data)
+    }
+
+    /* Read a circuit from the given reader as from_bytes does from memory,
+     * decoding its public parameters and module as they arrive. */
+    pub fn from_reader(reader: impl std::io::Read) -> Result<Self, Error> {
+        let data = parse_artifact_from_reader(reader, ArtifactKind::Halo2Circuit, |reader| HaloCircuitData::read(reader))
+            .map_err(Error::Artifact)?;
+        Self::from_data(data)
+    }
+
+    /* The contents of a circuit file holding this circuit, as the command line
+     * writes it in binary. */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        artifact_bytes(ArtifactKind::Halo2Circuit, |writer| self.data.write(writer))
+            .map_err(Error::Artifact)
+    }
+
+    pub fn data(&self) -> &HaloCircuitData {
+        &self.data
+    }
+
+    pub fn module(&self) -> &Module {
+        &self.data.circuit.module
+    }
+
+    /* Prove knowledge of the witnesses that the given inputs, named as in the
+     * source, give rise to. */
+    pub fn prove(&self, inputs: &HashMap<String, BigInt>) -> Result<Halo2Proof, Error> {
+        self.prove_with_rng(inputs, OsRng)
+    }
+
+    /* Prove as prove does, drawing the randomness that hides the witnesses
+     * from the given generator rather than from the operating system. */
+    pub fn prove_with_rng<R: RngCore>(&self, inputs: &HashMap<String, BigInt>, rng: R) -> Result<Halo2Proof, Error> {
+        let assigns = assign_inputs(self.module(), inputs, &PrimeFieldOps::<Fp>::default())?;
+        let assigns: HashMap<_, Fp> = assigns.into_iter().map(|(var, value)| (var, make_constant(value))).collect();
+        let mut circuit = self.data.circuit.clone();
+        circuit.populate_variables(assigns, &NoProgress)?;
+        let public_values = circuit.public_values()?;
+        let proof = prover_with_rng(circuit, &self.data.params, &self.pk, rng, &NoProgress)
+            .map_err(|err| Error::Backend(format!("unable to generate proof: {:?}", err)))?;
+        Ok(Halo2Proof { proof, public_values })
+    }
+
+    /* Check the given proof of this circuit against the public values that it
+     * carries. */
+    pub fn verify(&self, proof: &Halo2Proof) -> Result<(), Error> {
+        check_proof(&self.data.params, &self.vk, self.module().pubs.len(), proof)
+    }
+
+    /* What a verifier needs of this circuit: its public parameters and its
+     * layout, without its program. */
+    pub fn verifier_data(&self) -> VerifierData {
+        VerifierData { params: self.data.params.clone(), layout: self.layout.clone() }
+    }
+}
+
+/* A Halo2 proof as serialized with serde: its bytes in hexadecimal, and the
+ * values of its public variables as decimal strings. */
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedHalo2Proof {
+    proof: String,
+    public_values: Vec<String>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Halo2Proof {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let serialized = SerializedHalo2Proof {
+            proof: hex::encode(&self.proof),
+            public_values: self.public_values.iter().map(|val| field_value(val).to_string()).collect(),
+        };
+        serde::Serialize::serialize(&serialized, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Halo2Proof {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let SerializedHalo2Proof { proof, public_values } = serde::Deserialize::deserialize(deserializer)?;
+        let proof = hex::decode(proof).map_err(|err| D::Error::custom(format!("proof is not hexadecimal: {}", err)))?;
+        let public_values = public_values.iter().map(|text| {
+            let value = crate::ast::parse_prefixed_num::<BigInt>(text).ok()
+                .filter(|value| field_value(&make_constant(value.clone())) == *value)
+                .ok_or_else(|| D::Error::custom(format!("public value {} is not a field element", text)))?;
+            Ok(make_constant(value))
+        }).collect::<Result<_, _>>()?;
+        Ok(Self { proof, public_values })
+    }
+}
@@ -0,0 +1,102 @@
+use group::ff::Field;
+use ff::PrimeField;
+use halo2_proofs::arithmetic::FieldExt;
+
+use num_bigint::{BigInt, BigUint, ToBigInt, Sign};
+use num_traits::{Signed, Zero};
+
+use std::marker::PhantomData;
+
+use crate::ast::InfixOp;
+use crate::transform::{CostModel, FieldOpError, FieldOps};
+
+// Make field elements from signed values
+pub fn make_constant<F: FieldExt>(c: BigInt) -> F {
+    let mut bytes = c.magnitude().to_bytes_le();
+    bytes.resize(64, 0);
+    let magnitude = F::from_bytes_wide(&bytes.try_into().unwrap());
+    if c.is_positive() {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+#[derive(Default)]
+pub struct PrimeFieldOps<F> where F: PrimeField {
+    phantom: PhantomData<F>
+}
+
+/* Compute the smaller of the two square roots of the given field element, if
+ * it has any. Fixing the root makes witnesses independent of the particular
+ * square root algorithm. */
+pub fn field_sqrt<F>(a: F) -> Option<F> where F: PrimeField {
+    Option::<F>::from(a.sqrt()).map(|root| {
+        let neg_root = -root;
+        if BigUint::from_bytes_le(root.to_repr().as_ref()) <=
+            BigUint::from_bytes_le(neg_root.to_repr().as_ref()) { root } else { neg_root }
+    })
+}
+
+/* Costs of the constructs of circuits synthesized by the Halo2 backend. Every
+ * copy between cells is laid out as a region of its own, and every public
+ * variable takes a row of its own to be exposed in the instance column. */
+pub struct Halo2CostModel;
+
+impl CostModel for Halo2CostModel {
+    fn add_gate(&self) -> u64 { 2 }
+    fn mul_gate(&self) -> u64 { 3 }
+    fn copy_constraint(&self) -> u64 { 1 }
+    fn public_input(&self) -> u64 { 2 }
+}
+
+impl<F> FieldOps for PrimeFieldOps<F> where F: PrimeField + FieldExt {
+    /* Evaluate the given negation expression in the given prime field. */
+    fn canonical(&self, a: BigInt) -> BigInt {
+        let b = make_constant::<F>(a);
+        BigUint::from_bytes_le(b.to_repr().as_ref()).to_bigint().unwrap()
+    }
+    /* Evaluate the given negation expression in the given prime field. */
+    fn negate(&self, a: BigInt) -> BigInt {
+        let b = make_constant::<F>(a);
+        BigUint::from_bytes_le((-b).to_repr().as_ref()).to_bigint().unwrap()
+    }
+    /* Evaluate the given infix expression in the given prime field. */
+    fn infix(&self, op: InfixOp, a: BigInt, b: BigInt) -> Result<BigInt, FieldOpError> {
+        let c = make_constant::<F>(a.clone());
+        let d = make_constant::<F>(b.clone());
+        Ok(match op {
+            InfixOp::Add => BigUint::from_bytes_le((c + d).to_repr().as_ref()).to_bigint().unwrap(),
+            InfixOp::Subtract => BigUint::from_bytes_le((c - d).to_repr().as_ref()).to_bigint().unwrap(),
+            InfixOp::Multiply => BigUint::from_bytes_le((c * d).to_repr().as_ref()).to_bigint().unwrap(),
+            InfixOp::Divide if d == F::zero() => return Err(FieldOpError::DivisionByZero),
+            InfixOp::Divide => BigUint::from_bytes_le((c * d.invert().unwrap()).to_repr().as_ref()).to_bigint().unwrap(),
+            InfixOp::DivideZ => if d == F::zero() { BigInt::from(0) } else { BigUint::from_bytes_le((c * d.invert().unwrap()).to_repr().as_ref()).to_bigint().unwrap()},
+            InfixOp::IntDivide | InfixOp::Modulo if b.is_zero() =>
+                return Err(FieldOpError::DivisionByZero),
+            InfixOp::IntDivide => a / b,
+            InfixOp::Modulo => a % b,
+            InfixOp::Exponentiate if c == F::zero() && b.is_negative() =>
+                return Err(FieldOpError::DivisionByZero),
+            InfixOp::Exponentiate => {
+                // Exponents of non-zero bases can be reduced modulo the order
+                // of the multiplicative group
+                let order = BigUint::from_bytes_le((-F::one()).to_repr().as_ref());
+                let exp = if c == F::zero() { b.magnitude().clone() } else { b.magnitude() % order };
+                // Square and multiply over the exponent's digits
+                let pow = c.pow_vartime(exp.to_u64_digits());
+                BigUint::from_bytes_le(if b.sign() == Sign::Minus {
+                    pow.invert().unwrap()
+                } else {
+                    pow
+                }.to_repr().as_ref()).to_bigint().unwrap()
+            },
+            InfixOp::Equal => panic!("cannot evaluate equals expression"),
+        })
+    }
+    /* Compute the square root of the given value in the given prime field. */
+    fn sqrt(&self, a: BigInt) -> Option<BigInt> {
+        field_sqrt(make_constant::<F>(a))
+            .map(|root| BigUint::from_bytes_le(root.to_repr().as_ref()).to_bigint().unwrap())
+    }
+}
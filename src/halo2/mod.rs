@@ -1,2 +1,7 @@
-pub mod cli;
-pub mod synth;
\ No newline at end of file
+pub mod verify;
+#[cfg(feature = "compiler")]
+pub mod field;
+#[cfg(feature = "halo2")]
+pub mod synth;
+#[cfg(feature = "halo2")]
+pub mod circuit;
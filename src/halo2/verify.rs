@@ -0,0 +1,693 @@
+use crate::artifact::{artifact_bytes, parse_artifact, parse_artifact_from_reader, ArtifactKind, DECODE_LIMIT};
+use crate::error::Error;
+use ark_serialize::{Read, SerializationError};
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use bincode::error::{DecodeError, EncodeError};
+use bincode::{Decode, Encode};
+use ff::PrimeField;
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::pasta::{EqAffine, Fp};
+use halo2_proofs::plonk::{keygen_vk, verify_proof, Advice, Assigned, Circuit, Column, ConstraintSystem, Fixed, Instance, SingleVerifier, VerifyingKey};
+use halo2_proofs::plonk::Error as Halo2Error;
+use halo2_proofs::poly::{commitment::Params, Rotation};
+use halo2_proofs::transcript::{Blake2bRead, Challenge255};
+use num_bigint::{BigInt, Sign};
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::io::Write;
+use std::marker::PhantomData;
+
+#[derive(Clone)]
+pub struct PlonkConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+
+    sl: Column<Fixed>,
+    sr: Column<Fixed>,
+    so: Column<Fixed>,
+    sm: Column<Fixed>,
+    sc: Column<Fixed>,
+
+    // Values of the public variables, one to a row in the order they are
+    // declared
+    instance: Column<Instance>,
+}
+
+/* Configure the columns and the single gate that every circuit of this
+ * backend is laid out in, whether it is proved or only verified. */
+pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> PlonkConfig {
+    meta.set_minimum_degree(5);
+
+    let a = meta.advice_column();
+    let b = meta.advice_column();
+    let c = meta.advice_column();
+
+    meta.enable_equality(a);
+    meta.enable_equality(b);
+    meta.enable_equality(c);
+
+    let sm = meta.fixed_column();
+    let sl = meta.fixed_column();
+    let sr = meta.fixed_column();
+    let so = meta.fixed_column();
+    let sc = meta.fixed_column();
+
+    let instance = meta.instance_column();
+    meta.enable_equality(instance);
+
+    meta.create_gate("Combined add-mult", |meta| {
+        let a = meta.query_advice(a, Rotation::cur());
+        let b = meta.query_advice(b, Rotation::cur());
+        let c = meta.query_advice(c, Rotation::cur());
+
+        let sl = meta.query_fixed(sl, Rotation::cur());
+        let sr = meta.query_fixed(sr, Rotation::cur());
+        let so = meta.query_fixed(so, Rotation::cur());
+        let sm = meta.query_fixed(sm, Rotation::cur());
+        let sc = meta.query_fixed(sc, Rotation::cur());
+
+        vec![a.clone() * sl + b.clone() * sr + a * b * sm + (c * so) + sc]
+    });
+
+    PlonkConfig {
+        a,
+        b,
+        c,
+        sl,
+        sr,
+        so,
+        sm,
+        sc,
+        instance,
+    }
+}
+
+trait StandardCs<FF: FieldExt> {
+    fn raw_multiply<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        f: F,
+    ) -> Result<(Cell, Cell, Cell), Halo2Error>
+    where
+        F: FnMut() -> Value<(Assigned<FF>, Assigned<FF>, Assigned<FF>)>;
+    fn raw_add<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        f: F,
+    ) -> Result<(Cell, Cell, Cell), Halo2Error>
+    where
+        F: FnMut() -> Value<(Assigned<FF>, Assigned<FF>, Assigned<FF>)>;
+    fn raw_poly<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        f: F,
+    ) -> Result<(Cell, Cell, Cell), Halo2Error>
+    where
+        F: FnMut() -> PolyGate<Assigned<FF>>;
+    fn copy(&self, layouter: &mut impl Layouter<FF>, a: Cell, b: Cell) -> Result<(), Halo2Error>;
+}
+
+struct StandardPlonk<F: FieldExt> {
+    config: PlonkConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<FF: FieldExt> StandardPlonk<FF> {
+    fn new(config: PlonkConfig) -> Self {
+        StandardPlonk {
+            config,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct PolyGate<F> {
+    a: Value<F>,
+    b: Value<F>,
+    c: Value<F>,
+    q_m: F,
+    q_l: F,
+    q_r: F,
+    q_o: F,
+    q_c: F,
+}
+
+impl<FF: FieldExt> StandardCs<FF> for StandardPlonk<FF> {
+    fn raw_multiply<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        mut f: F,
+    ) -> Result<(Cell, Cell, Cell), Halo2Error>
+    where
+        F: FnMut() -> Value<(Assigned<FF>, Assigned<FF>, Assigned<FF>)>,
+    {
+        layouter.assign_region(
+            || "raw_multiply",
+            |mut region| {
+                let mut value = None;
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    self.config.a,
+                    0,
+                    || {
+                        value = Some(f());
+                        value.unwrap().map(|v| v.0)
+                    },
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    self.config.b,
+                    0,
+                    || value.unwrap().map(|v| v.1),
+                )?;
+                let out = region.assign_advice(
+                    || "out",
+                    self.config.c,
+                    0,
+                    || value.unwrap().map(|v| v.2),
+                )?;
+
+                region.assign_fixed(|| "a", self.config.sl, 0, || Value::known(FF::zero()))?;
+                region.assign_fixed(|| "b", self.config.sr, 0, || Value::known(FF::zero()))?;
+                region.assign_fixed(|| "c", self.config.so, 0, || Value::known(FF::one()))?;
+                region.assign_fixed(
+                    || "a * b",
+                    self.config.sm,
+                    0,
+                    || Value::known(FF::one()),
+                )?;
+                Ok((lhs.cell(), rhs.cell(), out.cell()))
+            },
+        )
+    }
+    fn raw_add<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        mut f: F,
+    ) -> Result<(Cell, Cell, Cell), Halo2Error>
+    where
+        F: FnMut() -> Value<(Assigned<FF>, Assigned<FF>, Assigned<FF>)>,
+    {
+        layouter.assign_region(
+            || "raw_add",
+            |mut region| {
+                let mut value = None;
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    self.config.a,
+                    0,
+                    || {
+                        value = Some(f());
+                        value.unwrap().map(|v| v.0)
+                    },
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    self.config.b,
+                    0,
+                    || value.unwrap().map(|v| v.1),
+                )?;
+                let out = region.assign_advice(
+                    || "out",
+                    self.config.c,
+                    0,
+                    || value.unwrap().map(|v| v.2),
+                )?;
+
+                region.assign_fixed(|| "a", self.config.sl, 0, || Value::known(FF::one()))?;
+                region.assign_fixed(|| "b", self.config.sr, 0, || Value::known(FF::one()))?;
+                region.assign_fixed(|| "c", self.config.so, 0, || Value::known(FF::one()))?;
+                region.assign_fixed(
+                    || "a + b",
+                    self.config.sm,
+                    0,
+                    || Value::known(FF::zero()),
+                )?;
+                Ok((lhs.cell(), rhs.cell(), out.cell()))
+            },
+        )
+    }
+    fn raw_poly<F>(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        mut f: F,
+    ) -> Result<(Cell, Cell, Cell), Halo2Error>
+    where
+        F: FnMut() -> PolyGate<Assigned<FF>>,
+    {
+        layouter.assign_region(
+            || "raw_poly",
+            |mut region| {
+                let value = f();
+                let lhs = region.assign_advice(
+                    || "lhs",
+                    self.config.a,
+                    0,
+                    || value.a,
+                )?;
+                let rhs = region.assign_advice(
+                    || "rhs",
+                    self.config.b,
+                    0,
+                    || value.b,
+                )?;
+                let out = region.assign_advice(
+                    || "out",
+                    self.config.c,
+                    0,
+                    || value.c,
+                )?;
+
+                region.assign_fixed(|| "a", self.config.sl, 0, || Value::known(value.q_l))?;
+                region.assign_fixed(|| "b", self.config.sr, 0, || Value::known(value.q_r))?;
+                region.assign_fixed(|| "c", self.config.so, 0, || Value::known(value.q_o))?;
+                region.assign_fixed(
+                    || "a * b",
+                    self.config.sm,
+                    0,
+                    || Value::known(value.q_m),
+                )?;
+                region.assign_fixed(|| "q_c", self.config.sc, 0, || Value::known(value.q_c))?;
+                Ok((lhs.cell(), rhs.cell(), out.cell()))
+            },
+        )
+    }
+    fn copy(
+        &self,
+        layouter: &mut impl Layouter<FF>,
+        left: Cell,
+        right: Cell,
+    ) -> Result<(), Halo2Error> {
+        layouter.assign_region(|| "copy", |mut region| region.constrain_equal(left, right))
+    }
+}
+
+/* A row of a circuit: the variables, by their identifiers in the module,
+ * whose values fill its three advice cells, with zero filling those without
+ * one, and the coefficients sl, sr, so, sm, and sc of its gate. */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gate<F> {
+    pub wires: [Option<u32>; 3],
+    pub coeffs: [F; 5],
+}
+
+/* The gates of a circuit in the order they are laid out, which is all of a
+ * compiled module that its verifying key depends on. */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout<F> {
+    // Variables exposed in the instance column, each in a row of its own
+    // ahead of the gates, in the order they are declared
+    pub public: Vec<u32>,
+    pub gates: Vec<Gate<F>>,
+}
+
+/* Tie the given cell to the earlier cell holding the same variable, if there
+ * is one, or else record it as the cell that later ones are tied to. */
+fn copy_variable<F: FieldExt>(
+    var: u32,
+    cell: Cell,
+    map: &mut BTreeMap<u32, Cell>,
+    cs: &impl StandardCs<F>,
+    layouter: &mut impl Layouter<F>,) -> Result<(), Halo2Error>
+{
+    match map.entry(var) {
+        Entry::Vacant(vac) => {
+            vac.insert(cell);
+        },
+        Entry::Occupied(occ) => {
+            cs.copy(layouter, cell, *occ.get())?
+        },
+    }
+    Ok(())
+}
+
+/* Lay out the given gate in a row of its own, tying each of its cells to the
+ * earlier cells of the same variable, or to the given cell of zero. */
+fn make_gate<F: FieldExt>(
+    gate: &Gate<F>,
+    value: &impl Fn(u32) -> Value<F>,
+    cell0: Cell,
+    inputs: &mut BTreeMap<u32, Cell>,
+    cs: &impl StandardCs<F>,
+    layouter: &mut impl Layouter<F>,
+) -> Result<(), Halo2Error> {
+    let cells = cs.raw_poly(layouter, || {
+        let [a, b, c] = gate.wires.map(|wire| -> Value<Assigned<F>> {
+            wire.map(value).unwrap_or(Value::known(F::zero())).into()
+        });
+        let [sl, sr, so, sm, sc] = gate.coeffs;
+        PolyGate {a, b, c, q_l:sl.into(), q_r:sr.into(), q_o:so.into(), q_m:sm.into(), q_c:sc.into()}
+    })?;
+    for (wire, cell) in gate.wires.iter().zip([cells.0, cells.1, cells.2]) {
+        match wire {
+            Some(var) => copy_variable(*var, cell, inputs, cs, layouter)?,
+            None => cs.copy(layouter, cell, cell0)?,
+        }
+    }
+    Ok(())
+}
+
+/* Lay out the given circuit with the variables taking the given values,
+ * which are all unknown when only keys are being generated. */
+pub fn synthesize<F: FieldExt>(
+    config: PlonkConfig,
+    mut layouter: impl Layouter<F>,
+    layout: &Layout<F>,
+    value: impl Fn(u32) -> Value<F>,
+) -> Result<(), Halo2Error> {
+    let instance = config.instance;
+    let cs = StandardPlonk::new(config);
+
+    let mut inputs = BTreeMap::new();
+
+    let val1: Assigned<_> = Assigned::from(F::one());
+    let val0: Assigned<_> = Assigned::from(F::zero());
+    let (_, cell0, _) = cs.raw_poly(&mut layouter, || {
+        PolyGate {
+            a: Value::known(val0),
+            b: Value::known(val0),
+            c: Value::known(val0),
+            q_l: val0,
+            q_r: val1,
+            q_o: val0,
+            q_m: val0,
+            q_c: val0,
+        }
+    })?;
+
+    // Expose each public variable by placing it in an otherwise idle row
+    // and tying that cell to its row of the instance column
+    for (row, var) in layout.public.iter().enumerate() {
+        let gate = Gate { wires: [Some(*var), None, None], coeffs: [F::zero(); 5] };
+        make_gate(&gate, &value, cell0, &mut inputs, &cs, &mut layouter)?;
+        layouter.constrain_instance(inputs[var], instance, row)?;
+    }
+
+    for gate in &layout.gates {
+        make_gate(gate, &value, cell0, &mut inputs, &cs, &mut layouter)?;
+    }
+
+    Ok(())
+}
+
+/* The coefficients of gates are written as the representations of the field
+ * elements, which must be canonical to be read back. */
+impl Encode for Layout<Fp> {
+    fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        let gates: Vec<_> = self.gates.iter()
+            .map(|gate| (gate.wires, gate.coeffs.map(|coeff| coeff.to_repr())))
+            .collect();
+        self.public.encode(encoder)?;
+        gates.encode(encoder)
+    }
+}
+
+impl Decode for Layout<Fp> {
+    fn decode<D: bincode::de::Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let public = Vec::<u32>::decode(decoder)?;
+        let gates = Vec::<([Option<u32>; 3], [[u8; 32]; 5])>::decode(decoder)?;
+        let gates = gates.into_iter().map(|(wires, coeffs)| {
+            let coeffs = coeffs.map(|repr| Option::<Fp>::from(Fp::from_repr(repr)));
+            if coeffs.iter().any(Option::is_none) {
+                return Err(DecodeError::Other("gate coefficient is not a field element"));
+            }
+            Ok(Gate { wires, coeffs: coeffs.map(Option::unwrap) })
+        }).collect::<Result<_, _>>()?;
+        Ok(Layout { public, gates })
+    }
+}
+
+/* A circuit of which only the layout is known, which is enough to generate
+ * its verifying key but not to prove anything. */
+struct LayoutCircuit(Layout<Fp>);
+
+impl Circuit<Fp> for LayoutCircuit {
+    type Config = PlonkConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        LayoutCircuit(self.0.clone())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> PlonkConfig {
+        configure(meta)
+    }
+
+    fn synthesize(&self, config: PlonkConfig, layouter: impl Layouter<Fp>) -> Result<(), Halo2Error> {
+        synthesize(config, layouter, &self.0, |_| Value::unknown())
+    }
+}
+
+/* Read public parameters, refusing those for more rows than the Pasta fields
+ * support before the 2^k points that they hold are read. */
+pub fn read_params<R>(mut reader: R) -> Result<Params<EqAffine>, DecodeError>
+where R: std::io::Read {
+    let mut k = [0u8; 4];
+    reader.read_exact(&mut k).map_err(|x| DecodeError::OtherString(x.to_string()))?;
+    if u32::from_le_bytes(k) > Fp::S {
+        return Err(DecodeError::OtherString(format!(
+            "parameters for 2^{} rows exceed the Pasta fields", u32::from_le_bytes(k),
+        )));
+    }
+    Params::<EqAffine>::read((&k[..]).chain(&mut reader))
+        .map_err(|x| DecodeError::OtherString(x.to_string()))
+}
+
+/* Captures all the data required to verify proofs of a Halo2 circuit. The
+ * verifying keys of halo2_proofs cannot be serialized, so the layout of the
+ * circuit stands in for one, and holds nothing of the program it came from. */
+pub struct VerifierData {
+    pub params: Params<EqAffine>,
+    pub layout: Layout<Fp>,
+}
+
+impl VerifierData {
+    pub fn read<R>(mut reader: R) -> Result<Self, DecodeError>
+    where R: std::io::Read {
+        let params = read_params(&mut reader)?;
+        let layout: Layout<Fp> =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        Ok(Self { params, layout })
+    }
+
+    pub fn write<W>(&self, mut writer: W) -> Result<(), EncodeError>
+    where W: std::io::Write {
+        self.params.write(&mut writer)
+            .map_err(|x| EncodeError::OtherString(x.to_string()))?;
+        bincode::encode_into_std_write(
+            &self.layout,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        Ok(())
+    }
+
+    /* The contents of a verifier file holding this data, as the command line
+     * writes it in binary. */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        artifact_bytes(ArtifactKind::Halo2Verifier, |writer| self.write(writer))
+            .map_err(Error::Artifact)
+    }
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofDataHalo2 {
+    pub proof: Vec<u8>,
+    // Values of the public variables that the proof commits to, in the order
+    // they are declared, as encoded by encode_public_values
+    pub public_inputs: Vec<Vec<u8>>,
+}
+
+/* The given field element as the integer it stands for. */
+pub fn field_value(val: &Fp) -> BigInt {
+    BigInt::from_bytes_le(Sign::Plus, val.to_repr().as_ref())
+}
+
+/* Encode the given values of public variables as they are written in proofs,
+ * which is as the little-endian representations of the field elements. */
+pub fn encode_public_values(values: &[Fp]) -> Vec<Vec<u8>> {
+    values.iter().map(|val| val.to_repr().as_ref().to_vec()).collect()
+}
+
+/* Decode the values of public variables written in a proof. */
+pub fn decode_public_values(encoded: &[Vec<u8>]) -> Result<Vec<Fp>, String> {
+    encoded.iter().map(|bytes| {
+        let repr = <[u8; 32]>::try_from(bytes.as_slice())
+            .map_err(|_| format!("public input of {} bytes is not a field element", bytes.len()))?;
+        Option::<Fp>::from(Fp::from_repr(repr))
+            .ok_or_else(|| "public input is not a field element".to_string())
+    }).collect()
+}
+
+/* A Halo2 proof along with the values of the public variables that it was
+ * made for, in the order that they are declared. */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Halo2Proof {
+    pub proof: Vec<u8>,
+    pub public_values: Vec<Fp>,
+}
+
+impl Halo2Proof {
+    /* Read a proof from the contents of a proof file written by the command
+     * line, in any of its encodings. */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let data = parse_artifact(bytes, ArtifactKind::Halo2Proof, |reader| ProofDataHalo2::deserialize(reader))
+            .map_err(Error::Artifact)?;
+        Self::from_data(data)
+    }
+
+    /* Read a proof from the given reader as from_bytes does from memory. The
+     * transcript of the proof comes before its public values, which the
+     * verifier needs first, so the transcript is still held in memory. */
+    pub fn from_reader(reader: impl Read) -> Result<Self, Error> {
+        let data = parse_artifact_from_reader(reader, ArtifactKind::Halo2Proof, |reader| ProofDataHalo2::deserialize(reader))
+            .map_err(Error::Artifact)?;
+        Self::from_data(data)
+    }
+
+    fn from_data(ProofDataHalo2 { proof, public_inputs }: ProofDataHalo2) -> Result<Self, Error> {
+        let public_values = decode_public_values(&public_inputs).map_err(Error::Artifact)?;
+        Ok(Self { proof, public_values })
+    }
+
+    /* The contents of a proof file holding this proof, as the command line
+     * writes it in binary. */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let proof_data = ProofDataHalo2 {
+            proof: self.proof.clone(),
+            public_inputs: encode_public_values(&self.public_values),
+        };
+        artifact_bytes(ArtifactKind::Halo2Proof, |writer| proof_data.serialize(writer))
+            .map_err(Error::Artifact)
+    }
+}
+
+/* Check the given proof against the given values of the public variables, in
+ * the order they are declared. */
+pub fn verifier(params: &Params<EqAffine>, vk: &VerifyingKey<EqAffine>, proof: &[u8], public_values: &[Fp]) -> Result<(), Halo2Error> {
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    verify_proof(params, vk, strategy, &[&[public_values]], &mut transcript)
+}
+
+/* Check the given proof of a circuit with the given number of public
+ * variables against the public values that it carries. */
+pub(crate) fn check_proof(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    public: usize,
+    proof: &Halo2Proof,
+) -> Result<(), Error> {
+    if proof.public_values.len() != public {
+        return Err(Error::InvalidProof(format!(
+            "proof has {} public inputs, but the circuit has {} public variables",
+            proof.public_values.len(),
+            public,
+        )));
+    }
+    verifier(params, vk, &proof.proof, &proof.public_values)
+        .map_err(|err| Error::InvalidProof(format!("{:?}", err)))
+}
+
+/* A Halo2 circuit as a verifier knows it: its public parameters and its
+ * verifying key, without its program or proving key. */
+pub struct VerifierArtifact {
+    params: Params<EqAffine>,
+    vk: VerifyingKey<EqAffine>,
+    // Number of public variables of the circuit
+    public: usize,
+}
+
+impl VerifierArtifact {
+    /* Generate the verifying key of the circuit of the given data, as read
+     * from a verifier file. */
+    pub fn from_data(data: VerifierData) -> Result<Self, Error> {
+        let VerifierData { params, layout } = data;
+        let public = layout.public.len();
+        let vk = keygen_vk(&params, &LayoutCircuit(layout))
+            .map_err(|err| Error::Artifact(format!("unable to generate verifying key: {:?}", err)))?;
+        Ok(Self { params, vk, public })
+    }
+
+    /* Check the given proof against the public values that it carries. */
+    pub fn verify(&self, proof: &Halo2Proof) -> Result<(), Error> {
+        check_proof(&self.params, &self.vk, self.public, proof)
+    }
+
+    /* Check the given proof as verify does, and also that it was made for
+     * the given values of the public variables, in the order that they are
+     * declared. The values are taken modulo the order of the field. */
+    pub fn verify_public(&self, proof: &Halo2Proof, expected: &[BigInt]) -> Result<(), Error> {
+        if expected.len() != self.public {
+            return Err(Error::Inputs(format!(
+                "{} public value(s) were given, but the circuit has {} public variable(s)",
+                expected.len(), self.public,
+            )));
+        }
+        self.verify(proof)?;
+        let modulus = field_value(&-Fp::from(1)) + 1;
+        let mismatches: Vec<_> = expected.iter().zip(&proof.public_values).enumerate()
+            .filter(|(_, (expected, actual))| ((*expected % &modulus) + &modulus) % &modulus != field_value(actual))
+            .map(|(index, _)| index.to_string())
+            .collect();
+        if !mismatches.is_empty() {
+            return Err(Error::InvalidProof(format!(
+                "proof is of other values of the public variable(s) at position(s) {}",
+                mismatches.join(", "),
+            )));
+        }
+        Ok(())
+    }
+}
+
+/* Read a verifier from the contents of a verifier file, in any of its
+ * encodings, and generate its verifying key. Nothing of the program is
+ * compiled or evaluated to do so. */
+pub fn load_verifier_artifact(bytes: &[u8]) -> Result<VerifierArtifact, Error> {
+    let data = parse_artifact(bytes, ArtifactKind::Halo2Verifier, |reader| VerifierData::read(reader))
+        .map_err(Error::Artifact)?;
+    VerifierArtifact::from_data(data)
+}
+
+/* Check the proof in the given contents of a proof file, in any of its
+ * encodings or in a container, with the given verifier. */
+pub fn verify(verifier: &VerifierArtifact, proof: &[u8]) -> Result<(), Error> {
+    verifier.verify(&Halo2Proof::from_bytes(proof)?)
+}
+
+/* Read a verifier from the given reader as load_verifier_artifact does from
+ * memory, decoding its public parameters and layout as they arrive. */
+pub fn read_verifier_artifact(reader: impl Read) -> Result<VerifierArtifact, Error> {
+    let data = parse_artifact_from_reader(reader, ArtifactKind::Halo2Verifier, |reader| VerifierData::read(reader))
+        .map_err(Error::Artifact)?;
+    VerifierArtifact::from_data(data)
+}
+
+/* Check the proof in the given contents of a proof file with the given
+ * verifier, as verify does, and also that it was made for the given values
+ * of the public variables, in the order that they are declared. The values
+ * are taken modulo the order of the field. */
+pub fn verify_public(verifier: &VerifierArtifact, proof: &[u8], expected: &[BigInt]) -> Result<(), Error> {
+    verifier.verify_public(&Halo2Proof::from_bytes(proof)?, expected)
+}
+
+/* Check the proof read from the given reader against the verifier file read
+ * from the other, and against the given values of the public variables if
+ * there are any, without first reading either file into memory. The
+ * verifier may come straight off a socket, as a circuit file may by way of
+ * Halo2Circuit::from_reader. */
+pub fn verify_from_reader(
+    verifier: impl Read,
+    proof: impl Read,
+    expected: Option<&[BigInt]>,
+) -> Result<(), Error> {
+    let verifier = read_verifier_artifact(verifier)?;
+    let proof = Halo2Proof::from_reader(proof)?;
+    match expected {
+        Some(expected) => verifier.verify_public(&proof, expected),
+        None => verifier.verify(&proof),
+    }
+}
@@ -0,0 +1,794 @@
+use crate::ast::{parse_prefixed_num, Module, Variable, VariableId};
+use crate::calldata::decode_public_calldata;
+use crate::interface::{describe_unexpected, input_shapes, InputShape};
+use crate::status::CommandError;
+use crate::stdio::is_stdin;
+use crate::transform::{input_name, ordered_module_variables, required_inputs, FieldOps};
+use clap::ValueEnum;
+use log::{debug, warn};
+use num_bigint::BigInt;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/* Whether files of inputs may name variables that the program lacks, as
+ * decided once at start. */
+static ALLOW_EXTRA_INPUTS: AtomicBool = AtomicBool::new(false);
+
+/* Decide whether names in files of inputs that match no variable of the
+ * program are ignored, as suits files shared by several programs, rather than
+ * rejected as the typos they usually are. */
+pub fn init(allow_extra_inputs: bool) {
+    ALLOW_EXTRA_INPUTS.store(allow_extra_inputs, Ordering::Relaxed);
+}
+
+/* The formats that files of inputs can be written in. Each maps variable
+ * names to values. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum InputsFormat {
+    /// JSON whose values are strings, as in the .inputs files written
+    /// alongside circuits
+    Inputs,
+    /// JSON whose values are strings or numbers
+    Json,
+    /// TOML whose values are strings or integers
+    Toml,
+}
+
+impl InputsFormat {
+    /* Determine the format of the given file from its extension. Standard
+     * input has none, so it is read as JSON, which accepts everything that the
+     * native format does. */
+    pub fn detect(path: &Path) -> Self {
+        if is_stdin(path) {
+            return InputsFormat::Json;
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => InputsFormat::Json,
+            Some("toml") => InputsFormat::Toml,
+            _ => InputsFormat::Inputs,
+        }
+    }
+}
+
+/* Describe where inputs from the given path are read from. */
+pub fn describe_inputs(path: &Path) -> String {
+    if is_stdin(path) {
+        "standard input".to_string()
+    } else {
+        path.to_string_lossy().to_string()
+    }
+}
+
+/* Make the JSON path of the value of the given variable name. */
+fn json_path(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        format!("$.{}", name)
+    } else {
+        format!("$[{}]", Value::String(name.to_string()))
+    }
+}
+
+/* Parse the given text as the value of an input, wherever it was given: in a
+ * file, on the command line, or at a prompt. Values are integers, possibly
+ * negative, in decimal or with a 0x, 0o, or 0b prefix. */
+pub fn parse_input_value(text: &str) -> Result<BigInt, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("expected an integer, found nothing".to_string());
+    }
+    parse_prefixed_num(text).map_err(|_| format!("expected an integer, found {}", text))
+}
+
+/* The prefix of the names of environment variables that supply inputs. */
+pub const ENV_INPUT_PREFIX: &str = "VAMPIR_INPUT_";
+
+/* The name of the environment variable that supplies the input of the given
+ * name. Every character other than an ASCII letter, digit, or underscore,
+ * such as the dot before a tuple component or the colons of a qualified name,
+ * becomes an underscore, so that p.0 is read from VAMPIR_INPUT_p_0. Case is
+ * kept. */
+pub fn env_input_var(name: &str) -> String {
+    let mangled: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}{}", ENV_INPUT_PREFIX, mangled)
+}
+
+/* Read the values of the given input variables from the environment variables
+ * named by env_input_var, parsing them as values given on the command line
+ * are. An environment variable that would supply more than one input is an
+ * error, and one with the prefix that supplies none is warned about. */
+fn read_env_inputs(input_variables: &[Variable]) -> Result<BTreeMap<String, BigInt>, String> {
+    let mut names: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for var in input_variables {
+        let name = input_name(var);
+        names.entry(env_input_var(&name)).or_default().insert(name);
+    }
+    for (key, _) in std::env::vars_os() {
+        let key = key.to_string_lossy();
+        if key.starts_with(ENV_INPUT_PREFIX) && !names.contains_key(&*key) {
+            warn!("environment variable {} does not name an input", key);
+        }
+    }
+    let mut named_inputs = BTreeMap::new();
+    for (key, inputs) in names {
+        let text = match std::env::var(&key) {
+            Ok(text) => text,
+            Err(std::env::VarError::NotPresent) => continue,
+            Err(err) => return Err(format!("environment variable {}: {}", key, err)),
+        };
+        if inputs.len() > 1 {
+            let inputs: Vec<_> = inputs.into_iter().collect();
+            return Err(format!("environment variable {} could supply any of {}", key, inputs.join(", ")));
+        }
+        let value = parse_input_value(&text)
+            .map_err(|err| format!("environment variable {}: {}", key, err))?;
+        named_inputs.extend(inputs.into_iter().map(|name| (name, value.clone())));
+    }
+    Ok(named_inputs)
+}
+
+/* Parse the given JSON value, found at the given path, as an integer. */
+fn parse_value(value: &Value, path: &str, format: InputsFormat) -> Result<BigInt, String> {
+    match (value, format) {
+        (Value::String(string), _) => parse_input_value(string)
+            .map_err(|err| format!("{}: {}", path, err)),
+        (Value::Number(number), InputsFormat::Json | InputsFormat::Toml) => match (number.as_i64(), number.as_u64()) {
+            (Some(int), _) => Ok(BigInt::from(int)),
+            (_, Some(int)) => Ok(BigInt::from(int)),
+            _ => Err(format!(
+                "{}: expected an integer, found {}; write large values as strings",
+                path, number,
+            )),
+        },
+        (Value::Number(_), InputsFormat::Inputs) => Err(format!(
+            "{}: expected a string, found {}; use --format json to allow numbers",
+            path, value,
+        )),
+        (Value::Array(_), _) => Err(format!("{}: expected an integer, found a list", path)),
+        (Value::Object(_), _) => Err(format!(
+            "{}: objects are reserved for future struct types",
+            path,
+        )),
+        (Value::Null | Value::Bool(_), _) =>
+            Err(format!("{}: expected an integer, found {}", path, value)),
+    }
+}
+
+/* Flatten the given JSON value, found at the given path, into the values of
+ * the variables that the input of the given name and shape was split into. */
+fn flatten_value(
+    value: &Value,
+    shape: &InputShape,
+    name: &str,
+    path: &str,
+    format: InputsFormat,
+    named_inputs: &mut BTreeMap<String, BigInt>,
+) -> Result<(), String> {
+    match (shape, value) {
+        (InputShape::Int, _) => {
+            named_inputs.insert(name.to_string(), parse_value(value, path, format)?);
+        },
+        (InputShape::Unit, Value::Array(elements)) if elements.is_empty() => {},
+        (InputShape::Unit, _) =>
+            return Err(format!("{}: expected [] for a value of type (), found {}", path, value)),
+        // A pair whose second component is itself a tuple may also be given
+        // as a list of its two components
+        (InputShape::Pair(first, rest), Value::Array(elements))
+            if elements.len() == 2 && matches!(rest.as_ref(), InputShape::Pair(_, _)) => {
+            flatten_value(&elements[0], first, &format!("{}.0", name), &format!("{}[0]", path), format, named_inputs)?;
+            flatten_value(&elements[1], rest, &format!("{}.1", name), &format!("{}[1]", path), format, named_inputs)?;
+        },
+        (InputShape::Pair(_, _), Value::Array(elements)) => {
+            let components = shape.components();
+            if elements.len() != components.len() {
+                return Err(format!(
+                    "{}: expected {} values for type {}, found {}",
+                    path, components.len(), shape, elements.len(),
+                ));
+            }
+            for (index, (element, component)) in elements.iter().zip(components).enumerate() {
+                let name = name.to_string() + &shape.component_suffix(index);
+                let path = format!("{}[{}]", path, index);
+                flatten_value(element, component, &name, &path, format, named_inputs)?;
+            }
+        },
+        (InputShape::Pair(_, _), _) => return Err(format!(
+            "{}: expected a list of {} values for type {}, found {}",
+            path, shape.components().len(), shape, value,
+        )),
+    }
+    Ok(())
+}
+
+/* Blank out the comments in the given text of a file in the native format,
+ * which run from // or # outside of strings to the end of their lines. They
+ * are replaced by spaces rather than removed so that errors cite the lines
+ * and columns of the file. */
+fn strip_comments(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let (mut in_string, mut escaped, mut in_comment) = (false, false, false);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_comment {
+            in_comment = c != '\n';
+            stripped.push(if in_comment { ' ' } else { c });
+            continue;
+        }
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '#' || (c == '/' && chars.peek() == Some(&'/')) {
+            in_comment = true;
+            stripped.push(' ');
+            continue;
+        }
+        stripped.push(c);
+    }
+    stripped
+}
+
+/* The number of the line of the given JSON text on which the entry of the
+ * given name is keyed, if it can be found. */
+fn entry_line(text: &str, name: &str) -> Option<usize> {
+    let key = Value::String(name.to_string()).to_string();
+    text.lines()
+        .position(|line| line.split_once(&key).is_some_and(|(_, rest)| rest.trim_start().starts_with(':')))
+        .map(|index| index + 1)
+}
+
+/* Read the named values from the given file of inputs, or from standard input
+ * if the path is -, in the given format or the format implied by the path if
+ * none is given. Inputs of the given variables that are tuples may be given
+ * as lists, which are flattened into their components, or by the names of
+ * their components. Files in the native format may have comments starting
+ * with // or #. Errors cite the JSON path of the offending value and, in
+ * JSON files, the line of its entry. */
+pub fn read_named_inputs(
+    path: &Path,
+    input_variables: &[Variable],
+    format: Option<InputsFormat>,
+) -> Result<BTreeMap<String, BigInt>, String> {
+    let format = format.unwrap_or_else(|| InputsFormat::detect(path));
+    let text = if is_stdin(path) {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).map(|_| text)
+    } else {
+        fs::read_to_string(path)
+    }.map_err(|err| err.to_string())?;
+    let text = match format {
+        InputsFormat::Inputs => strip_comments(&text),
+        InputsFormat::Json | InputsFormat::Toml => text,
+    };
+    let document: Value = match format {
+        InputsFormat::Toml => toml::from_str(&text).map_err(|err| err.to_string())?,
+        InputsFormat::Inputs | InputsFormat::Json =>
+            serde_json::from_str(&text).map_err(|err| err.to_string())?,
+    };
+    let entries = match document {
+        Value::Object(entries) => entries,
+        _ => return Err("$: expected an object mapping variable names to values".to_string()),
+    };
+    let shapes = input_shapes(input_variables);
+    let mut named_inputs = BTreeMap::new();
+    for (name, value) in &entries {
+        let parsed = match shapes.get(name) {
+            Some(shape) => flatten_value(value, shape, name, &json_path(name), format, &mut named_inputs),
+            None => parse_value(value, &json_path(name), format)
+                .map(|value| { named_inputs.insert(name.clone(), value); }),
+        };
+        parsed.map_err(|err| match (format, entry_line(&text, name)) {
+            (InputsFormat::Inputs | InputsFormat::Json, Some(line)) => format!("line {}: {}", line, err),
+            _ => err,
+        })?;
+    }
+    Ok(named_inputs)
+}
+
+/* Write the value of the given shape in a template, with the given value
+ * standing in for each integer. */
+fn template_value(shape: &InputShape, placeholder: &str) -> String {
+    match shape {
+        InputShape::Int => placeholder.to_string(),
+        InputShape::Unit => "[]".to_string(),
+        InputShape::Pair(_, _) => {
+            let components: Vec<_> = shape.components().iter()
+                .map(|component| template_value(component, placeholder))
+                .collect();
+            format!("[{}]", components.join(", "))
+        },
+    }
+}
+
+/* Write a file of inputs in the given format that names every input to the
+ * given program, in the order that they would be prompted for, without
+ * values. JSON has null for each value, whereas TOML, which has no null, has
+ * empty strings along with a comment giving the type and visibility of each
+ * input. The inputs are those that proving demands. */
+pub fn inputs_template(annotated: &Module, format: InputsFormat) -> String {
+    let interface = annotated.interface();
+    let mut lines = vec![];
+    match format {
+        InputsFormat::Inputs | InputsFormat::Json => {
+            let entries: Vec<_> = interface.required()
+                .map(|input| format!("  {}: {}", Value::String(input.name.clone()), template_value(&input.shape, "null")))
+                .collect();
+            lines.push("{".to_string());
+            if !entries.is_empty() {
+                lines.push(entries.join(",\n"));
+            }
+            lines.push("}".to_string());
+        },
+        InputsFormat::Toml => for input in interface.required() {
+            let name = &input.name;
+            let bare = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            let key = if bare { name.clone() } else { Value::String(name.clone()).to_string() };
+            lines.push(format!("# {}, {}", input.shape, input.visibility.name()));
+            lines.push(format!("{} = {}", key, template_value(&input.shape, "\"\"")));
+        },
+    }
+    lines.join("\n") + "\n"
+}
+
+/* Whether inputs that were supplied neither on the command line nor in a file
+ * may be prompted for. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Interactivity {
+    Prompt,
+    Fail,
+}
+
+impl Interactivity {
+    /* Choose the policy requested by the given flag or, failing that, by the
+     * CI or VAMPIR_NONINTERACTIVE environment variables. Variables that are
+     * empty, 0, or false do not count. */
+    pub fn choose(non_interactive: bool) -> Self {
+        let env_set = |name| match std::env::var(name) {
+            Ok(value) => !matches!(value.trim().to_lowercase().as_str(), "" | "0" | "false"),
+            Err(_) => false,
+        };
+        if non_interactive || env_set("CI") || env_set("VAMPIR_NONINTERACTIVE") {
+            Interactivity::Fail
+        } else {
+            Interactivity::Prompt
+        }
+    }
+}
+
+/* Take the names in the given named assignments that match no variable of
+ * the given program out of them. Unless extra inputs are allowed, in which
+ * case they are dropped, the names taken are returned. */
+fn take_unknown_inputs(
+    annotated: &Module,
+    named_assignments: &mut BTreeMap<String, BigInt>,
+) -> BTreeSet<String> {
+    let declared: HashSet<_> = ordered_module_variables(annotated).iter().map(input_name).collect();
+    let unknown: BTreeSet<_> = named_assignments.keys()
+        .filter(|name| !declared.contains(*name))
+        .cloned()
+        .collect();
+    named_assignments.retain(|name, _| declared.contains(name));
+    if !unknown.is_empty() && ALLOW_EXTRA_INPUTS.load(Ordering::Relaxed) {
+        let unknown: Vec<_> = unknown.into_iter().collect();
+        debug!("** Ignoring inputs that match no variable: {}", unknown.join(", "));
+        return BTreeSet::new();
+    }
+    unknown
+}
+
+/* Describe the given names that match no variable, each with the one of the
+ * given inputs that it likely misspells. */
+fn describe_unknown(unknown: &BTreeSet<String>, inputs: &BTreeSet<String>) -> String {
+    format!("unknown inputs: {}; give --allow-extra-inputs to ignore them", describe_unexpected(unknown, inputs))
+}
+
+/* Check that the given named assignments, read from a file of inputs, only
+ * name variables of the given program. Names that match none are rejected,
+ * with the inputs they likely misspell, or dropped if extra inputs are
+ * allowed. */
+pub fn check_known_inputs(
+    annotated: &Module,
+    named_assignments: &mut BTreeMap<String, BigInt>,
+) -> Result<(), String> {
+    let unknown = take_unknown_inputs(annotated, named_assignments);
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    let inputs: BTreeSet<_> = required_inputs(annotated).iter().chain(&annotated.pubs).map(input_name).collect();
+    Err(describe_unknown(&unknown, &inputs))
+}
+
+/* Check that the given named assignments only supply the given input
+ * variables and, if required, supply all of them, and that no name matching
+ * no variable at all, as collected from files of inputs, was given. Every
+ * missing, unknown, and unexpected name is reported in one message, so that a
+ * file with several mistakes need not be fixed one run at a time. */
+fn check_input_names(
+    input_variables: &[Variable],
+    named_assignments: &BTreeMap<String, BigInt>,
+    unknown: &BTreeSet<String>,
+    require_all: bool,
+) -> Result<(), String> {
+    let expected: BTreeSet<_> = input_variables
+        .iter()
+        .map(input_name)
+        .collect();
+    let missing: Vec<_> = expected
+        .iter()
+        .filter(|name| require_all && !named_assignments.contains_key(*name))
+        .cloned()
+        .collect();
+    let unexpected: Vec<_> = named_assignments
+        .keys()
+        .filter(|name| !expected.contains(*name))
+        .cloned()
+        .collect();
+    let mut problems = vec![];
+    if !missing.is_empty() {
+        problems.push(format!("missing inputs: {}", missing.join(", ")));
+    }
+    if !unknown.is_empty() {
+        problems.push(describe_unknown(unknown, &expected));
+    }
+    if !unexpected.is_empty() {
+        problems.push(format!("unexpected inputs: {}", describe_unexpected(&unexpected, &expected)));
+    }
+    if problems.is_empty() { Ok(()) } else { Err(problems.join("; ")) }
+}
+
+/* Describe where the given files and command line values came from, so that
+ * problems with the names they give can be traced back to them. */
+fn describe_given(files: &[(&Path, InputScope)], overrides: &[(String, BigInt)]) -> String {
+    let mut given: Vec<_> = files.iter().map(|(path, _)| describe_inputs(path)).collect();
+    if !overrides.is_empty() {
+        given.push("command line".to_string());
+    }
+    given.join(" and ")
+}
+
+/* Which of the inputs to a program a file of inputs may name. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputScope {
+    // Any input, public or not
+    All,
+    // Only public variables, as a verifier would supply
+    Public,
+    // Only variables that are not public, as kept secret by the prover
+    Private,
+}
+
+/* The names by which the public variables of the given program are given. */
+fn public_names(annotated: &Module) -> BTreeSet<String> {
+    annotated.pubs.iter().map(input_name).collect()
+}
+
+/* Check that the given named assignments only name variables that a file of
+ * the given scope may name for the given program. */
+fn check_scope(
+    annotated: &Module,
+    named_assignments: &BTreeMap<String, BigInt>,
+    scope: InputScope,
+) -> Result<(), String> {
+    let public = public_names(annotated);
+    let misplaced: Vec<_> = named_assignments.keys()
+        .filter(|name| match scope {
+            InputScope::All => false,
+            InputScope::Public => !public.contains(*name),
+            InputScope::Private => public.contains(*name),
+        })
+        .cloned()
+        .collect();
+    match scope {
+        _ if misplaced.is_empty() => Ok(()),
+        InputScope::Public => Err(format!(
+            "not declared pub, so not public inputs: {}; give them with the private inputs",
+            misplaced.join(", "),
+        )),
+        InputScope::Private => Err(format!(
+            "declared pub, so not private inputs: {}; give them with the public inputs",
+            misplaced.join(", "),
+        )),
+        InputScope::All => unreachable!("files of all inputs may name anything"),
+    }
+}
+
+/* Read the values of the public variables of the given program from the given
+ * file, which must name every one of them and nothing else. */
+pub fn read_public_inputs(
+    annotated: &Module,
+    path: &Path,
+    format: Option<InputsFormat>,
+) -> Result<BTreeMap<String, BigInt>, String> {
+    let mut named_assignments = read_named_inputs(path, &annotated.pubs, format)?;
+    check_known_inputs(annotated, &mut named_assignments)?;
+    check_scope(annotated, &named_assignments, InputScope::Public)?;
+    let missing: Vec<_> = public_names(annotated).into_iter()
+        .filter(|name| !named_assignments.contains_key(name))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("missing public inputs: {}", missing.join(", ")));
+    }
+    Ok(named_assignments)
+}
+
+/* Read the values that the public variables of the given program must take,
+ * if any are given, from the given file of public inputs or else from the
+ * given ABI-encoded calldata over the field described by the given
+ * operations. */
+pub fn read_expected_public(
+    annotated: &Module,
+    public_inputs: Option<&Path>,
+    public_calldata: Option<&str>,
+    format: Option<InputsFormat>,
+    field_ops: &dyn FieldOps,
+) -> Result<Option<BTreeMap<String, BigInt>>, CommandError> {
+    if let Some(path) = public_inputs {
+        return read_public_inputs(annotated, path, format)
+            .map(Some)
+            .map_err(|err| CommandError::input(&describe_inputs(path), err));
+    }
+    match public_calldata {
+        Some(calldata) => {
+            let modulus = field_ops.canonical(BigInt::from(-1)) + 1;
+            decode_public_calldata(calldata, &annotated.interface(), &modulus)
+                .map(Some)
+                .map_err(|err| CommandError::input("--public-calldata", err))
+        },
+        None => Ok(None),
+    }
+}
+
+/* Gather the values of inputs named in the given files, each of which may only
+ * name the inputs in its scope and none of which may name an input that
+ * another does, overlaid with those in the environment if asked for and then
+ * with those given on the command line. Public variables that the program
+ * derives may be named in files of public inputs, so that provers and
+ * verifiers can share them, but are left out. Where each value came from is
+ * also given, as are the names in files that match no variable, which are
+ * left for the caller to report with whatever else is amiss. */
+fn gather_named_inputs(
+    annotated: &Module,
+    input_variables: &[Variable],
+    files: &[(&Path, InputScope)],
+    format: Option<InputsFormat>,
+    env_inputs: bool,
+    overrides: &[(String, BigInt)],
+) -> Result<(BTreeMap<String, BigInt>, BTreeMap<String, String>, BTreeSet<String>), CommandError> {
+    let inputs: BTreeSet<_> = input_variables.iter().map(input_name).collect();
+    let shaped: Vec<_> = input_variables.iter().chain(&annotated.pubs).cloned().collect();
+    // Read the user-supplied inputs from the files, if any
+    let mut named_assignments = BTreeMap::new();
+    let mut sources: BTreeMap<String, String> = BTreeMap::new();
+    let mut unknown = BTreeSet::new();
+    for (path, scope) in files {
+        let source = describe_inputs(path);
+        debug!("* Reading inputs from {}...", source);
+        let mut named = read_named_inputs(path, &shaped, format)
+            .map_err(|err| CommandError::input(&source, err))?;
+        unknown.extend(take_unknown_inputs(annotated, &mut named));
+        check_scope(annotated, &named, *scope)
+            .map_err(|err| CommandError::input(&source, err))?;
+        if *scope == InputScope::Public {
+            named.retain(|name, _| inputs.contains(name));
+        }
+        for (name, value) in named {
+            if let Some(other) = sources.insert(name.clone(), source.clone()) {
+                return Err(CommandError::Input(format!("{} is given both in {} and in {}", name, other, source)));
+            }
+            named_assignments.insert(name, value);
+        }
+    }
+
+    // Overlay the values given in the environment
+    if env_inputs {
+        let named = read_env_inputs(input_variables).map_err(CommandError::Input)?;
+        for (name, value) in named {
+            sources.insert(name.clone(), format!("environment variable {}", env_input_var(&name)));
+            named_assignments.insert(name, value);
+        }
+    }
+
+    // Overlay the values given on the command line
+    sources.extend(overrides.iter().map(|(name, _)| (name.clone(), "command line".to_string())));
+    named_assignments.extend(overrides.iter().cloned());
+    Ok((named_assignments, sources, unknown))
+}
+
+/* Collect the values that the given inputs file, if any, the environment, if
+ * asked to, and the command line supply for inputs to the given program.
+ * Unlike resolve_inputs, inputs that are supplied by none of them are left
+ * unassigned rather than asked for. */
+pub fn supplied_inputs(
+    annotated: &Module,
+    inputs: Option<&Path>,
+    format: Option<InputsFormat>,
+    env_inputs: bool,
+    overrides: &[(String, BigInt)],
+) -> Result<HashMap<VariableId, BigInt>, CommandError> {
+    let input_variables = required_inputs(annotated);
+    let files: Vec<_> = inputs.map(|path| (path, InputScope::All)).into_iter().collect();
+    let (named_assignments, _, unknown) =
+        gather_named_inputs(annotated, &input_variables, &files, format, env_inputs, overrides)?;
+    check_input_names(&input_variables, &named_assignments, &unknown, false)
+        .map_err(|err| CommandError::input(&describe_given(&files, overrides), err))?;
+    Ok(input_variables.into_iter()
+        .filter_map(|var| named_assignments.get(&input_name(&var)).map(|value| (var.id, value.clone())))
+        .collect())
+}
+
+/* Resolve satisfying inputs to the given program. Values given on the command
+ * line take precedence over those in the environment, if asked for, which
+ * take precedence over those in the given inputs file, or the expected one if
+ * none is given and it exists. Without a file, the values supplied by neither
+ * are prompted for if the given policy allows it. Since a file of - is read
+ * from standard input, giving one rules out prompting. The resulting
+ * assignment is logged at debug level. */
+pub fn resolve_inputs(
+    annotated: &Module,
+    inputs: Option<&Path>,
+    expected_inputs: &Path,
+    format: Option<InputsFormat>,
+    env_inputs: bool,
+    overrides: &[(String, BigInt)],
+    interactivity: Interactivity,
+) -> Result<HashMap<VariableId, BigInt>, CommandError> {
+    // Get the expected inputs from the circuit module
+    let input_variables = required_inputs(annotated);
+    let path = inputs.or_else(|| expected_inputs.exists().then_some(expected_inputs));
+    if let Some(path) = path {
+        return resolve_file_inputs(annotated, &[(path, InputScope::All)], format, env_inputs, overrides);
+    }
+
+    // Ask for the inputs supplied by neither the environment nor the command
+    // line, once the names given there are known to be right
+    let (mut named_assignments, mut sources, _) =
+        gather_named_inputs(annotated, &input_variables, &[], format, env_inputs, overrides)?;
+    let missing: Vec<_> = input_variables.iter()
+        .map(input_name)
+        .filter(|name| !named_assignments.contains_key(name))
+        .collect();
+    let mut problems: Vec<_> = check_input_names(&input_variables, &named_assignments, &BTreeSet::new(), false)
+        .err()
+        .map(|err| format!("command line: {}", err))
+        .into_iter()
+        .collect();
+    if !missing.is_empty() && interactivity == Interactivity::Fail {
+        problems.insert(0, format!(
+            "missing inputs: {}; they were not given with -D{}, no file was given \
+             with --inputs, {} does not exist, and prompting is disabled",
+            missing.join(", "),
+            if env_inputs { " or in the environment" } else { "" },
+            expected_inputs.to_string_lossy(),
+        ));
+    }
+    if !problems.is_empty() {
+        return Err(CommandError::Input(problems.join("; ")));
+    }
+    if !missing.is_empty() {
+        eprintln!("* Soliciting circuit witnesses...");
+        prompt_inputs(annotated, &input_variables, &mut named_assignments)?;
+        sources.extend(missing.into_iter().map(|name| (name, "prompt".to_string())));
+    }
+    Ok(assign_inputs(annotated, input_variables, &named_assignments, &sources, env_inputs))
+}
+
+/* The files of public and private inputs given to a prover, each with the
+ * scope of the inputs it may name, or none if neither was given. */
+pub fn split_input_files<'a>(
+    public_inputs: Option<&'a Path>,
+    private_inputs: Option<&'a Path>,
+) -> Vec<(&'a Path, InputScope)> {
+    let public = public_inputs.map(|path| (path, InputScope::Public));
+    let private = private_inputs.map(|path| (path, InputScope::Private));
+    public.into_iter().chain(private).collect()
+}
+
+/* Resolve satisfying inputs to the given program from the given files, as
+ * gather_named_inputs reads them, with values given in the environment, if
+ * asked for, and then on the command line taking precedence. Every input must
+ * be supplied, and the user is told of every one that is not, along with every
+ * name that matches no input, in one message before any is used. When inputs
+ * are split into public and private files, or taken from the environment, the
+ * values of private ones are kept out of the log. */
+pub fn resolve_file_inputs(
+    annotated: &Module,
+    files: &[(&Path, InputScope)],
+    format: Option<InputsFormat>,
+    env_inputs: bool,
+    overrides: &[(String, BigInt)],
+) -> Result<HashMap<VariableId, BigInt>, CommandError> {
+    let input_variables = required_inputs(annotated);
+    let (named_assignments, sources, unknown) =
+        gather_named_inputs(annotated, &input_variables, files, format, env_inputs, overrides)?;
+    check_input_names(&input_variables, &named_assignments, &unknown, true)
+        .map_err(|err| CommandError::input(&describe_given(files, overrides), err))?;
+    let withhold_private = env_inputs || files.iter().any(|(_, scope)| *scope != InputScope::All);
+    Ok(assign_inputs(annotated, input_variables, &named_assignments, &sources, withhold_private))
+}
+
+/* Assign the given named values to the given input variables of the given
+ * program, logging the assignment and where each value came from at debug
+ * level, with the values of private inputs withheld if asked to. */
+fn assign_inputs(
+    annotated: &Module,
+    input_variables: Vec<Variable>,
+    named_assignments: &BTreeMap<String, BigInt>,
+    sources: &BTreeMap<String, String>,
+    withhold_private: bool,
+) -> HashMap<VariableId, BigInt> {
+    let public = public_names(annotated);
+    debug!("* Effective inputs:");
+    let mut variable_assignments = HashMap::new();
+    for expected_var in input_variables {
+        let name = input_name(&expected_var);
+        let value = named_assignments[&name].clone();
+        let source = &sources[&name];
+        if withhold_private && !public.contains(&name) {
+            debug!("** {} = <private> (from {})", name, source);
+        } else {
+            debug!("** {} = {} (from {})", name, value, source);
+        }
+        variable_assignments.insert(expected_var.id, value);
+    }
+    variable_assignments
+}
+
+/* Prompt for the values of those of the given inputs to the given program
+ * that are not yet named in the given assignments. Values that cannot be
+ * parsed are asked for again, and standard input closing is an error. */
+fn prompt_inputs(
+    annotated: &Module,
+    input_variables: &[Variable],
+    named_assignments: &mut BTreeMap<String, BigInt>,
+) -> Result<(), CommandError> {
+    // Collect all public variables in order to enable annotations
+    let mut public_variables = HashSet::new();
+    for var in &annotated.pubs {
+        public_variables.insert(var.id);
+    }
+
+    // Solicit input variables from user in the order they occur in
+    for var in input_variables {
+        if named_assignments.contains_key(&input_name(var)) {
+            continue;
+        }
+        let visibility = if public_variables.contains(&var.id) {
+            "(public)"
+        } else {
+            "(private)"
+        };
+        let value = loop {
+            eprint!("** {} {}: ", var, visibility);
+            std::io::stderr().flush()
+                .map_err(|err| CommandError::input("unable to write standard error", err))?;
+            let mut input_line = String::new();
+            let read = std::io::stdin()
+                .read_line(&mut input_line)
+                .map_err(|err| CommandError::input("unable to read standard input", err))?;
+            if read == 0 {
+                eprintln!();
+                return Err(CommandError::Input(format!(
+                    "standard input closed before a value was given for {}",
+                    input_name(var),
+                )));
+            }
+            match parse_input_value(&input_line) {
+                Ok(value) => break value,
+                Err(err) => eprintln!("*** {}; give a decimal, 0x hexadecimal, or negative integer", err),
+            }
+        };
+        named_assignments.insert(input_name(var), value);
+    }
+    Ok(())
+}
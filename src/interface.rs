@@ -0,0 +1,282 @@
+use crate::ast::{Module, Pat, Variable};
+use crate::transform::{input_name, is_auxiliary_name, required_inputs};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/* The version of the layout of interface documents. It is raised whenever a
+ * field is removed or changes meaning, so that callers can refuse documents
+ * they would misread; fields may be added without raising it. */
+pub const INTERFACE_VERSION: u64 = 1;
+
+/* The shape of the value of an input. The compiler splits each input of a
+ * tuple type into variables suffixed .0 and .1 for the components of its
+ * pairs, so that (a, b, c), being the pair of a and (b, c), becomes a.0,
+ * a.1.0, and a.1.1. */
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum InputShape {
+    Int,
+    Unit,
+    Pair(Box<InputShape>, Box<InputShape>),
+}
+
+impl InputShape {
+    /* The components of this shape as written in a tuple, which flattens the
+     * pairs nested in the second component of a pair. */
+    pub fn components(&self) -> Vec<&InputShape> {
+        match self {
+            InputShape::Pair(first, rest) => {
+                let mut components = vec![first.as_ref()];
+                match rest.as_ref() {
+                    InputShape::Pair(_, _) => components.extend(rest.components()),
+                    rest => components.push(rest),
+                }
+                components
+            },
+            shape => vec![shape],
+        }
+    }
+
+    /* The suffix of the variables for the given component of this shape. */
+    pub fn component_suffix(&self, index: usize) -> String {
+        if index + 1 == self.components().len() {
+            ".1".repeat(index)
+        } else {
+            ".1".repeat(index) + ".0"
+        }
+    }
+
+    /* Record that the variable with the given suffix path lies within this
+     * shape, widening the shape as necessary. */
+    fn insert(&mut self, path: &[&str]) {
+        let (side, rest) = match path.split_first() {
+            Some(split) => split,
+            None => {
+                *self = InputShape::Int;
+                return;
+            },
+        };
+        if !matches!(self, InputShape::Pair(_, _)) {
+            *self = InputShape::Pair(Box::new(InputShape::Unit), Box::new(InputShape::Unit));
+        }
+        if let InputShape::Pair(first, second) = self {
+            if *side == "0" { first.insert(rest) } else { second.insert(rest) }
+        }
+    }
+
+    /* This shape as it is written in interface documents: "int" for an
+     * integer, and an array of the shapes of the components of a tuple, which
+     * is empty for the unit. */
+    pub fn to_json(&self) -> Value {
+        match self {
+            InputShape::Int => json!("int"),
+            InputShape::Unit => json!([]),
+            InputShape::Pair(_, _) => Value::Array(self.components().iter().map(|shape| shape.to_json()).collect()),
+        }
+    }
+}
+
+impl std::fmt::Display for InputShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputShape::Int => write!(f, "int"),
+            InputShape::Unit => write!(f, "()"),
+            InputShape::Pair(_, _) => {
+                let components: Vec<_> = self.components().iter().map(ToString::to_string).collect();
+                write!(f, "({})", components.join(", "))
+            },
+        }
+    }
+}
+
+/* Split the given input name into the name of the whole input and the path
+ * to the component within it, if the name is that of a component. */
+pub fn split_input_name(name: &str) -> (&str, Vec<&str>) {
+    let mut parts = name.split('.');
+    let root = parts.next().unwrap_or_default();
+    let path: Vec<_> = parts.collect();
+    if path.iter().all(|part| *part == "0" || *part == "1") {
+        (root, path)
+    } else {
+        (name, vec![])
+    }
+}
+
+/* Recover the shapes of the given input variables from their names, keyed by
+ * the names of the whole inputs that they were split from. */
+pub fn input_shapes(input_variables: &[Variable]) -> BTreeMap<String, InputShape> {
+    let mut shapes = BTreeMap::new();
+    for var in input_variables {
+        let name = input_name(var);
+        let (root, path) = split_input_name(&name);
+        shapes.entry(root.to_string()).or_insert(InputShape::Unit).insert(&path);
+    }
+    shapes
+}
+
+/* Who supplies the value of an input, and who gets to see it. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Visibility {
+    // Known to the verifier as well as the prover
+    Public,
+    // Supplied by the prover and known only to it
+    Private,
+    // Derived by the prover from the other inputs, and known only to it
+    Fresh,
+}
+
+impl Visibility {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+            Visibility::Fresh => "fresh",
+        }
+    }
+}
+
+/* A whole input of a circuit, which may have been split into several
+ * variables if it is a tuple. */
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct InterfaceInput {
+    pub name: String,
+    pub visibility: Visibility,
+    // Whether proving demands a value for this input
+    pub required: bool,
+    pub shape: InputShape,
+    // Names of the variables that this input was split into, in order
+    pub variables: Vec<String>,
+}
+
+/* What a circuit expects of those that prove with it, without its
+ * constraints. Every integer is an element of the field of the circuit, as
+ * programs declare nothing narrower. */
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Interface {
+    pub version: u64,
+    pub inputs: Vec<InterfaceInput>,
+    // Variables whose values proofs carry, in the order they are declared
+    pub public: Vec<String>,
+}
+
+impl Interface {
+    /* Write this interface as a JSON document of its version, an array of
+     * its inputs, each with its name, visibility, whether it is required, its
+     * type as written in programs, its shape, and its variables, and the
+     * public variables in the order that proofs carry their values. */
+    pub fn to_json(&self) -> Value {
+        let inputs: Vec<_> = self.inputs.iter().map(|input| json!({
+            "name": input.name,
+            "visibility": input.visibility.name(),
+            "required": input.required,
+            "type": input.shape.to_string(),
+            "shape": input.shape.to_json(),
+            "variables": input.variables,
+        })).collect();
+        json!({ "version": self.version, "inputs": inputs, "public": self.public })
+    }
+
+    /* The inputs that proving demands, in the order they are prompted for. */
+    pub fn required(&self) -> impl Iterator<Item = &InterfaceInput> {
+        self.inputs.iter().filter(|input| input.required)
+    }
+}
+
+/* Group the given variables into whole inputs of the given visibility,
+ * skipping those whose inputs were already described. */
+fn push_inputs(
+    inputs: &mut Vec<InterfaceInput>,
+    vars: &[Variable],
+    visibility: impl Fn(&str) -> Visibility,
+    required: bool,
+) {
+    let shapes = input_shapes(vars);
+    let known: HashSet<String> = inputs.iter().map(|input| input.name.clone()).collect();
+    for var in vars {
+        let name = input_name(var);
+        let root = split_input_name(&name).0.to_string();
+        if known.contains(&root) {
+            continue;
+        }
+        match inputs.iter_mut().find(|input| input.name == root) {
+            Some(input) => if !input.variables.contains(&name) {
+                input.variables.push(name);
+            },
+            None => inputs.push(InterfaceInput {
+                visibility: visibility(&root),
+                required,
+                shape: shapes[&root].clone(),
+                variables: vec![name],
+                name: root,
+            }),
+        }
+    }
+}
+
+impl Module {
+    /* Describe the inputs of this module: first those that proving demands,
+     * in the order they are prompted for, then the public variables derived
+     * from them, then the variables named in the program that the prover
+     * derives. */
+    pub fn interface(&self) -> Interface {
+        let public: HashSet<_> = self.pubs.iter()
+            .map(|var| split_input_name(&input_name(var)).0.to_string())
+            .collect();
+        let visibility = |root: &str| if public.contains(root) { Visibility::Public } else { Visibility::Private };
+        let mut inputs = vec![];
+        push_inputs(&mut inputs, &required_inputs(self), visibility, true);
+        push_inputs(&mut inputs, &self.pubs, |_| Visibility::Public, false);
+        let fresh: Vec<_> = self.defs.iter()
+            .filter_map(|def| match &def.0.0.v {
+                Pat::Variable(var) => var.name.as_ref().map(|_| var.clone()),
+                _ => None,
+            })
+            // Auxiliary variables are the compiler's, not the program's
+            .filter(|var| !is_auxiliary_name(&input_name(var)))
+            .filter(|var| !public.contains(split_input_name(&input_name(var)).0))
+            .collect();
+        push_inputs(&mut inputs, &fresh, |_| Visibility::Fresh, false);
+        let public = self.pubs.iter().map(input_name).collect();
+        Interface { version: INTERFACE_VERSION, inputs, public }
+    }
+}
+
+/* The number of single character insertions, deletions, and substitutions
+ * that turn one of the given strings into the other. */
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/* The one of the given names nearest the given name, if any is near enough
+ * to be what was meant: within a third of its length, and at least one,
+ * edit. */
+fn near_miss<'a>(name: &str, candidates: &'a BTreeSet<String>) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+    candidates.iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+/* List the given unexpected names, each followed by the one of the given
+ * expected names that it is likely a typo of, if any. */
+pub fn describe_unexpected<'a>(names: impl IntoIterator<Item = &'a String>, expected: &BTreeSet<String>) -> String {
+    let described: Vec<_> = names.into_iter()
+        .map(|name| match near_miss(name, expected) {
+            Some(meant) => format!("{} (did you mean {}?)", name, meant),
+            None => name.clone(),
+        })
+        .collect();
+    described.join(", ")
+}
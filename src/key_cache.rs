@@ -0,0 +1,245 @@
+use crate::error::Error;
+use crate::status::Outcome;
+use ark_serialize::CanonicalSerialize;
+use log::{debug, warn};
+use serde_json::json;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* The names under which the public parameters, proving key, and verifying
+ * key of a circuit are cached. Halo2 keys are not cached, as halo2_proofs
+ * cannot write them; they are generated again from the parameters held in
+ * Halo2 circuit files. */
+pub const PARAMS: &str = "params";
+pub const PK: &str = "pk";
+pub const VK: &str = "vk";
+
+/* Told apart the files that threads of one process write at once. */
+static WRITES: AtomicU64 = AtomicU64::new(0);
+
+/* A directory of artifacts that are expensive to create, such as public
+ * parameters, kept for reuse by later commands. Entries are laid out as
+ * <backend>/<circuit-hash>/<item>, so that every circuit has its own. The
+ * circuit hash is the secure digest of the circuit file, as one whose entry
+ * could be taken for another's would be given the wrong parameters. */
+pub struct KeyCache {
+    root: PathBuf,
+}
+
+/* The items cached for one circuit of one backend. */
+pub struct KeyCacheEntry {
+    pub backend: String,
+    pub circuit_hash: String,
+    // Names and sizes in bytes of the items cached
+    pub items: Vec<(String, u64)>,
+}
+
+impl KeyCacheEntry {
+    /* The total size in bytes of the items of this entry. */
+    pub fn size(&self) -> u64 {
+        self.items.iter().map(|(_, size)| size).sum()
+    }
+}
+
+/* The names of the subdirectories of the given directory, in order, or none if
+ * it does not exist. */
+fn subdirectories(dir: &Path) -> Result<Vec<String>, String> {
+    let read = match fs::read_dir(dir) {
+        Ok(read) => read,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(format!("cannot read {}: {}", dir.to_string_lossy(), err)),
+    };
+    let mut names = vec![];
+    for entry in read {
+        let entry = entry.map_err(|err| format!("cannot read {}: {}", dir.to_string_lossy(), err))?;
+        if entry.path().is_dir() {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+impl KeyCache {
+    /* The cache in the given directory, or else in vamp-ir under the user's
+     * cache directory: $XDG_CACHE_HOME, or else ~/.cache. There is none if no
+     * such directory can be determined. */
+    pub fn open(dir: Option<&Path>) -> Option<Self> {
+        let root = match dir {
+            Some(dir) => dir.to_path_buf(),
+            None => std::env::var_os("XDG_CACHE_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?
+                .join("vamp-ir"),
+        };
+        Some(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn item_path(&self, backend: &str, circuit_hash: &str, item: &str) -> PathBuf {
+        self.root.join(backend).join(circuit_hash).join(item)
+    }
+
+    /* The path of the given item cached for the given circuit, if there is
+     * one. */
+    pub fn lookup(&self, backend: &str, circuit_hash: &str, item: &str) -> Option<PathBuf> {
+        let path = self.item_path(backend, circuit_hash, item);
+        path.is_file().then_some(path)
+    }
+
+    /* Cache the given contents as the given item of the given circuit. They
+     * are written to a file of their own first and then renamed into place,
+     * so that processes storing or reading the same item concurrently never
+     * see it half written. */
+    pub fn store(&self, backend: &str, circuit_hash: &str, item: &str, contents: &[u8]) -> Result<PathBuf, String> {
+        let path = self.item_path(backend, circuit_hash, item);
+        let dir = path.parent().expect("cached items are in directories");
+        fs::create_dir_all(dir).map_err(|err| format!("cannot create {}: {}", dir.to_string_lossy(), err))?;
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.subsec_nanos());
+        let write = WRITES.fetch_add(1, Ordering::Relaxed);
+        let temp = dir.join(format!(".{}.{}.{}.{}.tmp", item, std::process::id(), write, nanos));
+        let written = fs::write(&temp, contents).and_then(|_| fs::rename(&temp, &path));
+        if let Err(err) = written {
+            let _ = fs::remove_file(&temp);
+            return Err(format!("cannot write {}: {}", path.to_string_lossy(), err));
+        }
+        Ok(path)
+    }
+
+    /* Cache the given item of the given circuit as store does, with contents
+     * made only if it is not cached yet. Failing to only costs making it again
+     * later, so the failure is given back for callers to warn of. */
+    pub fn store_missing(
+        &self,
+        backend: &str,
+        circuit_hash: &str,
+        item: &str,
+        contents: impl FnOnce() -> Result<Vec<u8>, String>,
+    ) -> Result<Option<PathBuf>, String> {
+        if self.lookup(backend, circuit_hash, item).is_some() {
+            return Ok(None);
+        }
+        self.store(backend, circuit_hash, item, &contents()?).map(Some)
+    }
+
+    /* Cache the given key, described as given, as the given item of the given
+     * circuit unless one already is, for tools that want the keys apart from
+     * the circuit. Proving and verifying always take keys from the circuit
+     * file, so failing to is merely warned of. */
+    pub fn store_key(&self, backend: &str, circuit_hash: &str, item: &str, what: &str, key: &impl CanonicalSerialize) {
+        let stored = self.store_missing(backend, circuit_hash, item, || {
+            let mut contents = vec![];
+            key.serialize(&mut contents).map_err(|err| err.to_string())?;
+            Ok(contents)
+        });
+        match stored {
+            Ok(Some(path)) => debug!("* Cached {} in {}", what, path.to_string_lossy()),
+            Ok(None) => {},
+            Err(err) => warn!("* Warning: unable to cache {}: {}", what, err),
+        }
+    }
+
+    /* Every entry of the cache, ordered by backend and then circuit. Files
+     * left behind by interrupted writes are not counted as items. */
+    pub fn entries(&self) -> Result<Vec<KeyCacheEntry>, String> {
+        let mut entries = vec![];
+        for backend in subdirectories(&self.root)? {
+            for circuit_hash in subdirectories(&self.root.join(&backend))? {
+                let dir = self.root.join(&backend).join(&circuit_hash);
+                let read = fs::read_dir(&dir).map_err(|err| format!("cannot read {}: {}", dir.to_string_lossy(), err))?;
+                let mut items = vec![];
+                for item in read {
+                    let item = item.map_err(|err| format!("cannot read {}: {}", dir.to_string_lossy(), err))?;
+                    let name = item.file_name().to_string_lossy().to_string();
+                    let metadata = item.metadata().map_err(|err| format!("cannot read {}: {}", dir.to_string_lossy(), err))?;
+                    if metadata.is_file() && !name.starts_with('.') {
+                        items.push((name, metadata.len()));
+                    }
+                }
+                items.sort();
+                entries.push(KeyCacheEntry { backend: backend.clone(), circuit_hash, items });
+            }
+        }
+        Ok(entries)
+    }
+
+    /* Remove the entries of the given backend and circuit, or of all of them
+     * where not given, and return what was removed. */
+    pub fn clear(&self, backend: Option<&str>, circuit_hash: Option<&str>) -> Result<Vec<KeyCacheEntry>, String> {
+        let mut removed = vec![];
+        for entry in self.entries()? {
+            if backend.is_some_and(|backend| backend != entry.backend)
+                || circuit_hash.is_some_and(|hash| hash != entry.circuit_hash) {
+                continue;
+            }
+            let dir = self.root.join(&entry.backend).join(&entry.circuit_hash);
+            fs::remove_dir_all(&dir).map_err(|err| format!("cannot remove {}: {}", dir.to_string_lossy(), err))?;
+            removed.push(entry);
+        }
+        Ok(removed)
+    }
+}
+
+/* Open the cache in the given directory, or else the default one, failing if
+ * there is no default one. */
+fn open_required(dir: Option<&Path>) -> Result<KeyCache, Error> {
+    KeyCache::open(dir).ok_or_else(|| Error::Artifact(
+        "neither XDG_CACHE_HOME nor HOME is set; give the cache directory with --cache-dir".to_string(),
+    ))
+}
+
+/* Record the given cache entries in the given outcome, one per line. */
+fn report_entries(outcome: &mut Outcome, entries: &[KeyCacheEntry]) {
+    let mut listed = vec![];
+    for entry in entries {
+        let items: Vec<_> = entry.items.iter()
+            .map(|(name, size)| format!("{} ({} B)", name, size))
+            .collect();
+        outcome.println(format!("** {}/{}: {}", entry.backend, entry.circuit_hash, items.join(", ")));
+        let items: serde_json::Map<_, _> = entry.items.iter()
+            .map(|(name, size)| (name.clone(), json!(size)))
+            .collect();
+        listed.push(json!({
+            "backend": entry.backend,
+            "circuit_hash": entry.circuit_hash,
+            "bytes": entry.size(),
+            "items": items,
+        }));
+    }
+    let total: u64 = entries.iter().map(|entry| entry.size()).sum();
+    outcome.println(format!("** Total: {} entries, {} B", entries.len(), total));
+    outcome.detail("entries", listed);
+    outcome.detail("bytes", total);
+}
+
+/* List the entries of the cache in the given directory, or else the default
+ * one, with their sizes. */
+pub fn list_entries(dir: Option<&Path>) -> Result<Outcome, Error> {
+    let cache = open_required(dir)?;
+    let entries = cache.entries().map_err(Error::Artifact)?;
+    let mut outcome = Outcome::default();
+    outcome.println(format!("* Cache in {}:", cache.root().to_string_lossy()));
+    outcome.detail("cache_dir", cache.root().to_string_lossy());
+    report_entries(&mut outcome, &entries);
+    Ok(outcome)
+}
+
+/* Remove the entries of the given backend and circuit, or of all of them
+ * where not given, from the cache in the given directory, or else the default
+ * one, listing what was removed. */
+pub fn clear_entries(dir: Option<&Path>, backend: Option<&str>, circuit_hash: Option<&str>) -> Result<Outcome, Error> {
+    let cache = open_required(dir)?;
+    let removed = cache.clear(backend, circuit_hash).map_err(Error::Artifact)?;
+    let mut outcome = Outcome::default();
+    outcome.println(format!("* Removed from {}:", cache.root().to_string_lossy()));
+    outcome.detail("cache_dir", cache.root().to_string_lossy());
+    report_entries(&mut outcome, &removed);
+    Ok(outcome)
+}
@@ -0,0 +1,265 @@
+//! Vamp-IR compiles programs written in its language of polynomial
+//! constraints into circuits of either the Halo2 or the PLONK proving system,
+//! and proves and verifies knowledge of inputs satisfying them.
+//!
+//! Programs are parsed with [`parse`] and compiled over the field of a
+//! backend with [`compile`]. The circuits of each backend can then be proved
+//! and verified in memory, with inputs named as in the program:
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use halo2_proofs::pasta::Fp;
+//! use vamp_ir::halo2::circuit::Halo2Circuit;
+//! use vamp_ir::halo2::synth::{Halo2CostModel, PrimeFieldOps};
+//! use vamp_ir::transform::CompileOptions;
+//!
+//! let module = vamp_ir::parse("pub z; x * y = z;")?;
+//! let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+//! let compiled = vamp_ir::compile(module, &PrimeFieldOps::<Fp>::default(), &options)?;
+//! let circuit = Halo2Circuit::new(compiled.module)?;
+//! let inputs = HashMap::from([
+//!     ("x".to_string(), 3.into()),
+//!     ("y".to_string(), 4.into()),
+//!     ("z".to_string(), 12.into()),
+//! ]);
+//! let proof = circuit.prove(&inputs)?;
+//! circuit.verify(&proof)?;
+//! // Inputs that violate a constraint are refused before anything is proved
+//! let bad_inputs = HashMap::from([
+//!     ("x".to_string(), 3.into()),
+//!     ("y".to_string(), 4.into()),
+//!     ("z".to_string(), 13.into()),
+//! ]);
+//! assert!(matches!(circuit.prove(&bad_inputs), Err(vamp_ir::Error::Inputs(_))));
+//! # Ok::<(), vamp_ir::Error>(())
+//! ```
+//!
+//! PLONK circuits are made the same way, over their own field, from public
+//! parameters that are set up with them:
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use ark_bls12_381::Fr as BlsScalar;
+//! use vamp_ir::plonk::circuit::PlonkCircuit;
+//! use vamp_ir::plonk::synth::{PlonkCostModel, PrimeFieldOps};
+//! use vamp_ir::transform::CompileOptions;
+//!
+//! let module = vamp_ir::parse("pub z; x * y = z;")?;
+//! let options = CompileOptions::default().cost_model(Box::new(PlonkCostModel));
+//! let compiled = vamp_ir::compile(module, &PrimeFieldOps::<BlsScalar>::default(), &options)?;
+//! let mut circuit = PlonkCircuit::new(compiled.module)?;
+//! let inputs = HashMap::from([
+//!     ("x".to_string(), 3.into()),
+//!     ("y".to_string(), 4.into()),
+//!     ("z".to_string(), 12.into()),
+//! ]);
+//! let proof = circuit.prove(&inputs)?;
+//! circuit.verify(&proof)?;
+//! # Ok::<(), vamp_ir::Error>(())
+//! ```
+//!
+//! # Artifacts
+//!
+//! - The [`artifact`] module writes circuits and proofs to files and reads
+//!   them back, in the format of the `vamp-ir` command line.
+//! - The [`container`] module wraps proofs with their metadata in CBOR
+//!   envelopes.
+//! - `Module::interface` of the [`interface`] module describes the inputs that
+//!   a compiled module expects, with their visibility and shape.
+//! - `Module::constraints_json` of the [`constraints`] module writes the
+//!   constraints, definitions, and public variables of a module as a JSON
+//!   document for auditing.
+//! - The [`calldata`] module decodes public values that arrive as Ethereum
+//!   calldata against an interface.
+//! - The [`export`] module writes compiled modules and their witnesses for
+//!   other tools, and reads the rank-1 constraint systems they write back.
+//! - The [`sources`] module parses, merges, and compiles source files along
+//!   with their prelude, reusing compilations cached in `.vampir-cache`.
+//!
+//! # Optional features
+//!
+//! - `wasm`: the `wasm` module compiles, proves, and verifies Halo2 circuits
+//!   from JavaScript.
+//! - `ffi`: the `ffi` module does the same from C.
+//! - `ark-adapter`: the `ark_adapter` module hands compiled modules to the
+//!   proving systems of arkworks.
+//! - `groth16`: the `groth16` module proves them with Groth16.
+//! - `bellman-adapter`: the `bellman_adapter` module hands them to the provers
+//!   built on bellman.
+//! - `bulletproofs`: the `bp` module proves them with Bulletproofs, which
+//!   needs no setup.
+//! - `plonky2`: the experimental `plonky2` module proves them with plonky2
+//!   over the Goldilocks field.
+//! - `zkinterface`: the `zkif` module writes the rank-1 constraint systems of
+//!   the `r1cs` module as zkinterface messages.
+//! - `server`: the `server` module serves compiling, proving, and verifying
+//!   over HTTP, as the `vamp-ir-server` binary does.
+//! - `testing`: the `testing` module generates random programs with inputs
+//!   satisfying them, for property tests of tools built on vamp-ir.
+//!
+//! # Minimal builds
+//!
+//! Parsing, compiling, and proving need the `compiler` feature, which every
+//! other feature but `halo2-verify` and `verifier-cli` implies. The `halo2::synth`,
+//! `halo2::circuit`, `plonk::synth`, and `plonk::circuit` modules of each
+//! backend also need its own `halo2` or `plonk` feature, both on by default,
+//! while the fields of both backends are in `halo2::field` and `plonk::field`
+//! with `compiler` alone.
+//!
+//! Built with only `halo2-verify`, the library reads Halo2 verifier files, as
+//! written by `Halo2Circuit::verifier_data`, with [`load_verifier_artifact`]
+//! and checks proofs with [`verify`], or decodes both as they arrive from any
+//! reader with [`verify_from_reader`]. Such a build depends on neither clap
+//! nor serde_json, nor on the codecs of the text encodings and proof
+//! containers, so it reads binary files alone.
+
+#[cfg(feature = "compiler")]
+extern crate pest;
+#[cfg(feature = "compiler")]
+#[macro_use]
+extern crate pest_derive;
+
+#[cfg(feature = "compiler")]
+pub mod ast;
+#[cfg(feature = "compiler")]
+pub mod transform;
+#[cfg(feature = "compiler")]
+pub mod typecheck;
+#[cfg(feature = "compiler")]
+pub mod pretty;
+#[cfg(feature = "compiler")]
+pub mod diagnostics;
+#[cfg(feature = "compiler")]
+pub mod render;
+#[cfg(any(feature = "compiler", feature = "verifier-cli"))]
+pub mod status;
+#[cfg(feature = "compiler")]
+pub mod cache;
+#[cfg(feature = "compiler")]
+pub mod sources;
+#[cfg(feature = "compiler")]
+pub mod encoding;
+#[cfg(feature = "compiler")]
+pub mod output;
+#[cfg(feature = "compiler")]
+pub mod stdio;
+pub mod artifact;
+#[cfg(feature = "compiler")]
+pub mod metadata;
+#[cfg(feature = "compiler")]
+pub mod container;
+#[cfg(feature = "compiler")]
+pub mod key_cache;
+#[cfg(feature = "compiler")]
+pub mod progress;
+#[cfg(feature = "halo2-verify")]
+pub mod halo2;
+#[cfg(feature = "compiler")]
+pub mod plonk;
+pub mod error;
+#[cfg(feature = "compiler")]
+pub mod r1cs;
+#[cfg(feature = "compiler")]
+pub mod interface;
+#[cfg(feature = "compiler")]
+pub mod constraints;
+#[cfg(feature = "compiler")]
+pub mod calldata;
+#[cfg(feature = "compiler")]
+pub mod export;
+#[cfg(any(feature = "wasm", feature = "ffi"))]
+mod bindings;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "ark-adapter")]
+pub mod ark_adapter;
+#[cfg(feature = "bellman-adapter")]
+pub mod bellman_adapter;
+#[cfg(feature = "groth16")]
+pub mod groth16;
+#[cfg(feature = "bulletproofs")]
+pub mod bp;
+#[cfg(feature = "plonky2")]
+pub mod plonky2;
+#[cfg(feature = "zkinterface")]
+pub mod zkif;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use crate::error::Error;
+#[cfg(feature = "halo2-verify")]
+pub use crate::halo2::verify::{load_verifier_artifact, read_verifier_artifact, verify, verify_from_reader, verify_public};
+
+#[cfg(feature = "compiler")]
+use crate::ast::{Module, VariableId};
+#[cfg(feature = "compiler")]
+use crate::diagnostics::Diagnostic;
+#[cfg(feature = "compiler")]
+use crate::interface::describe_unexpected;
+#[cfg(feature = "compiler")]
+use crate::transform::{compile_with, derive_witnesses, input_name, required_inputs, violated_constraints, CompileOptions, CompileOutput, FieldOps};
+#[cfg(feature = "compiler")]
+use num_bigint::BigInt;
+#[cfg(feature = "compiler")]
+use std::collections::{BTreeSet, HashMap};
+
+/* Parse the given source into a module, failing with a diagnostic that
+ * locates the problem in the source. */
+#[cfg(feature = "compiler")]
+pub fn parse(source: &str) -> Result<Module, Error> {
+    Module::parse(source)
+        .map_err(|err| Error::Parse(Diagnostic::from_parse_error(&err, "<source>", source)))
+}
+
+/* Compile the given module over the field described by the given operations
+ * with the given options. Warnings that do not stop compilation are given
+ * back with the compiled module. */
+#[cfg(feature = "compiler")]
+pub fn compile(module: Module, field_ops: &dyn FieldOps, options: &CompileOptions) -> Result<CompileOutput, Error> {
+    compile_with(module, field_ops, options).map_err(Error::from)
+}
+
+/* Assign the given values, named as in the source, to the inputs of the given
+ * compiled module, and check that they satisfy its constraints once the rest
+ * of its variables are derived from them. Every input must be given, and
+ * nothing else. Returns the value of every variable, which provers take as
+ * it is. */
+#[cfg(feature = "compiler")]
+pub fn assign_inputs(
+    module: &Module,
+    inputs: &HashMap<String, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Result<HashMap<VariableId, BigInt>, Error> {
+    let required = required_inputs(module);
+    let names: BTreeSet<_> = required.iter().map(input_name).collect();
+    let missing: Vec<_> = names.iter().filter(|name| !inputs.contains_key(*name)).cloned().collect();
+    let mut unknown: Vec<_> = inputs.keys().filter(|name| !names.contains(*name)).cloned().collect();
+    unknown.sort();
+    let mut problems = vec![];
+    if !missing.is_empty() {
+        problems.push(format!("missing inputs: {}", missing.join(", ")));
+    }
+    if !unknown.is_empty() {
+        problems.push(format!("unknown inputs: {}", describe_unexpected(&unknown, &names)));
+    }
+    if !problems.is_empty() {
+        return Err(Error::Inputs(problems.join("; ")));
+    }
+    let mut derived: HashMap<_, _> = required.iter()
+        .map(|var| (var.id, inputs[&input_name(var)].clone()))
+        .collect();
+    derive_witnesses(module, &mut derived, field_ops)?;
+    let violated = violated_constraints(module, &derived, field_ops);
+    if !violated.is_empty() {
+        let violated: Vec<_> = violated.iter().map(|expr| expr.to_string()).collect();
+        return Err(Error::Inputs(format!(
+            "{} of {} constraint(s) are violated: {}",
+            violated.len(), module.exprs.len(), violated.join(", "),
+        )));
+    }
+    Ok(derived)
+}
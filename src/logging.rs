@@ -0,0 +1,36 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/* Writes the messages logged by vamp-ir, which are already formatted as
+ * progress lines, to standard error so that standard output only carries
+ * results. Messages logged by dependencies are left out. */
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level() && metadata.target().starts_with("vamp_ir")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/* Route log messages to standard error. Progress is logged at info level,
+ * which is shown by default; each verbosity step adds a further level, and
+ * quiet shows errors alone. */
+pub fn init(verbosity: u8, quiet: bool) {
+    let level = match (quiet, verbosity) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    log::set_logger(&LOGGER).expect("logger is only set once");
+    log::set_max_level(level);
+}
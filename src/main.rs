@@ -1,130 +1,357 @@
-mod ast;
-mod transform;
-mod plonk;
-mod halo2;
-mod typecheck;
-extern crate pest;
-#[macro_use]
-extern crate pest_derive;
+mod cli;
+mod inputs;
+mod logging;
+mod bench;
+mod batch;
+mod watch;
+mod dry_run;
+mod repeat;
+mod forward;
 
-use crate::ast::{Module, VariableId, Pat, parse_prefixed_num};
-use crate::transform::{compile, collect_module_variables};
+// The library's modules are brought in under their own names, so that the
+// command line reaches them as it reaches its own
+use vamp_ir::{artifact, ast, cache, calldata, container, diagnostics, encoding, export, halo2, interface, key_cache, metadata, output, plonk, pretty, progress, render, sources, status, stdio, transform};
+#[cfg(feature = "bulletproofs")]
+use vamp_ir::bp;
+#[cfg(feature = "groth16")]
+use vamp_ir::groth16;
+#[cfg(feature = "plonky2")]
+use vamp_ir::plonky2;
 
-use std::collections::{HashMap, HashSet};
+use crate::forward::{forward_args, ProjectConfig};
+use crate::status::{CommandError, CommandResult, Outcome};
+use crate::diagnostics::report;
+use crate::render::{Renderer, Style};
+use crate::stdio::{ClaimStdio, StdioClaims};
 
-use crate::halo2::cli::{Halo2Commands, halo2};
-use crate::plonk::cli::{PlonkCommands, plonk};
-use std::io::Write;
+use crate::cli::bench::{bench_cmd, Bench};
+use crate::cli::cache::{cache, CacheCommands};
+use crate::cli::circuit::{check_cmd, convert_cmd, inputs_template_cmd, inspect_cmd, CircuitCheck, Convert, InputsTemplate, Inspect};
+use crate::cli::compile::{canonicalize_cmd, compile_ir_cmd, diff_cmd, test_cmd, Canonicalize, Diff, IrCompile, SatisfiabilityTest};
+use crate::cli::evaluate::{export_cmd, import_cmd, interpret_cmd, witness_cmd, Export, Import, Interpret, WitnessExport};
+use crate::cli::shared::ProofSystems;
+use crate::cli::watch::{watch_cmd, SourceWatch};
+#[cfg(feature = "bulletproofs")]
+use crate::cli::bp::{BpCommands, bp};
+#[cfg(feature = "groth16")]
+use crate::cli::groth16::{Groth16Commands, groth16};
+#[cfg(feature = "halo2")]
+use crate::cli::halo2::{Halo2Commands, halo2};
+#[cfg(feature = "plonk")]
+use crate::cli::plonk::{PlonkCommands, plonk};
+#[cfg(feature = "plonky2")]
+use crate::cli::plonky2::{Plonky2Commands, plonky2};
 
-use std::fs::File;
+use std::time::{Duration, Instant};
+use log::error;
+use serde_json::json;
 
-use clap::{Parser, Subcommand, ValueEnum};
-use std::path::PathBuf;
-
-use std::ops::Neg;
-use num_traits::Num;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// Log more detail to standard error, such as effective inputs; give
+    /// twice to also log inferred types
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Log nothing but errors to standard error
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Print the result as a single JSON object on standard output instead of
+    /// as text, and log nothing but errors
+    #[arg(long, global = true)]
+    json: bool,
+    /// Never color diagnostics, as when NO_COLOR is set; they are otherwise
+    /// colored when standard error is a terminal
+    #[arg(long, global = true)]
+    no_color: bool,
+    /// Ignore names in files of inputs that match no variable of the program,
+    /// as when a file is shared by several programs, instead of rejecting them
+    #[arg(long, global = true)]
+    allow_extra_inputs: bool,
     #[command(subcommand)]
     backend: Backend,
 }
 
 #[derive(Subcommand)]
 enum Backend {
+    #[cfg(feature = "plonk")]
     #[command(subcommand)]
     Plonk(PlonkCommands),
+    /// Not available: this binary was built without the plonk backend
+    #[cfg(not(feature = "plonk"))]
+    Plonk(MissingBackend),
+    #[cfg(feature = "halo2")]
     #[command(subcommand)]
     Halo2(Halo2Commands),
+    /// Not available: this binary was built without the halo2 backend
+    #[cfg(not(feature = "halo2"))]
+    Halo2(MissingBackend),
+    #[cfg(feature = "groth16")]
+    #[command(subcommand)]
+    Groth16(Groth16Commands),
+    #[cfg(feature = "bulletproofs")]
+    #[command(subcommand)]
+    Bp(BpCommands),
+    #[cfg(feature = "plonky2")]
+    #[command(subcommand)]
+    Plonky2(Plonky2Commands),
+    /// Compiles source files to the intermediate representation only, or to
+    /// a circuit when given --backend or VAMPIR_BACKEND, taking the arguments
+    /// of that backend's compile
+    Compile(IrCompile),
+    /// Proves knowledge of witnesses satisfying a circuit with the backend
+    /// named by its header
+    Prove(Forwarded),
+    /// Verifies proofs with the backend named by the header of their circuit
+    Verify(Forwarded),
+    /// Checks that inputs satisfy a compiled circuit of either backend,
+    /// without proving
+    Check(CircuitCheck),
+    /// Prints a source file back in canonical form
+    Canonicalize(Canonicalize),
+    /// Checks whether two source files define the same relation
+    Diff(Diff),
+    /// Checks that the constraints of source files are satisfiable
+    Test(SatisfiabilityTest),
+    /// Evaluates source files on given inputs without proving
+    Interpret(Interpret),
+    /// Exports the values of all variables derived from given inputs as JSON
+    Witness(WitnessExport),
+    /// Exports the constraints of source files as a .r1cs file, or their
+    /// witness derived from given inputs as a .wtns file, for snarkjs
+    Export(Export),
+    /// Reads a rank-1 constraint system written by circom back as a source
+    /// file, so that it can be proved with the backend of its field
+    Import(Import),
+    /// Prints the metadata of a circuit, proof, or parameters file
+    Inspect(Inspect),
+    /// Writes a file of inputs to a circuit with every value left blank
+    InputsTemplate(InputsTemplate),
+    /// Rebuilds a compiled circuit for the other backend, without its sources
+    Convert(Convert),
+    /// Compares the time and space that proving source files takes in each
+    /// backend
+    Bench(Bench),
+    /// Lists or clears the cached public parameters and keys
+    #[command(subcommand)]
+    Cache(CacheCommands),
+    /// Recompiles source files, and checks inputs against them, whenever they
+    /// change
+    Watch(SourceWatch),
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
-enum ProofSystems {
-    /// PLONK general-purpose zero-knowledge proof scheme
-    Plonk,
-    /// Halo 2 zero-knowledge proving system
-    Halo2,
+/* The arguments of a top-level command that runs the command of the same name
+ * of a backend. Command lines are forwarded before they are parsed, so these
+ * are only ever parsed to describe the command in its help. */
+#[derive(Args)]
+struct Forwarded {
+    /// Backend whose command is run, by default the one named by the header
+    /// of the circuit given with --circuit, or else by VAMPIR_BACKEND; a
+    /// backend given with a circuit must be the one it was compiled for
+    #[arg(long, value_enum)]
+    backend: Option<ProofSystems>,
+    /// Arguments of the command of the backend, as given to vamp-ir halo2,
+    /// vamp-ir plonk, vamp-ir groth16, vamp-ir bp, or vamp-ir plonky2
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<std::ffi::OsString>,
 }
 
-/* Read satisfying inputs to the given program from a file. */
-fn read_inputs_from_file<F>(annotated: &Module, path_to_inputs: &PathBuf) -> HashMap<VariableId, F>
-where F: Num + Neg<Output = F>, <F as num_traits::Num>::FromStrRadixErr: std::fmt::Debug {
-    let inputs = File::open(path_to_inputs)
-        .expect("Could not open inputs file");
-
-    // Read the user-supplied inputs from the file
-    let named_assignments: HashMap<String, String> = serde_json::from_reader(inputs).unwrap();
-
-    // Get the expected inputs from the circuit module
-    let mut input_variables = HashMap::new();
-    collect_module_variables(&annotated, &mut input_variables);
+/* The arguments of the subcommands of a backend that this build leaves out,
+ * taken whatever they are so that invoking one is reported as such rather
+ * than as a subcommand that does not exist. */
+#[cfg(not(all(feature = "plonk", feature = "halo2")))]
+#[derive(Args)]
+struct MissingBackend {
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true, hide = true)]
+    args: Vec<std::ffi::OsString>,
+}
 
-    // Defined variables should not be requested from user
-    for def in &annotated.defs {
-        if let Pat::Variable(var) = &def.0.0.v {
-            input_variables.remove(&var.id);
+/* Main entry point for vamp-ir compiler, prover, and verifier. */
+fn main() {
+    // The top-level commands of either backend are forwarded to the commands
+    // of the backend that they are found to be for before anything is parsed
+    let mut definition = Cli::command();
+    definition.build();
+    let args = forward_args(&definition, std::env::args_os().collect(), &ProjectConfig::from_env())
+        .unwrap_or_else(|err| Cli::command().error(clap::error::ErrorKind::InvalidValue, err).exit());
+    let matches = Cli::command().get_matches_from(args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    // Arguments that would share standard input or output are usage errors,
+    // found before any work is done
+    let mut claims = StdioClaims::default();
+    cli.backend.claim_stdio(&mut claims);
+    // Results go to standard error when an argument writes standard output
+    let results_to_stderr = claims.claims_stdout();
+    if cli.json {
+        claims.writes_stdout("--json");
+    }
+    if let Err(err) = claims.check() {
+        Cli::command().error(clap::error::ErrorKind::ArgumentConflict, err).exit();
+    }
+    // Only the JSON object may be written when results are rendered as JSON
+    logging::init(cli.verbose, cli.quiet || cli.json);
+    render::init(cli.no_color);
+    inputs::init(cli.allow_extra_inputs);
+    let start = Instant::now();
+    let (status, outcome, error) = match dispatch(&cli) {
+        Ok(mut outcome) => match outcome.failure.take() {
+            None => (0, Some(outcome), None),
+            Some(err) => {
+                if !cli.json {
+                    report_error(&err);
+                }
+                (err.exit_status(), Some(outcome), Some((err.kind(), err.to_string())))
+            },
+        },
+        Err(err) => {
+            if !cli.json {
+                report_error(&err);
+            }
+            (err.exit_status(), None, Some((err.kind(), err.to_string())))
+        },
+    };
+    if cli.json {
+        let result = json_result(&command_name(&matches), status, outcome, error, start.elapsed());
+        println!("{}", serde_json::to_string_pretty(&result).expect("results are valid JSON"));
+    } else if let Some(outcome) = outcome {
+        if results_to_stderr {
+            eprint!("{}", outcome.text);
+        } else {
+            print!("{}", outcome.text);
         }
     }
+    std::process::exit(status);
+}
 
-    let mut variable_assignments = HashMap::new();
-
-    // Check that the user supplied the expected inputs
-    for (id, expected_var) in input_variables {
-        variable_assignments.insert(
-            id,
-            parse_prefixed_num(&named_assignments[&expected_var.name.unwrap()].clone())
-                .expect("input not an integer")
-        );
+/* Log the given error of a subcommand. Errors that come with a diagnostic are
+ * rendered as it is, in the format that the subcommand was given. */
+fn report_error(err: &CommandError) {
+    match err {
+        CommandError::Diagnosed(diagnostic, format) => report(std::slice::from_ref(diagnostic), *format),
+        err => error!("* {}: {}", Renderer::current().paint(Style::Error, "Error"), err),
     }
-
-    variable_assignments
-    
 }
 
-/* Prompt for satisfying inputs to the given program. */
-fn prompt_inputs<F>(annotated: &Module) -> HashMap<VariableId, F> where F: Num + Neg<Output = F>, <F as num_traits::Num>::FromStrRadixErr: std::fmt::Debug {
-    let mut input_variables = HashMap::new();
-    collect_module_variables(&annotated, &mut input_variables);
-    // Defined variables should not be requested from user
-    for def in &annotated.defs {
-        if let Pat::Variable(var) = &def.0.0.v {
-            input_variables.remove(&var.id);
-        }
-    }
-    // Collect all public variables in order to enable annotations
-    let mut public_variables = HashSet::new();
-    for var in &annotated.pubs {
-        public_variables.insert(var.id);
+/* Name the subcommand given on the command line, such as plonk compile. */
+fn command_name(matches: &clap::ArgMatches) -> String {
+    let mut names = vec![];
+    let mut matches = matches;
+    while let Some((name, submatches)) = matches.subcommand() {
+        names.push(name);
+        matches = submatches;
     }
+    names.join(" ")
+}
 
-    let mut var_assignments = HashMap::new();
+/* Put the result of the given subcommand into a single JSON object: whether
+ * and how it failed, how long it and each of its phases took, the files it
+ * wrote, the digest of the circuit involved, and its structured results. Text
+ * is only given when the subcommand has no structured results. */
+fn json_result(
+    command: &str,
+    status: i32,
+    outcome: Option<Outcome>,
+    error: Option<(&str, String)>,
+    elapsed: Duration,
+) -> serde_json::Value {
+    let outcome = outcome.unwrap_or_default();
+    let timings: serde_json::Map<_, _> = outcome.timings.iter()
+        .map(|(phase, duration)| (phase.to_string(), json!(duration.as_secs_f64() * 1000.0)))
+        .collect();
+    let artifacts: serde_json::Map<_, _> = outcome.artifacts.iter()
+        .map(|(role, path)| (role.to_string(), json!(path.to_string_lossy())))
+        .collect();
+    let mut result = json!({
+        "command": command,
+        "status": error.as_ref().map_or("success", |(kind, _)| *kind),
+        "exit_status": status,
+        "elapsed_ms": elapsed.as_secs_f64() * 1000.0,
+        "timings_ms": timings,
+        "artifacts": artifacts,
+        "circuit_hash": outcome.circuit_hash,
+        "error": error.map(|(_, message)| message),
+    });
+    let fields = result.as_object_mut().expect("results are objects");
+    if outcome.details.is_empty() && !outcome.text.is_empty() {
+        fields.insert("output".to_string(), json!(outcome.text));
+    }
+    fields.extend(outcome.details);
+    result
+}
 
-    // Solicit input variables from user and solve for choice point values
-    for (id, var) in input_variables {
-        let visibility = if public_variables.contains(&id) {
-            "(public)"
-        } else {
-            "(private)"
-        };
-        print!("** {} {}: ", var, visibility);
-        std::io::stdout().flush().expect("flush failed!");
-        let mut input_line = String::new();
-        std::io::stdin()
-            .read_line(&mut input_line)
-            .expect("failed to read input");
-        let x = parse_prefixed_num(input_line.trim())
-            .expect("input not an integer");
-        var_assignments.insert(id, x);
+impl ClaimStdio for Backend {
+    fn claim_stdio(&self, claims: &mut StdioClaims) {
+        match self {
+            #[cfg(feature = "plonk")]
+            Backend::Plonk(plonk_commands) => plonk_commands.claim_stdio(claims),
+            #[cfg(feature = "halo2")]
+            Backend::Halo2(halo2_commands) => halo2_commands.claim_stdio(claims),
+            #[cfg(not(feature = "plonk"))]
+            Backend::Plonk(_) => {},
+            #[cfg(not(feature = "halo2"))]
+            Backend::Halo2(_) => {},
+            #[cfg(feature = "groth16")]
+            Backend::Groth16(groth16_commands) => groth16_commands.claim_stdio(claims),
+            #[cfg(feature = "bulletproofs")]
+            Backend::Bp(bp_commands) => bp_commands.claim_stdio(claims),
+            #[cfg(feature = "plonky2")]
+            Backend::Plonky2(plonky2_commands) => plonky2_commands.claim_stdio(claims),
+            Backend::Compile(args) => args.claim_stdio(claims),
+            Backend::Canonicalize(args) => args.claim_stdio(claims),
+            Backend::Diff(args) => args.claim_stdio(claims),
+            Backend::Test(args) => args.claim_stdio(claims),
+            Backend::Interpret(args) => args.claim_stdio(claims),
+            Backend::Witness(args) => args.claim_stdio(claims),
+            Backend::Export(args) => args.claim_stdio(claims),
+            Backend::Import(args) => args.claim_stdio(claims),
+            Backend::Inspect(args) => args.claim_stdio(claims),
+            Backend::Prove(_) | Backend::Verify(_) => {},
+            Backend::Check(args) => args.claim_stdio(claims),
+            Backend::InputsTemplate(args) => args.claim_stdio(claims),
+            Backend::Convert(args) => args.claim_stdio(claims),
+            Backend::Bench(args) => args.claim_stdio(claims),
+            Backend::Cache(cache_commands) => cache_commands.claim_stdio(claims),
+            Backend::Watch(args) => args.claim_stdio(claims),
+        }
     }
-    var_assignments
 }
 
-/* Main entry point for vamp-ir compiler, prover, and verifier. */
-fn main() {
-    let cli = Cli::parse();
+/* Run the subcommand given on the command line. */
+fn dispatch(cli: &Cli) -> CommandResult {
     match &cli.backend {
+        #[cfg(feature = "plonk")]
         Backend::Plonk(plonk_commands) => plonk(plonk_commands),
+        #[cfg(feature = "halo2")]
         Backend::Halo2(halo2_commands) => halo2(halo2_commands),
+        #[cfg(not(feature = "plonk"))]
+        Backend::Plonk(_) => Err(ProofSystems::Plonk.missing()),
+        #[cfg(not(feature = "halo2"))]
+        Backend::Halo2(_) => Err(ProofSystems::Halo2.missing()),
+        #[cfg(feature = "groth16")]
+        Backend::Groth16(groth16_commands) => groth16(groth16_commands),
+        #[cfg(feature = "bulletproofs")]
+        Backend::Bp(bp_commands) => bp(bp_commands),
+        #[cfg(feature = "plonky2")]
+        Backend::Plonky2(plonky2_commands) => plonky2(plonky2_commands),
+        Backend::Compile(ir_compile) => compile_ir_cmd(ir_compile),
+        Backend::Prove(_) | Backend::Verify(_) => Err(CommandError::Internal(
+            "commands of a backend are forwarded to it before they are parsed".to_string(),
+        )),
+        Backend::Check(check) => check_cmd(check),
+        Backend::Canonicalize(canonicalize) => canonicalize_cmd(canonicalize),
+        Backend::Diff(diff) => diff_cmd(diff),
+        Backend::Test(test) => test_cmd(test),
+        Backend::Interpret(interpret) => interpret_cmd(interpret),
+        Backend::Witness(witness) => witness_cmd(witness),
+        Backend::Export(export) => export_cmd(export),
+        Backend::Import(import) => import_cmd(import),
+        Backend::Inspect(inspect) => inspect_cmd(inspect),
+        Backend::InputsTemplate(template) => inputs_template_cmd(template),
+        Backend::Convert(convert) => convert_cmd(convert),
+        Backend::Bench(bench) => bench_cmd(bench),
+        Backend::Cache(cache_commands) => cache(cache_commands),
+        Backend::Watch(watch) => watch_cmd(watch),
     }
 }
@@ -0,0 +1,171 @@
+use crate::artifact::ArtifactKind;
+use crate::output::write_output;
+use crate::stdio::is_stdout;
+use crate::status::Outcome;
+use serde_json::{json, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/* Facts about how a proof was made, kept in a file beside it so that where the
+ * proof came from can be audited long after. Nothing here is hashed or read
+ * when verifying, so it cannot affect whether the proof is accepted. */
+pub struct ProofMetadata {
+    // Version of vamp-ir that made the proof
+    pub tool_version: String,
+    pub backend: String,
+    pub curve: String,
+    // Digest of the contents of the circuit that the proof is for
+    pub circuit_hash: String,
+    // Wall-clock time taken to create the proof
+    pub prover_time: Duration,
+    // Seconds since the Unix epoch at which the proof was made
+    pub timestamp: u64,
+    // Values of the public variables, by name, in the order they are declared
+    pub public_inputs: Vec<(String, String)>,
+}
+
+/* The suffix appended to the name of a proof to name its metadata file. */
+const METADATA_SUFFIX: &str = ".meta.json";
+
+/* The path of the metadata file beside the proof at the given path. */
+pub fn metadata_path(proof: &Path) -> PathBuf {
+    let mut path = proof.as_os_str().to_os_string();
+    path.push(METADATA_SUFFIX);
+    PathBuf::from(path)
+}
+
+/* Whether the file at the given path is named as the metadata of a proof. */
+pub fn is_metadata_path(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(METADATA_SUFFIX)
+}
+
+impl ProofMetadata {
+    /* Describe a proof of the given kind, made just now by this vamp-ir. */
+    pub fn new(
+        kind: ArtifactKind,
+        circuit_hash: String,
+        prover_time: Duration,
+        public_inputs: Vec<(String, String)>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            backend: kind.backend().to_string(),
+            curve: kind.curve().to_string(),
+            circuit_hash,
+            prover_time,
+            timestamp,
+            public_inputs,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let public_inputs: Vec<_> = self.public_inputs.iter()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect();
+        json!({
+            "tool_version": self.tool_version,
+            "backend": self.backend,
+            "curve": self.curve,
+            "circuit_hash": self.circuit_hash,
+            "prover_ms": self.prover_time.as_secs_f64() * 1000.0,
+            "timestamp": self.timestamp,
+            "public_inputs": public_inputs,
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        let string = |key: &str| value[key].as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("{} is missing or not a string", key));
+        let prover_ms = value["prover_ms"].as_f64()
+            .filter(|ms| ms.is_finite() && *ms >= 0.0)
+            .ok_or("prover_ms is missing or not a duration")?;
+        let timestamp = value["timestamp"].as_u64()
+            .ok_or("timestamp is missing or not a number of seconds")?;
+        let public_inputs = value["public_inputs"].as_array()
+            .ok_or("public_inputs is missing or not a list")?
+            .iter()
+            .map(|input| match (input["name"].as_str(), input["value"].as_str()) {
+                (Some(name), Some(value)) => Ok((name.to_string(), value.to_string())),
+                _ => Err("public inputs must have a name and a value".to_string()),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            tool_version: string("tool_version")?,
+            backend: string("backend")?,
+            curve: string("curve")?,
+            circuit_hash: string("circuit_hash")?,
+            prover_time: Duration::from_secs_f64(prover_ms / 1000.0),
+            timestamp,
+            public_inputs,
+        })
+    }
+
+    /* Print this metadata to the given outcome and record it there. */
+    pub fn report(&self, outcome: &mut Outcome) {
+        outcome.println(self);
+        outcome.detail("metadata", self.to_json());
+    }
+}
+
+/* Serialized with serde, metadata takes the form of its metadata files. */
+#[cfg(feature = "serde")]
+impl serde::Serialize for ProofMetadata {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.to_json(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ProofMetadata {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value: Value = serde::Deserialize::deserialize(deserializer)?;
+        Self::from_json(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for ProofMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "* Proof metadata:")?;
+        writeln!(f, "** Proved by: vamp-ir {}", self.tool_version)?;
+        writeln!(f, "** Backend: {}", self.backend)?;
+        writeln!(f, "** Curve: {}", self.curve)?;
+        writeln!(f, "** Circuit digest: {}", self.circuit_hash)?;
+        writeln!(f, "** Proving time: {:.1} ms", self.prover_time.as_secs_f64() * 1000.0)?;
+        write!(f, "** Proved at: {} seconds since the Unix epoch", self.timestamp)?;
+        for (name, value) in &self.public_inputs {
+            write!(f, "\n** Public input {} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/* Write the given metadata beside the proof at the given path, subject to
+ * check_output, and return where it was written. Proofs written to standard
+ * output have nowhere for their metadata to go. */
+pub fn write_metadata(proof: &Path, metadata: &ProofMetadata, force: bool) -> Result<PathBuf, String> {
+    if is_stdout(proof) {
+        return Err("metadata can only be written beside a proof file, not standard output".to_string());
+    }
+    let path = metadata_path(proof);
+    let text = serde_json::to_string_pretty(&metadata.to_json())
+        .map_err(|err| format!("cannot serialize metadata: {}", err))?;
+    write_output(&path, text + "\n", force)?;
+    Ok(path)
+}
+
+/* Read the metadata beside the proof at the given path, if there is any. */
+pub fn read_metadata(proof: &Path) -> Result<Option<ProofMetadata>, String> {
+    let path = metadata_path(proof);
+    if is_stdout(proof) || !path.exists() {
+        return Ok(None);
+    }
+    let name = path.to_string_lossy();
+    let text = std::fs::read_to_string(&path).map_err(|err| format!("{}: {}", name, err))?;
+    let value: Value = serde_json::from_str(&text).map_err(|err| format!("{}: {}", name, err))?;
+    ProofMetadata::from_json(&value).map(Some).map_err(|err| format!("{}: {}", name, err))
+}
@@ -0,0 +1,50 @@
+use crate::stdio::is_stdout;
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+/* Check that a file can be written to the given path without destroying an
+ * existing one, unless forced to. Commands check their outputs before doing
+ * any work so that they do not fail at the very end. */
+pub fn check_output(path: &Path, force: bool) -> Result<(), String> {
+    if is_stdout(path) {
+        return Ok(());
+    }
+    let name = path.to_string_lossy();
+    if path.is_dir() {
+        return Err(format!("{} is a directory", name));
+    }
+    if path.exists() && !force {
+        return Err(format!("{} already exists; give --force to overwrite it", name));
+    }
+    Ok(())
+}
+
+/* Create the file at the given path for writing, subject to check_output,
+ * and explain the common reasons for failing to. */
+pub fn create_output(path: &Path, force: bool) -> Result<File, String> {
+    check_output(path, force)?;
+    let name = path.to_string_lossy();
+    File::create(path).map_err(|err| match err.kind() {
+        ErrorKind::PermissionDenied => format!("{} is not writable: permission denied", name),
+        ErrorKind::NotFound => match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() =>
+                format!("cannot create {}: directory {} does not exist", name, parent.to_string_lossy()),
+            _ => format!("cannot create {}: {}", name, err),
+        },
+        _ => format!("cannot create {}: {}", name, err),
+    })
+}
+
+/* Write the given contents to the file at the given path, subject to
+ * check_output, or to standard output if the path is -. */
+pub fn write_output(path: &Path, contents: impl AsRef<[u8]>, force: bool) -> Result<(), String> {
+    if is_stdout(path) {
+        return std::io::stdout()
+            .write_all(contents.as_ref())
+            .map_err(|err| format!("cannot write to standard output: {}", err));
+    }
+    create_output(path, force)?
+        .write_all(contents.as_ref())
+        .map_err(|err| format!("cannot write {}: {}", path.to_string_lossy(), err))
+}
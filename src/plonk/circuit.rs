@@ -0,0 +1,245 @@
+use crate::ast::Module;
+use crate::artifact::{parse_artifact_from_reader, ArtifactKind, DECODE_LIMIT};
+use crate::assign_inputs;
+use crate::error::Error;
+use crate::plonk::synth::{make_constant, PlonkModule, PrimeFieldOps};
+use crate::progress::NoProgress;
+use ark_bls12_381::{Bls12_381, Fr as BlsScalar};
+use ark_ec::PairingEngine;
+use ark_ed_on_bls12_381::EdwardsParameters as JubJubParameters;
+use ark_poly::polynomial::univariate::DensePolynomial;
+use ark_poly_commit::{sonic_pc::SonicKZG10, PolynomialCommitment};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError};
+use bincode::error::{DecodeError, EncodeError};
+use num_bigint::BigInt;
+use plonk::error::to_pc_error;
+use plonk_core::circuit::{verify_proof, Circuit};
+use plonk_core::prelude::VerifierData;
+use plonk_core::proof_system::pi::PublicInputs;
+use plonk_core::proof_system::{Proof, ProverKey, VerifierKey};
+use rand_core::OsRng;
+use std::collections::HashMap;
+use std::io::Write;
+
+/* The polynomial commitment scheme that PLONK circuits are proved with. */
+pub type PC = SonicKZG10<Bls12_381, DensePolynomial<BlsScalar>>;
+
+/* The public parameters of the commitment scheme, from which the keys of
+ * circuits are generated. */
+pub type UniversalParams = <PC as PolynomialCommitment<<Bls12_381 as PairingEngine>::Fr, DensePolynomial<BlsScalar>>>::UniversalParams;
+
+/* A PLONK circuit held in memory along with the public parameters and keys
+ * that it is proved and verified with. */
+pub struct PlonkCircuit {
+    pp: UniversalParams,
+    circuit: PlonkModule<BlsScalar, JubJubParameters>,
+    pk_p: ProverKey<BlsScalar>,
+    vk: VerifierKey<BlsScalar, PC>,
+}
+
+/* A PLONK proof along with the public inputs that it was made for. */
+pub struct PlonkProof {
+    pub proof: Proof<BlsScalar, PC>,
+    pub pi: PublicInputs<BlsScalar>,
+}
+
+impl PlonkCircuit {
+    /* Synthesize a circuit from the given module, compiled over the
+     * BLS12-381 scalar field, with public parameters set up just large enough
+     * for it, and generate its keys. */
+    pub fn new(module: Module) -> Result<Self, Error> {
+        let circuit = PlonkModule::<BlsScalar, JubJubParameters>::new(module);
+        let pp = PC::setup(2 * circuit.padded_circuit_size(), None, &mut OsRng)
+            .map_err(to_pc_error::<BlsScalar, PC>)
+            .map_err(|err| Error::Backend(format!("unable to setup public parameters: {:?}", err)))?;
+        Self::from_parts(circuit, pp)
+    }
+
+    /* Synthesize a circuit from the given module as new does, generating its
+     * keys from the given public parameters, which must be large enough. */
+    pub fn with_params(module: Module, pp: UniversalParams) -> Result<Self, Error> {
+        Self::from_parts(PlonkModule::new(module), pp)
+    }
+
+    fn from_parts(mut circuit: PlonkModule<BlsScalar, JubJubParameters>, pp: UniversalParams) -> Result<Self, Error> {
+        circuit.check_constraints().map_err(Error::Backend)?;
+        let (pk_p, (vk, _)) = circuit.compile::<PC>(&pp)
+            .map_err(|err| Error::Backend(format!("unable to compile circuit: {:?}", err)))?;
+        Ok(Self { pp, circuit, pk_p, vk })
+    }
+
+    pub fn module(&self) -> &Module {
+        &self.circuit.module
+    }
+
+    /* Prove knowledge of the witnesses that the given inputs, named as in the
+     * source, give rise to. */
+    pub fn prove(&mut self, inputs: &HashMap<String, BigInt>) -> Result<PlonkProof, Error> {
+        let assigns = assign_inputs(&self.circuit.module, inputs, &PrimeFieldOps::<BlsScalar>::default())?;
+        let assigns: HashMap<_, BlsScalar> = assigns.iter().map(|(var, value)| (*var, make_constant(value))).collect();
+        self.circuit.populate_variables(assigns, &NoProgress)?;
+        let (proof, pi) = self.circuit.gen_proof::<PC>(&self.pp, self.pk_p.clone(), b"Test")
+            .map_err(|err| Error::Backend(format!("unable to generate proof: {:?}", err)))?;
+        Ok(PlonkProof { proof, pi })
+    }
+
+    /* Check the given proof of this circuit against the public inputs that it
+     * carries. */
+    pub fn verify(&self, proof: &PlonkProof) -> Result<(), Error> {
+        verify_proof::<BlsScalar, JubJubParameters, PC>(&self.pp, self.vk.clone(), &proof.proof, &proof.pi, b"Test")
+            .map_err(|err| Error::InvalidProof(format!("{:?}", err)))
+    }
+}
+
+/* Captures all the data required to use a PLONK circuit. */
+pub struct PlonkCircuitData {
+    pub pk_p: ProverKey::<BlsScalar>,
+    pub vk: (VerifierKey::<BlsScalar, PC>, Vec<usize>),
+    pub circuit: PlonkModule::<BlsScalar, JubJubParameters>,
+    // Optimization level that the circuit was compiled at
+    pub opt_level: u8,
+    // Public variables fixed to constants at compile time, in order
+    pub defines: Vec<(String, String)>,
+}
+
+impl PlonkCircuitData {
+    pub fn read<R>(mut reader: R) -> Result<Self, DecodeError>
+    where R: std::io::Read {
+        let pk_p = ProverKey::<BlsScalar>::deserialize(&mut reader)
+            .map_err(|x| DecodeError::OtherString(x.to_string()))?;
+        let vk = <(VerifierKey::<_, _>, Vec::<usize>)>::deserialize(&mut reader)
+            .map_err(|x| DecodeError::OtherString(x.to_string()))?;
+        let circuit: PlonkModule::<BlsScalar, JubJubParameters> =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let opt_level: u8 =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let defines: Vec<(String, String)> =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        Ok(Self { pk_p, vk, circuit, opt_level, defines })
+    }
+
+    pub fn write<W>(&self, mut writer: W) -> Result<(), EncodeError>
+    where W: std::io::Write {
+        self.pk_p.serialize(&mut writer)
+            .map_err(|x| EncodeError::OtherString(x.to_string()))?;
+        self.vk.serialize(&mut writer)
+            .map_err(|x| EncodeError::OtherString(x.to_string()))?;
+        bincode::encode_into_std_write(
+            &self.circuit,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            self.opt_level,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            &self.defines,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        Ok(())
+    }
+}
+
+/* Captures all the data generated from proving circuit witnesses. */
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofDataPlonk {
+    pub proof: Proof<BlsScalar, PC>,
+    pub pi: PublicInputs<BlsScalar>,
+}
+
+/* A PLONK proof as serialized with serde: the proof and its public inputs,
+ * each in hexadecimal as they are written in proof files. */
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedPlonkProof {
+    proof: String,
+    pi: String,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PlonkProof {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::Error;
+        let (mut proof, mut pi) = (vec![], vec![]);
+        self.proof.serialize(&mut proof).map_err(|err| S::Error::custom(format!("{:?}", err)))?;
+        self.pi.serialize(&mut pi).map_err(|err| S::Error::custom(format!("{:?}", err)))?;
+        let serialized = SerializedPlonkProof { proof: hex::encode(proof), pi: hex::encode(pi) };
+        serde::Serialize::serialize(&serialized, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PlonkProof {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let SerializedPlonkProof { proof, pi } = serde::Deserialize::deserialize(deserializer)?;
+        let decode = |name: &str, text: &str| hex::decode(text)
+            .map_err(|err| D::Error::custom(format!("{} is not hexadecimal: {}", name, err)));
+        let proof = Proof::deserialize(decode("proof", &proof)?.as_slice())
+            .map_err(|err| D::Error::custom(format!("corrupted proof: {:?}", err)))?;
+        let pi = PublicInputs::deserialize(decode("pi", &pi)?.as_slice())
+            .map_err(|err| D::Error::custom(format!("corrupted public inputs: {:?}", err)))?;
+        Ok(Self { proof, pi })
+    }
+}
+
+/* Check the proof read from the given reader against the circuit and the
+ * public parameters read from the others, and against the given values of
+ * the public variables, in the order they are declared, if there are any.
+ * The parameters and circuit, which run to megabytes, are decoded as they
+ * arrive rather than read into memory first. */
+pub fn verify_from_reader(
+    params: impl Read,
+    circuit: impl Read,
+    proof: impl Read,
+    expected: Option<&[BigInt]>,
+) -> Result<(), Error> {
+    let pp = parse_artifact_from_reader(params, ArtifactKind::PlonkParams, |reader| UniversalParams::deserialize(reader))
+        .map_err(Error::Artifact)?;
+    let PlonkCircuitData { vk: (vk, positions), circuit, .. } =
+        parse_artifact_from_reader(circuit, ArtifactKind::PlonkCircuit, |reader| PlonkCircuitData::read(reader))
+            .map_err(Error::Artifact)?;
+    let ProofDataPlonk { proof, pi } =
+        parse_artifact_from_reader(proof, ArtifactKind::PlonkProof, |reader| ProofDataPlonk::deserialize(reader))
+            .map_err(Error::Artifact)?;
+    if let Some(expected) = expected {
+        check_public_values(&circuit, &positions, &pi, expected)?;
+    }
+    let verifier_data = VerifierData::new(vk, pi);
+    verify_proof::<BlsScalar, JubJubParameters, PC>(&pp, verifier_data.key, &proof, &verifier_data.pi, b"Test")
+        .map_err(|err| Error::InvalidProof(format!("{:?}", err)))
+}
+
+/* Check that the public inputs of a proof of the given circuit, whose public
+ * variables are at the given positions, take the given values in the order
+ * the variables are declared. */
+fn check_public_values(
+    circuit: &PlonkModule<BlsScalar, JubJubParameters>,
+    positions: &Vec<usize>,
+    pi: &PublicInputs<BlsScalar>,
+    expected: &[BigInt],
+) -> Result<(), Error> {
+    let pubs = &circuit.module.pubs;
+    if expected.len() != pubs.len() {
+        return Err(Error::Inputs(format!(
+            "{} public value(s) were given, but the circuit has {} public variable(s)",
+            expected.len(), pubs.len(),
+        )));
+    }
+    let expected: Vec<_> = expected.iter().map(make_constant::<BlsScalar>).collect();
+    let actual = circuit.annotate_public_inputs_strict(positions, pi, Some(&expected))?;
+    let mismatches: Vec<_> = pubs.iter().zip(&expected).enumerate()
+        .filter(|(_, (var, expected))| **expected != actual[&var.id].1)
+        .map(|(index, _)| index.to_string())
+        .collect();
+    if !mismatches.is_empty() {
+        return Err(Error::InvalidProof(format!(
+            "proof is of other values of the public variable(s) at position(s) {}",
+            mismatches.join(", "),
+        )));
+    }
+    Ok(())
+}
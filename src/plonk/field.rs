@@ -0,0 +1,97 @@
+use crate::ast::InfixOp;
+use crate::transform::{CostModel, FieldOpError, FieldOps};
+use ark_ff::{PrimeField, SquareRootField, LegendreSymbol};
+use std::marker::PhantomData;
+use num_bigint::{BigUint, BigInt, ToBigInt, Sign};
+use num_traits::{Signed, Zero};
+
+// Make field elements from signed values
+pub fn make_constant<F: PrimeField>(c: &BigInt) -> F {
+    let magnitude = F::from(c.magnitude().clone());
+    if c.is_positive() {
+        magnitude
+    } else {
+        -magnitude
+    }
+}
+
+#[derive(Default)]
+pub struct PrimeFieldOps<F> where F: PrimeField {
+    phantom: PhantomData<F>
+}
+
+/* Compute the smaller of the two square roots of the given field element, if
+ * it has any. Fixing the root makes witnesses independent of the particular
+ * square root algorithm. */
+pub fn field_sqrt<F>(a: F) -> Option<F> where F: PrimeField + SquareRootField {
+    a.sqrt().map(|root| {
+        if Into::<BigUint>::into(root) <= Into::<BigUint>::into(-root) { root } else { -root }
+    })
+}
+
+/* Costs of the constructs of circuits synthesized by the PLONK backend. Wires
+ * are tied together by the permutation argument at no cost per copy, but each
+ * public variable needs a gate of its own. */
+pub struct PlonkCostModel;
+
+impl CostModel for PlonkCostModel {
+    fn add_gate(&self) -> u64 { 2 }
+    fn mul_gate(&self) -> u64 { 3 }
+    fn copy_constraint(&self) -> u64 { 0 }
+    fn public_input(&self) -> u64 { 2 }
+}
+
+impl<F> FieldOps for PrimeFieldOps<F> where F: PrimeField + SquareRootField {
+    /* Evaluate the given negation expression in the given prime field. */
+    fn canonical(&self, a: BigInt) -> BigInt {
+        let b = make_constant::<F>(&a);
+        Into::<BigUint>::into(b).to_bigint().unwrap()
+    }
+    /* Evaluate the given negation expression in the given prime field. */
+    fn negate(&self, a: BigInt) -> BigInt {
+        let b = make_constant::<F>(&a);
+        Into::<BigUint>::into(-b).to_bigint().unwrap()
+    }
+    /* Evaluate the given infix expression in the given prime field. */
+    fn infix(&self, op: InfixOp, a: BigInt, b: BigInt) -> Result<BigInt, FieldOpError> {
+        let c = make_constant::<F>(&a);
+        let d = make_constant::<F>(&b);
+        Ok(match op {
+            InfixOp::Add => Into::<BigUint>::into(c + d).to_bigint().unwrap(),
+            InfixOp::Subtract => Into::<BigUint>::into(c - d).to_bigint().unwrap(),
+            InfixOp::Multiply => Into::<BigUint>::into(c * d).to_bigint().unwrap(),
+            InfixOp::Divide if d == F::zero() => return Err(FieldOpError::DivisionByZero),
+            InfixOp::Divide => Into::<BigUint>::into(c / d).to_bigint().unwrap(),
+            InfixOp::DivideZ => Into::<BigUint>::into(if d == F::zero() { F::zero() } else { c / d }).to_bigint().unwrap(),
+            InfixOp::IntDivide | InfixOp::Modulo if b.is_zero() =>
+                return Err(FieldOpError::DivisionByZero),
+            InfixOp::IntDivide => a / b,
+            InfixOp::Modulo => a % b,
+            InfixOp::Exponentiate if c == F::zero() && b.is_negative() =>
+                return Err(FieldOpError::DivisionByZero),
+            InfixOp::Exponentiate => {
+                // Exponents of non-zero bases can be reduced modulo the order
+                // of the multiplicative group
+                let order: BigUint = (-F::one()).into();
+                let exp = if c == F::zero() { b.magnitude().clone() } else { b.magnitude() % order };
+                // Square and multiply over the exponent's digits
+                let pow = c.pow(exp.to_u64_digits());
+                Into::<BigUint>::into(if b.sign() == Sign::Minus {
+                    F::one()/pow
+                } else {
+                    pow
+                }).to_bigint().unwrap()
+            },
+            InfixOp::Equal => panic!("cannot evaluate equals expression"),
+        })
+    }
+    /* Compute the square root of the given value in the given prime field. */
+    fn sqrt(&self, a: BigInt) -> Option<BigInt> {
+        field_sqrt(make_constant::<F>(&a))
+            .map(|root| Into::<BigUint>::into(root).to_bigint().unwrap())
+    }
+    /* Check whether the given value is a square in the given prime field. */
+    fn is_square(&self, a: BigInt) -> bool {
+        make_constant::<F>(&a).legendre() != LegendreSymbol::QuadraticNonResidue
+    }
+}
@@ -1,2 +1,6 @@
-pub mod cli;
-pub mod synth;
\ No newline at end of file
+#[cfg(feature = "compiler")]
+pub mod field;
+#[cfg(feature = "plonk")]
+pub mod synth;
+#[cfg(feature = "plonk")]
+pub mod circuit;
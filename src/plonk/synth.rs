@@ -1,15 +1,16 @@
-use crate::ast::{Module, VariableId, TExpr, InfixOp, Pat, Expr};
-use crate::transform::{collect_module_variables, FieldOps};
-use ark_ff::PrimeField;
+use crate::ast::{Module, VariableId, InfixOp, Expr, TExpr};
+use crate::transform::{input_name, ordered_module_variables, solve_definitions, EvalError};
+pub use crate::plonk::field::{field_sqrt, make_constant, PlonkCostModel, PrimeFieldOps};
+use crate::progress::{Phase, Progress};
+use ark_ff::{PrimeField, SquareRootField};
 use ark_ec::TEModelParameters;
 use plonk_core::circuit::Circuit;
 use plonk_core::constraint_system::StandardComposer;
 use plonk_core::error::Error;
 use plonk_core::proof_system::pi::PublicInputs;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::marker::PhantomData;
-use num_bigint::{BigUint, BigInt, ToBigInt, Sign};
-use num_traits::Signed;
+use num_bigint::{BigUint, ToBigInt};
 use crate::ast::Variable;
 
 struct PrimeFieldBincode<T>(T) where T: PrimeField;
@@ -37,109 +38,12 @@ impl<T> bincode::Decode for PrimeFieldBincode<T> where T: PrimeField {
     }
 }
 
-// Make field elements from signed values
-pub fn make_constant<F: PrimeField>(c: &BigInt) -> F {
-    let magnitude = F::from(c.magnitude().clone());
-    if c.is_positive() {
-        magnitude
-    } else {
-        -magnitude
-    }
-}
-
-/* Evaluate the given expression sourcing any variables from the given maps. */
-fn evaluate_expr<F>(
-    expr: &TExpr,
-    defs: &mut HashMap<VariableId, TExpr>,
-    assigns: &mut HashMap<VariableId, F>,
-) -> F where F: PrimeField {
-    match &expr.v {
-        Expr::Constant(c) => make_constant(c),
-        Expr::Variable(v) => {
-            if let Some(val) = assigns.get(&v.id) {
-                // First look for existing variable assignment
-                *val
-            } else {
-                // Otherwise compute variable from first principles
-                let val = evaluate_expr(&defs[&v.id].clone(), defs, assigns);
-                assigns.insert(v.id, val);
-                val
-            }
-        },
-        Expr::Negate(e) => -evaluate_expr(e, defs, assigns),
-        Expr::Infix(InfixOp::Add, a, b) =>
-            evaluate_expr(&a, defs, assigns) +
-            evaluate_expr(&b, defs, assigns),
-        Expr::Infix(InfixOp::Subtract, a, b) =>
-            evaluate_expr(&a, defs, assigns) -
-            evaluate_expr(&b, defs, assigns),
-        Expr::Infix(InfixOp::Multiply, a, b) =>
-            evaluate_expr(&a, defs, assigns) *
-            evaluate_expr(&b, defs, assigns),
-        Expr::Infix(InfixOp::Divide, a, b) =>
-            evaluate_expr(&a, defs, assigns) /
-            evaluate_expr(&b, defs, assigns),
-        Expr::Infix(InfixOp::DivideZ, a, b) => {
-            let denom = evaluate_expr(&b, defs, assigns);
-            if denom == F::zero() { F::zero() } else { evaluate_expr(&a, defs, assigns) / denom }    
-        },
-        Expr::Infix(InfixOp::IntDivide, a, b) =>
-            (Into::<BigUint>::into(evaluate_expr(&a, defs, assigns)) /
-            Into::<BigUint>::into(evaluate_expr(&b, defs, assigns))).into(),
-        Expr::Infix(InfixOp::Modulo, a, b) =>
-            (Into::<BigUint>::into(evaluate_expr(&a, defs, assigns)) %
-            Into::<BigUint>::into(evaluate_expr(&b, defs, assigns))).into(),
-        _ => unreachable!("encountered unexpected expression: {}", expr),
-    }
-}
-
-#[derive(Default)]
-pub struct PrimeFieldOps<F> where F: PrimeField {
-    phantom: PhantomData<F>
-}
-
-impl<F> FieldOps for PrimeFieldOps<F> where F: PrimeField {
-    /* Evaluate the given negation expression in the given prime field. */
-    fn canonical(&self, a: BigInt) -> BigInt {
-        let b = make_constant::<F>(&a);
-        Into::<BigUint>::into(b).to_bigint().unwrap()
-    }
-    /* Evaluate the given negation expression in the given prime field. */
-    fn negate(&self, a: BigInt) -> BigInt {
-        let b = make_constant::<F>(&a);
-        Into::<BigUint>::into(-b).to_bigint().unwrap()
-    }
-    /* Evaluate the given infix expression in the given prime field. */
-    fn infix(&self, op: InfixOp, a: BigInt, b: BigInt) -> BigInt {
-        let c = make_constant::<F>(&a);
-        let d = make_constant::<F>(&b);
-        match op {
-            InfixOp::Add => Into::<BigUint>::into(c + d).to_bigint().unwrap(),
-            InfixOp::Subtract => Into::<BigUint>::into(c - d).to_bigint().unwrap(),
-            InfixOp::Multiply => Into::<BigUint>::into(c * d).to_bigint().unwrap(),
-            InfixOp::Divide => Into::<BigUint>::into(c / d).to_bigint().unwrap(),
-            InfixOp::DivideZ => Into::<BigUint>::into(if d == F::zero() { F::zero() } else { c / d }).to_bigint().unwrap(),
-            InfixOp::IntDivide => a / b,
-            InfixOp::Modulo => a % b,
-            InfixOp::Exponentiate => {
-                let (sign, limbs) = b.to_u64_digits();
-                Into::<BigUint>::into(if sign == Sign::Minus {
-                    F::one()/c.pow(limbs)
-                } else {
-                    c.pow(limbs)
-                }).to_bigint().unwrap()
-            },
-            InfixOp::Equal => panic!("cannot evaluate equals expression"),
-        }
-    }
-}
-
 pub struct PlonkModule<F, P>
 where
     F: PrimeField,
     P: TEModelParameters<BaseField = F>, {
     pub module: Module,
-    variable_map: HashMap<VariableId, F>,
+    variable_map: BTreeMap<VariableId, F>,
     phantom: PhantomData<P>,
 }
 
@@ -151,7 +55,7 @@ where
         &self,
         encoder: &mut E,
     ) -> core::result::Result<(), bincode::error::EncodeError> {
-        let mut encoded_variable_map = HashMap::new();
+        let mut encoded_variable_map = BTreeMap::new();
         for (k, v) in self.variable_map.clone() {
             encoded_variable_map.insert(k, PrimeFieldBincode(v));
         }
@@ -167,8 +71,8 @@ impl<F, P> bincode::Decode for PlonkModule<F, P> where
     fn decode<D: bincode::de::Decoder>(
         decoder: &mut D,
     ) -> core::result::Result<Self, bincode::error::DecodeError> {
-        let encoded_variable_map = HashMap::<VariableId, PrimeFieldBincode<F>>::decode(decoder)?;
-        let mut variable_map = HashMap::new();
+        let encoded_variable_map = BTreeMap::<VariableId, PrimeFieldBincode<F>>::decode(decoder)?;
+        let mut variable_map = BTreeMap::new();
         for (k, v) in encoded_variable_map {
             variable_map.insert(k, v.0);
         }
@@ -184,37 +88,58 @@ where
 {
     /* Make new circuit with default assignments to all variables in module. */
     pub fn new(module: Module) -> PlonkModule<F, P> {
-        let mut variables = HashMap::new();
-        collect_module_variables(&module, &mut variables);
-        let mut variable_map = HashMap::new();
-        for variable in variables.keys() {
-            variable_map.insert(*variable, F::default());
+        let mut variable_map = BTreeMap::new();
+        for variable in ordered_module_variables(&module) {
+            variable_map.insert(variable.id, F::default());
         }
         PlonkModule { module, variable_map, phantom: PhantomData }
     }
 
-    /* Populate input and auxilliary variables from the given program inputs. */
+    /* Populate input and auxilliary variables from the given program inputs.
+     * Fails, naming the variable, if one has neither a definition nor an
+     * input, or if the inputs violate a constraint. */
     pub fn populate_variables(
         &mut self,
-        mut field_assigns: HashMap<VariableId, F>,
-    ) {
-        // Get the definitions necessary to populate auxiliary variables
-        let mut definitions = HashMap::new();
-        for def in &self.module.defs {
-            if let Pat::Variable(var) = &def.0.0.v {
-                definitions.insert(var.id, *def.0.1.clone());
-            }
-        }
-        // Start deriving witnesses
+        field_assigns: HashMap<VariableId, F>,
+        progress: &dyn Progress,
+    ) -> Result<(), EvalError> where F: SquareRootField {
+        progress.start(Phase::PopulateWitnesses, Some(self.variable_map.len() as u64));
+        // Derive the auxiliary variables from the inputs in definition order
+        let mut assigns = field_assigns
+            .into_iter()
+            .map(|(var, val)| (var, Into::<BigUint>::into(val).to_bigint().unwrap()))
+            .collect();
+        solve_definitions(&self.module, &mut assigns, &PrimeFieldOps::<F>::default())?;
         for (var, value) in &mut self.variable_map {
-            let var_expr = Expr::Variable(crate::ast::Variable::new(*var)).type_expr(None);
-            *value = evaluate_expr(&var_expr, &mut definitions, &mut field_assigns);
+            let val = assigns.get(var).ok_or_else(|| {
+                let undefined = ordered_module_variables(&self.module).into_iter()
+                    .find(|x| x.id == *var)
+                    .unwrap_or_else(|| Variable::new(*var));
+                EvalError::undefined(&undefined, None)
+            })?;
+            *value = make_constant(val);
+            progress.step();
+        }
+        progress.finish(Phase::PopulateWitnesses);
+        Ok(())
+    }
+
+    /* Check that every constraint of this module has one of the shapes that
+     * are synthesized as gates, as those of three-address codes do. Fails,
+     * naming the first constraint that does not, as one read from elsewhere
+     * might. */
+    pub fn check_constraints(&self) -> Result<(), String> {
+        match self.module.exprs.iter().find(|expr| !gate_shaped(expr)) {
+            Some(expr) => Err(format!("unsupported constraint encountered: {}", expr)),
+            None => Ok(()),
         }
     }
 
     /* Annotate the given public inputs with the variable names contained in
      * this module. This function assumes that the public variables in this
-     * module and the public inputs in the argument occur in the same order. */
+     * module and the public inputs in the argument occur in the same order,
+     * reading zero wherever it finds otherwise, so prefer
+     * annotate_public_inputs_strict unless the inputs are known to fit. */
     pub fn annotate_public_inputs(
         &self,
         intended_pi_pos: &Vec<usize>,
@@ -233,6 +158,81 @@ where
         }
         annotated
     }
+
+    /* Annotate the given public inputs as annotate_public_inputs does, but
+     * fail when they cannot be laid out as this module expects: when the
+     * intended positions do not match the public variables one for one, when
+     * there are values at positions that no public variable is intended for,
+     * or when an intended position has no value although the value expected
+     * of its variable, given in the order the public variables are declared,
+     * is not zero. plonk-core leaves out public inputs that are zero, so an
+     * intended position without a value is otherwise read as zero. */
+    pub fn annotate_public_inputs_strict(
+        &self,
+        intended_pi_pos: &Vec<usize>,
+        pi: &PublicInputs<F>,
+        expected: Option<&[F]>,
+    ) -> Result<HashMap<VariableId, (Variable, F)>, crate::error::Error> {
+        let pubs = &self.module.pubs;
+        if intended_pi_pos.len() != pubs.len() {
+            let unplaced: Vec<_> = pubs.iter().skip(intended_pi_pos.len()).map(input_name).collect();
+            let unplaced = if unplaced.is_empty() {
+                String::new()
+            } else {
+                format!(", leaving {} without any", unplaced.join(", "))
+            };
+            return Err(crate::error::Error::Artifact(format!(
+                "the circuit records {} public input position(s) for its {} public variable(s){}",
+                intended_pi_pos.len(), pubs.len(), unplaced,
+            )));
+        }
+        let intended: HashSet<_> = intended_pi_pos.iter().collect();
+        let stray: Vec<_> = pi.get_pos()
+            .filter(|pos| !intended.contains(pos))
+            .map(|pos| pos.to_string())
+            .collect();
+        if !stray.is_empty() {
+            return Err(crate::error::Error::InvalidProof(format!(
+                "public inputs at position(s) {} belong to no public variable of the circuit",
+                stray.join(", "),
+            )));
+        }
+        if let Some(expected) = expected {
+            let present: HashSet<_> = pi.get_pos().collect();
+            let missing: Vec<_> = pubs.iter().zip(intended_pi_pos).zip(expected)
+                .filter(|((_, pos), val)| !present.contains(pos) && !val.is_zero())
+                .map(|((var, pos), _)| format!("{} at position {}", input_name(var), pos))
+                .collect();
+            if !missing.is_empty() {
+                return Err(crate::error::Error::InvalidProof(format!(
+                    "the proof has no public input for {}, which are not expected to be zero",
+                    missing.join(", "),
+                )));
+            }
+        }
+        Ok(self.annotate_public_inputs(intended_pi_pos, pi))
+    }
+}
+
+/* Check whether the given constraint is synthesized as a gate: a variable or
+ * a constant equated with a variable, a constant, a negated one, or a binary
+ * operation on two of them. */
+fn gate_shaped(expr: &TExpr) -> bool {
+    let atom = |expr: &TExpr| matches!(expr.v, Expr::Variable(_) | Expr::Constant(_));
+    match &expr.v {
+        Expr::Infix(InfixOp::Equal, lhs, rhs) => atom(lhs) && match &rhs.v {
+            Expr::Variable(_) | Expr::Constant(_) => true,
+            Expr::Negate(expr1) => atom(expr1),
+            Expr::Infix(
+                InfixOp::Add | InfixOp::Subtract | InfixOp::Multiply | InfixOp::Divide | InfixOp::DivideZ,
+                expr1,
+                expr2,
+            ) => atom(expr1) && atom(expr2),
+            _ => false,
+        },
+        // Other expressions are not constraints and are passed over
+        _ => true,
+    }
 }
 
 impl<F, P> Circuit<F, P> for PlonkModule<F, P>
@@ -989,7 +989,9 @@ where
                         });
                         true
                     }) => {},
-                    _ => panic!("unsupported constraint encountered: {}", expr)
+                    // Errors here carry no message, so check_constraints is
+                    // what names the constraint
+                    _ => return Err(Error::CircuitInputsNotFound),
                 }
             }
         }
@@ -0,0 +1,197 @@
+use crate::ast::{Module, VariableId};
+use crate::artifact::{artifact_bytes, parse_artifact, ArtifactKind, DECODE_LIMIT};
+use crate::assign_inputs;
+use crate::error::Error;
+use crate::plonky2::synth::{make_constant, GoldilocksFieldOps, Plonky2Module, Plonky2ProofWithInputs, F};
+use ark_serialize::{Read, SerializationError};
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use bincode::error::{DecodeError, EncodeError};
+use num_bigint::BigInt;
+use plonky2::field::types::{Field, Field64, PrimeField64};
+use std::collections::HashMap;
+use std::io::Write;
+
+/* Captures all the data required to use a plonky2 circuit. Circuits need no
+ * trusted setup and are built deterministically, so only the module itself
+ * is kept. */
+pub struct Plonky2CircuitData {
+    pub module: Module,
+    // Optimization level that the circuit was compiled at
+    pub opt_level: u8,
+    // Public variables fixed to constants at compile time, in order
+    pub defines: Vec<(String, String)>,
+}
+
+impl Plonky2CircuitData {
+    pub fn read<R>(mut reader: R) -> Result<Self, DecodeError>
+    where R: std::io::Read {
+        let module: Module =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let opt_level: u8 =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        let defines: Vec<(String, String)> =
+            bincode::decode_from_std_read(&mut reader, bincode::config::standard().with_limit::<DECODE_LIMIT>())?;
+        Ok(Self { module, opt_level, defines })
+    }
+
+    pub fn write<W>(&self, mut writer: W) -> Result<(), EncodeError>
+    where W: std::io::Write {
+        bincode::encode_into_std_write(
+            &self.module,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            self.opt_level,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        bincode::encode_into_std_write(
+            &self.defines,
+            &mut writer,
+            bincode::config::standard(),
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProofDataPlonky2 {
+    // The proof as plonky2 serializes it, public inputs included
+    pub proof: Vec<u8>,
+    // Canonical values of the public variables that the proof was made for,
+    // in the order they are declared
+    pub public_inputs: Vec<u64>,
+}
+
+/* A plonky2 circuit held in memory along with the circuit that plonky2
+ * builds from its module. */
+pub struct Plonky2Circuit {
+    data: Plonky2CircuitData,
+    built: Plonky2Module,
+}
+
+/* A plonky2 proof along with the values of the public variables that it was
+ * made for, in the order that they are declared. The proof only verifies for
+ * the values that it embeds, which are checked against these. */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Plonky2Proof {
+    pub proof: Vec<u8>,
+    pub public_values: Vec<F>,
+}
+
+impl Plonky2Circuit {
+    /* Build the given module, compiled over the Goldilocks field, into a
+     * circuit. */
+    pub fn new(module: Module) -> Result<Self, Error> {
+        Self::from_data(Plonky2CircuitData { module, opt_level: 0, defines: vec![] })
+    }
+
+    /* Build the module of the given data, as compiled or as read from a
+     * file. */
+    pub fn from_data(data: Plonky2CircuitData) -> Result<Self, Error> {
+        let built = Plonky2Module::new(data.module.clone())?;
+        Ok(Self { data, built })
+    }
+
+    /* Read a circuit from the contents of a circuit file written by the command
+     * line, in any of its encodings. */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let data = parse_artifact(bytes, ArtifactKind::Plonky2Circuit, |reader| Plonky2CircuitData::read(reader))
+            .map_err(Error::Artifact)?;
+        Self::from_data(data)
+    }
+
+    /* The contents of a circuit file holding this circuit, as the command line
+     * writes it in binary. */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        artifact_bytes(ArtifactKind::Plonky2Circuit, |writer| self.data.write(writer))
+            .map_err(Error::Artifact)
+    }
+
+    pub fn data(&self) -> &Plonky2CircuitData {
+        &self.data
+    }
+
+    pub fn module(&self) -> &Module {
+        &self.data.module
+    }
+
+    /* The number of gates, including padding, in the built circuit. */
+    pub fn gates(&self) -> usize {
+        self.built.data.common.degree()
+    }
+
+    /* Prove knowledge of the witnesses that the given inputs, named as in the
+     * source, give rise to. */
+    pub fn prove(&self, inputs: &HashMap<String, BigInt>) -> Result<Plonky2Proof, Error> {
+        let assigns = assign_inputs(self.module(), inputs, &GoldilocksFieldOps)?;
+        self.prove_assigned(assigns)
+    }
+
+    /* Prove knowledge of the given values of every variable, as assign_inputs
+     * gives them back. */
+    pub fn prove_assigned(&self, assigns: HashMap<VariableId, BigInt>) -> Result<Plonky2Proof, Error> {
+        let module = self.module();
+        let proof = self.built.prove(&assigns)?;
+        let public_values: Vec<_> = module.pubs.iter().map(|var| make_constant(&assigns[&var.id])).collect();
+        Ok(Plonky2Proof { proof: proof.to_bytes(), public_values })
+    }
+
+    /* Check the given proof of this circuit against the public values that it
+     * carries. */
+    pub fn verify(&self, proof: &Plonky2Proof) -> Result<(), Error> {
+        let pubs = &self.module().pubs;
+        if proof.public_values.len() != pubs.len() {
+            return Err(Error::InvalidProof(format!(
+                "proof has {} public inputs, but the circuit has {} public variables",
+                proof.public_values.len(),
+                pubs.len(),
+            )));
+        }
+        let inner = Plonky2ProofWithInputs::from_bytes(proof.proof.clone(), &self.built.data.common)
+            .map_err(|err| Error::InvalidProof(err.to_string()))?;
+        if inner.public_inputs != proof.public_values {
+            return Err(Error::InvalidProof(
+                "proof was made for other values of the public variables".to_string()
+            ));
+        }
+        self.built.verify(inner)
+    }
+}
+
+impl Plonky2Proof {
+    pub fn read<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        let ProofDataPlonky2 { proof, public_inputs } = ProofDataPlonky2::deserialize(reader)?;
+        let public_values = public_inputs.into_iter()
+            .map(|value| if value < F::ORDER {
+                Ok(F::from_canonical_u64(value))
+            } else {
+                Err(SerializationError::InvalidData)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { proof, public_values })
+    }
+
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        let proof_data = ProofDataPlonky2 {
+            proof: self.proof.clone(),
+            public_inputs: self.public_values.iter().map(PrimeField64::to_canonical_u64).collect(),
+        };
+        proof_data.serialize(writer)
+    }
+
+    /* Read a proof from the contents of a proof file written by the command
+     * line, in any of its encodings. */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        parse_artifact(bytes, ArtifactKind::Plonky2Proof, |reader| Self::read(reader))
+            .map_err(Error::Artifact)
+    }
+
+    /* The contents of a proof file holding this proof, as the command line
+     * writes it in binary. */
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        artifact_bytes(ArtifactKind::Plonky2Proof, |writer| self.write(writer))
+            .map_err(Error::Artifact)
+    }
+}
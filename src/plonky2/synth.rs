@@ -0,0 +1,238 @@
+use crate::ast::{Expr, InfixOp, Module, TExpr, Variable, VariableId};
+use crate::error::Error;
+use crate::transform::{input_name, CostModel, FieldOpError, FieldOps};
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{ToPrimitive, Zero};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::{Field, Field64, PrimeField64};
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
+use plonky2::plonk::config::PoseidonGoldilocksConfig;
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use std::collections::HashMap;
+
+/* The Goldilocks field, of order 2^64 - 2^32 + 1, that plonky2 proves in. */
+pub type F = GoldilocksField;
+
+/* The configuration of the hashes that circuits are proved with. */
+pub type C = PoseidonGoldilocksConfig;
+
+/* The degree of the extension of the field that FRI works in. */
+pub const D: usize = 2;
+
+/* A plonky2 proof along with the values of its public inputs. */
+pub type Plonky2ProofWithInputs = ProofWithPublicInputs<F, C, D>;
+
+/* Make a Goldilocks element from a signed value. Values are reduced modulo
+ * the 64-bit order, so constants far larger than it wrap around many times,
+ * unlike in the 255-bit fields of the other backends. */
+pub fn make_constant(c: &BigInt) -> F {
+    let order = BigInt::from(F::ORDER);
+    let canonical = ((c % &order) + &order) % &order;
+    F::from_canonical_u64(canonical.to_u64().expect("canonical values fit in 64 bits"))
+}
+
+/* The given field element as the integer it stands for. */
+pub fn field_value(val: &F) -> BigInt {
+    BigInt::from(val.to_canonical_u64())
+}
+
+/* The operations of the Goldilocks field. */
+#[derive(Default)]
+pub struct GoldilocksFieldOps;
+
+impl FieldOps for GoldilocksFieldOps {
+    /* Put the given value in canonical form, below the order of the field. */
+    fn canonical(&self, a: BigInt) -> BigInt {
+        field_value(&make_constant(&a))
+    }
+    /* Evaluate the given negation expression in the Goldilocks field. */
+    fn negate(&self, a: BigInt) -> BigInt {
+        field_value(&-make_constant(&a))
+    }
+    /* Evaluate the given infix expression in the Goldilocks field. */
+    fn infix(&self, op: InfixOp, a: BigInt, b: BigInt) -> Result<BigInt, FieldOpError> {
+        let c = make_constant(&a);
+        let d = make_constant(&b);
+        Ok(match op {
+            InfixOp::Add => field_value(&(c + d)),
+            InfixOp::Subtract => field_value(&(c - d)),
+            InfixOp::Multiply => field_value(&(c * d)),
+            InfixOp::Divide if d == F::ZERO => return Err(FieldOpError::DivisionByZero),
+            InfixOp::Divide => field_value(&(c * d.inverse())),
+            InfixOp::DivideZ => field_value(&d.try_inverse().map_or(F::ZERO, |inv| c * inv)),
+            InfixOp::IntDivide | InfixOp::Modulo if b.is_zero() =>
+                return Err(FieldOpError::DivisionByZero),
+            InfixOp::IntDivide => a / b,
+            InfixOp::Modulo => a % b,
+            InfixOp::Exponentiate if c == F::ZERO && b.sign() == Sign::Minus =>
+                return Err(FieldOpError::DivisionByZero),
+            InfixOp::Exponentiate => {
+                // Exponents of non-zero bases can be reduced modulo the order
+                // of the multiplicative group, which fits in 64 bits
+                let pow = if c == F::ZERO {
+                    if b.is_zero() { F::ONE } else { F::ZERO }
+                } else {
+                    let exp = b.magnitude() % BigUint::from(F::ORDER - 1);
+                    c.exp_u64(exp.to_u64().expect("reduced exponents fit in 64 bits"))
+                };
+                field_value(&if b.sign() == Sign::Minus { pow.inverse() } else { pow })
+            },
+            InfixOp::Equal => panic!("cannot evaluate equals expression"),
+        })
+    }
+    /* Compute the smaller square root of the given value in the Goldilocks
+     * field, if it has any. */
+    fn sqrt(&self, a: BigInt) -> Option<BigInt> {
+        make_constant(&a).sqrt().map(|root| {
+            field_value(&root).min(field_value(&-root))
+        })
+    }
+}
+
+/* Costs of the constructs of circuits synthesized by the plonky2 backend.
+ * Its arithmetic gates each hold many additions or multiplications, and
+ * wires are tied together by the permutation argument at no cost per copy,
+ * but public inputs are hashed into the proof. */
+pub struct Plonky2CostModel;
+
+impl CostModel for Plonky2CostModel {
+    fn add_gate(&self) -> u64 { 1 }
+    fn mul_gate(&self) -> u64 { 1 }
+    fn copy_constraint(&self) -> u64 { 0 }
+    fn public_input(&self) -> u64 { 2 }
+}
+
+/* A compiled module built into a plonky2 circuit, along with the targets that
+ * hold the values of its variables. Building is deterministic, so circuits
+ * are rebuilt from their modules rather than stored. */
+pub struct Plonky2Module {
+    pub module: Module,
+    pub data: CircuitData<F, C, D>,
+    // Variables that appear in the circuit along with their targets
+    targets: Vec<(Variable, Target)>,
+}
+
+/* Builds circuits from modules, giving each variable a single target. */
+struct Lowering {
+    builder: CircuitBuilder<F, D>,
+    indices: HashMap<VariableId, usize>,
+    targets: Vec<(Variable, Target)>,
+}
+
+impl Lowering {
+    /* The target of the given variable, made the first time it is seen. */
+    fn variable(&mut self, var: &Variable) -> Target {
+        if let Some(index) = self.indices.get(&var.id) {
+            return self.targets[*index].1;
+        }
+        let target = self.builder.add_virtual_target();
+        self.indices.insert(var.id, self.targets.len());
+        self.targets.push((var.clone(), target));
+        target
+    }
+
+    /* The target of the given variable or constant. */
+    fn atom(&mut self, expr: &TExpr) -> Option<Target> {
+        match &expr.v {
+            Expr::Variable(var) => Some(self.variable(var)),
+            Expr::Constant(c) => Some(self.builder.constant(make_constant(c))),
+            _ => None,
+        }
+    }
+
+    /* Constrain the circuit as the given constraint of a module in
+     * three-address form does, whose sides are variables, constants, or a
+     * single operation on them. Gives nothing for constraints of any other
+     * shape. */
+    fn constraint(&mut self, expr: &TExpr) -> Option<()> {
+        let Expr::Infix(InfixOp::Equal, lhs, rhs) = &expr.v else {
+            return None;
+        };
+        let l = self.atom(lhs)?;
+        let r = match &rhs.v {
+            Expr::Negate(x) => {
+                let x = self.atom(x)?;
+                self.builder.neg(x)
+            },
+            Expr::Infix(InfixOp::Add, x, y) => {
+                let (x, y) = (self.atom(x)?, self.atom(y)?);
+                self.builder.add(x, y)
+            },
+            Expr::Infix(InfixOp::Subtract, x, y) => {
+                let (x, y) = (self.atom(x)?, self.atom(y)?);
+                self.builder.sub(x, y)
+            },
+            Expr::Infix(InfixOp::Multiply, x, y) => {
+                let (x, y) = (self.atom(x)?, self.atom(y)?);
+                self.builder.mul(x, y)
+            },
+            // l = x | y is zero when y is the constant zero
+            Expr::Infix(InfixOp::DivideZ, _, y) if matches!(&y.v, Expr::Constant(c) if make_constant(c) == F::ZERO) =>
+                self.builder.zero(),
+            // l = x / y holds as l * y = x, as in the gates of the other
+            // backends, and likewise l = x | y
+            Expr::Infix(InfixOp::Divide | InfixOp::DivideZ, x, y) => {
+                let (x, y) = (self.atom(x)?, self.atom(y)?);
+                let product = self.builder.mul(l, y);
+                self.builder.connect(product, x);
+                return Some(());
+            },
+            _ => self.atom(rhs)?,
+        };
+        self.builder.connect(l, r);
+        Some(())
+    }
+}
+
+impl Plonky2Module {
+    /* Build a circuit from the given module, compiled over the Goldilocks
+     * field, with its public variables as public inputs in the order they
+     * are declared. Fails naming each constraint that cannot be built. */
+    pub fn new(module: Module) -> Result<Self, Error> {
+        let mut lowering = Lowering {
+            builder: CircuitBuilder::new(CircuitConfig::standard_recursion_config()),
+            indices: HashMap::new(),
+            targets: vec![],
+        };
+        for var in &module.pubs {
+            let target = lowering.variable(var);
+            lowering.builder.register_public_input(target);
+        }
+        let unsupported: Vec<_> = module.exprs.iter()
+            .filter(|expr| lowering.constraint(expr).is_none())
+            .map(|expr| expr.to_string())
+            .collect();
+        if !unsupported.is_empty() {
+            return Err(Error::Compile(format!(
+                "{} constraint(s) cannot be built into a plonky2 circuit: {}",
+                unsupported.len(), unsupported.join("; "),
+            )));
+        }
+        let Lowering { builder, targets, .. } = lowering;
+        let data = builder.build::<C>();
+        Ok(Self { module, data, targets })
+    }
+
+    /* Prove that the given values of every variable of the module satisfy
+     * the circuit. */
+    pub fn prove(&self, assigns: &HashMap<VariableId, BigInt>) -> Result<Plonky2ProofWithInputs, Error> {
+        let mut witness = PartialWitness::new();
+        for (var, target) in &self.targets {
+            let value = assigns.get(&var.id)
+                .ok_or_else(|| Error::Inputs(format!("no value was derived for {}", input_name(var))))?;
+            witness.set_target(*target, make_constant(value));
+        }
+        self.data.prove(witness)
+            .map_err(|err| Error::Backend(format!("unable to generate proof: {}", err)))
+    }
+
+    /* Check the given proof of this circuit against the public inputs that
+     * it carries. */
+    pub fn verify(&self, proof: Plonky2ProofWithInputs) -> Result<(), Error> {
+        self.data.verify(proof)
+            .map_err(|err| Error::InvalidProof(err.to_string()))
+    }
+}
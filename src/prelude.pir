@@ -0,0 +1,41 @@
+/* The standard prelude of gadgets. It is merged into programs compiled with
+   --prelude std, so its definitions are available without being written out.
+*/
+
+// Ensure that the given argument is 1 or 0, and return it
+
+def bool x = { x * (x - 1) = 0; x };
+
+// Boolean operations on arguments that are known to be 1 or 0
+
+def not x = 1 - x;
+
+def and x y = x * y;
+
+def or x y = x + y - x * y;
+
+def xor x y = x + y - 2 * x * y;
+
+// Select the first value if the condition is 1 and the second if it is 0
+
+def select c a b = c * a + (1 - c) * b;
+
+// Return 1 if the given argument is zero and 0 otherwise
+
+def is_zero x = {
+    def y = 1 - x * fresh (1 | x);
+    x * y = 0;
+    y
+};
+
+// Decompose the given argument into the given number of bits, least
+// significant first. This also ensures that the argument is in range.
+
+def to_bits 0 a = { a = 0; [] };
+
+def to_bits n a = {
+    def b = bool (fresh (a % 2));
+    def r = fresh (a \ 2);
+    a = b + 2 * r;
+    b : to_bits (n - 1) r
+};
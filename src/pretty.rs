@@ -0,0 +1,396 @@
+use crate::ast::{Definition, Expr, Function, InfixOp, LetBinding, Match, Module, Pat, TExpr, TPat, Variable};
+use num_bigint::Sign;
+
+use std::collections::BTreeSet;
+
+/* Binding strengths of the expression forms of the source language, from the
+ * loosest to the tightest. Each corresponds to a level of the grammar. */
+const EXPR: u8 = 0;
+const SEQUENCE: u8 = 1;
+const PRODUCT: u8 = 2;
+const EQUAL: u8 = 3;
+const LIST: u8 = 4;
+const SUM: u8 = 5;
+const PRODUCT_OP: u8 = 6;
+const POWER: u8 = 7;
+const NEGATE: u8 = 8;
+const APPLICATION: u8 = 9;
+const ATOM: u8 = 10;
+
+/* Binding strengths of the pattern forms of the source language. */
+const PAT_AS: u8 = 0;
+const PAT_PRODUCT: u8 = 1;
+const PAT_CONS: u8 = 2;
+const PAT_ATOM: u8 = 3;
+
+/* Print the given parsed module back to source code. Parsing the result
+ * yields the given module again. Definitions are printed in their original
+ * order, or otherwise sorted by name as far as their dependencies allow.
+ * Public variables and constraints always keep their original order since
+ * the former determine the order of public inputs. */
+pub fn print_module(module: &Module, sort: bool) -> String {
+    let mut source = String::new();
+    if !module.pubs.is_empty() {
+        let names: Vec<_> = module.pubs.iter().map(print_variable).collect();
+        source.push_str(&format!("pub {};\n\n", names.join(", ")));
+    }
+    let defs = if sort { sort_definitions(&module.defs) } else { module.defs.iter().collect() };
+    // The namespaces enclosing the previously printed definition
+    let mut path: Vec<String> = vec![];
+    for def in defs {
+        let def_path = definition_path(def);
+        let common = path.iter().zip(&def_path).take_while(|(a, b)| a == b).count();
+        while path.len() > common {
+            path.pop();
+            source.push_str(&format!("{}}};\n", indent(path.len())));
+        }
+        while path.len() < def_path.len() {
+            let name = &def_path[path.len()];
+            source.push_str(&format!("{}module {} {{\n", indent(path.len()), name));
+            path.push(name.clone());
+        }
+        for clause in print_definition(def, path.len()) {
+            source.push_str(&format!("{}{};\n", indent(path.len()), clause));
+        }
+    }
+    while !path.is_empty() {
+        path.pop();
+        source.push_str(&format!("{}}};\n", indent(path.len())));
+    }
+    if !module.exprs.is_empty() {
+        if !source.is_empty() {
+            source.push('\n');
+        }
+        for expr in &module.exprs {
+            source.push_str(&format!("{};\n", print_expr(expr, EXPR)));
+        }
+    }
+    source
+}
+
+/* The indentation of items nested in the given number of namespaces. */
+fn indent(depth: usize) -> String {
+    "    ".repeat(depth)
+}
+
+/* The namespaces in which the names of the given definition were defined,
+ * outermost first. */
+fn definition_path(def: &Definition) -> Vec<String> {
+    let mut names = vec![];
+    def.0.0.collect_names(&mut names);
+    match names.first().and_then(|name| name.rsplit_once("::")) {
+        Some((prefix, _)) => prefix.split("::").map(str::to_string).collect(),
+        None => vec![],
+    }
+}
+
+/* Strip the given number of namespaces from the given qualified name. */
+fn unqualify(name: &str, depth: usize) -> &str {
+    name.splitn(depth + 1, "::").last().unwrap_or(name)
+}
+
+/* Order the given definitions by name without moving any definition past
+ * another that it is related to. Two definitions are related when one refers
+ * to a name defined by the other or to a name that could be resolved to one
+ * of the other's names from within some namespace. Related definitions keep
+ * their original order, so that names continue to resolve as they did. */
+fn sort_definitions(defs: &[Definition]) -> Vec<&Definition> {
+    let mut defined = vec![];
+    let mut referenced = vec![];
+    for def in defs {
+        let mut names = vec![];
+        def.0.0.collect_names(&mut names);
+        let mut refs = BTreeSet::new();
+        collect_referenced_names(&def.0.1, &mut refs);
+        for name in &names {
+            refs.remove(name);
+        }
+        defined.push(names);
+        referenced.push(refs);
+    }
+    let last_segment = |name: &String| name.rsplit("::").next().unwrap_or(name).to_string();
+    let related = |i: usize, j: usize| {
+        let tails: BTreeSet<_> = defined[j].iter().map(last_segment).collect();
+        referenced[i].iter().any(|name| tails.contains(&last_segment(name)))
+    };
+    let mut remaining: Vec<usize> = (0..defs.len()).collect();
+    let mut sorted = vec![];
+    while !remaining.is_empty() {
+        // Of the definitions unconstrained by earlier related ones, take the
+        // one whose name comes first
+        let ready = remaining.iter().enumerate()
+            .filter(|&(pos, &i)| remaining[..pos].iter().all(|&j| !related(i, j) && !related(j, i)))
+            .min_by_key(|&(_, &i)| defined[i].first().cloned().unwrap_or_default())
+            .map(|(pos, _)| pos)
+            .expect("the first remaining definition should always be ready");
+        sorted.push(&defs[remaining.remove(ready)]);
+    }
+    sorted
+}
+
+/* Collect the names of all the variables that the given expression refers
+ * to, including those that it binds. */
+fn collect_referenced_names(expr: &TExpr, names: &mut BTreeSet<String>) {
+    match &expr.v {
+        Expr::Variable(var) => names.extend(var.name.clone()),
+        Expr::Sequence(exprs) => {
+            for expr in exprs {
+                collect_referenced_names(expr, names);
+            }
+        },
+        Expr::Product(expr1, expr2) | Expr::Cons(expr1, expr2) |
+        Expr::Infix(_, expr1, expr2) | Expr::Application(expr1, expr2) => {
+            collect_referenced_names(expr1, names);
+            collect_referenced_names(expr2, names);
+        },
+        Expr::Negate(expr1) => collect_referenced_names(expr1, names),
+        Expr::Function(Function { body, .. }) => collect_referenced_names(body, names),
+        Expr::LetBinding(binding, expr2) => {
+            collect_referenced_names(&binding.1, names);
+            collect_referenced_names(expr2, names);
+        },
+        Expr::Match(Match(scrutinee, _, bodies)) => {
+            collect_referenced_names(scrutinee, names);
+            for body in bodies {
+                collect_referenced_names(body, names);
+            }
+        },
+        Expr::Intrinsic(_) | Expr::Unit | Expr::Nil | Expr::Constant(_) => {},
+    }
+}
+
+/* Print the clauses of the given definition, which is nested in the given
+ * number of namespaces. A function that was defined by several clauses is
+ * printed as those clauses again. */
+fn print_definition(def: &Definition, depth: usize) -> Vec<String> {
+    let LetBinding(pat, expr) = &def.0;
+    if let (Pat::Variable(var), Expr::Function(fun)) = (&pat.v, &expr.v) {
+        let name = unqualify(var.name.as_ref().expect("definition should be named"), depth);
+        if let Some(clauses) = split_clauses(fun) {
+            return clauses.into_iter().map(|(params, body)| {
+                format!("def {}", print_function_binding(name, &params, body))
+            }).collect();
+        }
+        let params: Vec<_> = fun.params.iter().collect();
+        return vec![format!("def {}", print_function_binding(name, &params, &fun.body))];
+    }
+    let mut pat = pat.clone();
+    unqualify_pattern(&mut pat, depth);
+    vec![format!("def {} = {}", print_pattern(&pat, PAT_AS), print_expr(expr, EXPR))]
+}
+
+/* Strip the given number of namespaces from the names bound by the given
+ * pattern. */
+fn unqualify_pattern(pat: &mut TPat, depth: usize) {
+    match &mut pat.v {
+        Pat::Variable(var) => {
+            var.name = var.name.as_ref().map(|name| unqualify(name, depth).to_string());
+        },
+        Pat::As(pat, var) => {
+            unqualify_pattern(pat, depth);
+            var.name = var.name.as_ref().map(|name| unqualify(name, depth).to_string());
+        },
+        Pat::Product(pat1, pat2) | Pat::Cons(pat1, pat2) => {
+            unqualify_pattern(pat1, depth);
+            unqualify_pattern(pat2, depth);
+        },
+        Pat::Unit | Pat::Nil | Pat::Constant(_) => {},
+    }
+}
+
+/* Recover the parameters and body of each clause of the given function if
+ * it was merged from several clauses. Such functions take parameters whose
+ * names are not valid identifiers and match them against each clause. */
+fn split_clauses(fun: &Function) -> Option<Vec<(Vec<&TPat>, &TExpr)>> {
+    let merged = fun.params.iter().all(|param| matches!(
+        &param.v,
+        Pat::Variable(Variable { name: Some(name), .. }) if name.contains('.')
+    ));
+    match &fun.body.v {
+        Expr::Match(Match(_, pats, bodies)) if merged => {
+            let mut clauses = vec![];
+            for (pat, body) in pats.iter().zip(bodies) {
+                // The parameters of each clause are matched as a tuple
+                let mut params = vec![];
+                let mut rest = pat;
+                for _ in 1..fun.params.len() {
+                    match &rest.v {
+                        Pat::Product(pat1, pat2) => {
+                            params.push(pat1.as_ref());
+                            rest = pat2;
+                        },
+                        _ => unreachable!("clause should match a tuple of parameters"),
+                    }
+                }
+                params.push(rest);
+                clauses.push((params, body));
+            }
+            Some(clauses)
+        },
+        _ => None,
+    }
+}
+
+/* Print a binding of the given name to a function of the given parameters
+ * and body. */
+fn print_function_binding(name: &str, params: &[&TPat], body: &TExpr) -> String {
+    let mut binding = name.to_string();
+    for param in params {
+        binding.push(' ');
+        binding.push_str(&print_pattern(param, PAT_ATOM));
+    }
+    format!("{} = {}", binding, print_expr(body, EXPR))
+}
+
+/* Print the given let binding. */
+fn print_binding(binding: &LetBinding) -> String {
+    let LetBinding(pat, expr) = binding;
+    match (&pat.v, &expr.v) {
+        (Pat::Variable(var), Expr::Function(fun)) => {
+            let params: Vec<_> = fun.params.iter().collect();
+            print_function_binding(&print_variable(var), &params, &fun.body)
+        },
+        _ => format!("{} = {}", print_pattern(pat, PAT_AS), print_expr(expr, EXPR)),
+    }
+}
+
+/* Print the name of the given variable as it occurs in source code. */
+fn print_variable(var: &Variable) -> String {
+    var.name.clone().expect("parsed variables should be named")
+}
+
+/* Print the given pattern so that it binds at least as tightly as the given
+ * level requires. */
+fn print_pattern(pat: &TPat, level: u8) -> String {
+    let (own, printed) = match &pat.v {
+        Pat::Unit => (PAT_ATOM, "()".to_string()),
+        Pat::Nil => (PAT_ATOM, "[]".to_string()),
+        Pat::Constant(val) if val.sign() == Sign::Minus =>
+            panic!("pattern {} has no source syntax", val),
+        Pat::Constant(val) => (PAT_ATOM, val.to_string()),
+        Pat::Variable(var) => (PAT_ATOM, print_variable(var)),
+        Pat::As(pat, var) =>
+            (PAT_AS, format!("{} as {}", print_pattern(pat, PAT_AS), print_variable(var))),
+        Pat::Product(pat1, pat2) => (PAT_PRODUCT, format!(
+            "{}, {}", print_pattern(pat1, PAT_CONS), print_pattern(pat2, PAT_PRODUCT),
+        )),
+        Pat::Cons(pat1, pat2) => (PAT_CONS, format!(
+            "{}: {}", print_pattern(pat1, PAT_ATOM), print_pattern(pat2, PAT_CONS),
+        )),
+    };
+    if own < level { format!("({})", printed) } else { printed }
+}
+
+/* Check whether the given expression is the given intrinsic applied to the
+ * given number of arguments, and if so get those arguments. */
+fn intrinsic_arguments<'a>(expr: &'a TExpr, name: &str, arity: usize) -> Option<Vec<&'a TExpr>> {
+    let mut args = vec![];
+    let mut expr = expr;
+    while let Expr::Application(expr1, expr2) = &expr.v {
+        args.push(expr2.as_ref());
+        expr = expr1;
+    }
+    match &expr.v {
+        Expr::Variable(Variable { name: Some(intrinsic), .. })
+            if intrinsic == name && args.len() == arity => {
+            args.reverse();
+            Some(args)
+        },
+        _ => None,
+    }
+}
+
+/* Print a local definition of the given binding followed by the given
+ * expressions. A local definition that ends the sequence absorbs it anyway,
+ * so it need not be delimited. */
+fn print_local_definition(binding: &LetBinding, exprs: &[TExpr]) -> String {
+    let mut printed = format!("def {}", print_binding(binding));
+    for (idx, expr) in exprs.iter().enumerate() {
+        let last = idx + 1 == exprs.len();
+        let printed_expr = match &expr.v {
+            Expr::LetBinding(binding, body) if last => match &body.v {
+                Expr::Sequence(rest) => print_local_definition(binding, rest),
+                _ => print_expr(expr, EXPR),
+            },
+            _ => print_expr(expr, EXPR),
+        };
+        printed.push_str(&format!("; {}", printed_expr));
+    }
+    printed
+}
+
+/* Print the given expression so that it binds at least as tightly as the
+ * given level requires. */
+fn print_expr(expr: &TExpr, level: u8) -> String {
+    let (own, printed) = match &expr.v {
+        Expr::Unit => (ATOM, "()".to_string()),
+        Expr::Nil => (ATOM, "[]".to_string()),
+        Expr::Constant(val) if val.sign() == Sign::Minus =>
+            (NEGATE, format!("(-{})", val.magnitude())),
+        Expr::Constant(val) => (ATOM, val.to_string()),
+        Expr::Variable(var) if matches!(var.name.as_deref(), Some("++" | "[..]")) =>
+            panic!("intrinsic {} must be fully applied", print_variable(var)),
+        Expr::Variable(var) => (ATOM, print_variable(var)),
+        Expr::Sequence(exprs) => {
+            let exprs: Vec<_> = exprs.iter().map(|expr| print_expr(expr, PRODUCT)).collect();
+            (SEQUENCE, format!("{{{}}}", exprs.join("; ")))
+        },
+        Expr::Product(expr1, expr2) => (PRODUCT, format!(
+            "{}, {}", print_expr(expr1, EQUAL), print_expr(expr2, PRODUCT),
+        )),
+        Expr::Cons(expr1, expr2) => (LIST, format!(
+            "{}: {}", print_expr(expr1, SUM), print_expr(expr2, LIST),
+        )),
+        Expr::Infix(op, expr1, expr2) => {
+            let (own, lhs, rhs) = match op {
+                InfixOp::Equal => (EQUAL, EQUAL, LIST),
+                InfixOp::Add | InfixOp::Subtract => (SUM, SUM, PRODUCT_OP),
+                InfixOp::Exponentiate => (POWER, NEGATE, POWER),
+                _ => (PRODUCT_OP, PRODUCT_OP, POWER),
+            };
+            (own, format!("{} {} {}", print_expr(expr1, lhs), op, print_expr(expr2, rhs)))
+        },
+        Expr::Negate(expr1) => {
+            // Consecutive negations share a single pair of parentheses
+            let mut signs = "-".to_string();
+            let mut operand = expr1;
+            while let Expr::Negate(expr2) = &operand.v {
+                signs.push('-');
+                operand = expr2;
+            }
+            (NEGATE, format!("({}{})", signs, print_expr(operand, APPLICATION)))
+        },
+        Expr::Application(expr1, expr2) => {
+            if let Some(args) = intrinsic_arguments(expr, "++", 2) {
+                (LIST, format!("{} ++ {}", print_expr(args[0], SUM), print_expr(args[1], LIST)))
+            } else if let Some(args) = intrinsic_arguments(expr, "[..]", 3) {
+                (ATOM, format!(
+                    "{}[{}..{}]",
+                    print_expr(args[2], ATOM), print_expr(args[0], EXPR), print_expr(args[1], EXPR),
+                ))
+            } else {
+                (APPLICATION, format!(
+                    "{} {}", print_expr(expr1, APPLICATION), print_expr(expr2, ATOM),
+                ))
+            }
+        },
+        Expr::Function(Function { params, body, .. }) => {
+            let params: Vec<_> = params.iter().map(|pat| print_pattern(pat, PAT_ATOM)).collect();
+            let body = match &body.v {
+                Expr::Sequence(_) => print_expr(body, SEQUENCE),
+                _ => format!("{{{}}}", print_expr(body, PRODUCT)),
+            };
+            (EXPR, format!("fun {} {}", params.join(" "), body))
+        },
+        Expr::LetBinding(binding, body) => match &body.v {
+            // Bindings followed by a sequence always come from definitions,
+            // which must be delimited so as not to absorb what follows them
+            Expr::Sequence(exprs) =>
+                (ATOM, format!("({})", print_local_definition(binding, exprs))),
+            _ => (EXPR, format!("let {} in {}", print_binding(binding), print_expr(body, EXPR))),
+        },
+        Expr::Match(_) => panic!("match expressions have no source syntax"),
+        Expr::Intrinsic(_) => panic!("intrinsics have no source syntax"),
+    };
+    if own < level { format!("({})", printed) } else { printed }
+}
@@ -0,0 +1,117 @@
+#[cfg(feature = "cli")]
+use indicatif::{ProgressBar, ProgressStyle};
+#[cfg(feature = "cli")]
+use log::LevelFilter;
+#[cfg(feature = "cli")]
+use std::cell::RefCell;
+use std::fmt;
+#[cfg(feature = "cli")]
+use std::io::IsTerminal;
+#[cfg(feature = "cli")]
+use std::time::Duration;
+
+/* The coarse phases of proving and verifying that can take minutes on large
+ * circuits, and so are reported as they happen. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    LoadParams,
+    KeygenVk,
+    KeygenPk,
+    PopulateWitnesses,
+    CreateProof,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::LoadParams => write!(f, "loading public parameters"),
+            Phase::KeygenVk => write!(f, "generating verifying key"),
+            Phase::KeygenPk => write!(f, "generating proving key"),
+            Phase::PopulateWitnesses => write!(f, "populating witnesses"),
+            Phase::CreateProof => write!(f, "creating proof"),
+        }
+    }
+}
+
+/* Receives reports of the phases that proving and verifying go through, so
+ * that those embedding vamp-ir can surface progress in their own interfaces.
+ * Phases do not nest, and every phase that starts also finishes. */
+pub trait Progress {
+    /* Called when the given phase starts, with the number of steps it takes
+     * if these are counted. */
+    fn start(&self, phase: Phase, steps: Option<u64>);
+    /* Called each time a counted step of the current phase completes. */
+    fn step(&self) {}
+    /* Called when the given phase finishes. */
+    fn finish(&self, phase: Phase);
+}
+
+/* Ignores all progress. */
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn start(&self, _phase: Phase, _steps: Option<u64>) {}
+    fn finish(&self, _phase: Phase) {}
+}
+
+/* Run the given function as the given uncounted phase. */
+pub fn in_phase<T>(progress: &dyn Progress, phase: Phase, f: impl FnOnce() -> T) -> T {
+    progress.start(phase, None);
+    let result = f();
+    progress.finish(phase);
+    result
+}
+
+/* Draws a spinner for each uncounted phase, and a bar for each counted one,
+ * on standard error. Each is cleared once its phase finishes, leaving the log
+ * lines alone. */
+#[cfg(feature = "cli")]
+pub struct TerminalProgress {
+    // The indicator of the current phase, if any
+    bar: RefCell<Option<ProgressBar>>,
+}
+
+#[cfg(feature = "cli")]
+impl Progress for TerminalProgress {
+    fn start(&self, phase: Phase, steps: Option<u64>) {
+        let bar = match steps {
+            Some(steps) => ProgressBar::new(steps).with_style(
+                ProgressStyle::with_template("{spinner} {msg} [{bar:40}] {pos}/{len}")
+                    .expect("progress template is valid")
+                    .progress_chars("=> "),
+            ),
+            None => ProgressBar::new_spinner().with_style(
+                ProgressStyle::with_template("{spinner} {msg} ({elapsed})")
+                    .expect("progress template is valid"),
+            ),
+        };
+        bar.set_message(phase.to_string());
+        bar.enable_steady_tick(Duration::from_millis(100));
+        *self.bar.borrow_mut() = Some(bar);
+    }
+
+    fn step(&self) {
+        if let Some(bar) = self.bar.borrow().as_ref() {
+            bar.inc(1);
+        }
+    }
+
+    fn finish(&self, _phase: Phase) {
+        if let Some(bar) = self.bar.borrow_mut().take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/* Choose how the command line reports progress: on a terminal, unless quiet
+ * was asked for or standard output is redirected, in which case vamp-ir is
+ * presumably being scripted. */
+#[cfg(feature = "cli")]
+pub fn terminal_progress() -> Box<dyn Progress> {
+    let interactive = std::io::stdout().is_terminal() && std::io::stderr().is_terminal();
+    if interactive && log::max_level() >= LevelFilter::Info {
+        Box::new(TerminalProgress { bar: RefCell::new(None) })
+    } else {
+        Box::new(NoProgress)
+    }
+}
@@ -0,0 +1,464 @@
+use crate::ast::{Expr, InfixOp, Module, TExpr, Variable, VariableId};
+use crate::error::Error;
+use crate::transform::{input_name, ordered_module_variables, required_inputs, FieldOps};
+use crate::typecheck::Type;
+use num_bigint::{BigInt, Sign};
+use num_traits::{One, Zero};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, Write};
+
+/* The number of bytes in which field elements are written, enough for every
+ * field that vamp-ir compiles over. */
+const FIELD_BYTES: usize = 32;
+
+/* A linear combination of wires, by their indices, with coefficients in
+ * canonical form. Wire 0 always holds one, so constants are multiples of
+ * it. */
+pub type LinearCombination = BTreeMap<u32, BigInt>;
+
+/* A constraint that a * b = c, for linear combinations a, b, and c. Linear
+ * constraints leave a and b empty. */
+#[derive(Clone, Debug, Default)]
+pub struct Constraint {
+    pub a: LinearCombination,
+    pub b: LinearCombination,
+    pub c: LinearCombination,
+}
+
+/* A compiled module as a rank-1 constraint system over a prime field, in the
+ * layout of circom: wire 0 holds one, and is followed by the wires of the
+ * public variables, then those of the private inputs, then those of the
+ * variables derived from them. */
+pub struct R1cs {
+    pub prime: BigInt,
+    // Variables on the wires after the first, in order
+    pub wires: Vec<Variable>,
+    // How many of the first wires hold public variables
+    pub public_inputs: u32,
+    // How many of the wires after the public ones hold private inputs
+    pub private_inputs: u32,
+    pub constraints: Vec<Constraint>,
+}
+
+/* Add the given multiple of a linear combination to another, keeping its
+ * coefficients canonical and dropping those that vanish. */
+fn add_scaled(acc: &mut LinearCombination, lc: &LinearCombination, scale: i64, field_ops: &dyn FieldOps) {
+    for (wire, coeff) in lc {
+        let sum = acc.get(wire).cloned().unwrap_or_default() + coeff * scale;
+        let sum = field_ops.canonical(sum);
+        if sum.is_zero() {
+            acc.remove(wire);
+        } else {
+            acc.insert(*wire, sum);
+        }
+    }
+}
+
+/* The linear combination that the given variable or constant stands for. */
+fn atom(expr: &TExpr, wires: &HashMap<VariableId, u32>, field_ops: &dyn FieldOps) -> Option<LinearCombination> {
+    let (wire, coeff) = match &expr.v {
+        Expr::Variable(var) => (*wires.get(&var.id)?, BigInt::from(1)),
+        Expr::Constant(c) => (0, field_ops.canonical(c.clone())),
+        _ => return None,
+    };
+    let mut lc = LinearCombination::new();
+    add_scaled(&mut lc, &LinearCombination::from([(wire, coeff)]), 1, field_ops);
+    Some(lc)
+}
+
+/* The linear combination that sums the given multiples of others. */
+fn linear(terms: &[(&LinearCombination, i64)], field_ops: &dyn FieldOps) -> LinearCombination {
+    let mut acc = LinearCombination::new();
+    for (lc, scale) in terms {
+        add_scaled(&mut acc, lc, *scale, field_ops);
+    }
+    acc
+}
+
+/* Lower the given constraint of a module in three-address form, whose sides
+ * are variables, constants, or a single operation on them, to a rank-1
+ * constraint. Gives nothing for constraints of any other shape. */
+fn lower_constraint(
+    expr: &TExpr,
+    wires: &HashMap<VariableId, u32>,
+    field_ops: &dyn FieldOps,
+) -> Option<Constraint> {
+    let Expr::Infix(InfixOp::Equal, lhs, rhs) = &expr.v else {
+        return None;
+    };
+    let l = atom(lhs, wires, field_ops)?;
+    let constraint = match &rhs.v {
+        Expr::Negate(x) => {
+            let x = atom(x, wires, field_ops)?;
+            Constraint { c: linear(&[(&l, 1), (&x, 1)], field_ops), ..Constraint::default() }
+        },
+        Expr::Infix(InfixOp::Add, x, y) => {
+            let (x, y) = (atom(x, wires, field_ops)?, atom(y, wires, field_ops)?);
+            Constraint { c: linear(&[(&l, 1), (&x, -1), (&y, -1)], field_ops), ..Constraint::default() }
+        },
+        Expr::Infix(InfixOp::Subtract, x, y) => {
+            let (x, y) = (atom(x, wires, field_ops)?, atom(y, wires, field_ops)?);
+            Constraint { c: linear(&[(&l, 1), (&x, -1), (&y, 1)], field_ops), ..Constraint::default() }
+        },
+        Expr::Infix(InfixOp::Multiply, x, y) => {
+            let (x, y) = (atom(x, wires, field_ops)?, atom(y, wires, field_ops)?);
+            Constraint { a: x, b: y, c: l }
+        },
+        // l = x / y holds as l * y = x, as in the gates of the backends
+        Expr::Infix(InfixOp::Divide, x, y) => {
+            let (x, y) = (atom(x, wires, field_ops)?, atom(y, wires, field_ops)?);
+            Constraint { a: l, b: y, c: x }
+        },
+        // l = x | y holds the same way, except that l is zero when y is the
+        // constant zero, whose linear combination is empty
+        Expr::Infix(InfixOp::DivideZ, x, y) => {
+            let (x, y) = (atom(x, wires, field_ops)?, atom(y, wires, field_ops)?);
+            if y.is_empty() {
+                Constraint { c: l, ..Constraint::default() }
+            } else {
+                Constraint { a: l, b: y, c: x }
+            }
+        },
+        _ => {
+            let r = atom(rhs, wires, field_ops)?;
+            Constraint { c: linear(&[(&l, 1), (&r, -1)], field_ops), ..Constraint::default() }
+        },
+    };
+    Some(constraint)
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_u64<W: Write>(writer: &mut W, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+/* Write the given canonical field element in little-endian order. */
+fn write_element<W: Write>(writer: &mut W, value: &BigInt) -> io::Result<()> {
+    let (_, mut bytes) = value.to_bytes_le();
+    bytes.resize(FIELD_BYTES, 0);
+    writer.write_all(&bytes)
+}
+
+/* Write a file of the binary formats of iden3, made of the given sections,
+ * each with its type. */
+fn write_sections<W: Write>(
+    mut writer: W,
+    magic: &[u8; 4],
+    version: u32,
+    sections: &[(u32, Vec<u8>)],
+) -> io::Result<()> {
+    writer.write_all(magic)?;
+    write_u32(&mut writer, version)?;
+    write_u32(&mut writer, sections.len() as u32)?;
+    for (kind, contents) in sections {
+        write_u32(&mut writer, *kind)?;
+        write_u64(&mut writer, contents.len() as u64)?;
+        writer.write_all(contents)?;
+    }
+    Ok(())
+}
+
+/* A cursor over the bytes of a file of the binary formats of iden3. Running
+ * out of bytes means that the file is malformed. */
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.bytes.len() < len {
+            return Err(Error::Artifact("the file ends early".to_string()));
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("four bytes were taken")))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("eight bytes were taken")))
+    }
+
+    /* Read a field element written in the given number of bytes in
+     * little-endian order. */
+    fn element(&mut self, size: usize) -> Result<BigInt, Error> {
+        Ok(BigInt::from_bytes_le(Sign::Plus, self.take(size)?))
+    }
+}
+
+/* The sections of a file of the binary formats of iden3, with their types,
+ * after checking its magic number and version. */
+fn read_sections<'a>(bytes: &'a [u8], magic: &[u8; 4], version: u32) -> Result<Vec<(u32, &'a [u8])>, Error> {
+    let mut reader = Reader { bytes };
+    if reader.take(4).ok() != Some(magic.as_slice()) {
+        return Err(Error::Artifact(format!(
+            "not a .{} file", String::from_utf8_lossy(magic),
+        )));
+    }
+    let found = reader.u32()?;
+    if found != version {
+        return Err(Error::Artifact(format!(
+            "version {} of the format is not supported, only version {}", found, version,
+        )));
+    }
+    let count = reader.u32()?;
+    let mut sections = vec![];
+    for _ in 0..count {
+        let kind = reader.u32()?;
+        let len = usize::try_from(reader.u64()?).unwrap_or(usize::MAX);
+        sections.push((kind, reader.take(len)?));
+    }
+    Ok(sections)
+}
+
+/* The one section of the given type among the given sections. */
+fn read_section<'a>(sections: &[(u32, &'a [u8])], kind: u32) -> Result<Reader<'a>, Error> {
+    let mut matching = sections.iter().filter(|(k, _)| *k == kind);
+    match (matching.next(), matching.next()) {
+        (Some((_, bytes)), None) => Ok(Reader { bytes: *bytes }),
+        (None, _) => Err(Error::Artifact(format!("the file has no section of type {}", kind))),
+        (Some(_), Some(_)) => Err(Error::Artifact(format!("the file has several sections of type {}", kind))),
+    }
+}
+
+fn int_expr(expr: Expr) -> TExpr {
+    expr.type_expr(Some(Type::Int))
+}
+
+fn infix_expr(op: InfixOp, lhs: TExpr, rhs: TExpr) -> TExpr {
+    int_expr(Expr::Infix(op, Box::new(lhs), Box::new(rhs)))
+}
+
+impl R1cs {
+    /* Lower the given module, compiled to three-address form over the field
+     * of the given operations, to a rank-1 constraint system. Every
+     * three-address constraint is already of rank 1; any other is refused,
+     * naming each constraint at fault. */
+    pub fn new(module: &Module, field_ops: &dyn FieldOps) -> Result<Self, Error> {
+        let prime = field_ops.canonical(BigInt::from(-1)) + 1;
+        let mut seen = HashSet::new();
+        let mut wires = vec![];
+        for var in &module.pubs {
+            if seen.insert(var.id) {
+                wires.push(var.clone());
+            }
+        }
+        let public_inputs = wires.len() as u32;
+        for var in required_inputs(module) {
+            if seen.insert(var.id) {
+                wires.push(var);
+            }
+        }
+        let private_inputs = wires.len() as u32 - public_inputs;
+        for var in ordered_module_variables(module) {
+            if seen.insert(var.id) {
+                wires.push(var);
+            }
+        }
+        let indices: HashMap<_, _> = wires.iter().enumerate()
+            .map(|(index, var)| (var.id, index as u32 + 1))
+            .collect();
+        let mut constraints = vec![];
+        let mut unsupported = vec![];
+        for expr in &module.exprs {
+            match lower_constraint(expr, &indices, field_ops) {
+                Some(constraint) => constraints.push(constraint),
+                None => unsupported.push(expr.to_string()),
+            }
+        }
+        if !unsupported.is_empty() {
+            return Err(Error::Compile(format!(
+                "{} constraint(s) cannot be lowered to rank-1 constraints: {}",
+                unsupported.len(), unsupported.join("; "),
+            )));
+        }
+        Ok(Self { prime, wires, public_inputs, private_inputs, constraints })
+    }
+
+    /* The number of wires, counting the one that holds one. */
+    pub fn wire_count(&self) -> u32 {
+        self.wires.len() as u32 + 1
+    }
+
+    /* The values of the wires, in order, given the values of every variable
+     * of the module. */
+    pub fn witness(&self, assigns: &HashMap<VariableId, BigInt>, field_ops: &dyn FieldOps) -> Result<Vec<BigInt>, Error> {
+        let mut witness = vec![BigInt::from(1)];
+        for var in &self.wires {
+            let value = assigns.get(&var.id)
+                .ok_or_else(|| Error::Inputs(format!("no value was derived for {}", input_name(var))))?;
+            witness.push(field_ops.canonical(value.clone()));
+        }
+        Ok(witness)
+    }
+
+    /* Write this system in the .r1cs format of circom, with each wire
+     * labeled by its index. */
+    pub fn write_r1cs<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut header = vec![];
+        write_u32(&mut header, FIELD_BYTES as u32)?;
+        write_element(&mut header, &self.prime)?;
+        write_u32(&mut header, self.wire_count())?;
+        // Public outputs, which vamp-ir does not tell apart from inputs
+        write_u32(&mut header, 0)?;
+        write_u32(&mut header, self.public_inputs)?;
+        write_u32(&mut header, self.private_inputs)?;
+        write_u64(&mut header, self.wire_count() as u64)?;
+        write_u32(&mut header, self.constraints.len() as u32)?;
+        let mut constraints = vec![];
+        for constraint in &self.constraints {
+            for lc in [&constraint.a, &constraint.b, &constraint.c] {
+                write_u32(&mut constraints, lc.len() as u32)?;
+                for (wire, coeff) in lc {
+                    write_u32(&mut constraints, *wire)?;
+                    write_element(&mut constraints, coeff)?;
+                }
+            }
+        }
+        let mut labels = vec![];
+        for wire in 0..self.wire_count() {
+            write_u64(&mut labels, wire as u64)?;
+        }
+        write_sections(writer, b"r1cs", 1, &[(1, header), (2, constraints), (3, labels)])
+    }
+
+    /* Write the given values of the wires of this system in the .wtns format
+     * of snarkjs. */
+    pub fn write_wtns<W: Write>(&self, witness: &[BigInt], writer: W) -> io::Result<()> {
+        let mut header = vec![];
+        write_u32(&mut header, FIELD_BYTES as u32)?;
+        write_element(&mut header, &self.prime)?;
+        write_u32(&mut header, witness.len() as u32)?;
+        let mut values = vec![];
+        for value in witness {
+            write_element(&mut values, value)?;
+        }
+        write_sections(writer, b"wtns", 2, &[(1, header), (2, values)])
+    }
+
+    /* Read a rank-1 constraint system in the .r1cs format of circom, as
+     * written by circom itself or by write_r1cs. Wire k becomes a variable
+     * named w_k, and public outputs are counted as public inputs, since
+     * vamp-ir does not tell them apart. */
+    pub fn read_r1cs(bytes: &[u8]) -> Result<Self, Error> {
+        let sections = read_sections(bytes, b"r1cs", 1)?;
+        let mut header = read_section(&sections, 1)?;
+        let size = header.u32()? as usize;
+        let prime = header.element(size)?;
+        if prime <= BigInt::one() {
+            return Err(Error::Artifact(format!("{} is not the prime of a field", prime)));
+        }
+        let wire_count = header.u32()?;
+        let public_outputs = header.u32()?;
+        let public_inputs = header.u32()?;
+        let private_inputs = header.u32()?;
+        let _label_count = header.u64()?;
+        let constraint_count = header.u32()?;
+        let io_wires = 1 + public_outputs as u64 + public_inputs as u64 + private_inputs as u64;
+        if io_wires > wire_count as u64 {
+            return Err(Error::Artifact(format!(
+                "the file has {} wire(s), too few for its {} public and {} private input(s)",
+                wire_count, public_outputs as u64 + public_inputs as u64, private_inputs,
+            )));
+        }
+        let mut body = read_section(&sections, 2)?;
+        let mut constraints = vec![];
+        for _ in 0..constraint_count {
+            let mut lcs = [LinearCombination::new(), LinearCombination::new(), LinearCombination::new()];
+            for lc in &mut lcs {
+                for _ in 0..body.u32()? {
+                    let wire = body.u32()?;
+                    if wire >= wire_count {
+                        return Err(Error::Artifact(format!(
+                            "constraint {} refers to wire {}, but there are only {}",
+                            constraints.len(), wire, wire_count,
+                        )));
+                    }
+                    // Terms of the same wire are summed
+                    let coeff = (lc.remove(&wire).unwrap_or_default() + body.element(size)?) % &prime;
+                    if !coeff.is_zero() {
+                        lc.insert(wire, coeff);
+                    }
+                }
+            }
+            let [a, b, c] = lcs;
+            constraints.push(Constraint { a, b, c });
+        }
+        let wires = (1..wire_count)
+            .map(|wire| Variable { id: wire, name: Some(format!("w_{}", wire)) })
+            .collect();
+        Ok(Self {
+            prime,
+            wires,
+            public_inputs: public_outputs + public_inputs,
+            private_inputs,
+            constraints,
+        })
+    }
+
+    /* The given linear combination of the wires of this system as an
+     * expression. Coefficients past half the prime are written as the
+     * subtraction of their negation, so that -1 is not written as p - 1. */
+    fn linear_expr(&self, lc: &LinearCombination) -> TExpr {
+        let half = &self.prime / 2;
+        let mut sum: Option<TExpr> = None;
+        for (wire, coeff) in lc {
+            let (negative, magnitude) = if coeff > &half {
+                (true, &self.prime - coeff)
+            } else {
+                (false, coeff.clone())
+            };
+            let term = match wire {
+                0 => int_expr(Expr::Constant(magnitude)),
+                wire => {
+                    let var = int_expr(Expr::Variable(self.wires[*wire as usize - 1].clone()));
+                    if magnitude.is_one() {
+                        var
+                    } else {
+                        infix_expr(InfixOp::Multiply, int_expr(Expr::Constant(magnitude)), var)
+                    }
+                },
+            };
+            sum = Some(match (sum, negative) {
+                (None, false) => term,
+                (None, true) => int_expr(Expr::Negate(Box::new(term))),
+                (Some(acc), false) => infix_expr(InfixOp::Add, acc, term),
+                (Some(acc), true) => infix_expr(InfixOp::Subtract, acc, term),
+            });
+        }
+        sum.unwrap_or_else(|| int_expr(Expr::Constant(BigInt::zero())))
+    }
+
+    /* This system as a module whose public variables are those of the
+     * public wires, in order, and whose constraints are its rows: a * b = c,
+     * or c = 0 when a or b is empty. The wire holding one becomes the
+     * constant 1. */
+    pub fn to_module(&self) -> Module {
+        let pubs = self.wires[..self.public_inputs as usize].to_vec();
+        let exprs = self.constraints.iter().map(|constraint| {
+            if constraint.a.is_empty() || constraint.b.is_empty() {
+                infix_expr(
+                    InfixOp::Equal,
+                    self.linear_expr(&constraint.c),
+                    int_expr(Expr::Constant(BigInt::zero())),
+                )
+            } else {
+                infix_expr(
+                    InfixOp::Equal,
+                    infix_expr(InfixOp::Multiply, self.linear_expr(&constraint.a), self.linear_expr(&constraint.b)),
+                    self.linear_expr(&constraint.c),
+                )
+            }
+        }).collect();
+        let names = self.wires.iter()
+            .filter_map(|var| Some((var.id, var.name.clone()?)))
+            .collect();
+        Module { pubs, defs: vec![], exprs, names, ..Module::default() }
+    }
+}
@@ -0,0 +1,129 @@
+use crate::diagnostics::{Diagnostic, Severity};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/* Whether diagnostics are rendered with colors, as decided once at start. */
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+/* Decide whether diagnostics are colored: never when asked not to by the given
+ * flag or by NO_COLOR, always when CLICOLOR_FORCE asks for it, and otherwise
+ * only when standard error is a terminal. Variables that are empty, 0, or
+ * false do not count. */
+pub fn init(no_color: bool) {
+    let env_set = |name| match std::env::var(name) {
+        Ok(value) => !matches!(value.trim().to_lowercase().as_str(), "" | "0" | "false"),
+        Err(_) => false,
+    };
+    let color = if no_color || env_set("NO_COLOR") {
+        false
+    } else {
+        env_set("CLICOLOR_FORCE") || std::io::stderr().is_terminal()
+    };
+    COLOR.store(color, Ordering::Relaxed);
+}
+
+/* The ways in which parts of a diagnostic are highlighted. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Style {
+    Error,
+    Warning,
+    // Labels and the message of a diagnostic
+    Emphasis,
+    // Line numbers and the rules beside the source
+    Gutter,
+}
+
+impl Style {
+    /* The ANSI select graphic rendition parameters of this style. */
+    fn code(&self) -> &'static str {
+        match self {
+            Style::Error => "1;31",
+            Style::Warning => "1;33",
+            Style::Emphasis => "1",
+            Style::Gutter => "1;34",
+        }
+    }
+
+    fn of(severity: Severity) -> Self {
+        match severity {
+            Severity::Error => Style::Error,
+            Severity::Warning => Style::Warning,
+        }
+    }
+}
+
+/* Renders diagnostics for people in the manner of rustc: a headline giving
+ * the severity, code, and message, then the offending source line with the
+ * region underlined, then any notes and suggestion. */
+#[derive(Clone, Copy, Debug)]
+pub struct Renderer {
+    color: bool,
+}
+
+impl Renderer {
+    pub fn new(color: bool) -> Self {
+        Self { color }
+    }
+
+    /* The renderer chosen by init. */
+    pub fn current() -> Self {
+        Self::new(COLOR.load(Ordering::Relaxed))
+    }
+
+    /* Highlight the given text in the given style, if colors are enabled. */
+    pub fn paint(&self, style: Style, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /* Render the given diagnostic as lines without a final newline. */
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let style = Style::of(diagnostic.severity);
+        let headline = format!("{}[{}]", diagnostic.severity.name(), diagnostic.code);
+        let mut lines = vec![format!(
+            "{}{}",
+            self.paint(style, &headline),
+            self.paint(Style::Emphasis, &format!(": {}", diagnostic.message)),
+        )];
+        // The gutter is as wide as the number of the line shown
+        let width = diagnostic.span.as_ref().map_or(0, |span| span.start.0.to_string().len());
+        let gutter = " ".repeat(width);
+        let bar = self.paint(Style::Gutter, "|");
+        if let Some(span) = &diagnostic.span {
+            lines.push(format!("{}{} {}", gutter, self.paint(Style::Gutter, "-->"), span));
+            if let Some(text) = &span.line {
+                lines.push(format!("{} {}", gutter, bar));
+                lines.push(format!("{} {} {}", self.paint(Style::Gutter, &span.start.0.to_string()), bar, text));
+                lines.push(format!("{} {} {}", gutter, bar, self.underline(span.start, span.end, text, style)));
+            }
+        }
+        let labelled = diagnostic.notes.iter().map(|note| ("note", note))
+            .chain(diagnostic.suggestion.iter().map(|suggestion| ("suggestion", suggestion)));
+        for (label, text) in labelled {
+            lines.push(format!(
+                "{} {} {}: {}",
+                gutter, self.paint(Style::Gutter, "="), self.paint(Style::Emphasis, label), text,
+            ));
+        }
+        lines.join("\n")
+    }
+
+    /* Underline the region between the given line and column pairs within the
+     * given line of source, which the region starts on. Regions that go on to
+     * later lines are underlined to the end of this one, and empty regions
+     * are marked by a single caret. Tabs are kept so that the carets line up
+     * however the tabs are displayed. */
+    fn underline(&self, start: (usize, usize), end: (usize, usize), text: &str, style: Style) -> String {
+        let chars: Vec<_> = text.chars().collect();
+        let first = start.1.saturating_sub(1).min(chars.len());
+        let last = if end.0 == start.0 { end.1.saturating_sub(1) } else { chars.len() };
+        let indent: String = chars[..first].iter()
+            .map(|c| if *c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(last.saturating_sub(first).max(1));
+        format!("{}{}", indent, self.paint(style, &carets))
+    }
+}
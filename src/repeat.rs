@@ -0,0 +1,42 @@
+use crate::bench::median;
+use crate::status::Outcome;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/* Where each of the given number of repeated proofs is written, if at all:
+ * every one into the given directory, named by its index counted from 1 and
+ * padded so that the names sort in order, or else only the last to the given
+ * output. */
+pub fn proof_targets(output: Option<&Path>, output_dir: Option<&Path>, repeat: usize) -> Vec<Option<PathBuf>> {
+    let width = repeat.to_string().len();
+    (0..repeat)
+        .map(|index| match output_dir {
+            Some(dir) => Some(dir.join(format!("proof-{:0width$}", index + 1, width = width))),
+            None => (index + 1 == repeat).then(|| {
+                output.expect("proofs go to an output or an output directory").to_path_buf()
+            }),
+        })
+        .collect()
+}
+
+/* Print the least, median, and greatest of the given times taken to prove to
+ * the given outcome and record them there, unless only one proof was made. */
+pub fn report_proving_times(outcome: &mut Outcome, times: &[Duration]) {
+    if times.len() < 2 {
+        return;
+    }
+    let millis = |time: Duration| time.as_secs_f64() * 1000.0;
+    let min = times.iter().min().copied().unwrap_or_default();
+    let max = times.iter().max().copied().unwrap_or_default();
+    outcome.println(format!(
+        "* Proved {} times: min {:.1} ms, median {:.1} ms, max {:.1} ms",
+        times.len(), millis(min), millis(median(times)), millis(max),
+    ));
+    outcome.detail("prove_ms", json!({
+        "runs": times.len(),
+        "min": millis(min),
+        "median": millis(median(times)),
+        "max": millis(max),
+    }));
+}
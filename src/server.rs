@@ -0,0 +1,588 @@
+use crate::artifact::secure_digest;
+use crate::ast::{parse_prefixed_num, Module};
+use crate::error::Error;
+use crate::halo2::circuit::{Halo2Circuit, Halo2Proof};
+use crate::halo2::synth::{Halo2CostModel, PrimeFieldOps};
+use crate::key_cache::KeyCache;
+use crate::transform::CompileOptions;
+use halo2_proofs::pasta::Fp;
+use num_bigint::BigInt;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response};
+
+/* The backend whose circuits the server compiles and proves, as the cache
+ * names it. */
+const BACKEND: &str = "halo2";
+
+/* The name under which compiled circuits are cached. */
+const CIRCUIT: &str = "circuit";
+
+/* Limits and locations of a proving service, built up as CompileOptions are.
+ * Requests beyond the limits are refused rather than queued without end. */
+pub struct ServerConfig {
+    // Directory of the key cache in which compiled circuits are kept
+    cache_dir: Option<PathBuf>,
+    // Threads that answer requests
+    request_threads: usize,
+    // Jobs, whether proofs or key generation, that are run at once
+    proof_workers: usize,
+    // Jobs that may wait for a worker before submissions are refused
+    max_queued_proofs: usize,
+    // Largest body, in bytes, of any request
+    max_body_bytes: usize,
+    // How long the outcome of a finished job is kept for it to be polled
+    job_ttl: Duration,
+    // Finished jobs whose outcomes are kept before the oldest are dropped
+    max_finished_jobs: usize,
+    // How long a circuit whose keys have been generated is kept in memory
+    // since it was last used
+    circuit_ttl: Duration,
+    // Circuits kept in memory before the least recently used are dropped
+    max_loaded_circuits: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            cache_dir: None,
+            request_threads: 4,
+            proof_workers: 1,
+            max_queued_proofs: 16,
+            max_body_bytes: 1 << 20,
+            job_ttl: Duration::from_secs(60 * 60),
+            max_finished_jobs: 256,
+            circuit_ttl: Duration::from_secs(60 * 60),
+            max_loaded_circuits: 16,
+        }
+    }
+}
+
+impl ServerConfig {
+    /* Keep circuits in the key cache in the given directory rather than in
+     * the user's cache directory. */
+    pub fn cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(cache_dir.into());
+        self
+    }
+
+    pub fn request_threads(mut self, request_threads: usize) -> Self {
+        self.request_threads = request_threads.max(1);
+        self
+    }
+
+    pub fn proof_workers(mut self, proof_workers: usize) -> Self {
+        self.proof_workers = proof_workers.max(1);
+        self
+    }
+
+    pub fn max_queued_proofs(mut self, max_queued_proofs: usize) -> Self {
+        self.max_queued_proofs = max_queued_proofs;
+        self
+    }
+
+    pub fn max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    /* Forget the outcome of a job once the given time has passed since it
+     * finished, whether or not it has been polled. */
+    pub fn job_ttl(mut self, job_ttl: Duration) -> Self {
+        self.job_ttl = job_ttl;
+        self
+    }
+
+    /* Keep the outcomes of at most the given number of finished jobs,
+     * forgetting the oldest first. */
+    pub fn max_finished_jobs(mut self, max_finished_jobs: usize) -> Self {
+        self.max_finished_jobs = max_finished_jobs;
+        self
+    }
+
+    /* Drop a circuit from memory once the given time has passed since it was
+     * last used. It is read from the cache again, and its keys generated
+     * again, when next asked for. */
+    pub fn circuit_ttl(mut self, circuit_ttl: Duration) -> Self {
+        self.circuit_ttl = circuit_ttl;
+        self
+    }
+
+    /* Keep at most the given number of circuits in memory, dropping the least
+     * recently used first. */
+    pub fn max_loaded_circuits(mut self, max_loaded_circuits: usize) -> Self {
+        self.max_loaded_circuits = max_loaded_circuits;
+        self
+    }
+}
+
+/* Where a submitted job has got to. */
+#[derive(Clone)]
+enum JobStatus {
+    Queued,
+    Running,
+    // The fields that the job gave, such as its proof
+    Done(Value),
+    Failed(String),
+}
+
+/* The statuses of submitted jobs. Jobs that have finished are also
+ * listed in the order that they finished, so that the oldest outcomes can be
+ * dropped and the statuses do not grow without end. */
+#[derive(Default)]
+struct Jobs {
+    statuses: HashMap<u64, JobStatus>,
+    finished: VecDeque<(u64, Instant)>,
+}
+
+/* What a job does once a worker takes it. */
+enum Work {
+    // Prove knowledge of the given inputs of the circuit
+    Prove { circuit: Arc<Halo2Circuit>, inputs: HashMap<String, BigInt> },
+    // Generate the keys of the given compiled module and cache its circuit
+    // under the given id
+    Compile { circuit: String, module: Module },
+}
+
+/* A job waiting for a worker. */
+struct Job {
+    id: u64,
+    work: Work,
+}
+
+/* What the threads of a server share. */
+struct State {
+    cache: KeyCache,
+    max_body_bytes: usize,
+    // Circuits whose keys have been generated, by their ids, with when each
+    // was last used
+    circuits: Mutex<HashMap<String, (Arc<Halo2Circuit>, Instant)>>,
+    circuit_ttl: Duration,
+    max_loaded_circuits: usize,
+    jobs: Mutex<Jobs>,
+    job_ttl: Duration,
+    max_finished_jobs: usize,
+    next_job: AtomicU64,
+    // Taken on shutdown, so that the workers find the queue closed
+    queue: Mutex<Option<SyncSender<Job>>>,
+    stopping: AtomicBool,
+}
+
+/* A proving service answering HTTP requests on threads of its own, which
+ * compiles programs to Halo2 circuits, proves and verifies knowledge of their
+ * inputs, and lists the circuits that it has compiled:
+ *
+ * POST /compile {"source": ...} gives {"circuit": id, "interface": {...}, "job": id}
+ * POST /prove {"circuit": id, "inputs": {...}} gives {"job": id}
+ * GET /jobs/<id> gives {"status": ..., "proof": ..., "circuit": ..., "error": ...}
+ * POST /verify {"circuit": id, "proof": ...} gives {"valid": ..., "error": ...}
+ * GET /circuits gives {"circuits": [{"circuit": id, "bytes": size}, ...]}
+ * GET /circuits/<id>/interface gives the interface of the circuit
+ *
+ * Proofs and the keys of new circuits can take minutes, so they are made in
+ * the background, and their jobs are polled until they are done. Circuits
+ * that are compiled already are given back without a job. */
+pub struct Server {
+    http: Arc<tiny_http::Server>,
+    state: Arc<State>,
+    threads: Vec<JoinHandle<()>>,
+    request_threads: usize,
+}
+
+impl Server {
+    /* Listen on the given address, which may have port 0 to have one chosen,
+     * and start answering requests. */
+    pub fn bind(addr: &str, config: ServerConfig) -> Result<Self, String> {
+        let cache = KeyCache::open(config.cache_dir.as_deref())
+            .ok_or_else(|| "no cache directory can be determined; give one".to_string())?;
+        let http = Arc::new(tiny_http::Server::http(addr)
+            .map_err(|err| format!("cannot listen on {}: {}", addr, err))?);
+        let (queue, jobs) = sync_channel(config.max_queued_proofs);
+        let state = Arc::new(State {
+            cache,
+            max_body_bytes: config.max_body_bytes,
+            circuits: Mutex::new(HashMap::new()),
+            circuit_ttl: config.circuit_ttl,
+            max_loaded_circuits: config.max_loaded_circuits,
+            jobs: Mutex::new(Jobs::default()),
+            job_ttl: config.job_ttl,
+            max_finished_jobs: config.max_finished_jobs,
+            next_job: AtomicU64::new(1),
+            queue: Mutex::new(Some(queue)),
+            stopping: AtomicBool::new(false),
+        });
+        let mut threads = vec![];
+        let jobs = Arc::new(Mutex::new(jobs));
+        for _ in 0..config.proof_workers {
+            let (state, jobs) = (state.clone(), jobs.clone());
+            threads.push(std::thread::spawn(move || work(&state, &jobs)));
+        }
+        for _ in 0..config.request_threads {
+            let (state, http) = (state.clone(), http.clone());
+            threads.push(std::thread::spawn(move || {
+                while let Ok(request) = http.recv() {
+                    answer(&state, request);
+                }
+            }));
+        }
+        Ok(Self { http, state, threads, request_threads: config.request_threads })
+    }
+
+    /* The address that the server listens on. */
+    pub fn addr(&self) -> SocketAddr {
+        self.http.server_addr().to_ip().expect("servers listen on IP addresses")
+    }
+
+    /* Answer requests until the process ends. */
+    pub fn wait(self) {
+        for thread in self.threads {
+            let _ = thread.join();
+        }
+    }
+
+    /* Stop answering requests, and return once every thread has ended.
+     * Queued proofs are dropped, whereas proofs under way are finished. */
+    pub fn shutdown(self) {
+        self.state.stopping.store(true, Ordering::Relaxed);
+        // Workers waiting for jobs find the queue closed once its sender is
+        // gone
+        self.state.queue.lock().expect("job queue is not poisoned").take();
+        // Each unblocking wakes a single thread
+        for _ in 0..self.request_threads {
+            self.http.unblock();
+        }
+        for thread in self.threads {
+            let _ = thread.join();
+        }
+    }
+}
+
+/* Run jobs as they are taken from the given queue, until the server is
+ * gone. */
+fn work(state: &State, jobs: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = match jobs.lock().expect("job queue is not poisoned").recv() {
+            Ok(job) => job,
+            Err(_) => return,
+        };
+        if state.stopping.load(Ordering::Relaxed) {
+            return;
+        }
+        set_status(state, job.id, JobStatus::Running);
+        let outcome = match job.work {
+            Work::Prove { circuit, inputs } => circuit.prove(&inputs)
+                .and_then(|proof| proof.to_bytes())
+                .map(|proof| json!({ "proof": hex::encode(proof) })),
+            Work::Compile { circuit, module } => generate(state, &circuit, module)
+                .map(|()| json!({ "circuit": circuit })),
+        };
+        let status = match outcome {
+            Ok(outcome) => JobStatus::Done(outcome),
+            Err(err) => JobStatus::Failed(err.to_string()),
+        };
+        set_status(state, job.id, status);
+    }
+}
+
+/* Generate the keys of the circuit of the given compiled module, caching the
+ * circuit under the given id and keeping it in memory. */
+fn generate(state: &State, id: &str, module: Module) -> Result<(), Error> {
+    let circuit = Halo2Circuit::new(module)?;
+    let bytes = circuit.to_bytes()?;
+    state.cache.store(BACKEND, id, CIRCUIT, &bytes).map_err(Error::Backend)?;
+    keep(state, id, Arc::new(circuit));
+    Ok(())
+}
+
+/* Queue the given work as a job, giving its id, unless the queue is full or
+ * closed. */
+fn submit(state: &State, work: Work) -> Result<u64, Reply> {
+    let id = state.next_job.fetch_add(1, Ordering::Relaxed);
+    set_status(state, id, JobStatus::Queued);
+    let queue = state.queue.lock().expect("job queue is not poisoned");
+    let sent = match queue.as_ref() {
+        Some(queue) => queue.try_send(Job { id, work }),
+        None => Err(TrySendError::Disconnected(Job { id, work })),
+    };
+    drop(queue);
+    match sent {
+        Ok(()) => Ok(id),
+        Err(err) => {
+            state.jobs.lock().expect("jobs are not poisoned").statuses.remove(&id);
+            Err(match err {
+                TrySendError::Full(_) => error(503, "too many jobs are queued; try again later"),
+                TrySendError::Disconnected(_) => error(503, "the server is shutting down"),
+            })
+        },
+    }
+}
+
+fn set_status(state: &State, job: u64, status: JobStatus) {
+    let mut jobs = state.jobs.lock().expect("jobs are not poisoned");
+    if matches!(status, JobStatus::Done(_) | JobStatus::Failed(_)) {
+        jobs.finished.push_back((job, Instant::now()));
+    }
+    jobs.statuses.insert(job, status);
+    expire_jobs(state, &mut jobs);
+}
+
+/* Drop the outcomes of jobs that finished too long ago, and then the oldest
+ * outcomes beyond those that are kept. */
+fn expire_jobs(state: &State, jobs: &mut Jobs) {
+    while let Some(&(job, finished)) = jobs.finished.front() {
+        if finished.elapsed() <= state.job_ttl && jobs.finished.len() <= state.max_finished_jobs {
+            break;
+        }
+        jobs.finished.pop_front();
+        jobs.statuses.remove(&job);
+    }
+}
+
+/* A response with the given status and JSON body. */
+type Reply = (u16, Value);
+
+fn error(status: u16, message: impl ToString) -> Reply {
+    (status, json!({ "error": message.to_string() }))
+}
+
+/* Route the given request to its handler and send back its reply. */
+fn answer(state: &State, mut request: Request) {
+    let (status, body) = match read_body(state, &mut request) {
+        Ok(body) => {
+            let url = request.url().to_string();
+            let path = url.split('?').next().unwrap_or_default();
+            match (request.method(), path) {
+                (Method::Post, "/compile") => compile(state, &body),
+                (Method::Post, "/prove") => prove(state, &body),
+                (Method::Post, "/verify") => verify(state, &body),
+                (Method::Get, "/circuits") => circuits(state),
+                (Method::Get, path) if interface_of(path).is_some() =>
+                    interface(state, interface_of(path).unwrap_or_default()),
+                (Method::Get, path) if path.starts_with("/jobs/") => job(state, &path["/jobs/".len()..]),
+                _ => error(404, format!("no such endpoint: {} {}", request.method(), path)),
+            }
+        },
+        Err(reply) => reply,
+    };
+    let content_type = Header::from_bytes("Content-Type", "application/json")
+        .expect("content type is a valid header");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    let _ = request.respond(response);
+}
+
+/* The body of the given request as JSON, if it has one. Bodies larger than
+ * allowed are refused without being read in full. */
+fn read_body(state: &State, request: &mut Request) -> Result<Value, Reply> {
+    let too_large = || error(413, format!("request body exceeds {} bytes", state.max_body_bytes));
+    if request.body_length().is_some_and(|length| length > state.max_body_bytes) {
+        return Err(too_large());
+    }
+    let mut body = vec![];
+    request.as_reader()
+        .take(state.max_body_bytes as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| error(400, format!("cannot read request body: {}", err)))?;
+    if body.len() > state.max_body_bytes {
+        return Err(too_large());
+    }
+    if body.is_empty() {
+        return Ok(Value::Null);
+    }
+    serde_json::from_slice(&body).map_err(|err| error(400, format!("request body is not JSON: {}", err)))
+}
+
+/* The string field of the given name of a request body. */
+fn field<'a>(body: &'a Value, name: &str) -> Result<&'a str, Reply> {
+    body.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| error(400, format!("request body has no string {}", name)))
+}
+
+/* The HTTP status that reports the given failure of the library. */
+fn status_of(err: &Error) -> u16 {
+    match err {
+        Error::Backend(_) => 500,
+        _ => 422,
+    }
+}
+
+/* Keep the given circuit in memory under the given id, unless another
+ * request kept one under it meanwhile, giving whichever is kept. */
+fn keep(state: &State, id: &str, circuit: Arc<Halo2Circuit>) -> Arc<Halo2Circuit> {
+    let mut circuits = state.circuits.lock().expect("circuits are not poisoned");
+    let (kept, used) = circuits.entry(id.to_string()).or_insert((circuit, Instant::now()));
+    *used = Instant::now();
+    let kept = kept.clone();
+    expire_circuits(state, &mut circuits);
+    kept
+}
+
+/* Drop the circuits that were last used too long ago, and then the least
+ * recently used beyond those that are kept. */
+fn expire_circuits(state: &State, circuits: &mut HashMap<String, (Arc<Halo2Circuit>, Instant)>) {
+    circuits.retain(|_, (_, used)| used.elapsed() <= state.circuit_ttl);
+    while circuits.len() > state.max_loaded_circuits {
+        let oldest = circuits.iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(id, _)| id.clone());
+        match oldest {
+            Some(id) => circuits.remove(&id),
+            None => break,
+        };
+    }
+}
+
+/* The circuit of the given id, with its keys generated whenever it is not
+ * kept in memory. */
+fn circuit(state: &State, id: &str) -> Result<Arc<Halo2Circuit>, Reply> {
+    {
+        let mut circuits = state.circuits.lock().expect("circuits are not poisoned");
+        expire_circuits(state, &mut circuits);
+        if let Some((circuit, used)) = circuits.get_mut(id) {
+            *used = Instant::now();
+            return Ok(circuit.clone());
+        }
+    }
+    // Ids name directories of the cache, so nothing else is looked up
+    let path = (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| state.cache.lookup(BACKEND, id, CIRCUIT))
+        .flatten()
+        .ok_or_else(|| error(404, format!("no such circuit: {}", id)))?;
+    let bytes = std::fs::read(&path).map_err(|err| error(500, format!("cannot read circuit: {}", err)))?;
+    let circuit = Arc::new(Halo2Circuit::from_bytes(&bytes).map_err(|err| error(500, err))?);
+    Ok(keep(state, id, circuit))
+}
+
+/* Compile the source of a request body, replying at once with the id and
+ * interface of its circuit, which programs that do not compile get no job
+ * for. The keys are generated as a job, unless a circuit is cached under the
+ * id already: ids are digests that no two sources share in practice, so that
+ * circuit is the one the job would give. */
+fn compile(state: &State, body: &Value) -> Reply {
+    let compiled = (|| -> Result<(String, Value, Option<u64>), Reply> {
+        let source = field(body, "source")?;
+        let id = secure_digest(&[BACKEND.as_bytes(), source.as_bytes()]);
+        let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+        let module = crate::parse(source)
+            .and_then(|module| crate::compile(module, &PrimeFieldOps::<Fp>::default(), &options))
+            .map_err(|err| error(status_of(&err), err))?
+            .module;
+        let interface = module.interface().to_json();
+        if state.cache.lookup(BACKEND, &id, CIRCUIT).is_some() {
+            return Ok((id, interface, None));
+        }
+        let job = submit(state, Work::Compile { circuit: id.clone(), module })?;
+        Ok((id, interface, Some(job)))
+    })();
+    match compiled {
+        Ok((id, interface, None)) => (200, json!({ "circuit": id, "interface": interface })),
+        Ok((id, interface, Some(job))) =>
+            (202, json!({ "circuit": id, "interface": interface, "job": job.to_string() })),
+        Err(reply) => reply,
+    }
+}
+
+/* The values of the inputs given in a request body, each as a string or as a
+ * number. */
+fn inputs(body: &Value) -> Result<HashMap<String, BigInt>, Reply> {
+    let entries = body.get("inputs")
+        .and_then(Value::as_object)
+        .ok_or_else(|| error(400, "request body has no object inputs"))?;
+    entries.iter().map(|(name, value)| {
+        let text = match value {
+            Value::String(text) => text.clone(),
+            Value::Number(number) if number.is_i64() || number.is_u64() => number.to_string(),
+            _ => return Err(error(400, format!("value of {} is not an integer", name))),
+        };
+        let value = parse_prefixed_num::<BigInt>(text.trim())
+            .map_err(|_| error(400, format!("value of {} is not an integer: {}", name, text)))?;
+        Ok((name.clone(), value))
+    }).collect()
+}
+
+fn prove(state: &State, body: &Value) -> Reply {
+    let submitted = (|| -> Result<u64, Reply> {
+        let circuit = circuit(state, field(body, "circuit")?)?;
+        let inputs = inputs(body)?;
+        submit(state, Work::Prove { circuit, inputs })
+    })();
+    match submitted {
+        Ok(id) => (202, json!({ "job": id.to_string() })),
+        Err(reply) => reply,
+    }
+}
+
+fn job(state: &State, id: &str) -> Reply {
+    let status = id.parse::<u64>().ok().and_then(|id| {
+        let mut jobs = state.jobs.lock().expect("jobs are not poisoned");
+        expire_jobs(state, &mut jobs);
+        jobs.statuses.get(&id).cloned()
+    });
+    match status {
+        None => error(404, format!("no such job: {}", id)),
+        Some(JobStatus::Queued) => (200, json!({ "status": "queued" })),
+        Some(JobStatus::Running) => (200, json!({ "status": "running" })),
+        Some(JobStatus::Done(mut outcome)) => {
+            outcome["status"] = json!("done");
+            (200, outcome)
+        },
+        Some(JobStatus::Failed(err)) => (200, json!({ "status": "failed", "error": err })),
+    }
+}
+
+fn verify(state: &State, body: &Value) -> Reply {
+    let verified = (|| -> Result<Result<(), Error>, Reply> {
+        let circuit = circuit(state, field(body, "circuit")?)?;
+        let proof = hex::decode(field(body, "proof")?.trim())
+            .map_err(|err| error(400, format!("proof is not hexadecimal: {}", err)))?;
+        let proof = Halo2Proof::from_bytes(&proof).map_err(|err| error(400, err))?;
+        Ok(circuit.verify(&proof))
+    })();
+    match verified {
+        Ok(Ok(())) => (200, json!({ "valid": true })),
+        Ok(Err(err @ Error::InvalidProof(_))) => (200, json!({ "valid": false, "error": err.to_string() })),
+        Ok(Err(err)) => error(status_of(&err), err),
+        Err(reply) => reply,
+    }
+}
+
+/* The id of the circuit whose interface the given path asks for, if it asks
+ * for one. */
+fn interface_of(path: &str) -> Option<&str> {
+    path.strip_prefix("/circuits/")?.strip_suffix("/interface")
+}
+
+/* The inputs that the circuit of the given id expects, as written by
+ * compile --emit-interface. */
+fn interface(state: &State, id: &str) -> Reply {
+    match circuit(state, id) {
+        Ok(circuit) => (200, circuit.module().interface().to_json()),
+        Err(reply) => reply,
+    }
+}
+
+fn circuits(state: &State) -> Reply {
+    match state.cache.entries() {
+        Ok(entries) => {
+            let circuits: Vec<_> = entries.iter()
+                .filter(|entry| entry.backend == BACKEND)
+                .filter_map(|entry| {
+                    let (_, size) = entry.items.iter().find(|(item, _)| item == CIRCUIT)?;
+                    Some(json!({ "circuit": entry.circuit_hash, "bytes": size }))
+                })
+                .collect();
+            (200, json!({ "circuits": circuits }))
+        },
+        Err(err) => error(500, err),
+    }
+}
@@ -0,0 +1,180 @@
+use crate::ast::Module;
+use crate::cache::{self, CacheKey};
+use crate::diagnostics::Diagnostic;
+use crate::error::Error;
+use crate::stdio::{is_stdin, PathOrStdio};
+use crate::transform::{compile_with, CompileError, CompileOptions, CompileOutput, FieldOps, ModuleStats};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/* The standard library of gadgets available through --prelude std. */
+pub const STD_PRELUDE: &str = include_str!("prelude.pir");
+
+/* The module merged into programs before the programs themselves. */
+#[derive(Clone, Debug)]
+pub enum Prelude {
+    /// Merge nothing into programs
+    None,
+    /// Merge the built-in library of gadgets into programs
+    Std,
+    /// Merge the source file at the given path into programs
+    Path(PathBuf),
+}
+
+impl FromStr for Prelude {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "none" => Ok(Prelude::None),
+            "std" => Ok(Prelude::Std),
+            "" => Err("prelude should be none, std, or a path".to_string()),
+            path => Ok(Prelude::Path(PathBuf::from(path))),
+        }
+    }
+}
+
+/* Read the source file at the given path, or standard input if the path is
+ * -. Standard input is remembered once read, since sources are read both to
+ * key the cache and to be parsed. */
+pub fn read_source(path: &Path) -> Result<String, Error> {
+    static STDIN_SOURCE: OnceLock<String> = OnceLock::new();
+    let unreadable = |err: std::io::Error| Error::Compile(format!("{}: cannot read file: {}", source_label(path), err));
+    if !is_stdin(path) {
+        return fs::read_to_string(path).map_err(unreadable);
+    }
+    if let Some(text) = STDIN_SOURCE.get() {
+        return Ok(text.clone());
+    }
+    let mut text = String::new();
+    std::io::stdin().read_to_string(&mut text).map_err(unreadable)?;
+    Ok(STDIN_SOURCE.get_or_init(|| text).clone())
+}
+
+/* Name the given source file in diagnostics. */
+pub fn source_label(path: &Path) -> String {
+    if is_stdin(path) {
+        "<stdin>".to_string()
+    } else {
+        path.to_string_lossy().to_string()
+    }
+}
+
+/* Record the given file as the one in which the given warnings were found. */
+fn attribute_warnings(warnings: &mut [Diagnostic], file: &Path) {
+    for warning in warnings {
+        if let Some(span) = &mut warning.span {
+            span.file = Some(source_label(file));
+        }
+    }
+}
+
+/* Parse the source file at the given path in the context of the given module,
+ * appending its warnings to the given list. */
+fn parse_file(path: &Path, context: &Module, warnings: &mut Vec<Diagnostic>) -> Result<Module, Error> {
+    let unparsed_file = read_source(path)?;
+    let start = warnings.len();
+    let mut module = Module::parse_with_context(&unparsed_file, context, warnings)
+        .map_err(|err| Error::Parse(Diagnostic::from_parse_error(&err, &source_label(path), &unparsed_file)))?;
+    attribute_warnings(&mut warnings[start..], path);
+    module.spans.set_file(&source_label(path));
+    Ok(module)
+}
+
+/* Parse and merge the given prelude followed by the given source files in
+ * order. Each file may refer to the definitions made by the files before it,
+ * but may not redefine them. Warnings are appended to the given list. */
+pub fn parse_sources(
+    prelude: &Prelude,
+    sources: &[PathOrStdio],
+    warnings: &mut Vec<Diagnostic>,
+) -> Result<Module, Error> {
+    let mut module = match prelude {
+        Prelude::None => Module::default(),
+        Prelude::Std => Module::parse_with_warnings(STD_PRELUDE, warnings)
+            .expect("standard prelude should be valid"),
+        Prelude::Path(path) => parse_file(path, &Module::default(), warnings)?,
+    };
+    for source in sources {
+        let parsed = parse_file(source, &module, warnings)?;
+        module.merge(parsed).map_err(|err| Error::Diagnosed(
+            Diagnostic::error("redefinition", format!("{}: {}", source_label(source), err)),
+        ))?;
+    }
+    Ok(module)
+}
+
+/* Make the key under which the compilation of the given prelude and sources
+ * by the given backend with the given options is cached. */
+pub fn cache_key(backend: &str, options: &str, prelude: &Prelude, sources: &[PathOrStdio]) -> Result<CacheKey, Error> {
+    let mut texts = vec![match prelude {
+        Prelude::None => String::new(),
+        Prelude::Std => STD_PRELUDE.to_string(),
+        Prelude::Path(path) => read_source(path)?,
+    }];
+    for source in sources {
+        texts.push(read_source(source)?);
+    }
+    Ok(CacheKey::new(backend, options, &texts))
+}
+
+/* Compile the given module over the given field with the given options,
+ * appending the warnings raised to the given list, along with those that
+ * were denied if that is why compilation failed. */
+pub fn compile_module(
+    module: Module,
+    field_ops: &dyn FieldOps,
+    options: &CompileOptions,
+    warnings: &mut Vec<Diagnostic>,
+) -> Result<CompileOutput, Error> {
+    match compile_with(module, field_ops, options) {
+        Ok(output) => {
+            warnings.extend(output.diagnostics.iter().cloned());
+            Ok(output)
+        },
+        Err(err) => {
+            if let CompileError::DeniedWarnings(denied) = &err {
+                warnings.extend(denied.iter().cloned());
+            }
+            Err(Error::Diagnosed(err.to_diagnostic()))
+        },
+    }
+}
+
+/* Parse the given prelude and sources and compile them over the given field
+ * with the given options, unless a compilation under the given key is found in
+ * the cache. Warnings, including those about the cache and, if asked for,
+ * those about shadowed bindings, are appended to the given list whether or
+ * not compilation succeeds. Returns the compiled module and its statistics. */
+pub fn compile_sources(
+    key: Option<CacheKey>,
+    prelude: &Prelude,
+    sources: &[PathOrStdio],
+    warn_shadowing: bool,
+    field_ops: &dyn FieldOps,
+    options: &CompileOptions,
+    warnings: &mut Vec<Diagnostic>,
+) -> Result<(Module, ModuleStats), Error> {
+    let cached = key.as_ref().and_then(|key| cache::lookup(key, warnings));
+    if let Some((module, source_constraints)) = cached {
+        let stats = options.module_stats(&module, source_constraints);
+        return Ok((module, stats));
+    }
+    let mut parse_warnings = vec![];
+    let module = parse_sources(prelude, sources, &mut parse_warnings)?;
+    let source_constraints = module.exprs.len();
+    if warn_shadowing {
+        warnings.extend(parse_warnings.iter().cloned());
+        if options.denies_warnings() && !parse_warnings.is_empty() {
+            return Err(Error::Diagnosed(CompileError::DeniedWarnings(parse_warnings).to_diagnostic()));
+        }
+    }
+    let output = compile_module(module, field_ops, options, warnings)?;
+    if let Some(key) = &key {
+        cache::store(key, &output.module, source_constraints, warnings);
+    }
+    Ok((output.module, output.stats))
+}
@@ -0,0 +1,166 @@
+#[cfg(feature = "compiler")]
+use crate::diagnostics::{Diagnostic, DiagnosticsFormat};
+use crate::error::Error;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+
+/* The status the process exits with when a proof is rejected, constraints
+ * cannot be satisfied, or circuits differ. */
+pub const FAILURE_STATUS: i32 = 1;
+/* The status the process exits with when the command line, or a source,
+ * inputs, circuit, or proof file given on it, cannot be used. */
+pub const INPUT_ERROR_STATUS: i32 = 2;
+/* The status the process exits with when something goes wrong that is not
+ * the fault of what was given. */
+pub const INTERNAL_ERROR_STATUS: i32 = 3;
+
+/* Why a subcommand did not succeed. Subcommands return these to main, which
+ * reports them and exits with the corresponding status so that scripts can
+ * tell the outcomes apart. */
+#[derive(Debug)]
+pub enum CommandError {
+    // A proof was rejected, constraints were violated, or circuits differ
+    Failure(String),
+    // The command line or a file given on it cannot be used
+    Input(String),
+    // Something went wrong that is not the fault of what was given
+    Internal(String),
+    // The sources given cannot be used, as the diagnostic describes, which is
+    // reported in the given format rather than as a line of its own
+    #[cfg(feature = "compiler")]
+    Diagnosed(Diagnostic, DiagnosticsFormat),
+}
+
+impl CommandError {
+    /* Blame the given error on the command line or the file it concerns. */
+    pub fn input(context: &str, err: impl fmt::Display) -> Self {
+        CommandError::Input(format!("{}: {}", context, err))
+    }
+
+    /* Blame the given error on vamp-ir itself. */
+    pub fn internal(context: &str, err: impl fmt::Display) -> Self {
+        CommandError::Internal(format!("{}: {}", context, err))
+    }
+
+    /* Blame the given failure of the library on whatever it concerns, with
+     * any diagnostic it carries to be reported in the given format. */
+    #[cfg(feature = "compiler")]
+    pub fn diagnosed(err: Error, format: DiagnosticsFormat) -> Self {
+        match err {
+            Error::Parse(diagnostic) | Error::Diagnosed(diagnostic) => CommandError::Diagnosed(diagnostic, format),
+            err => CommandError::from(err),
+        }
+    }
+
+    pub fn exit_status(&self) -> i32 {
+        match self {
+            CommandError::Failure(_) => FAILURE_STATUS,
+            CommandError::Input(_) => INPUT_ERROR_STATUS,
+            CommandError::Internal(_) => INTERNAL_ERROR_STATUS,
+            #[cfg(feature = "compiler")]
+            CommandError::Diagnosed(_, _) => INPUT_ERROR_STATUS,
+        }
+    }
+
+    /* The name by which the kind of this error is given in JSON results. */
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CommandError::Failure(_) => "failure",
+            CommandError::Input(_) => "input_error",
+            CommandError::Internal(_) => "internal_error",
+            #[cfg(feature = "compiler")]
+            CommandError::Diagnosed(_, _) => "input_error",
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Failure(msg) => write!(f, "{}", msg),
+            CommandError::Input(msg) => write!(f, "{}", msg),
+            CommandError::Internal(msg) => write!(f, "internal error: {}", msg),
+            #[cfg(feature = "compiler")]
+            CommandError::Diagnosed(diagnostic, _) => match &diagnostic.span {
+                Some(span) => write!(f, "{}: {}", span, diagnostic.message),
+                None => write!(f, "{}", diagnostic.message),
+            },
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/* Rejected proofs are failures and failures of the proving system are
+ * internal errors, whereas everything else is the fault of what was given. */
+impl From<Error> for CommandError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::InvalidProof(_) => CommandError::Failure(err.to_string()),
+            Error::Backend(message) => CommandError::Internal(message),
+            #[cfg(feature = "compiler")]
+            Error::Parse(diagnostic) | Error::Diagnosed(diagnostic) =>
+                CommandError::Diagnosed(diagnostic, DiagnosticsFormat::Human),
+            err => CommandError::Input(err.to_string()),
+        }
+    }
+}
+
+/* What a subcommand produced when it succeeded. Subcommands leave this to
+ * main to render, either as the text that they would have printed or, when
+ * --json is given, as the fields of a single JSON object. */
+#[derive(Default)]
+pub struct Outcome {
+    // Text for standard output when results are not rendered as JSON
+    pub text: String,
+    // Files written, by what they are, such as circuit or proof
+    pub artifacts: Vec<(&'static str, PathBuf)>,
+    // Digest of the contents of the circuit compiled or used
+    pub circuit_hash: Option<String>,
+    // Durations of the phases that the subcommand went through
+    pub timings: Vec<(&'static str, Duration)>,
+    // Results in structured form, which replace the text in JSON
+    pub details: Map<String, Value>,
+    // Why the subcommand failed after all, when its results are still shown
+    pub failure: Option<CommandError>,
+}
+
+impl Outcome {
+    /* Append the given line to the text of this outcome. */
+    pub fn println(&mut self, line: impl fmt::Display) {
+        self.text.push_str(&line.to_string());
+        self.text.push('\n');
+    }
+
+    /* Record that the given file was written as the given kind of output. */
+    pub fn artifact(&mut self, role: &'static str, path: &Path) {
+        self.artifacts.push((role, path.to_path_buf()));
+    }
+
+    /* Record the given result in structured form. */
+    pub fn detail(&mut self, key: &str, value: impl Into<Value>) {
+        self.details.insert(key.to_string(), value.into());
+    }
+
+    /* Time the given function as the given phase. */
+    pub fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.timings.push((phase, start.elapsed()));
+        result
+    }
+
+    /* How long the given phase took when it was last timed, if it was. */
+    pub fn timing(&self, phase: &str) -> Option<Duration> {
+        self.timings.iter().rev().find(|(timed, _)| *timed == phase).map(|(_, duration)| *duration)
+    }
+}
+
+/* The outcome of a subcommand. */
+pub type CommandResult = Result<Outcome, CommandError>;
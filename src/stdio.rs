@@ -0,0 +1,142 @@
+use std::fmt;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/* How standard input or output is named in place of a path. */
+const STDIO: &str = "-";
+
+/* A path given on the command line, where - stands for standard input when
+ * the path is read and for standard output when it is written. It
+ * dereferences to the path it names, which is - itself for standard input and
+ * output, so that it can be handed to anything that reads or writes files. A
+ * file that is really named - can still be given as ./-. */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathOrStdio {
+    Stdio,
+    Path(PathBuf),
+}
+
+impl PathOrStdio {
+    pub fn is_stdio(&self) -> bool {
+        matches!(self, PathOrStdio::Stdio)
+    }
+
+    pub fn as_path(&self) -> &Path {
+        match self {
+            PathOrStdio::Stdio => Path::new(STDIO),
+            PathOrStdio::Path(path) => path,
+        }
+    }
+}
+
+impl FromStr for PathOrStdio {
+    type Err = String;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            STDIO => Ok(PathOrStdio::Stdio),
+            "" => Err("expected a path, or - for standard input or output".to_string()),
+            path => Ok(PathOrStdio::Path(PathBuf::from(path))),
+        }
+    }
+}
+
+impl Deref for PathOrStdio {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl AsRef<Path> for PathOrStdio {
+    fn as_ref(&self) -> &Path {
+        self.as_path()
+    }
+}
+
+impl fmt::Display for PathOrStdio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_path().to_string_lossy())
+    }
+}
+
+/* Whether the given path stands for standard input. */
+pub fn is_stdin(path: &Path) -> bool {
+    path == Path::new(STDIO)
+}
+
+/* Whether the given path stands for standard output. */
+pub fn is_stdout(path: &Path) -> bool {
+    path == Path::new(STDIO)
+}
+
+/* The arguments of a command that read standard input or write standard
+ * output, gathered so that conflicts between them are found before any work
+ * is done: standard input can only be read once, and anything else written to
+ * standard output would corrupt what an argument writes there. */
+#[derive(Default)]
+pub struct StdioClaims {
+    // Arguments given - to read, by name
+    readers: Vec<&'static str>,
+    // Arguments given - to write, and anything else that writes standard
+    // output, by name
+    writers: Vec<&'static str>,
+}
+
+/* Explain that the given claims on the given stream conflict, if they do. */
+fn check_claims(stream: &str, claims: &[&'static str]) -> Result<(), String> {
+    if claims.len() < 2 {
+        return Ok(());
+    }
+    let mut names = vec![];
+    for claim in claims {
+        if !names.contains(claim) {
+            names.push(*claim);
+        }
+    }
+    if names.len() == 1 {
+        Err(format!("{} can only be used once, but - was given to {} more than once", stream, names[0]))
+    } else {
+        Err(format!("{} can only be used once, but - was given to {}", stream, names.join(" and ")))
+    }
+}
+
+impl StdioClaims {
+    /* Note that the argument of the given name reads the given paths. */
+    pub fn reads<'a>(&mut self, arg: &'static str, paths: impl IntoIterator<Item = &'a PathOrStdio>) -> &mut Self {
+        let stdin = paths.into_iter().filter(|path| path.is_stdio()).count();
+        self.readers.extend(std::iter::repeat(arg).take(stdin));
+        self
+    }
+
+    /* Note that the argument of the given name writes the given paths. */
+    pub fn writes<'a>(&mut self, arg: &'static str, paths: impl IntoIterator<Item = &'a PathOrStdio>) -> &mut Self {
+        let stdout = paths.into_iter().filter(|path| path.is_stdio()).count();
+        self.writers.extend(std::iter::repeat(arg).take(stdout));
+        self
+    }
+
+    /* Note that the given flag writes to standard output whatever it names. */
+    pub fn writes_stdout(&mut self, flag: &'static str) -> &mut Self {
+        self.writers.push(flag);
+        self
+    }
+
+    /* Whether anything was noted to write to standard output. */
+    pub fn claims_stdout(&self) -> bool {
+        !self.writers.is_empty()
+    }
+
+    /* Check that standard input and output are each claimed at most once. */
+    pub fn check(&self) -> Result<(), String> {
+        check_claims("standard input", &self.readers)?;
+        check_claims("standard output", &self.writers)
+    }
+}
+
+/* Commands whose arguments can name standard input or output. */
+pub trait ClaimStdio {
+    fn claim_stdio(&self, claims: &mut StdioClaims);
+}
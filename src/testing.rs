@@ -0,0 +1,226 @@
+use crate::ast::Module;
+use crate::halo2::field::PrimeFieldOps;
+use crate::transform::FieldOps;
+use halo2_proofs::pasta::Fp;
+use num_bigint::{BigInt, Sign};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/* Limits on the programs that module_strategy generates, built up as
+ * CompileOptions are. Values are reduced into the field of Halo2 unless
+ * another is given. */
+#[derive(Clone, Debug)]
+pub struct ModuleConfig {
+    // Most inputs that the constraints of a program draw on, at least one
+    max_inputs: usize,
+    // Most constraints in a program, at least one
+    max_constraints: usize,
+    // Deepest nesting of operations in an expression
+    max_depth: u32,
+    // Whether the results of constraints may be declared public
+    public: bool,
+    // Order of the field that the satisfying values are reduced into
+    modulus: BigInt,
+}
+
+impl Default for ModuleConfig {
+    fn default() -> Self {
+        Self {
+            max_inputs: 4,
+            max_constraints: 4,
+            max_depth: 4,
+            public: true,
+            modulus: field_modulus(&PrimeFieldOps::<Fp>::default()),
+        }
+    }
+}
+
+impl ModuleConfig {
+    pub fn max_inputs(mut self, max_inputs: usize) -> Self {
+        self.max_inputs = max_inputs.max(1);
+        self
+    }
+
+    pub fn max_constraints(mut self, max_constraints: usize) -> Self {
+        self.max_constraints = max_constraints.max(1);
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn public(mut self, public: bool) -> Self {
+        self.public = public;
+        self
+    }
+
+    /* Satisfy the generated programs over the field described by the given
+     * operations, which must be the field they are compiled over. */
+    pub fn field(mut self, field_ops: &dyn FieldOps) -> Self {
+        self.modulus = field_modulus(field_ops);
+        self
+    }
+}
+
+/* The order of the field described by the given operations, being one more
+ * than the canonical form of minus one. */
+fn field_modulus(field_ops: &dyn FieldOps) -> BigInt {
+    field_ops.canonical(BigInt::from(-1)) + 1
+}
+
+/* An expression of a generated program, over its inputs x0, x1, and so on. */
+#[derive(Clone, Debug)]
+enum Term {
+    Input(usize),
+    Constant(u64),
+    Negate(Box<Term>),
+    Power(Box<Term>, u32),
+    Infix(char, Box<Term>, Box<Term>),
+}
+
+impl Term {
+    /* The value of this term over the field of the given order with the given
+     * values of the inputs. */
+    fn evaluate(&self, values: &[BigInt], modulus: &BigInt) -> BigInt {
+        let reduce = |value: BigInt| ((value % modulus) + modulus) % modulus;
+        match self {
+            Term::Input(index) => values[*index].clone(),
+            Term::Constant(value) => reduce(BigInt::from(*value)),
+            Term::Negate(term) => reduce(-term.evaluate(values, modulus)),
+            Term::Power(term, exponent) =>
+                term.evaluate(values, modulus).modpow(&BigInt::from(*exponent), modulus),
+            Term::Infix(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(values, modulus), rhs.evaluate(values, modulus));
+                match op {
+                    '+' => reduce(lhs + rhs),
+                    '-' => reduce(lhs - rhs),
+                    _ => reduce(lhs * rhs),
+                }
+            },
+        }
+    }
+
+    /* Collect the indices of the inputs occuring in this term. */
+    fn collect_inputs(&self, inputs: &mut BTreeSet<usize>) {
+        match self {
+            Term::Input(index) => { inputs.insert(*index); },
+            Term::Constant(_) => {},
+            Term::Negate(term) | Term::Power(term, _) => term.collect_inputs(inputs),
+            Term::Infix(_, lhs, rhs) => {
+                lhs.collect_inputs(inputs);
+                rhs.collect_inputs(inputs);
+            },
+        }
+    }
+}
+
+/* Write this term as vamp-ir source, bracketing every operation so that no
+ * precedence is relied upon. */
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Input(index) => write!(f, "x{}", index),
+            Term::Constant(value) => write!(f, "{}", value),
+            Term::Negate(term) => write!(f, "(-{})", term),
+            Term::Power(term, exponent) => write!(f, "({}^{})", term, exponent),
+            Term::Infix(op, lhs, rhs) => write!(f, "({} {} {})", lhs, op, rhs),
+        }
+    }
+}
+
+/* Terms over the given number of inputs, nested no deeper than given. */
+fn term_strategy(inputs: usize, depth: u32) -> impl Strategy<Value = Term> {
+    let leaf = prop_oneof![
+        (0..inputs).prop_map(Term::Input),
+        (0u64..1 << 16).prop_map(Term::Constant),
+    ];
+    leaf.prop_recursive(depth, 1 << depth.min(8), 2, |inner| prop_oneof![
+        inner.clone().prop_map(|term| Term::Negate(Box::new(term))),
+        (inner.clone(), 1u32..4).prop_map(|(term, exponent)| Term::Power(Box::new(term), exponent)),
+        (prop_oneof![Just('+'), Just('-'), Just('*')], inner.clone(), inner)
+            .prop_map(|(op, lhs, rhs)| Term::Infix(op, Box::new(lhs), Box::new(rhs))),
+    ])
+}
+
+/* Elements of the field of the given order, small ones as often as not so
+ * that failures are easy to read. */
+fn element_strategy(modulus: &BigInt) -> impl Strategy<Value = BigInt> {
+    let modulus = modulus.clone();
+    // Twice the bytes of the modulus make the bias of the reduction negligible
+    let bytes = 2 * modulus.bits() as usize / 8 + 1;
+    prop_oneof![
+        (0u64..1000).prop_map(BigInt::from),
+        vec(any::<u8>(), bytes).prop_map(move |bytes| BigInt::from_bytes_le(Sign::Plus, &bytes) % &modulus),
+    ]
+}
+
+/* A program generated by module_strategy, along with values of its inputs,
+ * named as in its source, that satisfy each of its constraints. */
+#[derive(Clone)]
+pub struct GeneratedModule {
+    pub source: String,
+    pub module: Module,
+    pub inputs: HashMap<String, BigInt>,
+}
+
+/* Show the source and inputs alone, which is all that is needed to reproduce
+ * a failure. */
+impl fmt::Debug for GeneratedModule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut inputs: Vec<_> = self.inputs.iter().collect();
+        inputs.sort();
+        writeln!(f, "{}", self.source)?;
+        for (name, value) in inputs {
+            writeln!(f, "{} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/* Write a program with a constraint equating each of the given terms to an
+ * output z0, z1, and so on, some of them public, and assign each output the
+ * value of its term. */
+fn generate(values: &[BigInt], constraints: &[(Term, bool)], public: bool, modulus: &BigInt) -> GeneratedModule {
+    let mut used = BTreeSet::new();
+    let mut pubs = vec![];
+    let mut lines = vec![];
+    let mut inputs = HashMap::new();
+    for (index, (term, is_public)) in constraints.iter().enumerate() {
+        let output = format!("z{}", index);
+        if public && *is_public {
+            pubs.push(output.clone());
+        }
+        lines.push(format!("{} = {};\n", term, output));
+        inputs.insert(output, term.evaluate(values, modulus));
+        term.collect_inputs(&mut used);
+    }
+    // Inputs that no constraint draws on are not inputs of the program
+    for index in used {
+        inputs.insert(format!("x{}", index), values[index].clone());
+    }
+    let mut source = String::new();
+    if !pubs.is_empty() {
+        source.push_str(&format!("pub {};\n", pubs.join(", ")));
+    }
+    source.extend(lines);
+    let module = Module::parse(&source).expect("generated programs should parse");
+    GeneratedModule { source, module, inputs }
+}
+
+/* Well-formed programs within the given limits, each with inputs satisfying
+ * it. The constraints equate expressions of additions, subtractions,
+ * multiplications, negations, and small powers of the inputs and constants
+ * to outputs, so that evaluating the expressions satisfies them. */
+pub fn module_strategy(config: &ModuleConfig) -> impl Strategy<Value = GeneratedModule> {
+    let ModuleConfig { max_inputs, max_constraints, max_depth, public, modulus } = config.clone();
+    (1..=max_inputs).prop_flat_map(move |inputs| {
+        let values = vec(element_strategy(&modulus), inputs);
+        let constraints = vec((term_strategy(inputs, max_depth), any::<bool>()), 1..=max_constraints);
+        let modulus = modulus.clone();
+        (values, constraints).prop_map(move |(values, constraints)| generate(&values, &constraints, public, &modulus))
+    })
+}
@@ -1,11 +1,20 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, hash_map::Entry};
 use crate::typecheck::{infer_module_types, print_types, expand_pattern_variables, strip_module_types, expand_expr_variables, Type};
+use crate::diagnostics::{Diagnostic, SourceSpan};
 use crate::ast::{Module, Definition, TExpr, Pat, TPat, VariableId, LetBinding, Variable, InfixOp, Expr, Intrinsic, Function};
 use std::hash::Hash;
 use ark_ff::{One, Zero};
 use num_traits::sign::Signed;
-use num_bigint::BigInt;
+use num_bigint::{BigInt, Sign};
+use rand_core::{OsRng, RngCore};
 use num_traits::ToPrimitive;
+use std::time::Duration;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+// Browsers have no clock that std can read
+#[cfg(feature = "wasm")]
+use web_time::Instant;
+use log::debug;
 
 /* A structure for generating unique variable IDs. */
 pub struct VarGen(VariableId);
@@ -30,9 +39,11 @@ fn refresh_expr_variables(
     gen: &mut VarGen,
 ) {
     match &mut expr.v {
-        Expr::Intrinsic(Intrinsic { params, .. }) => {
+        Expr::Intrinsic(Intrinsic { params, pos, .. }) => {
             let mut map = map.clone();
-            for param in params.iter_mut() {
+            // Parameters that have already been filled are referenced by the
+            // intrinsic's environment, so leave them alone
+            for param in params.iter_mut().skip(*pos) {
                 refresh_pattern_variables(param, &mut map, prover_defs, gen);
             }
         },
@@ -90,24 +101,24 @@ fn match_pattern_expr(
     ext: &mut HashMap<VariableId, TExpr>,
     prover_defs: &mut HashSet<VariableId>,
     gen: &mut VarGen,
-) -> Tribool {
+) -> Result<Tribool, CompileError> {
     match (&pat.v, &expr.v) {
         (_, Expr::Variable(var)) if env.contains_key(&var.id) =>
             match_pattern_expr(pat, &env[&var.id].clone(), env, ext, prover_defs, gen),
         (Pat::As(pat, var), _) => {
-            let res = match_pattern_expr(pat, expr, env, ext, prover_defs, gen);
+            let res = match_pattern_expr(pat, expr, env, ext, prover_defs, gen)?;
             ext.insert(var.id, expr.clone().into());
-            res
+            Ok(res)
         },
         (Pat::Variable(var), _) => {
             ext.insert(var.id, expr.clone());
-            Tribool::True
+            Ok(Tribool::True)
         },
         (Pat::Product(pat1, pat2), Expr::Product(expr1, expr2)) |
         (Pat::Cons(pat1, pat2), Expr::Cons(expr1, expr2)) => {
-            let inner_res1 = match_pattern_expr(pat1, expr1, env, ext, prover_defs, gen);
-            let inner_res2 = match_pattern_expr(pat2, expr2, env, ext, prover_defs, gen);
-            std::cmp::min(inner_res1, inner_res2)
+            let inner_res1 = match_pattern_expr(pat1, expr1, env, ext, prover_defs, gen)?;
+            let inner_res2 = match_pattern_expr(pat2, expr2, env, ext, prover_defs, gen)?;
+            Ok(std::cmp::min(inner_res1, inner_res2))
         },
         (Pat::Product(_, _), Expr::Variable(var)) => {
             let new_var1 = Variable::new(gen.generate_id());
@@ -137,14 +148,15 @@ fn match_pattern_expr(
             env.insert(var.id, Expr::Cons(inner_expr1, inner_expr2).type_expr(Some(typ2.clone())));
             match_pattern_expr(pat, expr, env, ext, prover_defs, gen)
         },
-        (Pat::Unit, Expr::Unit) | (Pat::Nil, Expr::Nil) => Tribool::True,
+        (Pat::Unit, Expr::Unit) | (Pat::Nil, Expr::Nil) => Ok(Tribool::True),
+        (Pat::Nil, Expr::Cons(_, _)) | (Pat::Cons(_, _), Expr::Nil) => Ok(Tribool::False),
         (Pat::Constant(a), Expr::Constant(b)) if a == b =>
-            Tribool::True,
+            Ok(Tribool::True),
         (Pat::Constant(a), Expr::Constant(b)) if a != b =>
-            Tribool::False,
+            Ok(Tribool::False),
         (Pat::Constant(_), Expr::Variable(_) | Expr::Infix(_, _, _)) =>
-            Tribool::Indeterminate,
-        _ => panic!("unable to match {} against {}", expr, pat),
+            Ok(Tribool::Indeterminate),
+        _ => Err(CompileError::Malformed(format!("unable to match {} against {}", expr, pat), None)),
     }
 }
 
@@ -272,8 +284,7 @@ fn number_expr_variables(
         },
         Expr::LetBinding(binding, expr) => {
             let mut locals = locals.clone();
-            number_expr_variables(&mut binding.1, &mut locals, globals, gen);
-            number_pattern_variables(&mut binding.0, &mut locals, gen);
+            number_binding_variables(binding, &mut locals, globals, gen);
             number_expr_variables(expr, &mut locals, globals, gen);
         },
         Expr::Match(matche) => {
@@ -287,6 +298,25 @@ fn number_expr_variables(
     }
 }
 
+/* Numbers the variables occuring in the binding. Essentially numbers the inner
+ * expression, and then numbers the pattern variables in the given scope. The
+ * exception is function bindings, whose names are numbered first so that they
+ * can refer to themselves. */
+fn number_binding_variables(
+    binding: &mut LetBinding,
+    locals: &mut HashMap<String, VariableId>,
+    globals: &mut HashMap<String, VariableId>,
+    gen: &mut VarGen,
+) {
+    if let (Pat::Variable(_), Expr::Function(_)) = (&binding.0.v, &binding.1.v) {
+        number_pattern_variables(&mut binding.0, locals, gen);
+        number_expr_variables(&mut *binding.1, locals, globals, gen);
+    } else {
+        number_expr_variables(&mut *binding.1, locals, globals, gen);
+        number_pattern_variables(&mut binding.0, locals, gen);
+    }
+}
+
 /* Numbers the variables occuring in the definition. Essentially numbers the
  * inner expression, and then numbers the definition pattern variables in global
  * scope. */
@@ -296,8 +326,7 @@ fn number_def_variables(
     globals: &mut HashMap<String, VariableId>,
     gen: &mut VarGen,
 ) {
-    number_expr_variables(&mut *def.0.1, locals, globals, gen);
-    number_pattern_variables(&mut def.0.0, locals, gen);
+    number_binding_variables(&mut def.0, locals, globals, gen);
 }
 
 /* Numbers the variables occuring in the module definitions and then those
@@ -336,6 +365,24 @@ where U: Eq + Hash + Clone {
     }
 }
 
+/* The reasons that an arithmetic operation over a field can have no value. */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldOpError {
+    // A division, integer division, or remainder by zero, or a power of zero
+    // with a negative exponent
+    DivisionByZero,
+}
+
+impl std::fmt::Display for FieldOpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldOpError::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+impl std::error::Error for FieldOpError {}
+
 /* Defines an interface for completing various arithmetic operations over a
  * field without exposing the underlying field. */
 pub trait FieldOps {
@@ -343,8 +390,72 @@ pub trait FieldOps {
     fn canonical(&self, num: BigInt) -> BigInt;
     // Negates the given big integer over the given field
     fn negate(&self, num: BigInt) -> BigInt;
-    // Completes the given infix operation over the given field
-    fn infix(&self, op: InfixOp, lhs: BigInt, rhs: BigInt) -> BigInt;
+    // Completes the given infix operation over the given field, failing if
+    // it has no value there
+    fn infix(&self, op: InfixOp, lhs: BigInt, rhs: BigInt) -> Result<BigInt, FieldOpError>;
+    // Computes the smaller square root of the given big integer over the given
+    // field, if it is a quadratic residue
+    fn sqrt(&self, num: BigInt) -> Option<BigInt>;
+    // Checks whether the given big integer is a square over the given field
+    fn is_square(&self, num: BigInt) -> bool {
+        self.sqrt(num).is_some()
+    }
+}
+
+/* Defines an interface for estimating what the constructs of a circuit cost in
+ * a particular backend so that passes can choose between equivalent forms.
+ * Strength reduction consults it to decide whether doubling a variable is
+ * cheaper as an addition, and compile stats report the estimated total.
+ * Costs are relative and in arbitrary units. */
+pub trait CostModel {
+    // Cost of a gate adding, subtracting, negating, or equating its wires
+    fn add_gate(&self) -> u64;
+    // Cost of a gate multiplying or dividing its wires
+    fn mul_gate(&self) -> u64;
+    // Cost of constraining a wire to equal another occurence of its variable
+    fn copy_constraint(&self) -> u64;
+    // Cost of exposing a public variable
+    fn public_input(&self) -> u64;
+}
+
+/* Prices every gate alike and nothing else, so that the estimated cost of a
+ * circuit is just its number of gates. Used when no backend is targeted. */
+pub struct GateCountCostModel;
+
+impl CostModel for GateCountCostModel {
+    fn add_gate(&self) -> u64 { 1 }
+    fn mul_gate(&self) -> u64 { 1 }
+    fn copy_constraint(&self) -> u64 { 0 }
+    fn public_input(&self) -> u64 { 0 }
+}
+
+/* Keeps track of the named functions being applied during evaluation so that
+ * the unrolling of recursive definitions can be bounded. */
+pub struct Unroller {
+    // Maximum number of nested calls to the same function
+    limit: usize,
+    // The functions that are currently being applied
+    calls: Vec<Variable>,
+    // Module definitions that refer to themselves
+    recursive: HashSet<VariableId>,
+    // Values of previous calls to recursive definitions
+    memo: HashMap<(VariableId, String), TExpr>,
+}
+
+impl Unroller {
+    pub fn new(module: &Module, limit: usize) -> Self {
+        let mut recursive = HashSet::new();
+        for def in &module.defs {
+            if let (Pat::Variable(var), Expr::Function(fun)) = (&def.0.0.v, &def.0.1.v) {
+                let mut vars = HashMap::new();
+                collect_expr_variables(&fun.body, &mut vars);
+                if vars.contains_key(&var.id) {
+                    recursive.insert(var.id);
+                }
+            }
+        }
+        Self { limit, calls: vec![], recursive, memo: HashMap::new() }
+    }
 }
 
 /* Evaluate the given binding emitting constraints as necessary. Returns the new
@@ -356,17 +467,18 @@ fn evaluate_binding(
     bindings: &mut HashMap<VariableId, TExpr>,
     prover_defs: &mut HashSet<VariableId>,
     field_ops: &dyn FieldOps,
+    unroller: &mut Unroller,
     gen: &mut VarGen,
-) -> HashMap<VariableId, TExpr> {
+) -> Result<HashMap<VariableId, TExpr>, CompileError> {
     // Evaluate the binding expression in the current environment
-    let mut val = evaluate(&*binding.1, flattened, bindings, prover_defs, field_ops, gen);
+    let mut val = evaluate(&*binding.1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
     // Allow binding value to carry around its own context
     capture_env(&mut val, capture);
     // Now make a let binding for the expanded value whilst making sure that the
     // pattern is fully expanded
     let mut new_binding = Definition(LetBinding(binding.0.clone(), Box::new(val)));
     let mut pat_exps = HashMap::new();
-    expand_pattern_variables(&mut new_binding.0.0, &new_binding.0.1, &mut pat_exps, gen);
+    expand_pattern_variables(&mut new_binding.0.0, &new_binding.0.1, &mut pat_exps, gen)?;
     // Now decompose the let-binding into a flattened form
     flatten_binding(&new_binding.0.0, &new_binding.0.1, flattened);
     // Now expand the environment to reflect the binding that has been effected
@@ -381,8 +493,8 @@ fn evaluate_binding(
         &mut new_bindings,
         prover_defs,
         gen,
-    );
-    new_bindings
+    )?;
+    Ok(new_bindings)
 }
 
 /* Weakly add the given bindings to the environment of the given expression if
@@ -414,6 +526,166 @@ fn capture_env(val: &mut TExpr, new_bindings: HashMap<VariableId, TExpr>) {
     }
 }
 
+/* Apply the given function value to the given argument expression emitting
+ * constraints as necessary. Returns the value of the application. */
+fn apply(
+    mut expr1: TExpr,
+    expr2: &TExpr,
+    flattened: &mut Module,
+    bindings: &mut HashMap<VariableId, TExpr>,
+    prover_defs: &mut HashSet<VariableId>,
+    field_ops: &dyn FieldOps,
+    unroller: &mut Unroller,
+    gen: &mut VarGen,
+) -> Result<TExpr, CompileError> {
+    refresh_expr_variables(&mut expr1, &HashMap::new(), prover_defs, gen);
+    match &mut expr1.v {
+        Expr::Intrinsic(intr) => {
+            // Now that the current parameter is filled, move onto the
+            // next position
+            let param1 = intr.params[intr.pos].clone();
+            intr.pos += 1;
+            // The specific binding that needs to be added to
+            // environment
+            let new_bind = LetBinding(param1, Box::new(expr2.clone()));
+            // Make sure that the argument's environment does not get
+            // overriden by that of the function by producing
+            // alternatives sourced from this context
+            let mut implicit_env = HashMap::new();
+            for var in intr.env.keys() {
+                if let Some(val) = bindings.get(var) {
+                    implicit_env.insert(*var, val.clone());
+                }
+            }
+            // Setup the environment in which to evaluate body
+            let new_bindings = evaluate_binding(&new_bind, implicit_env, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            // Apply the new environment to the body
+            intr.env.extend(new_bindings.clone());
+            // Modify function type to account for the partial
+            // application that has just happened
+            expr1.t = None;
+            // Finally evaluate the body
+            let mut val = evaluate(&expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            // Enable closures by storing the required environment
+            // modifications inside the evaluation result
+            capture_env(&mut val, new_bindings);
+            Ok(val)
+        },
+        Expr::Function(fun) if fun.params.is_empty() => {
+            unreachable!("functions should have at least one parameter");
+        },
+        Expr::Function(fun) => {
+            // Now that we have an assignment, move the function
+            // parameter into the environment
+            let param1 = fun.params.remove(0);
+            // The specific binding that needs to be added to
+            // environment
+            let new_bind = LetBinding(param1, Box::new(expr2.clone()));
+            // Make sure that the argument's environment does not get
+            // overriden by that of the function by producing
+            // alternatives sourced from this context
+            let mut implicit_env = HashMap::new();
+            for var in fun.env.keys() {
+                if let Some(val) = bindings.get(var) {
+                    implicit_env.insert(*var, val.clone());
+                }
+            }
+            // Setup the environment in which to evaluate body
+            let new_bindings = evaluate_binding(
+                &new_bind,
+                implicit_env,
+                flattened,
+                bindings,
+                prover_defs,
+                field_ops,
+                unroller,
+                gen,
+            )?;
+            // Apply the new environment to the body
+            fun.env.extend(new_bindings.clone());
+            // Modify function type to account for the partial
+            // application that has just happened
+            expr1.t = None;
+            // Finally evaluate the body
+            let mut val = evaluate(&expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            // Enable closures by storing the required environment
+            // modifications inside the evaluation result
+            capture_env(&mut val, new_bindings);
+            Ok(val)
+        },
+        _ => Err(CompileError::Malformed(
+            format!("cannot apply argument {} to {}", expr2, expr1),
+            None,
+        )),
+    }
+}
+
+/* Get the named function and the arguments of the given application, provided
+ * that the function being applied is a variable. */
+fn named_call(expr: &TExpr) -> Option<(&Variable, Vec<&TExpr>)> {
+    let mut args = vec![];
+    let mut expr = expr;
+    while let Expr::Application(expr1, expr2) = &expr.v {
+        args.push(&**expr2);
+        expr = expr1;
+    }
+    args.reverse();
+    match &expr.v {
+        Expr::Variable(var) if var.name.is_some() => Some((var, args)),
+        _ => None,
+    }
+}
+
+/* Evaluate the application of the given named function to the given arguments.
+ * Calls are tracked so that runaway recursion can be reported along with the
+ * chain of calls that led to it. The values of calls to recursive definitions
+ * are memoized so that identical instantiations are only expanded once. */
+fn evaluate_call(
+    var: &Variable,
+    args: Vec<&TExpr>,
+    flattened: &mut Module,
+    bindings: &mut HashMap<VariableId, TExpr>,
+    prover_defs: &mut HashSet<VariableId>,
+    field_ops: &dyn FieldOps,
+    unroller: &mut Unroller,
+    gen: &mut VarGen,
+) -> Result<TExpr, CompileError> {
+    let func = Expr::Variable(var.clone()).type_expr(None);
+    let mut val = evaluate(&func, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+    let mut arg_vals = vec![];
+    for arg in args {
+        arg_vals.push(evaluate(arg, flattened, bindings, prover_defs, field_ops, unroller, gen)?);
+    }
+    // Closures may capture differing environments, so only memoize calls
+    // whose arguments are first-order
+    let memo_key = if unroller.recursive.contains(&var.id) &&
+        !arg_vals.iter().any(contains_function) {
+        let arg_strs: Vec<_> = arg_vals.iter().map(|x| x.to_string()).collect();
+        Some((var.id, arg_strs.join(" ")))
+    } else {
+        None
+    };
+    if let Some(memo_val) = memo_key.as_ref().and_then(|key| unroller.memo.get(key)) {
+        return Ok(memo_val.clone());
+    }
+    // Make sure that recursion has not gotten out of hand
+    let depth = unroller.calls.iter().filter(|x| x.id == var.id).count();
+    if depth >= unroller.limit {
+        let mut chain: Vec<_> = unroller.calls.iter().map(|x| x.to_string()).collect();
+        chain.push(var.to_string());
+        return Err(CompileError::RecursionLimit { limit: unroller.limit, chain, span: None });
+    }
+    unroller.calls.push(var.clone());
+    for arg_val in arg_vals {
+        val = apply(val, &arg_val, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+    }
+    unroller.calls.pop();
+    if let Some(key) = memo_key {
+        unroller.memo.insert(key, val.clone());
+    }
+    Ok(val)
+}
+
 /* Evaluate the given expression emitting constraints as necessary. Returns the
  * value that the given expression evaluates to. */
 fn evaluate(
@@ -422,89 +694,16 @@ fn evaluate(
     bindings: &mut HashMap<VariableId, TExpr>,
     prover_defs: &mut HashSet<VariableId>,
     field_ops: &dyn FieldOps,
+    unroller: &mut Unroller,
     gen: &mut VarGen,
-) -> TExpr {
+) -> Result<TExpr, CompileError> {
     match &expr.v {
         Expr::Application(expr1, expr2) => {
-            let mut expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, gen);
-            refresh_expr_variables(&mut expr1, &HashMap::new(), prover_defs, gen);
-            match &mut expr1.v {
-                Expr::Intrinsic(intr) => {
-                    // Now that the current parameter is filled, move onto the
-                    // next position
-                    let param1 = intr.params[intr.pos].clone();
-                    intr.pos += 1;
-                    // The specific binding that needs to be added to
-                    // environment
-                    let new_bind = LetBinding(param1, Box::new(*expr2.clone()));
-                    // Make sure that the argument's environment does not get
-                    // overriden by that of the function by producing
-                    // alternatives sourced from this context
-                    let mut implicit_env = HashMap::new();
-                    for var in intr.env.keys() {
-                        if let Some(val) = bindings.get(var) {
-                            implicit_env.insert(*var, val.clone());
-                        }
-                    }
-                    // Setup the environment in which to evaluate body
-                    let new_bindings = evaluate_binding(&new_bind, implicit_env, flattened, bindings, prover_defs, field_ops, gen);
-                    // Apply the new environment to the body
-                    intr.env.extend(new_bindings.clone());
-                    // Modify function type to account for the partial
-                    // application that has just happened
-                    expr1.t = None;
-                    // Finally evaluate the body
-                    let mut val = evaluate(&expr1, flattened, bindings, prover_defs, field_ops, gen);
-                    // Enable closures by storing the required environment
-                    // modifications inside the evaluation result
-                    capture_env(&mut val, new_bindings);
-                    val
-                },
-                Expr::Function(fun) if fun.params.is_empty() => {
-                    unreachable!("functions should have at least one parameter");
-                },
-                Expr::Function(fun) => {
-                    // Now that we have an assignment, move the function
-                    // parameter into the environment
-                    let param1 = fun.params.remove(0);
-                    // The specific binding that needs to be added to
-                    // environment
-                    let new_bind = LetBinding(param1, Box::new(*expr2.clone()));
-                    // Make sure that the argument's environment does not get
-                    // overriden by that of the function by producing
-                    // alternatives sourced from this context
-                    let mut implicit_env = HashMap::new();
-                    for var in fun.env.keys() {
-                        if let Some(val) = bindings.get(var) {
-                            implicit_env.insert(*var, val.clone());
-                        }
-                    }
-                    // Setup the environment in which to evaluate body
-                    let new_bindings = evaluate_binding(
-                        &new_bind,
-                        implicit_env,
-                        flattened,
-                        bindings,
-                        prover_defs,
-                        field_ops,
-                        gen,
-                    );
-                    // Apply the new environment to the body
-                    fun.env.extend(new_bindings.clone());
-                    // Modify function type to account for the partial
-                    // application that has just happened
-                    expr1.t = None;
-                    // Finally evaluate the body
-                    let mut val = evaluate(&expr1, flattened, bindings, prover_defs, field_ops, gen);
-                    // Enable closures by storing the required environment
-                    // modifications inside the evaluation result
-                    capture_env(&mut val, new_bindings);
-                    val
-                },
-                _ => {
-                    panic!("cannot apply argument {} to {}", expr2, expr1)
-                },
+            if let Some((var, args)) = named_call(expr) {
+                return evaluate_call(var, args, flattened, bindings, prover_defs, field_ops, unroller, gen);
             }
+            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            apply(expr1, expr2, flattened, bindings, prover_defs, field_ops, unroller, gen)
         },
         Expr::LetBinding(_, _) => {
             let mut acc_bindings = HashMap::new();
@@ -514,7 +713,7 @@ fn evaluate(
             while let Expr::LetBinding(binding, body) = &expr.v {
                 // Evaluate binding expression and get new bindings
                 let new_bindings =
-                    evaluate_binding(binding, HashMap::new(), flattened, bindings, prover_defs, field_ops, gen);
+                    evaluate_binding(binding, HashMap::new(), flattened, bindings, prover_defs, field_ops, unroller, gen)?;
                 let mut new_bindings = new_bindings.into_iter().map(|(k, v)| (k, Some(v))).collect();
                 // Insert new bindings into environment and get old bindings
                 exchange_map(bindings, &mut new_bindings);
@@ -527,7 +726,7 @@ fn evaluate(
                     // Iteratively evaluate a sequence expression here in order
                     // to avoid leaving this call frame
                     for expr in &seq[0..seq.len()-1] {
-                        evaluate(expr, flattened, bindings, prover_defs, field_ops, gen);
+                        evaluate(expr, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
                     }
                     // Hence the let's body is now effectively this sequence's
                     // last expression
@@ -537,49 +736,58 @@ fn evaluate(
                 }
             }
             // Now evaluate the inner-most body
-            let mut val = evaluate(expr, flattened, bindings, prover_defs, field_ops, gen);
+            let mut val = evaluate(expr, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
             // Now restore the old environment before this entire let expression
             exchange_map(bindings, &mut acc_bindings);
             let acc_bindings = acc_bindings.into_iter().map(|(k, v)| (k, v.unwrap())).collect();
             // Capture the environment modifications required to evaluate body
             // inside the body. Necessary for closures.
             capture_env(&mut val, acc_bindings);
-            val
+            Ok(val)
         },
         Expr::Sequence(seq) => {
             let mut val = None;
             for expr in seq {
-                val = Some(evaluate(expr, flattened, bindings, prover_defs, field_ops, gen));
+                val = Some(evaluate(expr, flattened, bindings, prover_defs, field_ops, unroller, gen)?);
             }
-            val.expect("encountered empty sequence")
+            Ok(val.expect("encountered empty sequence"))
         },
         Expr::Product(expr1, expr2) => {
-            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, gen);
-            let expr2 = evaluate(expr2, flattened, bindings, prover_defs, field_ops, gen);
-            Expr::Product(Box::new(expr1), Box::new(expr2)).type_expr(expr.t.clone())
+            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            let expr2 = evaluate(expr2, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            Ok(Expr::Product(Box::new(expr1), Box::new(expr2)).type_expr(expr.t.clone()))
         },
         Expr::Cons(expr1, expr2) => {
-            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, gen);
-            let expr2 = evaluate(expr2, flattened, bindings, prover_defs, field_ops, gen);
-            Expr::Cons(Box::new(expr1), Box::new(expr2)).type_expr(expr.t.clone())
+            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            let expr2 = evaluate(expr2, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            Ok(Expr::Cons(Box::new(expr1), Box::new(expr2)).type_expr(expr.t.clone()))
         },
         Expr::Infix(InfixOp::Equal, expr1, expr2) => {
-            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, gen);
-            let expr2 = evaluate(expr2, flattened, bindings, prover_defs, field_ops, gen);
+            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            let expr2 = evaluate(expr2, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            if contains_function(&expr1) || contains_function(&expr2) {
+                return Err(CompileError::EscapingFunction(
+                    format!("unapplied function {} = {} escapes into constraint {}", expr1, expr2, expr),
+                    None,
+                ));
+            }
             flatten_equals(&expr1, &expr2, flattened);
-            Expr::Unit.type_expr(Some(Type::Unit))
+            Ok(Expr::Unit.type_expr(Some(Type::Unit)))
         },
         Expr::Infix(InfixOp::Exponentiate, e1, e2) => {
             // Compute the base once and for all
-            let e1 = evaluate(e1, flattened, bindings, prover_defs, field_ops, gen);
-            let e2 = evaluate(e2, flattened, bindings, prover_defs, field_ops, gen);
+            let e1 = evaluate(e1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            let e2 = evaluate(e2, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
             match (&e1.v, &e2.v) {
-                (Expr::Constant(a), Expr::Constant(b)) =>
-                    Expr::Constant(field_ops.infix(InfixOp::Exponentiate, a.clone(), b.clone())).type_expr(Some(Type::Int)),
+                (Expr::Constant(a), Expr::Constant(b)) => {
+                    let val = field_ops.infix(InfixOp::Exponentiate, a.clone(), b.clone())
+                        .map_err(|err| CompileError::FieldOp(err, expr.to_string()))?;
+                    Ok(Expr::Constant(val).type_expr(Some(Type::Int)))
+                },
                 (_, Expr::Constant(c)) if c.is_zero() =>
-                    Expr::Constant(One::one()).type_expr(Some(Type::Int)),
+                    Ok(Expr::Constant(One::one()).type_expr(Some(Type::Int))),
                 (_, Expr::Constant(c)) if c.is_one() =>
-                    e1,
+                    Ok(e1),
                 (_, Expr::Constant(v2)) if v2.is_positive() => {
                     // Compute roughly the sqrt of this expression
                     let sqrt = Expr::Infix(
@@ -587,7 +795,7 @@ fn evaluate(
                         Box::new(e1.clone()),
                         Box::new(Expr::Constant(v2/2i8).type_expr(Some(Type::Int)))
                     ).type_expr(Some(Type::Int));
-                    let out2_term = evaluate(&sqrt, flattened, bindings, prover_defs, field_ops, gen);
+                    let out2_term = evaluate(&sqrt, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
                     // Now square the value to obtain roughly this expression
                     let mut rhs = infix_op(
                         InfixOp::Multiply,
@@ -603,7 +811,7 @@ fn evaluate(
                             e1,
                         );
                     }
-                    evaluate(&rhs, flattened, bindings, prover_defs, field_ops, gen)
+                    evaluate(&rhs, flattened, bindings, prover_defs, field_ops, unroller, gen)
                 },
                 (_, Expr::Constant(v2)) => {
                     // Compute the reciprocal of this expression
@@ -618,17 +826,27 @@ fn evaluate(
                         Expr::Constant(One::one()).type_expr(Some(Type::Int)),
                         recip.type_expr(Some(Type::Int)),
                     );
-                    evaluate(&rhs, flattened, bindings, prover_defs, field_ops, gen)
+                    evaluate(&rhs, flattened, bindings, prover_defs, field_ops, unroller, gen)
                 }
-                _ => panic!("variables are not permitted in expression exponents"),
+                _ => Err(CompileError::Malformed(
+                    format!("variables are not permitted in the exponent of {}", expr),
+                    None,
+                )),
             }
         },
         Expr::Infix(op, expr1, expr2) => {
-            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, gen);
-            let expr2 = evaluate(expr2, flattened, bindings, prover_defs, field_ops, gen);
+            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
+            let expr2 = evaluate(expr2, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
             match (&expr1.v, &expr2.v) {
-                (Expr::Constant(c1), Expr::Constant(c2)) =>
-                    Expr::Constant(field_ops.infix(*op, c1.clone(), c2.clone())).type_expr(expr.t.clone()),
+                (Expr::Constant(c1), Expr::Constant(c2)) => {
+                    let val = field_ops.infix(*op, c1.clone(), c2.clone())
+                        .map_err(|err| CompileError::FieldOp(err, expr.to_string()))?;
+                    Ok(Expr::Constant(val).type_expr(expr.t.clone()))
+                },
+                // Divisions of variables by zero are as undefined as those of
+                // constants, and no gate could express them
+                (_, Expr::Constant(c2)) if is_division(*op) && field_ops.canonical(c2.clone()).is_zero() =>
+                    Err(CompileError::FieldOp(FieldOpError::DivisionByZero, expr.to_string())),
                 (_, _) => {
                     let val = infix_op(op.clone(), expr1, expr2);
                     let var = Variable::new(gen.generate_id());
@@ -637,45 +855,45 @@ fn evaluate(
                         Box::new(val),
                     ));
                     flattened.defs.push(binding);
-                    Expr::Variable(var).type_expr(expr.t.clone())
+                    Ok(Expr::Variable(var).type_expr(expr.t.clone()))
                 }
             }
         },
         Expr::Negate(expr1) => {
-            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, gen);
+            let expr1 = evaluate(expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
             match expr1.v {
                 Expr::Constant(c1) =>
-                    Expr::Constant(field_ops.negate(c1)).type_expr(expr.t.clone()),
-                _ => Expr::Negate(Box::new(expr1)).type_expr(expr.t.clone()),
+                    Ok(Expr::Constant(field_ops.negate(c1)).type_expr(expr.t.clone())),
+                _ => Ok(Expr::Negate(Box::new(expr1)).type_expr(expr.t.clone())),
             }
         },
         Expr::Constant(c) =>
-            Expr::Constant(field_ops.canonical(c.clone())).type_expr(expr.t.clone()),
-        Expr::Unit | Expr::Nil => expr.clone(),
+            Ok(Expr::Constant(field_ops.canonical(c.clone())).type_expr(expr.t.clone())),
+        Expr::Unit | Expr::Nil => Ok(expr.clone()),
         Expr::Variable(var) => match bindings.get(&var.id) {
-            Some(val) if !prover_defs.contains(&var.id) => val.clone(),
-            _ => expr.clone(),
+            Some(val) if !prover_defs.contains(&var.id) => Ok(val.clone()),
+            _ => Ok(expr.clone()),
         },
         Expr::Function(Function { params, body, env, .. }) if params.len() == 0 => {
             let mut ext = env.clone().into_iter().map(|(k, v)| (k, Some(v))).collect();
             // Supplement the partially captured environment with bindings
             exchange_map(bindings, &mut ext);
-            let val = evaluate(body, flattened, bindings, prover_defs, field_ops, gen);
+            let val = evaluate(body, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
             exchange_map(bindings, &mut ext);
-            val
+            Ok(val)
         },
         Expr::Intrinsic(intr @ Intrinsic { pos, params, env, .. }) if *pos == params.len() => {
             let mut ext = env.clone().into_iter().map(|(k, v)| (k, Some(v))).collect();
             // Supplement the partially captured environment with bindings
             exchange_map(bindings, &mut ext);
-            let expr1 = intr.execute(bindings, prover_defs, gen);
-            let val = evaluate(&expr1, flattened, bindings, prover_defs, field_ops, gen);
+            let expr1 = intr.execute(bindings, prover_defs, gen)?;
+            let val = evaluate(&expr1, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
             exchange_map(bindings, &mut ext);
-            val
+            Ok(val)
         },
-        Expr::Function(_) | Expr::Intrinsic(_) => expr.clone(),
+        Expr::Function(_) | Expr::Intrinsic(_) => Ok(expr.clone()),
         Expr::Match(matche) => {
-            let val = evaluate(&matche.0, flattened, bindings, prover_defs, field_ops, gen);
+            let val = evaluate(&matche.0, flattened, bindings, prover_defs, field_ops, unroller, gen)?;
             for (pat, expr2) in matche.1.iter().zip(matche.2.iter()) {
                 let res = match_pattern_expr(
                     &pat,
@@ -684,7 +902,7 @@ fn evaluate(
                     &mut HashMap::new(),
                     prover_defs,
                     gen,
-                );
+                )?;
                 match res {
                     Tribool::True => {
                         let expr = TExpr {
@@ -694,14 +912,19 @@ fn evaluate(
                             ),
                             t: expr.t.clone()
                         };
-                        return evaluate(&expr, flattened, bindings, prover_defs, field_ops, gen);
+                        return evaluate(&expr, flattened, bindings, prover_defs, field_ops, unroller, gen);
                     },
-                    Tribool::Indeterminate =>
-                        panic!("cannot statically match {} against {}", val, pat),
+                    Tribool::Indeterminate => return Err(CompileError::Malformed(
+                        format!("cannot statically match {} against {}", val, pat),
+                        None,
+                    )),
                     Tribool::False => continue,
                 }
             }
-            panic!("cannot match {} to any pattern in {}", matche.0, expr);
+            Err(CompileError::Malformed(
+                format!("cannot match {} to any pattern in {}", matche.0, expr),
+                None,
+            ))
         },
     }
 }
@@ -714,8 +937,9 @@ fn evaluate_def(
     bindings: &mut HashMap<VariableId, TExpr>,
     prover_defs: &mut HashSet<VariableId>,
     field_ops: &dyn FieldOps,
+    unroller: &mut Unroller,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     let ext = evaluate_binding(
         &def.0,
         HashMap::new(),
@@ -723,9 +947,11 @@ fn evaluate_def(
         bindings,
         prover_defs,
         field_ops,
+        unroller,
         gen,
-    );
+    )?;
     bindings.extend(ext);
+    Ok(())
 }
 
 /* Evaluate the given module emitting the constraints that it implies. */
@@ -735,28 +961,88 @@ pub fn evaluate_module(
     bindings: &mut HashMap<VariableId, TExpr>,
     prover_defs: &mut HashSet<VariableId>,
     field_ops: &dyn FieldOps,
+    unroller: &mut Unroller,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     flattened.pubs.extend(module.pubs.clone());
     for def in &module.defs {
-        evaluate_def(def, flattened, bindings, prover_defs, field_ops, gen);
+        evaluate_def(def, flattened, bindings, prover_defs, field_ops, unroller, gen)
+            .map_err(|err| err.locate(def_span(module, def)))?;
     }
-    for expr in &module.exprs {
-        evaluate(expr, flattened, bindings, prover_defs, field_ops, gen);
+    for (idx, expr) in module.exprs.iter().enumerate() {
+        let span = module.spans.exprs.get(idx).and_then(Option::as_ref);
+        let val = evaluate(expr, flattened, bindings, prover_defs, field_ops, unroller, gen)
+            .map_err(|err| err.locate(span))?;
+        if contains_function(&val) {
+            return Err(CompileError::EscapingFunction(
+                format!("partially applied function {} escapes from expression {}", val, expr),
+                span.cloned(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/* Where the given definition of the given module came from in its source, if
+ * known, going by the first name that it defines. */
+pub(crate) fn def_span<'a>(module: &'a Module, def: &Definition) -> Option<&'a SourceSpan> {
+    let mut names = vec![];
+    def.0.0.collect_names(&mut names);
+    names.first().and_then(|name| module.spans.defs.get(name))
+}
+
+/* Check whether the given evaluated expression still contains a function or an
+ * intrinsic. Such values only exist at compile time and hence cannot be
+ * represented by any constraint. */
+fn contains_function(expr: &TExpr) -> bool {
+    match &expr.v {
+        Expr::Function(_) | Expr::Intrinsic(_) => true,
+        Expr::Product(expr1, expr2) | Expr::Cons(expr1, expr2) =>
+            contains_function(expr1) || contains_function(expr2),
+        _ => false,
+    }
+}
+
+/* A collection into which the variables occuring in a program are gathered,
+ * each variable being kept once. */
+pub trait VariableSink {
+    fn insert_variable(&mut self, var: &Variable);
+}
+
+impl VariableSink for HashMap<VariableId, Variable> {
+    fn insert_variable(&mut self, var: &Variable) {
+        self.insert(var.id, var.clone());
+    }
+}
+
+/* The variables occuring in a program in the order of their first occurence. */
+#[derive(Default)]
+pub struct OrderedVariables {
+    // Identifiers of the variables collected so far
+    seen: HashSet<VariableId>,
+    // The variables collected so far, in order
+    vars: Vec<Variable>,
+}
+
+impl VariableSink for OrderedVariables {
+    fn insert_variable(&mut self, var: &Variable) {
+        if self.seen.insert(var.id) {
+            self.vars.push(var.clone());
+        }
     }
 }
 
 /* Collect all the variables occuring in the given pattern. */
-pub fn collect_pattern_variables(
+pub fn collect_pattern_variables<S: VariableSink>(
     pat: &TPat,
-    map: &mut HashMap<VariableId, Variable>,
+    map: &mut S,
 ) {
     match &pat.v {
         Pat::Variable(var) => {
-            map.insert(var.id, var.clone());
+            map.insert_variable(var);
         },
         Pat::As(pat, var) => {
-            map.insert(var.id, var.clone());
+            map.insert_variable(var);
             collect_pattern_variables(pat, map);
         },
         Pat::Product(pat1, pat2) | Pat::Cons(pat1, pat2) => {
@@ -768,13 +1054,13 @@ pub fn collect_pattern_variables(
 }
 
 /* Collect all the variables occuring in the given expression. */
-fn collect_expr_variables(
+fn collect_expr_variables<S: VariableSink>(
     expr: &TExpr,
-    map: &mut HashMap<VariableId, Variable>,
+    map: &mut S,
 ) {
     match &expr.v {
         Expr::Variable(var) => {
-            map.insert(var.id, var.clone());
+            map.insert_variable(var);
         },
         Expr::Sequence(exprs) => {
             for expr in exprs {
@@ -816,22 +1102,25 @@ fn collect_expr_variables(
     }
 }
 
-/* Collect all the variables occuring in the given definition. */
-fn collect_def_variables(
+/* Collect all the variables occuring in the given definition, starting with
+ * those that it defines. */
+fn collect_def_variables<S: VariableSink>(
     def: &Definition,
-    map: &mut HashMap<VariableId, Variable>,
+    map: &mut S,
 ) {
-    collect_expr_variables(&*def.0.1, map);
     collect_pattern_variables(&def.0.0, map);
+    collect_expr_variables(&*def.0.1, map);
 }
 
 /* Collect all the variables occuring in the given module. */
+#[deprecated(note = "iterates in no particular order, use ordered_module_variables instead")]
+#[allow(dead_code)]
 pub fn collect_module_variables(
     module: &Module,
     map: &mut HashMap<VariableId, Variable>,
 ) {
     for var in &module.pubs {
-        map.insert(var.id, var.clone());
+        map.insert_variable(var);
     }
     for def in &module.defs {
         collect_def_variables(def, map);
@@ -841,6 +1130,23 @@ pub fn collect_module_variables(
     }
 }
 
+/* Collect all the variables occuring in the given module in the order of
+ * their first occurence: the public variables in declaration order, then those
+ * of the definitions and finally those of the constraints. */
+pub fn ordered_module_variables(module: &Module) -> Vec<Variable> {
+    let mut ordered = OrderedVariables::default();
+    for var in &module.pubs {
+        ordered.insert_variable(var);
+    }
+    for def in &module.defs {
+        collect_def_variables(def, &mut ordered);
+    }
+    for expr in &module.exprs {
+        collect_expr_variables(expr, &mut ordered);
+    }
+    ordered.vars
+}
+
 /* Produce the given binary operation making sure to do any straightforward
  * simplifications. */
 fn infix_op(op: InfixOp, e1: TExpr, e2: TExpr) -> TExpr {
@@ -873,6 +1179,292 @@ fn infix_op(op: InfixOp, e1: TExpr, e2: TExpr) -> TExpr {
     }
 }
 
+/* Count the occurences of each variable in the given arithmetic expression. */
+fn count_expr_variables(expr: &TExpr, counts: &mut HashMap<VariableId, usize>) {
+    match &expr.v {
+        Expr::Variable(var) => *counts.entry(var.id).or_insert(0) += 1,
+        Expr::Negate(expr1) => count_expr_variables(expr1, counts),
+        Expr::Infix(_, expr1, expr2) => {
+            count_expr_variables(expr1, counts);
+            count_expr_variables(expr2, counts);
+        },
+        _ => {},
+    }
+}
+
+/* Negate the given normalized expression, cancelling double negations and
+ * folding negated constants. A negated difference becomes the difference of
+ * the swapped operands since this takes one gate instead of two. */
+fn negate_expr(expr: TExpr, field_ops: &dyn FieldOps) -> TExpr {
+    match expr.v {
+        Expr::Negate(expr1) => *expr1,
+        Expr::Constant(c) => Expr::Constant(field_ops.negate(c)).type_expr(Some(Type::Int)),
+        Expr::Infix(InfixOp::Subtract, expr1, expr2) =>
+            infix_op(InfixOp::Subtract, *expr2, *expr1),
+        v => Expr::Negate(Box::new(v.type_expr(Some(Type::Int)))).type_expr(Some(Type::Int)),
+    }
+}
+
+/* Rewrite the given arithmetic expression into a normal form in which
+ * negations are pushed into the operations that absorb them and trivial
+ * identities are removed. Variables with inlinable definitions are replaced
+ * by those definitions so that these rewrites can see through them. */
+fn normalize_expr(
+    expr: &TExpr,
+    inlinable: &mut HashMap<VariableId, TExpr>,
+    field_ops: &dyn FieldOps,
+) -> TExpr {
+    match &expr.v {
+        Expr::Variable(var) => match inlinable.remove(&var.id) {
+            Some(def) => def,
+            None => expr.clone(),
+        },
+        Expr::Negate(expr1) =>
+            negate_expr(normalize_expr(expr1, inlinable, field_ops), field_ops),
+        Expr::Infix(InfixOp::Equal, expr1, expr2) => Expr::Infix(
+            InfixOp::Equal,
+            Box::new(normalize_expr(expr1, inlinable, field_ops)),
+            Box::new(normalize_expr(expr2, inlinable, field_ops)),
+        ).type_expr(expr.t.clone()),
+        Expr::Infix(op, expr1, expr2) => {
+            let expr1 = normalize_expr(expr1, inlinable, field_ops);
+            let expr2 = normalize_expr(expr2, inlinable, field_ops);
+            match (op, expr1.v, expr2.v) {
+                // 0 - b = -b
+                (InfixOp::Subtract, Expr::Constant(c), v2) if c.is_zero() =>
+                    negate_expr(v2.type_expr(Some(Type::Int)), field_ops),
+                // a - a = 0
+                (InfixOp::Subtract, Expr::Variable(v1), Expr::Variable(v2)) if v1.id == v2.id =>
+                    Expr::Constant(Zero::zero()).type_expr(Some(Type::Int)),
+                // a + -b = a - b
+                (InfixOp::Add, v1, Expr::Negate(e2)) =>
+                    infix_op(InfixOp::Subtract, v1.type_expr(Some(Type::Int)), *e2),
+                // -a + b = b - a
+                (InfixOp::Add, Expr::Negate(e1), v2) =>
+                    infix_op(InfixOp::Subtract, v2.type_expr(Some(Type::Int)), *e1),
+                // a - -b = a + b
+                (InfixOp::Subtract, v1, Expr::Negate(e2)) =>
+                    infix_op(InfixOp::Add, v1.type_expr(Some(Type::Int)), *e2),
+                // -a * -b = a * b
+                (InfixOp::Multiply, Expr::Negate(e1), Expr::Negate(e2)) =>
+                    infix_op(InfixOp::Multiply, *e1, *e2),
+                // -a * c = a * -c
+                (InfixOp::Multiply, Expr::Negate(e1), Expr::Constant(c)) |
+                (InfixOp::Multiply, Expr::Constant(c), Expr::Negate(e1)) =>
+                    infix_op(
+                        InfixOp::Multiply,
+                        *e1,
+                        Expr::Constant(field_ops.negate(c)).type_expr(Some(Type::Int)),
+                    ),
+                (op, v1, v2) => infix_op(
+                    *op,
+                    v1.type_expr(Some(Type::Int)),
+                    v2.type_expr(Some(Type::Int)),
+                ),
+            }
+        },
+        _ => expr.clone(),
+    }
+}
+
+/* Normalize the arithmetic in the given module's definitions and constraints.
+ * Synthetic constraint definitions that are used exactly once are inlined
+ * into their use so that normalization can act across them. */
+pub fn normalize_module(
+    module: &mut Module,
+    prover_defs: &HashSet<VariableId>,
+    field_ops: &dyn FieldOps,
+) {
+    // Count the uses of each variable, distinguishing those by the prover
+    let mut uses = HashMap::new();
+    let mut prover_uses = HashMap::new();
+    for def in &module.defs {
+        match &def.0.0.v {
+            Pat::Variable(var) if prover_defs.contains(&var.id) =>
+                count_expr_variables(&def.0.1, &mut prover_uses),
+            _ => count_expr_variables(&def.0.1, &mut uses),
+        }
+    }
+    for expr in &module.exprs {
+        count_expr_variables(expr, &mut uses);
+    }
+    let pubs: HashSet<_> = module.pubs.iter().map(|var| var.id).collect();
+    let mut inlinable = HashMap::new();
+    let mut inlined = vec![];
+    let mut defs = vec![];
+    for mut def in std::mem::take(&mut module.defs) {
+        match &def.0.0.v {
+            Pat::Variable(var) if !prover_defs.contains(&var.id) => {
+                let val = normalize_expr(&def.0.1, &mut inlinable, field_ops);
+                let single_use = uses.get(&var.id) == Some(&1) &&
+                    !prover_uses.contains_key(&var.id);
+                if var.name.is_none() && single_use && !pubs.contains(&var.id) {
+                    inlinable.insert(var.id, val);
+                    inlined.push((var.id, def));
+                } else {
+                    *def.0.1 = val;
+                    defs.push(def);
+                }
+            },
+            _ => defs.push(def),
+        }
+    }
+    for expr in &mut module.exprs {
+        *expr = normalize_expr(expr, &mut inlinable, field_ops);
+    }
+    // Definitions whose single use was not found must be retained
+    module.defs = defs;
+    module.defs.extend(inlined.into_iter().filter_map(|(id, mut def)| {
+        inlinable.remove(&id).map(|val| {
+            *def.0.1 = val;
+            def
+        })
+    }));
+}
+
+/* Rewrite the given normalized arithmetic expression so that it performs
+ * cheaper operations where possible. Doubling a variable becomes an addition
+ * if the given cost model prices an addition along with the copy of the
+ * variable it needs below a multiplication, and the difference of identical
+ * terms becomes zero unless those terms constrain their variables, as division
+ * does its divisor. Trivial identities exposed by these rewrites are
+ * removed. */
+fn reduce_expr_strength(
+    expr: &TExpr,
+    field_ops: &dyn FieldOps,
+    cost_model: &dyn CostModel,
+) -> TExpr {
+    match &expr.v {
+        Expr::Negate(expr1) =>
+            Expr::Negate(Box::new(reduce_expr_strength(expr1, field_ops, cost_model))).type_expr(expr.t.clone()),
+        Expr::Infix(InfixOp::Equal, expr1, expr2) => Expr::Infix(
+            InfixOp::Equal,
+            Box::new(reduce_expr_strength(expr1, field_ops, cost_model)),
+            Box::new(reduce_expr_strength(expr2, field_ops, cost_model)),
+        ).type_expr(expr.t.clone()),
+        Expr::Infix(op, expr1, expr2) => {
+            let expr1 = reduce_expr_strength(expr1, field_ops, cost_model);
+            let expr2 = reduce_expr_strength(expr2, field_ops, cost_model);
+            let is_two = |expr: &TExpr| matches!(
+                &expr.v,
+                Expr::Constant(c) if field_ops.canonical(c.clone()) == BigInt::from(2)
+            );
+            let is_variable = |expr: &TExpr| matches!(expr.v, Expr::Variable(_));
+            let doubling =
+                cost_model.add_gate() + cost_model.copy_constraint() < cost_model.mul_gate();
+            if doubling && *op == InfixOp::Multiply && is_variable(&expr1) && is_two(&expr2) {
+                // a * 2 = a + a
+                infix_op(InfixOp::Add, expr1.clone(), expr1)
+            } else if doubling && *op == InfixOp::Multiply && is_two(&expr1) && is_variable(&expr2) {
+                // 2 * a = a + a
+                infix_op(InfixOp::Add, expr2.clone(), expr2)
+            } else if *op == InfixOp::Subtract && unconstraining(&expr1) &&
+                expr1.to_string() == expr2.to_string() {
+                // a - a = 0
+                Expr::Constant(Zero::zero()).type_expr(Some(Type::Int))
+            } else {
+                infix_op(*op, expr1, expr2)
+            }
+        },
+        _ => expr.clone(),
+    }
+}
+
+/* Check whether the given arithmetic expression can be evaluated for all
+ * values of its variables. Division requires its divisor to be non-zero, and
+ * the operations other than ring operations become gates that constrain their
+ * operands, so expressions containing them cannot be dropped. */
+fn unconstraining(expr: &TExpr) -> bool {
+    match &expr.v {
+        Expr::Variable(_) | Expr::Constant(_) => true,
+        Expr::Negate(expr1) => unconstraining(expr1),
+        Expr::Infix(InfixOp::Add | InfixOp::Subtract | InfixOp::Multiply, expr1, expr2) =>
+            unconstraining(expr1) && unconstraining(expr2),
+        Expr::Infix(InfixOp::Exponentiate, expr1, expr2) =>
+            unconstraining(expr1) && matches!(expr2.v, Expr::Constant(_)),
+        _ => false,
+    }
+}
+
+/* Reduce the strength of the operations in the given module's definitions and
+ * constraints. Definitions only used by the prover do not become gates and are
+ * hence left alone. */
+pub fn reduce_strength(
+    module: &mut Module,
+    prover_defs: &HashSet<VariableId>,
+    field_ops: &dyn FieldOps,
+    cost_model: &dyn CostModel,
+) {
+    for def in &mut module.defs {
+        match &def.0.0.v {
+            Pat::Variable(var) if !prover_defs.contains(&var.id) =>
+                *def.0.1 = reduce_expr_strength(&def.0.1, field_ops, cost_model),
+            _ => {},
+        }
+    }
+    for expr in &mut module.exprs {
+        *expr = reduce_expr_strength(expr, field_ops, cost_model);
+    }
+}
+
+/* The largest definition, in expression nodes, that is inlined into its single
+ * use when no other limit is given. */
+pub const DEFAULT_INLINE_LIMIT: usize = 8;
+
+/* Count the nodes in the given arithmetic expression. */
+fn expr_size(expr: &TExpr) -> usize {
+    match &expr.v {
+        Expr::Negate(expr1) => 1 + expr_size(expr1),
+        Expr::Infix(_, expr1, expr2) => 1 + expr_size(expr1) + expr_size(expr2),
+        _ => 1,
+    }
+}
+
+/* Substitute constraint definitions directly into their uses so that they
+ * need neither a variable nor a gate of their own. Aliases of variables and
+ * constants are always inlined, whereas other definitions are only inlined
+ * into their single use and only if they have at most the given number of
+ * nodes. Public variables are never inlined so that they remain visible to
+ * verifiers. Returns the number of definitions inlined. */
+pub fn inline_definitions(
+    module: &mut Module,
+    prover_defs: &HashSet<VariableId>,
+    inline_limit: usize,
+) -> usize {
+    let mut uses = HashMap::new();
+    for def in &module.defs {
+        count_expr_variables(&def.0.1, &mut uses);
+    }
+    for expr in &module.exprs {
+        count_expr_variables(expr, &mut uses);
+    }
+    let pubs: HashSet<_> = module.pubs.iter().map(|var| var.id).collect();
+    let mut substitutions = HashMap::new();
+    module.defs.retain(|def| match &def.0.0.v {
+        Pat::Variable(var) if !prover_defs.contains(&var.id) && !pubs.contains(&var.id) => {
+            let trivial = matches!(def.0.1.v, Expr::Variable(_) | Expr::Constant(_));
+            let small = uses.get(&var.id) == Some(&1) && expr_size(&def.0.1) <= inline_limit;
+            if !trivial && !small { return true }
+            // Synthetic variables should be named after their aliases
+            if let (Some(name), Expr::Variable(var2)) = (&var.name, &def.0.1.v) {
+                if var2.name.is_none() {
+                    module.names.entry(var2.id).or_insert(name.clone());
+                }
+            }
+            substitutions.insert(var.id, *def.0.1.clone());
+            false
+        },
+        _ => true,
+    });
+    for def in &mut module.defs {
+        copy_propagate_expr(&mut def.0.1, &substitutions);
+    }
+    for expr in &mut module.exprs {
+        copy_propagate_expr(expr, &substitutions);
+    }
+    substitutions.len()
+}
+
 /* Flatten the given binding down into the set of constraints it defines. */
 fn flatten_binding(
     pat: &TPat,
@@ -880,10 +1472,19 @@ fn flatten_binding(
     flattened: &mut Module,
 ) {
     match (&pat.v, &expr.v) {
-        (Pat::Variable(_), Expr::Function(_)) => {},
+        (Pat::Variable(_), Expr::Function(_) | Expr::Intrinsic(_)) => {},
+        (Pat::Variable(var1), Expr::Variable(var2)) => {
+            // Synthetic variables should be named after what they are bound to
+            if let (Some(name), None) = (&var1.name, &var2.name) {
+                flattened.names.entry(var2.id).or_insert(name.clone());
+            }
+            flattened.defs.push(Definition(LetBinding(
+                pat.clone(),
+                Box::new(expr.clone()),
+            )));
+        },
         (Pat::Variable(_),
-         Expr::Variable(_) | Expr::Constant(_) |
-         Expr::Infix(_, _, _) | Expr::Negate(_)) => {
+         Expr::Constant(_) | Expr::Infix(_, _, _) | Expr::Negate(_)) => {
             flattened.defs.push(Definition(LetBinding(
                 pat.clone(),
                 Box::new(expr.clone()),
@@ -978,6 +1579,11 @@ fn flatten_expr_to_3ac(
             push_constraint_def(flattened, out.clone(), rhs.type_expr(Some(Type::Int)));
             out
         },
+        (out, Expr::Infix(op @ (InfixOp::Add | InfixOp::Multiply), _, _)) => {
+            let mut operands = vec![];
+            collect_operands(*op, expr, &mut operands);
+            flatten_operands_to_3ac(out, *op, &operands, flattened, gen)
+        },
         (out, Expr::Infix(op, e1, e2)) if *op != InfixOp::Exponentiate => {
             let out1_term = flatten_expr_to_3ac(None, e1, flattened, gen);
             let out2_term = flatten_expr_to_3ac(None, e2, flattened, gen);
@@ -995,6 +1601,45 @@ fn flatten_expr_to_3ac(
     }
 }
 
+/* Collect the operands of the largest subtree of the given expression that
+ * only applies the given associative operation, from left to right. This is
+ * done without recursion since such subtrees can be very deep. */
+fn collect_operands<'a>(op: InfixOp, expr: &'a TExpr, operands: &mut Vec<&'a TExpr>) {
+    let mut stack = vec![expr];
+    while let Some(expr) = stack.pop() {
+        match &expr.v {
+            Expr::Infix(op2, e1, e2) if *op2 == op => {
+                stack.push(e2);
+                stack.push(e1);
+            },
+            _ => operands.push(expr),
+        }
+    }
+}
+
+/* Flatten the application of the given associative operation to the given
+ * operands into a balanced tree of three-address codes. The chains of
+ * definitions made this way have logarithmic rather than linear depth. */
+fn flatten_operands_to_3ac(
+    out: Option<TPat>,
+    op: InfixOp,
+    operands: &[&TExpr],
+    flattened: &mut Module,
+    gen: &mut VarGen,
+) -> TPat {
+    if let [operand] = operands {
+        return flatten_expr_to_3ac(out, operand, flattened, gen);
+    }
+    let (operands1, operands2) = operands.split_at(operands.len() / 2);
+    let out1_term = flatten_operands_to_3ac(None, op, operands1, flattened, gen);
+    let out2_term = flatten_operands_to_3ac(None, op, operands2, flattened, gen);
+    let rhs = infix_op(op, out1_term.to_expr(), out2_term.to_expr());
+    let out_var = Variable::new(gen.generate_id());
+    let out = out.unwrap_or(Pat::Variable(out_var).type_pat(Some(Type::Int)));
+    push_constraint_def(flattened, out.clone(), rhs);
+    out
+}
+
 /* Flatten the given definition into three-address form. */
 fn flatten_def_to_3ac(
     def: &Definition,
@@ -1013,6 +1658,7 @@ pub fn flatten_module_to_3ac(
     gen: &mut VarGen,
 ) {
     flattened.pubs.extend(module.pubs.clone());
+    flattened.names.extend(module.names.clone());
     for def in &module.defs {
         match &def.0.0.v {
             Pat::Variable(var) if !prover_defs.contains(&var.id) =>
@@ -1141,15 +1787,18 @@ fn expand_global_variables(
     types: &mut HashMap<VariableId, Type>,
     bindings: &HashMap<VariableId, TExpr>,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     let mut expansions = HashMap::new();
     // Use the derived type information to figure out the form of each global
-    // variable
+    // variable. Visit them in a fixed order so that the variable IDs they are
+    // expanded into do not vary between compilations.
+    let mut globals: Vec<_> = globals.iter().collect();
+    globals.sort_by_key(|(_, id)| **id);
     for (name, id) in globals {
         if !bindings.contains_key(id) {
             let mut expr = Expr::Variable(Variable { name: Some(name.clone()), id: *id })
                 .type_expr(Some(vars[id].clone()));
-            expand_expr_variables(&mut expr, &mut expansions, types, gen);
+            expand_expr_variables(&mut expr, &mut expansions, types, gen)?;
         }
     }
     // Now substitute each reference to a global variable with its inner
@@ -1160,47 +1809,617 @@ fn expand_global_variables(
     for expr in &mut module.exprs {
         copy_propagate_expr(expr, &expansions);
     }
+    Ok(())
 }
 
-/* Compile the given module down into three-address codes. */
-pub fn compile(mut module: Module, field_ops: &dyn FieldOps) -> Module {
-    let mut vg = VarGen::new();
-    let mut globals = HashMap::new();
-    let mut bindings = HashMap::new();
-    let mut prog_types = HashMap::new();
-    let mut global_types = HashMap::new();
-    register_fresh_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
-    register_iter_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
-    register_fold_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
-    number_module_variables(&mut module, &mut globals, &mut vg);
-    infer_module_types(&mut module, &globals, &mut global_types, &mut prog_types, &mut vg);
-    println!("** Inferring types...");
-    print_types(&module, &prog_types);
-    // Global variables may have further internal structure, determine this
-    // using derived type information
-    expand_global_variables(&mut module, &globals, &global_types, &mut prog_types, &bindings, &mut vg);
-    // Type information is no longer required since we do symbolic
-    // execution from now on
-    strip_module_types(&mut module);
-    let mut prover_defs = HashSet::new();
-    let mut constraints = Module::default();
-    // Start generating arithmetic constraints
-    evaluate_module(
-        &module,
-        &mut constraints,
-        &mut bindings,
-        &mut prover_defs,
-        field_ops,
-        &mut vg,
-    );
+/* The passes that the pass manager can run over a module. The first four act
+ * on arithmetic constraints, the rest on three-address codes. */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pass {
+    Normalize,
+    ReduceStrength,
+    InlineDefinitions,
+    SolveLinearConstraints,
+    CopyPropagate,
+    FoldConstants,
+    EliminateCommonSubexpressions,
+    DeduplicateConstraints,
+    EliminateDeadEqualities,
+    OrderDefinitions,
+}
+
+impl Pass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pass::Normalize => "normalize",
+            Pass::ReduceStrength => "reduce strength",
+            Pass::InlineDefinitions => "inline definitions",
+            Pass::SolveLinearConstraints => "solve linear constraints",
+            Pass::CopyPropagate => "copy propagate",
+            Pass::FoldConstants => "fold constants",
+            Pass::EliminateCommonSubexpressions => "eliminate common subexpressions",
+            Pass::DeduplicateConstraints => "deduplicate constraints",
+            Pass::EliminateDeadEqualities => "eliminate dead equalities",
+            Pass::OrderDefinitions => "order definitions",
+        }
+    }
+}
+
+/* The highest optimization level accepted by the pass manager. */
+pub const MAX_OPT_LEVEL: u8 = 2;
+
+/* Owns the choice and order of the passes that compile runs at a given
+ * optimization level, and records how long each of them took. Level 0 runs
+ * only the passes that compilation has always run, level 1 adds the cheap
+ * rewrites of arithmetic constraints, and level 2 runs every pass. */
+pub struct PassManager<'a> {
+    // Optimization level that the passes were chosen for
+    pub opt_level: u8,
+    // Largest single-use definition, in expression nodes, that gets inlined
+    pub inline_limit: usize,
+    // Costs of the target backend's constructs that guide the passes
+    pub cost_model: &'a dyn CostModel,
+    // Passes run over the arithmetic constraints, in order
+    arithmetic_passes: Vec<Pass>,
+    // Passes run over the three-address codes, in order
+    three_address_passes: Vec<Pass>,
+    // Time taken by each pass run so far, in order
+    timings: Vec<(Pass, Duration)>,
+}
+
+impl<'a> PassManager<'a> {
+    pub fn new(opt_level: u8, inline_limit: usize, cost_model: &'a dyn CostModel) -> Result<Self, CompileError> {
+        if opt_level > MAX_OPT_LEVEL {
+            return Err(CompileError::OptLevel(opt_level));
+        }
+        let mut arithmetic_passes = vec![Pass::Normalize];
+        if opt_level >= 1 {
+            arithmetic_passes.extend([Pass::ReduceStrength, Pass::InlineDefinitions]);
+        }
+        arithmetic_passes.push(Pass::SolveLinearConstraints);
+        let mut three_address_passes = vec![Pass::CopyPropagate, Pass::FoldConstants];
+        if opt_level >= 2 {
+            three_address_passes.extend([
+                Pass::EliminateCommonSubexpressions,
+                Pass::DeduplicateConstraints,
+            ]);
+        }
+        three_address_passes.extend([Pass::EliminateDeadEqualities, Pass::OrderDefinitions]);
+        Ok(Self {
+            opt_level,
+            inline_limit,
+            cost_model,
+            arithmetic_passes,
+            three_address_passes,
+            timings: vec![],
+        })
+    }
+
+    /* The time taken by each pass run so far, in the order they were run. */
+    pub fn timings(&self) -> Vec<(&'static str, Duration)> {
+        self.timings.iter().map(|(pass, time)| (pass.name(), *time)).collect()
+    }
+
+    /* Run the given pass over the given module. */
+    fn apply(
+        &self,
+        pass: Pass,
+        module: &mut Module,
+        prover_defs: &mut HashSet<VariableId>,
+        field_ops: &dyn FieldOps,
+    ) -> Result<(), CompileError> {
+        match pass {
+            Pass::Normalize => normalize_module(module, prover_defs, field_ops),
+            Pass::ReduceStrength =>
+                reduce_strength(module, prover_defs, field_ops, self.cost_model),
+            Pass::InlineDefinitions => {
+                let inlined = inline_definitions(module, prover_defs, self.inline_limit);
+                if inlined > 0 {
+                    debug!("** Inlined {} definition(s)", inlined);
+                }
+            },
+            Pass::SolveLinearConstraints =>
+                solve_linear_constraints(module, prover_defs, field_ops),
+            Pass::CopyPropagate => copy_propagate(module, prover_defs),
+            Pass::FoldConstants => fold_constants(module, prover_defs, field_ops)?,
+            Pass::EliminateCommonSubexpressions =>
+                eliminate_common_subexpressions(module, prover_defs),
+            Pass::DeduplicateConstraints => {
+                let duplicates = deduplicate_constraints(module, field_ops);
+                if duplicates > 0 {
+                    debug!("** Dropped {} duplicate constraint(s)", duplicates);
+                }
+            },
+            Pass::EliminateDeadEqualities => eliminate_dead_equalities(module),
+            Pass::OrderDefinitions => order_definitions(module)?,
+        }
+        Ok(())
+    }
+
+    /* Run the given passes over the given module, timing each of them. */
+    fn run(
+        &mut self,
+        passes: &[Pass],
+        module: &mut Module,
+        prover_defs: &mut HashSet<VariableId>,
+        field_ops: &dyn FieldOps,
+    ) -> Result<(), CompileError> {
+        for pass in passes {
+            let start = Instant::now();
+            self.apply(*pass, module, prover_defs, field_ops)?;
+            self.timings.push((*pass, start.elapsed()));
+        }
+        Ok(())
+    }
+
+    /* Run the chosen passes over the given arithmetic constraints. */
+    pub fn run_arithmetic_passes(
+        &mut self,
+        module: &mut Module,
+        prover_defs: &mut HashSet<VariableId>,
+        field_ops: &dyn FieldOps,
+    ) -> Result<(), CompileError> {
+        let passes = self.arithmetic_passes.clone();
+        self.run(&passes, module, prover_defs, field_ops)
+    }
+
+    /* Run the chosen passes over the given three-address codes. */
+    pub fn run_three_address_passes(
+        &mut self,
+        module: &mut Module,
+        prover_defs: &mut HashSet<VariableId>,
+        field_ops: &dyn FieldOps,
+    ) -> Result<(), CompileError> {
+        let passes = self.three_address_passes.clone();
+        self.run(&passes, module, prover_defs, field_ops)
+    }
+}
+
+/* The default maximum depth to which recursive definitions are unrolled. */
+pub const DEFAULT_UNROLL_LIMIT: usize = 128;
+
+/* Options controlling compilation. Start from the defaults and adjust them
+ * with the builder methods below, for example
+ * CompileOptions::default().opt_level(1).deny_warnings(true). */
+pub struct CompileOptions {
+    // Maximum depth to which recursive definitions are unrolled
+    unroll_limit: usize,
+    // Whether warnings abort compilation
+    deny_warnings: bool,
+    // Optimization level that passes are chosen for
+    opt_level: u8,
+    // Largest single-use definition, in expression nodes, that gets inlined
+    inline_limit: usize,
+    // Costs of the target backend's constructs that guide the passes
+    cost_model: Box<dyn CostModel>,
+    // Public variables fixed to constants at compile time, in order
+    defines: Vec<(String, BigInt)>,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            unroll_limit: DEFAULT_UNROLL_LIMIT,
+            deny_warnings: false,
+            opt_level: 0,
+            inline_limit: DEFAULT_INLINE_LIMIT,
+            cost_model: Box::new(GateCountCostModel),
+            defines: vec![],
+        }
+    }
+}
+
+impl CompileOptions {
+    pub fn unroll_limit(mut self, unroll_limit: usize) -> Self {
+        self.unroll_limit = unroll_limit;
+        self
+    }
+
+    pub fn deny_warnings(mut self, deny_warnings: bool) -> Self {
+        self.deny_warnings = deny_warnings;
+        self
+    }
+
+    pub fn opt_level(mut self, opt_level: u8) -> Self {
+        self.opt_level = opt_level;
+        self
+    }
+
+    pub fn inline_limit(mut self, inline_limit: usize) -> Self {
+        self.inline_limit = inline_limit;
+        self
+    }
+
+    pub fn cost_model(mut self, cost_model: Box<dyn CostModel>) -> Self {
+        self.cost_model = cost_model;
+        self
+    }
+
+    /* Fix the public variable of the given name to the given constant, so
+     * that the circuit is specialized to it. */
+    pub fn define(mut self, name: String, value: BigInt) -> Self {
+        self.defines.push((name, value));
+        self
+    }
+
+    /* Whether warnings abort compilation under these options. */
+    pub fn denies_warnings(&self) -> bool {
+        self.deny_warnings
+    }
+
+    /* Compute the statistics of the given compiled module, estimating its
+     * cost with the chosen cost model. */
+    pub fn module_stats(&self, module: &Module, source_constraints: usize) -> ModuleStats {
+        let mut stats = ModuleStats::from(module);
+        stats.source_constraints = Some(source_constraints);
+        stats.estimated_cost = Some(estimate_cost(module, &*self.cost_model));
+        stats
+    }
+}
+
+/* The result of a successful compilation. */
+pub struct CompileOutput {
+    // The module compiled down into three-address codes
+    pub module: Module,
+    // Warnings raised during compilation, in the order they were raised
+    pub diagnostics: Vec<Diagnostic>,
+    // Statistics of the compiled module, including pass timings
+    pub stats: ModuleStats,
+    // Variables whose definitions only provers use to derive witnesses, and
+    // which the passes hence leave alone
+    pub prover_defs: HashSet<VariableId>,
+}
+
+/* The reasons that compilation can fail. */
+#[derive(Debug)]
+pub enum CompileError {
+    // Warnings were raised while they were being treated as errors
+    DeniedWarnings(Vec<Diagnostic>),
+    // The given optimization level is above the highest there is
+    OptLevel(u8),
+    // A constant was given for a name that is not a public variable
+    UndeclaredDefine(String),
+    // The given operation on constants in the given expression has no value
+    FieldOp(FieldOpError, String),
+    // The given constraint equates distinct constants
+    Unsatisfiable(String),
+    // A function is left, as described, where only a value that constraints
+    // can hold may be, in the item at the given span if known
+    EscapingFunction(String, Option<SourceSpan>),
+    // Unrolling recursive definitions went deeper than the given limit along
+    // the given chain of calls, in the item at the given span if known
+    RecursionLimit { limit: usize, chain: Vec<String>, span: Option<SourceSpan> },
+    // The given definitions, each depending on the next and the last being
+    // the first again, cannot be put in an order in which they evaluate
+    CyclicDefinitions(Vec<String>),
+    // The types of the program do not fit together as described, in the item
+    // at the given span if known
+    Type(String, Option<SourceSpan>),
+    // The program cannot be evaluated into constraints for the reason
+    // described, in the item at the given span if known
+    Malformed(String, Option<SourceSpan>),
+}
+
+impl std::fmt::Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompileError::DeniedWarnings(warnings) =>
+                write!(f, "compilation aborted due to {} warning(s)", warnings.len()),
+            CompileError::OptLevel(opt_level) =>
+                write!(f, "optimization level {} exceeds the maximum of {}", opt_level, MAX_OPT_LEVEL),
+            CompileError::UndeclaredDefine(name) =>
+                write!(f, "cannot define {}, which is not a public variable", name),
+            CompileError::FieldOp(err, expr) => write!(f, "{} in {}", err, expr),
+            CompileError::Unsatisfiable(constraint) =>
+                write!(f, "constraint {} can never be satisfied", constraint),
+            CompileError::EscapingFunction(message, _) => write!(f, "{}", message),
+            CompileError::RecursionLimit { limit, chain, .. } => write!(
+                f,
+                "recursion limit of {} exceeded whilst unrolling {}, call chain: {}",
+                limit,
+                chain.last().map_or("", String::as_str),
+                chain.join(" -> "),
+            ),
+            CompileError::CyclicDefinitions(cycle) =>
+                write!(f, "definitions depend on each other cyclically: {}", cycle.join(" -> ")),
+            CompileError::Type(message, _) | CompileError::Malformed(message, _) =>
+                write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl CompileError {
+    /* Make the diagnostic reporting this error. */
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let code = match self {
+            CompileError::DeniedWarnings(_) => "denied-warnings",
+            CompileError::OptLevel(_) => "opt-level",
+            CompileError::UndeclaredDefine(_) => "undeclared-define",
+            CompileError::FieldOp(FieldOpError::DivisionByZero, _) => "division-by-zero",
+            CompileError::Unsatisfiable(_) => "unsatisfiable-constraint",
+            CompileError::EscapingFunction(_, _) => "escaping-function",
+            CompileError::RecursionLimit { .. } => "recursion-limit",
+            CompileError::CyclicDefinitions(_) => "cyclic-definitions",
+            CompileError::Type(_, _) => "type-error",
+            CompileError::Malformed(_, _) => "malformed-program",
+        };
+        let mut diagnostic = Diagnostic::error(code, self.to_string());
+        if let Some(span) = self.span() {
+            diagnostic = diagnostic.with_span(span.clone());
+        }
+        match self {
+            CompileError::DeniedWarnings(_) =>
+                diagnostic.with_note("warnings are treated as errors because of --deny-warnings".to_string()),
+            CompileError::RecursionLimit { limit, .. } =>
+                diagnostic.with_suggestion(format!(
+                    "make sure that the recursion terminates, or raise --unroll-limit above {}",
+                    limit,
+                )),
+            _ => diagnostic,
+        }
+    }
+
+    /* Where in the source this error was found, if known. */
+    pub fn span(&self) -> Option<&SourceSpan> {
+        match self {
+            CompileError::EscapingFunction(_, span) |
+            CompileError::RecursionLimit { span, .. } |
+            CompileError::Type(_, span) |
+            CompileError::Malformed(_, span) => span.as_ref(),
+            _ => None,
+        }
+    }
+
+    /* Place this error in the item of the source at the given span, unless it
+     * was already placed. */
+    pub(crate) fn locate(mut self, at: Option<&SourceSpan>) -> Self {
+        match &mut self {
+            CompileError::EscapingFunction(_, span) |
+            CompileError::RecursionLimit { span, .. } |
+            CompileError::Type(_, span) |
+            CompileError::Malformed(_, span) if span.is_none() => *span = at.cloned(),
+            _ => {},
+        }
+        self
+    }
+}
+
+/* Fix each of the given public variables to the given constant. Each becomes
+ * a definition ahead of the rest of the module rather than a public variable,
+ * so that evaluation substitutes the constant and the passes can fold away
+ * whatever it determines. */
+pub fn specialize_pubs(
+    module: &mut Module,
+    defines: &[(String, BigInt)],
+    field_ops: &dyn FieldOps,
+) -> Result<(), CompileError> {
+    for (name, value) in defines.iter().rev() {
+        let idx = module.pubs.iter()
+            .position(|var| var.name.as_ref() == Some(name))
+            .ok_or_else(|| CompileError::UndeclaredDefine(name.clone()))?;
+        let var = module.pubs.remove(idx);
+        let value = Expr::Constant(field_ops.canonical(value.clone())).type_expr(None);
+        module.defs.insert(0, Definition(LetBinding(
+            Pat::Variable(var).type_pat(None),
+            Box::new(value),
+        )));
+    }
+    Ok(())
+}
+
+/* Compile the given module down into three-address codes with the default
+ * options. Kept for callers of the original signature; it panics where
+ * compile_with gives back an error. */
+#[deprecated(note = "panics on malformed programs, use compile_with instead")]
+pub fn compile(module: Module, field_ops: &dyn FieldOps) -> Module {
+    compile_with(module, field_ops, &CompileOptions::default())
+        .unwrap_or_else(|err| panic!("{}", err))
+        .module
+}
+
+/* Compile the given module down into three-address codes with the given
+ * options, collecting the warnings raised and the statistics of the result. */
+pub fn compile_with(
+    mut module: Module,
+    field_ops: &dyn FieldOps,
+    options: &CompileOptions,
+) -> Result<CompileOutput, CompileError> {
+    let source_constraints = module.exprs.len();
+    // Literals that wrap around the modulus likely do not mean what was intended
+    let diagnostics = overflowing_constants(&module, field_ops);
+    if options.deny_warnings && !diagnostics.is_empty() {
+        return Err(CompileError::DeniedWarnings(diagnostics));
+    }
+    specialize_pubs(&mut module, &options.defines, field_ops)?;
+    let mut passes = PassManager::new(options.opt_level, options.inline_limit, &*options.cost_model)?;
+    let mut vg = VarGen::new();
+    let mut globals = HashMap::new();
+    let mut bindings = HashMap::new();
+    let mut prog_types = HashMap::new();
+    let mut global_types = HashMap::new();
+    register_fresh_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
+    register_iter_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
+    register_fold_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
+    register_map_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
+    register_zip_with_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
+    register_append_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
+    register_slice_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
+    register_len_intrinsic(&mut globals, &mut global_types, &mut bindings, &mut vg);
+    number_module_variables(&mut module, &mut globals, &mut vg);
+    infer_module_types(&mut module, &globals, &mut global_types, &mut prog_types, &mut vg)?;
+    debug!("** Inferring types...");
+    print_types(&module, &prog_types);
+    // Global variables may have further internal structure, determine this
+    // using derived type information
+    expand_global_variables(&mut module, &globals, &global_types, &mut prog_types, &bindings, &mut vg)?;
+    // Type information is no longer required since we do symbolic
+    // execution from now on
+    strip_module_types(&mut module);
+    let mut prover_defs = HashSet::new();
+    let mut constraints = Module::default();
+    let mut unroller = Unroller::new(&module, options.unroll_limit);
+    // Start generating arithmetic constraints
+    evaluate_module(
+        &module,
+        &mut constraints,
+        &mut bindings,
+        &mut prover_defs,
+        field_ops,
+        &mut unroller,
+        &mut vg,
+    )?;
     // Classify each definition that occurs in the constraints
     classify_defs(&mut constraints, &mut prover_defs);
+    // Simplify the arithmetic before it is broken down into gates, and solve
+    // for the values that provers need not supply
+    passes.run_arithmetic_passes(&mut constraints, &mut prover_defs, field_ops)?;
     let mut module_3ac = Module::default();
     flatten_module_to_3ac(&constraints, &prover_defs, &mut module_3ac, &mut vg);
-    // Start doing basic optimizations
-    copy_propagate(&mut module_3ac, &prover_defs);
-    eliminate_dead_equalities(&mut module_3ac);
-    module_3ac
+    // Name the synthetic variables before aliases are propagated away
+    name_synthetic_variables(&mut module_3ac);
+    // Start doing optimizations, leaving definitions in the order that
+    // provers derive witnesses in
+    passes.run_three_address_passes(&mut module_3ac, &mut prover_defs, field_ops)?;
+    let mut stats = options.module_stats(&module_3ac, source_constraints);
+    stats.pass_timings = passes.timings();
+    Ok(CompileOutput { module: module_3ac, diagnostics, stats, prover_defs })
+}
+
+/* Collect the integer literals occuring in the given pattern. */
+fn collect_pat_constants<'a>(pat: &'a TPat, constants: &mut Vec<&'a BigInt>) {
+    match &pat.v {
+        Pat::Constant(c) => constants.push(c),
+        Pat::As(pat1, _) => collect_pat_constants(pat1, constants),
+        Pat::Product(pat1, pat2) | Pat::Cons(pat1, pat2) => {
+            collect_pat_constants(pat1, constants);
+            collect_pat_constants(pat2, constants);
+        },
+        Pat::Unit | Pat::Nil | Pat::Variable(_) => {},
+    }
+}
+
+/* Collect the integer literals occuring in the given expression. */
+fn collect_expr_constants<'a>(expr: &'a TExpr, constants: &mut Vec<&'a BigInt>) {
+    match &expr.v {
+        Expr::Constant(c) => constants.push(c),
+        Expr::Sequence(exprs) => {
+            for expr in exprs {
+                collect_expr_constants(expr, constants);
+            }
+        },
+        Expr::Product(expr1, expr2) | Expr::Cons(expr1, expr2) |
+        Expr::Infix(_, expr1, expr2) | Expr::Application(expr1, expr2) => {
+            collect_expr_constants(expr1, constants);
+            collect_expr_constants(expr2, constants);
+        },
+        Expr::Negate(expr1) => collect_expr_constants(expr1, constants),
+        Expr::Function(fun) => {
+            for param in &fun.params {
+                collect_pat_constants(param, constants);
+            }
+            collect_expr_constants(&fun.body, constants);
+        },
+        Expr::LetBinding(binding, expr2) => {
+            collect_pat_constants(&binding.0, constants);
+            collect_expr_constants(&binding.1, constants);
+            collect_expr_constants(expr2, constants);
+        },
+        Expr::Match(matche) => {
+            collect_expr_constants(&matche.0, constants);
+            for (pat, expr2) in matche.1.iter().zip(matche.2.iter()) {
+                collect_pat_constants(pat, constants);
+                collect_expr_constants(expr2, constants);
+            }
+        },
+        Expr::Unit | Expr::Nil | Expr::Variable(_) | Expr::Intrinsic(_) => {},
+    }
+}
+
+/* Make a warning for each integer literal in the given module that does not
+ * survive reduction modulo the field's characteristic. Literals are written
+ * without a sign, negation being a separate operation, so any non-negative
+ * literal whose canonical form differs from itself must exceed the modulus. */
+pub fn overflowing_constants(module: &Module, field_ops: &dyn FieldOps) -> Vec<Diagnostic> {
+    let mut warnings = vec![];
+    let mut check = |constants: Vec<&BigInt>, location: String| {
+        for c in constants {
+            if c.is_negative() { continue }
+            let reduced = field_ops.canonical(c.clone());
+            if &reduced != c {
+                let warning = Diagnostic::warning("overflowing-constant", format!(
+                    "constant {} exceeds the field modulus and reduces to {} in {}",
+                    c, reduced, location,
+                ));
+                warnings.push(warning.with_suggestion(format!("write {} instead", reduced)));
+            }
+        }
+    };
+    for def in &module.defs {
+        let mut constants = vec![];
+        collect_pat_constants(&def.0.0, &mut constants);
+        collect_expr_constants(&def.0.1, &mut constants);
+        check(constants, format!("definition of {}", def.0.0));
+    }
+    for expr in &module.exprs {
+        let mut constants = vec![];
+        collect_expr_constants(expr, &mut constants);
+        check(constants, format!("expression {}", expr));
+    }
+    warnings
+}
+
+/* The integer nearest zero that the given element of the field with the given
+ * operations stands for. */
+fn signed_value(c: &BigInt, field_ops: &dyn FieldOps) -> BigInt {
+    let c = field_ops.canonical(c.clone());
+    let negated = field_ops.negate(c.clone());
+    if negated < c { -negated } else { c }
+}
+
+/* Apply the given function to every constant of the given 3AC expression. */
+fn map_3ac_constants(expr: &mut TExpr, f: &mut dyn FnMut(&mut BigInt)) {
+    match &mut expr.v {
+        Expr::Constant(c) => f(c),
+        Expr::Negate(expr1) => map_3ac_constants(expr1, f),
+        Expr::Infix(_, expr1, expr2) => {
+            map_3ac_constants(expr1, f);
+            map_3ac_constants(expr2, f);
+        },
+        _ => {},
+    }
+}
+
+/* Rewrite the constants of the given 3AC module, compiled over the field with
+ * the first operations, as elements of the field with the second. Each is read
+ * as the integer nearest zero that it stands for, so that negative constants
+ * keep their meaning, and must stand for the same integer in the new field.
+ * The constants that do not are returned instead. Constants whose meaning
+ * depends on the original field, like inverses folded at compile time, cannot
+ * be told apart from large integers and are carried over as integers. */
+pub fn convert_constants(
+    module: &mut Module,
+    from: &dyn FieldOps,
+    to: &dyn FieldOps,
+) -> Result<(), Vec<BigInt>> {
+    let mut lost = vec![];
+    let mut convert = |c: &mut BigInt| {
+        let value = signed_value(c, from);
+        let converted = to.canonical(value.clone());
+        if signed_value(&converted, to) == value {
+            *c = converted;
+        } else if !lost.contains(c) {
+            lost.push(c.clone());
+        }
+    };
+    for def in &mut module.defs {
+        map_3ac_constants(&mut def.0.1, &mut convert);
+    }
+    for expr in &mut module.exprs {
+        map_3ac_constants(expr, &mut convert);
+    }
+    if lost.is_empty() { Ok(()) } else { Err(lost) }
 }
 
 /* Apply all the substitutions in the given map to the given expression. */
@@ -1256,42 +2475,1220 @@ pub fn copy_propagate_def(
                 Expr::Variable(_) | Expr::Constant(_) if !prover_defs.contains(&v1.id) => {
                     substitutions.insert(v1.id, *def.0.1.clone());
                 },
-                _ => {},
+                _ => {},
+            }
+        },
+        _ => panic!("only variable patterns should be present at this stage"),
+    }
+}
+
+/* Replace all variables defined to be equal with a single representative.
+ * If a variable is proven to be equal to a constant, then replace its
+ * occurences with the constant. */
+pub fn copy_propagate(module: &mut Module, prover_defs: &HashSet<VariableId>) {
+    let mut substitutions: HashMap<VariableId, TExpr> = HashMap::new();
+    for def in &mut module.defs {
+        copy_propagate_def(def, &mut substitutions, prover_defs);
+    }
+    for expr in &mut module.exprs {
+        copy_propagate_expr(expr, &substitutions);
+    }
+}
+
+/* Eliminate equalities that are obviously true from the constraint set. This
+ * will reduce the number of gates in the circuit. */
+pub fn eliminate_dead_equalities(module: &mut Module) {
+    module.exprs.retain(|expr| {
+        match &expr.v {
+            Expr::Infix(InfixOp::Equal, expr1, expr2) if
+                matches!((&expr1.v, &expr2.v), (Expr::Constant(c1), Expr::Constant(c2)) if
+                         c1 == c2) => false,
+            Expr::Infix(InfixOp::Equal, expr1, expr2) if
+                matches!((&expr1.v, &expr2.v), (Expr::Variable(v1), Expr::Variable(v2)) if
+                         v1.id == v2.id) => false,
+            _ => {
+                true
+            },
+        }
+    });
+}
+
+/* Replace the operations in the given 3AC expression whose operands are all
+ * constants with their values. Fails if one of them has no value. */
+/* Whether the given operation has no value where its divisor is zero. */
+fn is_division(op: InfixOp) -> bool {
+    matches!(op, InfixOp::Divide | InfixOp::IntDivide | InfixOp::Modulo)
+}
+
+fn fold_expr_constants(expr: &mut TExpr, field_ops: &dyn FieldOps) -> Result<(), FieldOpError> {
+    match &mut expr.v {
+        Expr::Negate(expr1) => {
+            fold_expr_constants(expr1, field_ops)?;
+            if let Expr::Constant(c) = &expr1.v {
+                expr.v = Expr::Constant(field_ops.negate(c.clone()));
+            }
+        },
+        Expr::Infix(op, expr1, expr2) => {
+            fold_expr_constants(expr1, field_ops)?;
+            fold_expr_constants(expr2, field_ops)?;
+            match (&expr1.v, &expr2.v) {
+                (Expr::Constant(c1), Expr::Constant(c2)) if *op != InfixOp::Equal => {
+                    expr.v = Expr::Constant(field_ops.infix(*op, c1.clone(), c2.clone())?);
+                },
+                // Divisors that only became zero once constants propagated
+                (_, Expr::Constant(c2)) if is_division(*op) && field_ops.canonical(c2.clone()).is_zero() =>
+                    return Err(FieldOpError::DivisionByZero),
+                _ => {},
+            }
+        },
+        _ => {},
+    }
+    Ok(())
+}
+
+/* Fold the constant operations in the given 3AC module, propagating the
+ * definitions that fold to constants into their uses. Equalities between
+ * constants are then either dropped if they hold or rejected otherwise since
+ * no assignment could ever satisfy them. Fails, naming the definition or
+ * constraint concerned, if an operation that folds has no value. */
+pub fn fold_constants(
+    module: &mut Module,
+    prover_defs: &HashSet<VariableId>,
+    field_ops: &dyn FieldOps,
+) -> Result<(), CompileError> {
+    let mut substitutions = HashMap::new();
+    for def in &mut module.defs {
+        let original = def.clone();
+        copy_propagate_expr(&mut def.0.1, &substitutions);
+        fold_expr_constants(&mut def.0.1, field_ops)
+            .map_err(|err| CompileError::FieldOp(err, original.to_string()))?;
+        match (&def.0.0.v, &def.0.1.v) {
+            (Pat::Variable(var), Expr::Constant(_)) if !prover_defs.contains(&var.id) => {
+                substitutions.insert(var.id, *def.0.1.clone());
+            },
+            _ => {},
+        }
+    }
+    let mut error = None;
+    module.exprs.retain_mut(|expr| {
+        if error.is_some() {
+            return true;
+        }
+        let original = expr.clone();
+        copy_propagate_expr(expr, &substitutions);
+        if let Err(err) = fold_expr_constants(expr, field_ops) {
+            error = Some(CompileError::FieldOp(err, original.to_string()));
+            return true;
+        }
+        match &expr.v {
+            Expr::Infix(InfixOp::Equal, expr1, expr2) => match (&expr1.v, &expr2.v) {
+                (Expr::Constant(c1), Expr::Constant(c2)) if c1 == c2 => false,
+                (Expr::Constant(_), Expr::Constant(_)) if original.to_string() == expr.to_string() => {
+                    error = Some(CompileError::Unsatisfiable(original.to_string()));
+                    true
+                },
+                (Expr::Constant(c1), Expr::Constant(c2)) => {
+                    error = Some(CompileError::Unsatisfiable(
+                        format!("{}, which reduces to {} = {},", original, c1, c2),
+                    ));
+                    true
+                },
+                _ => true,
+            },
+            _ => true,
+        }
+    });
+    error.map_or(Ok(()), Err)
+}
+
+/* Make a key that is identical for structurally equal 3AC expressions. The
+ * operands of commutative operations are ordered so that a*b and b*a share a
+ * key. */
+fn subexpression_key(expr: &TExpr) -> String {
+    match &expr.v {
+        Expr::Infix(op @ (InfixOp::Add | InfixOp::Multiply), expr1, expr2) => {
+            let mut operands = [expr1.to_string(), expr2.to_string()];
+            operands.sort();
+            format!("({}{}{})", operands[0], op, operands[1])
+        },
+        _ => expr.to_string(),
+    }
+}
+
+/* Make all the synthetic definitions in the given 3AC module that compute the
+ * same operation on the same operands share a single variable. The duplicate
+ * definitions are removed so that the shared variable is only derived once,
+ * and the constraints that thereby become identical are merged. */
+pub fn eliminate_common_subexpressions(module: &mut Module, prover_defs: &HashSet<VariableId>) {
+    let pubs: HashSet<_> = module.pubs.iter().map(|var| var.id).collect();
+    let mut substitutions = HashMap::new();
+    let mut computed = HashMap::<String, TExpr>::new();
+    module.defs.retain_mut(|def| {
+        copy_propagate_expr(&mut def.0.1, &substitutions);
+        match (&def.0.0.v, &def.0.1.v) {
+            (Pat::Variable(var), Expr::Infix(_, _, _) | Expr::Negate(_)) if
+                !prover_defs.contains(&var.id) && !pubs.contains(&var.id) => {
+                    match computed.entry(subexpression_key(&def.0.1)) {
+                        Entry::Occupied(entry) => {
+                            substitutions.insert(var.id, entry.get().clone());
+                            false
+                        },
+                        Entry::Vacant(entry) => {
+                            entry.insert(def.0.0.to_expr());
+                            true
+                        },
+                    }
+                },
+            _ => true,
+        }
+    });
+    let mut constraints = HashSet::new();
+    module.exprs.retain_mut(|expr| {
+        copy_propagate_expr(expr, &substitutions);
+        match &expr.v {
+            Expr::Infix(InfixOp::Equal, expr1, expr2) => constraints.insert(format!(
+                "{}={}",
+                expr1,
+                subexpression_key(expr2),
+            )),
+            _ => true,
+        }
+    });
+}
+
+/* Make a key that is identical for 3AC terms that are equal up to the order
+ * of commutative operands, including the sides of equalities, and the
+ * representation of constants. Variables are told apart by their ids alone. */
+fn constraint_key(expr: &TExpr, field_ops: &dyn FieldOps) -> String {
+    match &expr.v {
+        Expr::Constant(c) => field_ops.canonical(c.clone()).to_string(),
+        Expr::Variable(var) => format!("[{}]", var.id),
+        Expr::Negate(expr1) => format!("-{}", constraint_key(expr1, field_ops)),
+        Expr::Infix(op, expr1, expr2) => {
+            let mut operands = [constraint_key(expr1, field_ops), constraint_key(expr2, field_ops)];
+            if matches!(op, InfixOp::Add | InfixOp::Multiply | InfixOp::Equal) {
+                operands.sort();
+            }
+            format!("({}{}{})", operands[0], op, operands[1])
+        },
+        _ => expr.to_string(),
+    }
+}
+
+/* Remove the constraints of the given 3AC module that duplicate an earlier
+ * constraint up to the order of commutative operands and the representation
+ * of constants. Constraints on distinct variables are never merged, so every
+ * constraint on a public variable is kept. Returns the number of constraints
+ * removed. */
+pub fn deduplicate_constraints(module: &mut Module, field_ops: &dyn FieldOps) -> usize {
+    let original = module.exprs.len();
+    let mut keys = HashSet::new();
+    module.exprs.retain(|expr| keys.insert(constraint_key(expr, field_ops)));
+    original - module.exprs.len()
+}
+
+/* Strip any auxiliary suffix from the given derived name so that names
+ * derived from other derived names do not grow without bound. */
+fn base_name(name: &str) -> &str {
+    match name.rsplit_once(".aux") {
+        Some((base, n)) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => base,
+        _ => name,
+    }
+}
+
+/* Find the name of the first named variable occuring in the given 3AC
+ * expression. */
+fn first_variable_name<'a>(
+    expr: &'a TExpr,
+    names: &'a BTreeMap<VariableId, String>,
+) -> Option<&'a str> {
+    match &expr.v {
+        Expr::Variable(var) => var.name.as_deref().or(names.get(&var.id).map(|x| x.as_str())),
+        Expr::Negate(expr1) => first_variable_name(expr1, names),
+        Expr::Infix(_, expr1, expr2) =>
+            first_variable_name(expr1, names).or(first_variable_name(expr2, names)),
+        _ => None,
+    }
+}
+
+/* Attach the names in the given map to the unnamed variables occuring in the
+ * given 3AC expression. */
+fn rename_expr_variables(expr: &mut TExpr, names: &BTreeMap<VariableId, String>) {
+    match &mut expr.v {
+        Expr::Variable(var) if var.name.is_none() =>
+            var.name = names.get(&var.id).cloned(),
+        Expr::Negate(expr1) => rename_expr_variables(expr1, names),
+        Expr::Infix(_, expr1, expr2) => {
+            rename_expr_variables(expr1, names);
+            rename_expr_variables(expr2, names);
+        },
+        _ => {},
+    }
+}
+
+/* Give every synthetic variable in the given 3AC module a human-readable name
+ * so that diagnostics can refer back to the source program. A synthetic
+ * variable inherits the name of the source variable it was bound to, or
+ * otherwise derives a name of the form x.auxN from the first named variable
+ * in its definition. The resulting names are recorded in the module. */
+pub fn name_synthetic_variables(module: &mut Module) {
+    let mut names = module.names.clone();
+    // Synthetic variables directly bound to source variables take their names
+    for def in &module.defs {
+        if let (Pat::Variable(var1), Expr::Variable(var2)) = (&def.0.0.v, &def.0.1.v) {
+            if let (Some(name), None) = (&var1.name, &var2.name) {
+                names.entry(var2.id).or_insert(name.clone());
+            }
+        }
+    }
+    // The remaining synthetic variables derive names from their definitions
+    let mut counter = 0;
+    for def in &module.defs {
+        if let Pat::Variable(var) = &def.0.0.v {
+            if var.name.is_none() && !names.contains_key(&var.id) {
+                let base = first_variable_name(&def.0.1, &names)
+                    .map(base_name)
+                    .unwrap_or("")
+                    .to_string();
+                counter += 1;
+                let name = if base.is_empty() {
+                    format!("aux{}", counter)
+                } else {
+                    format!("{}.aux{}", base, counter)
+                };
+                names.insert(var.id, name);
+            }
+        }
+    }
+    // Now attach the derived names to every variable occurence
+    for var in &mut module.pubs {
+        if var.name.is_none() {
+            var.name = names.get(&var.id).cloned();
+        }
+    }
+    for def in &mut module.defs {
+        if let Pat::Variable(var) = &mut def.0.0.v {
+            if var.name.is_none() {
+                var.name = names.get(&var.id).cloned();
+            }
+        }
+        rename_expr_variables(&mut def.0.1, &names);
+    }
+    for expr in &mut module.exprs {
+        rename_expr_variables(expr, &names);
+    }
+    module.names = names;
+}
+
+/* Check whether the given name was derived by name_synthetic_variables for an
+ * auxiliary variable. */
+pub(crate) fn is_auxiliary_name(name: &str) -> bool {
+    let suffix = match name.rsplit_once(".aux") {
+        Some((_, suffix)) => suffix,
+        None => match name.strip_prefix("aux") {
+            Some(suffix) => suffix,
+            None => return false,
+        },
+    };
+    !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit())
+}
+
+/* Describe the shape of the given 3AC constraint, that is the operation that
+ * its gate performs. */
+fn constraint_shape(expr: &TExpr) -> &'static str {
+    match &expr.v {
+        Expr::Infix(InfixOp::Equal, expr1, expr2) => match (&expr1.v, &expr2.v) {
+            (Expr::Negate(_), _) | (_, Expr::Negate(_)) => "negate",
+            (Expr::Infix(op, _, _), _) | (_, Expr::Infix(op, _, _)) => match op {
+                InfixOp::Add => "add",
+                InfixOp::Subtract => "subtract",
+                InfixOp::Multiply => "multiply",
+                InfixOp::Divide | InfixOp::DivideZ => "divide",
+                InfixOp::IntDivide | InfixOp::Modulo => "integer division",
+                InfixOp::Exponentiate => "exponentiate",
+                InfixOp::Equal => "other",
+            },
+            (Expr::Constant(_), _) | (_, Expr::Constant(_)) => "equal constant",
+            (Expr::Variable(_), Expr::Variable(_)) => "equal variable",
+            _ => "other",
+        },
+        _ => "other",
+    }
+}
+
+/* Estimate the total cost of the given compiled module under the given cost
+ * model. Each constraint occupies a gate, each further occurence of a variable
+ * in the constraints needs a copy constraint, and each public variable must be
+ * exposed. */
+pub fn estimate_cost(module: &Module, cost_model: &dyn CostModel) -> u64 {
+    let mut cost = 0;
+    let mut occurences = HashMap::new();
+    for expr in &module.exprs {
+        cost += match constraint_shape(expr) {
+            "multiply" | "divide" | "integer division" | "exponentiate" => cost_model.mul_gate(),
+            _ => cost_model.add_gate(),
+        };
+        count_expr_variables(expr, &mut occurences);
+    }
+    let copies: usize = occurences.values().map(|count| count - 1).sum();
+    cost += copies as u64 * cost_model.copy_constraint();
+    cost += module.pubs.len() as u64 * cost_model.public_input();
+    cost
+}
+
+/* Summary statistics describing the size and shape of a compiled module. */
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ModuleStats {
+    // Number of constraints written in the source program
+    pub source_constraints: Option<usize>,
+    // Number of constraints in three-address form
+    pub constraints: usize,
+    // Number of variables introduced by the compiler
+    pub auxiliary_variables: usize,
+    // Length of the longest chain of definitions depending on each other
+    pub definition_depth: usize,
+    // Number of public variables
+    pub pubs: usize,
+    // Number of constraints of each shape
+    pub shapes: BTreeMap<&'static str, usize>,
+    // Base 2 logarithm of the padded circuit size chosen by the backend
+    pub k: Option<u32>,
+    // Time taken by each compiler pass, in the order they were run
+    pub pass_timings: Vec<(&'static str, Duration)>,
+    // Total cost of the circuit according to the backend's cost model
+    pub estimated_cost: Option<u64>,
+}
+
+/* Compute the length of the longest chain of definitions in the given module
+ * in which each definition uses the variable defined by the previous one. The
+ * definitions are assumed to be ordered so that variables are defined before
+ * they are used. */
+fn definition_depth(module: &Module) -> usize {
+    let mut depths = HashMap::new();
+    let mut max_depth = 0;
+    for def in &module.defs {
+        if let Pat::Variable(var) = &def.0.0.v {
+            let mut vars = HashMap::new();
+            collect_expr_variables(&def.0.1, &mut vars);
+            let depth = 1 + vars
+                .keys()
+                .filter_map(|id| depths.get(id))
+                .max()
+                .unwrap_or(&0);
+            depths.insert(var.id, depth);
+            max_depth = max_depth.max(depth);
+        }
+    }
+    max_depth
+}
+
+impl From<&Module> for ModuleStats {
+    fn from(module: &Module) -> Self {
+        let mut shapes = BTreeMap::new();
+        for expr in &module.exprs {
+            *shapes.entry(constraint_shape(expr)).or_insert(0) += 1;
+        }
+        let auxiliary_variables = ordered_module_variables(module)
+            .iter()
+            .filter(|var| var.name.as_deref().map_or(true, is_auxiliary_name))
+            .count();
+        Self {
+            source_constraints: None,
+            constraints: module.exprs.len(),
+            auxiliary_variables,
+            definition_depth: definition_depth(module),
+            pubs: module.pubs.len(),
+            shapes,
+            k: None,
+            pass_timings: vec![],
+            estimated_cost: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ModuleStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(source_constraints) = self.source_constraints {
+            writeln!(f, "** Source constraints: {}", source_constraints)?;
+        }
+        writeln!(f, "** Three-address constraints: {}", self.constraints)?;
+        for (shape, count) in &self.shapes {
+            writeln!(f, "*** {}: {}", shape, count)?;
+        }
+        writeln!(f, "** Auxiliary variables: {}", self.auxiliary_variables)?;
+        writeln!(f, "** Definition depth: {}", self.definition_depth)?;
+        write!(f, "** Public variables: {}", self.pubs)?;
+        if let Some(k) = self.k {
+            write!(f, "\n** Circuit size: 2^{} = {} rows", k, 1usize << k)?;
+        }
+        if let Some(cost) = self.estimated_cost {
+            write!(f, "\n** Estimated cost: {}", cost)?;
+        }
+        if !self.pass_timings.is_empty() {
+            write!(f, "\n** Pass timings:")?;
+            for (pass, time) in &self.pass_timings {
+                write!(f, "\n*** {}: {:.2?}", pass, time)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/* Check whether the value of the given variable is derived from that of the
+ * given target by way of the given definition dependencies. */
+fn depends_on(
+    var: VariableId,
+    target: VariableId,
+    dependencies: &HashMap<VariableId, Vec<VariableId>>,
+) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack = vec![var];
+    while let Some(var) = stack.pop() {
+        if var == target { return true }
+        if visited.insert(var) {
+            stack.extend(dependencies.get(&var).into_iter().flatten());
+        }
+    }
+    false
+}
+
+/* Rearrange the constraint lhs = rhs, where the given variable occurs exactly
+ * once in rhs, into an expression for that variable by undoing the operations
+ * applied to it. This only succeeds if the constraint is linear in the
+ * variable, and division is only undone for nonzero constant factors. */
+fn solve_for_variable(
+    lhs: TExpr,
+    rhs: &TExpr,
+    var: &Variable,
+    field_ops: &dyn FieldOps,
+) -> Option<TExpr> {
+    let contains = |expr: &TExpr| {
+        let mut vars = HashMap::new();
+        collect_expr_variables(expr, &mut vars);
+        vars.contains_key(&var.id)
+    };
+    let nonzero = |expr: &TExpr| matches!(
+        &expr.v,
+        Expr::Constant(c) if !field_ops.canonical(c.clone()).is_zero()
+    );
+    match &rhs.v {
+        Expr::Variable(v) if v.id == var.id => Some(lhs),
+        Expr::Negate(e1) =>
+            solve_for_variable(negate_expr(lhs, field_ops), e1, var, field_ops),
+        Expr::Infix(InfixOp::Add, e1, e2) if contains(e1) =>
+            solve_for_variable(infix_op(InfixOp::Subtract, lhs, *e2.clone()), e1, var, field_ops),
+        Expr::Infix(InfixOp::Add, e1, e2) =>
+            solve_for_variable(infix_op(InfixOp::Subtract, lhs, *e1.clone()), e2, var, field_ops),
+        Expr::Infix(InfixOp::Subtract, e1, e2) if contains(e1) =>
+            solve_for_variable(infix_op(InfixOp::Add, lhs, *e2.clone()), e1, var, field_ops),
+        Expr::Infix(InfixOp::Subtract, e1, e2) =>
+            solve_for_variable(infix_op(InfixOp::Subtract, *e1.clone(), lhs), e2, var, field_ops),
+        Expr::Infix(InfixOp::Multiply, e1, e2) if contains(e1) && nonzero(e2) =>
+            solve_for_variable(infix_op(InfixOp::Divide, lhs, *e2.clone()), e1, var, field_ops),
+        Expr::Infix(InfixOp::Multiply, e1, e2) if contains(e2) && nonzero(e1) =>
+            solve_for_variable(infix_op(InfixOp::Divide, lhs, *e1.clone()), e2, var, field_ops),
+        Expr::Infix(InfixOp::Divide, e1, e2) if contains(e1) && nonzero(e2) =>
+            solve_for_variable(infix_op(InfixOp::Multiply, lhs, *e2.clone()), e1, var, field_ops),
+        _ => None,
+    }
+}
+
+/* Give definitions to the undefined variables of the given module whose
+ * values are forced by a linear constraint in which they are the only
+ * undefined variable. The other variables of such a constraint must not
+ * themselves be derived from the forced variable. This is repeated until no
+ * more variables can be solved for so that chains of forced values are also
+ * derived. The new definitions are only used by the prover to derive
+ * witnesses, and the remaining undefined variables are left as inputs. */
+pub fn solve_linear_constraints(
+    module: &mut Module,
+    prover_defs: &mut HashSet<VariableId>,
+    field_ops: &dyn FieldOps,
+) {
+    let mut dependencies = HashMap::new();
+    for def in &module.defs {
+        if let Pat::Variable(var) = &def.0.0.v {
+            let mut vars = HashMap::new();
+            collect_expr_variables(&def.0.1, &mut vars);
+            dependencies.insert(var.id, vars.into_keys().collect::<Vec<_>>());
+        }
+    }
+    let mut progress = true;
+    while progress {
+        progress = false;
+        for expr in &module.exprs {
+            let (lhs, rhs) = match &expr.v {
+                Expr::Infix(InfixOp::Equal, lhs, rhs) => (lhs, rhs),
+                _ => continue,
+            };
+            let mut vars = HashMap::new();
+            collect_expr_variables(expr, &mut vars);
+            let unknowns: Vec<_> = vars
+                .values()
+                .filter(|var| !dependencies.contains_key(&var.id))
+                .cloned()
+                .collect();
+            let unknown = match &unknowns[..] {
+                [unknown] => unknown.clone(),
+                _ => continue,
+            };
+            let mut occurences = HashMap::new();
+            count_expr_variables(expr, &mut occurences);
+            if occurences[&unknown.id] != 1 || vars.keys().any(|var| {
+                *var != unknown.id && depends_on(*var, unknown.id, &dependencies)
+            }) {
+                continue;
+            }
+            // Move the side containing the unknown to the right
+            let mut lhs_vars = HashMap::new();
+            collect_expr_variables(lhs, &mut lhs_vars);
+            let (lhs, rhs) = if lhs_vars.contains_key(&unknown.id) { (rhs, lhs) } else { (lhs, rhs) };
+            if let Some(mut val) = solve_for_variable(*lhs.clone(), rhs, &unknown, field_ops) {
+                // Leave constraints whose solutions have no value to be
+                // reported by constant folding
+                if fold_expr_constants(&mut val, field_ops).is_err() {
+                    continue;
+                }
+                vars.remove(&unknown.id);
+                dependencies.insert(unknown.id, vars.into_keys().collect());
+                prover_defs.insert(unknown.id);
+                module.defs.push(Definition(LetBinding(
+                    Pat::Variable(unknown).type_pat(Some(Type::Int)),
+                    Box::new(val),
+                )));
+                progress = true;
+            }
+        }
+    }
+}
+
+/* Order the definitions of the given module so that every definition comes
+ * after the definitions of the variables that it depends on. Definitions that
+ * are already in order keep their relative positions. Fails with the
+ * variables involved if the definitions depend on each other cyclically. */
+pub fn order_definitions(module: &mut Module) -> Result<(), CompileError> {
+    // Find the definitions of each variable
+    let mut definers: HashMap<VariableId, Vec<usize>> = HashMap::new();
+    for (idx, def) in module.defs.iter().enumerate() {
+        if let Pat::Variable(var) = &def.0.0.v {
+            definers.entry(var.id).or_default().push(idx);
+        }
+    }
+    // Now make edges from each definition to the definitions that use it
+    let mut dependents = vec![vec![]; module.defs.len()];
+    let mut dependencies = vec![vec![]; module.defs.len()];
+    for (idx, def) in module.defs.iter().enumerate() {
+        let mut vars = HashMap::new();
+        collect_expr_variables(&def.0.1, &mut vars);
+        for var in vars.keys() {
+            for dep in definers.get(var).into_iter().flatten() {
+                dependents[*dep].push(idx);
+                dependencies[idx].push(*dep);
+            }
+        }
+    }
+    // Repeatedly emit the earliest definition whose dependencies are satisfied
+    let mut pending: Vec<_> = dependencies.iter().map(Vec::len).collect();
+    let mut ready: BTreeSet<_> = (0..pending.len()).filter(|idx| pending[*idx] == 0).collect();
+    let mut order = Vec::with_capacity(pending.len());
+    while let Some(idx) = ready.pop_first() {
+        order.push(idx);
+        for dependent in &dependents[idx] {
+            pending[*dependent] -= 1;
+            if pending[*dependent] == 0 {
+                ready.insert(*dependent);
+            }
+        }
+    }
+    if order.len() < module.defs.len() {
+        // Every remaining definition waits on another remaining one, so
+        // following dependencies from any of them must eventually loop
+        let mut path = vec![];
+        let mut visited = HashMap::new();
+        let mut idx = (0..pending.len()).find(|idx| pending[*idx] > 0).unwrap();
+        while !visited.contains_key(&idx) {
+            visited.insert(idx, path.len());
+            path.push(idx);
+            idx = *dependencies[idx].iter().find(|dep| pending[**dep] > 0).unwrap();
+        }
+        path.push(idx);
+        let cycle: Vec<_> = path[visited[&idx]..]
+            .iter()
+            .map(|idx| module.defs[*idx].0.0.to_string())
+            .collect();
+        return Err(CompileError::CyclicDefinitions(cycle));
+    }
+    let mut defs: Vec<_> = std::mem::take(&mut module.defs).into_iter().map(Some).collect();
+    module.defs = order.into_iter().map(|idx| defs[idx].take().unwrap()).collect();
+    Ok(())
+}
+
+/* Evaluate the given arithmetic expression over the field using the given
+ * variable assignments. Fails if a variable has no value or if an operation
+ * has none, as when dividing by zero. */
+pub fn evaluate_arithmetic(
+    expr: &TExpr,
+    assigns: &HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Result<BigInt, EvalError> {
+    match &expr.v {
+        Expr::Constant(c) => Ok(field_ops.canonical(c.clone())),
+        Expr::Variable(var) => assigns.get(&var.id)
+            .cloned()
+            .ok_or_else(|| EvalError::undefined(var, None)),
+        Expr::Negate(expr1) => Ok(field_ops.negate(evaluate_arithmetic(expr1, assigns, field_ops)?)),
+        Expr::Infix(InfixOp::Equal, _, _) => unreachable!("cannot evaluate equals expression"),
+        Expr::Infix(op, expr1, expr2) => field_ops.infix(
+            *op,
+            evaluate_arithmetic(expr1, assigns, field_ops)?,
+            evaluate_arithmetic(expr2, assigns, field_ops)?,
+        ).map_err(|err| EvalError::FieldOp(err, expr.to_string())),
+        _ => unreachable!("encountered unexpected expression: {}", expr),
+    }
+}
+
+/* Derive the values of the defined variables of the given module from the
+ * given inputs. The definitions are evaluated in order, so they should have
+ * been ordered by order_definitions. Variables that were supplied as inputs
+ * are not derived again. Fails, naming the definition concerned, if a
+ * definition needs a variable without a value or has no value itself. */
+pub fn derive_witnesses(
+    module: &Module,
+    assigns: &mut HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Result<(), EvalError> {
+    for val in assigns.values_mut() {
+        *val = field_ops.canonical(val.clone());
+    }
+    for def in &module.defs {
+        if let Pat::Variable(var) = &def.0.0.v {
+            if !assigns.contains_key(&var.id) {
+                let val = evaluate_arithmetic(&def.0.1, assigns, field_ops)
+                    .map_err(|err| err.within(input_name(var)))?;
+                assigns.insert(var.id, val);
+            }
+        }
+    }
+    Ok(())
+}
+
+/* Evaluate the given arithmetic expression under the given assignment, or
+ * return None if it refers to an unassigned variable or divides by zero. */
+pub fn try_evaluate_arithmetic(
+    expr: &TExpr,
+    assigns: &HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Option<BigInt> {
+    match &expr.v {
+        Expr::Constant(c) => Some(field_ops.canonical(c.clone())),
+        Expr::Variable(var) => assigns.get(&var.id).cloned(),
+        Expr::Negate(expr1) =>
+            try_evaluate_arithmetic(expr1, assigns, field_ops).map(|val| field_ops.negate(val)),
+        Expr::Infix(InfixOp::Equal, _, _) => None,
+        Expr::Infix(op, expr1, expr2) => {
+            let val1 = try_evaluate_arithmetic(expr1, assigns, field_ops)?;
+            let val2 = try_evaluate_arithmetic(expr2, assigns, field_ops)?;
+            field_ops.infix(*op, val1, val2).ok()
+        },
+        _ => None,
+    }
+}
+
+/* Derive the values of as many defined variables of the given module as the
+ * given partial inputs allow, and return the defined variables whose values
+ * could not be derived. */
+pub fn derive_available_witnesses(
+    module: &Module,
+    assigns: &mut HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Vec<Variable> {
+    for val in assigns.values_mut() {
+        *val = field_ops.canonical(val.clone());
+    }
+    let mut underivable = vec![];
+    for def in &module.defs {
+        if let Pat::Variable(var) = &def.0.0.v {
+            if !assigns.contains_key(&var.id) {
+                match try_evaluate_arithmetic(&def.0.1, assigns, field_ops) {
+                    Some(val) => { assigns.insert(var.id, val); },
+                    None => underivable.push(var.clone()),
+                }
+            }
+        }
+    }
+    underivable
+}
+
+/* Check whether the given constraint is violated by the given assignment.
+ * Constraints that cannot be evaluated, such as those dividing by zero, are
+ * violated since no value satisfies them. */
+fn violates(
+    expr: &TExpr,
+    assigns: &HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> bool {
+    match &expr.v {
+        Expr::Infix(InfixOp::Equal, lhs, rhs) => match (
+            evaluate_arithmetic(lhs, assigns, field_ops),
+            evaluate_arithmetic(rhs, assigns, field_ops),
+        ) {
+            (Ok(lhs), Ok(rhs)) => lhs != rhs,
+            _ => true,
+        },
+        _ => false,
+    }
+}
+
+/* Find the first constraint of the given module that the given assignment
+ * of all its variables violates. */
+pub fn violated_constraint<'a>(
+    module: &'a Module,
+    assigns: &HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Option<&'a TExpr> {
+    module.exprs.iter().find(|expr| violates(expr, assigns, field_ops))
+}
+
+/* Find every constraint of the given module that the given assignment of all
+ * its variables violates, in order. */
+pub fn violated_constraints<'a>(
+    module: &'a Module,
+    assigns: &HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Vec<&'a TExpr> {
+    module.exprs.iter().filter(|expr| violates(expr, assigns, field_ops)).collect()
+}
+
+/* The reasons that the values of the variables of a module cannot be
+ * derived from the inputs given for it. */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvalError {
+    // The given variable has neither a definition nor an input, yet the value
+    // of the given variable or constraint, if any, needs it
+    Undefined { id: VariableId, name: String, referrer: Option<String> },
+    // The given constraint is not satisfied by the derived values
+    Unsatisfied(String),
+    // The given operation on values in the given expression has no value
+    FieldOp(FieldOpError, String),
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvalError::Undefined { name, referrer: Some(referrer), .. } => write!(
+                f,
+                "no definition or input provided for variable `{}`, referenced while computing `{}`",
+                name, referrer,
+            ),
+            EvalError::Undefined { name, referrer: None, .. } =>
+                write!(f, "no definition or input provided for variable `{}`", name),
+            EvalError::Unsatisfied(expr) =>
+                write!(f, "constraint {} is not satisfied by the supplied inputs", expr),
+            EvalError::FieldOp(err, expr) => write!(f, "{} in {}", err, expr),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl EvalError {
+    /* The error for the given variable, which has no value, needed by the
+     * given variable or constraint, if any. */
+    pub fn undefined(var: &Variable, referrer: Option<String>) -> Self {
+        EvalError::Undefined { id: var.id, name: input_name(var), referrer }
+    }
+
+    /* Attribute this error to computing the given variable, unless it was
+     * already attributed. */
+    fn within(self, name: String) -> Self {
+        match self {
+            EvalError::Undefined { id, name: undefined, referrer: None } =>
+                EvalError::Undefined { id, name: undefined, referrer: Some(name) },
+            EvalError::FieldOp(err, expr) =>
+                EvalError::FieldOp(err, format!("{} while computing `{}`", expr, name)),
+            err => err,
+        }
+    }
+}
+
+/* Check that every variable that the definitions and constraints of the given
+ * module refer to is either given a value by the given assignment or defined
+ * before it is needed, so that deriving witnesses from the assignment never
+ * meets a variable without a value. */
+fn check_definitions_complete(
+    module: &Module,
+    assigns: &HashMap<VariableId, BigInt>,
+) -> Result<(), EvalError> {
+    let mut available: HashSet<_> = assigns.keys().copied().collect();
+    let missing = |expr: &TExpr, available: &HashSet<VariableId>| {
+        let mut vars = OrderedVariables::default();
+        collect_expr_variables(expr, &mut vars);
+        vars.vars.into_iter().find(|var| !available.contains(&var.id))
+    };
+    for def in &module.defs {
+        if let Pat::Variable(var) = &def.0.0.v {
+            if !available.contains(&var.id) {
+                if let Some(undefined) = missing(&def.0.1, &available) {
+                    return Err(EvalError::undefined(&undefined, Some(input_name(var))));
+                }
+                available.insert(var.id);
+            }
+        }
+    }
+    for expr in &module.exprs {
+        if let Some(undefined) = missing(expr, &available) {
+            return Err(EvalError::undefined(&undefined, Some(expr.to_string())));
+        }
+    }
+    Ok(())
+}
+
+/* Derive the values of the defined variables of the given module from the
+ * given inputs, and then check that these satisfy every constraint. Fails if
+ * a variable has neither a definition nor an input, or if a constraint is
+ * violated. */
+pub fn solve_definitions(
+    module: &Module,
+    assigns: &mut HashMap<VariableId, BigInt>,
+    field_ops: &dyn FieldOps,
+) -> Result<(), EvalError> {
+    check_definitions_complete(module, assigns)?;
+    derive_witnesses(module, assigns, field_ops)?;
+    // Report the first constraint that the derived witnesses violate
+    match violated_constraint(module, assigns, field_ops) {
+        Some(expr) => Err(EvalError::Unsatisfied(expr.to_string())),
+        None => Ok(()),
+    }
+}
+
+/* Get the variables of the given module that provers must supply, that is
+ * those that are not derived from definitions, in the order of their first
+ * occurence. */
+pub fn required_inputs(module: &Module) -> Vec<Variable> {
+    // Defined variables should not be requested from user
+    let mut defined = HashSet::new();
+    for def in &module.defs {
+        if let Pat::Variable(var) = &def.0.0.v {
+            defined.insert(var.id);
+        }
+    }
+    let mut input_variables = ordered_module_variables(module);
+    input_variables.retain(|var| !defined.contains(&var.id));
+    input_variables
+}
+
+/* Get the name by which the given input variable is supplied. */
+pub fn input_name(var: &Variable) -> String {
+    var.name.clone().unwrap_or_else(|| var.to_string())
+}
+
+/* Draw a uniformly random element of the field described by the given
+ * operations. Sampling twice as many bits as any supported modulus makes the
+ * bias of the reduction negligible. */
+fn random_field_element(field_ops: &dyn FieldOps) -> BigInt {
+    let mut bytes = [0u8; 64];
+    OsRng.fill_bytes(&mut bytes);
+    field_ops.canonical(BigInt::from_bytes_le(Sign::Plus, &bytes))
+}
+
+/* A partial bijection between the variables of two modules. Bindings are
+ * recorded on a trail so that failed matches can be undone. */
+#[derive(Default)]
+struct Renaming {
+    forward: HashMap<VariableId, VariableId>,
+    backward: HashMap<VariableId, VariableId>,
+    trail: Vec<(VariableId, VariableId)>,
+}
+
+impl Renaming {
+    /* Identify the given variables, failing if either is already identified
+     * with some other variable. */
+    fn bind(&mut self, var1: &Variable, var2: &Variable) -> bool {
+        match (self.forward.get(&var1.id), self.backward.get(&var2.id)) {
+            (None, None) => {
+                self.forward.insert(var1.id, var2.id);
+                self.backward.insert(var2.id, var1.id);
+                self.trail.push((var1.id, var2.id));
+                true
+            },
+            (Some(id2), Some(id1)) => *id2 == var2.id && *id1 == var1.id,
+            _ => false,
+        }
+    }
+
+    /* Forget every binding made since the trail had the given length. */
+    fn undo(&mut self, mark: usize) {
+        for (id1, id2) in self.trail.drain(mark..) {
+            self.forward.remove(&id1);
+            self.backward.remove(&id2);
+        }
+    }
+
+    /* Check whether the given 3AC patterns are equal under this renaming,
+     * extending it as necessary. */
+    fn match_pat(&mut self, pat1: &TPat, pat2: &TPat, field_ops: &dyn FieldOps) -> bool {
+        match (&pat1.v, &pat2.v) {
+            (Pat::Variable(var1), Pat::Variable(var2)) => self.bind(var1, var2),
+            (Pat::Constant(c1), Pat::Constant(c2)) =>
+                field_ops.canonical(c1.clone()) == field_ops.canonical(c2.clone()),
+            _ => false,
+        }
+    }
+
+    /* Check whether the given 3AC expressions are equal under this renaming,
+     * extending it as necessary. The operands of commutative operations may
+     * occur in either order. */
+    fn match_expr(&mut self, expr1: &TExpr, expr2: &TExpr, field_ops: &dyn FieldOps) -> bool {
+        match (&expr1.v, &expr2.v) {
+            (Expr::Variable(var1), Expr::Variable(var2)) => self.bind(var1, var2),
+            (Expr::Constant(c1), Expr::Constant(c2)) =>
+                field_ops.canonical(c1.clone()) == field_ops.canonical(c2.clone()),
+            (Expr::Negate(expr1), Expr::Negate(expr2)) =>
+                self.match_expr(expr1, expr2, field_ops),
+            (Expr::Infix(op1, expr11, expr12), Expr::Infix(op2, expr21, expr22)) if op1 == op2 => {
+                let mark = self.trail.len();
+                if self.match_expr(expr11, expr21, field_ops) &&
+                    self.match_expr(expr12, expr22, field_ops) {
+                    return true;
+                }
+                self.undo(mark);
+                matches!(op1, InfixOp::Add | InfixOp::Multiply | InfixOp::Equal) &&
+                    self.match_expr(expr11, expr22, field_ops) &&
+                    self.match_expr(expr12, expr21, field_ops)
+            },
+            _ => false,
+        }
+    }
+}
+
+/* Check whether the given 3AC modules are identical up to a renaming of their
+ * variables. Public variables, definitions, and constraints are compared in
+ * order. */
+fn isomorphic(a: &Module, b: &Module, field_ops: &dyn FieldOps) -> bool {
+    if a.pubs.len() != b.pubs.len() || a.defs.len() != b.defs.len() ||
+        a.exprs.len() != b.exprs.len() {
+        return false;
+    }
+    let mut renaming = Renaming::default();
+    a.pubs.iter().zip(&b.pubs).all(|(var1, var2)| renaming.bind(var1, var2)) &&
+        a.defs.iter().zip(&b.defs).all(|(def1, def2)| {
+            renaming.match_pat(&def1.0.0, &def2.0.0, field_ops) &&
+                renaming.match_expr(&def1.0.1, &def2.0.1, field_ops)
+        }) &&
+        a.exprs.iter().zip(&b.exprs).all(|(expr1, expr2)| {
+            renaming.match_expr(expr1, expr2, field_ops)
+        })
+}
+
+/* The outcome of comparing the relations defined by two compiled modules. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EquivalenceReport {
+    // The modules are identical up to a renaming of their variables
+    Isomorphic,
+    // No sampled assignment satisfied exactly one of the modules. Only the
+    // samples satisfying both modules say much about their equivalence.
+    Undistinguished { samples: usize, satisfied: usize },
+    // The given named assignment satisfies exactly one of the modules
+    Distinguished { assignment: BTreeMap<String, BigInt>, satisfies_first: bool },
+}
+
+impl std::fmt::Display for EquivalenceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Isomorphic =>
+                write!(f, "** Modules are identical up to a renaming of variables"),
+            Self::Undistinguished { samples, satisfied } => write!(
+                f,
+                "** No distinguishing assignment found in {} samples, {} of which satisfied both modules",
+                samples, satisfied,
+            ),
+            Self::Distinguished { assignment, satisfies_first } => {
+                let (yes, no) = if *satisfies_first { ("first", "second") } else { ("second", "first") };
+                write!(f, "** The following assignment satisfies the {} module but not the {}:", yes, no)?;
+                for (name, val) in assignment {
+                    write!(f, "\n*** {} = {}", name, val)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+/* A 3AC module prepared for sampling satisfying assignments. Random values
+ * for all of its inputs would rarely satisfy a constraint relating several of
+ * them, so inputs are instead fixed one at a time, each time deriving the
+ * inputs that the constraints then force. */
+struct Sampler {
+    // The module with definitions for the inputs forced by its constraints
+    module: Module,
+    // The inputs that are not forced and are hence drawn at random
+    free: Vec<Variable>,
+    // All the inputs of the original module
+    inputs: Vec<Variable>,
+}
+
+impl Sampler {
+    fn new(module: &Module, field_ops: &dyn FieldOps) -> Result<Self, CompileError> {
+        let mut inputs = required_inputs(module);
+        inputs.sort_by_key(|var| var.id);
+        let mut module = module.clone();
+        let mut prover_defs = HashSet::new();
+        let mut free = vec![];
+        loop {
+            solve_linear_constraints(&mut module, &mut prover_defs, field_ops);
+            let remaining = required_inputs(&module);
+            let remaining_ids: HashSet<_> = remaining.iter().map(|var| var.id).collect();
+            // Fix the earliest input of the first constraint relating several
+            let next = module.exprs.iter().find_map(|expr| {
+                let mut vars = HashMap::new();
+                collect_expr_variables(expr, &mut vars);
+                let mut unknowns: Vec<_> = vars.into_values()
+                    .filter(|var| remaining_ids.contains(&var.id))
+                    .collect();
+                unknowns.sort_by_key(|var| var.id);
+                if unknowns.len() > 1 { unknowns.into_iter().next() } else { None }
+            });
+            match next {
+                Some(var) => {
+                    // A placeholder definition marks the input as known
+                    module.defs.push(Definition(LetBinding(
+                        Pat::Variable(var.clone()).type_pat(Some(Type::Int)),
+                        Box::new(Expr::Variable(var.clone()).type_expr(Some(Type::Int))),
+                    )));
+                    free.push(var);
+                },
+                None => {
+                    free.extend(remaining);
+                    break;
+                },
             }
-        },
-        _ => panic!("only variable patterns should be present at this stage"),
+        }
+        // The placeholders refer to themselves and cannot be ordered
+        let free_ids: HashSet<_> = free.iter().map(|var| var.id).collect();
+        module.defs.retain(|def| !matches!(&def.0.0.v, Pat::Variable(var) if free_ids.contains(&var.id)));
+        order_definitions(&mut module)?;
+        free.sort_by_key(|var| var.id);
+        Ok(Self { module, free, inputs })
+    }
+
+    /* Draw an assignment to the variables of this module. Public variables
+     * and inputs named in the given map take the given values, the remaining
+     * free inputs take random values, and all other variables are derived
+     * from these where they can be. Those that cannot, as when dividing by
+     * zero, are left without values so that the constraints on them are
+     * violated. */
+    fn sample(
+        &self,
+        fixed: &BTreeMap<String, BigInt>,
+        field_ops: &dyn FieldOps,
+    ) -> HashMap<VariableId, BigInt> {
+        let mut assigns = HashMap::new();
+        for var in self.module.pubs.iter().chain(&self.inputs) {
+            if let Some(val) = fixed.get(&input_name(var)) {
+                assigns.insert(var.id, val.clone());
+            }
+        }
+        for var in &self.free {
+            assigns.entry(var.id).or_insert_with(|| random_field_element(field_ops));
+        }
+        derive_available_witnesses(&self.module, &mut assigns, field_ops);
+        assigns
+    }
+
+    /* Get the values of the public variables and inputs of this module in the
+     * given assignment by name, omitting those without values. */
+    fn named_values(&self, assigns: &HashMap<VariableId, BigInt>) -> BTreeMap<String, BigInt> {
+        self.module.pubs.iter().chain(&self.inputs)
+            .filter_map(|var| Some((input_name(var), assigns.get(&var.id)?.clone())))
+            .collect()
     }
 }
 
-/* Replace all variables defined to be equal with a single representative.
- * If a variable is proven to be equal to a constant, then replace its
- * occurences with the constant. */
-pub fn copy_propagate(module: &mut Module, prover_defs: &HashSet<VariableId>) {
-    let mut substitutions: HashMap<VariableId, TExpr> = HashMap::new();
-    for def in &mut module.defs {
-        copy_propagate_def(def, &mut substitutions, prover_defs);
+/* Check whether the given 3AC modules define the same relation. Modules that
+ * are identical up to a renaming of their variables trivially do. Otherwise
+ * the given number of random assignments are tried: each module in turn is
+ * given random inputs from which its witnesses are derived, and the other
+ * module is given the resulting values of its public variables and inputs of
+ * the same names. An assignment satisfying only one module distinguishes
+ * them. Fails if the definitions of either module cannot be ordered. */
+pub fn equivalent(
+    a: &Module,
+    b: &Module,
+    field_ops: &dyn FieldOps,
+    samples: usize,
+) -> Result<EquivalenceReport, CompileError> {
+    if isomorphic(a, b, field_ops) {
+        return Ok(EquivalenceReport::Isomorphic);
     }
-    for expr in &mut module.exprs {
-        copy_propagate_expr(expr, &substitutions);
+    let samplers = [Sampler::new(a, field_ops)?, Sampler::new(b, field_ops)?];
+    let mut satisfied = 0;
+    for sample in 0..samples {
+        let (first, second) = (&samplers[sample % 2], &samplers[1 - sample % 2]);
+        let assigns1 = first.sample(&BTreeMap::new(), field_ops);
+        let satisfied1 = violated_constraint(&first.module, &assigns1, field_ops).is_none();
+        // Give the second module the corresponding values where they exist
+        let values = first.named_values(&assigns1);
+        let assigns2 = second.sample(&values, field_ops);
+        let satisfied2 = violated_constraint(&second.module, &assigns2, field_ops).is_none();
+        if satisfied1 != satisfied2 {
+            let mut assignment = values;
+            assignment.extend(second.named_values(&assigns2));
+            let satisfies_first = if sample % 2 == 0 { satisfied1 } else { satisfied2 };
+            return Ok(EquivalenceReport::Distinguished { assignment, satisfies_first });
+        }
+        satisfied += satisfied1 as usize;
     }
+    Ok(EquivalenceReport::Undistinguished { samples, satisfied })
 }
 
-/* Eliminate equalities that are obviously true from the constraint set. This
- * will reduce the number of gates in the circuit. */
-pub fn eliminate_dead_equalities(module: &mut Module) {
-    module.exprs.retain(|expr| {
-        match &expr.v {
-            Expr::Infix(InfixOp::Equal, expr1, expr2) if
-                matches!((&expr1.v, &expr2.v), (Expr::Constant(c1), Expr::Constant(c2)) if
-                         c1 == c2) => false,
-            Expr::Infix(InfixOp::Equal, expr1, expr2) if
-                matches!((&expr1.v, &expr2.v), (Expr::Variable(v1), Expr::Variable(v2)) if
-                         v1.id == v2.id) => false,
-            _ => {
-                true
+/* The outcome of checking a compiled module against sampled assignments. */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SatisfiabilityReport {
+    // Number of assignments tried
+    pub samples: usize,
+    // Number of assignments that satisfied every constraint
+    pub satisfied: usize,
+    // The first assignment found to violate a constraint, and that constraint
+    pub counterexample: Option<(BTreeMap<String, BigInt>, String)>,
+}
+
+impl std::fmt::Display for SatisfiabilityReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "** Satisfying samples: {} of {}", self.satisfied, self.samples)?;
+        if let Some((assignment, constraint)) = &self.counterexample {
+            write!(f, "\n** Constraint {} is violated by the following assignment:", constraint)?;
+            for (name, val) in assignment {
+                write!(f, "\n*** {} = {}", name, val)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/* Check the given 3AC module against the given number of sampled
+ * assignments. Public variables and inputs named in the given map take the
+ * given values, whereas the other inputs are drawn at random or derived as
+ * the constraints force them to be. Fails if the definitions of the module
+ * cannot be ordered. */
+pub fn check_satisfiability(
+    module: &Module,
+    fixed: &BTreeMap<String, BigInt>,
+    field_ops: &dyn FieldOps,
+    samples: usize,
+) -> Result<SatisfiabilityReport, CompileError> {
+    let sampler = Sampler::new(module, field_ops)?;
+    let mut report = SatisfiabilityReport { samples, satisfied: 0, counterexample: None };
+    for _ in 0..samples {
+        let assigns = sampler.sample(fixed, field_ops);
+        match violated_constraint(&sampler.module, &assigns, field_ops) {
+            None => report.satisfied += 1,
+            Some(expr) if report.counterexample.is_none() => {
+                report.counterexample = Some((sampler.named_values(&assigns), expr.to_string()));
             },
+            Some(_) => {},
         }
-    });
+    }
+    Ok(report)
 }
 
 /* Register the fresh intrinsic in the compilation environment. */
@@ -1342,11 +3739,11 @@ fn expand_fresh_intrinsic(
     _bindings: &HashMap<VariableId, TExpr>,
     prover_defs: &mut HashSet<VariableId>,
     _gen: &mut VarGen,
-) -> TExpr {
+) -> Result<TExpr, CompileError> {
     match &params[..] {
         [param] if matches!(param.v, Pat::Variable(param_var) if {
             prover_defs.insert(param_var.id);
-            return param.to_expr();
+            return Ok(param.to_expr());
         }) => unreachable!(),
         _ => panic!("unexpected parameters for fresh: {:?}", params),
     }
@@ -1404,7 +3801,7 @@ fn expand_iter_intrinsic(
     bindings: &HashMap<VariableId, TExpr>,
     _prover_defs: &mut HashSet<VariableId>,
     gen: &mut VarGen,
-) -> TExpr {
+) -> Result<TExpr, CompileError> {
     match &params[..] {
         [TPat { v: Pat::Variable(param_var), .. }] => {
             let iter_arg = Variable::new(gen.generate_id());
@@ -1424,9 +3821,16 @@ fn expand_iter_intrinsic(
             let val = if let Expr::Constant(c) = &bindings[&param_var.id].v {
                 c
             } else {
-                panic!("only constant arguments to iter supported")
+                return Err(CompileError::Malformed(
+                    format!("only constant arguments to iter supported, found {}", bindings[&param_var.id]),
+                    None,
+                ));
             };
-            for _ in 0..val.to_i8().expect("specified iteration count is too large") {
+            let count = val.to_i8().ok_or_else(|| CompileError::Malformed(
+                format!("iteration count {} given to iter is too large", val),
+                None,
+            ))?;
+            for _ in 0..count {
                 body = TExpr {
                     v: Expr::Application(
                         Box::new(iter_func.clone()),
@@ -1435,7 +3839,7 @@ fn expand_iter_intrinsic(
                     t: body.t,
                 };
             }
-            TExpr {
+            Ok(TExpr {
                 t: Some(Type::Function(
                     Box::new(iter_func.t.clone().unwrap()),
                     Box::new(iter_func.t.clone().unwrap()),
@@ -1448,7 +3852,7 @@ fn expand_iter_intrinsic(
                     body: Box::new(body),
                     env: HashMap::new(),
                 }),
-            }
+            })
         },
         _ => panic!("unexpected arguments to iter: {:?}", params),
     }
@@ -1515,7 +3919,7 @@ fn expand_fold_intrinsic(
     bindings: &HashMap<VariableId, TExpr>,
     _prover_defs: &mut HashSet<VariableId>,
     gen: &mut VarGen,
-) -> TExpr {
+) -> Result<TExpr, CompileError> {
     match &params[..] {
         [TPat { v: Pat::Variable(param_var), .. }] => {
             let fold_arg = Variable::new(gen.generate_id());
@@ -1533,18 +3937,7 @@ fn expand_fold_intrinsic(
                     Box::new(fold_func_func.clone()),
                 ))
             };
-            let mut param_val = &bindings[&param_var.id];
-            let mut param_list = Vec::new();
-            let param_list = loop {
-                if let Expr::Cons(hd, tl) = &param_val.v {
-                    param_list.push(hd.clone());
-                    param_val = tl;
-                } else if let Expr::Nil = &param_val.v {
-                    break param_list;
-                } else {
-                    panic!("only list arguments to fold supported")
-                };
-            };
+            let param_list = list_elements(&bindings[&param_var.id], "fold")?;
             let mut body = TExpr {
                 v: Expr::Variable(fold_arg.clone()),
                 t: Some(fold_arg_typ.clone())
@@ -1555,7 +3948,7 @@ fn expand_fold_intrinsic(
                         Box::new(TExpr {
                             v: Expr::Application(
                                 Box::new(fold_func.clone()),
-                                param_elt,
+                                Box::new(param_elt.clone()),
                             ),
                             t: Some(fold_func_func.clone()),
                         }),
@@ -1564,7 +3957,7 @@ fn expand_fold_intrinsic(
                     t: body.t,
                 };
             };
-            TExpr {
+            Ok(TExpr {
                 t: Some(Type::Function(
                     Box::new(fold_func.t.clone().unwrap()),
                     Box::new(fold_func_func),
@@ -1577,8 +3970,425 @@ fn expand_fold_intrinsic(
                     body: Box::new(body),
                     env: HashMap::new(),
                 }),
-            }
+            })
         },
         _ => panic!("unexpected arguments to fold: {:?}", params),
     }
 }
+
+/* Get the elements of the given list value. Only lists whose structure is
+ * known at compile time are supported. */
+fn list_elements<'a>(list: &'a TExpr, intrinsic: &str) -> Result<Vec<&'a TExpr>, CompileError> {
+    let mut val = list;
+    let mut elts = Vec::new();
+    loop {
+        match &val.v {
+            Expr::Cons(hd, tl) => {
+                elts.push(&**hd);
+                val = tl;
+            },
+            Expr::Nil => break Ok(elts),
+            _ => break Err(CompileError::Malformed(
+                format!("only list arguments to {} supported, found {}", intrinsic, list),
+                None,
+            )),
+        }
+    }
+}
+
+/* Register the map intrinsic in the compilation environment. */
+fn register_map_intrinsic(
+    globals: &mut HashMap<String, VariableId>,
+    global_types: &mut HashMap<VariableId, Type>,
+    bindings: &mut HashMap<VariableId, TExpr>,
+    gen: &mut VarGen,
+) {
+    let map_id = gen.generate_id();
+    let map_arg = Variable::new(gen.generate_id());
+    let map_res = Variable::new(gen.generate_id());
+    let map_func = Type::Function(
+        Box::new(Type::Variable(map_arg.clone())),
+        Box::new(Type::Variable(map_res.clone())),
+    );
+    let map_list = Type::List(Box::new(Type::Variable(map_arg.clone())));
+    let map_func_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(map_func.clone()));
+    let map_list_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(map_list.clone()));
+    // Register the map function in global namespace
+    globals.insert("map".to_string(), map_id);
+    // Describe the intrinsic's type, arity, and implementation
+    let map_intrinsic = Intrinsic::new(
+        vec![map_func_pat, map_list_pat],
+        expand_map_intrinsic,
+    );
+    let imp_typ = Type::Function(
+        Box::new(map_func),
+        Box::new(Type::Function(
+            Box::new(map_list),
+            Box::new(Type::List(Box::new(Type::Variable(map_res.clone())))),
+        )),
+    );
+    // Register the intrinsic descriptor with the global binding
+    global_types.insert(
+        map_id,
+        Type::Forall(
+            map_arg,
+            Box::new(Type::Forall(
+                map_res,
+                Box::new(imp_typ.clone()),
+            )),
+        ),
+    );
+    // Register the intrinsic descriptor with the global binding
+    bindings.insert(
+        map_id,
+        Expr::Intrinsic(map_intrinsic)
+            .type_expr(Some(imp_typ)),
+    );
+}
+
+/* map f [a0, a1, ..., aN] = [f a0, f a1, ..., f aN]. */
+fn expand_map_intrinsic(
+    params: &Vec<TPat>,
+    bindings: &HashMap<VariableId, TExpr>,
+    _prover_defs: &mut HashSet<VariableId>,
+    _gen: &mut VarGen,
+) -> Result<TExpr, CompileError> {
+    match &params[..] {
+        [TPat { v: Pat::Variable(func_var), .. },
+         TPat { v: Pat::Variable(list_var), .. }] => {
+            let func = Expr::Variable(func_var.clone()).type_expr(None);
+            let elts = list_elements(&bindings[&list_var.id], "map")?;
+            // Build the result list from the back so that its elements are
+            // evaluated from the front
+            let mut body = Expr::Nil.type_expr(None);
+            for elt in elts.into_iter().rev() {
+                let app = Expr::Application(Box::new(func.clone()), Box::new(elt.clone()));
+                body = Expr::Cons(Box::new(app.type_expr(None)), Box::new(body)).type_expr(None);
+            }
+            Ok(body)
+        },
+        _ => panic!("unexpected arguments to map: {:?}", params),
+    }
+}
+
+/* Register the zip_with intrinsic in the compilation environment. */
+fn register_zip_with_intrinsic(
+    globals: &mut HashMap<String, VariableId>,
+    global_types: &mut HashMap<VariableId, Type>,
+    bindings: &mut HashMap<VariableId, TExpr>,
+    gen: &mut VarGen,
+) {
+    let zip_id = gen.generate_id();
+    let zip_arg1 = Variable::new(gen.generate_id());
+    let zip_arg2 = Variable::new(gen.generate_id());
+    let zip_res = Variable::new(gen.generate_id());
+    let zip_func = Type::Function(
+        Box::new(Type::Variable(zip_arg1.clone())),
+        Box::new(Type::Function(
+            Box::new(Type::Variable(zip_arg2.clone())),
+            Box::new(Type::Variable(zip_res.clone())),
+        )),
+    );
+    let zip_list1 = Type::List(Box::new(Type::Variable(zip_arg1.clone())));
+    let zip_list2 = Type::List(Box::new(Type::Variable(zip_arg2.clone())));
+    let zip_func_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(zip_func.clone()));
+    let zip_list1_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(zip_list1.clone()));
+    let zip_list2_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(zip_list2.clone()));
+    // Register the zip_with function in global namespace
+    globals.insert("zip_with".to_string(), zip_id);
+    // Describe the intrinsic's type, arity, and implementation
+    let zip_intrinsic = Intrinsic::new(
+        vec![zip_func_pat, zip_list1_pat, zip_list2_pat],
+        expand_zip_with_intrinsic,
+    );
+    let imp_typ = Type::Function(
+        Box::new(zip_func),
+        Box::new(Type::Function(
+            Box::new(zip_list1),
+            Box::new(Type::Function(
+                Box::new(zip_list2),
+                Box::new(Type::List(Box::new(Type::Variable(zip_res.clone())))),
+            )),
+        )),
+    );
+    // Register the intrinsic descriptor with the global binding
+    global_types.insert(
+        zip_id,
+        Type::Forall(
+            zip_arg1,
+            Box::new(Type::Forall(
+                zip_arg2,
+                Box::new(Type::Forall(
+                    zip_res,
+                    Box::new(imp_typ.clone()),
+                )),
+            )),
+        ),
+    );
+    // Register the intrinsic descriptor with the global binding
+    bindings.insert(
+        zip_id,
+        Expr::Intrinsic(zip_intrinsic)
+            .type_expr(Some(imp_typ)),
+    );
+}
+
+/* zip_with f [a0, ..., aN] [b0, ..., bN] = [f a0 b0, ..., f aN bN]. */
+fn expand_zip_with_intrinsic(
+    params: &Vec<TPat>,
+    bindings: &HashMap<VariableId, TExpr>,
+    _prover_defs: &mut HashSet<VariableId>,
+    _gen: &mut VarGen,
+) -> Result<TExpr, CompileError> {
+    match &params[..] {
+        [TPat { v: Pat::Variable(func_var), .. },
+         TPat { v: Pat::Variable(list1_var), .. },
+         TPat { v: Pat::Variable(list2_var), .. }] => {
+            let func = Expr::Variable(func_var.clone()).type_expr(None);
+            let elts1 = list_elements(&bindings[&list1_var.id], "zip_with")?;
+            let elts2 = list_elements(&bindings[&list2_var.id], "zip_with")?;
+            if elts1.len() != elts2.len() {
+                return Err(CompileError::Malformed(
+                    format!(
+                        "zip_with applied to lists of differing lengths {} and {}",
+                        elts1.len(),
+                        elts2.len(),
+                    ),
+                    None,
+                ));
+            }
+            // Build the result list from the back so that its elements are
+            // evaluated from the front
+            let mut body = Expr::Nil.type_expr(None);
+            for (elt1, elt2) in elts1.into_iter().zip(elts2).rev() {
+                let app = Expr::Application(Box::new(func.clone()), Box::new(elt1.clone()));
+                let app = Expr::Application(Box::new(app.type_expr(None)), Box::new(elt2.clone()));
+                body = Expr::Cons(Box::new(app.type_expr(None)), Box::new(body)).type_expr(None);
+            }
+            Ok(body)
+        },
+        _ => panic!("unexpected arguments to zip_with: {:?}", params),
+    }
+}
+
+/* Register the list concatenation intrinsic in the compilation environment. */
+fn register_append_intrinsic(
+    globals: &mut HashMap<String, VariableId>,
+    global_types: &mut HashMap<VariableId, Type>,
+    bindings: &mut HashMap<VariableId, TExpr>,
+    gen: &mut VarGen,
+) {
+    let append_id = gen.generate_id();
+    let append_elt = Variable::new(gen.generate_id());
+    let append_list = Type::List(Box::new(Type::Variable(append_elt.clone())));
+    let append_list1_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(append_list.clone()));
+    let append_list2_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(append_list.clone()));
+    // Register the append function in global namespace
+    globals.insert("++".to_string(), append_id);
+    // Describe the intrinsic's type, arity, and implementation
+    let append_intrinsic = Intrinsic::new(
+        vec![append_list1_pat, append_list2_pat],
+        expand_append_intrinsic,
+    );
+    let imp_typ = Type::Function(
+        Box::new(append_list.clone()),
+        Box::new(Type::Function(
+            Box::new(append_list.clone()),
+            Box::new(append_list),
+        )),
+    );
+    // Register the intrinsic descriptor with the global binding
+    global_types.insert(
+        append_id,
+        Type::Forall(
+            append_elt,
+            Box::new(imp_typ.clone()),
+        ),
+    );
+    // Register the intrinsic descriptor with the global binding
+    bindings.insert(
+        append_id,
+        Expr::Intrinsic(append_intrinsic)
+            .type_expr(Some(imp_typ)),
+    );
+}
+
+/* [a0, ..., aN] ++ b = a0:...:aN:b. */
+fn expand_append_intrinsic(
+    params: &Vec<TPat>,
+    bindings: &HashMap<VariableId, TExpr>,
+    _prover_defs: &mut HashSet<VariableId>,
+    _gen: &mut VarGen,
+) -> Result<TExpr, CompileError> {
+    match &params[..] {
+        [TPat { v: Pat::Variable(list1_var), .. },
+         TPat { v: Pat::Variable(list2_var), .. }] => {
+            let elts = list_elements(&bindings[&list1_var.id], "++")?;
+            let mut body = bindings[&list2_var.id].clone();
+            for elt in elts.into_iter().rev() {
+                body = Expr::Cons(Box::new(elt.clone()), Box::new(body)).type_expr(None);
+            }
+            Ok(body)
+        },
+        _ => panic!("unexpected arguments to ++: {:?}", params),
+    }
+}
+
+/* Register the list slicing intrinsic in the compilation environment. */
+fn register_slice_intrinsic(
+    globals: &mut HashMap<String, VariableId>,
+    global_types: &mut HashMap<VariableId, Type>,
+    bindings: &mut HashMap<VariableId, TExpr>,
+    gen: &mut VarGen,
+) {
+    let slice_id = gen.generate_id();
+    let slice_elt = Variable::new(gen.generate_id());
+    let slice_list = Type::List(Box::new(Type::Variable(slice_elt.clone())));
+    let slice_start_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(Type::Int));
+    let slice_end_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(Type::Int));
+    let slice_list_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(slice_list.clone()));
+    // Register the slice function in global namespace
+    globals.insert("[..]".to_string(), slice_id);
+    // Describe the intrinsic's type, arity, and implementation
+    let slice_intrinsic = Intrinsic::new(
+        vec![slice_start_pat, slice_end_pat, slice_list_pat],
+        expand_slice_intrinsic,
+    );
+    let imp_typ = Type::Function(
+        Box::new(Type::Int),
+        Box::new(Type::Function(
+            Box::new(Type::Int),
+            Box::new(Type::Function(
+                Box::new(slice_list.clone()),
+                Box::new(slice_list),
+            )),
+        )),
+    );
+    // Register the intrinsic descriptor with the global binding
+    global_types.insert(
+        slice_id,
+        Type::Forall(
+            slice_elt,
+            Box::new(imp_typ.clone()),
+        ),
+    );
+    // Register the intrinsic descriptor with the global binding
+    bindings.insert(
+        slice_id,
+        Expr::Intrinsic(slice_intrinsic)
+            .type_expr(Some(imp_typ)),
+    );
+}
+
+/* [a0, ..., aN][i..j] = [ai, ..., a(j-1)]. */
+fn expand_slice_intrinsic(
+    params: &Vec<TPat>,
+    bindings: &HashMap<VariableId, TExpr>,
+    _prover_defs: &mut HashSet<VariableId>,
+    _gen: &mut VarGen,
+) -> Result<TExpr, CompileError> {
+    match &params[..] {
+        [TPat { v: Pat::Variable(start_var), .. },
+         TPat { v: Pat::Variable(end_var), .. },
+         TPat { v: Pat::Variable(list_var), .. }] => {
+            let list = &bindings[&list_var.id];
+            let elts = list_elements(list, "slice")?;
+            let (start, end) = match (&bindings[&start_var.id].v, &bindings[&end_var.id].v) {
+                (Expr::Constant(start), Expr::Constant(end)) => (start, end),
+                (start, end) => return Err(CompileError::Malformed(
+                    format!(
+                        "only constant slice bounds supported, found [{}..{}]",
+                        start.clone().type_expr(None),
+                        end.clone().type_expr(None),
+                    ),
+                    None,
+                )),
+            };
+            match (start.to_usize(), end.to_usize()) {
+                (Some(start), Some(end)) if start <= end && end <= elts.len() => {
+                    let mut body = Expr::Nil.type_expr(None);
+                    for elt in elts[start..end].iter().rev() {
+                        body = Expr::Cons(Box::new((*elt).clone()), Box::new(body)).type_expr(None);
+                    }
+                    Ok(body)
+                },
+                _ => Err(CompileError::Malformed(
+                    format!(
+                        "slice [{}..{}] is out of range for list {} of length {}",
+                        start,
+                        end,
+                        list,
+                        elts.len(),
+                    ),
+                    None,
+                )),
+            }
+        },
+        _ => panic!("unexpected arguments to slice: {:?}", params),
+    }
+}
+
+/* Register the list length intrinsic in the compilation environment. */
+fn register_len_intrinsic(
+    globals: &mut HashMap<String, VariableId>,
+    global_types: &mut HashMap<VariableId, Type>,
+    bindings: &mut HashMap<VariableId, TExpr>,
+    gen: &mut VarGen,
+) {
+    let len_id = gen.generate_id();
+    let len_elt = Variable::new(gen.generate_id());
+    let len_list = Type::List(Box::new(Type::Variable(len_elt.clone())));
+    let len_list_pat = Pat::Variable(Variable::new(gen.generate_id()))
+        .type_pat(Some(len_list.clone()));
+    // Register the len function in global namespace
+    globals.insert("len".to_string(), len_id);
+    // Describe the intrinsic's type, arity, and implementation
+    let len_intrinsic = Intrinsic::new(
+        vec![len_list_pat],
+        expand_len_intrinsic,
+    );
+    let imp_typ = Type::Function(
+        Box::new(len_list),
+        Box::new(Type::Int),
+    );
+    // Register the intrinsic descriptor with the global binding
+    global_types.insert(
+        len_id,
+        Type::Forall(
+            len_elt,
+            Box::new(imp_typ.clone()),
+        ),
+    );
+    // Register the intrinsic descriptor with the global binding
+    bindings.insert(
+        len_id,
+        Expr::Intrinsic(len_intrinsic)
+            .type_expr(Some(imp_typ)),
+    );
+}
+
+/* len [a0, a1, ..., aN] = N+1. */
+fn expand_len_intrinsic(
+    params: &Vec<TPat>,
+    bindings: &HashMap<VariableId, TExpr>,
+    _prover_defs: &mut HashSet<VariableId>,
+    _gen: &mut VarGen,
+) -> Result<TExpr, CompileError> {
+    match &params[..] {
+        [TPat { v: Pat::Variable(list_var), .. }] => {
+            let elts = list_elements(&bindings[&list_var.id], "len")?;
+            Ok(Expr::Constant(BigInt::from(elts.len())).type_expr(Some(Type::Int)))
+        },
+        _ => panic!("unexpected arguments to len: {:?}", params),
+    }
+}
@@ -1,8 +1,9 @@
 use std::fmt::{self, Display};
 use crate::ast::{Module, VariableId, Pat, TPat, Variable, TExpr, InfixOp, Function, Definition, Expr, LetBinding, Intrinsic};
-use crate::transform::{VarGen, collect_pattern_variables};
+use crate::transform::{def_span, VarGen, collect_pattern_variables, CompileError};
 use std::collections::{HashMap, HashSet};
 use bincode::{Decode, Encode};
+use log::trace;
 
 /* Collect the free variables occuring in the given type. */
 fn collect_free_type_vars(
@@ -123,6 +124,7 @@ pub fn allocate_module_types(
 
 /* A representation of expression types. */
 #[derive(Debug, Clone, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Type {
     Unit,
     Int,
@@ -178,21 +180,25 @@ fn unify_variable(
     type2: &Type,
     types: &mut HashMap<VariableId, Type>,
     inserts: &mut Option<HashSet<VariableId>>,
-) {
+) -> Result<(), CompileError> {
     match (var1, type2) {
         (var1, Type::Variable(var2)) if var1.id == var2.id => {},
         (var1, type2) if types.contains_key(&var1.id) =>
-            unify_types(&types[&var1.id].clone(), type2, types, inserts),
+            unify_types(&types[&var1.id].clone(), type2, types, inserts)?,
         (var1, Type::Variable(var2)) if types.contains_key(&var2.id) =>
-            unify_types(&Type::Variable(var1.clone()), &types[&var2.id].clone(), types, inserts),
+            unify_types(&Type::Variable(var1.clone()), &types[&var2.id].clone(), types, inserts)?,
         (var1, type2) if !occurs_in(var1, type2, types) => {
             types.insert(var1.id, type2.clone());
             if let Some(x) = inserts {
                 x.insert(var1.id);
             }
         }
-        _ => panic!("unable to match {:?} with {}", var1, type2),
+        _ => return Err(CompileError::Type(
+            format!("unable to match {} with {}, which contains it", Type::Variable(var1.clone()), expand_type(type2, types)),
+            None,
+        )),
     }
+    Ok(())
 }
 
 /* Unify the two given types together. */
@@ -201,20 +207,24 @@ pub fn unify_types(
     type2: &Type,
     types: &mut HashMap<VariableId, Type>,
     inserts: &mut Option<HashSet<VariableId>>,
-) {
+) -> Result<(), CompileError> {
     match (type1, type2) {
         (Type::Int, Type::Int) |
         (Type::Unit, Type::Unit) => {},
-        (Type::List(a), Type::List(b)) => unify_types(a, b, types, inserts),
+        (Type::List(a), Type::List(b)) => unify_types(a, b, types, inserts)?,
         (Type::Function(a1, b1), Type::Function(a2, b2)) |
         (Type::Product(a1, b1), Type::Product(a2, b2)) => {
-            unify_types(&*a1, &*a2, types, inserts);
-            unify_types(&*b1, &*b2, types, inserts);
+            unify_types(&*a1, &*a2, types, inserts)?;
+            unify_types(&*b1, &*b2, types, inserts)?;
         },
         (Type::Variable(v1), type2) | (type2, Type::Variable(v1)) =>
-            unify_variable(v1, type2, types, inserts),
-        _ => panic!("unable to match {} with {}", type1, type2),
+            unify_variable(v1, type2, types, inserts)?,
+        _ => return Err(CompileError::Type(
+            format!("unable to match {} with {}", expand_type(type1, types), expand_type(type2, types)),
+            None,
+        )),
     }
+    Ok(())
 }
 
 /* Fully expand the variables in the given type. */
@@ -373,17 +383,25 @@ fn infer_binding_types(
     vars: &mut HashMap<VariableId, Type>,
     types: &mut HashMap<VariableId, Type>,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     let expr1_var = expr_type_var(&*def.1);
-    infer_expr_types(&*def.1, env_ftvs, vars, types, gen);
-    infer_pat_types(&def.0, vars, types, gen);
-    unify_types(pat_type_var(&def.0), expr_type_var(&def.1), types, &mut None);
+    // Pattern variables are typed first since function bindings may refer to
+    // themselves. Such recursive references are monomorphic.
+    infer_pat_types(&def.0, vars, types, gen)?;
+    infer_expr_types(&*def.1, env_ftvs, vars, types, gen)?;
+    unify_types(pat_type_var(&def.0), expr_type_var(&def.1), types, &mut None)?;
     // Compute the set of free variables occuring in RHS' TYPE that
     // do not occur in the type environment
     let mut quant_vars = HashMap::new();
     collect_free_type_vars(&expand_type(expr1_var, types), &mut quant_vars);
-    for env_ftv in env_ftvs.keys() {
-        quant_vars.remove(env_ftv);
+    for env_ftv in env_ftvs.values() {
+        // The environment's type variables may have since been unified with
+        // other types, so remove the free variables of their expansions
+        let mut env_vars = HashMap::new();
+        collect_free_type_vars(&expand_type(&Type::Variable(env_ftv.clone()), types), &mut env_vars);
+        for env_var in env_vars.keys() {
+            quant_vars.remove(env_var);
+        }
     }
     // Quantify the type of each variable bound by the pattern with the
     // free variables unique to the RHS
@@ -391,8 +409,11 @@ fn infer_binding_types(
     collect_pattern_variables(&def.0, &mut pat_vars);
     for pat_var in pat_vars.keys() {
         let quant_expr = vars.get_mut(&pat_var).unwrap();
-        // Quantify every free variable unique to the RHS' type
-        for qvar in quant_vars.values() {
+        // Quantify every free variable unique to the RHS' type, in order of
+        // allocation so that instantiations allocate reproducible IDs
+        let mut qvars: Vec<_> = quant_vars.values().collect();
+        qvars.sort_by_key(|qvar| qvar.id);
+        for qvar in qvars {
             *quant_expr = Type::Forall(qvar.clone(), Box::new(quant_expr.clone()));
         }
         // Add this type schema to the type environment in which the let
@@ -400,6 +421,7 @@ fn infer_binding_types(
         let quant_expr = quant_expr.clone();
         collect_free_type_vars(&expand_type(&quant_expr, types), env_ftvs);
     }
+    Ok(())
 }
 
 /* Get or generate the type variable associated with a given pattern. */
@@ -414,23 +436,23 @@ pub fn infer_pat_types(
     vars: &mut HashMap<VariableId, Type>,
     types: &mut HashMap<VariableId, Type>,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     match &pat.v {
         Pat::Nil => {
             let pat_var = pat_type_var(pat);
             let poly_var = Type::Variable(Variable::new(gen.generate_id()));
             // []: [a]
-            unify_types(pat_var, &Type::List(Box::new(poly_var)), types, &mut None);
+            unify_types(pat_var, &Type::List(Box::new(poly_var)), types, &mut None)?;
         },
         Pat::Unit => {
             let pat_var = pat_type_var(pat);
             // (): ()
-            unify_types(pat_var, &Type::Unit, types, &mut None);
+            unify_types(pat_var, &Type::Unit, types, &mut None)?;
         },
         Pat::Constant(_) => {
             let pat_var = pat_type_var(pat);
             // num: int
-            unify_types(pat_var, &Type::Int, types, &mut None);
+            unify_types(pat_var, &Type::Int, types, &mut None)?;
         },
         Pat::Variable(var) => {
             let pat_var = pat_type_var(pat);
@@ -441,8 +463,8 @@ pub fn infer_pat_types(
             let pat1_var = pat_type_var(pat1);
             let pat_var = pat_type_var(pat);
             // a1: t1 |- a1 as _: t1
-            unify_types(&pat_var, &pat1_var, types, &mut None);
-            infer_pat_types(&pat1, vars, types, gen);
+            unify_types(&pat_var, &pat1_var, types, &mut None)?;
+            infer_pat_types(&pat1, vars, types, gen)?;
             // Map the pattern name to its type
             vars.insert(name.id, pat_var.clone());
         },
@@ -456,9 +478,9 @@ pub fn infer_pat_types(
                 &Type::Product(Box::new(pat1_var.clone()), Box::new(pat2_var.clone())),
                 types,
                 &mut None,
-            );
-            infer_pat_types(pat1, vars, types, gen);
-            infer_pat_types(pat2, vars, types, gen);
+            )?;
+            infer_pat_types(pat1, vars, types, gen)?;
+            infer_pat_types(pat2, vars, types, gen)?;
         },
         Pat::Cons(pat1, pat2) => {
             let pat1_var = pat_type_var(pat1);
@@ -470,12 +492,13 @@ pub fn infer_pat_types(
                 &Type::List(Box::new(pat1_var.clone())),
                 types,
                 &mut None,
-            );
-            unify_types(&pat_var, &pat2_var, types, &mut None);
-            infer_pat_types(pat1, vars, types, gen);
-            infer_pat_types(pat2, vars, types, gen);
+            )?;
+            unify_types(&pat_var, &pat2_var, types, &mut None)?;
+            infer_pat_types(pat1, vars, types, gen)?;
+            infer_pat_types(pat2, vars, types, gen)?;
         },
     }
+    Ok(())
 }
 
 /* Recursively infer the types of expressions in the given expression tree.
@@ -487,34 +510,34 @@ fn infer_expr_types(
     vars: &HashMap<VariableId, Type>,
     types: &mut HashMap<VariableId, Type>,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     match &expr.v {
         Expr::Nil => {
             let expr_var = expr_type_var(expr);
             let poly_var = Type::Variable(Variable::new(gen.generate_id()));
             // []: [a]
-            unify_types(expr_var, &Type::List(Box::new(poly_var)), types, &mut None);
+            unify_types(expr_var, &Type::List(Box::new(poly_var)), types, &mut None)?;
         },
         Expr::Unit => {
             let expr_var = expr_type_var(expr);
             // (): ()
-            unify_types(expr_var, &Type::Unit, types, &mut None);
+            unify_types(expr_var, &Type::Unit, types, &mut None)?;
         },
         Expr::Constant(_) => {
             let expr_var = expr_type_var(expr);
             // num: int
-            unify_types(expr_var, &Type::Int, types, &mut None);
+            unify_types(expr_var, &Type::Int, types, &mut None)?;
         },
         Expr::Infix(InfixOp::Equal, expr1, expr2) => {
             let expr_var = expr_type_var(expr);
             let expr1_var = expr_type_var(expr1);
             let expr2_var = expr_type_var(expr2);
             // a = b: ()
-            unify_types(&expr_var, &Type::Unit, types, &mut None);
+            unify_types(&expr_var, &Type::Unit, types, &mut None)?;
             // a: c |- b: c
-            unify_types(&expr1_var, &expr2_var, types, &mut None);
-            infer_expr_types(expr1, env, vars, types, gen);
-            infer_expr_types(expr2, env, vars, types, gen);
+            unify_types(&expr1_var, &expr2_var, types, &mut None)?;
+            infer_expr_types(expr1, env, vars, types, gen)?;
+            infer_expr_types(expr2, env, vars, types, gen)?;
         },
         Expr::Infix(
             InfixOp::Add | InfixOp::Subtract | InfixOp::Multiply |
@@ -527,31 +550,31 @@ fn infer_expr_types(
             let expr1_var = expr_type_var(expr1);
             let expr2_var = expr_type_var(expr2);
             // a op b: int
-            unify_types(&expr_var, &Type::Int, types, &mut None);
+            unify_types(&expr_var, &Type::Int, types, &mut None)?;
             // a: int
-            unify_types(&expr1_var, &Type::Int, types, &mut None);
+            unify_types(&expr1_var, &Type::Int, types, &mut None)?;
             // b: int
-            unify_types(&expr2_var, &Type::Int, types, &mut None);
-            infer_expr_types(expr1, env, vars, types, gen);
-            infer_expr_types(expr2, env, vars, types, gen);
+            unify_types(&expr2_var, &Type::Int, types, &mut None)?;
+            infer_expr_types(expr1, env, vars, types, gen)?;
+            infer_expr_types(expr2, env, vars, types, gen)?;
         },
         Expr::Negate(expr1) => {
             let expr_var = expr_type_var(expr);
             let expr1_var = expr_type_var(expr1);
             // (-a): int
-            unify_types(&expr_var, &Type::Int, types, &mut None);
+            unify_types(&expr_var, &Type::Int, types, &mut None)?;
             // a: int
-            unify_types(&expr1_var, &Type::Int, types, &mut None);
-            infer_expr_types(expr1, env, vars, types, gen);
+            unify_types(&expr1_var, &Type::Int, types, &mut None)?;
+            infer_expr_types(expr1, env, vars, types, gen)?;
         },
         Expr::Sequence(seq) => {
             let last_expr = seq.last().expect("encountered empty sequence");
             let expr_var = expr_type_var(expr);
             let last_expr_var = expr_type_var(last_expr);
             // aN: c |- (a1; ...; aN): c
-            unify_types(&expr_var, &last_expr_var, types, &mut None);
+            unify_types(&expr_var, &last_expr_var, types, &mut None)?;
             for expr in seq {
-                infer_expr_types(expr, env, vars, types, gen);
+                infer_expr_types(expr, env, vars, types, gen)?;
             }
         },
         Expr::Product(expr1, expr2) => {
@@ -564,9 +587,9 @@ fn infer_expr_types(
                 &Type::Product(Box::new(expr1_var.clone()), Box::new(expr2_var.clone())),
                 types,
                 &mut None,
-            );
-            infer_expr_types(expr1, env, vars, types, gen);
-            infer_expr_types(expr2, env, vars, types, gen);
+            )?;
+            infer_expr_types(expr1, env, vars, types, gen)?;
+            infer_expr_types(expr2, env, vars, types, gen)?;
         },
         Expr::Cons(expr1, expr2) => {
             let expr1_var = expr_type_var(expr1);
@@ -578,10 +601,10 @@ fn infer_expr_types(
                 &Type::List(Box::new(expr1_var.clone())),
                 types,
                 &mut None,
-            );
-            unify_types(&expr_var, &expr2_var, types, &mut None);
-            infer_expr_types(expr1, env, vars, types, gen);
-            infer_expr_types(expr2, env, vars, types, gen);
+            )?;
+            unify_types(&expr_var, &expr2_var, types, &mut None)?;
+            infer_expr_types(expr1, env, vars, types, gen)?;
+            infer_expr_types(expr2, env, vars, types, gen)?;
         },
         Expr::Application(expr1, expr2) => {
             let expr_var = expr_type_var(expr);
@@ -596,9 +619,9 @@ fn infer_expr_types(
                 ),
                 types,
                 &mut None
-            );
-            infer_expr_types(expr1, env, vars, types, gen);
-            infer_expr_types(expr2, env, vars, types, gen);
+            )?;
+            infer_expr_types(expr1, env, vars, types, gen)?;
+            infer_expr_types(expr2, env, vars, types, gen)?;
         },
         Expr::Function(Function { params, body: expr1, .. }) => {
             let expr_var = expr_type_var(expr);
@@ -607,15 +630,15 @@ fn infer_expr_types(
             let mut env = env.clone();
             let mut vars = vars.clone();
             for param in params.iter().rev() {
-                infer_pat_types(param, &mut vars, types, gen);
+                infer_pat_types(param, &mut vars, types, gen)?;
                 let param_type = pat_type_var(param);
                 collect_free_type_vars(&expand_type(&param_type, types), &mut env);
                 func_var = Type::Function(Box::new(param_type.clone()), Box::new(func_var));
             }
             // a1: t1, ..., aN: tN |- b: u
             // fun a1 ... aN -> b : t1 -> ... -> tN -> u
-            unify_types(&expr_var, &func_var, types, &mut None);
-            infer_expr_types(expr1, &env, &vars, types, gen);
+            unify_types(&expr_var, &func_var, types, &mut None)?;
+            infer_expr_types(expr1, &env, &vars, types, gen)?;
         },
         Expr::Match(matche) => {
             let expr_var = expr_type_var(expr);
@@ -623,13 +646,13 @@ fn infer_expr_types(
             for (pat, expr2) in matche.1.iter().zip(matche.2.iter()) {
                 let mut vars = vars.clone();
                 let mut env = env.clone();
-                infer_pat_types(pat, &mut vars, types, gen);
+                infer_pat_types(pat, &mut vars, types, gen)?;
                 let pat_type = pat_type_var(pat);
-                unify_types(&pat_type, &expr1_var, types, &mut None);
+                unify_types(&pat_type, &expr1_var, types, &mut None)?;
                 let expr2_var = expr_type_var(expr2);
-                unify_types(&expr_var, &expr2_var, types, &mut None);
+                unify_types(&expr_var, &expr2_var, types, &mut None)?;
                 collect_free_type_vars(&expand_type(&pat_type, types), &mut env);
-                infer_expr_types(expr2, &env, &vars, types, gen);
+                infer_expr_types(expr2, &env, &vars, types, gen)?;
             }
         },
         Expr::Intrinsic(Intrinsic { params, ..}) => {
@@ -637,20 +660,20 @@ fn infer_expr_types(
             let mut vars = vars.clone();
             let mut func_var = Type::Variable(Variable::new(gen.generate_id()));
             for param in params.iter().rev() {
-                infer_pat_types(param, &mut vars, types, gen);
+                infer_pat_types(param, &mut vars, types, gen)?;
                 let param_type = pat_type_var(param);
                 func_var = Type::Function(Box::new(param_type.clone()), Box::new(func_var));
             }
-            unify_types(&func_var, &expr_var, types, &mut None);
+            unify_types(&func_var, &expr_var, types, &mut None)?;
         },
         Expr::LetBinding(def, expr2) => {
             let expr_var = expr_type_var(expr);
             let expr2_var = expr_type_var(expr2);
             let mut env = env.clone();
             let mut vars = vars.clone();
-            infer_binding_types(def, &mut env, &mut vars, types, gen);
-            unify_types(&expr_var, &expr2_var, types, &mut None);
-            infer_expr_types(expr2, &env, &vars, types, gen);
+            infer_binding_types(def, &mut env, &mut vars, types, gen)?;
+            unify_types(&expr_var, &expr2_var, types, &mut None)?;
+            infer_expr_types(expr2, &env, &vars, types, gen)?;
         },
         Expr::Variable(var) => {
             let expr_var = expr_type_var(expr);
@@ -662,9 +685,10 @@ fn infer_expr_types(
                 &fresh,
                 types,
                 &mut None,
-            );
+            )?;
         },
     }
+    Ok(())
 }
 
 /* Infer the type of the definition bindings and its contained sub-expressions.
@@ -675,8 +699,9 @@ fn infer_def_types(
     vars: &mut HashMap<VariableId, Type>,
     types: &mut HashMap<VariableId, Type>,
     gen: &mut VarGen,
-) {
-    infer_binding_types(&def.0, env, vars, types, gen);
+) -> Result<(), CompileError> {
+    infer_binding_types(&def.0, env, vars, types, gen)?;
+    Ok(())
 }
 
 /* Type check the module using Hindley Milner. */
@@ -686,10 +711,13 @@ pub fn infer_module_types(
     vars: &mut HashMap<VariableId, Type>,
     types: &mut HashMap<VariableId, Type>,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     allocate_module_types(annotated, gen);
     let mut env = HashMap::new();
-    // Initialize the type environment with the types of global variables
+    // Initialize the type environment with the types of global variables in a
+    // fixed order so that the type variables allocated here are reproducible
+    let mut globals: Vec<_> = globals.iter().collect();
+    globals.sort_by_key(|(_, id)| **id);
     for (name, id) in globals {
         if !vars.contains_key(id) {
             let mut var = Variable::new(gen.generate_id());
@@ -700,12 +728,16 @@ pub fn infer_module_types(
     for typ in vars.values() {
         collect_free_type_vars(typ, &mut env);
     }
-    for def in &mut annotated.defs {
-        infer_def_types(def, &mut env, vars, types, gen);
+    // Type errors are placed at the top-level item they are found in
+    for def in &annotated.defs {
+        infer_def_types(def, &mut env, vars, types, gen)
+            .map_err(|err| err.locate(def_span(annotated, def)))?;
     }
-    for expr in &mut annotated.exprs {
-        infer_expr_types(expr, &env, vars, types, gen);
+    for (idx, expr) in annotated.exprs.iter().enumerate() {
+        infer_expr_types(expr, &env, vars, types, gen)
+            .map_err(|err| err.locate(annotated.spans.exprs.get(idx).and_then(Option::as_ref)))?;
     }
+    Ok(())
 }
 
 /* Expand tuple pattern variables into tuple patterns. */
@@ -714,7 +746,7 @@ pub fn expand_pattern_variables(
     expr: &TExpr,
     map: &mut HashMap<VariableId, TPat>,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     match (&mut pat.v, &expr.v) {
         (Pat::Variable(var), _) if map.contains_key(&var.id) => {
             *pat = map[&var.id].clone();
@@ -726,7 +758,7 @@ pub fn expand_pattern_variables(
                 .as_ref()
                 .map(|x| x.to_owned() + ".0");
             let mut var1 = Pat::Variable(new_var1).type_pat(None);
-            expand_pattern_variables(&mut var1, &expr1, map, gen);
+            expand_pattern_variables(&mut var1, &expr1, map, gen)?;
             
             let mut new_var2 = Variable::new(gen.generate_id());
             new_var2.name = var
@@ -734,7 +766,7 @@ pub fn expand_pattern_variables(
                 .as_ref()
                 .map(|x| x.to_owned() + ".1");
             let mut var2 = Pat::Variable(new_var2).type_pat(None);
-            expand_pattern_variables(&mut var2, &expr2, map, gen);
+            expand_pattern_variables(&mut var2, &expr2, map, gen)?;
 
             let curr_id = var.id;
             pat.v = Pat::Product(Box::new(var1), Box::new(var2));
@@ -748,7 +780,7 @@ pub fn expand_pattern_variables(
                 .as_ref()
                 .map(|x| x.to_owned() + ".0");
             let mut var1 = Pat::Variable(new_var1).type_pat(expr1.t.clone());
-            expand_pattern_variables(&mut var1, &expr1, map, gen);
+            expand_pattern_variables(&mut var1, &expr1, map, gen)?;
             
             let mut new_var2 = Variable::new(gen.generate_id());
             new_var2.name = var
@@ -756,7 +788,7 @@ pub fn expand_pattern_variables(
                 .as_ref()
                 .map(|x| x.to_owned() + ".1");
             let mut var2 = Pat::Variable(new_var2).type_pat(expr2.t.clone());
-            expand_pattern_variables(&mut var2, &expr2, map, gen);
+            expand_pattern_variables(&mut var2, &expr2, map, gen)?;
 
             let curr_id = var.id;
             pat.v = Pat::Cons(Box::new(var1), Box::new(var2));
@@ -772,21 +804,22 @@ pub fn expand_pattern_variables(
         },
         (Pat::Variable(_), _) => {},
         (Pat::Product(pat1, pat2), Expr::Product(expr1, expr2)) => {
-            expand_pattern_variables(pat1, &expr1, map, gen);
-            expand_pattern_variables(pat2, &expr2, map, gen);
+            expand_pattern_variables(pat1, &expr1, map, gen)?;
+            expand_pattern_variables(pat2, &expr2, map, gen)?;
         },
         (Pat::Cons(pat1, pat2), Expr::Cons(expr1, expr2)) => {
-            expand_pattern_variables(pat1, &expr1, map, gen);
-            expand_pattern_variables(pat2, &expr2, map, gen);
+            expand_pattern_variables(pat1, &expr1, map, gen)?;
+            expand_pattern_variables(pat2, &expr2, map, gen)?;
         },
         (Pat::Constant(_), _) => {},
         (Pat::Unit, _) => {},
         (Pat::Nil, Expr::Nil) => {},
         (Pat::As(pat1, _name), _) => {
-            expand_pattern_variables(pat1, expr, map, gen);
+            expand_pattern_variables(pat1, expr, map, gen)?;
         },
-        _ => panic!("pattern {} cannot match {}", pat, expr),
+        _ => return Err(CompileError::Malformed(format!("pattern {} cannot match {}", pat, expr), None)),
     }
+    Ok(())
 }
 
 /* Expand tuple variables into tuple expressions using the available type
@@ -796,7 +829,7 @@ pub fn expand_expr_variables(
     map: &mut HashMap<VariableId, TExpr>,
     types: &HashMap<VariableId, Type>,
     gen: &mut VarGen,
-) {
+) -> Result<(), CompileError> {
     let typ = partial_expand_type(expr_type_var(expr), types);
     match (&mut expr.v, typ) {
         (Expr::Variable(var), _) if map.contains_key(&var.id) => {
@@ -809,7 +842,7 @@ pub fn expand_expr_variables(
                 .as_ref()
                 .map(|x| x.to_owned() + ".0");
             let mut var1 = Expr::Variable(new_var1).type_expr(Some(*typ1.clone()));
-            expand_expr_variables(&mut var1, map, types, gen);
+            expand_expr_variables(&mut var1, map, types, gen)?;
             
             let mut new_var2 = Variable::new(gen.generate_id());
             new_var2.name = var
@@ -817,7 +850,7 @@ pub fn expand_expr_variables(
                 .as_ref()
                 .map(|x| x.to_owned() + ".1");
             let mut var2 = Expr::Variable(new_var2).type_expr(Some(*typ2.clone()));
-            expand_expr_variables(&mut var2, map, types, gen);
+            expand_expr_variables(&mut var2, map, types, gen)?;
 
             let curr_id = var.id;
             expr.v = Expr::Product(Box::new(var1), Box::new(var2));
@@ -829,25 +862,29 @@ pub fn expand_expr_variables(
         },
         (Expr::Variable(_), Type::Int) => {},
         (Expr::Variable(var), Type::Function(_, _)) =>
-            panic!("the global function {} is undefined", var),
+            return Err(CompileError::Type(format!("the global function {} is undefined", var), None)),
         (Expr::Variable(var), Type::List(_)) =>
-            panic!("the global list {} is undefined", var),
+            return Err(CompileError::Type(format!("the global list {} is undefined", var), None)),
         (Expr::Variable(var), Type::Variable(_)) =>
-            panic!("unable to determine type of global variable {}", var),
+            return Err(CompileError::Type(format!("unable to determine type of global variable {}", var), None)),
         (Expr::Product(expr1, expr2), _) => {
-            expand_expr_variables(expr1, map, types, gen);
-            expand_expr_variables(expr2, map, types, gen);
+            expand_expr_variables(expr1, map, types, gen)?;
+            expand_expr_variables(expr2, map, types, gen)?;
         },
         (Expr::Constant(_), Type::Int) | (Expr::Unit, Type::Unit) => {},
-        _ => panic!("expression {} cannot have type {}", expr, expand_type(expr_type_var(expr), types)),
+        _ => return Err(CompileError::Type(
+            format!("expression {} cannot have type {}", expr, expand_type(expr_type_var(expr), types)),
+            None,
+        )),
     }
+    Ok(())
 }
 
-/* Print out the types of top-level program definitions. */
+/* Log the types of top-level program definitions. */
 pub fn print_types(module: &Module, types: &HashMap<VariableId, Type>) {
     for def in &module.defs {
         if let Some(typ) = &def.0.1.t {
-            println!("{}: {}", def.0.0, expand_type(typ, types));
+            trace!("{}: {}", def.0.0, expand_type(typ, types));
         }
     }
 }
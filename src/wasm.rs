@@ -0,0 +1,36 @@
+use crate::bindings;
+use crate::error::Error;
+use wasm_bindgen::prelude::*;
+
+fn js_error(err: Error) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+/* Compile the given source to a circuit of the given backend, given back as
+ * the contents of the circuit file that the command line would write. */
+#[wasm_bindgen]
+pub fn compile(source: &str, backend: &str) -> Result<Vec<u8>, JsError> {
+    bindings::compile(source, backend).map_err(js_error)
+}
+
+/* Prove the given circuit on the inputs of the given JSON object, drawing the
+ * randomness that hides the witnesses from the given entropy, which the
+ * caller takes from a source such as crypto.getRandomValues. Gives back the
+ * contents of the proof file that the command line would write. */
+#[wasm_bindgen]
+pub fn prove(circuit: &[u8], inputs_json: &str, entropy: &[u8]) -> Result<Vec<u8>, JsError> {
+    let rng = bindings::seeded_rng(entropy).map_err(js_error)?;
+    bindings::prove(circuit, inputs_json, rng).map_err(js_error)
+}
+
+/* Tell whether the given proof of the given circuit is valid and was made for
+ * the public values of the given JSON object, which must name every public
+ * variable of the circuit and nothing else. */
+#[wasm_bindgen]
+pub fn verify(circuit: &[u8], proof: &[u8], public_json: &str) -> Result<bool, JsError> {
+    match bindings::verify(circuit, proof, public_json) {
+        Ok(()) => Ok(true),
+        Err(Error::InvalidProof(_)) => Ok(false),
+        Err(err) => Err(js_error(err)),
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/* How often watched files are looked at. */
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/* What is known of the state of a file without reading it: its time of
+ * modification and its size, or nothing if it cannot be found, as happens
+ * while editors replace files on saving. */
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/* Watches files for changes by polling their stamps, which needs no support
+ * from the platform and is cheap for the handful of files that make up a
+ * program. */
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    stamps: Vec<Stamp>,
+    // How long files must stay unchanged before a change is reported
+    debounce: Duration,
+}
+
+impl Watcher {
+    /* Watch the given files from their current state. */
+    pub fn new(paths: Vec<PathBuf>, debounce: Duration) -> Self {
+        let stamps = paths.iter().map(|path| stamp(path)).collect();
+        Self { paths, stamps, debounce }
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /* Take the current stamps and give the files whose stamps have changed. */
+    fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed = vec![];
+        for (path, last) in self.paths.iter().zip(&mut self.stamps) {
+            let current = stamp(path);
+            if current != *last {
+                *last = current;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    /* Wait until some of the files change and then stay unchanged for the
+     * debounce interval, so that the bursts of writes made by a save are seen
+     * as one change, and give the files that changed. */
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        let mut changed = vec![];
+        let mut settled_at = Instant::now();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let now_changed = self.poll();
+            if !now_changed.is_empty() {
+                settled_at = Instant::now();
+                for path in now_changed {
+                    if !changed.contains(&path) {
+                        changed.push(path);
+                    }
+                }
+            } else if !changed.is_empty() && settled_at.elapsed() >= self.debounce {
+                return changed;
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::r1cs::{LinearCombination, R1cs};
+use num_bigint::BigInt;
+use std::io::{self, Write};
+use zkinterface::{BilinearConstraint, CircuitHeader, ConstraintSystem, Variables, Witness};
+
+/* The zkinterface id of the variable on the given wire of the given system.
+ * The wire holding one is variable 0, as zkinterface requires, and every
+ * other wire is one more than the id of its variable, so that ids do not
+ * depend on how the system lays its wires out. */
+pub fn variable_id(r1cs: &R1cs, wire: u32) -> u64 {
+    match wire {
+        0 => 0,
+        wire => r1cs.wires[wire as usize - 1].id as u64 + 1,
+    }
+}
+
+/* The number of bytes in which the elements of the field of the given system
+ * are written, enough for its largest element. */
+fn element_bytes(r1cs: &R1cs) -> usize {
+    let (_, bytes) = (&r1cs.prime - 1).to_bytes_le();
+    bytes.len()
+}
+
+/* Encode the given canonical field elements as zkinterface does, each in the
+ * same number of bytes in little-endian order. */
+fn encode_elements<'a>(values: impl IntoIterator<Item = &'a BigInt>, size: usize) -> Vec<u8> {
+    let mut encoded = vec![];
+    for value in values {
+        let (_, mut bytes) = value.to_bytes_le();
+        bytes.resize(size, 0);
+        encoded.extend(bytes);
+    }
+    encoded
+}
+
+/* The given linear combination as zkinterface variables, each with its
+ * coefficient as its value. */
+fn variables(r1cs: &R1cs, lc: &LinearCombination, size: usize) -> Variables {
+    Variables {
+        variable_ids: lc.keys().map(|wire| variable_id(r1cs, *wire)).collect(),
+        values: Some(encode_elements(lc.values(), size)),
+    }
+}
+
+/* The wires of the given system in the given range, as zkinterface variables,
+ * with the given values of every wire if there are any. */
+fn assigned(r1cs: &R1cs, wires: std::ops::Range<u32>, witness: Option<&[BigInt]>, size: usize) -> Variables {
+    Variables {
+        variable_ids: wires.clone().map(|wire| variable_id(r1cs, wire)).collect(),
+        values: witness.map(|witness| encode_elements(&witness[wires.start as usize..wires.end as usize], size)),
+    }
+}
+
+/* Write the given system as the messages of zkinterface: a circuit header
+ * declaring the public variables as instance variables, then the constraint
+ * system, then a witness assigning every other variable when the values of
+ * the wires, as given by R1cs::witness, are given. The header carries the
+ * values of the instance variables along with the witness. */
+pub fn write_zkif<W: Write>(r1cs: &R1cs, witness: Option<&[BigInt]>, mut writer: W) -> io::Result<()> {
+    let size = element_bytes(r1cs);
+    let public_end = r1cs.public_inputs + 1;
+    let free_variable_id = (1..r1cs.wire_count())
+        .map(|wire| variable_id(r1cs, wire) + 1)
+        .max()
+        .unwrap_or(1);
+    let header = CircuitHeader {
+        instance_variables: assigned(r1cs, 1..public_end, witness, size),
+        free_variable_id,
+        field_maximum: Some(encode_elements([&(&r1cs.prime - 1)], size)),
+        configuration: None,
+    };
+    let constraints = ConstraintSystem {
+        constraints: r1cs.constraints.iter().map(|constraint| BilinearConstraint {
+            linear_combination_a: variables(r1cs, &constraint.a, size),
+            linear_combination_b: variables(r1cs, &constraint.b, size),
+            linear_combination_c: variables(r1cs, &constraint.c, size),
+        }).collect(),
+    };
+    let to_io = |err: Box<dyn std::error::Error>| io::Error::new(io::ErrorKind::Other, err.to_string());
+    header.write_into(&mut writer).map_err(to_io)?;
+    constraints.write_into(&mut writer).map_err(to_io)?;
+    if witness.is_some() {
+        let witness = Witness {
+            assigned_variables: assigned(r1cs, public_end..r1cs.wire_count(), witness, size),
+        };
+        witness.write_into(&mut writer).map_err(to_io)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,37 @@
+/* Checks that compiled modules handed to arkworks make constraint systems
+ * that are satisfied by exactly the values that satisfy the modules. Only
+ * built with the ark-adapter feature. */
+#![cfg(feature = "ark-adapter")]
+
+mod common;
+
+use ark_bls12_381::Fr as BlsScalar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+use common::adapters::{assignments, check_satisfaction, compiled};
+use std::collections::HashMap;
+use vamp_ir::ark_adapter::ModuleCircuit;
+use vamp_ir::ast::Module;
+
+/* Synthesize the given module with the given values of its inputs, and tell
+ * whether they satisfy the constraint system made. */
+fn satisfied(module: &Module, inputs: &[(&str, i64)]) -> bool {
+    let cs = ConstraintSystem::<BlsScalar>::new_ref();
+    let circuit = ModuleCircuit::<BlsScalar>::new(module, assignments(module, inputs)).unwrap();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    // The instance holds one, followed by the public variables
+    assert_eq!(cs.num_instance_variables(), module.pubs.len() + 1);
+    cs.is_satisfied().unwrap()
+}
+
+#[test]
+fn constraint_system_agrees_with_module() {
+    check_satisfaction(satisfied);
+}
+
+#[test]
+fn missing_values_are_reported() {
+    let module = compiled();
+    let cs = ConstraintSystem::<BlsScalar>::new_ref();
+    let circuit = ModuleCircuit::<BlsScalar>::new(&module, HashMap::new()).unwrap();
+    assert!(matches!(circuit.generate_constraints(cs), Err(SynthesisError::AssignmentMissing)));
+}
@@ -0,0 +1,53 @@
+/* Checks that compiled modules handed to bellman make constraint systems that
+ * are satisfied by exactly the values that satisfy the modules, and that
+ * modules of fields other than bellman's are refused. Only built with the
+ * bellman-adapter feature. */
+#![cfg(feature = "bellman-adapter")]
+
+mod common;
+
+use ark_bls12_381::Fr as BlsScalar;
+use ark_bn254::Fr as Bn254Scalar;
+use bellman::gadgets::test::TestConstraintSystem;
+use bellman::{Circuit, SynthesisError};
+use bls12_381::Scalar;
+use common::adapters::{assignments, check_satisfaction, compiled, SOURCE};
+use std::collections::HashMap;
+use vamp_ir::ast::Module;
+use vamp_ir::bellman_adapter::BellmanCircuit;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::CompileOptions;
+
+/* Synthesize the given module with the given values of its inputs, and tell
+ * whether they satisfy the constraint system made. */
+fn satisfied(module: &Module, inputs: &[(&str, i64)]) -> bool {
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let field_ops = PrimeFieldOps::<BlsScalar>::default();
+    let circuit = BellmanCircuit::<Scalar>::new(module, assignments(module, inputs), &field_ops).unwrap();
+    circuit.synthesize(&mut cs).unwrap();
+    // The inputs are one, followed by the public variables
+    assert_eq!(cs.num_inputs(), module.pubs.len() + 1);
+    cs.is_satisfied()
+}
+
+#[test]
+fn constraint_system_agrees_with_module() {
+    check_satisfaction(satisfied);
+}
+
+#[test]
+fn missing_values_are_reported() {
+    let module = compiled();
+    let mut cs = TestConstraintSystem::<Scalar>::new();
+    let field_ops = PrimeFieldOps::<BlsScalar>::default();
+    let circuit = BellmanCircuit::<Scalar>::new(&module, HashMap::new(), &field_ops).unwrap();
+    assert!(matches!(circuit.synthesize(&mut cs), Err(SynthesisError::AssignmentMissing)));
+}
+
+#[test]
+fn modules_of_other_fields_are_refused() {
+    let module = vamp_ir::parse(SOURCE).unwrap();
+    let field_ops = PrimeFieldOps::<Bn254Scalar>::default();
+    let module = vamp_ir::compile(module, &field_ops, &CompileOptions::default()).unwrap().module;
+    assert!(BellmanCircuit::<Scalar>::new(&module, HashMap::new(), &field_ops).is_err());
+}
@@ -126,9 +126,9 @@ def (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15) = (65
 def (h0, h1, h2, h3, h4, h5, h6, h7) = (1779033703, 3144134277 , 1013904242, 2773480762, 1359893119, 2600822924, 528734635, 1541459225);
 
 // Second H vector has first value modified
-def h0 = xor32 (xor32 h0 16842752) 32;
+def h0_p = xor32 (xor32 h0 16842752) 32;
 
-def (h0_0, h1_0, h2_0, h3_0, h4_0, h5_0, h6_0, h7_0) = (h0, 3144134277 , 1013904242, 2773480762, 1359893119, 2600822924, 528734635, 1541459225);
+def (h0_0, h1_0, h2_0, h3_0, h4_0, h5_0, h6_0, h7_0) = (h0_p, 3144134277 , 1013904242, 2773480762, 1359893119, 2600822924, 528734635, 1541459225);
 
 // In our scenario, dd = 1. So we go straight to the final block:
 // h := F( h, d[dd - 1], ll + bb, TRUE ) = F(h, m, ll, TRUE)
@@ -144,9 +144,9 @@ def ll = 3;
 // Inizialize working vector v, first half from the state h, second half from IV.
 
 def (v0,v1,v2,v3,v4,v5,v6,v7,v8,v9,v10,v11,v12,v13,v14,v15) = (h0_0, h1_0, h2_0, h3_0, h4_0, h5_0, h6_0, h7_0, 1779033703, 3144134277 , 1013904242, 2773480762, 1359893119, 2600822924, 528734635, 1541459225);
-def v12 = xor32 v12 (fresh(ll % 4294967296));
-def v14 = xor32 v14 4294967295;
-def (v0_0,v1_0,v2_0,v3_0,v4_0,v5_0,v6_0,v7_0,v8_0,v9_0,v10_0,v11_0,v12_0,v13_0,v14_0,v15_0) = (v0,v1,v2,v3,v4,v5,v6,v7,v8,v9,v10,v11,v12,v13,v14,v15);
+def v12_p = xor32 v12 (fresh(ll % 4294967296));
+def v14_p = xor32 v14 4294967295;
+def (v0_0,v1_0,v2_0,v3_0,v4_0,v5_0,v6_0,v7_0,v8_0,v9_0,v10_0,v11_0,v12_0,v13_0,v14_0,v15_0) = (v0,v1,v2,v3,v4,v5,v6,v7,v8,v9,v10,v11,v12_p,v13,v14_p,v15);
 // first check with reference
 // (v0_0,v1_0,v2_0,v3_0,v4_0,v5_0,v6_0,v7_0,v8_0,v9_0,v10_0,v11_0,v12_0,v13_0,v14_0,v15_0) = (1795745351,3144134277,1013904242,2773480762,1359893119,2600822924,528734635,1541459225,1779033703,3144134277,1013904242,2773480762,1359893116,2600822924,3766232660,1541459225);
 
@@ -179,39 +179,29 @@ def sigma7 (s0, s1, s2, s3, s4, s5, s6, s7, s8, s9, s10, s11, s12, s13, s14, s15
 def sigma8 (s0, s1, s2, s3, s4, s5, s6, s7, s8, s9, s10, s11, s12, s13, s14, s15) = {(s6, s15, s14, s9, s11, s3, s0, s8, s12, s2, s13, s7, s1, s4, s10, s5)};
 def sigma9 (s0, s1, s2, s3, s4, s5, s6, s7, s8, s9, s10, s11, s12, s13, s14, s15) = {(s10, s2, s8, s4, s7, s6, s1, s5, s15, s11, s9, s14, s3, s12, s13, s0)};
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma0  (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_1,v1_1,v2_1,v3_1,v4_1,v5_1,v6_1,v7_1,v8_1,v9_1,v10_1,v11_1,v12_1,v13_1,v14_1,v15_1) = g_total (v0_0,v1_0,v2_0,v3_0,v4_0,v5_0,v6_0,v7_0,v8_0,v9_0,v10_0,v11_0,v12_0,v13_0,v14_0,v15_0)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_1,v1_1,v2_1,v3_1,v4_1,v5_1,v6_1,v7_1,v8_1,v9_1,v10_1,v11_1,v12_1,v13_1,v14_1,v15_1) = g_total (v0_0,v1_0,v2_0,v3_0,v4_0,v5_0,v6_0,v7_0,v8_0,v9_0,v10_0,v11_0,v12_0,v13_0,v14_0,v15_0)(sigma0 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 // check from reference:
 // (v0_1,v1_1,v2_1,v3_1,v4_1,v5_1,v6_1,v7_1,v8_1,v9_1,v10_1,v11_1,v12_1,v13_1,v14_1,v15_1) = (379790382,3619021368,3465339467,2457529825,2813604057,2477039950,2756607025,1104442779,2512335827,2593767809,1619671659,3060152382,2056115471,3191312087,893198054,1001671787);
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma1 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_2,v1_2,v2_2,v3_2,v4_2,v5_2,v6_2,v7_2,v8_2,v9_2,v10_2,v11_2,v12_2,v13_2,v14_2,v15_2) = g_total (v0_1,v1_1,v2_1,v3_1,v4_1,v5_1,v6_1,v7_1,v8_1,v9_1,v10_1,v11_1,v12_1,v13_1,v14_1,v15_1)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_2,v1_2,v2_2,v3_2,v4_2,v5_2,v6_2,v7_2,v8_2,v9_2,v10_2,v11_2,v12_2,v13_2,v14_2,v15_2) = g_total (v0_1,v1_1,v2_1,v3_1,v4_1,v5_1,v6_1,v7_1,v8_1,v9_1,v10_1,v11_1,v12_1,v13_1,v14_1,v15_1)(sigma1 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 // check from reference:
 // (v0_2,v1_2,v2_2,v3_2,v4_2,v5_2,v6_2,v7_2,v8_2,v9_2,v10_2,v11_2,v12_2,v13_2,v14_2,v15_2) = (987959267,159557995,3900802484,1043569430,4064491725,241619750,3758484748,3583091319,403382074,4243863828,819684054,1212646172,2130757006,4219762822,3309627446,1380626658);
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma2 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_3,v1_3,v2_3,v3_3,v4_3,v5_3,v6_3,v7_3,v8_3,v9_3,v10_3,v11_3,v12_3,v13_3,v14_3,v15_3) = g_total (v0_2,v1_2,v2_2,v3_2,v4_2,v5_2,v6_2,v7_2,v8_2,v9_2,v10_2,v11_2,v12_2,v13_2,v14_2,v15_2)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_3,v1_3,v2_3,v3_3,v4_3,v5_3,v6_3,v7_3,v8_3,v9_3,v10_3,v11_3,v12_3,v13_3,v14_3,v15_3) = g_total (v0_2,v1_2,v2_2,v3_2,v4_2,v5_2,v6_2,v7_2,v8_2,v9_2,v10_2,v11_2,v12_2,v13_2,v14_2,v15_2)(sigma2 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma3 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_4,v1_4,v2_4,v3_4,v4_4,v5_4,v6_4,v7_4,v8_4,v9_4,v10_4,v11_4,v12_4,v13_4,v14_4,v15_4) = g_total (v0_3,v1_3,v2_3,v3_3,v4_3,v5_3,v6_3,v7_3,v8_3,v9_3,v10_3,v11_3,v12_3,v13_3,v14_3,v15_3)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_4,v1_4,v2_4,v3_4,v4_4,v5_4,v6_4,v7_4,v8_4,v9_4,v10_4,v11_4,v12_4,v13_4,v14_4,v15_4) = g_total (v0_3,v1_3,v2_3,v3_3,v4_3,v5_3,v6_3,v7_3,v8_3,v9_3,v10_3,v11_3,v12_3,v13_3,v14_3,v15_3)(sigma3 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma4 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_5,v1_5,v2_5,v3_5,v4_5,v5_5,v6_5,v7_5,v8_5,v9_5,v10_5,v11_5,v12_5,v13_5,v14_5,v15_5) = g_total (v0_4,v1_4,v2_4,v3_4,v4_4,v5_4,v6_4,v7_4,v8_4,v9_4,v10_4,v11_4,v12_4,v13_4,v14_4,v15_4)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_5,v1_5,v2_5,v3_5,v4_5,v5_5,v6_5,v7_5,v8_5,v9_5,v10_5,v11_5,v12_5,v13_5,v14_5,v15_5) = g_total (v0_4,v1_4,v2_4,v3_4,v4_4,v5_4,v6_4,v7_4,v8_4,v9_4,v10_4,v11_4,v12_4,v13_4,v14_4,v15_4)(sigma4 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma5 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_6,v1_6,v2_6,v3_6,v4_6,v5_6,v6_6,v7_6,v8_6,v9_6,v10_6,v11_6,v12_6,v13_6,v14_6,v15_6) = g_total (v0_5,v1_5,v2_5,v3_5,v4_5,v5_5,v6_5,v7_5,v8_5,v9_5,v10_5,v11_5,v12_5,v13_5,v14_5,v15_5)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_6,v1_6,v2_6,v3_6,v4_6,v5_6,v6_6,v7_6,v8_6,v9_6,v10_6,v11_6,v12_6,v13_6,v14_6,v15_6) = g_total (v0_5,v1_5,v2_5,v3_5,v4_5,v5_5,v6_5,v7_5,v8_5,v9_5,v10_5,v11_5,v12_5,v13_5,v14_5,v15_5)(sigma5 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma6 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_7,v1_7,v2_7,v3_7,v4_7,v5_7,v6_7,v7_7,v8_7,v9_7,v10_7,v11_7,v12_7,v13_7,v14_7,v15_7) = g_total (v0_6,v1_6,v2_6,v3_6,v4_6,v5_6,v6_6,v7_6,v8_6,v9_6,v10_6,v11_6,v12_6,v13_6,v14_6,v15_6)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_7,v1_7,v2_7,v3_7,v4_7,v5_7,v6_7,v7_7,v8_7,v9_7,v10_7,v11_7,v12_7,v13_7,v14_7,v15_7) = g_total (v0_6,v1_6,v2_6,v3_6,v4_6,v5_6,v6_6,v7_6,v8_6,v9_6,v10_6,v11_6,v12_6,v13_6,v14_6,v15_6)(sigma6 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma7 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_8,v1_8,v2_8,v3_8,v4_8,v5_8,v6_8,v7_8,v8_8,v9_8,v10_8,v11_8,v12_8,v13_8,v14_8,v15_8) = g_total (v0_7,v1_7,v2_7,v3_7,v4_7,v5_7,v6_7,v7_7,v8_7,v9_7,v10_7,v11_7,v12_7,v13_7,v14_7,v15_7)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_8,v1_8,v2_8,v3_8,v4_8,v5_8,v6_8,v7_8,v8_8,v9_8,v10_8,v11_8,v12_8,v13_8,v14_8,v15_8) = g_total (v0_7,v1_7,v2_7,v3_7,v4_7,v5_7,v6_7,v7_7,v8_7,v9_7,v10_7,v11_7,v12_7,v13_7,v14_7,v15_7)(sigma7 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma8 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_9,v1_9,v2_9,v3_9,v4_9,v5_9,v6_9,v7_9,v8_9,v9_9,v10_9,v11_9,v12_9,v13_9,v14_9,v15_9) = g_total (v0_8,v1_8,v2_8,v3_8,v4_8,v5_8,v6_8,v7_8,v8_8,v9_8,v10_8,v11_8,v12_8,v13_8,v14_8,v15_8)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_9,v1_9,v2_9,v3_9,v4_9,v5_9,v6_9,v7_9,v8_9,v9_9,v10_9,v11_9,v12_9,v13_9,v14_9,v15_9) = g_total (v0_8,v1_8,v2_8,v3_8,v4_8,v5_8,v6_8,v7_8,v8_8,v9_8,v10_8,v11_8,v12_8,v13_8,v14_8,v15_8)(sigma8 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 
-def (m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15) = sigma9 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15);
-def (v0_10,v1_10,v2_10,v3_10,v4_10,v5_10,v6_10,v7_10,v8_10,v9_10,v10_10,v11_10,v12_10,v13_10,v14_10,v15_10) = g_total (v0_9,v1_9,v2_9,v3_9,v4_9,v5_9,v6_9,v7_9,v8_9,v9_9,v10_9,v11_9,v12_9,v13_9,v14_9,v15_9)(m0, m1, m2, m3, m4, m5, m6, m7, m8, m9, m10, m11, m12, m13, m14, m15);
+def (v0_10,v1_10,v2_10,v3_10,v4_10,v5_10,v6_10,v7_10,v8_10,v9_10,v10_10,v11_10,v12_10,v13_10,v14_10,v15_10) = g_total (v0_9,v1_9,v2_9,v3_9,v4_9,v5_9,v6_9,v7_9,v8_9,v9_9,v10_9,v11_9,v12_9,v13_9,v14_9,v15_9)(sigma9 (t0, t1, t2, t3, t4, t5, t6, t7, t8, t9, t10, t11, t12, t13, t14, t15));
 // check from reference
 // (v0_10,v1_10,v2_10,v3_10,v4_10,v5_10,v6_10,v7_10,v8_10,v9_10,v10_10,v11_10,v12_10,v13_10,v14_10,v15_10) = (3653866666,3488365222,1879902898,741893902,2942967654,486686451,496943741,2488489893,1050672829,2527062033,4014497313,2788325754,3740207150,2940043489,1317438107,1307245882);
 
@@ -0,0 +1,39 @@
+/* A mid-sized program, range checking two inputs to 16 bits and relating
+   them by a polynomial, proved with Bulletproofs. Run as follows:
+   vamp-ir bp compile -s tests/bulletproofs.pir -o circuit.bp
+   vamp-ir bp prove -c circuit.bp -i tests/bulletproofs.inputs -o proof.bp
+   vamp-ir bp verify -c circuit.bp -p proof.bp
+*/
+
+pub y;
+
+// Ensure that the given argument is 1 or 0, and returns it
+def bool x = { x*(x-1) = 0; x };
+
+// Ensure that the given argument fits in 16 bits, and returns it
+def range16 a = {
+    def a0 = bool (fresh ((a\1) % 2));
+    def a1 = bool (fresh ((a\2) % 2));
+    def a2 = bool (fresh ((a\4) % 2));
+    def a3 = bool (fresh ((a\8) % 2));
+    def a4 = bool (fresh ((a\16) % 2));
+    def a5 = bool (fresh ((a\32) % 2));
+    def a6 = bool (fresh ((a\64) % 2));
+    def a7 = bool (fresh ((a\128) % 2));
+    def a8 = bool (fresh ((a\256) % 2));
+    def a9 = bool (fresh ((a\512) % 2));
+    def a10 = bool (fresh ((a\1024) % 2));
+    def a11 = bool (fresh ((a\2048) % 2));
+    def a12 = bool (fresh ((a\4096) % 2));
+    def a13 = bool (fresh ((a\8192) % 2));
+    def a14 = bool (fresh ((a\16384) % 2));
+    def a15 = bool (fresh ((a\32768) % 2));
+    a = a0 + 2*a1 + 4*a2 + 8*a3 + 16*a4 + 32*a5 + 64*a6 + 128*a7 + 256*a8 + 512*a9 +
+        1024*a10 + 2048*a11 + 4096*a12 + 8192*a13 + 16384*a14 + 32768*a15;
+    a
+};
+
+def x16 = range16 x;
+def z16 = range16 z;
+
+x16*x16*x16 + 5*x16*z16 + 7 = y;
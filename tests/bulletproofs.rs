@@ -0,0 +1,91 @@
+/* Checks that a mid-sized program proves and verifies with Bulletproofs both
+ * on the command line and through the library, and that proofs are only
+ * accepted for the public inputs that they were made for. Only built with the
+ * bulletproofs feature, along with the command line. */
+#![cfg(all(feature = "cli", feature = "bulletproofs"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use vamp_ir::bp::circuit::{BulletproofsCircuit, BulletproofsProof};
+use vamp_ir::bp::synth::{make_scalar, BulletproofsCostModel, ScalarFieldOps};
+use vamp_ir::transform::CompileOptions;
+
+const SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bulletproofs.pir");
+const INPUTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/bulletproofs.inputs");
+
+/* Compile and prove tests/bulletproofs.pir in the given directory, giving the
+ * paths of the circuit and the proof. */
+fn prove(dir: &Path) -> (PathBuf, PathBuf) {
+    let circuit = dir.join("bulletproofs.bp");
+    let proof = dir.join("bulletproofs.proof");
+    assert_eq!(vamp_ir(&["bp", "compile", "-s", SOURCE, "-o", circuit.to_str().unwrap()]), 0);
+    assert_eq!(vamp_ir(&[
+        "bp", "prove", "-c", circuit.to_str().unwrap(), "-i", INPUTS, "-o", proof.to_str().unwrap(),
+    ]), 0);
+    (circuit, proof)
+}
+
+/* Verify the given proof against a file of the given public inputs. */
+fn verify(dir: &Path, circuit: &Path, proof: &Path, public_inputs: &str) -> i32 {
+    let path = dir.join("public.json");
+    fs::write(&path, public_inputs).unwrap();
+    vamp_ir(&[
+        "bp", "verify", "-c", circuit.to_str().unwrap(), "-p", proof.to_str().unwrap(),
+        "--public-inputs", path.to_str().unwrap(),
+    ])
+}
+
+#[test]
+fn proofs_verify() {
+    let dir = scratch_dir("verify");
+    let (circuit, proof) = prove(&dir);
+    assert_eq!(vamp_ir(&["bp", "verify", "-c", circuit.to_str().unwrap(), "-p", proof.to_str().unwrap()]), 0);
+    assert_eq!(verify(&dir, &circuit, &proof, r#"{"y": "1905741481"}"#), 0);
+}
+
+#[test]
+fn proofs_of_other_public_inputs_are_rejected() {
+    let dir = scratch_dir("other-public-inputs");
+    let (circuit, proof) = prove(&dir);
+    assert_eq!(verify(&dir, &circuit, &proof, r#"{"y": "1905741482"}"#), 1);
+}
+
+#[test]
+fn out_of_range_inputs_are_not_proved() {
+    let dir = scratch_dir("out-of-range");
+    let (circuit, _) = prove(&dir);
+    let proof = dir.join("bad.proof");
+    // 65536 does not fit in 16 bits, though it satisfies the polynomial
+    assert_eq!(vamp_ir(&[
+        "bp", "prove", "-c", circuit.to_str().unwrap(), "-i", INPUTS, "-D", "x=65536",
+        "-D", "y=281476392615943", "-o", proof.to_str().unwrap(),
+    ]), 1);
+    assert!(!proof.exists());
+}
+
+#[test]
+fn library_proofs_verify() {
+    let source = fs::read_to_string(SOURCE).unwrap();
+    let module = vamp_ir::parse(&source).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(BulletproofsCostModel));
+    let module = vamp_ir::compile(module, &ScalarFieldOps::default(), &options).unwrap().module;
+    let circuit = BulletproofsCircuit::new(module).unwrap();
+    let circuit = BulletproofsCircuit::from_bytes(&circuit.to_bytes().unwrap()).unwrap();
+
+    let inputs = HashMap::from([
+        ("x".to_string(), BigInt::from(1234)),
+        ("z".to_string(), BigInt::from(4321)),
+        ("y".to_string(), BigInt::from(1905741481)),
+    ]);
+    let proof = circuit.prove(&inputs).unwrap();
+    let mut proof = BulletproofsProof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+    assert!(circuit.verify(&proof).is_ok());
+    // The same proof is invalid for any other value of y
+    proof.public_values[0] = make_scalar(&BigInt::from(1905741482));
+    assert!(matches!(circuit.verify(&proof), Err(vamp_ir::Error::InvalidProof(_))));
+}
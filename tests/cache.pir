@@ -0,0 +1,20 @@
+/* An example for checking the compilation cache. The first compilation below
+   records its result in .vampir-cache, and the second reports reusing it
+   without inferring types again. Changing an option such as the optimization
+   level misses the cache and compiles afresh. After the cached entries are
+   overwritten with garbage, compilation warns about the corrupted entry and
+   compiles afresh, and --no-cache neither reads nor writes the cache. Run as
+   follows:
+   vamp-ir compile tests/cache.pir --emit-ir cache.ir
+   vamp-ir compile tests/cache.pir --emit-ir cache.ir --force
+   vamp-ir compile -O2 tests/cache.pir --emit-ir cache.ir --force
+   for entry in .vampir-cache/*.bin; do echo garbage > $entry; done
+   vamp-ir compile tests/cache.pir --emit-ir cache.ir --force
+   vamp-ir compile tests/cache.pir --emit-ir cache.ir --no-cache --force
+*/
+
+pub x, y;
+
+def square a = a * a;
+
+square x + square y = 25;
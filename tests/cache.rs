@@ -0,0 +1,57 @@
+/* Checks that compilations are found in the cache under exactly the sources
+ * and options they were made with, and that damaged entries are recompiled
+ * with a warning rather than reused. These all share the working directory
+ * that the cache is kept in, so they are checked in a single test. */
+#![cfg(feature = "compiler")]
+
+mod common;
+
+use ark_bls12_381::Fr;
+use common::scratch_dir;
+use std::fs;
+use vamp_ir::cache::{lookup, store, CacheKey};
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::CompileOptions;
+
+const SOURCE: &str = "pub x, y;\ndef square a = a * a;\nsquare x + square y = 25;\n";
+
+#[test]
+fn cache_entries_are_keyed_and_checked() {
+    std::env::set_current_dir(scratch_dir("cache")).unwrap();
+    let module = vamp_ir::parse(SOURCE).unwrap();
+    let module = vamp_ir::compile(module, &PrimeFieldOps::<Fr>::default(), &CompileOptions::default())
+        .unwrap()
+        .module;
+    let key = |options: &str, source: &str| CacheKey::new("plonk", options, &[String::new(), source.to_string()]);
+    let mut warnings = vec![];
+    assert!(lookup(&key("-O0", SOURCE), &mut warnings).is_none());
+    store(&key("-O0", SOURCE), &module, 1, &mut warnings);
+    // The same sources and options hit
+    let (cached, source_constraints) = lookup(&key("-O0", SOURCE), &mut warnings).unwrap();
+    assert_eq!(cached.to_string(), module.to_string());
+    assert_eq!(source_constraints, 1);
+    // Changing either misses without complaint
+    assert!(lookup(&key("-O2", SOURCE), &mut warnings).is_none());
+    assert!(lookup(&key("-O0", &SOURCE.replace("25", "26")), &mut warnings).is_none());
+    // Sources cannot be run together to collide
+    assert_ne!(key("-O0", "ab").path(), CacheKey::new("plonk", "-O0", &["a".to_string(), "b".to_string()]).path());
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    // Truncated and overwritten entries are warned about and not reused
+    let path = key("-O0", SOURCE).path();
+    let bytes = fs::read(&path).unwrap();
+    for damaged in [bytes[..bytes.len() / 2].to_vec(), b"garbage\n".to_vec()] {
+        fs::write(&path, damaged).unwrap();
+        assert!(lookup(&key("-O0", SOURCE), &mut warnings).is_none());
+        assert_eq!(warnings.drain(..).map(|warning| warning.code).collect::<Vec<_>>(), ["corrupted-cache-entry"]);
+    }
+    // A module altered after its checksum was taken is not reused either
+    let mut altered = bytes.clone();
+    altered[bytes.len() / 2] ^= 1;
+    fs::write(&path, altered).unwrap();
+    assert!(lookup(&key("-O0", SOURCE), &mut warnings).is_none());
+    // Storing again recovers the entry
+    warnings.clear();
+    store(&key("-O0", SOURCE), &module, 1, &mut warnings);
+    assert!(lookup(&key("-O0", SOURCE), &mut warnings).is_some());
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
@@ -0,0 +1,85 @@
+/* Checks that public values given as Ethereum ABI-encoded calldata, laid out
+ * as ethers encodes uint256 arguments and uint256[] arrays, are decoded in the
+ * order that the public variables are declared and checked by verifiers just
+ * as files of public inputs are. */
+#![cfg(all(feature = "cli", feature = "halo2"))]
+
+mod common;
+
+use ark_bn254::Fr;
+use common::{scratch_dir, vamp_ir};
+use num_bigint::BigInt;
+use std::collections::BTreeMap;
+use std::fs;
+use vamp_ir::calldata::decode_public_calldata;
+use vamp_ir::interface::Interface;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{CompileOptions, FieldOps};
+
+const R1CS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/r1cs.pir");
+
+/* Encode the given value as a single word of the ABI, in hexadecimal. */
+fn word(value: &BigInt) -> String {
+    format!("{:0>64}", value.to_str_radix(16))
+}
+
+/* The interface of tests/r1cs.pir, whose public variables are z and d, along
+ * with the order of its field. */
+fn r1cs_interface() -> (Interface, BigInt) {
+    let source = fs::read_to_string(R1CS).unwrap();
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let module = vamp_ir::compile(vamp_ir::parse(&source).unwrap(), &field_ops, &CompileOptions::default())
+        .unwrap()
+        .module;
+    (module.interface(), field_ops.canonical(BigInt::from(-1)) + 1)
+}
+
+#[test]
+fn calldata_is_decoded_in_declared_order() {
+    let (interface, modulus) = r1cs_interface();
+    assert_eq!(interface.public, ["z", "d"]);
+    let (z, d) = (word(&12.into()), word(&6.into()));
+    let expected: BTreeMap<_, _> = [("z".to_string(), BigInt::from(12)), ("d".to_string(), BigInt::from(6))].into();
+    // As uint256 arguments, with and without 0x
+    let static_args = format!("0x{}{}", z, d);
+    assert_eq!(decode_public_calldata(&static_args, &interface, &modulus).unwrap(), expected);
+    assert_eq!(decode_public_calldata(&static_args[2..], &interface, &modulus).unwrap(), expected);
+    // As a uint256[], whose elements follow its offset and length
+    let array = format!("0x{}{}{}{}", word(&32.into()), word(&2.into()), z, d);
+    assert_eq!(decode_public_calldata(&array, &interface, &modulus).unwrap(), expected);
+    // As either, behind the selector of the function called
+    let call = format!("0xdeadbeef{}{}{}{}", word(&32.into()), word(&2.into()), z, d);
+    assert_eq!(decode_public_calldata(&call, &interface, &modulus).unwrap(), expected);
+}
+
+#[test]
+fn malformed_calldata_is_rejected() {
+    let (interface, modulus) = r1cs_interface();
+    let (z, d) = (word(&12.into()), word(&6.into()));
+    // Values that are not elements of the field are not reduced
+    let too_large = format!("0x{}{}", z, word(&modulus));
+    assert!(decode_public_calldata(&too_large, &interface, &modulus).is_err());
+    let below = format!("0x{}{}", z, word(&(&modulus - 1)));
+    assert!(decode_public_calldata(&below, &interface, &modulus).is_ok());
+    // Too few words, a stray byte, or an array of the wrong length
+    assert!(decode_public_calldata(&format!("0x{}", z), &interface, &modulus).is_err());
+    assert!(decode_public_calldata(&format!("0x{}{}00", z, d), &interface, &modulus).is_err());
+    let short_array = format!("0x{}{}{}{}", word(&32.into()), word(&1.into()), z, d);
+    assert!(decode_public_calldata(&short_array, &interface, &modulus).is_err());
+    assert!(decode_public_calldata("0xnothex", &interface, &modulus).is_err());
+}
+
+#[test]
+fn verifiers_check_public_calldata() {
+    let dir = scratch_dir("verify");
+    let (source, circuit, proof) = (dir.join("mul.pir"), dir.join("mul.halo2"), dir.join("mul.proof"));
+    fs::write(&source, "pub z;\nx * y = z;\n").unwrap();
+    let (source, circuit, proof) = (source.to_str().unwrap(), circuit.to_str().unwrap(), proof.to_str().unwrap());
+    assert_eq!(vamp_ir(&["halo2", "compile", "--no-cache", "-s", source, "-o", circuit]), 0);
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", proof, "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 0);
+    let calldata = |value: u64| format!("0x{}", word(&value.into()));
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", proof, "--public-calldata", &calldata(12)]), 0);
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", proof, "--public-calldata", &calldata(13)]), 1);
+    // Calldata that cannot be decoded is a mistake of the caller
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", proof, "--public-calldata", "0x0c"]), 2);
+}
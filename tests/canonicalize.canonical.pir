@@ -0,0 +1,16 @@
+pub x, y;
+
+def mask = 65535;
+module util {
+    def sq a = a * a;
+    def pick 0 b = b;
+    def pick n b = util::sq b;
+};
+def twice f a = f (f a);
+def inc a = a + 1;
+def r = (x + 1) * (-y) ^ 2;
+
+util::pick 1 r = mask * y;
+twice inc x = y - 2;
+(def z = x; z) = 0;
+let s = x in s * s = y;
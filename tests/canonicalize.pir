@@ -0,0 +1,30 @@
+/* An example whose canonical form is kept alongside it in
+   tests/canonicalize.canonical.pir as a golden file. Run as follows:
+   vamp-ir canonicalize tests/canonicalize.pir -o canonical.pir
+   diff canonical.pir tests/canonicalize.canonical.pir
+*/
+
+pub x, y;
+
+def mask=0xff_ff;
+
+module util {
+  def sq a=a*a;
+  // Clauses of the same function are printed as clauses again
+  def pick 0 b = b;
+  def pick n b = sq b;
+};
+
+def twice f a = f(f a);
+
+def inc = fun a { a+1 };
+
+def r = (x+1)*(-y)^0b10;
+
+util::pick 1 r = mask*y;
+
+twice inc x = y-2;
+
+(def z=x;z) = 0o0;
+
+let s = x in s*s = y;
@@ -0,0 +1,11 @@
+/* An example of a file of inputs annotated with comments. Run as follows:
+   vamp-ir interpret -s tests/commented.pir -i tests/commented.inputs
+   The inputs must be read despite the comments and blank lines around their
+   entries, and the values written in hexadecimal and as negative integers
+   must give out = 11. Deleting the quotes around the value of fee must abort
+   with an error citing line 8 of the inputs.
+*/
+
+pub out;
+
+balance - fee + adjustment = out;
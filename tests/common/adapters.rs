@@ -0,0 +1,58 @@
+/* Fixtures shared by the tests of the circuit adapters, which hand compiled
+ * modules to other proving libraries. */
+
+use ark_bls12_381::Fr as BlsScalar;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use vamp_ir::ast::{Module, VariableId};
+use vamp_ir::plonk::field::{PlonkCostModel, PrimeFieldOps};
+use vamp_ir::transform::{input_name, required_inputs, CompileOptions};
+
+/* A program that uses every operation left after compilation. */
+pub const SOURCE: &str = "
+pub z;
+pub d;
+x * y = z;
+def q = z / y;
+q = x;
+def r = x | 0;
+(x - y) * -x + 3 + r = d;
+";
+
+/* Values of the inputs of SOURCE that satisfy it, and values that do not. */
+pub const SATISFYING: [&[(&str, i64)]; 2] = [
+    &[("x", 3), ("y", 4), ("z", 12), ("d", 6)],
+    &[("x", -5), ("y", 2), ("z", -10), ("d", -32)],
+];
+pub const VIOLATING: [&[(&str, i64)]; 2] = [
+    &[("x", 3), ("y", 4), ("z", 13), ("d", 6)],
+    &[("x", 3), ("y", 4), ("z", 12), ("d", 7)],
+];
+
+/* SOURCE compiled over the BLS12-381 scalar field. */
+pub fn compiled() -> Module {
+    let module = vamp_ir::parse(SOURCE).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(PlonkCostModel));
+    vamp_ir::compile(module, &PrimeFieldOps::<BlsScalar>::default(), &options).unwrap().module
+}
+
+/* The given values of the inputs of the given module, by their variables. */
+pub fn assignments(module: &Module, inputs: &[(&str, i64)]) -> HashMap<VariableId, BigInt> {
+    let inputs: HashMap<_, _> = inputs.iter().cloned().collect();
+    required_inputs(module).iter()
+        .map(|var| (var.id, BigInt::from(inputs[input_name(var).as_str()])))
+        .collect()
+}
+
+/* Check that the constraint system that an adapter makes of SOURCE, as told
+ * by the given function, is satisfied by exactly the values that satisfy the
+ * program. */
+pub fn check_satisfaction(satisfied: impl Fn(&Module, &[(&str, i64)]) -> bool) {
+    let module = compiled();
+    for inputs in SATISFYING {
+        assert!(satisfied(&module, inputs), "{:?} is not satisfying", inputs);
+    }
+    for inputs in VIOLATING {
+        assert!(!satisfied(&module, inputs), "{:?} is satisfying", inputs);
+    }
+}
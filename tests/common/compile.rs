@@ -0,0 +1,15 @@
+/* Helpers shared by the tests of compilation errors. */
+
+use ark_bls12_381::Fr;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{compile_with, CompileError, CompileOptions};
+
+/* Compile the given source without catching panics, so that only errors that
+ * are returned pass. */
+pub fn compile_error(source: &str) -> CompileError {
+    let module = vamp_ir::parse(source).unwrap();
+    match compile_with(module, &PrimeFieldOps::<Fr>::default(), &CompileOptions::default()) {
+        Ok(_) => panic!("{} compiled", source),
+        Err(err) => err,
+    }
+}
@@ -0,0 +1,38 @@
+/* Helpers shared by the tests, such as those that run the command line. Each
+ * test binary uses only some of them. */
+#![allow(dead_code)]
+
+#[cfg(any(feature = "ark-adapter", feature = "bellman-adapter"))]
+pub mod adapters;
+#[cfg(feature = "compiler")]
+pub mod compile;
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/* Make an empty directory, private to the given test, for artifacts. */
+pub fn scratch_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vamp-ir-{}-{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("unable to create scratch directory");
+    dir
+}
+
+/* Run vamp-ir with the given arguments and return its exit status. */
+#[cfg(feature = "cli")]
+pub fn vamp_ir(args: &[&str]) -> i32 {
+    run(env!("CARGO_BIN_EXE_vamp-ir"), args)
+}
+
+/* Run the given binary with the given arguments and return its exit status. */
+pub fn run(binary: &str, args: &[&str]) -> i32 {
+    Command::new(binary)
+        .args(args)
+        .env("VAMPIR_NONINTERACTIVE", "1")
+        .output()
+        .expect("unable to run vamp-ir")
+        .status
+        .code()
+        .expect("vamp-ir was killed by a signal")
+}
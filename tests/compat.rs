@@ -0,0 +1,26 @@
+/* Checks that the original signature of compile, kept for existing callers,
+ * still compiles as compile_with does with the default options. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{compile_with, CompileOptions};
+
+const SOURCE: &str = "pub z;\ndef square a = a * a;\nsquare x + square y = z;\n";
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_compile_matches_compile_with() {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let module = vamp_ir::transform::compile(vamp_ir::parse(SOURCE).unwrap(), &field_ops);
+    let expected = compile_with(vamp_ir::parse(SOURCE).unwrap(), &field_ops, &CompileOptions::default()).unwrap();
+    assert_eq!(module.to_string(), expected.module.to_string());
+}
+
+#[test]
+#[allow(deprecated)]
+#[should_panic(expected = "variables are not permitted")]
+fn deprecated_compile_panics_on_errors() {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    vamp_ir::transform::compile(vamp_ir::parse("pub x;\n\nx ^ x = 4;\n").unwrap(), &field_ops);
+}
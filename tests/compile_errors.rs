@@ -0,0 +1,95 @@
+/* Checks that malformed programs are reported as compile errors that say what
+ * went wrong and where in the source, rather than as panics. */
+#![cfg(feature = "compiler")]
+
+mod common;
+
+use ark_bls12_381::Fr;
+use common::compile::compile_error;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{compile_with, CompileError, CompileOptions};
+
+/* The line of the source that the given error was placed on. */
+fn error_line(err: &CompileError) -> usize {
+    err.span().unwrap_or_else(|| panic!("{} has no location", err)).start.0
+}
+
+#[test]
+fn escaping_functions_are_located() {
+    // A function equated with itself gets past type inference
+    let err = compile_error("def square a = a * a;\n\nsquare = square;\n");
+    assert!(matches!(err, CompileError::EscapingFunction(_, _)), "{}", err);
+    assert!(err.to_string().starts_with("unapplied function"), "{}", err);
+    assert_eq!(error_line(&err), 3);
+    assert_eq!(err.to_diagnostic().code, "escaping-function");
+    // Partial applications cannot stand as constraints either
+    let err = compile_error("def add a b = a + b;\nadd 1;\n");
+    assert!(err.to_string().starts_with("partially applied function"), "{}", err);
+    assert_eq!(error_line(&err), 2);
+    // Escapes within definitions are placed at the definition
+    let err = compile_error("def add a b = a + b;\ndef bad = { add = add; 0 };\nbad = 0;\n");
+    assert!(matches!(err, CompileError::EscapingFunction(_, _)), "{}", err);
+    assert_eq!(error_line(&err), 2);
+    assert!(matches!(
+        vamp_ir::compile(vamp_ir::parse("def add a b = a + b;\nadd 1;\n").unwrap(), &PrimeFieldOps::<Fr>::default(), &CompileOptions::default()),
+        Err(vamp_ir::Error::Compile(msg)) if msg.starts_with("2:1: partially applied function")
+    ));
+}
+
+#[test]
+fn runaway_recursion_names_its_calls() {
+    let source = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/merkle.pir")).unwrap();
+    let module = vamp_ir::parse(&source).unwrap();
+    let options = CompileOptions::default().unroll_limit(4);
+    let err = match compile_with(module, &PrimeFieldOps::<Fr>::default(), &options) {
+        Ok(_) => panic!("a path of depth 8 unrolled within 4 calls"),
+        Err(err) => err,
+    };
+    let CompileError::RecursionLimit { limit, chain, .. } = &err else {
+        panic!("{} is not a recursion limit", err);
+    };
+    assert_eq!(*limit, 4);
+    // The chain ends with the call that went one deeper than allowed
+    let calls = chain.iter().filter(|call| call.starts_with("merkle_root[")).count();
+    assert_eq!(calls, 5, "{}", err);
+    assert!(chain.last().unwrap().starts_with("merkle_root["), "{}", err);
+    assert_eq!(error_line(&err), 37);
+    assert_eq!(err.to_diagnostic().code, "recursion-limit");
+    // The default limit is enough for the same path
+    let module = vamp_ir::parse(&source).unwrap();
+    assert!(compile_with(module, &PrimeFieldOps::<Fr>::default(), &CompileOptions::default()).is_ok());
+}
+
+#[test]
+fn type_errors_are_located() {
+    // A function cannot be constrained like a number
+    let err = compile_error("def id x = x;\n\nid = 1;\n");
+    assert!(matches!(err, CompileError::Type(_, _)), "{}", err);
+    assert!(err.to_string().starts_with("unable to match"), "{}", err);
+    assert_eq!(error_line(&err), 3);
+    assert_eq!(err.to_diagnostic().code, "type-error");
+    // Mismatches within definitions are placed at the definition
+    let err = compile_error("def id x = x;\ndef bad = id + 1;\nbad = 0;\n");
+    assert!(matches!(err, CompileError::Type(_, _)), "{}", err);
+    assert_eq!(error_line(&err), 2);
+}
+
+#[test]
+fn evaluation_errors_are_located() {
+    // Exponents must be known when the program is evaluated
+    let err = compile_error("pub x;\n\nx ^ x = 4;\n");
+    assert!(matches!(err, CompileError::Malformed(_, _)), "{}", err);
+    assert!(err.to_string().starts_with("variables are not permitted"), "{}", err);
+    assert_eq!(error_line(&err), 3);
+    assert_eq!(err.to_diagnostic().code, "malformed-program");
+    // So must the lists that intrinsics are applied to
+    let err = compile_error("def mul a b = a * b;\ndef ps = zip_with mul (1:2:[]) (3:[]);\nps = 3:[];\n");
+    assert!(err.to_string().starts_with("zip_with applied to lists of differing lengths 2 and 1"), "{}", err);
+    assert_eq!(error_line(&err), 2);
+    // Constraints between distinct constants are found once constants fold
+    let source = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/contradiction.pir")).unwrap();
+    let err = compile_error(&source);
+    assert!(matches!(err, CompileError::Unsatisfiable(_)), "{}", err);
+    assert!(err.to_string().contains("9 = 10"), "{}", err);
+    assert_eq!(err.to_diagnostic().code, "unsatisfiable-constraint");
+}
@@ -0,0 +1,135 @@
+/* Checks the constraint documents written by export --format constraints-json
+ * by reading them back into modules, with an importer that exists only here,
+ * and checking that nothing of the constraint system was lost. */
+#![cfg(feature = "cli")]
+
+mod common;
+
+use ark_bn254::Fr;
+use common::{scratch_dir, vamp_ir};
+use num_bigint::BigInt;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use vamp_ir::ast::{Definition, Expr, InfixOp, LetBinding, Module, Pat, TExpr, TPat, Variable};
+use vamp_ir::constraints::CONSTRAINTS_VERSION;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::CompileOptions;
+
+const R1CS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/r1cs.pir");
+
+fn expr(v: Expr) -> TExpr {
+    TExpr { v, t: None }
+}
+
+fn import_variable(value: &Value) -> Variable {
+    Variable {
+        id: value["id"].as_u64().expect("variables have ids") as u32,
+        name: value["name"].as_str().map(str::to_string),
+    }
+}
+
+fn import_operand(value: &Value) -> TExpr {
+    match value["kind"].as_str() {
+        Some("variable") => expr(Expr::Variable(import_variable(value))),
+        Some("constant") => expr(Expr::Constant(value["value"].as_str().unwrap().parse::<BigInt>().unwrap())),
+        Some("operation") => import_operation(value),
+        kind => panic!("unknown kind of operand {:?}", kind),
+    }
+}
+
+/* Rebuild the expression of the given operator and operands, which may be a
+ * definition, a constraint, or an operand of either. */
+fn import_operation(value: &Value) -> TExpr {
+    let operands: Vec<_> = value["operands"].as_array().unwrap().iter().map(import_operand).collect();
+    let op = match (value["operator"].as_str().unwrap(), operands.len()) {
+        ("=", 1) => return operands[0].clone(),
+        ("-", 1) => return expr(Expr::Negate(Box::new(operands[0].clone()))),
+        ("+", 2) => InfixOp::Add,
+        ("-", 2) => InfixOp::Subtract,
+        ("*", 2) => InfixOp::Multiply,
+        ("/", 2) => InfixOp::Divide,
+        ("|", 2) => InfixOp::DivideZ,
+        ("^", 2) => InfixOp::Exponentiate,
+        ("\\", 2) => InfixOp::IntDivide,
+        ("%", 2) => InfixOp::Modulo,
+        (operator, count) => panic!("unknown operator {} of {} operand(s)", operator, count),
+    };
+    expr(Expr::Infix(op, Box::new(operands[0].clone()), Box::new(operands[1].clone())))
+}
+
+/* Read the given constraint document back into the module it was written
+ * from. */
+fn import(document: &Value) -> Module {
+    assert_eq!(document["version"], CONSTRAINTS_VERSION);
+    let pubs = document["public"].as_array().unwrap().iter().map(import_variable).collect();
+    let defs = document["definitions"].as_array().unwrap().iter().map(|def| {
+        let pat = TPat { v: Pat::Variable(import_variable(&def["result"])), t: None };
+        Definition(LetBinding(pat, Box::new(import_operation(def))))
+    }).collect();
+    let exprs = document["constraints"].as_array().unwrap().iter().map(|constraint| expr(Expr::Infix(
+        InfixOp::Equal,
+        Box::new(import_operation(constraint)),
+        Box::new(import_operand(&constraint["result"])),
+    ))).collect();
+    let names: BTreeMap<_, _> = document["variables"].as_array().unwrap().iter()
+        .filter_map(|var| Some((var["id"].as_u64()? as u32, var["name"].as_str()?.to_string())))
+        .collect();
+    Module { pubs, defs, exprs, names, ..Module::default() }
+}
+
+#[test]
+fn constraint_documents_round_trip() {
+    let source = fs::read_to_string(R1CS).unwrap();
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let module = vamp_ir::compile(vamp_ir::parse(&source).unwrap(), &field_ops, &CompileOptions::default())
+        .unwrap()
+        .module;
+    let document = module.constraints_json().unwrap();
+    let imported = import(&document);
+    assert_eq!(imported.to_string(), module.to_string());
+    assert_eq!(imported.constraints_json().unwrap(), document);
+    // The imported module is the same relation, so the same inputs satisfy it
+    let inputs = HashMap::from([
+        ("x".to_string(), 3.into()),
+        ("y".to_string(), 4.into()),
+        ("z".to_string(), 12.into()),
+        ("d".to_string(), 6.into()),
+    ]);
+    vamp_ir::assign_inputs(&imported, &inputs, &field_ops).unwrap();
+    let mut forged = inputs.clone();
+    forged.insert("d".to_string(), 7.into());
+    assert!(vamp_ir::assign_inputs(&imported, &forged, &field_ops).is_err());
+}
+
+#[test]
+fn exported_documents_describe_constraints() {
+    let dir = scratch_dir("export");
+    let path = dir.join("r1cs.json");
+    assert_eq!(vamp_ir(&[
+        "export", "--format", "constraints-json", R1CS, "-o", path.to_str().unwrap(),
+    ]), 0);
+    let mut document: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(document["field"], "bn254");
+    let public: Vec<_> = document["public"].as_array().unwrap().iter()
+        .map(|var| var["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(public, ["z", "d"]);
+    // Every variable that the constraints refer to is listed, by name and id
+    let variables = document["variables"].as_array().unwrap();
+    let roles: HashMap<_, _> = variables.iter()
+        .map(|var| (var["id"].as_u64().unwrap(), var["role"].as_str().unwrap()))
+        .collect();
+    for constraint in document["constraints"].as_array().unwrap() {
+        for operand in constraint["operands"].as_array().unwrap().iter().chain([&constraint["result"]]) {
+            if operand["kind"] == "variable" {
+                assert!(roles.contains_key(&operand["id"].as_u64().unwrap()), "{} is not listed", operand);
+            }
+        }
+    }
+    let x = variables.iter().find(|var| var["name"] == "x").expect("x is listed");
+    assert_eq!(x["role"], "input");
+    // The field is the command line's, not the module's
+    document.as_object_mut().unwrap().remove("field");
+    assert_eq!(import(&document).constraints_json().unwrap(), document);
+}
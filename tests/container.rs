@@ -0,0 +1,101 @@
+/* Checks proof containers against the vectors in tests/container, which
+ * other implementations can check themselves against as well, and that
+ * proofs written in containers are verified wherever bare proofs are. */
+#![cfg(all(feature = "cli", feature = "halo2"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use serde_json::Value;
+use std::fs;
+use std::time::Duration;
+use vamp_ir::container::{unwrap_container, ProofContainer, CONTAINER_START};
+use vamp_ir::metadata::ProofMetadata;
+
+const VECTORS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/container/vectors.json");
+
+fn vectors() -> Value {
+    serde_json::from_str(&fs::read_to_string(VECTORS).unwrap()).unwrap()
+}
+
+fn text(value: &Value) -> String {
+    value.as_str().expect("vector field is a string").to_string()
+}
+
+fn bytes(value: &Value) -> Vec<u8> {
+    hex::decode(value.as_str().expect("vector field is hexadecimal")).unwrap()
+}
+
+/* The container whose fields the given vector lists. */
+fn container(vector: &Value) -> ProofContainer {
+    let about = &vector["metadata"];
+    let public_inputs = vector["public_inputs"].as_array().unwrap().iter()
+        .map(|pair| (text(&pair[0]), text(&pair[1])))
+        .collect();
+    ProofContainer {
+        metadata: ProofMetadata {
+            tool_version: text(&about["tool_version"]),
+            backend: text(&vector["backend"]),
+            curve: text(&vector["curve"]),
+            circuit_hash: text(&vector["circuit_hash"]),
+            prover_time: Duration::from_micros(about["prover_us"].as_u64().unwrap()),
+            timestamp: about["timestamp"].as_u64().unwrap(),
+            public_inputs,
+        },
+        proof: bytes(&vector["proof"]),
+    }
+}
+
+#[test]
+fn containers_match_vectors() {
+    for vector in vectors()["valid"].as_array().unwrap() {
+        let cbor = bytes(&vector["cbor"]);
+        let expected = container(vector);
+        assert!(cbor.starts_with(CONTAINER_START));
+        // Encoding the fields gives exactly the bytes of the vector
+        assert_eq!(expected.to_cbor(), cbor, "{}", vector["description"]);
+        let decoded = ProofContainer::from_cbor(&cbor).unwrap();
+        assert_eq!(decoded.to_cbor(), cbor);
+        assert_eq!(decoded.metadata.backend, expected.metadata.backend);
+        assert_eq!(decoded.metadata.public_inputs, expected.metadata.public_inputs);
+        assert_eq!(decoded.metadata.prover_time, expected.metadata.prover_time);
+        assert_eq!(decoded.proof, expected.proof);
+        // Containers in text encodings are read as well
+        assert_eq!(unwrap_container(hex::encode(&cbor).into_bytes()).unwrap(), expected.proof);
+    }
+    for vector in vectors()["invalid"].as_array().unwrap() {
+        assert!(ProofContainer::from_cbor(&bytes(&vector["cbor"])).is_err(), "{}", vector["description"]);
+    }
+}
+
+#[test]
+fn contained_proofs_verify() {
+    let dir = scratch_dir("verify");
+    let source = dir.join("circuit.pir");
+    let circuit = dir.join("circuit.halo2");
+    let proof = dir.join("proof.cbor");
+    let text_proof = dir.join("proof.cbor.b64");
+    fs::write(&source, "pub z;\nx * y = z;\n").unwrap();
+    let (source, circuit) = (source.to_str().unwrap(), circuit.to_str().unwrap());
+    assert_eq!(vamp_ir(&["halo2", "compile", "--no-cache", "-s", source, "-o", circuit]), 0);
+    for (path, encoding) in [(&proof, "binary"), (&text_proof, "base64")] {
+        assert_eq!(vamp_ir(&[
+            "halo2", "prove", "-c", circuit, "-o", path.to_str().unwrap(), "--container", "cbor",
+            "--encoding", encoding, "-D", "x=3", "-D", "y=4", "-D", "z=12",
+        ]), 0);
+        assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", path.to_str().unwrap()]), 0);
+        assert_eq!(vamp_ir(&["verify", "-c", circuit, "-p", path.to_str().unwrap()]), 0);
+    }
+    let contents = fs::read(&proof).unwrap();
+    let contained = ProofContainer::from_cbor(&contents).unwrap();
+    assert_eq!((contained.metadata.backend.as_str(), contained.metadata.curve.as_str()), ("halo2", "pallas"));
+    assert_eq!(contained.metadata.public_inputs, [("z".to_string(), "12".to_string())]);
+    assert_eq!(vamp_ir(&["inspect", proof.to_str().unwrap()]), 0);
+    // A container claiming another backend than its proof is refused
+    let mut forged = contained;
+    forged.metadata.backend = "plonk".to_string();
+    forged.metadata.curve = "bls12-381".to_string();
+    let forged_path = dir.join("forged.cbor");
+    fs::write(&forged_path, forged.to_cbor()).unwrap();
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", forged_path.to_str().unwrap()]), 2);
+}
@@ -0,0 +1,14 @@
+/* Output should be a compilation error since the last constraint reduces to
+   9 = 10. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/contradiction.pir -o circuit.plonk
+*/
+
+pub x;
+
+def sq z = z * z;
+
+x = sq 2;
+// Constraints that always hold are dropped
+sq 2 = 4;
+sq 3 = 10;
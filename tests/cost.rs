@@ -0,0 +1,39 @@
+/* Checks that the cost model of the targeted backend decides how a circuit is
+ * lowered, and that the estimated costs in the stats follow from it. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use vamp_ir::halo2::field::Halo2CostModel;
+use vamp_ir::plonk::field::{PlonkCostModel, PrimeFieldOps};
+use vamp_ir::transform::{CompileOptions, CompileOutput, CostModel};
+
+/* Doubling a variable is cheaper as an addition only where copies are free. */
+const SOURCE: &str = "pub x, y; x * 2 = y;";
+
+/* Compile SOURCE at the given optimization level for the given backend. */
+fn compile(opt_level: u8, cost_model: Box<dyn CostModel>) -> CompileOutput {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let options = CompileOptions::default().opt_level(opt_level).cost_model(cost_model);
+    vamp_ir::compile(vamp_ir::parse(SOURCE).unwrap(), &field_ops, &options).unwrap()
+}
+
+#[test]
+fn cost_models_choose_lowerings() {
+    // Without optimizations, both backends keep the multiplication
+    for cost_model in [Box::new(PlonkCostModel) as Box<dyn CostModel>, Box::new(Halo2CostModel)] {
+        let compiled = compile(0, cost_model);
+        assert_eq!(compiled.stats.shapes.get("multiply"), Some(&1), "{}", compiled.module);
+        assert_eq!(compiled.stats.estimated_cost, Some(7));
+    }
+    // PLONK ties wires together for free, so the doubling becomes an addition
+    let plonk = compile(1, Box::new(PlonkCostModel));
+    assert_eq!(plonk.stats.shapes.get("add"), Some(&1), "{}", plonk.module);
+    assert_eq!(plonk.stats.shapes.get("multiply"), None);
+    assert_eq!(plonk.stats.estimated_cost, Some(6));
+    // Halo2 pays for the extra copy of x, which outweighs the saving
+    let halo2 = compile(1, Box::new(Halo2CostModel));
+    assert_eq!(halo2.stats.shapes.get("multiply"), Some(&1), "{}", halo2.module);
+    assert_eq!(halo2.stats.shapes.get("add"), None);
+    assert_eq!(halo2.stats.estimated_cost, Some(7));
+    assert_ne!(plonk.module.to_string(), halo2.module.to_string());
+}
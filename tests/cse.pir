@@ -0,0 +1,15 @@
+/* An example in which the same products recur in every call, so that sharing
+   identical operations at -O2 saves gates, as tests/cse.rs checks. Run as
+   follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/cse.pir -o circuit.plonk -O2
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub a, b, c;
+
+// a * b and b * a are the same operation
+def f x = (a * b + x) * (b * a + x);
+
+c = f 1 + f 2 + f 3;
@@ -0,0 +1,52 @@
+/* Checks that eliminating common subexpressions at the highest optimization
+ * level shares gates between identical operations without changing the
+ * values that provers derive. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use num_bigint::BigInt;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use vamp_ir::ast::Module;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{CompileOptions, MAX_OPT_LEVEL};
+
+const CSE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/cse.pir");
+
+/* Compile tests/cse.pir at the given optimization level. */
+fn compile(opt_level: u8) -> Module {
+    let module = vamp_ir::parse(&fs::read_to_string(CSE).unwrap()).unwrap();
+    let options = CompileOptions::default().opt_level(opt_level);
+    vamp_ir::compile(module, &PrimeFieldOps::<Fr>::default(), &options).unwrap().module
+}
+
+/* The distinct values of the variables of the given module derived from the
+ * given value of c, where a = 2 and b = 3. */
+fn witnesses(module: &Module, c: i64) -> Result<BTreeSet<BigInt>, vamp_ir::Error> {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let inputs = HashMap::from([
+        ("a".to_string(), BigInt::from(2)),
+        ("b".to_string(), BigInt::from(3)),
+        ("c".to_string(), BigInt::from(c)),
+    ]);
+    let assigns = vamp_ir::assign_inputs(module, &inputs, &field_ops)?;
+    Ok(assigns.into_values().collect())
+}
+
+#[test]
+fn common_subexpressions_are_shared() {
+    let unshared = compile(0);
+    let shared = compile(MAX_OPT_LEVEL);
+    assert!(
+        shared.exprs.len() < unshared.exprs.len(),
+        "sharing left {} of {} gates", shared.exprs.len(), unshared.exprs.len(),
+    );
+    // (6 + 1)^2 + (6 + 2)^2 + (6 + 3)^2 = 194, and every value derived for a
+    // shared variable was derived for each of the variables it replaced
+    let shared_witnesses = witnesses(&shared, 194).unwrap();
+    assert!(shared_witnesses.is_subset(&witnesses(&unshared, 194).unwrap()));
+    assert!(shared_witnesses.contains(&BigInt::from(6)));
+    // Both circuits reject the same wrong sum
+    assert!(witnesses(&unshared, 195).is_err());
+    assert!(witnesses(&shared, 195).is_err());
+}
@@ -0,0 +1,16 @@
+/* An example that raises diagnostics of more than one kind. Run as follows:
+   vamp-ir compile --warn-shadowing --diagnostics-format json -s tests/diagnostics.pir --emit-ir diagnostics.ir
+   Standard error must then hold exactly two lines, each a JSON object:
+   a shadowed-binding warning whose span starts at line 14, column 1 and
+   whose suggestion is to rename this binding of x, followed by an
+   overflowing-constant warning with no span whose suggestion is to write 1
+   instead. With --deny-warnings also given, the shadowed-binding warning
+   must instead be followed by a denied-warnings error, and the command
+   must exit with status 2, as for any input error, rather than panic.
+*/
+
+pub a, b;
+
+let x = a in let x = x + 1 in x = b;
+
+a * 52435875175126190479447740508185965837690552500527637822603658699938581184514 = b;
@@ -0,0 +1,48 @@
+/* Checks how diagnostics are rendered for people, with and without colors,
+ * against the snapshots in tests/snapshots. Escape characters are written
+ * there as \e so that the colored snapshots can be read and edited. */
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::scratch_dir;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/* Run vamp-ir quietly in the given directory with the given arguments and
+ * environment variables, and return what it wrote to standard error. */
+fn vamp_ir_stderr(dir: &Path, args: &[&str], env: &[(&str, &str)]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_vamp-ir"))
+        .current_dir(dir)
+        .arg("-q")
+        .args(args)
+        .env_remove("NO_COLOR")
+        .env_remove("CLICOLOR_FORCE")
+        .envs(env.iter().copied())
+        .output()
+        .expect("unable to run vamp-ir");
+    assert_eq!(output.status.code(), Some(2));
+    String::from_utf8(output.stderr).expect("diagnostics are UTF-8").replace('\x1b', "\\e")
+}
+
+/* Read the snapshot of the given name. */
+fn snapshot(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("snapshots").join(name);
+    fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {}", path.to_string_lossy(), err))
+}
+
+#[test]
+fn diagnostics_match_snapshots() {
+    let dir = scratch_dir("diagnostics");
+    fs::write(dir.join("redefined.pir"), "def x = 1;\nx = 1;\ndef x = 2;\n").unwrap();
+    let args = ["interpret", "redefined.pir"];
+
+    // Standard error is not a terminal here, so colors must be forced
+    assert_eq!(vamp_ir_stderr(&dir, &args, &[]), snapshot("redefinition.plain"));
+    assert_eq!(vamp_ir_stderr(&dir, &args, &[("CLICOLOR_FORCE", "1")]), snapshot("redefinition.color"));
+    // Asking for no colors wins over forcing them
+    assert_eq!(vamp_ir_stderr(&dir, &args, &[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]), snapshot("redefinition.plain"));
+    let args = ["--no-color", "interpret", "redefined.pir"];
+    assert_eq!(vamp_ir_stderr(&dir, &args, &[("CLICOLOR_FORCE", "1")]), snapshot("redefinition.plain"));
+}
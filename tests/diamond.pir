@@ -0,0 +1,18 @@
+/* An example whose witnesses depend on each other in a diamond: both b and c
+   are derived from a, and d is derived from both b and c. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/diamond.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub x, y;
+
+def a = fresh (x + 1);
+def b = fresh (a * 2);
+def c = fresh (a * 3);
+def d = fresh (b + c);
+
+a = x + 1;
+d = 5 * a;
+y = d;
@@ -0,0 +1,10 @@
+/* An example that defines the same relation as tests/diff_alt.pir even
+   though it compiles to different constraints. Run as follows:
+   vamp-ir diff tests/diff.pir tests/diff_alt.pir
+*/
+
+pub x, y;
+
+def double a = a + a;
+
+double (x * x) = y;
@@ -0,0 +1,9 @@
+/* An example that defines the same relation as tests/diff.pir. Replacing the
+   2 below with any other constant makes the circuits distinguishable. Run as
+   follows:
+   vamp-ir diff tests/diff.pir tests/diff_alt.pir
+*/
+
+pub x, y;
+
+2 * x ^ 2 = y;
@@ -0,0 +1,82 @@
+/* Checks that dividing by zero is reported as an error of compilation naming
+ * the expression that divides, rather than as a panic of the field arithmetic
+ * or of the synthesis of gates beneath it. */
+#![cfg(all(feature = "cli", feature = "halo2"))]
+
+mod common;
+
+use ark_bls12_381::Fr;
+use common::compile::compile_error;
+use common::{scratch_dir, vamp_ir};
+use std::fs;
+use vamp_ir::ast::InfixOp;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{CompileError, CompileOptions, FieldOpError, FieldOps};
+
+#[test]
+fn field_operations_report_division_by_zero() {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    for op in [InfixOp::Divide, InfixOp::IntDivide, InfixOp::Modulo] {
+        assert_eq!(field_ops.infix(op, 5.into(), 0.into()), Err(FieldOpError::DivisionByZero));
+    }
+    assert_eq!(field_ops.infix(InfixOp::Exponentiate, 0.into(), (-1).into()), Err(FieldOpError::DivisionByZero));
+    assert_eq!(field_ops.infix(InfixOp::Exponentiate, 0.into(), 0.into()), Ok(1.into()));
+    assert_eq!(field_ops.infix(InfixOp::DivideZ, 5.into(), 0.into()), Ok(0.into()));
+}
+
+#[test]
+fn constant_division_by_zero_names_expression() {
+    let err = compile_error("x = 5 / 0;");
+    assert!(matches!(err, CompileError::FieldOp(FieldOpError::DivisionByZero, _)));
+    assert_eq!(err.to_string(), "division by zero in (5/0)");
+    assert_eq!(err.to_diagnostic().code, "division-by-zero");
+    // Divisors that only fold to zero are caught just the same
+    let err = compile_error("x = 5 / (3 - 3);");
+    assert_eq!(err.to_string(), "division by zero in (5/(3-3))");
+    let err = compile_error("def f y = 5 / y;\nx = f 0;");
+    assert!(matches!(err, CompileError::FieldOp(FieldOpError::DivisionByZero, _)));
+    assert!(matches!(
+        vamp_ir::compile(vamp_ir::parse("x = 5 / 0;").unwrap(), &PrimeFieldOps::<Fr>::default(), &CompileOptions::default()),
+        Err(vamp_ir::Error::Compile(msg)) if msg == "division by zero in (5/0)"
+    ));
+}
+
+#[test]
+fn constant_division_by_zero_is_an_input_error() {
+    let dir = scratch_dir("cli");
+    let source = dir.join("divide.pir");
+    fs::write(&source, "x = 5 / (3 - 3);\n").unwrap();
+    let source = source.to_str().unwrap();
+    assert_eq!(vamp_ir(&["interpret", source]), 2);
+    let circuit = dir.join("divide.halo2");
+    assert_eq!(vamp_ir(&["halo2", "compile", "--no-cache", "-s", source, "-o", circuit.to_str().unwrap()]), 2);
+    assert!(!circuit.exists());
+}
+
+#[test]
+fn variable_division_by_zero_names_expression() {
+    for (source, expr) in [("x = y / 0;", "(y/0)"), ("x = y \\ (2 - 2);", "(y\\(2-2))"), ("x = y % 0;", "(y%0)")] {
+        let err = compile_error(source);
+        assert!(matches!(err, CompileError::FieldOp(FieldOpError::DivisionByZero, _)), "{}", err);
+        assert_eq!(err.to_string(), format!("division by zero in {}", expr));
+    }
+    // Divisors that become zero only once definitions are propagated
+    let err = compile_error("def z = 0;
+x = y / z;");
+    assert!(matches!(err, CompileError::FieldOp(FieldOpError::DivisionByZero, _)), "{}", err);
+    // Dividing by zero where it is defined as zero still compiles
+    let module = vamp_ir::parse("x = y | 0;").unwrap();
+    assert!(vamp_ir::compile(module, &PrimeFieldOps::<Fr>::default(), &CompileOptions::default()).is_ok());
+}
+
+#[test]
+fn variable_division_by_zero_is_an_input_error() {
+    let dir = scratch_dir("variable");
+    let source = dir.join("divide.pir");
+    fs::write(&source, "pub x;\nx = y / 0;\n").unwrap();
+    let source = source.to_str().unwrap();
+    // Halo2 synthesis would otherwise panic inverting the divisor
+    let circuit = dir.join("divide.halo2");
+    assert_eq!(vamp_ir(&["halo2", "compile", "--no-cache", "-s", source, "-o", circuit.to_str().unwrap()]), 2);
+    assert!(!circuit.exists());
+}
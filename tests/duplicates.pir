@@ -0,0 +1,14 @@
+/* An example whose second constraint duplicates the first with its sides
+   swapped. Without -O2, the summary reports 6 three-address constraints. With
+   it, the shared squares leave the two equalities between them identical up
+   to orientation, so one is dropped and 3 constraints remain, as
+   tests/duplicates.rs checks. Run as follows:
+   vamp-ir compile tests/duplicates.pir --emit-ir duplicates.ir
+   vamp-ir compile -O2 tests/duplicates.pir --emit-ir duplicates.ir --force
+*/
+
+pub x, y;
+
+x * x = y * y;
+
+y * y = x * x;
@@ -0,0 +1,32 @@
+/* Checks that constraints duplicating others up to the order of their
+ * operands are dropped, and that constraints on distinct variables are not. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use std::fs;
+use vamp_ir::ast::Module;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{deduplicate_constraints, CompileOptions, MAX_OPT_LEVEL};
+
+const DUPLICATES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/duplicates.pir");
+
+/* Compile tests/duplicates.pir at the given optimization level. */
+fn compile(opt_level: u8) -> Module {
+    let module = vamp_ir::parse(&fs::read_to_string(DUPLICATES).unwrap()).unwrap();
+    let options = CompileOptions::default().opt_level(opt_level);
+    vamp_ir::compile(module, &PrimeFieldOps::<Fr>::default(), &options).unwrap().module
+}
+
+#[test]
+fn duplicate_constraints_are_dropped() {
+    // Each equality of squares takes a gate per square and one equating them
+    let mut module = compile(0);
+    assert_eq!(module.exprs.len(), 6, "{}", module);
+    // Without shared squares, no two constraints are on the same variables
+    assert_eq!(deduplicate_constraints(&mut module, &PrimeFieldOps::<Fr>::default()), 0);
+    assert_eq!(module.exprs.len(), 6);
+    // Sharing the squares leaves the two equalities identical up to
+    // orientation, and only one of them is kept
+    let module = compile(MAX_OPT_LEVEL);
+    assert_eq!(module.exprs.len(), 3, "{}", module);
+}
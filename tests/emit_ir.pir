@@ -0,0 +1,13 @@
+/* An example whose intermediate representation is kept alongside it in
+   tests/emit_ir.ir as a golden file. Run as follows:
+   vamp-ir compile tests/emit_ir.pir --emit-ir emit_ir.ir
+   diff emit_ir.ir tests/emit_ir.ir
+*/
+
+pub x, y;
+
+def square a = a * a;
+
+def r = fresh (x + 1);
+
+square x + square y = r * r;
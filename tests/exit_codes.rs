@@ -0,0 +1,367 @@
+/* Checks that the exit status of vamp-ir tells scripts what happened: 0 for
+ * success, 1 for a rejected proof or violated constraints, and 2 for unusable
+ * inputs. */
+#![cfg(all(feature = "cli", feature = "halo2", feature = "plonk"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn halo2_verify_rejects_bad_proof() {
+    let dir = scratch_dir("halo2");
+    let source = dir.join("circuit.pir");
+    let circuit = dir.join("circuit.halo2");
+    let proof = dir.join("proof.halo2");
+    let bad_proof = dir.join("bad_proof.halo2");
+    fs::write(&source, "pub z;\nx * y = z;\n").unwrap();
+    let (source, circuit, proof, bad_proof) = (
+        source.to_str().unwrap(),
+        circuit.to_str().unwrap(),
+        proof.to_str().unwrap(),
+        bad_proof.to_str().unwrap(),
+    );
+
+    assert_eq!(vamp_ir(&["halo2", "compile", "--no-cache", "-s", source, "-o", circuit]), 0);
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", proof, "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 0);
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", proof]), 0);
+    // Existing outputs are only replaced when forced
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", proof, "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 2);
+    // Proofs written as text on standard output verify just as well
+    let output = Command::new(env!("CARGO_BIN_EXE_vamp-ir"))
+        .args(["-q", "halo2", "prove", "-c", circuit, "-o", "-", "--encoding", "hex", "-D", "x=3", "-D", "y=4", "-D", "z=12"])
+        .output()
+        .expect("unable to run vamp-ir");
+    assert_eq!(output.status.code(), Some(0));
+    let hex_proof = dir.join("proof.hex");
+    fs::write(&hex_proof, output.stdout).unwrap();
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", hex_proof.to_str().unwrap()]), 0);
+    assert_eq!(vamp_ir(&["inspect", circuit]), 0);
+    // Templates of inputs only come from circuits
+    let template = dir.join("template.toml");
+    assert_eq!(vamp_ir(&["inputs-template", "-c", circuit, "-o", template.to_str().unwrap()]), 0);
+    assert_eq!(vamp_ir(&["inputs-template", "-c", proof]), 2);
+    assert_eq!(vamp_ir(&["inspect", proof]), 0);
+    // Metadata is written beside proofs on request, and only shown when found
+    let described_proof = dir.join("described.halo2");
+    let described_proof = described_proof.to_str().unwrap();
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", described_proof, "--metadata", "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 0);
+    assert!(dir.join("described.halo2.meta.json").exists());
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", described_proof]), 0);
+    assert_eq!(vamp_ir(&["inspect", described_proof]), 0);
+    fs::write(dir.join("described.halo2.meta.json"), "not metadata").unwrap();
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", described_proof]), 0);
+    // Public and private inputs may be split, but only along declarations
+    let public = dir.join("public.inputs");
+    let private = dir.join("private.inputs");
+    let misplaced = dir.join("misplaced.inputs");
+    fs::write(&public, "{\"z\": \"12\"}").unwrap();
+    fs::write(&private, "{\"x\": \"3\", \"y\": \"4\"}").unwrap();
+    fs::write(&misplaced, "{\"y\": \"4\", \"z\": \"12\"}").unwrap();
+    let (public, private, misplaced) = (public.to_str().unwrap(), private.to_str().unwrap(), misplaced.to_str().unwrap());
+    let split_proof = dir.join("split.halo2");
+    let split_proof = split_proof.to_str().unwrap();
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", split_proof, "--public-inputs", public, "--private-inputs", private]), 0);
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", split_proof]), 0);
+    // Verifiers holding the public inputs reject proofs of other values
+    let other_public = dir.join("other.public.inputs");
+    fs::write(&other_public, "{\"z\": \"13\"}").unwrap();
+    let other_public = other_public.to_str().unwrap();
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", split_proof, "--public-inputs", public]), 0);
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", split_proof, "--public-inputs", other_public]), 1);
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", split_proof, "--public-inputs", private]), 2);
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", split_proof, "--force", "--public-inputs", misplaced, "--private-inputs", private]), 2);
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", split_proof, "--force", "--public-inputs", public, "--private-inputs", misplaced]), 2);
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", split_proof, "--force", "--public-inputs", public]), 2);
+    // Sources have no header to tell what they are
+    assert_eq!(vamp_ir(&["inspect", source]), 2);
+    // Dry runs write nothing but still reject inputs that would fail
+    let dry_circuit = dir.join("dry.halo2");
+    let dry_proof = dir.join("dry.proof");
+    assert_eq!(vamp_ir(&["halo2", "compile", "--no-cache", "--dry-run", "-s", source, "-o", dry_circuit.to_str().unwrap()]), 0);
+    assert!(!dry_circuit.exists());
+    assert_eq!(vamp_ir(&["halo2", "prove", "--dry-run", "-c", circuit, "-o", dry_proof.to_str().unwrap(), "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 0);
+    assert!(!dry_proof.exists());
+    assert_eq!(vamp_ir(&["halo2", "prove", "--dry-run", "-c", circuit, "-o", dry_proof.to_str().unwrap(), "-D", "x=3", "-D", "y=4", "-D", "z=13"]), 1);
+    // Repeated proofs share a key, and are identical when their randomness
+    // is seeded
+    let repeated = dir.join("repeated");
+    let repeated_dir = repeated.to_str().unwrap();
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "--output-dir", repeated_dir, "--repeat", "3", "--seed", "7", "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 0);
+    let repeated_proofs: Vec<_> = (1..=3).map(|index| fs::read(repeated.join(format!("proof-{}", index))).unwrap()).collect();
+    assert!(repeated_proofs.iter().all(|proof| *proof == repeated_proofs[0]));
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "--proof-dir", repeated_dir]), 0);
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", dry_proof.to_str().unwrap(), "--repeat", "0", "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 2);
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", circuit, "-o", dry_proof.to_str().unwrap(), "--output-dir", repeated_dir, "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 2);
+
+    // Corrupt the middle of the proof while keeping its length
+    let mut bytes = fs::read(proof).unwrap();
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0x5a;
+    fs::write(bad_proof, bytes).unwrap();
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", bad_proof]), 1);
+    // A batch fails if any one of its proofs does
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", proof, "-p", hex_proof.to_str().unwrap()]), 0);
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", proof, "-p", bad_proof]), 1);
+
+    // A proof that cannot be found is an input error
+    let missing = dir.join("missing.halo2");
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", circuit, "-p", missing.to_str().unwrap()]), 2);
+    // And so is a proof given in place of a circuit, which its header exposes
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", proof, "-p", proof]), 2);
+}
+
+#[test]
+fn interpret_reports_violated_constraints() {
+    let dir = scratch_dir("interpret");
+    let source = dir.join("circuit.pir");
+    fs::write(&source, "pub z;\nx * y = z;\n").unwrap();
+    let source = source.to_str().unwrap();
+
+    assert_eq!(vamp_ir(&["interpret", "-s", source, "-D", "x=3", "-D", "y=4", "-D", "z=12"]), 0);
+    assert_eq!(vamp_ir(&["interpret", "-s", source, "-D", "x=3", "-D", "y=4", "-D", "z=13"]), 1);
+    // Naming a variable that the circuit lacks is an input error
+    assert_eq!(vamp_ir(&["interpret", "-s", source, "-D", "w=1"]), 2);
+
+    // Inputs can come from the environment, below those on the command line
+    let with_env = |args: &[&str], y: &str| Command::new(env!("CARGO_BIN_EXE_vamp-ir"))
+        .args(args)
+        .env("VAMPIR_NONINTERACTIVE", "1")
+        .env("VAMPIR_INPUT_x", "3")
+        .env("VAMPIR_INPUT_y", y)
+        .output()
+        .expect("unable to run vamp-ir")
+        .status
+        .code();
+    assert_eq!(with_env(&["interpret", "-s", source, "--env-inputs", "-D", "z=12"], "4"), Some(0));
+    assert_eq!(with_env(&["interpret", "-s", source, "--env-inputs", "-D", "z=12", "-D", "y=5"], "4"), Some(1));
+    assert_eq!(with_env(&["interpret", "-s", source, "--env-inputs", "-D", "z=12"], "four"), Some(2));
+    // Environment variables are only read when asked for
+    assert_eq!(with_env(&["interpret", "-s", source, "-D", "z=12"], "4"), Some(2));
+
+    // Names in files that match no variable are rejected along with the names
+    // they likely misspell, unless extra inputs are allowed
+    let misspelt = dir.join("misspelt.json");
+    fs::write(&misspelt, "{\"x\": 3, \"yy\": 4, \"z\": 12}").unwrap();
+    let misspelt = misspelt.to_str().unwrap();
+    let (status, result) = vamp_ir_json(&["interpret", "-s", source, "-i", misspelt]);
+    assert_eq!(status, 2);
+    assert!(result["error"].as_str().unwrap().contains("yy (did you mean y?)"));
+    let shared = dir.join("shared.toml");
+    fs::write(&shared, "x = 3\ny = 4\nz = 12\namount = 5\n").unwrap();
+    let shared = shared.to_str().unwrap();
+    assert_eq!(vamp_ir(&["interpret", "-s", source, "-i", shared]), 2);
+    assert_eq!(vamp_ir(&["interpret", "-s", source, "-i", shared, "--allow-extra-inputs"]), 0);
+    assert_eq!(vamp_ir(&["interpret", "-s", source, "-i", misspelt, "--allow-extra-inputs"]), 2);
+}
+
+#[test]
+fn native_inputs_allow_comments() {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let source = fixtures.join("commented.pir");
+    let inputs = fixtures.join("commented.inputs");
+    let (source, inputs_path) = (source.to_str().unwrap(), inputs.to_str().unwrap());
+    let (status, result) = vamp_ir_json(&["interpret", "-s", source, "-i", inputs_path]);
+    assert_eq!(status, 0);
+    assert_eq!(result["values"]["out"], "11");
+
+    // Malformed entries are reported by line, counting comments
+    let dir = scratch_dir("commented");
+    let malformed = dir.join("malformed.inputs");
+    let text = fs::read_to_string(&inputs).unwrap().replace("\"fee\": \"3\"", "\"fee\": 3");
+    fs::write(&malformed, text).unwrap();
+    let (status, result) = vamp_ir_json(&["interpret", "-s", source, "-i", malformed.to_str().unwrap()]);
+    assert_eq!(status, 2);
+    assert!(result["error"].as_str().unwrap().contains("line 8: $.fee"));
+    // Comments are only allowed in the native format
+    assert_eq!(vamp_ir(&["interpret", "-s", source, "-i", inputs_path, "--format", "json"]), 2);
+}
+
+/* Run vamp-ir with the given arguments and --json, and return its exit status
+ * along with the JSON object it printed. */
+fn vamp_ir_json(args: &[&str]) -> (i32, serde_json::Value) {
+    let output = Command::new(env!("CARGO_BIN_EXE_vamp-ir"))
+        .arg("--json")
+        .args(args)
+        .env("VAMPIR_NONINTERACTIVE", "1")
+        .output()
+        .expect("unable to run vamp-ir");
+    let result = serde_json::from_slice(&output.stdout).expect("vamp-ir printed a single JSON object");
+    (output.status.code().expect("vamp-ir was killed by a signal"), result)
+}
+
+#[test]
+fn json_results_describe_outcomes() {
+    let dir = scratch_dir("json");
+    let source = dir.join("circuit.pir");
+    let circuit = dir.join("circuit.halo2");
+    fs::write(&source, "pub z;\nx * y = z;\n").unwrap();
+    let (source, circuit) = (source.to_str().unwrap(), circuit.to_str().unwrap());
+
+    let (status, result) = vamp_ir_json(&["halo2", "compile", "--no-cache", "-s", source, "-o", circuit]);
+    assert_eq!(status, 0);
+    assert_eq!(result["command"], "halo2 compile");
+    assert_eq!(result["status"], "success");
+    assert_eq!(result["artifacts"]["circuit"], circuit);
+    // Proving and verifying name the circuit by the same digest
+    let hash = result["circuit_hash"].clone();
+    assert!(hash.is_string());
+    let (status, result) = vamp_ir_json(&["interpret", "-s", source, "-D", "x=3", "-D", "y=4", "-D", "z=12"]);
+    assert_eq!(status, 0);
+    assert_eq!(result["values"]["z"], "12");
+    let (_, result) = vamp_ir_json(&["inspect", circuit]);
+    assert_eq!(result["content_digest"], hash);
+
+    let (status, result) = vamp_ir_json(&["interpret", "-s", source, "-D", "x=3", "-D", "y=4", "-D", "z=13"]);
+    assert_eq!(status, 1);
+    assert_eq!(result["status"], "failure");
+    assert!(result["error"].is_string());
+    // Errors raised while compiling are reported in the object too
+    let (status, result) = vamp_ir_json(&["interpret", "-s", source, "-D", "w=1"]);
+    assert_eq!(status, 2);
+    assert_eq!(result["status"], "input_error");
+}
+
+#[test]
+fn input_mistakes_are_reported_together() {
+    let dir = scratch_dir("mistakes");
+    let source = dir.join("circuit.pir");
+    let params = dir.join("params.pp");
+    let halo2_circuit = dir.join("circuit.halo2");
+    let plonk_circuit = dir.join("circuit.plonk");
+    let proof = dir.join("proof");
+    let inputs = dir.join("inputs.json");
+    fs::write(&source, "pub total;\namount * rate = total;\n").unwrap();
+    // Two names are misspelt and total is left out
+    fs::write(&inputs, "{\"amonut\": 3, \"rte\": 4}").unwrap();
+    let [source, params, halo2_circuit, plonk_circuit, proof, inputs] =
+        [&source, &params, &halo2_circuit, &plonk_circuit, &proof, &inputs].map(|path| path.to_str().unwrap());
+
+    assert_eq!(vamp_ir(&["plonk", "setup", "-o", params]), 0);
+    assert_eq!(vamp_ir(&["halo2", "compile", "--no-cache", "-s", source, "-o", halo2_circuit]), 0);
+    assert_eq!(vamp_ir(&["plonk", "compile", "--no-cache", "-u", params, "-s", source, "-o", plonk_circuit]), 0);
+    for args in [
+        &["halo2", "prove", "-c", halo2_circuit, "-o", proof, "-i", inputs][..],
+        &["plonk", "prove", "-u", params, "-c", plonk_circuit, "-o", proof, "-i", inputs][..],
+    ] {
+        let (status, result) = vamp_ir_json(args);
+        assert_eq!(status, 2);
+        let error = result["error"].as_str().unwrap();
+        assert!(error.contains("missing inputs: amount, rate, total"), "{}", error);
+        assert!(error.contains("amonut (did you mean amount?)"), "{}", error);
+        assert!(error.contains("rte (did you mean rate?)"), "{}", error);
+    }
+}
+
+#[test]
+fn stdin_supplies_one_source_at_most() {
+    assert_eq!(vamp_ir(&["interpret", "-s", "-", "-i", "-"]), 2);
+    assert_eq!(vamp_ir(&["compile", "-", "-", "--emit-ir", "-"]), 2);
+    // Standard input never changes in a way that could be watched
+    assert_eq!(vamp_ir(&["watch", "-"]), 2);
+}
+
+#[test]
+fn convert_rebuilds_circuits_for_the_other_backend() {
+    let dir = scratch_dir("convert");
+    let source = dir.join("circuit.pir");
+    let big = dir.join("big.pir");
+    let params = dir.join("params.pp");
+    let circuit = dir.join("circuit.halo2");
+    let converted = dir.join("converted.plonk");
+    let back = dir.join("back.halo2");
+    let plonk_proof = dir.join("proof.plonk");
+    let halo2_proof = dir.join("proof.halo2");
+    // Negative constants must keep their meaning in the other field
+    fs::write(&source, "pub z;\nx * y - 1 = z;\n").unwrap();
+    // This constant is an integer of the BLS12-381 scalar field but not of the
+    // smaller Pallas field
+    fs::write(&big, format!("pub x;\nx = 0x3{};\n", "0".repeat(63))).unwrap();
+    let [source, big, params, circuit, converted, back, plonk_proof, halo2_proof] =
+        [&source, &big, &params, &circuit, &converted, &back, &plonk_proof, &halo2_proof].map(|path| path.to_str().unwrap());
+    let inputs = ["-D", "x=3", "-D", "y=4", "-D", "z=11"];
+
+    assert_eq!(vamp_ir(&["plonk", "setup", "-o", params]), 0);
+    assert_eq!(vamp_ir(&["halo2", "compile", "--no-cache", "-s", source, "-o", circuit]), 0);
+    assert_eq!(vamp_ir(&["convert", "-i", circuit, "--to", "plonk", "-u", params, "-o", converted]), 0);
+    assert_eq!(vamp_ir(&[&["plonk", "prove", "-u", params, "-c", converted, "-o", plonk_proof][..], &inputs[..]].concat()), 0);
+    assert_eq!(vamp_ir(&["plonk", "verify", "-u", params, "-c", converted, "-p", plonk_proof]), 0);
+    // Converting back gives the very circuit that was compiled
+    assert_eq!(vamp_ir(&["convert", "-i", converted, "--to", "halo2", "-o", back]), 0);
+    assert_eq!(fs::read(circuit).unwrap(), fs::read(back).unwrap());
+    assert_eq!(vamp_ir(&[&["halo2", "prove", "-c", back, "-o", halo2_proof][..], &inputs[..]].concat()), 0);
+    assert_eq!(vamp_ir(&["halo2", "verify", "-c", back, "-p", halo2_proof]), 0);
+
+    // Circuits are only converted to the other backend, and PLONK circuits
+    // need parameters for their keys
+    assert_eq!(vamp_ir(&["convert", "-i", back, "--to", "halo2", "-o", "unused.halo2"]), 2);
+    assert_eq!(vamp_ir(&["convert", "-i", circuit, "--to", "plonk", "-o", "unused.plonk"]), 2);
+    assert_eq!(vamp_ir(&["convert", "-i", halo2_proof, "--to", "plonk", "-u", params, "-o", "unused.plonk"]), 2);
+    let big_circuit = dir.join("big.plonk");
+    let big_circuit = big_circuit.to_str().unwrap();
+    assert_eq!(vamp_ir(&["plonk", "compile", "--no-cache", "-u", params, "-s", big, "-o", big_circuit]), 0);
+    assert_eq!(vamp_ir(&["convert", "-i", big_circuit, "--to", "halo2", "-o", "unused.halo2"]), 2);
+}
+
+#[test]
+fn stdio_conflicts_are_usage_errors() {
+    assert_eq!(vamp_ir(&["halo2", "prove", "-c", "-", "-o", "proof", "-i", "-"]), 2);
+    assert_eq!(vamp_ir(&["halo2", "compile", "-s", "source.pir", "-o", "-", "--emit-ir", "-"]), 2);
+    assert_eq!(vamp_ir(&["plonk", "verify", "-u", "-", "-c", "circuit", "-p", "-"]), 2);
+    // Results rendered as JSON take standard output for themselves
+    assert_eq!(vamp_ir(&["--json", "witness", "-s", "source.pir", "-o", "-"]), 2);
+}
+
+/* Compile, prove, and verify through pipes alone, with the verifier reading
+ * a second, identical compilation through process substitution. */
+#[test]
+fn pipes_carry_compile_prove_and_verify() {
+    let bin = env!("CARGO_BIN_EXE_vamp-ir");
+    let compile = format!("printf 'pub z;\\nx * y = z;\\n' | '{}' -q halo2 compile --no-cache -s - -o -", bin);
+    let prove = format!("'{}' -q halo2 prove -c - -o - -D x=3 -D y=4 -D z=12", bin);
+    let verify = format!("'{}' -q halo2 verify -c <({}) -p -", bin, compile);
+    let status = Command::new("bash")
+        .arg("-c")
+        .arg(format!("set -o pipefail; {} | {} | {}", compile, prove, verify))
+        .env("VAMPIR_NONINTERACTIVE", "1")
+        .status()
+        .expect("unable to run bash");
+    assert_eq!(status.code(), Some(0));
+}
+
+#[test]
+fn usage_errors_exit_with_status_2() {
+    assert_eq!(vamp_ir(&["halo2", "verify"]), 2);
+    assert_eq!(vamp_ir(&["no-such-command"]), 2);
+}
+
+#[test]
+fn top_level_commands_infer_the_backend() {
+    let dir = scratch_dir("top-level");
+    let source = dir.join("circuit.pir");
+    let ir = dir.join("circuit.ir");
+    let circuit = dir.join("circuit.halo2");
+    let proof = dir.join("proof.halo2");
+    fs::write(&source, "pub z;\nx * y = z;\n").unwrap();
+    let [source, ir, circuit, proof] = [&source, &ir, &circuit, &proof].map(|path| path.to_str().unwrap());
+    let inputs = ["-D", "x=3", "-D", "y=4", "-D", "z=12"];
+
+    // Compiling without a backend still only gives the IR
+    assert_eq!(vamp_ir(&["compile", "-s", source, "--emit-ir", ir]), 0);
+    assert_eq!(vamp_ir(&["compile", "--backend", "halo2", "--no-cache", "-s", source, "-o", circuit]), 0);
+    // The circuit's header names the backend that proves and verifies it
+    assert_eq!(vamp_ir(&[&["prove", "-c", circuit, "-o", proof][..], &inputs[..]].concat()), 0);
+    assert_eq!(vamp_ir(&["verify", "-c", circuit, "-p", proof]), 0);
+    assert_eq!(vamp_ir(&["verify", "--backend=halo2", "-c", circuit, "-p", proof]), 0);
+    assert_eq!(vamp_ir(&[&["check", "-c", circuit][..], &inputs[..]].concat()), 0);
+    assert_eq!(vamp_ir(&["check", "-c", circuit, "-D", "x=3", "-D", "y=4", "-D", "z=13"]), 1);
+    // A backend that disagrees with the header is a usage error
+    assert_eq!(vamp_ir(&["verify", "--backend", "plonk", "-c", circuit, "-p", proof]), 2);
+    assert_eq!(vamp_ir(&[&["check", "--backend", "plonk", "-c", circuit][..], &inputs[..]].concat()), 2);
+    assert_eq!(vamp_ir(&["prove", "--backend", "groth16", "-c", circuit, "-o", "unused"]), 2);
+    // Nothing names the backend of a circuit read from standard input
+    assert_eq!(vamp_ir(&["prove", "-c", "-", "-o", "unused"]), 2);
+}
@@ -0,0 +1,38 @@
+/* Checks that the library and binaries build with each combination of the
+ * features of the proving backends, so that shared code never names what a
+ * feature leaves out. Each build checks the crate again from scratch in a
+ * target directory of its own, which takes minutes, so these only run when
+ * asked for with --ignored, as the feature builds workflow does. */
+
+use std::process::Command;
+
+/* Check the crate with the given features alone, returning whether it built. */
+fn check_features(features: &str) -> bool {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let target_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("feature-builds");
+    Command::new(cargo)
+        .args(["check", "--lib", "--bins", "--no-default-features", "--features", features])
+        .arg("--manifest-path").arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+        .arg("--target-dir").arg(target_dir)
+        .status()
+        .expect("unable to run cargo")
+        .success()
+}
+
+#[test]
+#[ignore]
+fn command_line_builds_with_each_backend() {
+    for features in [
+        "cli", "cli,halo2", "cli,plonk", "cli,halo2,plonk", "cli,groth16", "cli,bulletproofs", "cli,zkinterface",
+    ] {
+        assert!(check_features(features), "the command line does not build with {}", features);
+    }
+}
+
+#[test]
+#[ignore]
+fn library_builds_with_each_backend() {
+    for features in ["halo2-verify", "verifier-cli", "compiler", "halo2", "plonk", "halo2,plonk"] {
+        assert!(check_features(features), "the library does not build with {}", features);
+    }
+}
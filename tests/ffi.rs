@@ -0,0 +1,34 @@
+/* Checks the C bindings from C: builds the harness in tests/ffi against
+ * include/vampir.h and the shared library, and runs it. Only built with the
+ * ffi feature, and only on Unix, where the library is found by rpath. */
+#![cfg(all(feature = "ffi", unix))]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/* The directory that cargo puts the shared library in, which is the parent
+ * of the one holding this test. */
+fn library_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("unable to find the test executable");
+    exe.parent().and_then(Path::parent).expect("test executable is in a deps directory").to_path_buf()
+}
+
+#[test]
+fn c_harness_round_trips() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let lib = library_dir();
+    let harness = Path::new(env!("CARGO_TARGET_TMPDIR")).join("vampir-ffi-harness");
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg(root.join("tests/ffi/harness.c"))
+        .arg("-I").arg(root.join("include"))
+        .arg("-L").arg(&lib)
+        .arg(format!("-Wl,-rpath,{}", lib.display()))
+        .args(["-lvamp_ir", "-o"])
+        .arg(&harness)
+        .status()
+        .expect("unable to run the C compiler");
+    assert!(status.success(), "the C harness does not build");
+    let status = Command::new(&harness).status().expect("unable to run the C harness");
+    assert!(status.success(), "the C harness failed");
+}
@@ -0,0 +1,64 @@
+/* Drives the C bindings through a round trip, as a service linking against
+ * vamp-ir would, checking that every buffer handed out is freed exactly once
+ * and that failures leave nothing behind. Exits with status 0 if all is
+ * well. */
+
+#include <stdio.h>
+#include <string.h>
+#include "vampir.h"
+
+static int failures = 0;
+
+#define CHECK(cond) do { \
+    if (!(cond)) { \
+        const char *error = vampir_last_error(); \
+        fprintf(stderr, "%s:%d: %s failed (%s)\n", __FILE__, __LINE__, #cond, error ? error : "no error"); \
+        failures++; \
+    } \
+} while (0)
+
+int main(void) {
+    VampirBuffer circuit = { NULL, 0 };
+    VampirBuffer proof = { NULL, 0 };
+    uint8_t entropy[32];
+    memset(entropy, 7, sizeof entropy);
+
+    CHECK(vampir_compile("pub z; x * y = z;", "halo2", &circuit) == VAMPIR_OK);
+    CHECK(circuit.data != NULL && circuit.len > 0);
+    CHECK(vampir_last_error() == NULL);
+
+    CHECK(vampir_prove(circuit.data, circuit.len, "{\"x\": 3, \"y\": \"4\", \"z\": \"0xc\"}",
+                       entropy, sizeof entropy, &proof) == VAMPIR_OK);
+    CHECK(proof.data != NULL && proof.len > 0);
+    CHECK(vampir_verify(circuit.data, circuit.len, proof.data, proof.len, "{\"z\": 12}") == VAMPIR_OK);
+    CHECK(vampir_verify(circuit.data, circuit.len, proof.data, proof.len, "{\"z\": 13}") == VAMPIR_INVALID_PROOF);
+    CHECK(vampir_last_error() != NULL);
+
+    /* Proving from the randomness of the operating system */
+    vampir_buffer_free(&proof);
+    CHECK(proof.data == NULL && proof.len == 0);
+    CHECK(vampir_prove(circuit.data, circuit.len, "{\"x\": 3, \"y\": 4, \"z\": 12}", NULL, 0, &proof) == VAMPIR_OK);
+    CHECK(vampir_verify(circuit.data, circuit.len, proof.data, proof.len, "{\"z\": 12}") == VAMPIR_OK);
+    vampir_buffer_free(&proof);
+
+    /* Failures empty the buffer to be filled in, and say why */
+    CHECK(vampir_prove(circuit.data, circuit.len, "{\"x\": 3, \"y\": 4, \"z\": 13}",
+                       entropy, sizeof entropy, &proof) == VAMPIR_INPUT_ERROR);
+    CHECK(proof.data == NULL && proof.len == 0);
+    CHECK(vampir_last_error() != NULL && strstr(vampir_last_error(), "violated") != NULL);
+    CHECK(vampir_prove(circuit.data, circuit.len, "{\"x\": 3, \"y\": 4, \"z\": 12}",
+                       entropy, 16, &proof) == VAMPIR_INPUT_ERROR);
+    CHECK(vampir_prove(circuit.data, circuit.len, "{}", NULL, 0, NULL) == VAMPIR_INPUT_ERROR);
+    vampir_buffer_free(&circuit);
+    CHECK(vampir_compile("x * = z;", "halo2", &circuit) == VAMPIR_INPUT_ERROR);
+    CHECK(circuit.data == NULL && circuit.len == 0);
+    CHECK(vampir_compile("pub z; x * y = z;", "plonk", &circuit) == VAMPIR_INPUT_ERROR);
+    CHECK(vampir_verify(NULL, 0, NULL, 0, "{}") == VAMPIR_INPUT_ERROR);
+
+    /* Freeing empty buffers, or none, is harmless */
+    vampir_buffer_free(&circuit);
+    vampir_buffer_free(&circuit);
+    vampir_buffer_free(NULL);
+
+    return failures == 0 ? 0 : 1;
+}
@@ -0,0 +1,17 @@
+/* An example whose arithmetic on constants is folded away at compile time,
+   leaving the 4 constraints that tests/folding.rs expects. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/folding.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub x, y;
+def three = 3;
+def sq z = z * z;
+x = three * 4;
+y = sq (three + 1) - sq three;
+x + y = 12 + 7;
+(- three) * (- 4) = 12;
+def w = fresh (three * 2);
+w = 6;
@@ -0,0 +1,27 @@
+/* Checks that arithmetic on constants is folded away at compile time rather
+ * than occupying gates. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use std::collections::BTreeMap;
+use std::fs;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::CompileOptions;
+
+const FOLDING: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/folding.pir");
+
+#[test]
+fn constants_are_folded() {
+    let module = vamp_ir::parse(&fs::read_to_string(FOLDING).unwrap()).unwrap();
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let compiled = vamp_ir::compile(module, &field_ops, &CompileOptions::default()).unwrap();
+    // Only x = 12, y = 7, x + y = 19, and w = 6 remain, while the equality of
+    // the two products of negations always holds and occupies no gate
+    assert_eq!(compiled.stats.constraints, 4, "{}", compiled.module);
+    assert_eq!(
+        compiled.stats.shapes,
+        BTreeMap::from([("add", 1), ("equal constant", 3)]),
+        "{}", compiled.module,
+    );
+    assert_eq!(compiled.stats.source_constraints, Some(5));
+}
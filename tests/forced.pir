@@ -0,0 +1,19 @@
+/* An example in which most variables are forced by linear constraints, so only
+   w needs to be supplied when proving. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/forced.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+   Supply w = 3 or w = -3 when prompted.
+*/
+
+pub out;
+
+// A chain of forced values: x = 7, y = 14, z = 6, and out = 20
+x + 3 = 10;
+y = x * 2;
+3 * z = y + 4;
+out - z = 14;
+
+// The square root is not determined by a linear constraint
+w * w = 9;
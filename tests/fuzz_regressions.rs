@@ -0,0 +1,61 @@
+/* Checks what the fuzz targets under fuzz/ hold the library to: sources
+ * nested too deeply and corrupted circuit files are refused with errors,
+ * rather than with overflowing stacks, panics, or allocations as large as the
+ * lengths written in them. */
+#![cfg(feature = "halo2")]
+
+use halo2_proofs::pasta::EqAffine;
+use halo2_proofs::poly::commitment::Params;
+use std::collections::BTreeMap;
+use std::io::Write;
+use vamp_ir::artifact::{artifact_bytes, ArtifactKind};
+use vamp_ir::ast::Module;
+use vamp_ir::halo2::circuit::Halo2Circuit;
+
+/* A Halo2 circuit file whose parameters are sound, followed by the given
+ * bytes in place of the rest. */
+fn circuit_file(rest: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    artifact_bytes(ArtifactKind::Halo2Circuit, |bytes| {
+        Params::<EqAffine>::new(4).write(&mut *bytes)?;
+        rest(bytes);
+        Ok::<(), std::io::Error>(())
+    }).unwrap()
+}
+
+#[test]
+fn deep_nesting_is_refused() {
+    let source = format!("{}x{} = 1;", "(".repeat(100_000), ")".repeat(100_000));
+    assert!(matches!(vamp_ir::parse(&source), Err(vamp_ir::Error::Parse(_))));
+    // Brackets in comments do not count
+    let source = format!("/* {} */ x = 1;", "(".repeat(100_000));
+    assert!(vamp_ir::parse(&source).is_ok());
+}
+
+#[test]
+fn huge_parameters_are_refused() {
+    let bytes = artifact_bytes(ArtifactKind::Halo2Circuit, |bytes| bytes.write_all(&u32::MAX.to_le_bytes())).unwrap();
+    assert!(Halo2Circuit::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn huge_lengths_are_refused() {
+    let bytes = circuit_file(|bytes| {
+        // An empty map of variables, then public variables of length 2^64 - 1
+        bytes.push(0);
+        bytes.push(253);
+        bytes.extend(u64::MAX.to_le_bytes());
+    });
+    assert!(Halo2Circuit::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn values_outside_the_field_are_refused() {
+    let bytes = circuit_file(|bytes| {
+        let config = bincode::config::standard();
+        let values = BTreeMap::from([(0u32, Some([0xffu8; 32]))]);
+        bincode::encode_into_std_write(values, bytes, config).unwrap();
+        bincode::encode_into_std_write(Module::default(), bytes, config).unwrap();
+        bincode::encode_into_std_write(4u32, bytes, config).unwrap();
+    });
+    assert!(Halo2Circuit::from_bytes(&bytes).is_err());
+}
@@ -0,0 +1,48 @@
+/* Property tests over random programs from the testing module: the inputs
+ * generated alongside each program must satisfy it once compiled, whichever
+ * field and optimization level it is compiled with, or else compilation and
+ * evaluation disagree. Only built with the testing feature. */
+#![cfg(feature = "testing")]
+
+use ark_bls12_381::Fr as BlsScalar;
+use halo2_proofs::pasta::Fp;
+use proptest::prelude::*;
+use vamp_ir::halo2::field::{Halo2CostModel, PrimeFieldOps as Halo2FieldOps};
+use vamp_ir::plonk::field::{PlonkCostModel, PrimeFieldOps as PlonkFieldOps};
+use vamp_ir::testing::{module_strategy, GeneratedModule, ModuleConfig};
+use vamp_ir::transform::{CompileOptions, CostModel, FieldOps, MAX_OPT_LEVEL};
+
+/* Compile the given program at the given optimization level and check the
+ * inputs generated with it against the compiled module. */
+fn check(generated: &GeneratedModule, field_ops: &dyn FieldOps, cost_model: Box<dyn CostModel>, opt_level: u8) -> Result<(), String> {
+    let options = CompileOptions::default().opt_level(opt_level).cost_model(cost_model);
+    let compiled = vamp_ir::compile(generated.module.clone(), field_ops, &options)
+        .map_err(|err| format!("compiling at level {}: {}", opt_level, err))?;
+    vamp_ir::assign_inputs(&compiled.module, &generated.inputs, field_ops)
+        .map(|_| ())
+        .map_err(|err| format!("checking at level {}: {}", opt_level, err))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn halo2_modules_are_satisfied(generated in module_strategy(&ModuleConfig::default())) {
+        let field_ops = Halo2FieldOps::<Fp>::default();
+        for opt_level in 0..=MAX_OPT_LEVEL {
+            let checked = check(&generated, &field_ops, Box::new(Halo2CostModel), opt_level);
+            prop_assert!(checked.is_ok(), "{}", checked.unwrap_err());
+        }
+    }
+
+    #[test]
+    fn plonk_modules_are_satisfied(generated in module_strategy(
+        &ModuleConfig::default().field(&PlonkFieldOps::<BlsScalar>::default()).max_depth(3),
+    )) {
+        let field_ops = PlonkFieldOps::<BlsScalar>::default();
+        for opt_level in 0..=MAX_OPT_LEVEL {
+            let checked = check(&generated, &field_ops, Box::new(PlonkCostModel), opt_level);
+            prop_assert!(checked.is_ok(), "{}", checked.unwrap_err());
+        }
+    }
+}
@@ -0,0 +1,116 @@
+/* Checks that Groth16 circuits over either curve prove and verify both on the
+ * command line and through the library, and that proofs are only accepted for
+ * the public inputs that they were made for. Only built with the groth16
+ * feature, along with the command line. */
+#![cfg(all(feature = "cli", feature = "groth16"))]
+
+mod common;
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::PairingEngine;
+use ark_ff::SquareRootField;
+use common::{scratch_dir, vamp_ir};
+use num_bigint::BigInt;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use vamp_ir::groth16::{Groth16CircuitData, Groth16Proof};
+use vamp_ir::plonk::field::{PlonkCostModel, PrimeFieldOps};
+use vamp_ir::transform::CompileOptions;
+
+/* Compile and prove tests/r1cs.pir over the given curve in the given
+ * directory, giving the paths of the circuit and the proof. */
+fn prove(dir: &Path, curve: &str) -> (PathBuf, PathBuf) {
+    let source = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/r1cs.pir");
+    let inputs = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/r1cs.inputs");
+    let circuit = dir.join("r1cs.groth16");
+    let proof = dir.join("r1cs.proof");
+    assert_eq!(vamp_ir(&[
+        "groth16", "compile", "-s", source, "--curve", curve, "--seed", "1",
+        "-o", circuit.to_str().unwrap(),
+    ]), 0);
+    assert_eq!(vamp_ir(&[
+        "groth16", "prove", "-c", circuit.to_str().unwrap(), "-i", inputs,
+        "-o", proof.to_str().unwrap(),
+    ]), 0);
+    (circuit, proof)
+}
+
+/* Verify the given proof against a file of the given public inputs. */
+fn verify(dir: &Path, circuit: &Path, proof: &Path, public_inputs: &str) -> i32 {
+    let path = dir.join("public.json");
+    fs::write(&path, public_inputs).unwrap();
+    vamp_ir(&[
+        "groth16", "verify", "-c", circuit.to_str().unwrap(), "-p", proof.to_str().unwrap(),
+        "--public-inputs", path.to_str().unwrap(),
+    ])
+}
+
+#[test]
+fn proofs_verify_on_either_curve() {
+    for curve in ["bn254", "bls12-381"] {
+        let dir = scratch_dir(curve);
+        let (circuit, proof) = prove(&dir, curve);
+        assert_eq!(vamp_ir(&["groth16", "verify", "-c", circuit.to_str().unwrap(), "-p", proof.to_str().unwrap()]), 0);
+        assert_eq!(verify(&dir, &circuit, &proof, r#"{"z": "12", "d": "6"}"#), 0);
+    }
+}
+
+#[test]
+fn proofs_of_other_public_inputs_are_rejected() {
+    let dir = scratch_dir("other-public-inputs");
+    let (circuit, proof) = prove(&dir, "bn254");
+    assert_eq!(verify(&dir, &circuit, &proof, r#"{"z": "13", "d": "6"}"#), 1);
+}
+
+#[test]
+fn violating_inputs_are_not_proved() {
+    let dir = scratch_dir("violating-inputs");
+    let (circuit, _) = prove(&dir, "bn254");
+    let inputs = dir.join("bad.json");
+    fs::write(&inputs, r#"{"x": "3", "y": "4", "z": "13", "d": "6"}"#).unwrap();
+    let proof = dir.join("bad.proof");
+    assert_eq!(vamp_ir(&[
+        "groth16", "prove", "-c", circuit.to_str().unwrap(), "-i", inputs.to_str().unwrap(),
+        "-o", proof.to_str().unwrap(),
+    ]), 1);
+    assert!(!proof.exists());
+}
+
+/* Set up, prove, and verify the circuit of x * y = z over the curve of E
+ * through the library, passing the circuit and proof through their
+ * serializations. */
+fn library_round_trip<E>()
+where E: PairingEngine, E::Fr: SquareRootField {
+    let module = vamp_ir::parse("pub z; x * y = z;").unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(PlonkCostModel));
+    let module = vamp_ir::compile(module, &PrimeFieldOps::<E::Fr>::default(), &options).unwrap().module;
+    let data = Groth16CircuitData::<E>::setup(module, 0, vec![], ChaCha20Rng::seed_from_u64(1)).unwrap();
+    let mut bytes = vec![];
+    data.write(&mut bytes).unwrap();
+    let data = Groth16CircuitData::<E>::read(bytes.as_slice()).unwrap();
+
+    let field_ops = PrimeFieldOps::<E::Fr>::default();
+    let inputs = HashMap::from([("x", 3), ("y", 4), ("z", 12)])
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), BigInt::from(value)))
+        .collect();
+    let assigns = vamp_ir::assign_inputs(&data.module, &inputs, &field_ops).unwrap();
+    let proof = data.prove(assigns, ChaCha20Rng::seed_from_u64(2)).unwrap();
+    let mut bytes = vec![];
+    proof.write(&mut bytes).unwrap();
+    let mut proof = Groth16Proof::<E>::read(bytes.as_slice()).unwrap();
+    assert!(data.verify(&proof).is_ok());
+    // The same proof is invalid for any other value of z
+    proof.public_values[0] = E::Fr::from(13u64);
+    assert!(matches!(data.verify(&proof), Err(vamp_ir::Error::InvalidProof(_))));
+}
+
+#[test]
+fn library_proofs_verify_on_either_curve() {
+    library_round_trip::<Bn254>();
+    library_round_trip::<Bls12_381>();
+}
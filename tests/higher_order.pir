@@ -0,0 +1,34 @@
+/* An example of gadgets composed through a higher-order helper. Any solution
+   such that y = (x+1)^2 and z = x^2+1 is valid. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/higher_order.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub x, y, z;
+
+// Two simple gadgets
+
+def square a = a * a;
+
+def incr a = a + 1;
+
+// A higher-order helper that composes the given gadgets
+
+def compose f g a = f (g a);
+
+// Apply the given gadget to both components of a pair
+
+def map2 f (a, b) = (f a, f b);
+
+compose square incr x = y;
+
+compose incr square x = z;
+
+// Partially applied helpers may be passed around freely as long as they are
+// fully applied before reaching a constraint
+
+def both = map2 (compose square incr);
+
+both (x, x) = (y, y);
@@ -0,0 +1,13 @@
+/* A circuit whose inputs are named after the wires of the .r1cs file that it
+   is exported to, so that the program imported back from that file can be
+   compared with it. Run as follows:
+   vamp-ir export --format r1cs --field pallas tests/import.pir -o circuit.r1cs
+   vamp-ir import --format r1cs --field pallas circuit.r1cs -o imported.pir
+   vamp-ir diff --field halo2 tests/import.pir imported.pir
+*/
+
+pub w_1, w_2;
+
+w_3 * w_4 = w_1;
+
+w_2 = 3 * (w_3 + w_4) - 5;
@@ -0,0 +1,10 @@
+/* Output should be a single error naming a and b as missing inputs and d as
+   an unexpected one. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/incomplete.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -i tests/incomplete.inputs -o proof.plonk
+*/
+
+pub c;
+
+a * b = c;
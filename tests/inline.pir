@@ -0,0 +1,18 @@
+/* An example in which multiplying by one leaves an alias of x, named scaled,
+   that is used twice. Without -O1, the alias survives as a definition and
+   hence as an extra variable in both backends. With it, the alias is inlined
+   into its uses while the public variables x and y remain, as
+   tests/inline.rs checks. Passing --inline-limit 0 restricts inlining to
+   aliases and constants. Run as follows:
+   vamp-ir compile tests/inline.pir --emit-ir inline.ir
+   vamp-ir compile -O1 tests/inline.pir --emit-ir inline.ir --force
+   vamp-ir compile -O1 --inline-limit 0 tests/inline.pir --emit-ir inline.ir --force
+*/
+
+pub x, y;
+
+def twice a = a + a;
+
+def scaled = x * 1;
+
+twice scaled = y;
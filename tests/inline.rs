@@ -0,0 +1,95 @@
+/* Checks that trivial and small single-use definitions are inlined into their
+ * uses from -O1, within the inline limit, and that public variables are never
+ * inlined away. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use std::collections::HashSet;
+use std::fs;
+use vamp_ir::ast::{Definition, Expr, InfixOp, LetBinding, Module, Pat, TExpr, Variable};
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{inline_definitions, ordered_module_variables, CompileOptions};
+
+const INLINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/inline.pir");
+
+/* Compile tests/inline.pir at the given optimization level and inline
+ * limit. */
+fn compile(opt_level: u8, inline_limit: usize) -> Module {
+    let module = vamp_ir::parse(&fs::read_to_string(INLINE).unwrap()).unwrap();
+    let options = CompileOptions::default().opt_level(opt_level).inline_limit(inline_limit);
+    vamp_ir::compile(module, &PrimeFieldOps::<Fr>::default(), &options).unwrap().module
+}
+
+/* The names of the variables of the given module, in order. */
+fn names(module: &Module) -> Vec<String> {
+    ordered_module_variables(module).iter().map(|var| var.name.clone().unwrap_or_default()).collect()
+}
+
+#[test]
+fn aliases_are_inlined() {
+    // Without -O1, the alias of x named scaled keeps a variable of its own
+    let module = compile(0, 8);
+    assert_eq!(module.exprs.len(), 1, "{}", module);
+    assert_eq!(names(&module), ["x", "y", "scaled"]);
+    // With it, the alias is inlined, even when nothing else may be
+    for inline_limit in [8, 0] {
+        let module = compile(1, inline_limit);
+        assert_eq!(module.exprs.len(), 1, "{}", module);
+        assert_eq!(names(&module), ["x", "y"]);
+        assert_eq!(module.pubs.len(), 2);
+    }
+}
+
+/* The variable of the given name, numbered by its position in NAMES. */
+fn variable(name: &str) -> TExpr {
+    let id = NAMES.iter().position(|other| *other == name).unwrap() as u32;
+    Expr::Variable(Variable { name: Some(name.to_string()), id }).type_expr(None)
+}
+
+const NAMES: [&str; 4] = ["x", "y", "z", "t"];
+
+/* The given operation applied to the given operands. */
+fn infix(op: InfixOp, expr1: TExpr, expr2: TExpr) -> TExpr {
+    Expr::Infix(op, Box::new(expr1), Box::new(expr2)).type_expr(None)
+}
+
+/* The definition of the variable of the given name as the given value. */
+fn definition(name: &str, value: TExpr) -> Definition {
+    let Expr::Variable(var) = variable(name).v else { unreachable!() };
+    Definition(LetBinding(Pat::Variable(var).type_pat(None), Box::new(value)))
+}
+
+/* A module defining t = x * y + 1, with z public, and constraining t = z. */
+fn module() -> Module {
+    let product = infix(InfixOp::Multiply, variable("x"), variable("y"));
+    let value = infix(InfixOp::Add, product, Expr::Constant(1.into()).type_expr(None));
+    let Expr::Variable(z) = variable("z").v else { unreachable!() };
+    Module {
+        pubs: vec![z],
+        defs: vec![definition("t", value)],
+        exprs: vec![infix(InfixOp::Equal, variable("t"), variable("z"))],
+        ..Module::default()
+    }
+}
+
+#[test]
+fn inline_limit_is_respected() {
+    // The definition of t has five nodes, so it is inlined within a limit of
+    // five but not of four
+    let mut inlined = module();
+    assert_eq!(inline_definitions(&mut inlined, &HashSet::new(), 5), 1);
+    assert!(inlined.defs.is_empty());
+    assert_eq!(inlined.exprs[0].to_string(), infix(
+        InfixOp::Equal,
+        infix(InfixOp::Add, infix(InfixOp::Multiply, variable("x"), variable("y")), Expr::Constant(1.into()).type_expr(None)),
+        variable("z"),
+    ).to_string());
+    let mut kept = module();
+    assert_eq!(inline_definitions(&mut kept, &HashSet::new(), 4), 0);
+    assert_eq!(kept.defs.len(), 1);
+    // Public variables are never inlined, however small their definitions
+    let mut public = module();
+    public.defs.push(definition("z", variable("x")));
+    assert_eq!(inline_definitions(&mut public, &HashSet::new(), 0), 0);
+    assert_eq!(public.defs.len(), 2);
+}
@@ -0,0 +1,99 @@
+/* Checks the interface documents written by compile --emit-interface, which
+ * describe the inputs of a circuit without its constraints, against the
+ * interfaces that the library gives for the same programs, and the names
+ * that the library suggests for inputs that are not in an interface. */
+#![cfg(all(feature = "cli", feature = "halo2"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use ark_bn254::Fr;
+use num_bigint::BigInt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use vamp_ir::interface::{Visibility, INTERFACE_VERSION};
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::CompileOptions;
+
+const EMIT_IR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/emit_ir.pir");
+const TUPLE_INPUTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/tuple_inputs.pir");
+
+fn read_json(path: &PathBuf) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn interfaces_name_every_visibility() {
+    let dir = scratch_dir("visibility");
+    let (ir, interface) = (dir.join("emit_ir.ir"), dir.join("emit_ir.json"));
+    assert_eq!(vamp_ir(&[
+        "compile", EMIT_IR, "--no-cache", "--emit-ir", ir.to_str().unwrap(),
+        "--emit-interface", interface.to_str().unwrap(),
+    ]), 0);
+    let interface = read_json(&interface);
+    assert_eq!(interface["version"], INTERFACE_VERSION);
+    let inputs: Vec<_> = interface["inputs"].as_array().unwrap().iter()
+        .map(|input| (input["name"].as_str().unwrap(), input["visibility"].as_str().unwrap(), input["required"] == true))
+        .collect();
+    // The auxiliary variables of the compiler are left out
+    assert_eq!(inputs, [("x", "public", true), ("y", "public", true), ("r", "fresh", false)]);
+}
+
+#[test]
+fn interfaces_give_tuple_shapes() {
+    let dir = scratch_dir("tuples");
+    let (circuit, interface) = (dir.join("circuit.halo2"), dir.join("circuit.json"));
+    assert_eq!(vamp_ir(&[
+        "halo2", "compile", "--no-cache", "-s", TUPLE_INPUTS, "-o", circuit.to_str().unwrap(),
+        "--emit-interface", interface.to_str().unwrap(),
+    ]), 0);
+    let interface = read_json(&interface);
+    let q = &interface["inputs"][0];
+    assert_eq!(q["name"], "q");
+    assert_eq!(q["visibility"], "private");
+    assert_eq!(q["type"], "(int, int, int, int)");
+    assert_eq!(q["shape"], json!(["int", "int", "int", "int"]));
+    assert_eq!(q["variables"], json!(["q.0", "q.1.0", "q.1.1.0", "q.1.1.1"]));
+    let r = &interface["inputs"][1];
+    assert_eq!(r["type"], "((int, int), (int, int), (int, int), (int, int))");
+    assert_eq!(r["shape"][3], json!(["int", "int"]));
+    // Existing documents are only replaced when forced
+    assert_eq!(vamp_ir(&[
+        "halo2", "compile", "--no-cache", "-s", TUPLE_INPUTS, "-o", dir.join("other.halo2").to_str().unwrap(),
+        "--emit-interface", dir.join("circuit.json").to_str().unwrap(),
+    ]), 2);
+}
+
+#[test]
+fn library_interfaces_match_documents() {
+    let source = fs::read_to_string(TUPLE_INPUTS).unwrap();
+    let module = vamp_ir::parse(&source).unwrap();
+    let options = vamp_ir::transform::CompileOptions::default();
+    let field_ops = vamp_ir::halo2::synth::PrimeFieldOps::<halo2_proofs::pasta::Fp>::default();
+    let compiled = vamp_ir::compile(module, &field_ops, &options).unwrap().module;
+    let interface = compiled.interface();
+    assert!(interface.inputs.iter().all(|input| input.required && input.visibility == Visibility::Private));
+    assert_eq!(interface.to_json()["inputs"][0]["variables"].as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn library_suggests_names_of_mistyped_inputs() {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let module = vamp_ir::parse("pub total;\nfirst * second = total;\n").unwrap();
+    let module = vamp_ir::compile(module, &field_ops, &CompileOptions::default()).unwrap().module;
+    let inputs: HashMap<String, BigInt> = HashMap::from([
+        ("frst".to_string(), 3.into()),
+        ("second".to_string(), 4.into()),
+        ("total".to_string(), 12.into()),
+        ("unrelated".to_string(), 0.into()),
+    ]);
+    match vamp_ir::assign_inputs(&module, &inputs, &field_ops) {
+        Err(vamp_ir::Error::Inputs(message)) => assert_eq!(
+            message,
+            "missing inputs: first; unknown inputs: frst (did you mean first?), unrelated",
+        ),
+        other => panic!("mistyped inputs were assigned: {:?}", other),
+    }
+}
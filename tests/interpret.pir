@@ -0,0 +1,18 @@
+/* An example of a circuit evaluated as a program. Run as follows:
+   vamp-ir interpret -s tests/interpret.pir -D x=3 -D y=27 -D z=30
+   This must print x = 3, y = 27, and z = 30 as the public variables and
+   report that all constraints are satisfied. Giving --all must also print
+   the value 27 of w. Giving --field pallas or --field bn254 must print the same
+   values. Giving -D y=28 instead must report the violated constraint and
+   exit with status 1.
+*/
+
+pub x, y, z;
+
+def cube a = a * a * a;
+
+def w = cube x;
+
+w = y;
+
+z = x + y;
@@ -0,0 +1,14 @@
+/* An example whose inputs are supplied as JSON. Run as follows:
+   vamp-ir plonk setup -o params.pp
+   vamp-ir plonk compile -u params.pp -s tests/json_inputs.pir -o circuit.plonk
+   vamp-ir plonk prove -u params.pp -c circuit.plonk -o proof.plonk -i tests/json_inputs.json
+   vamp-ir plonk verify -u params.pp -c circuit.plonk -p proof.plonk
+   The inputs file mixes a number, a hexadecimal string, and a decimal string.
+   Proving must succeed. Passing --format inputs must instead abort, citing
+   $.x as a number where a string was expected. Replacing the value of y with
+   [5] must abort, citing $.y as a list where an integer was expected.
+*/
+
+pub z;
+
+x * y = z;
@@ -0,0 +1,109 @@
+/* Checks that the key cache lays out public parameters and keys per backend
+ * and circuit, that concurrent stores of the same item never leave it half
+ * written, and that PLONK proving and verifying find the parameters that
+ * compiling cached while taking keys from the circuit file alone. */
+#![cfg(feature = "compiler")]
+
+mod common;
+
+use common::scratch_dir;
+#[cfg(all(feature = "cli", feature = "plonk"))]
+use common::vamp_ir;
+use std::fs;
+use std::sync::Barrier;
+use vamp_ir::key_cache::{KeyCache, PARAMS, PK, VK};
+#[cfg(all(feature = "cli", feature = "plonk"))]
+use vamp_ir::artifact::{read_header, Digests};
+
+const HASH: &str = "0123456789abcdef";
+
+#[test]
+fn concurrent_stores_keep_entries_intact() {
+    let cache = KeyCache::open(Some(&scratch_dir("key-cache-stores"))).unwrap();
+    // Large enough that a write in place would be seen half done
+    let contents = [vec![1u8; 1 << 20], vec![2u8; 1 << 20]];
+    let barrier = Barrier::new(contents.len());
+    std::thread::scope(|scope| {
+        for mine in &contents {
+            let (cache, contents, barrier) = (&cache, &contents, &barrier);
+            scope.spawn(move || {
+                barrier.wait();
+                for _ in 0..16 {
+                    let path = cache.store("plonk", HASH, PK, mine).unwrap();
+                    assert!(path.ends_with(format!("plonk/{}/{}", HASH, PK)));
+                    // Whichever store was renamed into place last is read
+                    // back whole
+                    let stored = fs::read(&path).unwrap();
+                    assert!(contents.contains(&stored), "{} bytes were read", stored.len());
+                }
+            });
+        }
+    });
+    // Nothing but the item is left behind by either
+    let entries = cache.entries().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!((entries[0].backend.as_str(), entries[0].circuit_hash.as_str()), ("plonk", HASH));
+    assert_eq!(entries[0].items, [(PK.to_string(), 1 << 20)]);
+    let temps = fs::read_dir(cache.root().join("plonk").join(HASH)).unwrap().count();
+    assert_eq!(temps, 1);
+    // Items that are cached already are not made again
+    let made = cache.store_missing("plonk", HASH, PK, || panic!("pk was made again")).unwrap();
+    assert!(made.is_none());
+    assert!(cache.lookup("plonk", HASH, VK).is_none());
+}
+
+#[test]
+#[cfg(all(feature = "cli", feature = "plonk"))]
+fn plonk_keys_come_from_the_circuit_file() {
+    let dir = scratch_dir("key-cache-plonk");
+    let cache_dir = dir.join("cache");
+    let source = dir.join("circuit.pir");
+    let other_source = dir.join("other.pir");
+    let params = dir.join("params.pp");
+    let circuit = dir.join("circuit.plonk");
+    let other_circuit = dir.join("other.plonk");
+    let proof = dir.join("proof.plonk");
+    fs::write(&source, "pub z;\nx * y = z;\n").unwrap();
+    fs::write(&other_source, "pub z;\nx + y = z;\n").unwrap();
+    let [cache_dir, source, other_source, params, circuit, other_circuit, proof] =
+        [&cache_dir, &source, &other_source, &params, &circuit, &other_circuit, &proof].map(|path| path.to_str().unwrap());
+
+    assert_eq!(vamp_ir(&["plonk", "setup", "-o", params]), 0);
+    let compile = ["plonk", "compile", "--cache-dir", cache_dir, "-u", params, "-s", source, "-o", circuit];
+    assert_eq!(vamp_ir(&compile), 0);
+    let compile_other = ["plonk", "compile", "--cache-dir", cache_dir, "-u", params, "-s", other_source, "-o", other_circuit];
+    assert_eq!(vamp_ir(&compile_other), 0);
+    let cache = KeyCache::open(Some(cache_dir.as_ref())).unwrap();
+    let entries = cache.entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    for entry in &entries {
+        let names: Vec<_> = entry.items.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, [PARAMS, PK, VK]);
+    }
+    // Entries are named by the BLAKE2b digest of the circuit file
+    let circuit_contents = fs::read(circuit).unwrap();
+    let (_, body) = read_header(circuit_contents.as_slice()).unwrap();
+    let hash = Digests::of(body.get_ref().1).secure;
+    assert!(entries.iter().any(|entry| entry.circuit_hash == hash), "no entry is named {}", hash);
+    let other_hash = entries.iter().map(|entry| &entry.circuit_hash).find(|name| **name != hash).unwrap();
+
+    // Plant the keys of the other circuit in the entry of this one, which
+    // proving and verifying pass over for those of the circuit file
+    for item in [PK, VK] {
+        let planted = fs::read(cache.lookup("plonk", other_hash, item).unwrap()).unwrap();
+        cache.store("plonk", &hash, item, &planted).unwrap();
+    }
+    let inputs = ["-D", "x=3", "-D", "y=4", "-D", "z=12"];
+    let prove = [&["plonk", "prove", "--cache-dir", cache_dir, "-c", circuit, "-o", proof][..], &inputs].concat();
+    assert_eq!(vamp_ir(&prove), 0);
+    let verify = ["plonk", "verify", "--cache-dir", cache_dir, "-c", circuit, "-p", proof];
+    assert_eq!(vamp_ir(&verify), 0);
+    // A proof made for the other circuit is still refused
+    let other_proof = dir.join("other-proof.plonk");
+    let other_proof = other_proof.to_str().unwrap();
+    let prove_other = [&["plonk", "prove", "--cache-dir", cache_dir, "-c", other_circuit, "-o", other_proof][..], &["-D", "x=3", "-D", "y=4", "-D", "z=7"]].concat();
+    assert_eq!(vamp_ir(&prove_other), 0);
+    assert_ne!(vamp_ir(&["plonk", "verify", "--cache-dir", cache_dir, "-c", circuit, "-p", other_proof]), 0);
+    // Without the cache, nothing says where the parameters are
+    assert_eq!(vamp_ir(&[&verify[..], &["--no-cache"]].concat()), 2);
+}
@@ -0,0 +1,23 @@
+/* An example of local bindings introduced with let expressions. Run as
+   follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/let.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub a, b, c;
+
+// The bound variable is captured in two different constraints
+
+let t = a * b in { t + t = c; t - a = b*b };
+
+// Nested lets, where the inner binding shadows the outer one
+
+def square x = let y = x * x in y;
+
+let y = a + 1 in let y = y * y in y = square (a + 1);
+
+// The argument to let may also shadow module level names
+
+let input = b in let a = input + a in a - b = square 1 * a - b;
@@ -0,0 +1,29 @@
+/* An example of digit separators and power literals. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/literals.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+   The powers below fold into constants during compilation, so the printed
+   constraints contain 340282366920938463463374607431768211455 rather than any
+   exponentiation.
+*/
+
+pub x, y;
+
+// Separators may appear between the digits of any literal
+
+x = 1_000_000 + 0xff_ff + 0b1010_1010 + 0o7_7;
+
+// Powers of constants fold into a single constant however wide they are
+
+y = 2^128 - 1;
+
+def mask = 2^2_56 - 1;
+
+x * mask = y * mask;
+
+// Separators are also allowed in patterns
+
+def small 1_0 = 10;
+
+small 10 = 10;
@@ -0,0 +1,29 @@
+/* Constrain two lists of length 16 to be the element-wise products of a third
+   list with two other lists. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/map_zip.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+def xs = x0:x1:x2:x3:x4:x5:x6:x7:x8:x9:x10:x11:x12:x13:x14:x15:[];
+
+def ys = y0:y1:y2:y3:y4:y5:y6:y7:y8:y9:y10:y11:y12:y13:y14:y15:[];
+
+def zs = z0:z1:z2:z3:z4:z5:z6:z7:z8:z9:z10:z11:z12:z13:z14:z15:[];
+
+def ps = p0:p1:p2:p3:p4:p5:p6:p7:p8:p9:p10:p11:p12:p13:p14:p15:[];
+
+def qs = q0:q1:q2:q3:q4:q5:q6:q7:q8:q9:q10:q11:q12:q13:q14:q15:[];
+
+def mul a b = a * b;
+
+// zip_with applies the given function to corresponding elements
+
+zip_with mul xs zs = ps;
+
+zip_with mul ys zs = qs;
+
+// map applies the given function to each element
+
+map (fun x { x * x }) zs = zip_with mul zs zs;
@@ -0,0 +1,41 @@
+/* A Merkle path of depth 8 verified using a recursive definition. The path is
+   given as a list of direction bits and sibling nodes from the leaf upwards.
+   Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/merkle.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub root;
+
+// A toy compression function standing in for a real hash
+
+def hash a b = a^5 + 3*b + 7;
+
+// Select a if bit is 1 and b if bit is 0
+
+def select bit a b = bit*(a - b) + b;
+
+// Compute the root from the leaf by recursing on the remaining depth
+
+def merkle_root 0 node path = node;
+
+def merkle_root n node ((bit, sibling):path) = {
+    bit*(bit-1) = 0;
+    def left = select bit sibling node;
+    def right = select bit node sibling;
+    merkle_root (n-1) (hash left right) path
+};
+
+// Recursion on a constant exponent
+
+def pow x 0 = 1;
+
+def pow x n = x * pow x (n-1);
+
+merkle_root 8 leaf
+    ((b0, s0):(b1, s1):(b2, s2):(b3, s3):(b4, s4):(b5, s5):(b6, s6):(b7, s7):[])
+    = root;
+
+pow leaf 3 = leaf^3;
@@ -0,0 +1,44 @@
+/* Checks that the recursive Merkle path gadget of merkle.pir unrolls into a
+ * circuit that proves and verifies the root of a path computed here. */
+#![cfg(feature = "halo2")]
+
+use halo2_proofs::pasta::Fp;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use vamp_ir::halo2::circuit::Halo2Circuit;
+use vamp_ir::halo2::synth::{Halo2CostModel, PrimeFieldOps};
+use vamp_ir::halo2::verify::field_value;
+use vamp_ir::transform::{input_name, required_inputs, CompileOptions, FieldOps};
+
+const MERKLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/merkle.pir");
+
+#[test]
+fn merkle_paths_prove() {
+    let field_ops = PrimeFieldOps::<Fp>::default();
+    let modulus = field_ops.canonical(BigInt::from(-1)) + 1;
+    // The toy compression function and selection of the source
+    let hash = |a: &BigInt, b: &BigInt| (a.modpow(&BigInt::from(5), &modulus) + b * 3 + 7) % &modulus;
+    let select = |bit: u8, a: &BigInt, b: &BigInt| if bit == 1 { a.clone() } else { b.clone() };
+    let leaf = BigInt::from(42);
+    let bits = [1u8, 0, 0, 1, 1, 0, 1, 0];
+    let mut inputs = HashMap::from([("leaf".to_string(), leaf.clone())]);
+    let mut node = leaf;
+    for (i, bit) in bits.into_iter().enumerate() {
+        let sibling = BigInt::from(1000 + i);
+        node = hash(&select(bit, &sibling, &node), &select(bit, &node, &sibling));
+        inputs.insert(format!("b{}", i), bit.into());
+        inputs.insert(format!("s{}", i), sibling);
+    }
+    inputs.insert("root".to_string(), node.clone());
+
+    let module = vamp_ir::parse(&std::fs::read_to_string(MERKLE).unwrap()).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+    let compiled = vamp_ir::compile(module, &field_ops, &options).unwrap();
+    let circuit = Halo2Circuit::new(compiled.module).unwrap();
+    // The root may have been solved for rather than left as an input
+    let required: Vec<_> = required_inputs(circuit.module()).iter().map(input_name).collect();
+    inputs.retain(|name, _| required.contains(name));
+    let proof = circuit.prove(&inputs).unwrap();
+    circuit.verify(&proof).unwrap();
+    assert_eq!(proof.public_values.iter().map(field_value).collect::<Vec<_>>(), vec![node]);
+}
@@ -0,0 +1,12 @@
+/* A module that clashes with tests/multi/gadgets.pir. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp tests/multi/gadgets.pir tests/multi/clash.pir -o circuit.plonk
+   Compilation must fail with an error stating that square is defined in an
+   earlier module and again at 10:1 of this file.
+*/
+
+pub a;
+
+def square x = x * x * x;
+
+square a = 8;
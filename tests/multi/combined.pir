@@ -0,0 +1,20 @@
+/* The concatenation of tests/multi/gadgets.pir and tests/multi/main.pir. Run
+   as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/multi/combined.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub r;
+
+module gadgets {
+    def bool x = { x * (x - 1) = 0 };
+    def select c a b = c * a + (1 - c) * b;
+};
+
+def square x = x * x;
+
+gadgets::bool c;
+
+gadgets::select c (square a) b = r;
@@ -0,0 +1,15 @@
+/* A library of gadgets used by tests/multi/main.pir. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp tests/multi/gadgets.pir tests/multi/main.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+   The resulting circuit must be the same as that compiled from
+   tests/multi/combined.pir.
+*/
+
+module gadgets {
+    def bool x = { x * (x - 1) = 0 };
+    def select c a b = c * a + (1 - c) * b;
+};
+
+def square x = x * x;
@@ -0,0 +1,9 @@
+/* A client of the gadgets in tests/multi/gadgets.pir. See that file for how
+   to compile them together.
+*/
+
+pub r;
+
+gadgets::bool c;
+
+gadgets::select c (square a) b = r;
@@ -0,0 +1,19 @@
+/* An example of diagnostics that refer back to source variable names. Run as
+   follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/names.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+   Proving with the inputs x = 1, y = 1, z = 5 must fail with a message
+   stating that the constraint offset.aux2[..] = z.aux3[..] is not satisfied,
+   whereas the inputs x = 1, y = 1, z = 4 must succeed, as tests/names.rs
+   checks.
+*/
+
+pub z;
+
+def product = x * y;
+
+def offset = product + 1;
+
+offset * offset = z * x;
@@ -0,0 +1,39 @@
+/* Checks that the variables of compiled modules keep the names of the source
+ * variables they stand for, and that the variables made by the compiler are
+ * named after those, so that diagnostics refer back to the source. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::fs;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{ordered_module_variables, CompileOptions};
+
+const NAMES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/names.pir");
+
+#[test]
+fn variables_keep_source_names() {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let module = vamp_ir::parse(&fs::read_to_string(NAMES).unwrap()).unwrap();
+    let module = vamp_ir::compile(module, &field_ops, &CompileOptions::default()).unwrap().module;
+    // Offset is used twice and so keeps a variable of its own, whereas the
+    // products are named after the first variable they multiply
+    let names: Vec<_> = ordered_module_variables(&module).into_iter().map(|var| var.name).collect();
+    assert_eq!(names, ["z", "x.aux1", "x", "y", "offset", "offset.aux2", "z.aux3"].map(|name| Some(name.to_string())));
+    // Names survive encoding
+    let decoded: vamp_ir::ast::Module = bincode::decode_from_slice(
+        &bincode::encode_to_vec(&module, bincode::config::standard()).unwrap(),
+        bincode::config::standard(),
+    ).unwrap().0;
+    assert_eq!(decoded.to_string(), module.to_string());
+    // Violated constraints are reported by the names of their variables
+    let inputs = |z: i64| HashMap::from([
+        ("x".to_string(), BigInt::from(1)),
+        ("y".to_string(), BigInt::from(1)),
+        ("z".to_string(), BigInt::from(z)),
+    ]);
+    vamp_ir::assign_inputs(&module, &inputs(4), &field_ops).unwrap();
+    let message = vamp_ir::assign_inputs(&module, &inputs(5), &field_ops).unwrap_err().to_string();
+    assert!(message.contains("offset.aux2[") && message.contains("z.aux3["), "{}", message);
+}
@@ -0,0 +1,32 @@
+/* An example of namespaced definitions referred to by qualified names. Run as
+   follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/namespaces.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub h1, h2, h3;
+
+// Two libraries that both define a function called hash
+
+module square {
+    def round x = x * x;
+    def hash x y = round (x + y);
+};
+
+module cube {
+    def hash x y = x * x * x + y;
+    module inner {
+        // Unqualified names refer to the innermost enclosing definition
+        def twice x y = hash (hash x y) y;
+    };
+};
+
+// Clients disambiguate using qualified names
+
+square::hash a b = h1;
+
+cube::hash a b = h2;
+
+cube::inner::twice a b = h3;
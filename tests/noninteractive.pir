@@ -0,0 +1,12 @@
+/* An example of proving without anyone to answer prompts. Run as follows:
+   vamp-ir halo2 compile -s tests/noninteractive.pir -o circuit.halo2
+   vamp-ir halo2 prove -c circuit.halo2 -o proof.halo2 --non-interactive -D a=2
+   Proving must abort at once with an error naming b and c as missing inputs
+   and circuit.inputs as the file looked for, rather than waiting for input.
+   Setting CI=true or VAMPIR_NONINTERACTIVE=1 instead of passing the flag must
+   do the same, whereas CI=false must prompt for b and c.
+*/
+
+pub c;
+
+a * b = c;
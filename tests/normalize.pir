@@ -0,0 +1,33 @@
+/* An example stuffed with algebraic identities that compile to no gates of
+   their own, as tests/normalize.rs checks. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/normalize.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub x, y, z;
+
+// Double negations cancel out
+
+(- - x) = y;
+
+// Additive and multiplicative identities vanish
+
+(x + 0) * 1 = 1 * (0 + y);
+
+// Subtraction from zero is negation, and adding a negation is subtraction
+
+z + (0 - x) = y - x;
+
+// Negated differences swap their operands
+
+(- (x - 3)) = 3 - y;
+
+// Products of negations are products of the originals
+
+(- x) * (- y) = x * y;
+
+// A difference of a variable with itself is zero
+
+x - x = z - z;
@@ -0,0 +1,27 @@
+/* Checks that algebraic identities are normalized away before constraints are
+ * broken down into gates, so that they compile to no gates of their own. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use std::collections::BTreeMap;
+use std::fs;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::CompileOptions;
+
+const NORMALIZE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/normalize.pir");
+
+#[test]
+fn identities_need_no_gates() {
+    let module = vamp_ir::parse(&fs::read_to_string(NORMALIZE).unwrap()).unwrap();
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let compiled = vamp_ir::compile(module, &field_ops, &CompileOptions::default()).unwrap();
+    // Both sides of the first two constraints reduce to x = y, the next three
+    // each take a gate per side and one equating them, and the last vanishes
+    assert_eq!(compiled.stats.constraints, 11, "{}", compiled.module);
+    // Negations are all absorbed into subtractions and products
+    assert_eq!(
+        compiled.stats.shapes,
+        BTreeMap::from([("equal variable", 5), ("multiply", 2), ("subtract", 4)]),
+        "{}", compiled.module,
+    );
+}
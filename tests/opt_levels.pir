@@ -0,0 +1,15 @@
+/* An example exercising every optimization level, over which
+   tests/opt_levels.rs checks that rerunning the passes changes nothing. With
+   --stats, compile lists how long each pass took. Run as follows:
+   vamp-ir compile -O0 tests/opt_levels.pir --emit-ir opt_levels.ir --stats
+   vamp-ir compile -O1 tests/opt_levels.pir --emit-ir opt_levels.ir --stats --force
+   vamp-ir compile -O2 tests/opt_levels.pir --emit-ir opt_levels.ir --stats --force
+*/
+
+pub x, y;
+
+def double a = a * 2;
+
+x * x = double y;
+
+double y = x * x;
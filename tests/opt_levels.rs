@@ -0,0 +1,38 @@
+/* Checks that the passes chosen at every optimization level are idempotent,
+ * that is that running them again over the circuits they produced changes
+ * nothing. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use std::fs;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{CompileOptions, GateCountCostModel, PassManager, DEFAULT_INLINE_LIMIT, MAX_OPT_LEVEL};
+
+/* Example programs of tests/ that compile on their own, between them using
+ * every construct that the passes rewrite. */
+const EXAMPLES: [&str; 14] = [
+    "cse", "duplicates", "folding", "higher_order", "if", "inline", "let", "map_zip", "normalize",
+    "opt_levels", "pyt", "r1cs", "range", "tuples",
+];
+
+#[test]
+fn passes_are_idempotent() {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    for example in EXAMPLES {
+        let path = format!("{}/tests/{}.pir", env!("CARGO_MANIFEST_DIR"), example);
+        let source = fs::read_to_string(&path).unwrap();
+        for opt_level in 0..=MAX_OPT_LEVEL {
+            let options = CompileOptions::default().opt_level(opt_level);
+            let compiled = vamp_ir::compile(vamp_ir::parse(&source).unwrap(), &field_ops, &options).unwrap();
+            let mut module = compiled.module.clone();
+            let mut prover_defs = compiled.prover_defs.clone();
+            let mut passes = PassManager::new(opt_level, DEFAULT_INLINE_LIMIT, &GateCountCostModel).unwrap();
+            passes.run_three_address_passes(&mut module, &mut prover_defs, &field_ops).unwrap();
+            assert_eq!(
+                module.to_string(), compiled.module.to_string(),
+                "rerunning the passes at -O{} changed {}", opt_level, example,
+            );
+            assert_eq!(prover_defs, compiled.prover_defs, "at -O{} in {}", opt_level, example);
+        }
+    }
+}
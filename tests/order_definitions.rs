@@ -0,0 +1,68 @@
+/* Checks that definitions are put in an order in which they can be evaluated
+ * where one exists, and that definitions depending on each other cyclically
+ * are reported as compile errors naming the cycle. */
+#![cfg(feature = "compiler")]
+
+use vamp_ir::ast::{Definition, Expr, InfixOp, LetBinding, Module, Pat, TExpr, Variable};
+use vamp_ir::transform::{order_definitions, CompileError};
+
+/* The variable of the given name, numbered by its position in NAMES. */
+fn variable(name: &str) -> Variable {
+    let id = NAMES.iter().position(|other| *other == name).unwrap() as u32;
+    Variable { name: Some(name.to_string()), id }
+}
+
+const NAMES: [&str; 4] = ["a", "b", "c", "d"];
+
+/* A module with a definition of each of the given variables as the sum of
+ * the variables that it is paired with, or of one if there are none. */
+fn module(defs: &[(&str, &[&str])]) -> Module {
+    let defs = defs.iter().map(|(name, deps)| {
+        let mut value = Expr::Constant(1.into()).type_expr(None);
+        for dep in *deps {
+            let var = Expr::Variable(variable(dep)).type_expr(None);
+            value = Expr::Infix(InfixOp::Add, Box::new(value), Box::new(var)).type_expr(None);
+        }
+        Definition(LetBinding(Pat::Variable(variable(name)).type_pat(None), Box::new(value)))
+    }).collect();
+    Module { defs, ..Module::default() }
+}
+
+/* The names of the variables defined by the given module, in order. */
+fn defined(module: &Module) -> Vec<String> {
+    module.defs.iter().map(|def| match &def.0.0.v {
+        Pat::Variable(var) => var.name.clone().unwrap(),
+        pat => panic!("{:?} is not a variable", pat),
+    }).collect()
+}
+
+/* The cycle that ordering the given definitions is refused for. */
+fn cycle(defs: &[(&str, &[&str])]) -> Vec<String> {
+    match order_definitions(&mut module(defs)) {
+        Ok(()) => panic!("{:?} were ordered", defs),
+        Err(CompileError::CyclicDefinitions(cycle)) => cycle,
+        Err(err) => panic!("{} is not a cycle", err),
+    }
+}
+
+#[test]
+fn diamonds_are_ordered() {
+    // Both b and c depend on a, and d depends on both, all given backwards
+    let mut diamond = module(&[("d", &["b", "c"]), ("c", &["a"]), ("b", &["a"]), ("a", &[])]);
+    order_definitions(&mut diamond).unwrap();
+    assert_eq!(defined(&diamond), ["a", "c", "b", "d"]);
+    // Definitions already in order are left as they are
+    order_definitions(&mut diamond).unwrap();
+    assert_eq!(defined(&diamond), ["a", "c", "b", "d"]);
+}
+
+#[test]
+fn cycles_are_named() {
+    // Each definition of the cycle depends on the next
+    assert_eq!(cycle(&[("a", &["b"]), ("b", &["a"])]), ["a[0]", "b[1]", "a[0]"]);
+    assert_eq!(cycle(&[("a", &["a"])]), ["a[0]", "a[0]"]);
+    // Definitions outside the cycle are not named
+    let err = CompileError::CyclicDefinitions(cycle(&[("d", &[]), ("a", &["d", "c"]), ("c", &["a"])]));
+    assert_eq!(err.to_string(), "definitions depend on each other cyclically: a[0] -> c[2] -> a[0]");
+    assert_eq!(err.to_diagnostic().code, "cyclic-definitions");
+}
@@ -0,0 +1,13 @@
+/* An example of literals that exceed the field modulus. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/overflow.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+   Compilation must warn that the literal on the left, which equals 2^256, reduces
+   to a different value, and must abort if --deny-warnings is also given. The
+   negative literal below must not be warned about.
+*/
+
+pub x, y;
+
+x * 115792089237316195423570985008687907853269984665640564039457584007913129639936 = (-5) * y;
@@ -0,0 +1,13 @@
+/* An example of inputs given on the command line. Run as follows:
+   vamp-ir test tests/overrides.pir -i tests/overrides.inputs -D b=7 -v
+   vamp-ir halo2 compile -s tests/overrides.pir -o circuit.halo2
+   vamp-ir halo2 prove -c circuit.halo2 -i tests/overrides.inputs -D b=7 -D c=21 -v -o proof.halo2
+   The effective inputs must show a = 3 from the file and b = 7 from the
+   command line, which overrides the 5 in the file. Giving -D d=1 must abort
+   with an error naming d as an unexpected input. Without -i, proving must
+   only prompt for the inputs not given with -D.
+*/
+
+pub c;
+
+a * b = c;
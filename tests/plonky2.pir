@@ -0,0 +1,23 @@
+/* A program whose constants lie near or beyond the order of the Goldilocks
+   field, 2^64 - 2^32 + 1, so that they only make sense once reduced in it.
+   Requires a vamp-ir built with --features plonky2. Run as follows:
+   vamp-ir plonky2 compile -s tests/plonky2.pir -o circuit.plonky2
+   vamp-ir plonky2 prove -c circuit.plonky2 -i tests/plonky2.inputs -o proof.plonky2
+   vamp-ir plonky2 verify -c circuit.plonky2 -p proof.plonky2
+*/
+
+pub y;
+pub w;
+
+// The order minus one is -1
+x * 18446744069414584320 = y;
+x * -1 = y;
+
+// 2^64 wraps around to 2^32 - 1
+(x + 18446744073709551616) * z = w;
+
+// Dividing by -1 negates
+z / 18446744069414584320 = -z;
+
+// The order itself is zero
+x * 18446744069414584321 + z = z;
@@ -0,0 +1,102 @@
+/* Checks that a program whose constants lie near the order of the Goldilocks
+ * field proves and verifies with plonky2 both on the command line and through
+ * the library, and that constants are reduced in that field rather than in
+ * the larger fields of the other backends. Only built with the plonky2
+ * feature, along with the command line. */
+#![cfg(all(feature = "cli", feature = "plonky2"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use vamp_ir::plonky2::circuit::{Plonky2Circuit, Plonky2Proof};
+use vamp_ir::plonky2::synth::{make_constant, GoldilocksFieldOps, Plonky2CostModel};
+use vamp_ir::transform::{CompileOptions, FieldOps};
+
+const SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/plonky2.pir");
+const INPUTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/plonky2.inputs");
+
+// The order of the Goldilocks field, 2^64 - 2^32 + 1
+const ORDER: u64 = 18446744069414584321;
+
+/* Compile and prove tests/plonky2.pir in the given directory, giving the paths
+ * of the circuit and the proof. */
+fn prove(dir: &Path) -> (PathBuf, PathBuf) {
+    let circuit = dir.join("plonky2.plonky2");
+    let proof = dir.join("plonky2.proof");
+    assert_eq!(vamp_ir(&["plonky2", "compile", "-s", SOURCE, "-o", circuit.to_str().unwrap()]), 0);
+    assert_eq!(vamp_ir(&[
+        "plonky2", "prove", "-c", circuit.to_str().unwrap(), "-i", INPUTS, "-o", proof.to_str().unwrap(),
+    ]), 0);
+    (circuit, proof)
+}
+
+#[test]
+fn constants_are_reduced_in_goldilocks() {
+    let field_ops = GoldilocksFieldOps;
+    let order = BigInt::from(ORDER);
+    assert_eq!(field_ops.canonical(order.clone()), BigInt::from(0));
+    assert_eq!(field_ops.canonical(BigInt::from(-1)), &order - 1);
+    assert_eq!(field_ops.canonical(BigInt::from(1u128 << 64)), BigInt::from(u32::MAX));
+    assert_eq!(field_ops.canonical(&order * &order + 7), BigInt::from(7));
+    assert_eq!(field_ops.negate(&order - 1), BigInt::from(1));
+    assert_eq!(field_ops.infix(vamp_ir::ast::InfixOp::Divide, BigInt::from(1), &order - 1).unwrap(), &order - 1);
+    assert_eq!(make_constant(&BigInt::from(-5)), make_constant(&(&order - 5)));
+}
+
+#[test]
+fn proofs_verify() {
+    let dir = scratch_dir("verify");
+    let (circuit, proof) = prove(&dir);
+    assert_eq!(vamp_ir(&["plonky2", "verify", "-c", circuit.to_str().unwrap(), "-p", proof.to_str().unwrap()]), 0);
+    let public = dir.join("public.json");
+    fs::write(&public, r#"{"y": "-5", "w": "12884901900"}"#).unwrap();
+    assert_eq!(vamp_ir(&[
+        "plonky2", "verify", "-c", circuit.to_str().unwrap(), "-p", proof.to_str().unwrap(),
+        "--public-inputs", public.to_str().unwrap(),
+    ]), 0);
+    fs::write(&public, r#"{"y": "5", "w": "12884901900"}"#).unwrap();
+    assert_eq!(vamp_ir(&[
+        "plonky2", "verify", "-c", circuit.to_str().unwrap(), "-p", proof.to_str().unwrap(),
+        "--public-inputs", public.to_str().unwrap(),
+    ]), 1);
+}
+
+#[test]
+fn violating_inputs_are_not_proved() {
+    let dir = scratch_dir("violating");
+    let (circuit, _) = prove(&dir);
+    let proof = dir.join("bad.proof");
+    // 2^64 is 2^32 - 1 in Goldilocks, not zero as a 64-bit wraparound gives
+    assert_eq!(vamp_ir(&[
+        "plonky2", "prove", "-c", circuit.to_str().unwrap(), "-i", INPUTS, "-D", "w=15",
+        "-o", proof.to_str().unwrap(),
+    ]), 1);
+    assert!(!proof.exists());
+}
+
+#[test]
+fn library_proofs_verify() {
+    let source = fs::read_to_string(SOURCE).unwrap();
+    let module = vamp_ir::parse(&source).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(Plonky2CostModel));
+    let module = vamp_ir::compile(module, &GoldilocksFieldOps, &options).unwrap().module;
+    let circuit = Plonky2Circuit::new(module).unwrap();
+    let circuit = Plonky2Circuit::from_bytes(&circuit.to_bytes().unwrap()).unwrap();
+
+    let inputs = HashMap::from([
+        ("x".to_string(), BigInt::from(5)),
+        ("z".to_string(), BigInt::from(3)),
+        ("y".to_string(), BigInt::from(ORDER - 5)),
+        ("w".to_string(), BigInt::from(12884901900u64)),
+    ]);
+    let proof = circuit.prove(&inputs).unwrap();
+    let mut proof = Plonky2Proof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+    assert!(circuit.verify(&proof).is_ok());
+    // The same proof is invalid for any other value of y
+    proof.public_values[0] = make_constant(&BigInt::from(5));
+    assert!(matches!(circuit.verify(&proof), Err(vamp_ir::Error::InvalidProof(_))));
+}
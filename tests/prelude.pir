@@ -0,0 +1,25 @@
+/* An example that relies on the standard prelude. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/prelude.pir -o circuit.plonk --prelude std
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+   Compiling without --prelude std must fail since bool and to_bits are then
+   undefined, and compiling with --prelude tests/prelude.pir must fail since
+   the prelude and the program would both define is_small.
+*/
+
+pub x, y;
+
+// x must fit into 8 bits, and y must be a bit
+
+def is_small z = len (to_bits 8 z) = 8;
+
+is_small x;
+
+bool y;
+
+// Pick x when y is 1 and its square otherwise
+
+def z = select y x (x * x);
+
+is_zero (z - x) = y;
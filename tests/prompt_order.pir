@@ -0,0 +1,19 @@
+/* An example for checking that inputs are solicited in a stable order: the
+   public variables in the order they are declared, followed by the private
+   inputs in the order they first occur, as tests/prompt_order.rs checks. Run
+   as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/prompt_order.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   The prompts should read as follows, every time:
+   ** c (public):
+   ** a (public):
+   ** b (private):
+   ** d (private):
+*/
+
+pub c, a;
+
+b * d = c;
+
+a * b = 1;
@@ -0,0 +1,31 @@
+/* Checks that inputs are solicited in a stable order: the public variables in
+ * the order they are declared, followed by the private inputs in the order
+ * they first occur. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use std::fs;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{input_name, required_inputs, CompileOptions};
+
+const PROMPT_ORDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/prompt_order.pir");
+
+/* The names of the inputs of the given source, in the order solicited. */
+fn prompts(source: &str) -> Vec<String> {
+    let module = vamp_ir::parse(source).unwrap();
+    let options = CompileOptions::default();
+    let module = vamp_ir::compile(module, &PrimeFieldOps::<Fr>::default(), &options).unwrap().module;
+    required_inputs(&module).iter().map(input_name).collect()
+}
+
+#[test]
+fn inputs_follow_declaration_order() {
+    let source = fs::read_to_string(PROMPT_ORDER).unwrap();
+    assert_eq!(prompts(&source), ["c", "a", "b", "d"]);
+    // The same for every compilation
+    for _ in 0..10 {
+        assert_eq!(prompts(&source), ["c", "a", "b", "d"]);
+    }
+    // Declaring the public variables the other way round swaps them alone
+    assert_eq!(prompts(&source.replace("pub c, a;", "pub a, c;")), ["a", "c", "b", "d"]);
+}
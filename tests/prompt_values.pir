@@ -0,0 +1,15 @@
+/* An example for checking that prompts accept the same values as input files
+   and -D, and recover from mistakes. Run as follows:
+   vamp-ir halo2 compile -s tests/prompt_values.pir -o circuit.halo2
+   vamp-ir halo2 prove -c circuit.halo2 -o proof.halo2
+   Answering z with 13, x with 0x10, and y with -3 must succeed. Answering
+   with 12z must report that an integer was expected and ask for the same
+   variable again. Running instead as
+   printf '13\n0x10\n' | vamp-ir halo2 prove -c circuit.halo2 -o proof.halo2 --force
+   must abort with an error saying that standard input closed before a value
+   was given for y, rather than prompting forever.
+*/
+
+pub z;
+
+x + y = z;
@@ -0,0 +1,97 @@
+/* Checks that the public inputs of PLONK proofs are only annotated with the
+ * public variables of a circuit when they are laid out at the positions that
+ * the circuit intends for those variables. */
+#![cfg(feature = "plonk")]
+
+use ark_bls12_381::Fr as BlsScalar;
+use ark_ed_on_bls12_381::EdwardsParameters as JubJubParameters;
+use plonk_core::proof_system::pi::PublicInputs;
+use std::collections::HashMap;
+use std::fs;
+use vamp_ir::ast::{Variable, VariableId};
+use vamp_ir::plonk::synth::{PlonkCostModel, PlonkModule, PrimeFieldOps};
+use vamp_ir::transform::{input_name, CompileOptions};
+use vamp_ir::Error;
+
+const R1CS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/r1cs.pir");
+
+/* The positions at which the circuit below intends its public variables, z
+ * and d, to be. */
+const POSITIONS: [usize; 2] = [3, 7];
+
+/* The circuit of tests/r1cs.pir, whose public variables are z and d. */
+fn circuit() -> PlonkModule<BlsScalar, JubJubParameters> {
+    let module = vamp_ir::parse(&fs::read_to_string(R1CS).unwrap()).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(PlonkCostModel));
+    let compiled = vamp_ir::compile(module, &PrimeFieldOps::<BlsScalar>::default(), &options).unwrap();
+    PlonkModule::new(compiled.module)
+}
+
+/* Public inputs holding the given values at the given positions. */
+fn public_inputs(values: &[(usize, u64)]) -> PublicInputs<BlsScalar> {
+    let mut pi = PublicInputs::new();
+    for (pos, val) in values {
+        pi.insert(*pos, BlsScalar::from(*val));
+    }
+    pi
+}
+
+/* The values of z and d in the given annotation. */
+fn values(annotated: &HashMap<VariableId, (Variable, BlsScalar)>) -> Vec<(String, BlsScalar)> {
+    let mut values: Vec<_> = annotated.values().map(|(var, val)| (input_name(var), *val)).collect();
+    values.sort_by(|a, b| a.0.cmp(&b.0));
+    values
+}
+
+#[test]
+fn inputs_at_intended_positions_are_annotated() {
+    let circuit = circuit();
+    let positions = POSITIONS.to_vec();
+    let annotated = circuit.annotate_public_inputs_strict(&positions, &public_inputs(&[(3, 12), (7, 6)]), None).unwrap();
+    assert_eq!(values(&annotated), [("d".to_string(), BlsScalar::from(6u64)), ("z".to_string(), BlsScalar::from(12u64))]);
+}
+
+#[test]
+fn misplaced_inputs_are_refused() {
+    let circuit = circuit();
+    let positions = POSITIONS.to_vec();
+    // The value of d was dropped from its position and turns up at another
+    let pi = public_inputs(&[(3, 12), (8, 6)]);
+    match circuit.annotate_public_inputs_strict(&positions, &pi, None) {
+        Err(Error::InvalidProof(message)) => assert!(message.contains("position(s) 8 "), "{}", message),
+        other => panic!("misplaced inputs were not refused: {:?}", other.map(|annotated| values(&annotated))),
+    }
+    // Leniency reads the dropped position as zero
+    let annotated = circuit.annotate_public_inputs(&positions, &pi);
+    assert_eq!(values(&annotated)[0], ("d".to_string(), BlsScalar::from(0u64)));
+}
+
+#[test]
+fn dropped_zero_inputs_read_as_zero() {
+    // Public inputs that are zero are left out of proofs altogether, so a
+    // dropped position is only refused where its value should not be zero
+    let circuit = circuit();
+    let positions = POSITIONS.to_vec();
+    let pi = public_inputs(&[(3, 12)]);
+    let zero = [BlsScalar::from(12u64), BlsScalar::from(0u64)];
+    let annotated = circuit.annotate_public_inputs_strict(&positions, &pi, Some(&zero)).unwrap();
+    assert_eq!(values(&annotated)[0], ("d".to_string(), BlsScalar::from(0u64)));
+    let six = [BlsScalar::from(12u64), BlsScalar::from(6u64)];
+    match circuit.annotate_public_inputs_strict(&positions, &pi, Some(&six)) {
+        Err(Error::InvalidProof(message)) => assert!(message.contains("d at position 7"), "{}", message),
+        other => panic!("a missing input was read as zero: {:?}", other.map(|annotated| values(&annotated))),
+    }
+    // Without expected values nothing tells a dropped zero from a missing input
+    let annotated = circuit.annotate_public_inputs_strict(&positions, &pi, None).unwrap();
+    assert_eq!(values(&annotated)[0], ("d".to_string(), BlsScalar::from(0u64)));
+}
+
+#[test]
+fn public_variables_without_positions_are_named() {
+    let circuit = circuit();
+    let pi = public_inputs(&[(3, 12)]);
+    match circuit.annotate_public_inputs_strict(&vec![3], &pi, None) {
+        Err(Error::Artifact(message)) => assert!(message.ends_with("leaving d without any"), "{}", message),
+        other => panic!("missing positions were not reported: {:?}", other.map(|annotated| values(&annotated))),
+    }
+}
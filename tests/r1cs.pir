@@ -0,0 +1,16 @@
+/* A circuit using each operation that lowers to a rank-1 constraint, which
+   snarkjs can check once exported. Run as follows:
+   vamp-ir export --format r1cs tests/r1cs.pir -o circuit.r1cs
+   vamp-ir export --format wtns tests/r1cs.pir -i tests/r1cs.inputs -o witness.wtns
+   snarkjs wtns check circuit.r1cs witness.wtns
+*/
+
+pub z;
+pub d;
+
+x * y = z;
+
+def q = z / y;
+q = x;
+
+(x - y) * -x + 3 = d;
@@ -0,0 +1,218 @@
+/* Checks that exported .r1cs and .wtns files are laid out as circom and
+ * snarkjs lay them out, by reading them back with a parser written from the
+ * description of the formats, and that the exported witness satisfies the
+ * exported constraints. Checking them with snarkjs itself needs it on the
+ * path, so that test is ignored by default. Importing an exported .r1cs file
+ * is checked to give back an equivalent program. */
+#![cfg(all(feature = "cli", feature = "halo2"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use num_bigint::BigInt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const BN254_PRIME: &str = "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+/* A cursor over the bytes of an exported file. */
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> &'a [u8] {
+        assert!(self.bytes.len() >= len, "file ends early");
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        taken
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take(4).try_into().unwrap())
+    }
+
+    fn u64(&mut self) -> u64 {
+        u64::from_le_bytes(self.take(8).try_into().unwrap())
+    }
+
+    fn element(&mut self, n8: usize) -> BigInt {
+        BigInt::from_bytes_le(num_bigint::Sign::Plus, self.take(n8))
+    }
+}
+
+/* The sections of a file of the binary formats of iden3, by type, after
+ * checking its magic number and version. */
+fn sections<'a>(bytes: &'a [u8], magic: &[u8; 4], version: u32) -> Vec<(u32, &'a [u8])> {
+    let mut reader = Reader { bytes };
+    assert_eq!(reader.take(4), magic);
+    assert_eq!(reader.u32(), version);
+    let count = reader.u32();
+    let mut sections = vec![];
+    for _ in 0..count {
+        let kind = reader.u32();
+        let len = reader.u64() as usize;
+        sections.push((kind, reader.take(len)));
+    }
+    assert!(reader.bytes.is_empty(), "bytes after the last section");
+    sections
+}
+
+fn section<'a>(sections: &[(u32, &'a [u8])], kind: u32) -> Reader<'a> {
+    let matching: Vec<_> = sections.iter().filter(|(k, _)| *k == kind).collect();
+    assert_eq!(matching.len(), 1, "section {} is not there exactly once", kind);
+    Reader { bytes: matching[0].1 }
+}
+
+type LinearCombination = Vec<(u32, BigInt)>;
+
+struct R1cs {
+    prime: BigInt,
+    wires: u32,
+    pub_out: u32,
+    pub_in: u32,
+    prv_in: u32,
+    constraints: Vec<[LinearCombination; 3]>,
+}
+
+fn parse_r1cs(bytes: &[u8]) -> R1cs {
+    let sections = sections(bytes, b"r1cs", 1);
+    let mut header = section(&sections, 1);
+    let n8 = header.u32() as usize;
+    let prime = header.element(n8);
+    let wires = header.u32();
+    let (pub_out, pub_in, prv_in) = (header.u32(), header.u32(), header.u32());
+    let labels = header.u64();
+    let count = header.u32();
+    assert!(header.bytes.is_empty());
+    let mut body = section(&sections, 2);
+    let mut constraints = vec![];
+    for _ in 0..count {
+        let mut lc = || {
+            let nnz = body.u32();
+            (0..nnz).map(|_| (body.u32(), body.element(n8))).collect::<LinearCombination>()
+        };
+        constraints.push([lc(), lc(), lc()]);
+    }
+    assert!(body.bytes.is_empty());
+    let mut map = section(&sections, 3);
+    for wire in 0..wires {
+        assert!(map.u64() < labels, "label of wire {} out of range", wire);
+    }
+    assert!(map.bytes.is_empty());
+    R1cs { prime, wires, pub_out, pub_in, prv_in, constraints }
+}
+
+fn parse_wtns(bytes: &[u8]) -> (BigInt, Vec<BigInt>) {
+    let sections = sections(bytes, b"wtns", 2);
+    let mut header = section(&sections, 1);
+    let n8 = header.u32() as usize;
+    let prime = header.element(n8);
+    let count = header.u32();
+    let mut values = section(&sections, 2);
+    let witness = (0..count).map(|_| values.element(n8)).collect();
+    assert!(values.bytes.is_empty());
+    (prime, witness)
+}
+
+/* Tell whether every constraint holds for the given values of the wires. */
+fn satisfied(r1cs: &R1cs, witness: &[BigInt]) -> bool {
+    let eval = |lc: &LinearCombination| -> BigInt {
+        lc.iter().map(|(wire, coeff)| coeff * &witness[*wire as usize]).sum::<BigInt>() % &r1cs.prime
+    };
+    r1cs.constraints.iter().all(|[a, b, c]| (eval(a) * eval(b) - eval(c)) % &r1cs.prime == BigInt::from(0))
+}
+
+/* Export the example circuit and its witness to the given directory. */
+fn export(dir: &Path) -> (PathBuf, PathBuf) {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/r1cs.pir");
+    let inputs = source.with_extension("inputs");
+    let (r1cs, wtns) = (dir.join("circuit.r1cs"), dir.join("witness.wtns"));
+    let source = source.to_str().unwrap();
+    assert_eq!(vamp_ir(&["export", "--format", "r1cs", source, "-o", r1cs.to_str().unwrap()]), 0);
+    assert_eq!(vamp_ir(&[
+        "export", "--format", "wtns", source, "-i", inputs.to_str().unwrap(), "-o", wtns.to_str().unwrap(),
+    ]), 0);
+    (r1cs, wtns)
+}
+
+#[test]
+fn exported_witness_satisfies_exported_constraints() {
+    let dir = scratch_dir("satisfied");
+    let (r1cs, wtns) = export(&dir);
+    let r1cs = parse_r1cs(&fs::read(r1cs).unwrap());
+    let (prime, mut witness) = parse_wtns(&fs::read(wtns).unwrap());
+    assert_eq!(r1cs.prime, BN254_PRIME.parse().unwrap());
+    assert_eq!(prime, r1cs.prime);
+    assert_eq!(witness.len(), r1cs.wires as usize);
+    assert_eq!((r1cs.pub_out, r1cs.pub_in, r1cs.prv_in), (0, 2, 2));
+    // Wire 0 holds one, and the public variables follow in order
+    assert_eq!(witness[..3], [BigInt::from(1), BigInt::from(12), BigInt::from(6)]);
+    assert!(!r1cs.constraints.is_empty());
+    assert!(satisfied(&r1cs, &witness));
+    // Any other value of a public variable breaks some constraint
+    witness[1] += 1;
+    assert!(!satisfied(&r1cs, &witness));
+}
+
+#[test]
+fn unsatisfied_witness_is_not_exported() {
+    let dir = scratch_dir("unsatisfied");
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/r1cs.pir");
+    let wtns = dir.join("witness.wtns");
+    let status = vamp_ir(&[
+        "export", "--format", "wtns", source.to_str().unwrap(),
+        "-D", "x=3", "-D", "y=4", "-D", "z=13", "-D", "d=6", "-o", wtns.to_str().unwrap(),
+    ]);
+    assert_eq!(status, 1);
+    assert!(!wtns.exists());
+}
+
+#[test]
+#[ignore = "needs snarkjs on the path"]
+fn snarkjs_accepts_exports() {
+    let dir = scratch_dir("snarkjs");
+    let (r1cs, wtns) = export(&dir);
+    let snarkjs = |args: &[&Path]| {
+        let status = Command::new("snarkjs")
+            .args(args)
+            .status()
+            .expect("unable to run snarkjs");
+        assert!(status.success(), "snarkjs {:?} failed", args);
+    };
+    snarkjs(&[Path::new("r1cs"), Path::new("info"), &r1cs]);
+    snarkjs(&[Path::new("wtns"), Path::new("check"), &r1cs, &wtns]);
+}
+
+#[test]
+fn imported_program_is_equivalent() {
+    let dir = scratch_dir("import");
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/import.pir");
+    let (r1cs, imported) = (dir.join("circuit.r1cs"), dir.join("imported.pir"));
+    let (source, r1cs, imported) = (source.to_str().unwrap(), r1cs.to_str().unwrap(), imported.to_str().unwrap());
+    assert_eq!(vamp_ir(&["export", "--format", "r1cs", "--field", "pallas", source, "-o", r1cs]), 0);
+    assert_eq!(vamp_ir(&["import", "--format", "r1cs", "--field", "pallas", r1cs, "-o", imported]), 0);
+    // The public wires keep their order, and so the names of the source
+    assert!(fs::read_to_string(imported).unwrap().starts_with("pub w_1, w_2;"));
+    assert_eq!(vamp_ir(&["diff", "--field", "halo2", source, imported]), 0);
+    // A program that is not imported from that file is told apart from it
+    let other = dir.join("other.pir");
+    fs::write(&other, "pub w_1, w_2;\nw_3 * w_4 = w_1;\nw_2 = 3 * (w_3 + w_4) - 4;\n").unwrap();
+    assert_eq!(vamp_ir(&["diff", "--field", "halo2", other.to_str().unwrap(), imported]), 1);
+}
+
+#[test]
+fn import_over_another_field_is_refused() {
+    let dir = scratch_dir("import-field");
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/r1cs.pir");
+    let (r1cs, imported) = (dir.join("circuit.r1cs"), dir.join("imported.pir"));
+    let (source, r1cs, imported) = (source.to_str().unwrap(), r1cs.to_str().unwrap(), imported.to_str().unwrap());
+    assert_eq!(vamp_ir(&["export", "--format", "r1cs", source, "-o", r1cs]), 0);
+    assert_eq!(vamp_ir(&["import", "--format", "r1cs", "--field", "pallas", r1cs, "-o", imported]), 2);
+    assert!(!Path::new(imported).exists());
+    // Constraints exported over BN254 are imported over it by default
+    assert_eq!(vamp_ir(&["import", "--format", "r1cs", r1cs, "-o", imported]), 0);
+    let program = fs::read_to_string(imported).unwrap();
+    assert!(program.starts_with("pub w_1, w_2;"));
+}
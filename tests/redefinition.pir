@@ -0,0 +1,16 @@
+/* An example of an erroneous redefinition. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/redefinition.pir -o circuit.plonk
+   Compilation must fail with an error stating that x is defined at 12:1 and
+   again at 14:1.
+*/
+
+pub a;
+
+// Top-level definitions cannot be replaced by later ones
+
+def x = a * a;
+
+def x = a + a;
+
+x = a;
@@ -0,0 +1,19 @@
+/* An example exercising polymorphism, closures, and intrinsics, all of which
+   allocate variables during compilation. Compiling it repeatedly should
+   produce byte-identical circuits, as tests/reproducible.rs checks. Run as
+   follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/reproducible.pir -o circuit.plonk
+*/
+
+pub a, b, c;
+
+def id x = x;
+
+def add_to x = fun y { x + y };
+
+def pair = (id a, id (a, b));
+
+def (p, (q, r)) = pair;
+
+c = fold (map (add_to p) (q : r : [])) (fun x acc { x * acc }) 1;
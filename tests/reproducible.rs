@@ -0,0 +1,28 @@
+/* Checks that compiling the same source repeatedly allocates the same
+ * variable ids, and hence writes byte-identical circuits. */
+#![cfg(all(feature = "cli", feature = "halo2"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use std::fs;
+
+const SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/reproducible.pir");
+
+/* Number of times that the source is compiled. */
+const COMPILES: usize = 50;
+
+#[test]
+fn compiles_are_byte_identical() {
+    let dir = scratch_dir("reproducible");
+    let first = dir.join("first.ir");
+    let again = dir.join("again.ir");
+    let (first, again) = (first.to_str().unwrap(), again.to_str().unwrap());
+    assert_eq!(vamp_ir(&["compile", "--no-cache", SOURCE, "--emit-ir", first]), 0);
+    let expected = fs::read(first).unwrap();
+    assert!(!expected.is_empty());
+    for compile in 1..COMPILES {
+        assert_eq!(vamp_ir(&["compile", "--no-cache", SOURCE, "--emit-ir", again, "--force"]), 0);
+        assert!(fs::read(again).unwrap() == expected, "compile {} differs from the first", compile + 1);
+    }
+}
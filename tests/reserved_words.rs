@@ -0,0 +1,38 @@
+/* Checks that names taken over by later syntax are refused with a parse
+ * error saying that they are reserved, so that programs written before know
+ * what to rename, while names that merely begin with them still parse. */
+#![cfg(feature = "compiler")]
+
+fn parse_error(source: &str) -> vamp_ir::diagnostics::Diagnostic {
+    match vamp_ir::parse(source) {
+        Err(vamp_ir::Error::Parse(diagnostic)) => diagnostic,
+        Err(err) => panic!("{} gave {}", source, err),
+        Ok(_) => panic!("{} parsed", source),
+    }
+}
+
+#[test]
+fn let_in_words_are_reserved() {
+    for (source, word) in [("in = 1;", "in"), ("def let = 1;\nlet = 1;", "let"), ("def f in = in;", "in")] {
+        let diagnostic = parse_error(source);
+        assert_eq!(diagnostic.code, "parse-error");
+        if source == "in = 1;" {
+            assert_eq!(diagnostic.span.as_ref().map(|span| span.start), Some((1, 1)));
+        }
+        assert!(diagnostic.notes.contains(&format!("{} is a reserved word and cannot name a variable or function", word)), "{:?}", diagnostic);
+        assert!(diagnostic.notes.contains(&format!("{} became reserved with let-in expressions", word)), "{:?}", diagnostic);
+        assert_eq!(diagnostic.suggestion, Some(format!("rename {} to something else, such as {}_", word, word)));
+    }
+    for source in ["in_ = 1;", "index = 1;", "def letter = 1;\nletter = 1;"] {
+        assert!(vamp_ir::parse(source).is_ok(), "{} did not parse", source);
+    }
+}
+
+#[test]
+fn module_is_reserved() {
+    let diagnostic = parse_error("module = 1;");
+    assert_eq!(diagnostic.code, "parse-error");
+    assert!(diagnostic.notes.contains(&"module became reserved with module declarations".to_string()), "{:?}", diagnostic);
+    assert_eq!(diagnostic.suggestion, Some("rename module to something else, such as module_".to_string()));
+    assert!(vamp_ir::parse("modules = 1;").is_ok());
+}
@@ -0,0 +1,119 @@
+/* Checks that programs, inputs, and proofs survive a round trip through JSON
+ * with serde unchanged, comparing them by their bincode encodings, which the
+ * serde support must leave as they were. Only built with the serde
+ * feature, along with the halo2 one. */
+#![cfg(all(feature = "serde", feature = "halo2"))]
+
+use halo2_proofs::pasta::Fp;
+use num_bigint::BigInt;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{RngCore, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use vamp_ir::ast::{Assignments, Module};
+use vamp_ir::halo2::circuit::{Halo2Circuit, Halo2Proof};
+use vamp_ir::halo2::synth::{Halo2CostModel, PrimeFieldOps};
+use vamp_ir::transform::CompileOptions;
+
+/* Serialize the given value to JSON and back. Parsed programs nest as deeply
+ * as their expressions, so the recursion limit is lifted. */
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).expect("unable to serialize");
+    let mut deserializer = serde_json::Deserializer::from_str(&json);
+    deserializer.disable_recursion_limit();
+    T::deserialize(&mut deserializer).expect("unable to deserialize")
+}
+
+fn bincode_bytes(module: &Module) -> Vec<u8> {
+    bincode::encode_to_vec(module, bincode::config::standard()).expect("unable to encode module")
+}
+
+/* The parsed and compiled modules of every example program that compiles. */
+fn example_modules() -> Vec<(String, Module)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut modules = vec![];
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(true, |ext| ext != "pir") {
+            continue;
+        }
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let Ok(parsed) = vamp_ir::parse(&fs::read_to_string(&path).unwrap()) else {
+            continue;
+        };
+        modules.push((name.clone(), parsed.clone()));
+        let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+        if let Ok(compiled) = vamp_ir::compile(parsed, &PrimeFieldOps::<Fp>::default(), &options) {
+            modules.push((format!("{} (compiled)", name), compiled.module));
+        }
+    }
+    modules
+}
+
+#[test]
+fn modules_round_trip() {
+    let modules = example_modules();
+    assert!(modules.len() > 40, "too few example programs compile");
+    for (name, module) in modules {
+        let restored = round_trip(&module);
+        assert_eq!(bincode_bytes(&module), bincode_bytes(&restored), "{} changed", name);
+        assert_eq!(module.to_string(), restored.to_string(), "{} changed", name);
+    }
+}
+
+#[test]
+fn constants_serialize_as_strings() {
+    let module = vamp_ir::parse("x = 123456789012345678901234567890; -7 = y;").unwrap();
+    let json = serde_json::to_string(&module).unwrap();
+    assert!(json.contains("\"123456789012345678901234567890\""), "{}", json);
+    assert!(!json.contains("[1,"), "{}", json);
+}
+
+#[test]
+fn assignments_round_trip() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for _ in 0..100 {
+        let mut values = HashMap::new();
+        for _ in 0..rng.next_u32() % 20 {
+            // Values of every size up to well past the width of any field
+            let mut bytes = vec![0u8; (rng.next_u32() % 80) as usize];
+            rng.fill_bytes(&mut bytes);
+            let mut value = BigInt::from_signed_bytes_le(&bytes);
+            if rng.next_u32() % 2 == 0 {
+                value = -value;
+            }
+            values.insert(rng.next_u32(), value);
+        }
+        let assignments = Assignments::from(values);
+        let json = serde_json::to_value(&assignments).unwrap();
+        assert!(json.as_object().unwrap().values().all(|value| value.is_string()), "{}", json);
+        assert_eq!(round_trip(&assignments), assignments);
+    }
+}
+
+#[test]
+fn halo2_proofs_round_trip() {
+    let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+    let module = vamp_ir::parse("pub z; pub w; x * y = z; x + y = w;").unwrap();
+    let compiled = vamp_ir::compile(module, &PrimeFieldOps::<Fp>::default(), &options).unwrap();
+    let circuit = Halo2Circuit::new(compiled.module).unwrap();
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    for _ in 0..3 {
+        let (x, y) = (BigInt::from(rng.next_u64()), BigInt::from(rng.next_u64()));
+        let inputs = HashMap::from([
+            ("x".to_string(), x.clone()),
+            ("y".to_string(), y.clone()),
+            ("z".to_string(), &x * &y),
+            ("w".to_string(), &x + &y),
+        ]);
+        let proof = circuit.prove_with_rng(&inputs, &mut rng).unwrap();
+        let restored: Halo2Proof = round_trip(&proof);
+        assert_eq!(restored, proof);
+        circuit.verify(&restored).unwrap();
+    }
+    let bad: Result<Halo2Proof, _> = serde_json::from_str(r#"{"proof": "00", "public_values": ["-1"]}"#);
+    assert!(bad.is_err());
+}
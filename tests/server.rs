@@ -0,0 +1,192 @@
+/* Drives an in-process proving service over HTTP, as clients of
+ * vamp-ir-server would: compiling a program and reading its interface,
+ * generating keys and proving as jobs polled until done, verifying, and
+ * listing circuits, along with the limits on what it accepts and keeps. Only
+ * built with the server feature. */
+#![cfg(feature = "server")]
+
+mod common;
+
+use common::scratch_dir;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use vamp_ir::server::{Server, ServerConfig};
+
+/* Start a server with the given configuration on a port of its own, giving
+ * it along with the root of its URLs. */
+fn start(test: &str, config: ServerConfig) -> (Server, String) {
+    let server = Server::bind("127.0.0.1:0", config.cache_dir(scratch_dir(test))).unwrap();
+    let url = format!("http://{}", server.addr());
+    (server, url)
+}
+
+/* Post the given JSON to the given URL, giving the status and JSON reply. */
+fn post(client: &Client, url: &str, body: &Value) -> (StatusCode, Value) {
+    let response = client.post(url).json(body).send().unwrap();
+    (response.status(), response.json().unwrap())
+}
+
+fn get(client: &Client, url: &str) -> (StatusCode, Value) {
+    let response = client.get(url).send().unwrap();
+    (response.status(), response.json().unwrap())
+}
+
+/* Compile the given source, waiting for the job that generates its keys if
+ * there is one, and give the reply. */
+fn compile(client: &Client, url: &str, source: &str) -> Value {
+    let (status, reply) = post(client, &format!("{}/compile", url), &json!({ "source": source }));
+    if status == StatusCode::ACCEPTED {
+        let done = wait_for(client, url, reply["job"].as_str().unwrap());
+        assert_eq!(done["status"], "done");
+        assert_eq!(done["circuit"], reply["circuit"]);
+    } else {
+        assert_eq!(status, StatusCode::OK);
+    }
+    reply
+}
+
+/* Poll the given job until it is done or failed, giving its last status. */
+fn wait_for(client: &Client, url: &str, job: &str) -> Value {
+    let start = Instant::now();
+    loop {
+        let (status, reply) = get(client, &format!("{}/jobs/{}", url, job));
+        assert_eq!(status, StatusCode::OK);
+        if reply["status"] == "done" || reply["status"] == "failed" {
+            return reply;
+        }
+        assert!(start.elapsed() < Duration::from_secs(300), "proof took too long");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn proofs_are_made_as_jobs() {
+    let (server, url) = start("jobs", ServerConfig::default());
+    let client = Client::new();
+    let (status, reply) = post(&client, &format!("{}/compile", url), &json!({ "source": "pub z; x * y = z;" }));
+    // Keys are generated as a job, while the interface is known at once
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let circuit = reply["circuit"].as_str().unwrap().to_string();
+    assert_eq!(reply["interface"]["version"], 1);
+    let names: Vec<_> = reply["interface"]["inputs"].as_array().unwrap().iter()
+        .map(|input| (input["name"].as_str().unwrap(), input["visibility"].as_str().unwrap()))
+        .collect();
+    assert_eq!(names, [("z", "public"), ("x", "private"), ("y", "private")]);
+    let done = wait_for(&client, &url, reply["job"].as_str().unwrap());
+    assert_eq!(done["status"], "done");
+    assert_eq!(done["circuit"], circuit.as_str());
+    let (status, interface) = get(&client, &format!("{}/circuits/{}/interface", url, circuit));
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(interface, reply["interface"]);
+
+    let (status, circuits) = get(&client, &format!("{}/circuits", url));
+    assert_eq!(status, StatusCode::OK);
+    assert!(circuits["circuits"].as_array().unwrap().iter().any(|entry| entry["circuit"] == circuit.as_str()));
+
+    let inputs = json!({ "x": "3", "y": 4, "z": "12" });
+    let (status, reply) = post(&client, &format!("{}/prove", url), &json!({ "circuit": circuit, "inputs": inputs }));
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let done = wait_for(&client, &url, reply["job"].as_str().unwrap());
+    assert_eq!(done["status"], "done");
+    let proof = done["proof"].as_str().unwrap();
+
+    let (status, reply) = post(&client, &format!("{}/verify", url), &json!({ "circuit": circuit, "proof": proof }));
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reply["valid"], true);
+
+    // Inputs that violate the constraints fail their job, not the submission
+    let inputs = json!({ "x": "3", "y": "4", "z": "13" });
+    let (status, reply) = post(&client, &format!("{}/prove", url), &json!({ "circuit": circuit, "inputs": inputs }));
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(wait_for(&client, &url, reply["job"].as_str().unwrap())["status"], "failed");
+    server.shutdown();
+}
+
+#[test]
+fn bad_requests_are_refused() {
+    let (server, url) = start("refused", ServerConfig::default().max_body_bytes(64));
+    let client = Client::new();
+    let source = format!("pub z; x * y = z; // {}", "padding ".repeat(16));
+    let (status, _) = post(&client, &format!("{}/compile", url), &json!({ "source": source }));
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    let (status, _) = post(&client, &format!("{}/compile", url), &json!({ "source": "x * = z;" }));
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = post(&client, &format!("{}/prove", url), &json!({ "circuit": "0123", "inputs": {} }));
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    // Only full digests are looked up in the cache
+    let (status, _) = post(&client, &format!("{}/prove", url), &json!({ "circuit": "", "inputs": {} }));
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get(&client, &format!("{}/jobs/99", url));
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get(&client, &format!("{}/circuits/0123/interface", url));
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get(&client, &format!("{}/nowhere", url));
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    server.shutdown();
+}
+
+#[test]
+fn circuits_and_outcomes_are_kept_once() {
+    let (server, url) = start("kept", ServerConfig::default().max_finished_jobs(1));
+    let client = Client::new();
+    // Compiling the same source again gives back the same circuit, without
+    // generating its keys again
+    let first = compile(&client, &url, "pub z; x * y = z;");
+    let circuit = first["circuit"].as_str().unwrap().to_string();
+    assert_eq!(circuit.len(), 64);
+    let (status, again) = post(&client, &format!("{}/compile", url), &json!({ "source": "pub z; x * y = z;" }));
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(again, json!({ "circuit": first["circuit"], "interface": first["interface"] }));
+    let other = compile(&client, &url, "pub z; x + y = z;");
+    assert_ne!(other["circuit"], first["circuit"]);
+
+    // Only the outcome of the last proof to finish is kept
+    let inputs = json!({ "x": "3", "y": 4, "z": "12" });
+    let (_, reply) = post(&client, &format!("{}/prove", url), &json!({ "circuit": circuit, "inputs": inputs }));
+    let old = reply["job"].as_str().unwrap().to_string();
+    assert_eq!(wait_for(&client, &url, &old)["status"], "done");
+    let (_, reply) = post(&client, &format!("{}/prove", url), &json!({ "circuit": circuit, "inputs": inputs }));
+    assert_eq!(wait_for(&client, &url, reply["job"].as_str().unwrap())["status"], "done");
+    let (status, _) = get(&client, &format!("{}/jobs/{}", url, old));
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    server.shutdown();
+}
+
+#[test]
+fn circuits_dropped_from_memory_are_read_again() {
+    let config = ServerConfig::default().max_loaded_circuits(1).circuit_ttl(Duration::from_secs(1));
+    let (server, url) = start("loaded", config);
+    let client = Client::new();
+    let product = compile(&client, &url, "pub z; x * y = z;")["circuit"].as_str().unwrap().to_string();
+    let sum = compile(&client, &url, "pub z; x + y = z;")["circuit"].as_str().unwrap().to_string();
+    // Only one circuit is kept at a time, and none for long, so each of these
+    // reads its circuit from the cache again
+    let prove = |circuit: &str, inputs: Value| {
+        let (status, reply) = post(&client, &format!("{}/prove", url), &json!({ "circuit": circuit, "inputs": inputs }));
+        assert_eq!(status, StatusCode::ACCEPTED);
+        wait_for(&client, &url, reply["job"].as_str().unwrap())
+    };
+    assert_eq!(prove(&product, json!({ "x": 3, "y": 4, "z": 12 }))["status"], "done");
+    assert_eq!(prove(&sum, json!({ "x": 3, "y": 4, "z": 7 }))["status"], "done");
+    std::thread::sleep(Duration::from_secs(2));
+    let done = prove(&product, json!({ "x": 3, "y": 4, "z": 12 }));
+    assert_eq!(done["status"], "done");
+    let (status, reply) = post(&client, &format!("{}/verify", url), &json!({ "circuit": product, "proof": done["proof"] }));
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reply["valid"], true);
+    server.shutdown();
+}
+
+#[test]
+fn shutdown_ends_every_thread() {
+    let config = ServerConfig::default().request_threads(2).proof_workers(3);
+    let (server, url) = start("shutdown", config);
+    let (status, _) = get(&Client::new(), &format!("{}/circuits", url));
+    assert_eq!(status, StatusCode::OK);
+    // Shutting down returns only once the idle workers have been woken too,
+    // after which nothing listens on the address
+    server.shutdown();
+    assert!(Client::new().get(format!("{}/circuits", url)).send().is_err());
+}
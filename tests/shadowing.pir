@@ -0,0 +1,26 @@
+/* An example of local bindings that shadow earlier bindings. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/shadowing.pir -o circuit.plonk --warn-shadowing
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+   Compilation must succeed whilst warning about the bindings of x, y, and z
+   below, and must abort if --deny-warnings is also given.
+*/
+
+pub a, b;
+
+def x = a + 1;
+
+// The parameter shadows the definition above
+
+def double x = x + x;
+
+// Nested lets where the inner binding shadows the outer one
+
+let y = b in let y = y * y in double x = y + a;
+
+// Lambdas shadowing variables bound by an enclosing lambda
+
+def twice z = (fun z { z * z }) z;
+
+twice a = b;
@@ -0,0 +1,42 @@
+/* Decompose x into 8 bits, and then recompose bits 2 to 5 into y. Any x such
+   that y = (x \ 4) % 16 is valid. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/slice.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub x, y;
+
+// Ensure that the given argument is 1 or 0, and returns it
+
+def bool x = { x*(x-1) = 0; x };
+
+// Extract the given number of bits from a number, least significant first
+
+def bits 0 a = [];
+
+def bits n a = {
+    def lsb = bool (fresh (a % 2));
+    def rest = fresh (a \ 2);
+    a = lsb + 2*rest;
+    lsb : bits (n-1) rest
+};
+
+// Combine a list of bits back into a number
+
+def combine [] = 0;
+
+def combine (b:bs) = b + 2*(combine bs);
+
+def xs = bits 8 x;
+
+len xs = 8;
+
+x = combine xs;
+
+y = combine xs[2..6];
+
+// Concatenating the slices gives back the original list
+
+xs[0..2] ++ xs[2..6] ++ xs[6..8] = xs;
@@ -0,0 +1,11 @@
+/* An example whose constraints hold for any choice of a and b, so every
+   sample should satisfy them. Run as follows:
+   vamp-ir test tests/smoke.pir -n 32
+   vamp-ir test tests/smoke.pir -i tests/smoke.inputs
+*/
+
+pub c;
+
+a * b = c;
+
+(a + b) * (a + b) = 4 * c + (a - b) * (a - b);
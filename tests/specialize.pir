@@ -0,0 +1,25 @@
+/* An example of a circuit gated by a flag that is fixed in practice. Run as
+   follows:
+   vamp-ir compile -s tests/specialize.pir --emit-ir general.ir
+   vamp-ir compile -s tests/specialize.pir --emit-ir special.ir --define flag=0
+   The summary of the second compilation must report 2 public variables
+   rather than 3 and strictly fewer constraints than the first, and
+   special.ir must mention neither flag nor the cube of x. For proving:
+   vamp-ir plonk setup -o params.pp
+   vamp-ir plonk compile -u params.pp -s tests/specialize.pir -o circuit.plonk --define flag=0
+   vamp-ir plonk prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir plonk verify -u params.pp -c circuit.plonk -p proof.plonk
+   Both prove and verify must report that flag was fixed to 0 at compile time,
+   and prove must only ask for x and y. Giving --define z=1 instead must abort
+   because z is not a public variable.
+*/
+
+pub flag, x, y;
+
+def cube a = a * a * a;
+
+flag * (flag - 1) = 0;
+
+// With the flag fixed to 0, the polynomial branch vanishes entirely
+
+flag * (cube x + 3 * x * x + 5) + (1 - flag) * x = y;
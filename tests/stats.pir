@@ -0,0 +1,15 @@
+/* An example with a summary hand-checked by tests/stats.rs. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/stats.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub x, y;
+
+// Each of these fits in a single gate without auxiliary variables
+x * y = 6;
+x + 1 = y;
+z = 3;
+// A constraint that always holds occupies no gate
+z - z = 0;
@@ -0,0 +1,42 @@
+/* Checks the summaries of compiled modules against the hand-checked counts
+ * given in tests/stats.pir and tests/stats_aux.pir. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use std::collections::BTreeMap;
+use std::fs;
+use vamp_ir::plonk::field::{PlonkCostModel, PrimeFieldOps};
+use vamp_ir::transform::{CompileOptions, ModuleStats};
+
+/* Compile the given example of tests/ for the PLONK backend and summarize
+ * it. */
+fn stats(example: &str) -> ModuleStats {
+    let path = format!("{}/tests/{}.pir", env!("CARGO_MANIFEST_DIR"), example);
+    let module = vamp_ir::parse(&fs::read_to_string(path).unwrap()).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(PlonkCostModel));
+    vamp_ir::compile(module, &PrimeFieldOps::<Fr>::default(), &options).unwrap().stats
+}
+
+#[test]
+fn stats_without_auxiliary_variables() {
+    let stats = stats("stats");
+    assert_eq!(stats.source_constraints, Some(4));
+    assert_eq!(stats.constraints, 3);
+    assert_eq!(stats.shapes, BTreeMap::from([("add", 1), ("equal constant", 1), ("multiply", 1)]));
+    assert_eq!(stats.auxiliary_variables, 0);
+    assert_eq!(stats.definition_depth, 1);
+    assert_eq!(stats.pubs, 2);
+    assert_eq!(stats.estimated_cost, Some(11));
+}
+
+#[test]
+fn stats_with_auxiliary_variables() {
+    let stats = stats("stats_aux");
+    assert_eq!(stats.source_constraints, Some(2));
+    assert_eq!(stats.constraints, 4);
+    assert_eq!(stats.shapes, BTreeMap::from([("add", 1), ("multiply", 2), ("negate", 1)]));
+    assert_eq!(stats.auxiliary_variables, 2);
+    assert_eq!(stats.definition_depth, 1);
+    assert_eq!(stats.pubs, 1);
+    assert_eq!(stats.estimated_cost, Some(12));
+}
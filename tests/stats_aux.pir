@@ -0,0 +1,13 @@
+/* An example that needs auxiliary variables, with a summary hand-checked by
+   tests/stats.rs. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/stats_aux.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub a;
+
+// Both products need variables of their own, the sum is c
+a * a + b * b = c;
+(- c) = d;
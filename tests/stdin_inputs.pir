@@ -0,0 +1,12 @@
+/* An example whose inputs are piped in. Run as follows:
+   vamp-ir halo2 compile -s tests/stdin_inputs.pir -o circuit.halo2
+   echo '{"a": 4, "b": "0x6", "c": "24"}' | vamp-ir halo2 prove -c circuit.halo2 -i - -o proof.halo2
+   vamp-ir halo2 verify -c circuit.halo2 -p proof.halo2
+   Proving must read the inputs from standard input as JSON without prompting.
+   Piping in {"a": "4"} instead must abort with an error naming b and c as
+   missing inputs of standard input, and never prompt for them.
+*/
+
+pub c;
+
+a * b = c;
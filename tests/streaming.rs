@@ -0,0 +1,94 @@
+/* Checks that verification from readers makes no assumption about how much
+ * each read returns, by feeding every file through a reader that hands out a
+ * few bytes at a time, as a slow socket might. */
+#![cfg(all(feature = "cli", feature = "halo2", feature = "plonk"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use halo2_proofs::pasta::Fp;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use vamp_ir::encoding::Encoding;
+use vamp_ir::halo2::circuit::Halo2Circuit;
+use vamp_ir::halo2::synth::{Halo2CostModel, PrimeFieldOps};
+use vamp_ir::halo2::verify::Halo2Proof;
+use vamp_ir::transform::CompileOptions;
+use vamp_ir::{verify_from_reader, Error};
+
+const SMOKE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/smoke.pir");
+
+/* A reader of the given bytes that returns one to three of them from each
+ * read, however large the buffer it is given. */
+struct Trickle {
+    bytes: Vec<u8>,
+    position: usize,
+    reads: usize,
+}
+
+fn trickle(bytes: Vec<u8>) -> Trickle {
+    Trickle { bytes, position: 0, reads: 0 }
+}
+
+impl Read for Trickle {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads += 1;
+        let remaining = self.bytes.len() - self.position;
+        let count = (1 + self.reads % 3).min(remaining).min(buf.len());
+        buf[..count].copy_from_slice(&self.bytes[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+#[test]
+fn halo2_verifies_from_trickling_readers() {
+    let module = vamp_ir::parse(&fs::read_to_string(SMOKE).unwrap()).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+    let compiled = vamp_ir::compile(module, &PrimeFieldOps::<Fp>::default(), &options).unwrap();
+    let circuit = Halo2Circuit::new(compiled.module).unwrap();
+    let inputs = HashMap::from([
+        ("a".to_string(), 3.into()),
+        ("b".to_string(), 4.into()),
+        ("c".to_string(), 12.into()),
+    ]);
+    let proof = circuit.prove(&inputs).unwrap().to_bytes().unwrap();
+    let verifier = circuit.verifier_data().to_bytes().unwrap();
+
+    verify_from_reader(trickle(verifier.clone()), trickle(proof.clone()), None).unwrap();
+    verify_from_reader(trickle(verifier.clone()), trickle(proof.clone()), Some(&[12.into()])).unwrap();
+    let forged = verify_from_reader(trickle(verifier.clone()), trickle(proof.clone()), Some(&[13.into()]));
+    assert!(matches!(forged, Err(Error::InvalidProof(_))));
+    // Text encodings are read whole before they are decoded
+    let hex_proof = Encoding::Hex.encode(proof.clone());
+    verify_from_reader(trickle(Encoding::Base64.encode(verifier.clone())), trickle(hex_proof), None).unwrap();
+    // Circuits stream just as verifiers do
+    let streamed = Halo2Circuit::from_reader(trickle(circuit.to_bytes().unwrap())).unwrap();
+    streamed.verify(&Halo2Proof::from_reader(trickle(proof.clone())).unwrap()).unwrap();
+    // A verifier cut short is refused rather than waited on
+    let truncated = verifier[..verifier.len() / 2].to_vec();
+    assert!(matches!(verify_from_reader(trickle(truncated), trickle(proof), None), Err(Error::Artifact(_))));
+}
+
+#[test]
+fn plonk_verifies_from_trickling_readers() {
+    let dir = scratch_dir("plonk");
+    let (params, circuit, proof) = (dir.join("params.plonk"), dir.join("smoke.plonk"), dir.join("smoke.proof"));
+    let (params, circuit, proof) = (params.to_str().unwrap(), circuit.to_str().unwrap(), proof.to_str().unwrap());
+    assert_eq!(vamp_ir(&["plonk", "setup", "-o", params]), 0);
+    assert_eq!(vamp_ir(&["plonk", "compile", "--no-cache", "-u", params, "-s", SMOKE, "-o", circuit]), 0);
+    assert_eq!(vamp_ir(&[
+        "plonk", "prove", "-u", params, "-c", circuit, "-o", proof, "-D", "a=3", "-D", "b=4", "-D", "c=12",
+    ]), 0);
+    let verify = |expected: Option<&[num_bigint::BigInt]>| vamp_ir::plonk::circuit::verify_from_reader(
+        trickle(fs::read(params).unwrap()),
+        trickle(fs::read(circuit).unwrap()),
+        trickle(fs::read(proof).unwrap()),
+        expected,
+    );
+    verify(None).unwrap();
+    verify(Some(&[12.into()])).unwrap();
+    assert!(matches!(verify(Some(&[13.into()])), Err(Error::InvalidProof(_))));
+    assert!(matches!(verify(Some(&[12.into(), 12.into()])), Err(Error::Inputs(_))));
+}
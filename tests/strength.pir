@@ -0,0 +1,16 @@
+/* An example whose multiplications and subtractions are all removed by -O1.
+   Without it, the summary reports one multiply and one subtract constraint,
+   whereas with it only additions remain. Both circuits define the same
+   relation. Run as follows:
+   vamp-ir compile tests/strength.pir --emit-ir strength.ir
+   vamp-ir compile -O1 tests/strength.pir --emit-ir strength.ir --force
+   vamp-ir diff tests/strength.pir tests/strength.pir --optimize-second
+*/
+
+pub x, y, z;
+
+// Doubling a variable is an addition
+x * 2 = y;
+
+// Identical terms cancel, leaving z = x + 1
+(x + y) - (x + y) + z = x + 1;
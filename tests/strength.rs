@@ -0,0 +1,28 @@
+/* Checks that reducing the strength of operations keeps the relation that the
+ * constraints define, including the non-zero divisors that division implies. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::{
+    derive_available_witnesses, required_inputs, violated_constraints, CompileOptions, MAX_OPT_LEVEL,
+};
+
+#[test]
+fn cancelled_divisions_keep_their_divisors() {
+    let field_ops = PrimeFieldOps::<Fr>::default();
+    let module = vamp_ir::parse("(1/x) - (1/x) = 0;").unwrap();
+    let options = CompileOptions::default().opt_level(MAX_OPT_LEVEL);
+    let module = vamp_ir::compile(module, &field_ops, &options).unwrap().module;
+    let inputs = required_inputs(&module);
+    assert_eq!(inputs.len(), 1, "{}", module);
+    // A zero divisor leaves the quotient without a value
+    let mut assigns = HashMap::from([(inputs[0].id, BigInt::from(0))]);
+    assert!(!derive_available_witnesses(&module, &mut assigns, &field_ops).is_empty(), "{}", module);
+    // Any other divisor satisfies the constraint
+    let mut assigns = HashMap::from([(inputs[0].id, BigInt::from(3))]);
+    assert!(derive_available_witnesses(&module, &mut assigns, &field_ops).is_empty());
+    assert!(violated_constraints(&module, &assigns, &field_ops).is_empty());
+}
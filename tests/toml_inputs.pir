@@ -0,0 +1,14 @@
+/* An example whose inputs are supplied as TOML, in the form of the template
+   that vamp-ir writes. Run as follows:
+   vamp-ir halo2 compile -s tests/toml_inputs.pir -o circuit.halo2
+   vamp-ir inputs-template -c circuit.halo2 -o template.toml
+   vamp-ir halo2 prove -c circuit.halo2 -o proof.halo2 -i tests/toml_inputs.toml
+   vamp-ir halo2 verify -c circuit.halo2 -p proof.halo2
+   The template must name z, x, and y in that order, each with an empty
+   string, and proving with it must abort, citing $.z as empty. Proving with
+   the filled-in file must succeed.
+*/
+
+pub z;
+
+x * y = z;
@@ -0,0 +1,22 @@
+/* An example whose tuple inputs are supplied as JSON lists. Run as follows:
+   vamp-ir halo2 compile -s tests/tuple_inputs.pir -o circuit.halo2
+   vamp-ir halo2 prove -c circuit.halo2 -o proof.halo2 -i tests/tuple_inputs.json
+   vamp-ir halo2 verify -c circuit.halo2 -p proof.halo2
+   Proving must succeed, with q flattened into q.0, q.1.0, q.1.1.0, and
+   q.1.1.1, and likewise each pair of r. Dropping an element of q must abort,
+   citing $.q as having 3 values where type (int, int, int, int) needs 4.
+   Replacing r with a number must abort, citing $.r as needing a list.
+*/
+
+def map f (a, b, c, d) = {
+    f a;
+    f b;
+    f c;
+    f d
+};
+
+def bool x = { x*(x-1) = 0 };
+
+map bool q;
+
+map (fun (a, b) { a = b }) r;
@@ -0,0 +1,95 @@
+/* Checks that populating a circuit whose module lost a definition, as a
+ * hand-edited module or a faulty pass could, names the variable left without
+ * a value and what needed it rather than panicking. */
+#![cfg(all(feature = "halo2", feature = "plonk"))]
+
+use ark_bls12_381::Fr as BlsScalar;
+use ark_ed_on_bls12_381::EdwardsParameters as JubJubParameters;
+use halo2_proofs::pasta::Fp;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::fs;
+use vamp_ir::ast::{Module, Pat, Variable, VariableId};
+use vamp_ir::halo2::synth::{Halo2Module, PrimeFieldOps as Halo2FieldOps};
+use vamp_ir::plonk::synth::{PlonkModule, PrimeFieldOps as PlonkFieldOps};
+use vamp_ir::progress::NoProgress;
+use vamp_ir::transform::{input_name, required_inputs, CompileOptions, EvalError, FieldOps};
+
+const R1CS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/r1cs.pir");
+
+/* Inputs of tests/r1cs.pir that satisfy it. */
+const INPUTS: [(&str, i64); 4] = [("x", 3), ("y", 4), ("z", 12), ("d", 6)];
+
+/* Compile tests/r1cs.pir over the given field, and then drop the first
+ * definition whose variable something else refers to. Returns the module,
+ * the values of its original inputs, and the variable left undefined. */
+fn module_missing_definition(field_ops: &dyn FieldOps) -> (Module, HashMap<VariableId, BigInt>, Variable) {
+    let module = vamp_ir::parse(&fs::read_to_string(R1CS).unwrap()).unwrap();
+    let mut module = vamp_ir::compile(module, field_ops, &CompileOptions::default()).unwrap().module;
+    let values: HashMap<_, _> = INPUTS.into_iter().collect();
+    let assigns = required_inputs(&module).iter()
+        .map(|var| (var.id, BigInt::from(values[input_name(var).as_str()])))
+        .collect();
+    let index = (0..module.defs.len()).find(|index| match &module.defs[*index].0.0.v {
+        Pat::Variable(var) => {
+            let name = var.to_string();
+            module.defs[index + 1..].iter().any(|def| def.0.1.to_string().contains(&name)) ||
+                module.exprs.iter().any(|expr| expr.to_string().contains(&name))
+        },
+        _ => false,
+    }).expect("some definition is referred to");
+    let removed = module.defs.remove(index);
+    let Pat::Variable(var) = &removed.0.0.v else { unreachable!() };
+    (module, assigns, var.clone())
+}
+
+/* Check that the given error names the given variable as undefined. */
+fn check_undefined(err: EvalError, var: &Variable) {
+    let message = err.to_string();
+    match err {
+        EvalError::Undefined { id, name, referrer } => {
+            assert_eq!(id, var.id);
+            assert_eq!(name, input_name(var));
+            let referrer = referrer.expect("the variable is referred to");
+            assert_eq!(message, format!(
+                "no definition or input provided for variable `{}`, referenced while computing `{}`",
+                name, referrer,
+            ));
+        },
+        other => panic!("{} is not reported as undefined: {}", var, other),
+    }
+}
+
+#[test]
+fn halo2_names_undefined_variables() {
+    let field_ops = Halo2FieldOps::<Fp>::default();
+    let (module, assigns, var) = module_missing_definition(&field_ops);
+    let mut circuit = Halo2Module::<Fp>::new(module);
+    let assigns = assigns.into_iter()
+        .map(|(id, value)| (id, vamp_ir::halo2::synth::make_constant(value)))
+        .collect();
+    check_undefined(circuit.populate_variables(assigns, &NoProgress).unwrap_err(), &var);
+}
+
+#[test]
+fn plonk_names_undefined_variables() {
+    let field_ops = PlonkFieldOps::<BlsScalar>::default();
+    let (module, assigns, var) = module_missing_definition(&field_ops);
+    let mut circuit = PlonkModule::<BlsScalar, JubJubParameters>::new(module);
+    let assigns = assigns.iter()
+        .map(|(id, value)| (*id, vamp_ir::plonk::synth::make_constant(value)))
+        .collect();
+    check_undefined(circuit.populate_variables(assigns, &NoProgress).unwrap_err(), &var);
+}
+
+#[test]
+fn violated_constraints_are_reported() {
+    let field_ops = PlonkFieldOps::<BlsScalar>::default();
+    let module = vamp_ir::parse(&fs::read_to_string(R1CS).unwrap()).unwrap();
+    let module = vamp_ir::compile(module, &field_ops, &CompileOptions::default()).unwrap().module;
+    let assigns = required_inputs(&module).iter()
+        .map(|var| (var.id, vamp_ir::plonk::synth::make_constant(&BigInt::from(if input_name(var) == "z" { 13 } else { 3 }))))
+        .collect();
+    let mut circuit = PlonkModule::<BlsScalar, JubJubParameters>::new(module);
+    assert!(matches!(circuit.populate_variables(assigns, &NoProgress), Err(EvalError::Unsatisfied(_))));
+}
@@ -0,0 +1,100 @@
+/* Checks that the API of verify-only builds, load_verifier_artifact and
+ * verify, and the vamp-ir-verify binary built on it, accept the proofs of the
+ * full library and command line from a verifier file and a proof file alone.
+ * These are compiled in the normal profile, which has the same API, so that
+ * the proofs can be made here. */
+#![cfg(all(feature = "cli", feature = "verifier-cli", feature = "halo2"))]
+
+mod common;
+
+use common::{run, scratch_dir, vamp_ir};
+use halo2_proofs::pasta::Fp;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use vamp_ir::halo2::circuit::Halo2Circuit;
+use vamp_ir::halo2::synth::{Halo2CostModel, PrimeFieldOps};
+use vamp_ir::transform::CompileOptions;
+use vamp_ir::{load_verifier_artifact, verify, Error};
+
+const SMOKE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/smoke.pir");
+
+#[test]
+fn emitted_verifiers_check_proofs() {
+    let dir = scratch_dir("emitted");
+    let circuit = dir.join("smoke.halo2");
+    let verifier = dir.join("smoke.verifier");
+    let (proof, contained) = (dir.join("smoke.proof"), dir.join("smoke.cbor"));
+    assert_eq!(vamp_ir(&[
+        "halo2", "compile", "--no-cache", "-s", SMOKE, "-o", circuit.to_str().unwrap(),
+        "--emit-verifier", verifier.to_str().unwrap(),
+    ]), 0);
+    for (path, container) in [(&proof, None), (&contained, Some("cbor"))] {
+        let mut args = vec![
+            "halo2", "prove", "-c", circuit.to_str().unwrap(), "-o", path.to_str().unwrap(),
+            "-D", "a=3", "-D", "b=4", "-D", "c=12",
+        ];
+        args.extend(container.map(|format| ["--container", format]).into_iter().flatten());
+        assert_eq!(vamp_ir(&args), 0);
+    }
+    let verifier = load_verifier_artifact(&fs::read(&verifier).unwrap()).unwrap();
+    verify(&verifier, &fs::read(&proof).unwrap()).unwrap();
+    verify(&verifier, &fs::read(&contained).unwrap()).unwrap();
+    // Verifiers are inspected like any other file
+    assert_eq!(vamp_ir(&["inspect", dir.join("smoke.verifier").to_str().unwrap()]), 0);
+    // Circuits are not verifiers, even though they hold all that one does
+    assert!(matches!(load_verifier_artifact(&fs::read(&circuit).unwrap()), Err(Error::Artifact(_))));
+}
+
+#[test]
+fn library_verifiers_check_proofs() {
+    let module = vamp_ir::parse(&fs::read_to_string(SMOKE).unwrap()).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+    let compiled = vamp_ir::compile(module, &PrimeFieldOps::<Fp>::default(), &options).unwrap();
+    let circuit = Halo2Circuit::new(compiled.module).unwrap();
+    let inputs = HashMap::from([
+        ("a".to_string(), 3.into()),
+        ("b".to_string(), 4.into()),
+        ("c".to_string(), 12.into()),
+    ]);
+    let proof = circuit.prove(&inputs).unwrap();
+    let verifier = load_verifier_artifact(&circuit.verifier_data().to_bytes().unwrap()).unwrap();
+    verify(&verifier, &proof.to_bytes().unwrap()).unwrap();
+    // The proof only holds for the public values that it was made for
+    let mut forged = proof.clone();
+    forged.public_values[0] = Fp::from(13);
+    assert!(matches!(verifier.verify(&forged), Err(Error::InvalidProof(_))));
+    forged.public_values.push(Fp::from(12));
+    assert!(matches!(verifier.verify(&forged), Err(Error::InvalidProof(_))));
+}
+
+#[test]
+fn standalone_verifiers_check_proofs() {
+    let dir = scratch_dir("standalone");
+    let (circuit, verifier, proof) = (dir.join("smoke.halo2"), dir.join("smoke.verifier"), dir.join("smoke.proof"));
+    assert_eq!(vamp_ir(&[
+        "halo2", "compile", "--no-cache", "-s", SMOKE, "-o", circuit.to_str().unwrap(),
+        "--emit-verifier", verifier.to_str().unwrap(),
+    ]), 0);
+    assert_eq!(vamp_ir(&[
+        "halo2", "prove", "-c", circuit.to_str().unwrap(), "-o", proof.to_str().unwrap(),
+        "-D", "a=3", "-D", "b=4", "-D", "c=12",
+    ]), 0);
+    let verify = |circuit: &PathBuf, public: Option<&str>| {
+        let public_path = dir.join("public.json");
+        let mut args = vec!["-c", circuit.to_str().unwrap(), "-p", proof.to_str().unwrap()];
+        if let Some(public) = public {
+            fs::write(&public_path, public).unwrap();
+            args.extend(["--public-inputs", public_path.to_str().unwrap()]);
+        }
+        run(env!("CARGO_BIN_EXE_vamp-ir-verify"), &args)
+    };
+    assert_eq!(verify(&verifier, None), 0);
+    // Circuits hold their verifiers, and full builds can read them
+    assert_eq!(verify(&circuit, None), 0);
+    assert_eq!(verify(&verifier, Some("[\"12\"]")), 0);
+    // The proof is of c = 12, and of only one public variable
+    assert_eq!(verify(&verifier, Some("[13]")), 1);
+    assert_eq!(verify(&verifier, Some("[12, 12]")), 2);
+    assert_eq!(verify(&proof, None), 2);
+}
@@ -0,0 +1,39 @@
+/* Round trips through the WebAssembly bindings. Run with
+ *   wasm-pack test --node -- --no-default-features --features wasm
+ * as .github/workflows/wasm.yml does, since cargo test skips them. */
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use vamp_ir::wasm::{compile, prove, verify};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const SOURCE: &str = "pub z; x * y = z;";
+
+#[wasm_bindgen_test]
+fn proofs_round_trip() {
+    let circuit = compile(SOURCE, "halo2").unwrap_or_else(|_| panic!("cannot compile"));
+    let entropy = [7u8; 32];
+    let proof = prove(&circuit, r#"{"x": 3, "y": "4", "z": "0xc"}"#, &entropy)
+        .unwrap_or_else(|_| panic!("cannot prove"));
+    assert!(verify(&circuit, &proof, r#"{"z": 12}"#).unwrap_or_else(|_| panic!("cannot verify")));
+    // A proof does not vouch for other public values
+    assert!(!verify(&circuit, &proof, r#"{"z": 13}"#).unwrap_or_else(|_| panic!("cannot verify")));
+}
+
+#[wasm_bindgen_test]
+fn proving_refuses_bad_inputs_and_little_entropy() {
+    let circuit = compile(SOURCE, "halo2").unwrap_or_else(|_| panic!("cannot compile"));
+    assert!(prove(&circuit, r#"{"x": 3, "y": 4, "z": 13}"#, &[7u8; 32]).is_err());
+    assert!(prove(&circuit, r#"{"x": 3, "y": 4, "z": 12}"#, &[7u8; 16]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn malformed_programs_are_errors() {
+    // Builds for the web abort on panics, so these must come back as errors
+    for source in ["def id x = x;\n\nid = 1;\n", "pub x;\n\nx ^ x = 4;\n"] {
+        match compile(source, "halo2") {
+            Err(vamp_ir::Error::Compile(msg)) => assert!(msg.starts_with("3:"), "{}", msg),
+            Err(err) => panic!("{} gave {}", source, err),
+            Ok(_) => panic!("{} compiled", source),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+/* An example of constant exponents wider than 64 bits. Run as follows:
+   vamp-ir setup -o params.pp
+   vamp-ir compile -u params.pp -s tests/wide_exponents.pir -o circuit.plonk
+   vamp-ir prove -u params.pp -c circuit.plonk -o proof.plonk
+   vamp-ir verify -u params.pp -c circuit.plonk -p proof.plonk
+*/
+
+pub x;
+
+// A 65-bit exponent
+
+def a = 3 ^ (2^64 + 1);
+
+// A 200-bit exponent
+
+def b = 3 ^ (2^200);
+
+// A negated exponent whose magnitude is 200 bits wide. Since exponents are
+// field elements, this is 3 ^ (p - 2^200) = 3 ^ (1 - 2^200)
+
+def c = 3 ^ (-(2^200));
+
+b * c = 3;
+
+a = x;
+
+// Zero raised to any positive power is still zero
+
+0 ^ (2^200) = x - a;
@@ -0,0 +1,66 @@
+/* Checks that constant exponents of any width fold in the fields of both the
+ * Halo2 and the arkworks backends, whether they fit in one limb of 64 bits,
+ * fill four, or spill into a fifth, and whatever their signs. */
+#![cfg(feature = "halo2")]
+
+use ark_bls12_381::Fr;
+use halo2_proofs::pasta::Fp;
+use num_bigint::BigInt;
+use std::fs;
+use vamp_ir::ast::InfixOp;
+use vamp_ir::halo2::synth::{Halo2CostModel, PrimeFieldOps as Halo2FieldOps};
+use vamp_ir::plonk::field::{PlonkCostModel, PrimeFieldOps as PlonkFieldOps};
+use vamp_ir::transform::{CompileOptions, FieldOps};
+
+const WIDE_EXPONENTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/wide_exponents.pir");
+
+/* Exponents of one, four, and five limbs, each with both signs. */
+fn exponents() -> Vec<BigInt> {
+    let one = BigInt::from(1);
+    let magnitudes = [(&one << 63) + 5, (&one << 250) + 7, (&one << 300) + 3];
+    magnitudes.iter().flat_map(|e| [e.clone(), -e]).collect()
+}
+
+/* The given power of the given base in the field described by the given
+ * operations, computed without them. */
+fn expected_pow(base: &BigInt, exp: &BigInt, field_ops: &dyn FieldOps) -> BigInt {
+    let order = field_ops.canonical(BigInt::from(-1)) + 1;
+    let pow = base.modpow(&BigInt::from(exp.magnitude().clone()), &order);
+    if exp.sign() == num_bigint::Sign::Minus {
+        pow.modpow(&(&order - 2), &order)
+    } else {
+        pow
+    }
+}
+
+/* Check every exponent against the given field's operations. */
+fn check_powers(field_ops: &dyn FieldOps) {
+    let base = BigInt::from(3);
+    for exp in exponents() {
+        let pow = field_ops.infix(InfixOp::Exponentiate, base.clone(), exp.clone()).unwrap();
+        assert_eq!(pow, expected_pow(&base, &exp, field_ops), "3 ^ {}", exp);
+        // Zero stays zero under any positive power
+        if exp.sign() == num_bigint::Sign::Plus {
+            assert_eq!(field_ops.infix(InfixOp::Exponentiate, 0.into(), exp).unwrap(), 0.into());
+        }
+    }
+}
+
+#[test]
+fn halo2_folds_wide_exponents() {
+    check_powers(&Halo2FieldOps::<Fp>::default());
+}
+
+#[test]
+fn arkworks_folds_wide_exponents() {
+    check_powers(&PlonkFieldOps::<Fr>::default());
+}
+
+#[test]
+fn wide_exponents_compile_in_both_fields() {
+    let source = fs::read_to_string(WIDE_EXPONENTS).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(Halo2CostModel));
+    vamp_ir::compile(vamp_ir::parse(&source).unwrap(), &Halo2FieldOps::<Fp>::default(), &options).unwrap();
+    let options = CompileOptions::default().cost_model(Box::new(PlonkCostModel));
+    vamp_ir::compile(vamp_ir::parse(&source).unwrap(), &PlonkFieldOps::<Fr>::default(), &options).unwrap();
+}
@@ -0,0 +1,41 @@
+/* Checks that wide sums are split into balanced trees of three-address codes,
+ * so that the chains of definitions deriving them grow only logarithmically
+ * with the number of terms. */
+#![cfg(feature = "compiler")]
+
+use ark_bls12_381::Fr;
+use num_bigint::BigInt;
+use std::collections::HashMap;
+use std::thread;
+use vamp_ir::plonk::field::PrimeFieldOps;
+use vamp_ir::transform::CompileOptions;
+
+/* Number of terms in the sum. */
+const TERMS: usize = 10000;
+
+/* Extra definitions allowed on top of the depth of a balanced tree. */
+const SLACK: usize = 1;
+
+#[test]
+fn wide_sums_are_balanced() {
+    // Parsing and evaluating the sum recurses along its whole length
+    thread::Builder::new().stack_size(256 << 20).spawn(|| {
+        let source = format!("pub x, y;\n{} = y;\n", vec!["x"; TERMS].join(" + "));
+        let field_ops = PrimeFieldOps::<Fr>::default();
+        let module = vamp_ir::parse(&source).unwrap();
+        let compiled = vamp_ir::compile(module, &field_ops, &CompileOptions::default()).unwrap();
+        assert_eq!(compiled.stats.constraints, TERMS - 1);
+        let balanced = TERMS.next_power_of_two().trailing_zeros() as usize;
+        assert!(
+            compiled.stats.definition_depth <= balanced + SLACK,
+            "definition depth {} exceeds {}", compiled.stats.definition_depth, balanced + SLACK,
+        );
+        // The sum is still computed correctly
+        let inputs = |y: u64| HashMap::from([
+            ("x".to_string(), BigInt::from(1)),
+            ("y".to_string(), BigInt::from(y)),
+        ]);
+        vamp_ir::assign_inputs(&compiled.module, &inputs(TERMS as u64), &field_ops).unwrap();
+        assert!(vamp_ir::assign_inputs(&compiled.module, &inputs(TERMS as u64 - 1), &field_ops).is_err());
+    }).unwrap().join().unwrap();
+}
@@ -0,0 +1,17 @@
+/* An example of a witness exported for external tools. Run as follows:
+   vamp-ir witness -s tests/witness.pir -i tests/witness.json -o witness.json
+   The variables section of witness.json must give x = 4 and r = 16 as
+   decimal strings, alongside the values of the compiler's temporaries,
+   each with its name and numeric id. Since y is supplied neither by the
+   inputs file nor with -D, it and q, which depends on it, must instead be
+   listed in the unknown section. Giving -D y=0 must also leave q unknown,
+   since it divides by y, whereas giving -D y=2 must give q = 8.
+*/
+
+pub r;
+
+def q = x * x / y;
+
+x * x = r;
+
+q * y = r;
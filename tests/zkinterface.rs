@@ -0,0 +1,88 @@
+/* Checks that exported zkinterface messages are read back by the zkinterface
+ * crate itself, and that the witness they carry satisfies the constraints
+ * they carry. Only built with the zkinterface feature, along with the
+ * command line. */
+#![cfg(all(feature = "cli", feature = "zkinterface"))]
+
+mod common;
+
+use common::{scratch_dir, vamp_ir};
+use num_bigint::{BigInt, Sign};
+use std::collections::HashMap;
+use zkinterface::{Messages, Variables, Workspace};
+
+const SOURCE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/r1cs.pir");
+const INPUTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/r1cs.inputs");
+
+/* Export tests/r1cs.pir with the given extra arguments and read the messages
+ * back. */
+fn export(test: &str, args: &[&str]) -> Messages {
+    let path = scratch_dir(test).join("circuit.zkif");
+    let mut command = vec!["export", "--format", "zkinterface", SOURCE, "-o", path.to_str().unwrap()];
+    command.extend(args);
+    assert_eq!(vamp_ir(&command), 0);
+    Workspace::new(vec![path]).read_all_messages()
+}
+
+/* The values of the given variables by their ids. */
+fn values(vars: &Variables) -> HashMap<u64, BigInt> {
+    let bytes = vars.values.as_ref().expect("variables have values");
+    let size = bytes.len() / vars.variable_ids.len();
+    vars.variable_ids.iter().zip(bytes.chunks(size))
+        .map(|(id, value)| (*id, BigInt::from_bytes_le(Sign::Plus, value)))
+        .collect()
+}
+
+/* The value of the given linear combination with the given values of the
+ * variables. */
+fn evaluate(lc: &Variables, assigns: &HashMap<u64, BigInt>) -> BigInt {
+    if lc.variable_ids.is_empty() {
+        return BigInt::from(0);
+    }
+    values(lc).iter().map(|(id, coeff)| coeff * &assigns[id]).sum()
+}
+
+#[test]
+fn constraints_are_exported_alone() {
+    let messages = export("constraints", &[]);
+    assert_eq!(messages.circuit_headers.len(), 1);
+    assert_eq!(messages.constraint_systems.len(), 1);
+    assert!(messages.witnesses.is_empty());
+    let header = &messages.circuit_headers[0];
+    // z and d are public, and are numbered after the variable holding one
+    assert_eq!(header.instance_variables.variable_ids.len(), 2);
+    assert!(header.instance_variables.variable_ids.iter().all(|id| *id != 0 && *id < header.free_variable_id));
+    assert!(header.instance_variables.values.is_none());
+    assert!(!messages.constraint_systems[0].constraints.is_empty());
+}
+
+#[test]
+fn witnesses_satisfy_constraints() {
+    let messages = export("witness", &["-i", INPUTS]);
+    let header = &messages.circuit_headers[0];
+    let maximum = header.field_maximum.as_ref().expect("field is given");
+    let prime = BigInt::from_bytes_le(Sign::Plus, maximum) + 1;
+    let mut assigns = values(&header.instance_variables);
+    assert!(assigns.values().any(|value| *value == BigInt::from(12)));
+    assigns.extend(values(&messages.witnesses[0].assigned_variables));
+    assigns.insert(0, BigInt::from(1));
+    assert!(assigns.keys().all(|id| *id < header.free_variable_id));
+    for constraint in &messages.constraint_systems[0].constraints {
+        let a = evaluate(&constraint.linear_combination_a, &assigns);
+        let b = evaluate(&constraint.linear_combination_b, &assigns);
+        let c = evaluate(&constraint.linear_combination_c, &assigns);
+        assert_eq!((a * b - c) % &prime, BigInt::from(0));
+    }
+    // Numbering does not depend on whether a witness is exported
+    let alone = export("numbering", &[]);
+    assert_eq!(alone.circuit_headers[0].instance_variables.variable_ids, header.instance_variables.variable_ids);
+}
+
+#[test]
+fn violating_inputs_are_not_exported() {
+    let path = scratch_dir("violating").join("circuit.zkif");
+    assert_eq!(vamp_ir(&[
+        "export", "--format", "zkinterface", SOURCE, "-i", INPUTS, "-D", "z=13", "-o", path.to_str().unwrap(),
+    ]), 1);
+    assert!(!path.exists());
+}